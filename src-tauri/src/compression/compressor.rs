@@ -105,11 +105,7 @@ impl MemoryCompressor {
             .unwrap_or(&messages[0].content);
 
         // Limiter à 100 caractères
-        if user_query.len() > 100 {
-            format!("{}...", &user_query[..97])
-        } else {
-            user_query.clone()
-        }
+        truncate_with_ellipsis(user_query, 100)
     }
 
     /// Compresse le texte
@@ -213,13 +209,20 @@ impl MemoryCompressor {
         }
 
         // Limiter à 150 caractères
-        let combined = summaries.join(" | ");
-        if combined.len() > 150 {
-            format!("{}...", &combined[..147])
-        } else {
-            combined
-        }
+        truncate_with_ellipsis(&summaries.join(" | "), 150)
+    }
+}
+
+/// Tronque sur une frontière de caractère (les accents sont multi-octets)
+fn truncate_with_ellipsis(text: &str, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text.to_string();
+    }
+    let mut end = max_len - 3;
+    while !text.is_char_boundary(end) {
+        end -= 1;
     }
+    format!("{}...", &text[..end])
 }
 
 /// Message simple
@@ -0,0 +1,523 @@
+#![allow(dead_code)]
+// ╔══════════════════════════════════════════════════════════════════════════════╗
+// ║                    MEMORY CONSOLIDATOR v13                                   ║
+// ║     Consolidation programmée : promotion, fusion, méta-résumés, oubli        ║
+// ╚══════════════════════════════════════════════════════════════════════════════╝
+
+use super::compressor::MemoryCompressor;
+use super::forgetfulness::{ForgetReason, ForgetRecord, SelectiveForgetfulness, DEFAULT_AUDIT_CAPACITY};
+use super::hierarchy::{MemoryHierarchy, Promotion};
+use super::{MemoryEntry, MemoryLevel};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Préfixe des indices de rappel identifiant un résumé quotidien (`daily:2024-05-12`)
+pub const DAILY_INDEX_PREFIX: &str = "daily:";
+/// Préfixe des indices de rappel identifiant un résumé hebdomadaire (`weekly:2024-W19`)
+pub const WEEKLY_INDEX_PREFIX: &str = "weekly:";
+
+/// Configuration de la consolidation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsolidationConfig {
+    /// Intervalle entre deux passes du job programmé
+    pub interval_secs: u64,
+    /// Âge minimal avant qu'une entrée puisse être fusionnée
+    pub merge_min_age_secs: u64,
+    /// Similarité de Jaccard minimale des indices de rappel pour fusionner
+    pub merge_similarity: f32,
+    pub max_group_size: usize,
+    /// Capacité de la mémoire (hors épinglées et méta-résumés)
+    pub max_entries: usize,
+    pub compression_ratio: f32,
+    /// Journal JSONL des oublis (voir `forgetfulness::default_audit_log_path`) ;
+    /// `None` : journal en mémoire seulement
+    #[serde(default)]
+    pub audit_log_path: Option<PathBuf>,
+    /// Traces d'oubli conservées avant rotation du journal
+    #[serde(default = "default_audit_capacity")]
+    pub audit_capacity: usize,
+}
+
+fn default_audit_capacity() -> usize {
+    DEFAULT_AUDIT_CAPACITY
+}
+
+impl Default for ConsolidationConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 3600,
+            merge_min_age_secs: 3600,
+            merge_similarity: 0.5,
+            max_group_size: 8,
+            max_entries: 5000,
+            compression_ratio: 0.5,
+            audit_log_path: None,
+            audit_capacity: DEFAULT_AUDIT_CAPACITY,
+        }
+    }
+}
+
+/// Fusion d'entrées liées
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeRecord {
+    pub into: String,
+    pub sources: Vec<String>,
+    pub level: MemoryLevel,
+}
+
+/// Bilan d'une passe de consolidation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsolidationReport {
+    pub promoted: Vec<Promotion>,
+    pub merged: Vec<MergeRecord>,
+    pub daily_summaries: Vec<String>,
+    pub weekly_summaries: Vec<String>,
+    pub forgotten: Vec<ForgetRecord>,
+    pub ran_at: SystemTime,
+}
+
+/// Consolidateur de mémoire
+pub struct MemoryConsolidator {
+    config: ConsolidationConfig,
+    compressor: MemoryCompressor,
+    forgetfulness: SelectiveForgetfulness,
+}
+
+impl MemoryConsolidator {
+    /// Crée un consolidateur
+    pub fn new(config: ConsolidationConfig) -> Self {
+        let forgetfulness = match &config.audit_log_path {
+            Some(path) => SelectiveForgetfulness::with_audit_log(config.max_entries, path.clone(), config.audit_capacity)
+                .unwrap_or_else(|e| {
+                    println!("[COMPRESSION] ⚠️ Journal d'audit indisponible ({}), tenu en mémoire: {}", path.display(), e);
+                    SelectiveForgetfulness::new(config.max_entries)
+                }),
+            None => SelectiveForgetfulness::new(config.max_entries),
+        };
+
+        Self {
+            compressor: MemoryCompressor::new(config.compression_ratio),
+            forgetfulness,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &ConsolidationConfig {
+        &self.config
+    }
+
+    /// Journal des entrées oubliées ou fusionnées
+    pub fn forgetfulness(&self) -> &SelectiveForgetfulness {
+        &self.forgetfulness
+    }
+
+    /// Exécute une passe complète
+    pub fn run(&mut self, hierarchy: &mut MemoryHierarchy) -> ConsolidationReport {
+        self.run_at(hierarchy, SystemTime::now())
+    }
+
+    /// Exécute une passe complète avec `now` comme date de référence
+    pub fn run_at(&mut self, hierarchy: &mut MemoryHierarchy, now: SystemTime) -> ConsolidationReport {
+        // 1. Promotion selon l'âge et les accès
+        let promoted = hierarchy.promote_eligible();
+
+        // 2. Fusion des entrées liées d'un même niveau
+        let merged = self.merge_related(hierarchy, now);
+
+        // 3. Méta-résumés des journées puis des semaines révolues
+        let daily_summaries = self.summarize_days(hierarchy, now);
+        let weekly_summaries = self.summarize_weeks(hierarchy, now);
+
+        // 4. Oubli sélectif (les méta-résumés conservent la trace des journées)
+        let forgotten = self.forgetfulness.sweep(hierarchy);
+
+        ConsolidationReport {
+            promoted,
+            merged,
+            daily_summaries,
+            weekly_summaries,
+            forgotten,
+            ran_at: now,
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Fusion
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Fusionne via `consolidate_entries` les groupes d'entrées partageant
+    /// leurs indices de rappel ou liées explicitement
+    pub fn merge_related(&mut self, hierarchy: &mut MemoryHierarchy, now: SystemTime) -> Vec<MergeRecord> {
+        let min_age = Duration::from_secs(self.config.merge_min_age_secs);
+        let mut records = Vec::new();
+
+        for level in [MemoryLevel::ShortTerm, MemoryLevel::MediumTerm] {
+            let candidates: Vec<MemoryEntry> = hierarchy
+                .at_level(level)
+                .into_iter()
+                .filter(|e| !e.pinned && age_at(e, now) >= min_age)
+                .cloned()
+                .collect();
+
+            for group in self.related_groups(&candidates) {
+                for batch in group.chunks(self.config.max_group_size.max(2)) {
+                    if batch.len() < 2 {
+                        continue;
+                    }
+                    let sources: Vec<MemoryEntry> = batch.iter().map(|&i| candidates[i].clone()).collect();
+                    records.push(self.merge(hierarchy, &sources));
+                }
+            }
+        }
+
+        records
+    }
+
+    fn merge(&mut self, hierarchy: &mut MemoryHierarchy, sources: &[MemoryEntry]) -> MergeRecord {
+        let mut merged = self.compressor.consolidate_entries(sources);
+        merged.created_at = sources.iter().map(|e| e.created_at).min().unwrap_or(merged.created_at);
+        merged.access_count = sources.iter().map(|e| e.access_count).sum();
+        merged.links = sources.iter().map(|e| e.id.clone()).collect();
+
+        let into = hierarchy.insert(merged.clone());
+        for source in sources {
+            if let Some(entry) = hierarchy.remove(&source.id) {
+                self.forgetfulness
+                    .record(&entry, ForgetReason::Consolidated { into: into.clone() });
+            }
+        }
+
+        MergeRecord {
+            into,
+            sources: sources.iter().map(|e| e.id.clone()).collect(),
+            level: merged.level,
+        }
+    }
+
+    /// Composantes connexes de la relation « liée », triées par ancienneté
+    fn related_groups(&self, entries: &[MemoryEntry]) -> Vec<Vec<usize>> {
+        let mut parent: Vec<usize> = (0..entries.len()).collect();
+        fn find(parent: &mut [usize], i: usize) -> usize {
+            let mut root = i;
+            while parent[root] != root {
+                root = parent[root];
+            }
+            parent[i] = root;
+            root
+        }
+
+        for i in 0..entries.len() {
+            for j in (i + 1)..entries.len() {
+                if self.are_related(&entries[i], &entries[j]) {
+                    let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                    parent[a.max(b)] = a.min(b);
+                }
+            }
+        }
+
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for i in 0..entries.len() {
+            let root = find(&mut parent, i);
+            groups.entry(root).or_default().push(i);
+        }
+        groups.into_values().filter(|g| g.len() > 1).collect()
+    }
+
+    fn are_related(&self, a: &MemoryEntry, b: &MemoryEntry) -> bool {
+        if a.links.contains(&b.id) || b.links.contains(&a.id) {
+            return true;
+        }
+        let left: HashSet<&String> = a.recall_indices.iter().collect();
+        let right: HashSet<&String> = b.recall_indices.iter().collect();
+        let union = left.union(&right).count();
+        union > 0 && left.intersection(&right).count() as f32 / union as f32 >= self.config.merge_similarity
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Méta-résumés
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Résume chaque journée révolue (UTC) qui n'a pas encore de méta-résumé
+    pub fn summarize_days(&mut self, hierarchy: &mut MemoryHierarchy, now: SystemTime) -> Vec<String> {
+        let today = day_of(now);
+        let done = period_keys(hierarchy, DAILY_INDEX_PREFIX);
+
+        let mut days: BTreeMap<NaiveDate, Vec<MemoryEntry>> = BTreeMap::new();
+        for entry in hierarchy.entries().filter(|e| e.level != MemoryLevel::MetaSummary) {
+            let day = day_of(entry.created_at);
+            if day < today && !done.contains(&day.to_string()) {
+                days.entry(day).or_default().push(entry.clone());
+            }
+        }
+
+        let mut created = Vec::new();
+        for (day, mut entries) in days {
+            entries.sort_by_key(|e| e.created_at);
+            let key = day.to_string();
+            let summary = self.meta_summary(&entries, format!("Journée du {}", key), DAILY_INDEX_PREFIX, &key, day);
+            created.push(hierarchy.insert(summary));
+        }
+        created
+    }
+
+    /// Résume chaque semaine ISO révolue à partir de ses résumés quotidiens
+    pub fn summarize_weeks(&mut self, hierarchy: &mut MemoryHierarchy, now: SystemTime) -> Vec<String> {
+        let current_week = week_key(day_of(now));
+        let done = period_keys(hierarchy, WEEKLY_INDEX_PREFIX);
+
+        let mut weeks: BTreeMap<String, (NaiveDate, Vec<MemoryEntry>)> = BTreeMap::new();
+        for entry in hierarchy.entries().filter(|e| e.level == MemoryLevel::MetaSummary) {
+            let Some(day) = entry
+                .recall_indices
+                .iter()
+                .find_map(|i| i.strip_prefix(DAILY_INDEX_PREFIX))
+                .and_then(|d| d.parse::<NaiveDate>().ok())
+            else {
+                continue;
+            };
+            let week = week_key(day);
+            if week < current_week && !done.contains(&week) {
+                let slot = weeks.entry(week).or_insert_with(|| (day, Vec::new()));
+                slot.0 = slot.0.min(day);
+                slot.1.push(entry.clone());
+            }
+        }
+
+        let mut created = Vec::new();
+        for (week, (first_day, mut entries)) in weeks {
+            entries.sort_by_key(|e| e.created_at);
+            let summary = self.meta_summary(&entries, format!("Semaine {}", week), WEEKLY_INDEX_PREFIX, &week, first_day);
+            created.push(hierarchy.insert(summary));
+        }
+        created
+    }
+
+    fn meta_summary(
+        &self,
+        entries: &[MemoryEntry],
+        label: String,
+        prefix: &str,
+        key: &str,
+        day: NaiveDate,
+    ) -> MemoryEntry {
+        let mut summary = self.compressor.consolidate_entries(entries);
+        summary.level = MemoryLevel::MetaSummary;
+        summary.summary = format!("{} — {}", label, summary.summary);
+        summary.recall_indices.retain(|i| !i.starts_with(DAILY_INDEX_PREFIX) && !i.starts_with(WEEKLY_INDEX_PREFIX));
+        summary.recall_indices.push(format!("{}{}", prefix, key));
+        summary.links = entries.iter().map(|e| e.id.clone()).collect();
+        summary.importance = entries.iter().map(|e| e.importance).fold(0.0, f32::max);
+        summary.created_at = start_of_day(day);
+        summary.original_content = None;
+        summary
+    }
+}
+
+fn age_at(entry: &MemoryEntry, now: SystemTime) -> Duration {
+    now.duration_since(entry.created_at).unwrap_or(Duration::from_secs(0))
+}
+
+fn day_of(time: SystemTime) -> NaiveDate {
+    DateTime::<Utc>::from(time).date_naive()
+}
+
+fn start_of_day(day: NaiveDate) -> SystemTime {
+    let midnight = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+    SystemTime::from(DateTime::<Utc>::from_naive_utc_and_offset(midnight, Utc))
+}
+
+fn week_key(day: NaiveDate) -> String {
+    let week = day.iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+/// Périodes déjà résumées (valeurs des indices portant le préfixe)
+fn period_keys(hierarchy: &MemoryHierarchy, prefix: &str) -> HashSet<String> {
+    hierarchy
+        .entries()
+        .filter(|e| e.level == MemoryLevel::MetaSummary)
+        .flat_map(|e| e.recall_indices.iter())
+        .filter_map(|i| i.strip_prefix(prefix))
+        .map(str::to_string)
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Job programmé
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Exécute la consolidation en tâche de fond à intervalle régulier
+pub struct ConsolidationJob {
+    consolidator: Arc<Mutex<MemoryConsolidator>>,
+    last_report: Arc<Mutex<Option<ConsolidationReport>>>,
+    running: Arc<AtomicBool>,
+    trigger: Arc<AtomicBool>,
+}
+
+impl ConsolidationJob {
+    pub fn spawn(hierarchy: Arc<Mutex<MemoryHierarchy>>, consolidator: MemoryConsolidator) -> Self {
+        let interval = Duration::from_secs(consolidator.config().interval_secs.max(1));
+        let job = Self {
+            consolidator: Arc::new(Mutex::new(consolidator)),
+            last_report: Arc::new(Mutex::new(None)),
+            running: Arc::new(AtomicBool::new(true)),
+            trigger: Arc::new(AtomicBool::new(false)),
+        };
+
+        let consolidator = job.consolidator.clone();
+        let last_report = job.last_report.clone();
+        let running = job.running.clone();
+        let trigger = job.trigger.clone();
+
+        std::thread::spawn(move || {
+            let mut last_run = Instant::now();
+
+            while running.load(Ordering::Relaxed) {
+                if last_run.elapsed() >= interval || trigger.swap(false, Ordering::Relaxed) {
+                    let report = {
+                        let mut hierarchy = hierarchy.lock().unwrap();
+                        consolidator.lock().unwrap().run(&mut hierarchy)
+                    };
+                    println!(
+                        "[COMPRESSION] Consolidation: {} promues, {} fusions, {} résumés, {} oubliées",
+                        report.promoted.len(),
+                        report.merged.len(),
+                        report.daily_summaries.len() + report.weekly_summaries.len(),
+                        report.forgotten.len()
+                    );
+                    *last_report.lock().unwrap() = Some(report);
+                    last_run = Instant::now();
+                }
+                std::thread::sleep(Duration::from_millis(250));
+            }
+        });
+
+        job
+    }
+
+    /// Demande une passe immédiate
+    pub fn run_now(&self) {
+        self.trigger.store(true, Ordering::Relaxed);
+    }
+
+    pub fn last_report(&self) -> Option<ConsolidationReport> {
+        self.last_report.lock().unwrap().clone()
+    }
+
+    pub fn audit_trail(&self) -> Vec<ForgetRecord> {
+        self.consolidator.lock().unwrap().forgetfulness().audit_trail().cloned().collect()
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl Drop for ConsolidationJob {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86400;
+
+    fn entry_at(content: &str, indices: &[&str], created_at: SystemTime) -> MemoryEntry {
+        let mut entry = MemoryEntry::new(content.to_string(), 0.4);
+        entry.summary = content.to_string();
+        entry.recall_indices = indices.iter().map(|i| i.to_string()).collect();
+        entry.created_at = created_at;
+        entry
+    }
+
+    #[test]
+    fn test_merges_related_entries() {
+        let now = SystemTime::now();
+        let two_hours_ago = now - Duration::from_secs(7200);
+        let mut hierarchy = MemoryHierarchy::new();
+        let a = hierarchy.insert(entry_at("Migration serveur prévue.", &["migration", "serveur"], two_hours_ago));
+        let b = hierarchy.insert(entry_at("Serveur migré vendredi.", &["migration", "serveur"], two_hours_ago));
+        let other = hierarchy.insert(entry_at("Recette de crêpes.", &["crêpes"], two_hours_ago));
+        let pinned = hierarchy.insert(entry_at("Mot de passe wifi.", &["migration", "serveur"], two_hours_ago));
+        hierarchy.pin(&pinned);
+
+        let mut consolidator = MemoryConsolidator::new(ConsolidationConfig::default());
+        let merged = consolidator.merge_related(&mut hierarchy, now);
+
+        assert_eq!(merged.len(), 1);
+        let record = &merged[0];
+        assert_eq!(record.sources.len(), 2);
+        assert!(record.sources.contains(&a) && record.sources.contains(&b));
+        assert!(hierarchy.get(&a).is_none());
+        assert!(hierarchy.get(&other).is_some());
+        assert!(hierarchy.get(&pinned).is_some());
+
+        let consolidated = hierarchy.get(&record.into).unwrap();
+        assert_eq!(consolidated.level, MemoryLevel::MediumTerm);
+        assert_eq!(consolidated.created_at, two_hours_ago);
+        assert_eq!(
+            consolidator.forgetfulness().why_forgotten(&a).unwrap().reason,
+            ForgetReason::Consolidated { into: record.into.clone() }
+        );
+    }
+
+    #[test]
+    fn test_daily_and_weekly_meta_summaries() {
+        // Mercredi 15 mai 2024, 12:00 UTC
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_715_774_400);
+        let mut hierarchy = MemoryHierarchy::new();
+        for days_ago in [1, 8, 9] {
+            let at = now - Duration::from_secs(days_ago * DAY);
+            hierarchy.insert(entry_at(&format!("Point d'équipe J-{}.", days_ago), &["équipe"], at));
+        }
+        hierarchy.insert(entry_at("Aujourd'hui.", &["équipe"], now));
+
+        let mut consolidator = MemoryConsolidator::new(ConsolidationConfig::default());
+        let daily = consolidator.summarize_days(&mut hierarchy, now);
+        assert_eq!(daily.len(), 3);
+        let weekly = consolidator.summarize_weeks(&mut hierarchy, now);
+        assert_eq!(weekly.len(), 1);
+
+        let week = hierarchy.get(&weekly[0]).unwrap();
+        assert_eq!(week.level, MemoryLevel::MetaSummary);
+        assert!(week.recall_indices.contains(&"weekly:2024-W19".to_string()));
+        assert_eq!(week.links.len(), 2);
+        assert!(week.summary.starts_with("Semaine 2024-W19"));
+
+        // Passe suivante : rien à refaire
+        assert!(consolidator.summarize_days(&mut hierarchy, now).is_empty());
+        assert!(consolidator.summarize_weeks(&mut hierarchy, now).is_empty());
+    }
+
+    #[test]
+    fn test_run_keeps_pinned_and_summaries() {
+        let now = SystemTime::now();
+        let mut hierarchy = MemoryHierarchy::new();
+        let old = now - Duration::from_secs(3 * DAY);
+        let mut pinned = entry_at("Anniversaire de Marie le 3 mars.", &["anniversaire"], old);
+        pinned.importance = 0.1;
+        let pinned = hierarchy.insert(pinned);
+        hierarchy.pin(&pinned);
+        let mut stale = entry_at("Bavardage sans suite.", &["météo"], old);
+        stale.importance = 0.1;
+        let stale = hierarchy.insert(stale);
+
+        let mut consolidator = MemoryConsolidator::new(ConsolidationConfig::default());
+        let report = consolidator.run_at(&mut hierarchy, now);
+
+        assert!(hierarchy.get(&pinned).is_some());
+        assert!(hierarchy.get(&stale).is_none());
+        assert!(report.forgotten.iter().any(|r| r.entry_id == stale && r.reason == ForgetReason::Expired));
+        // La journée reste résumée même si l'entrée a été oubliée
+        let day = hierarchy.get(&report.daily_summaries[0]).unwrap();
+        assert!(day.links.contains(&stale));
+    }
+}
@@ -0,0 +1,346 @@
+#![allow(dead_code)]
+// ╔══════════════════════════════════════════════════════════════════════════════╗
+// ║                   SELECTIVE FORGETFULNESS v13                                ║
+// ║         Élagage sélectif avec journal d'audit des entrées oubliées           ║
+// ╚══════════════════════════════════════════════════════════════════════════════╝
+//
+// Le journal d'audit est borné à `audit_capacity` entrées en mémoire. S'il est
+// persisté, chaque trace est ajoutée en JSONL ; quand le fichier atteint la
+// capacité, il devient `<fichier>.1` (l'archive précédente est écrasée).
+
+use super::hierarchy::MemoryHierarchy;
+use super::{MemoryEntry, MemoryLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Traces d'oubli conservées par défaut (mémoire et fichier courant)
+pub const DEFAULT_AUDIT_CAPACITY: usize = 10_000;
+const AUDIT_LOG_FILE: &str = "forget_audit.jsonl";
+const ROTATED_SUFFIX: &str = ".1";
+
+/// Journal d'audit par défaut, dans le répertoire de données de la mémoire compressée
+pub fn default_audit_log_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("titane")
+        .join("compression")
+        .join(AUDIT_LOG_FILE)
+}
+
+/// Raison de l'oubli d'une entrée
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ForgetReason {
+    /// Critères d'âge, d'importance et d'accès du niveau atteints (`should_forget`)
+    Expired,
+    /// Capacité maximale dépassée : entrées les moins utiles retirées
+    CapacityExceeded { limit: usize },
+    /// Fusionnée dans une entrée consolidée
+    Consolidated { into: String },
+}
+
+/// Trace d'une entrée retirée de la hiérarchie
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgetRecord {
+    pub entry_id: String,
+    pub summary: String,
+    pub level: MemoryLevel,
+    pub importance: f32,
+    pub access_count: u32,
+    pub age_secs: u64,
+    pub reason: ForgetReason,
+    pub forgotten_at: SystemTime,
+}
+
+impl ForgetRecord {
+    pub fn new(entry: &MemoryEntry, reason: ForgetReason) -> Self {
+        let summary = if entry.summary.is_empty() {
+            entry.compressed_content.chars().take(100).collect()
+        } else {
+            entry.summary.clone()
+        };
+
+        Self {
+            entry_id: entry.id.clone(),
+            summary,
+            level: entry.level,
+            importance: entry.importance,
+            access_count: entry.access_count,
+            age_secs: entry.age().as_secs(),
+            reason,
+            forgotten_at: SystemTime::now(),
+        }
+    }
+}
+
+/// Fichier JSONL du journal d'audit
+struct AuditLog {
+    path: PathBuf,
+    /// Lignes du fichier courant, pour décider de la rotation sans le relire
+    lines: usize,
+}
+
+/// Élagueur sélectif : n'oublie jamais les entrées épinglées ni les méta-résumés
+pub struct SelectiveForgetfulness {
+    /// Nombre maximal d'entrées oubliables conservées
+    max_entries: usize,
+    audit: VecDeque<ForgetRecord>,
+    audit_capacity: usize,
+    audit_log: Option<AuditLog>,
+}
+
+impl SelectiveForgetfulness {
+    /// Crée un élagueur avec une capacité maximale (journal en mémoire seulement)
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            audit: VecDeque::new(),
+            audit_capacity: DEFAULT_AUDIT_CAPACITY,
+            audit_log: None,
+        }
+    }
+
+    /// Crée un élagueur dont le journal d'audit est persisté dans `path` ;
+    /// les traces existantes (archive comprise) sont rechargées
+    pub fn with_audit_log(max_entries: usize, path: PathBuf, audit_capacity: usize) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        let audit_capacity = audit_capacity.max(1);
+        let mut audit = VecDeque::new();
+        for record in read_records(&rotated_path(&path))? {
+            push_bounded(&mut audit, record, audit_capacity);
+        }
+        let current = read_records(&path)?;
+        let lines = current.len();
+        for record in current {
+            push_bounded(&mut audit, record, audit_capacity);
+        }
+
+        Ok(Self {
+            max_entries,
+            audit,
+            audit_capacity,
+            audit_log: Some(AuditLog { path, lines }),
+        })
+    }
+
+    /// Élague la hiérarchie et retourne les entrées oubliées lors de cette passe
+    pub fn sweep(&mut self, hierarchy: &mut MemoryHierarchy) -> Vec<ForgetRecord> {
+        let mut records = Vec::new();
+
+        // 1. Entrées expirées selon les critères de leur niveau
+        let expired: Vec<String> = hierarchy
+            .entries()
+            .filter(|e| e.should_forget())
+            .map(|e| e.id.clone())
+            .collect();
+        for id in expired {
+            if let Some(entry) = hierarchy.remove(&id) {
+                records.push(ForgetRecord::new(&entry, ForgetReason::Expired));
+            }
+        }
+
+        // 2. Dépassement de capacité : les moins utiles d'abord
+        let mut forgettable: Vec<&MemoryEntry> = hierarchy.entries().filter(|e| is_forgettable(e)).collect();
+        if forgettable.len() > self.max_entries {
+            forgettable.sort_by(|a, b| {
+                retention_score(a)
+                    .partial_cmp(&retention_score(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then_with(|| a.created_at.cmp(&b.created_at))
+            });
+            let excess: Vec<String> = forgettable
+                .iter()
+                .take(forgettable.len() - self.max_entries)
+                .map(|e| e.id.clone())
+                .collect();
+            for id in excess {
+                if let Some(entry) = hierarchy.remove(&id) {
+                    records.push(ForgetRecord::new(
+                        &entry,
+                        ForgetReason::CapacityExceeded { limit: self.max_entries },
+                    ));
+                }
+            }
+        }
+
+        for record in &records {
+            self.push_audit(record.clone());
+        }
+        records
+    }
+
+    /// Consigne une entrée retirée hors du balayage (fusion par exemple)
+    pub fn record(&mut self, entry: &MemoryEntry, reason: ForgetReason) {
+        self.push_audit(ForgetRecord::new(entry, reason));
+    }
+
+    /// Traces d'oubli conservées, de la plus ancienne à la plus récente
+    pub fn audit_trail(&self) -> impl DoubleEndedIterator<Item = &ForgetRecord> {
+        self.audit.iter()
+    }
+
+    /// Retrouve la trace d'une entrée oubliée
+    pub fn why_forgotten(&self, entry_id: &str) -> Option<&ForgetRecord> {
+        self.audit.iter().rev().find(|r| r.entry_id == entry_id)
+    }
+
+    /// Exporte le journal d'audit en JSON
+    pub fn export_audit(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(&self.audit).map_err(|e| e.to_string())
+    }
+
+    /// Ajoute une trace au journal ; un échec d'écriture n'interrompt pas l'élagage
+    fn push_audit(&mut self, record: ForgetRecord) {
+        if let Some(log) = &mut self.audit_log {
+            if let Err(e) = append_record(log, &record, self.audit_capacity) {
+                println!("[COMPRESSION] ⚠️ Journal d'audit non écrit ({}): {}", log.path.display(), e);
+            }
+        }
+        push_bounded(&mut self.audit, record, self.audit_capacity);
+    }
+}
+
+fn push_bounded(audit: &mut VecDeque<ForgetRecord>, record: ForgetRecord, capacity: usize) {
+    if audit.len() >= capacity {
+        audit.pop_front();
+    }
+    audit.push_back(record);
+}
+
+/// Ajoute une ligne au fichier courant, archivé au préalable s'il est plein
+fn append_record(log: &mut AuditLog, record: &ForgetRecord, capacity: usize) -> Result<(), String> {
+    if log.lines >= capacity {
+        fs::rename(&log.path, rotated_path(&log.path)).map_err(|e| e.to_string())?;
+        log.lines = 0;
+    }
+
+    let line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log.path)
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+    log.lines += 1;
+    Ok(())
+}
+
+/// Traces d'un fichier JSONL (absent = vide) ; une ligne illisible est ignorée
+fn read_records(path: &Path) -> Result<Vec<ForgetRecord>, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    Ok(content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                println!("[COMPRESSION] ⚠️ Trace d'audit illisible ignorée: {}", e);
+                None
+            }
+        })
+        .collect())
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(ROTATED_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn is_forgettable(entry: &MemoryEntry) -> bool {
+    !entry.pinned && entry.level != MemoryLevel::MetaSummary
+}
+
+/// Score de rétention : importance, usage et niveau atteint
+fn retention_score(entry: &MemoryEntry) -> f32 {
+    let usage = (entry.access_count as f32 / 10.0).min(1.0);
+    let level_bonus = match entry.level {
+        MemoryLevel::ShortTerm => 0.0,
+        MemoryLevel::MediumTerm => 0.1,
+        MemoryLevel::LongTerm => 0.2,
+        MemoryLevel::MetaSummary => 1.0,
+    };
+    entry.importance * 0.6 + usage * 0.3 + level_bonus
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn stale_entry(content: &str) -> MemoryEntry {
+        let mut entry = MemoryEntry::new(content.to_string(), 0.1);
+        entry.created_at = SystemTime::now() - Duration::from_secs(3 * 3600);
+        entry
+    }
+
+    #[test]
+    fn test_pinned_entries_never_forgotten() {
+        let mut hierarchy = MemoryHierarchy::new();
+        let pinned = hierarchy.insert(stale_entry("code wifi invités"));
+        hierarchy.pin(&pinned);
+        let stale = hierarchy.insert(stale_entry("bavardage"));
+
+        let mut forgetfulness = SelectiveForgetfulness::new(0);
+        let records = forgetfulness.sweep(&mut hierarchy);
+
+        assert!(hierarchy.get(&pinned).is_some());
+        assert!(hierarchy.get(&stale).is_none());
+        assert_eq!(records.len(), 1);
+        assert_eq!(forgetfulness.why_forgotten(&stale).unwrap().reason, ForgetReason::Expired);
+        assert!(forgetfulness.why_forgotten(&pinned).is_none());
+    }
+
+    #[test]
+    fn test_audit_log_survives_restart_and_rotates() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("audit").join(AUDIT_LOG_FILE);
+
+        let mut forgetfulness = SelectiveForgetfulness::with_audit_log(0, path.clone(), 2).unwrap();
+        let mut forgotten = Vec::new();
+        for content in ["premier", "deuxième", "troisième"] {
+            let entry = stale_entry(content);
+            forgotten.push(entry.id.clone());
+            forgetfulness.record(&entry, ForgetReason::Expired);
+        }
+        assert_eq!(forgetfulness.audit_trail().count(), 2);
+        assert!(forgetfulness.why_forgotten(&forgotten[0]).is_none());
+        drop(forgetfulness);
+
+        // Fichier plein archivé : deux traces dans l'archive, une dans le courant
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+        assert_eq!(fs::read_to_string(rotated_path(&path)).unwrap().lines().count(), 2);
+
+        let reopened = SelectiveForgetfulness::with_audit_log(0, path, 2).unwrap();
+        let ids: Vec<&str> = reopened.audit_trail().map(|r| r.entry_id.as_str()).collect();
+        assert_eq!(ids, vec![forgotten[1].as_str(), forgotten[2].as_str()]);
+        assert_eq!(reopened.why_forgotten(&forgotten[2]).unwrap().summary, "troisième");
+    }
+
+    #[test]
+    fn test_capacity_drops_least_useful() {
+        let mut hierarchy = MemoryHierarchy::new();
+        let keep = hierarchy.insert(MemoryEntry::new("décision importante".to_string(), 0.9));
+        let drop = hierarchy.insert(MemoryEntry::new("détail".to_string(), 0.2));
+
+        let mut forgetfulness = SelectiveForgetfulness::new(1);
+        forgetfulness.sweep(&mut hierarchy);
+
+        assert!(hierarchy.get(&keep).is_some());
+        assert_eq!(
+            forgetfulness.why_forgotten(&drop).unwrap().reason,
+            ForgetReason::CapacityExceeded { limit: 1 }
+        );
+    }
+}
@@ -0,0 +1,173 @@
+#![allow(dead_code)]
+// ╔══════════════════════════════════════════════════════════════════════════════╗
+// ║                      MEMORY HIERARCHY v13                                    ║
+// ║          Stockage par niveaux, rappel et promotion des entrées               ║
+// ╚══════════════════════════════════════════════════════════════════════════════╝
+
+use super::{MemoryEntry, MemoryLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Promotion effectuée lors d'une consolidation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Promotion {
+    pub entry_id: String,
+    pub from: MemoryLevel,
+    pub to: MemoryLevel,
+}
+
+/// Hiérarchie mémoire à quatre niveaux
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryHierarchy {
+    entries: HashMap<String, MemoryEntry>,
+}
+
+impl MemoryHierarchy {
+    /// Crée une hiérarchie vide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ajoute une entrée et retourne son identifiant
+    pub fn insert(&mut self, entry: MemoryEntry) -> String {
+        let id = entry.id.clone();
+        self.entries.insert(id.clone(), entry);
+        id
+    }
+
+    /// Consulte une entrée sans la marquer comme accédée
+    pub fn get(&self, id: &str) -> Option<&MemoryEntry> {
+        self.entries.get(id)
+    }
+
+    /// Rappelle une entrée (compte l'accès pour la promotion)
+    pub fn recall(&mut self, id: &str) -> Option<&MemoryEntry> {
+        let entry = self.entries.get_mut(id)?;
+        entry.mark_accessed();
+        Some(entry)
+    }
+
+    /// Rappelle les entrées portant un indice de rappel
+    pub fn recall_by_index(&mut self, index: &str) -> Vec<MemoryEntry> {
+        let index = index.to_lowercase();
+        let mut found: Vec<MemoryEntry> = self
+            .entries
+            .values_mut()
+            .filter(|e| e.recall_indices.contains(&index))
+            .map(|e| {
+                e.mark_accessed();
+                e.clone()
+            })
+            .collect();
+        found.sort_by(|a, b| b.importance.partial_cmp(&a.importance).unwrap_or(std::cmp::Ordering::Equal));
+        found
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<MemoryEntry> {
+        self.entries.remove(id)
+    }
+
+    /// Épingle une entrée : elle ne sera jamais oubliée
+    pub fn pin(&mut self, id: &str) -> bool {
+        self.set_pinned(id, true)
+    }
+
+    pub fn unpin(&mut self, id: &str) -> bool {
+        self.set_pinned(id, false)
+    }
+
+    fn set_pinned(&mut self, id: &str, pinned: bool) -> bool {
+        match self.entries.get_mut(id) {
+            Some(entry) => {
+                entry.pinned = pinned;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Entrées d'un niveau, de la plus ancienne à la plus récente
+    pub fn at_level(&self, level: MemoryLevel) -> Vec<&MemoryEntry> {
+        let mut entries: Vec<&MemoryEntry> = self.entries.values().filter(|e| e.level == level).collect();
+        entries.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        entries
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &MemoryEntry> {
+        self.entries.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Promeut les entrées éligibles au niveau supérieur (`should_promote`)
+    pub fn promote_eligible(&mut self) -> Vec<Promotion> {
+        let mut promotions = Vec::new();
+        for entry in self.entries.values_mut() {
+            if !entry.should_promote() {
+                continue;
+            }
+            let to = match entry.level {
+                MemoryLevel::ShortTerm => MemoryLevel::MediumTerm,
+                MemoryLevel::MediumTerm => MemoryLevel::LongTerm,
+                MemoryLevel::LongTerm | MemoryLevel::MetaSummary => continue,
+            };
+            promotions.push(Promotion {
+                entry_id: entry.id.clone(),
+                from: entry.level,
+                to,
+            });
+            entry.level = to;
+        }
+        promotions.sort_by(|a, b| a.entry_id.cmp(&b.entry_id));
+        promotions
+    }
+
+    /// Nombre d'entrées par niveau
+    pub fn level_counts(&self) -> HashMap<MemoryLevel, usize> {
+        let mut counts = HashMap::new();
+        for entry in self.entries.values() {
+            *counts.entry(entry.level).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_promotion_by_age_and_importance() {
+        let mut hierarchy = MemoryHierarchy::new();
+
+        let mut old = MemoryEntry::new("décision projet".to_string(), 0.8);
+        old.created_at = SystemTime::now() - Duration::from_secs(2 * 3600);
+        let old_id = hierarchy.insert(old);
+        let fresh_id = hierarchy.insert(MemoryEntry::new("note rapide".to_string(), 0.8));
+
+        let promotions = hierarchy.promote_eligible();
+        assert_eq!(promotions.len(), 1);
+        assert_eq!(promotions[0].entry_id, old_id);
+        assert_eq!(hierarchy.get(&old_id).unwrap().level, MemoryLevel::MediumTerm);
+        assert_eq!(hierarchy.get(&fresh_id).unwrap().level, MemoryLevel::ShortTerm);
+    }
+
+    #[test]
+    fn test_recall_counts_access() {
+        let mut hierarchy = MemoryHierarchy::new();
+        let mut entry = MemoryEntry::new("budget serveur".to_string(), 0.5);
+        entry.recall_indices = vec!["budget".to_string()];
+        let id = hierarchy.insert(entry);
+
+        assert_eq!(hierarchy.recall_by_index("Budget").len(), 1);
+        hierarchy.recall(&id);
+        assert_eq!(hierarchy.get(&id).unwrap().access_count, 2);
+    }
+}
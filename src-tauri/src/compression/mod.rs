@@ -5,23 +5,23 @@
 // ╚══════════════════════════════════════════════════════════════════════════════╝
 
 pub mod compressor;
+pub mod hierarchy;
+pub mod consolidator;
+pub mod forgetfulness;
 
-// Modules à implémenter (templates disponibles dans TITANE_V13_INTEGRATION_GUIDE.md)
-// pub mod hierarchy;
+// Module à implémenter (template disponible dans TITANE_V13_INTEGRATION_GUIDE.md)
 // pub mod indexer;
-// pub mod consolidator;
-// pub mod forgetfulness;
-
-// pub use hierarchy::MemoryHierarchy;
 // pub use indexer::MemoryIndexer;
-// pub use consolidator::MemoryConsolidator;
-// pub use forgetfulness::SelectiveForgetfulness;
+
+pub use hierarchy::MemoryHierarchy;
+pub use consolidator::MemoryConsolidator;
+pub use forgetfulness::SelectiveForgetfulness;
 
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, SystemTime};
 
 /// Niveau de mémoire
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum MemoryLevel {
     /// Court terme (< 1 heure)
     ShortTerm,
@@ -58,6 +58,9 @@ pub struct MemoryEntry {
     pub created_at: SystemTime,
     /// Liens vers autres entrées
     pub links: Vec<String>,
+    /// Épinglée : jamais oubliée ni fusionnée
+    #[serde(default)]
+    pub pinned: bool,
 }
 
 /// Statistiques de compression
@@ -88,6 +91,7 @@ impl MemoryEntry {
             last_accessed: now,
             created_at: now,
             links: Vec::new(),
+            pinned: false,
         }
     }
 
@@ -125,6 +129,10 @@ impl MemoryEntry {
 
     /// Devrait être élagué ?
     pub fn should_forget(&self) -> bool {
+        if self.pinned {
+            return false;
+        }
+
         let age = self.age();
         let access_freq = self.access_count as f32 / age.as_secs().max(1) as f32;
 
@@ -6,7 +6,7 @@
 use crate::{
     types::{MemoryState, Snapshot, LogEntry, TimelineEvent},
     services::StorageService,
-    utils::{AppResult, log_info, MEMORY_MAX_SNAPSHOTS},
+    utils::{AppResult, log_info, MEMORY_MAX_SNAPSHOTS, MEMORY_MAX_TIMELINE_EVENTS},
 };
 use chrono::Utc;
 use std::sync::Arc;
@@ -86,14 +86,71 @@ impl MemoryCore {
         
         timeline.push_back(event);
         
-        // Keep only last 500 events
-        if timeline.len() > 500 {
+        // Keep only last N events
+        if timeline.len() > MEMORY_MAX_TIMELINE_EVENTS {
             timeline.pop_front();
         }
         
         Ok(())
     }
     
+    /// Export snapshots and timeline (backup)
+    pub async fn export_history(&self) -> (Vec<Snapshot>, Vec<TimelineEvent>) {
+        let snapshots = self.snapshots.read().await;
+        let timeline = self.timeline.read().await;
+        (snapshots.iter().cloned().collect(), timeline.iter().cloned().collect())
+    }
+    
+    /// Import snapshots and timeline from a backup (merge by id, or replace)
+    pub async fn import_history(
+        &self,
+        snapshots: Vec<Snapshot>,
+        timeline: Vec<TimelineEvent>,
+        replace: bool,
+    ) -> AppResult<()> {
+        log_info("Memory", "Importing history from backup");
+        
+        let imported: Vec<Snapshot> = {
+            let mut current = self.snapshots.write().await;
+            if replace {
+                current.clear();
+            }
+            let new: Vec<Snapshot> = snapshots
+                .into_iter()
+                .filter(|s| !current.iter().any(|c| c.id == s.id))
+                .collect();
+            current.extend(new.iter().cloned());
+            current.make_contiguous().sort_by_key(|s| s.timestamp);
+            while current.len() > MEMORY_MAX_SNAPSHOTS {
+                current.pop_front();
+            }
+            new
+        };
+        
+        {
+            let mut current = self.timeline.write().await;
+            if replace {
+                current.clear();
+            }
+            for event in timeline {
+                if !current.iter().any(|e| e.id == event.id) {
+                    current.push_back(event);
+                }
+            }
+            current.make_contiguous().sort_by_key(|e| e.timestamp);
+            while current.len() > MEMORY_MAX_TIMELINE_EVENTS {
+                current.pop_front();
+            }
+        }
+        
+        // Persist imported snapshots (locks released)
+        for snapshot in &imported {
+            self.storage.save(&format!("snapshot_{}", snapshot.id), snapshot).await?;
+        }
+        
+        Ok(())
+    }
+    
     /// Get current state
     pub async fn get_state(&self) -> AppResult<MemoryState> {
         let snapshots = self.snapshots.read().await;
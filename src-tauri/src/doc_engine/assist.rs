@@ -0,0 +1,580 @@
+// TITANE∞ v13 - Assistance IA à la rédaction et à la relecture
+// Passe optionnelle de `DocumentGenerator` via l'`AIRouter` : rédaction des
+// sections vides à partir d'un brief, réécriture selon le style et le niveau
+// de détail, relecture convertie en avertissements et suggestions ciblés.
+// Tout contenu produit par l'IA est tracé dans `DocumentMetadata::ai_contributions`.
+
+use super::*;
+use crate::ai::router::AIRouter;
+use crate::ai::AIRequest;
+use std::sync::Arc;
+
+/// Taille maximale du document transmis pour la relecture
+const REVIEW_MAX_CHARS: usize = 12_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiAssistOptions {
+    /// Rédige les sections et clauses vides à partir de `params["brief"]`
+    pub draft: bool,
+    /// Réécrit les sections existantes selon `DocumentStyle` / `DetailLevel`
+    pub rewrite: bool,
+    /// Relecture : constats convertis en `ValidationWarning` / `Suggestion`
+    pub review: bool,
+    pub temperature: f32,
+    pub max_tokens: usize,
+}
+
+impl Default for AiAssistOptions {
+    fn default() -> Self {
+        Self {
+            draft: true,
+            rewrite: false,
+            review: true,
+            temperature: 0.3,
+            max_tokens: 1200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AiContributionKind {
+    Drafted,
+    Rewritten,
+    Reviewed,
+}
+
+/// Trace d'une intervention de l'IA sur un élément du document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiContribution {
+    /// `section:<id>`, `clause:<id>` ou `document` pour la relecture
+    pub target: String,
+    pub kind: AiContributionKind,
+    pub provider: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Relecteur humain ayant validé la contribution
+    pub reviewed_by: Option<String>,
+}
+
+impl DocumentMetadata {
+    /// Contributions IA pas encore validées par un humain
+    pub fn pending_ai_review(&self) -> Vec<&AiContribution> {
+        self.ai_contributions
+            .iter()
+            .filter(|c| c.reviewed_by.is_none())
+            .collect()
+    }
+
+    /// Valide les contributions IA sur `target` ; renvoie leur nombre
+    pub fn mark_ai_reviewed(&mut self, target: &str, reviewer: &str) -> usize {
+        let mut count = 0;
+        for contribution in &mut self.ai_contributions {
+            if contribution.target == target && contribution.reviewed_by.is_none() {
+                contribution.reviewed_by = Some(reviewer.to_string());
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+/// Résultat de la relecture
+#[derive(Debug, Clone, Default)]
+pub struct ReviewOutcome {
+    pub warnings: Vec<ValidationWarning>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlmReview {
+    #[serde(default)]
+    findings: Vec<LlmFinding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlmFinding {
+    #[serde(default)]
+    target: Option<String>,
+    #[serde(default)]
+    kind: String,
+    message: String,
+    #[serde(default)]
+    suggestion: Option<String>,
+    #[serde(default)]
+    priority: Option<u8>,
+}
+
+pub struct DocumentAssistant {
+    router: Arc<AIRouter>,
+    options: AiAssistOptions,
+}
+
+impl DocumentAssistant {
+    pub fn new(router: Arc<AIRouter>, options: AiAssistOptions) -> Self {
+        Self { router, options }
+    }
+
+    pub fn options(&self) -> &AiAssistOptions {
+        &self.options
+    }
+
+    async fn ask(&self, prompt: String) -> std::result::Result<(String, String), String> {
+        let response = self
+            .router
+            .query(AIRequest {
+                prompt,
+                temperature: self.options.temperature,
+                max_tokens: self.options.max_tokens,
+                stream: false,
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok((response.content, format!("{:?}", response.provider)))
+    }
+
+    fn contribution(target: String, kind: AiContributionKind, provider: String) -> AiContribution {
+        AiContribution {
+            target,
+            kind,
+            provider,
+            created_at: chrono::Utc::now(),
+            reviewed_by: None,
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Rédaction / réécriture
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Rédige puis réécrit selon les options. Un échec d'appel n'interrompt
+    /// pas la génération : l'élément garde son contenu déterministe et
+    /// l'échec est renvoyé comme avertissement.
+    pub async fn compose(
+        &self,
+        content: &mut DocumentContent,
+        config: &GenerationConfig,
+        params: &HashMap<String, String>,
+    ) -> (Vec<AiContribution>, Vec<ValidationWarning>) {
+        let brief = params
+            .get("brief")
+            .or_else(|| params.get("purpose"))
+            .cloned()
+            .unwrap_or_default();
+        let mut contributions = Vec::new();
+        let mut failures = Vec::new();
+
+        for target in targets(content) {
+            let current = body_of(content, &target.key).unwrap_or_default();
+            let (kind, prompt) = if current.trim().is_empty() {
+                if !self.options.draft || brief.trim().is_empty() {
+                    continue;
+                }
+                (
+                    AiContributionKind::Drafted,
+                    draft_prompt(content, config, &brief, &target.title),
+                )
+            } else if self.options.rewrite && target.key.starts_with("section:") {
+                (
+                    AiContributionKind::Rewritten,
+                    rewrite_prompt(config, &target.title, &current),
+                )
+            } else {
+                continue;
+            };
+
+            match self.ask(prompt).await {
+                Ok((text, provider)) => {
+                    contributions.extend(apply_response(content, &target, kind, &text, provider));
+                }
+                Err(e) => failures.push(ValidationWarning {
+                    message: format!(
+                        "Assistance IA indisponible pour « {} » : {}",
+                        target.title, e
+                    ),
+                    suggestion: Some("Compléter ce contenu manuellement".to_string()),
+                    target: Some(target.key),
+                }),
+            }
+        }
+
+        (contributions, failures)
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Relecture
+    // ─────────────────────────────────────────────────────────────────────────
+
+    pub async fn review(
+        &self,
+        content: &DocumentContent,
+        config: &GenerationConfig,
+    ) -> std::result::Result<(ReviewOutcome, AiContribution), String> {
+        let known: Vec<Target> = targets(content);
+        let listing: String = known
+            .iter()
+            .map(|t| format!("- {} : {}", t.key, t.title))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let text: String = document_text(content)
+            .chars()
+            .take(REVIEW_MAX_CHARS)
+            .collect();
+
+        let prompt = format!(
+            "Tu relis un document professionnel ({:?}, style {:?}, langue {}).\n\
+             Relève les incohérences, ambiguïtés, clauses manquantes ou risquées et problèmes de ton.\n\
+             Réponds uniquement en JSON : {{\"findings\": [{{\"target\": \"section:<id>|clause:<id>|null\", \
+             \"kind\": \"warning|suggestion\", \"message\": \"...\", \"suggestion\": \"...\", \"priority\": 1-10}}]}}\n\n\
+             Éléments du document :\n{}\n\nDocument :\n{}",
+            config.doc_type, config.style, config.language, listing, text
+        );
+
+        let (response, provider) = self.ask(prompt).await?;
+        let outcome = parse_review(&response, &known)?;
+        Ok((
+            outcome,
+            Self::contribution(
+                "document".to_string(),
+                AiContributionKind::Reviewed,
+                provider,
+            ),
+        ))
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Éléments ciblés
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct Target {
+    key: String,
+    title: String,
+}
+
+/// Sections (récursivement) puis clauses, dans l'ordre du document
+fn targets(content: &DocumentContent) -> Vec<Target> {
+    fn walk(sections: &[Section], out: &mut Vec<Target>) {
+        for section in sections {
+            out.push(Target {
+                key: format!("section:{}", section.id),
+                title: section.title.clone(),
+            });
+            walk(&section.subsections, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    walk(&content.sections, &mut out);
+    for clause in content.mandatory_clauses.iter().flatten() {
+        out.push(Target {
+            key: format!("clause:{}", clause.id),
+            title: clause.title.clone(),
+        });
+    }
+    out
+}
+
+fn find_section<'a>(sections: &'a mut [Section], id: &str) -> Option<&'a mut Section> {
+    for section in sections {
+        if section.id == id {
+            return Some(section);
+        }
+        if let Some(found) = find_section(&mut section.subsections, id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+fn body_slot<'a>(content: &'a mut DocumentContent, key: &str) -> Option<&'a mut String> {
+    if let Some(id) = key.strip_prefix("section:") {
+        return find_section(&mut content.sections, id).map(|s| &mut s.content);
+    }
+    let id = key.strip_prefix("clause:")?;
+    content
+        .mandatory_clauses
+        .iter_mut()
+        .flatten()
+        .find(|c| c.id == id)
+        .map(|c| &mut c.content)
+}
+
+fn body_of(content: &mut DocumentContent, key: &str) -> Option<String> {
+    body_slot(content, key).map(|body| body.clone())
+}
+
+fn set_body(content: &mut DocumentContent, key: &str, text: String) {
+    if let Some(body) = body_slot(content, key) {
+        *body = text;
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Prompts
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn style_guidance(style: &DocumentStyle) -> &'static str {
+    match style {
+        DocumentStyle::Formal => "registre soutenu, phrases complètes, vouvoiement",
+        DocumentStyle::Legal => "rédaction juridique précise, termes définis, aucune ambiguïté",
+        DocumentStyle::Technical => "précis et factuel, vocabulaire technique, listes si utile",
+        DocumentStyle::Editorial => "fluide et engageant, transitions soignées",
+        DocumentStyle::Pedagogical => "clair et progressif, exemples concrets",
+        DocumentStyle::Professional => "professionnel, direct et structuré",
+        DocumentStyle::Academic => "argumenté, nuancé, sources évoquées",
+    }
+}
+
+fn detail_guidance(level: &DetailLevel) -> &'static str {
+    match level {
+        DetailLevel::Summary => "2 à 3 phrases",
+        DetailLevel::Standard => "un à deux paragraphes",
+        DetailLevel::Advanced => "plusieurs paragraphes détaillés",
+        DetailLevel::Exhaustive => "exhaustif, avec sous-points et exemples",
+    }
+}
+
+fn draft_prompt(
+    content: &DocumentContent,
+    config: &GenerationConfig,
+    brief: &str,
+    title: &str,
+) -> String {
+    let outline: Vec<String> = targets(content).into_iter().map(|t| t.title).collect();
+    format!(
+        "Rédige le contenu de la partie « {} » d'un document « {} » ({:?}).\n\
+         Brief : {}\n\
+         Plan du document : {}\n\
+         Style : {}. Longueur : {}. Ton : {}. Langue : {}.\n\
+         Réponds uniquement avec le corps de la partie en Markdown, sans titre.",
+        title,
+        content.title,
+        config.doc_type,
+        brief,
+        outline.join(" ; "),
+        style_guidance(&config.style),
+        detail_guidance(&config.detail_level),
+        config.tone,
+        config.language
+    )
+}
+
+fn rewrite_prompt(config: &GenerationConfig, title: &str, current: &str) -> String {
+    format!(
+        "Réécris la partie « {} » ci-dessous sans changer le sens ni retirer d'information.\n\
+         Style : {}. Longueur : {}. Ton : {}. Langue : {}.\n\
+         Réponds uniquement avec le corps réécrit en Markdown, sans titre.\n\n{}",
+        title,
+        style_guidance(&config.style),
+        detail_guidance(&config.detail_level),
+        config.tone,
+        config.language,
+        current
+    )
+}
+
+/// Retire les clôtures de code et un titre répété en tête de réponse
+fn clean_body(text: &str, title: &str) -> String {
+    let mut text = text.trim();
+    if let Some(inner) = text.strip_prefix("```") {
+        let inner = inner.split_once('\n').map_or("", |(_, rest)| rest);
+        text = inner.strip_suffix("```").unwrap_or(inner).trim();
+    }
+    if let Some((first, rest)) = text.split_once('\n') {
+        let heading = first.trim_start_matches('#').trim();
+        if first.starts_with('#') && heading.eq_ignore_ascii_case(title.trim()) {
+            text = rest.trim();
+        }
+    }
+    text.to_string()
+}
+
+/// Remplace le corps ciblé par la réponse nettoyée et trace la contribution
+/// (élément, nature, fournisseur) ; une réponse vide ne change rien
+fn apply_response(
+    content: &mut DocumentContent,
+    target: &Target,
+    kind: AiContributionKind,
+    response: &str,
+    provider: String,
+) -> Option<AiContribution> {
+    let text = clean_body(response, &target.title);
+    if text.is_empty() {
+        return None;
+    }
+    set_body(content, &target.key, text);
+    Some(DocumentAssistant::contribution(target.key.clone(), kind, provider))
+}
+
+fn document_text(content: &DocumentContent) -> String {
+    fn walk(sections: &[Section], out: &mut String) {
+        for section in sections {
+            out.push_str(&format!(
+                "\n[section:{}] {}\n{}\n",
+                section.id, section.title, section.content
+            ));
+            walk(&section.subsections, out);
+        }
+    }
+
+    let mut out = format!("# {}\n{}\n", content.title, content.executive_summary);
+    walk(&content.sections, &mut out);
+    for clause in content.mandatory_clauses.iter().flatten() {
+        out.push_str(&format!(
+            "\n[clause:{}] {}\n{}\n",
+            clause.id, clause.title, clause.content
+        ));
+    }
+    out
+}
+
+fn parse_review(response: &str, known: &[Target]) -> std::result::Result<ReviewOutcome, String> {
+    let start = response.find('{').ok_or("Réponse de relecture sans JSON")?;
+    let end = response
+        .rfind('}')
+        .ok_or("Réponse de relecture sans JSON")?;
+    let parsed: LlmReview = serde_json::from_str(&response[start..=end])
+        .map_err(|e| format!("JSON de relecture invalide: {}", e))?;
+
+    let mut outcome = ReviewOutcome::default();
+    for finding in parsed.findings {
+        // Cible inconnue du document : constat rattaché au document entier
+        let target = finding
+            .target
+            .map(|t| t.trim().to_string())
+            .filter(|t| known.iter().any(|k| &k.key == t));
+        let message = format!("[IA] {}", finding.message.trim());
+
+        if finding.kind.eq_ignore_ascii_case("warning") {
+            outcome.warnings.push(ValidationWarning {
+                message,
+                suggestion: finding.suggestion,
+                target,
+            });
+        } else {
+            let message = match finding.suggestion {
+                Some(suggestion) if !suggestion.trim().is_empty() => {
+                    format!("{} — {}", message, suggestion.trim())
+                }
+                _ => message,
+            };
+            outcome.suggestions.push(Suggestion {
+                category: "ai_review".to_string(),
+                message,
+                // Plafonné sous le seuil d'application automatique (8)
+                priority: finding.priority.unwrap_or(5).clamp(1, 7),
+                target,
+            });
+        }
+    }
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known() -> Vec<Target> {
+        targets(&test_document(DocumentType::Analysis, DocumentStyle::Professional).content)
+    }
+
+    #[test]
+    fn test_parse_review_rejects_malformed_json() {
+        let known = known();
+        assert_eq!(
+            parse_review("Aucun problème relevé.", &known).unwrap_err(),
+            "Réponse de relecture sans JSON"
+        );
+        assert_eq!(parse_review("{\"findings\": [", &known).unwrap_err(), "Réponse de relecture sans JSON");
+        let error = parse_review("{\"findings\": [}", &known).unwrap_err();
+        assert!(error.starts_with("JSON de relecture invalide"), "{}", error);
+        // `message` est obligatoire
+        assert!(parse_review("{\"findings\": [{\"kind\": \"warning\"}]}", &known).is_err());
+        let empty = parse_review("{}", &known).unwrap();
+        assert!(empty.warnings.is_empty() && empty.suggestions.is_empty());
+    }
+
+    #[test]
+    fn test_parse_review_accepts_fenced_response_and_drops_unknown_targets() {
+        let response = "Voici ma relecture :\n```json\n{\"findings\": [\n\
+            {\"target\": \" section:contexte \", \"kind\": \"Warning\", \"message\": \" Liste vague \", \"suggestion\": \"Chiffrer\"},\n\
+            {\"target\": \"section:inconnue\", \"kind\": \"warning\", \"message\": \"Hors document\"},\n\
+            {\"target\": null, \"kind\": \"suggestion\", \"message\": \"Ajouter un glossaire\"}\n\
+            ]}\n```";
+        let outcome = parse_review(response, &known()).unwrap();
+
+        assert_eq!(outcome.warnings.len(), 2);
+        assert_eq!(outcome.warnings[0].message, "[IA] Liste vague");
+        assert_eq!(outcome.warnings[0].target.as_deref(), Some("section:contexte"));
+        assert_eq!(outcome.warnings[0].suggestion.as_deref(), Some("Chiffrer"));
+        // Cible inconnue : rattachée au document entier
+        assert_eq!(outcome.warnings[1].target, None);
+        assert_eq!(outcome.suggestions.len(), 1);
+        assert_eq!(outcome.suggestions[0].category, "ai_review");
+        assert_eq!(outcome.suggestions[0].target, None);
+    }
+
+    #[test]
+    fn test_parse_review_clamps_priority() {
+        let response = r#"{"findings": [
+            {"target": "section:intro", "kind": "suggestion", "message": "A", "suggestion": "Préciser", "priority": 10},
+            {"kind": "suggestion", "message": "B", "suggestion": "  ", "priority": 0},
+            {"kind": "autre", "message": "C"}
+        ]}"#;
+        let outcome = parse_review(response, &known()).unwrap();
+        let priorities: Vec<u8> = outcome.suggestions.iter().map(|s| s.priority).collect();
+        // Jamais au niveau d'application automatique (8)
+        assert_eq!(priorities, [7, 1, 5]);
+        assert_eq!(outcome.suggestions[0].message, "[IA] A — Préciser");
+        assert_eq!(outcome.suggestions[0].target.as_deref(), Some("section:intro"));
+        assert_eq!(outcome.suggestions[1].message, "[IA] B");
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_clean_body_strips_fences_and_repeated_title() {
+        assert_eq!(clean_body("```markdown\n# Introduction\nCorps.\n```", "Introduction"), "Corps.");
+        assert_eq!(clean_body("  ## introduction \n\nCorps.  ", "Introduction"), "Corps.");
+        assert_eq!(clean_body("```\nCorps.```", "Introduction"), "Corps.");
+        // Autre titre ou réponse d'une ligne : conservés
+        assert_eq!(clean_body("# Contexte\nCorps.", "Introduction"), "# Contexte\nCorps.");
+        assert_eq!(clean_body("# Introduction", "Introduction"), "# Introduction");
+        assert_eq!(clean_body("```\n```", "Introduction"), "");
+    }
+
+    #[test]
+    fn test_applied_response_records_provenance() {
+        let mut document = test_document(DocumentType::Analysis, DocumentStyle::Professional);
+        let target = known().into_iter().find(|t| t.key == "section:contexte").unwrap();
+
+        let contribution = apply_response(
+            &mut document.content,
+            &target,
+            AiContributionKind::Rewritten,
+            "```\n### Contexte\n- point réécrit\n```",
+            "Gemini".to_string(),
+        )
+        .unwrap();
+        assert_eq!(document.content.sections[0].subsections[0].content, "- point réécrit");
+        assert_eq!(contribution.target, "section:contexte");
+        assert_eq!(contribution.kind, AiContributionKind::Rewritten);
+        assert_eq!(contribution.provider, "Gemini");
+        assert_eq!(contribution.reviewed_by, None);
+
+        // Réponse vide : contenu déterministe conservé, aucune trace
+        assert!(apply_response(&mut document.content, &target, AiContributionKind::Drafted, " ", "Ollama".to_string()).is_none());
+        assert_eq!(document.content.sections[0].subsections[0].content, "- point réécrit");
+
+        document.metadata.ai_contributions.push(contribution);
+        document.metadata.ai_contributions.push(DocumentAssistant::contribution(
+            "document".to_string(),
+            AiContributionKind::Reviewed,
+            "Ollama".to_string(),
+        ));
+        assert_eq!(document.metadata.pending_ai_review().len(), 2);
+        assert_eq!(document.metadata.mark_ai_reviewed("section:contexte", "juriste"), 1);
+        assert_eq!(document.metadata.mark_ai_reviewed("section:contexte", "juriste"), 0);
+        let pending = document.metadata.pending_ai_review();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].target, "document");
+    }
+}
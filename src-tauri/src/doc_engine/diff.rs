@@ -0,0 +1,544 @@
+// TITANE∞ v13 - Diff textuel et fusion à trois voies
+// Primitives utilisées par `versioning` : script d'édition (LCS), diff ligne
+// par ligne avec détail mot à mot, fusion diff3 avec marqueurs de conflit et
+// rendu au format diff unifié.
+
+use serde::{Deserialize, Serialize};
+
+/// Au-delà de cette taille de table LCS, la zone modifiée est traitée comme
+/// un remplacement en bloc (évite une explosion mémoire sur les gros textes)
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// Similarité minimale pour présenter deux lignes comme une modification
+const MODIFIED_LINE_SIMILARITY: f64 = 0.5;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Types
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Changement de ligne (numéros à partir de 1)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum LineChange {
+    Added {
+        line: usize,
+        text: String,
+    },
+    Removed {
+        line: usize,
+        text: String,
+    },
+    Modified {
+        old_line: usize,
+        new_line: usize,
+        old_text: String,
+        new_text: String,
+        words: Vec<WordChange>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WordOp {
+    Equal,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordChange {
+    pub op: WordOp,
+    pub text: String,
+}
+
+/// Opération élémentaire du script d'édition (indices dans `a` et `b`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Edit {
+    Keep(usize, usize),
+    Remove(usize),
+    Add(usize),
+}
+
+/// Labels des marqueurs de conflit
+pub(crate) struct MergeLabels<'a> {
+    pub ours: &'a str,
+    pub base: &'a str,
+    pub theirs: &'a str,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Script d'édition
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Plus longue sous-séquence commune, après retrait du préfixe et du
+/// suffixe communs. Les suppressions précèdent les ajouts dans chaque bloc.
+pub(crate) fn edit_script<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Edit> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let middle_a = &a[prefix..a.len() - suffix];
+    let middle_b = &b[prefix..b.len() - suffix];
+    let (n, m) = (middle_a.len(), middle_b.len());
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Keep(i, i)).collect();
+
+    if n.saturating_mul(m) <= MAX_LCS_CELLS {
+        // lcs[i][j] = longueur de la LCS de middle_a[i..] et middle_b[j..]
+        let width = m + 1;
+        let mut lcs = vec![0u32; (n + 1) * width];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i * width + j] = if middle_a[i] == middle_b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if middle_a[i] == middle_b[j] {
+                edits.push(Edit::Keep(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                edits.push(Edit::Remove(prefix + i));
+                i += 1;
+            } else {
+                edits.push(Edit::Add(prefix + j));
+                j += 1;
+            }
+        }
+        edits.extend((i..n).map(|i| Edit::Remove(prefix + i)));
+        edits.extend((j..m).map(|j| Edit::Add(prefix + j)));
+    } else {
+        edits.extend((0..n).map(|i| Edit::Remove(prefix + i)));
+        edits.extend((0..m).map(|j| Edit::Add(prefix + j)));
+    }
+
+    edits.extend((0..suffix).map(|k| Edit::Keep(a.len() - suffix + k, b.len() - suffix + k)));
+    edits
+}
+
+/// Pour chaque élément de `base`, son indice dans `other` s'il est conservé
+fn matches<T: PartialEq>(base: &[T], other: &[T]) -> Vec<Option<usize>> {
+    let mut matched = vec![None; base.len()];
+    for edit in edit_script(base, other) {
+        if let Edit::Keep(i, j) = edit {
+            matched[i] = Some(j);
+        }
+    }
+    matched
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Diff lignes / mots
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Mots, espaces et ponctuation forment des jetons distincts
+fn tokenize(text: &str) -> Vec<&str> {
+    #[derive(PartialEq, Clone, Copy)]
+    enum Class {
+        Word,
+        Space,
+        Other,
+    }
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Other
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut current: Option<Class> = None;
+    for (idx, c) in text.char_indices() {
+        let c_class = class(c);
+        // La ponctuation n'est jamais regroupée
+        if current != Some(c_class) || c_class == Class::Other {
+            if idx > start {
+                tokens.push(&text[start..idx]);
+            }
+            start = idx;
+            current = Some(c_class);
+        }
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+pub fn diff_words(old: &str, new: &str) -> Vec<WordChange> {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let mut changes: Vec<WordChange> = Vec::new();
+
+    for edit in edit_script(&old_tokens, &new_tokens) {
+        let (op, text) = match edit {
+            Edit::Keep(i, _) => (WordOp::Equal, old_tokens[i]),
+            Edit::Remove(i) => (WordOp::Removed, old_tokens[i]),
+            Edit::Add(j) => (WordOp::Added, new_tokens[j]),
+        };
+        match changes.last_mut() {
+            Some(last) if last.op == op => last.text.push_str(text),
+            _ => changes.push(WordChange {
+                op,
+                text: text.to_string(),
+            }),
+        }
+    }
+    changes
+}
+
+/// Part du texte conservée entre deux lignes (0 à 1)
+fn similarity(words: &[WordChange], old: &str, new: &str) -> f64 {
+    let total = old.chars().count() + new.chars().count();
+    if total == 0 {
+        return 1.0;
+    }
+    let kept: usize = words
+        .iter()
+        .filter(|w| w.op == WordOp::Equal && !w.text.trim().is_empty())
+        .map(|w| w.text.chars().count())
+        .sum();
+    (2 * kept) as f64 / total as f64
+}
+
+pub fn diff_lines(old: &str, new: &str) -> Vec<LineChange> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let mut changes = Vec::new();
+    let mut removed: Vec<usize> = Vec::new();
+    let mut added: Vec<usize> = Vec::new();
+
+    let flush =
+        |removed: &mut Vec<usize>, added: &mut Vec<usize>, changes: &mut Vec<LineChange>| {
+            // Appariement des lignes d'un même bloc : i-ème supprimée / i-ème ajoutée
+            let paired = removed.len().min(added.len());
+            let mut unpaired_removed = Vec::new();
+            let mut unpaired_added = Vec::new();
+            for k in 0..paired {
+                let (i, j) = (removed[k], added[k]);
+                let words = diff_words(old_lines[i], new_lines[j]);
+                if similarity(&words, old_lines[i], new_lines[j]) >= MODIFIED_LINE_SIMILARITY {
+                    changes.push(LineChange::Modified {
+                        old_line: i + 1,
+                        new_line: j + 1,
+                        old_text: old_lines[i].to_string(),
+                        new_text: new_lines[j].to_string(),
+                        words,
+                    });
+                } else {
+                    unpaired_removed.push(i);
+                    unpaired_added.push(j);
+                }
+            }
+            unpaired_removed.extend(&removed[paired..]);
+            unpaired_added.extend(&added[paired..]);
+            changes.extend(unpaired_removed.into_iter().map(|i| LineChange::Removed {
+                line: i + 1,
+                text: old_lines[i].to_string(),
+            }));
+            changes.extend(unpaired_added.into_iter().map(|j| LineChange::Added {
+                line: j + 1,
+                text: new_lines[j].to_string(),
+            }));
+            removed.clear();
+            added.clear();
+        };
+
+    for edit in edit_script(&old_lines, &new_lines) {
+        match edit {
+            Edit::Keep(..) => flush(&mut removed, &mut added, &mut changes),
+            Edit::Remove(i) => {
+                // Un ajout suivi d'une suppression ouvre un nouveau bloc
+                if !added.is_empty() {
+                    flush(&mut removed, &mut added, &mut changes);
+                }
+                removed.push(i);
+            }
+            Edit::Add(j) => added.push(j),
+        }
+    }
+    flush(&mut removed, &mut added, &mut changes);
+    changes
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Fusion à trois voies
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Fusion diff3 ligne à ligne. Les zones modifiées des deux côtés de façon
+/// différente sont encadrées de marqueurs ; renvoie le texte et le nombre
+/// de conflits.
+pub(crate) fn merge_text(
+    base: &str,
+    ours: &str,
+    theirs: &str,
+    labels: &MergeLabels,
+) -> (String, usize) {
+    if ours == theirs || theirs == base {
+        return (ours.to_string(), 0);
+    }
+    if ours == base {
+        return (theirs.to_string(), 0);
+    }
+
+    let base_lines: Vec<&str> = base.lines().collect();
+    let our_lines: Vec<&str> = ours.lines().collect();
+    let their_lines: Vec<&str> = theirs.lines().collect();
+    let to_ours = matches(&base_lines, &our_lines);
+    let to_theirs = matches(&base_lines, &their_lines);
+
+    let mut merged: Vec<String> = Vec::new();
+    let mut conflicts = 0;
+    let (mut b, mut o, mut t) = (0, 0, 0);
+
+    loop {
+        // Prochaine ligne de base conservée des deux côtés
+        let sync = (b..base_lines.len()).find_map(|k| match (to_ours[k], to_theirs[k]) {
+            (Some(ko), Some(kt)) if ko >= o && kt >= t => Some((k, ko, kt)),
+            _ => None,
+        });
+        let (end_b, end_o, end_t) =
+            sync.unwrap_or((base_lines.len(), our_lines.len(), their_lines.len()));
+
+        let chunk_base = &base_lines[b..end_b];
+        let chunk_ours = &our_lines[o..end_o];
+        let chunk_theirs = &their_lines[t..end_t];
+        let owned = |lines: &[&str]| lines.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+        if chunk_ours == chunk_base {
+            merged.extend(owned(chunk_theirs));
+        } else if chunk_theirs == chunk_base || chunk_ours == chunk_theirs {
+            merged.extend(owned(chunk_ours));
+        } else {
+            conflicts += 1;
+            merged.push(format!("<<<<<<< {}", labels.ours));
+            merged.extend(owned(chunk_ours));
+            merged.push(format!("||||||| {}", labels.base));
+            merged.extend(owned(chunk_base));
+            merged.push("=======".to_string());
+            merged.extend(owned(chunk_theirs));
+            merged.push(format!(">>>>>>> {}", labels.theirs));
+        }
+
+        match sync {
+            Some((k, ko, kt)) => {
+                merged.push(base_lines[k].to_string());
+                b = k + 1;
+                o = ko + 1;
+                t = kt + 1;
+            }
+            None => break,
+        }
+    }
+
+    (merged.join("\n"), conflicts)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Diff unifié
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Rendu `diff -u` de deux listes de lignes ; chaîne vide si identiques
+pub(crate) fn unified_diff(
+    old: &[String],
+    new: &[String],
+    old_label: &str,
+    new_label: &str,
+    context: usize,
+) -> String {
+    let edits = edit_script(old, new);
+    let changed: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !matches!(e, Edit::Keep(..)))
+        .map(|(k, _)| k)
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // Position (lignes consommées) avant chaque opération
+    let mut positions = Vec::with_capacity(edits.len() + 1);
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for edit in &edits {
+        positions.push((old_pos, new_pos));
+        match edit {
+            Edit::Keep(..) => {
+                old_pos += 1;
+                new_pos += 1;
+            }
+            Edit::Remove(_) => old_pos += 1,
+            Edit::Add(_) => new_pos += 1,
+        }
+    }
+
+    // Regroupement des changements proches en blocs avec contexte
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for k in changed {
+        let start = k.saturating_sub(context);
+        let end = (k + context + 1).min(edits.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for (start, end) in hunks {
+        let range = &edits[start..end];
+        let old_count = range.iter().filter(|e| !matches!(e, Edit::Add(_))).count();
+        let new_count = range
+            .iter()
+            .filter(|e| !matches!(e, Edit::Remove(_)))
+            .count();
+        let (old_start, new_start) = positions[start];
+        // Convention diff : un bloc vide pointe sur la ligne précédente
+        let old_start = if old_count == 0 {
+            old_start
+        } else {
+            old_start + 1
+        };
+        let new_start = if new_count == 0 {
+            new_start
+        } else {
+            new_start + 1
+        };
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_count, new_start, new_count
+        ));
+        for edit in range {
+            let line = match edit {
+                Edit::Keep(i, _) => format!(" {}", old[*i]),
+                Edit::Remove(i) => format!("-{}", old[*i]),
+                Edit::Add(j) => format!("+{}", new[*j]),
+            };
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LABELS: MergeLabels<'static> = MergeLabels {
+        ours: "ours",
+        base: "base",
+        theirs: "theirs",
+    };
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_edit_script_keeps_common_subsequence() {
+        let edits = edit_script(&["a", "b", "c", "d"], &["a", "c", "x", "d"]);
+        assert_eq!(
+            edits,
+            vec![
+                Edit::Keep(0, 0),
+                Edit::Remove(1),
+                Edit::Keep(2, 1),
+                Edit::Add(2),
+                Edit::Keep(3, 3),
+            ]
+        );
+        assert!(edit_script::<&str>(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_diff_lines_pairs_similar_lines() {
+        let changes = diff_lines(
+            "Le prestataire livre le rapport.\nLigne supprimée\nFin",
+            "Le prestataire livre le rapport final.\nFin\nLigne ajoutée",
+        );
+        assert_eq!(changes.len(), 3);
+        match &changes[0] {
+            LineChange::Modified { old_line, new_line, words, .. } => {
+                assert_eq!((*old_line, *new_line), (1, 1));
+                assert!(words.iter().any(|w| w.op == WordOp::Added && w.text.trim() == "final"));
+            }
+            other => panic!("modification attendue: {:?}", other),
+        }
+        assert_eq!(
+            changes[1],
+            LineChange::Removed {
+                line: 2,
+                text: "Ligne supprimée".to_string()
+            }
+        );
+        assert_eq!(
+            changes[2],
+            LineChange::Added {
+                line: 3,
+                text: "Ligne ajoutée".to_string()
+            }
+        );
+
+        // Lignes trop différentes : suppression + ajout plutôt que modification
+        let changes = diff_lines("alpha beta", "gamma delta");
+        assert!(matches!(changes[0], LineChange::Removed { .. }));
+        assert!(matches!(changes[1], LineChange::Added { .. }));
+        assert!(diff_lines("même", "même").is_empty());
+    }
+
+    #[test]
+    fn test_merge_text_combines_disjoint_edits() {
+        let base = "un\ndeux\ntrois\nquatre";
+        let ours = "UN\ndeux\ntrois\nquatre";
+        let theirs = "un\ndeux\ntrois\nQUATRE\ncinq";
+        let (merged, conflicts) = merge_text(base, ours, theirs, &LABELS);
+        assert_eq!(conflicts, 0);
+        assert_eq!(merged, "UN\ndeux\ntrois\nQUATRE\ncinq");
+    }
+
+    #[test]
+    fn test_merge_text_marks_conflicts() {
+        let (merged, conflicts) = merge_text("a\nb\nc", "a\nB1\nc", "a\nB2\nc", &LABELS);
+        assert_eq!(conflicts, 1);
+        assert_eq!(
+            merged,
+            "a\n<<<<<<< ours\nB1\n||||||| base\nb\n=======\nB2\n>>>>>>> theirs\nc"
+        );
+
+        // Même modification des deux côtés : pas de conflit
+        assert_eq!(merge_text("a\nb", "a\nc", "a\nc", &LABELS), ("a\nc".to_string(), 0));
+    }
+
+    #[test]
+    fn test_unified_diff_hunks() {
+        let old = lines("1\n2\n3\n4\n5\n6\n7\n8\n9");
+        let mut new = old.clone();
+        new[1] = "deux".to_string();
+        new.push("10".to_string());
+
+        assert_eq!(
+            unified_diff(&old, &new, "a", "b", 1),
+            "--- a\n+++ b\n\
+             @@ -1,3 +1,3 @@\n 1\n-2\n+deux\n 3\n\
+             @@ -9,1 +9,2 @@\n 9\n+10\n"
+        );
+        // Contexte large : un seul bloc
+        assert_eq!(unified_diff(&old, &new, "a", "b", 8).matches("@@ -").count(), 1);
+        assert_eq!(unified_diff(&old, &old, "a", "b", 3), "");
+        assert_eq!(
+            unified_diff(&[], &lines("x"), "a", "b", 3),
+            "--- a\n+++ b\n@@ -0,0 +1,1 @@\n+x\n"
+        );
+    }
+}
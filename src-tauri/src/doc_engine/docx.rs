@@ -0,0 +1,1182 @@
+// TITANE∞ v13 - Export et import DOCX (Office Open XML)
+// Écriture : styles de titres réels, listes et clauses numérotées, tableaux,
+// en-têtes/pieds de page. Lecture : paragraphes relus en sections afin de
+// comparer un document revenu de Word avec `VersioningEngine::diff_versions`.
+
+use super::office::{self, escape_xml, Block, ParagraphKind, Span};
+use super::*;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const NS_W: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const NS_R: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const REL_BASE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// Instances de numérotation fixes ; les listes du contenu suivent
+const NUM_CLAUSES: usize = 1;
+const NUM_ANNEXES: usize = 2;
+const NUM_LISTS_START: usize = 3;
+
+/// Génère le fichier DOCX d'un document
+pub fn render_docx(document: &Document) -> Result<Vec<u8>> {
+    let blocks = office::document_blocks(document);
+    let mut body = DocxBody::default();
+    for block in &blocks {
+        body.block(block);
+    }
+
+    let numbering = numbering_xml(office::numbers_clauses(document), &body.lists);
+    let parts = [
+        ("[Content_Types].xml", CONTENT_TYPES.to_string()),
+        ("_rels/.rels", ROOT_RELS.to_string()),
+        ("docProps/core.xml", core_properties(document)),
+        ("word/document.xml", body.document_xml()),
+        ("word/_rels/document.xml.rels", body.relationships_xml()),
+        ("word/styles.xml", STYLES.to_string()),
+        ("word/numbering.xml", numbering),
+        ("word/header1.xml", header_xml(document)),
+        ("word/footer1.xml", footer_xml(document)),
+    ];
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, xml) in parts {
+        zip.start_file(name, options).map_err(export_error)?;
+        zip.write_all(xml.as_bytes()).map_err(export_error)?;
+    }
+    Ok(zip.finish().map_err(export_error)?.into_inner())
+}
+
+fn export_error(e: impl std::fmt::Display) -> DocEngineError {
+    DocEngineError::ExportError(format!("Erreur DOCX: {}", e))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Écriture
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+struct DocxBody {
+    xml: String,
+    /// Liens externes (rId, url)
+    links: Vec<(String, String)>,
+    /// Listes du contenu : (instance, ordonnée)
+    lists: Vec<(usize, bool)>,
+}
+
+impl DocxBody {
+    fn block(&mut self, block: &Block) {
+        match block {
+            Block::Title(text) => self.paragraph(Some("Title"), None, &[office::plain(text)]),
+            Block::Heading { level, text } => self.paragraph(
+                Some(&format!("Heading{}", level)),
+                None,
+                &[office::plain(text)],
+            ),
+            Block::Paragraph { kind, spans } => {
+                let style = match kind {
+                    ParagraphKind::Body => None,
+                    ParagraphKind::Quote => Some("Quote"),
+                    ParagraphKind::Subtitle => Some("Subtitle"),
+                };
+                self.paragraph(style, None, spans)
+            }
+            Block::ListItem {
+                list,
+                ordered,
+                level,
+                spans,
+            } => {
+                if !self.lists.iter().any(|(id, _)| id == list) {
+                    self.lists.push((*list, *ordered));
+                }
+                let numbering = (NUM_LISTS_START + list - 1, *level);
+                self.paragraph(Some("ListParagraph"), Some(numbering), spans)
+            }
+            Block::ClauseTitle(text) => self.paragraph(
+                Some("ClauseTitle"),
+                Some((NUM_CLAUSES, 0)),
+                &[office::plain(text)],
+            ),
+            Block::AnnexTitle { text, numbered } => {
+                let numbering = numbered.then_some((NUM_ANNEXES, 0));
+                self.paragraph(Some("AnnexTitle"), numbering, &[office::plain(text)])
+            }
+            Block::Table { header, rows } => self.table(header, rows),
+            Block::Code(lines) => {
+                for line in lines {
+                    let span = Span {
+                        text: line.clone(),
+                        ..Span::default()
+                    };
+                    self.paragraph(Some("Code"), None, &[span]);
+                }
+            }
+            Block::PageBreak => self
+                .xml
+                .push_str(r#"<w:p><w:r><w:br w:type="page"/></w:r></w:p>"#),
+        }
+    }
+
+    fn paragraph(&mut self, style: Option<&str>, numbering: Option<(usize, u8)>, spans: &[Span]) {
+        self.xml.push_str("<w:p>");
+        if style.is_some() || numbering.is_some() {
+            self.xml.push_str("<w:pPr>");
+            if let Some(style) = style {
+                self.xml
+                    .push_str(&format!(r#"<w:pStyle w:val="{}"/>"#, style));
+            }
+            if let Some((num_id, level)) = numbering {
+                self.xml.push_str(&format!(
+                    r#"<w:numPr><w:ilvl w:val="{}"/><w:numId w:val="{}"/></w:numPr>"#,
+                    level, num_id
+                ));
+            }
+            self.xml.push_str("</w:pPr>");
+        }
+        for span in spans {
+            self.run(span);
+        }
+        self.xml.push_str("</w:p>");
+    }
+
+    fn run(&mut self, span: &Span) {
+        let link_id = span.link.as_ref().map(|url| {
+            let id = format!("rIdLink{}", self.links.len() + 1);
+            self.links.push((id.clone(), url.clone()));
+            id
+        });
+        if let Some(id) = &link_id {
+            self.xml
+                .push_str(&format!(r#"<w:hyperlink r:id="{}">"#, id));
+        }
+
+        self.xml.push_str("<w:r>");
+        if span.bold || span.italic || span.code || link_id.is_some() {
+            self.xml.push_str("<w:rPr>");
+            if span.code {
+                self.xml.push_str(r#"<w:rStyle w:val="CodeChar"/>"#);
+            } else if link_id.is_some() {
+                self.xml.push_str(r#"<w:rStyle w:val="Hyperlink"/>"#);
+            }
+            if span.bold {
+                self.xml.push_str("<w:b/>");
+            }
+            if span.italic {
+                self.xml.push_str("<w:i/>");
+            }
+            self.xml.push_str("</w:rPr>");
+        }
+        for (i, line) in span.text.split('\n').enumerate() {
+            if i > 0 {
+                self.xml.push_str("<w:br/>");
+            }
+            self.xml.push_str(&format!(
+                r#"<w:t xml:space="preserve">{}</w:t>"#,
+                escape_xml(line)
+            ));
+        }
+        self.xml.push_str("</w:r>");
+
+        if link_id.is_some() {
+            self.xml.push_str("</w:hyperlink>");
+        }
+    }
+
+    fn table(&mut self, header: &[Vec<Span>], rows: &[Vec<Vec<Span>>]) {
+        let columns = rows
+            .iter()
+            .map(Vec::len)
+            .chain([header.len()])
+            .max()
+            .unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        self.xml.push_str(
+            r#"<w:tbl><w:tblPr><w:tblStyle w:val="TableGrid"/><w:tblW w:w="5000" w:type="pct"/></w:tblPr><w:tblGrid>"#,
+        );
+        for _ in 0..columns {
+            self.xml
+                .push_str(&format!(r#"<w:gridCol w:w="{}"/>"#, 9000 / columns));
+        }
+        self.xml.push_str("</w:tblGrid>");
+        if !header.is_empty() {
+            self.table_row(header, columns, Some("TableHeading"));
+        }
+        for row in rows {
+            self.table_row(row, columns, None);
+        }
+        self.xml.push_str("</w:tbl>");
+        // Word exige un paragraphe entre deux tableaux consécutifs
+        self.xml.push_str("<w:p/>");
+    }
+
+    fn table_row(&mut self, cells: &[Vec<Span>], columns: usize, style: Option<&str>) {
+        self.xml.push_str("<w:tr>");
+        if style.is_some() {
+            self.xml.push_str("<w:trPr><w:tblHeader/></w:trPr>");
+        }
+        for index in 0..columns {
+            self.xml.push_str("<w:tc>");
+            let spans = cells.get(index).cloned().unwrap_or_default();
+            self.paragraph(style, None, &spans);
+            self.xml.push_str("</w:tc>");
+        }
+        self.xml.push_str("</w:tr>");
+    }
+
+    fn document_xml(&self) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="{w}" xmlns:r="{r}"><w:body>{body}<w:sectPr><w:headerReference w:type="default" r:id="rIdHeader"/><w:footerReference w:type="default" r:id="rIdFooter"/><w:pgSz w:w="11906" w:h="16838"/><w:pgMar w:top="1418" w:right="1247" w:bottom="1247" w:left="1247" w:header="567" w:footer="567" w:gutter="0"/></w:sectPr></w:body></w:document>"#,
+            w = NS_W,
+            r = NS_R,
+            body = self.xml
+        )
+    }
+
+    fn relationships_xml(&self) -> String {
+        let mut xml = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+        );
+        for (id, kind, target) in [
+            ("rIdStyles", "styles", "styles.xml"),
+            ("rIdNumbering", "numbering", "numbering.xml"),
+            ("rIdHeader", "header", "header1.xml"),
+            ("rIdFooter", "footer", "footer1.xml"),
+        ] {
+            xml.push_str(&format!(
+                r#"<Relationship Id="{}" Type="{}/{}" Target="{}"/>"#,
+                id, REL_BASE, kind, target
+            ));
+        }
+        for (id, url) in &self.links {
+            xml.push_str(&format!(
+                r#"<Relationship Id="{}" Type="{}/hyperlink" Target="{}" TargetMode="External"/>"#,
+                id,
+                REL_BASE,
+                escape_xml(url)
+            ));
+        }
+        xml.push_str("</Relationships>");
+        xml
+    }
+}
+
+/// Définitions de numérotation : puces, listes décimales, clauses et annexes
+fn numbering_xml(article_clauses: bool, lists: &[(usize, bool)]) -> String {
+    let levels = |format: &dyn Fn(usize) -> (&'static str, String)| -> String {
+        (0..9)
+            .map(|level| {
+                let (num_fmt, text) = format(level);
+                format!(
+                    r#"<w:lvl w:ilvl="{l}"><w:start w:val="1"/><w:numFmt w:val="{f}"/><w:lvlText w:val="{t}"/><w:lvlJc w:val="left"/><w:pPr><w:ind w:left="{i}" w:hanging="360"/></w:pPr></w:lvl>"#,
+                    l = level,
+                    f = num_fmt,
+                    t = escape_xml(&text),
+                    i = 720 + level * 360
+                )
+            })
+            .collect()
+    };
+    let clause_text = if article_clauses {
+        "Article %1 —"
+    } else {
+        "%1."
+    };
+
+    let mut xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:numbering xmlns:w="{w}"><w:abstractNum w:abstractNumId="0">{bullets}</w:abstractNum><w:abstractNum w:abstractNumId="1">{decimal}</w:abstractNum><w:abstractNum w:abstractNumId="2"><w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="{clause}"/><w:lvlJc w:val="left"/><w:suff w:val="space"/></w:lvl></w:abstractNum><w:abstractNum w:abstractNumId="3"><w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="upperLetter"/><w:lvlText w:val="Annexe %1 —"/><w:lvlJc w:val="left"/><w:suff w:val="space"/></w:lvl></w:abstractNum><w:num w:numId="{clauses}"><w:abstractNumId w:val="2"/></w:num><w:num w:numId="{annexes}"><w:abstractNumId w:val="3"/></w:num>"#,
+        w = NS_W,
+        bullets = levels(&|level| ("bullet", ["•", "◦", "▪"][level % 3].to_string())),
+        decimal = levels(&|level| ("decimal", format!("%{}.", level + 1))),
+        clause = clause_text,
+        clauses = NUM_CLAUSES,
+        annexes = NUM_ANNEXES,
+    );
+    // Une instance par liste pour que chaque liste numérotée reparte à 1
+    for (list, ordered) in lists {
+        xml.push_str(&format!(
+            r#"<w:num w:numId="{}"><w:abstractNumId w:val="{}"/>{}</w:num>"#,
+            NUM_LISTS_START + list - 1,
+            if *ordered { 1 } else { 0 },
+            if *ordered {
+                r#"<w:lvlOverride w:ilvl="0"><w:startOverride w:val="1"/></w:lvlOverride>"#
+            } else {
+                ""
+            }
+        ));
+    }
+    xml.push_str("</w:numbering>");
+    xml
+}
+
+fn header_xml(document: &Document) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:hdr xmlns:w="{w}"><w:p><w:pPr><w:pStyle w:val="Header"/></w:pPr><w:r><w:t xml:space="preserve">{title}</w:t></w:r><w:r><w:tab/><w:t>{date}</w:t></w:r></w:p></w:hdr>"#,
+        w = NS_W,
+        title = escape_xml(&document.content.title),
+        date = document.metadata.updated_at.format("%d/%m/%Y")
+    )
+}
+
+fn footer_xml(document: &Document) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:ftr xmlns:w="{w}"><w:p><w:pPr><w:pStyle w:val="Footer"/></w:pPr><w:r><w:t xml:space="preserve">Version {version}</w:t></w:r><w:r><w:tab/><w:t xml:space="preserve">Page </w:t></w:r><w:fldSimple w:instr=" PAGE "><w:r><w:t>1</w:t></w:r></w:fldSimple><w:r><w:t xml:space="preserve"> / </w:t></w:r><w:fldSimple w:instr=" NUMPAGES "><w:r><w:t>1</w:t></w:r></w:fldSimple></w:p></w:ftr>"#,
+        w = NS_W,
+        version = escape_xml(&document.metadata.version)
+    )
+}
+
+fn core_properties(document: &Document) -> String {
+    let metadata = &document.metadata;
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><dc:title>{title}</dc:title><dc:subject>{subject}</dc:subject><dc:creator>{author}</dc:creator><cp:keywords>{keywords}</cp:keywords><cp:version>{version}</cp:version><dcterms:created xsi:type="dcterms:W3CDTF">{created}</dcterms:created><dcterms:modified xsi:type="dcterms:W3CDTF">{modified}</dcterms:modified></cp:coreProperties>"#,
+        title = escape_xml(&document.content.title),
+        subject = escape_xml(&metadata.category),
+        author = escape_xml(&metadata.author),
+        keywords = escape_xml(&metadata.tags.join(", ")),
+        version = escape_xml(&metadata.version),
+        created = metadata.created_at.format("%Y-%m-%dT%H:%M:%SZ"),
+        modified = metadata.updated_at.format("%Y-%m-%dT%H:%M:%SZ"),
+    )
+}
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/><Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/><Override PartName="/word/numbering.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml"/><Override PartName="/word/header1.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.header+xml"/><Override PartName="/word/footer1.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.footer+xml"/><Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/></Types>"#;
+
+const ROOT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/></Relationships>"#;
+
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:docDefaults><w:rPrDefault><w:rPr><w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:cs="Calibri"/><w:sz w:val="22"/><w:lang w:val="fr-FR"/></w:rPr></w:rPrDefault><w:pPrDefault><w:pPr><w:spacing w:after="120" w:line="276" w:lineRule="auto"/></w:pPr></w:pPrDefault></w:docDefaults><w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:qFormat/></w:style><w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:next w:val="Subtitle"/><w:qFormat/><w:pPr><w:spacing w:after="60"/></w:pPr><w:rPr><w:b/><w:color w:val="1F2D3D"/><w:sz w:val="48"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Subtitle"><w:name w:val="Subtitle"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:pBdr><w:bottom w:val="single" w:sz="6" w:space="4" w:color="2F6FAD"/></w:pBdr><w:spacing w:after="360"/></w:pPr><w:rPr><w:color w:val="666666"/><w:sz w:val="18"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="360" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:color w:val="1F2D3D"/><w:sz w:val="32"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="240" w:after="80"/><w:outlineLvl w:val="1"/></w:pPr><w:rPr><w:b/><w:color w:val="2F3E4E"/><w:sz w:val="28"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Heading3"><w:name w:val="heading 3"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="200" w:after="60"/><w:outlineLvl w:val="2"/></w:pPr><w:rPr><w:b/><w:color w:val="4B5A69"/><w:sz w:val="24"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Heading4"><w:name w:val="heading 4"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:outlineLvl w:val="3"/></w:pPr><w:rPr><w:b/><w:i/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Heading5"><w:name w:val="heading 5"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:outlineLvl w:val="4"/></w:pPr><w:rPr><w:b/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Heading6"><w:name w:val="heading 6"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:outlineLvl w:val="5"/></w:pPr><w:rPr><w:i/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="ClauseTitle"><w:name w:val="Clause Title"/><w:basedOn w:val="Heading3"/><w:next w:val="Normal"/><w:qFormat/></w:style><w:style w:type="paragraph" w:styleId="AnnexTitle"><w:name w:val="Annex Title"/><w:basedOn w:val="Heading1"/><w:next w:val="Normal"/><w:qFormat/></w:style><w:style w:type="paragraph" w:styleId="Quote"><w:name w:val="Quote"/><w:basedOn w:val="Normal"/><w:qFormat/><w:pPr><w:ind w:left="567"/></w:pPr><w:rPr><w:i/><w:color w:val="555555"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="ListParagraph"><w:name w:val="List Paragraph"/><w:basedOn w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:after="40"/><w:ind w:left="720"/></w:pPr></w:style><w:style w:type="paragraph" w:styleId="Code"><w:name w:val="Code"/><w:basedOn w:val="Normal"/><w:pPr><w:shd w:val="clear" w:color="auto" w:fill="F6F8FA"/><w:spacing w:after="0" w:line="240" w:lineRule="auto"/></w:pPr><w:rPr><w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/><w:sz w:val="18"/></w:rPr></w:style><w:style w:type="character" w:styleId="CodeChar"><w:name w:val="Code Char"/><w:rPr><w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:cs="Consolas"/><w:sz w:val="20"/></w:rPr></w:style><w:style w:type="character" w:styleId="Hyperlink"><w:name w:val="Hyperlink"/><w:rPr><w:color w:val="2F6FAD"/><w:u w:val="single"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Header"><w:name w:val="header"/><w:basedOn w:val="Normal"/><w:pPr><w:tabs><w:tab w:val="right" w:pos="9412"/></w:tabs><w:spacing w:after="0"/></w:pPr><w:rPr><w:color w:val="666666"/><w:sz w:val="16"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Footer"><w:name w:val="footer"/><w:basedOn w:val="Header"/></w:style><w:style w:type="paragraph" w:styleId="TableHeading"><w:name w:val="Table Heading"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="0"/></w:pPr><w:rPr><w:b/></w:rPr></w:style><w:style w:type="table" w:styleId="TableGrid"><w:name w:val="Table Grid"/><w:tblPr><w:tblBorders><w:top w:val="single" w:sz="4" w:color="BBBBBB"/><w:left w:val="single" w:sz="4" w:color="BBBBBB"/><w:bottom w:val="single" w:sz="4" w:color="BBBBBB"/><w:right w:val="single" w:sz="4" w:color="BBBBBB"/><w:insideH w:val="single" w:sz="4" w:color="BBBBBB"/><w:insideV w:val="single" w:sz="4" w:color="BBBBBB"/></w:tblBorders><w:tblCellMar><w:left w:w="100" w:type="dxa"/><w:right w:w="100" w:type="dxa"/></w:tblCellMar></w:tblPr></w:style></w:styles>"#;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Import
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Relit un DOCX en contenu de document.
+///
+/// Les modifications suivies sont lues comme acceptées (insertions conservées,
+/// suppressions ignorées). Les styles sont reconnus par leur nom canonique
+/// (« heading 1 »…) : un fichier enregistré par un Word francophone est lu
+/// comme un fichier anglophone.
+pub fn import_docx(bytes: &[u8]) -> Result<DocumentContent> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(import_error)?;
+    let document_xml = read_part(&mut archive, "word/document.xml")?
+        .ok_or_else(|| DocEngineError::ImportError("word/document.xml absent".to_string()))?;
+    let styles = read_part(&mut archive, "word/styles.xml")?
+        .map(|xml| parse_styles(&xml))
+        .transpose()?
+        .unwrap_or_default();
+    let numbering = read_part(&mut archive, "word/numbering.xml")?
+        .map(|xml| parse_numbering(&xml))
+        .transpose()?
+        .unwrap_or_default();
+    let links = read_part(&mut archive, "word/_rels/document.xml.rels")?
+        .map(|xml| parse_links(&xml))
+        .transpose()?
+        .unwrap_or_default();
+
+    let paragraphs = parse_body(&document_xml, &links)?;
+    Ok(ContentAssembler::new(&styles, &numbering).assemble(paragraphs))
+}
+
+/// Relit un DOCX par-dessus le document d'origine.
+///
+/// Identifiants, formats d'annexes, catégories de clauses et URL de références
+/// sont repris de `base` par position, pour que `VersioningEngine::diff_versions`
+/// compare le document revenu de relecture avec sa version d'origine.
+pub fn import_docx_into(bytes: &[u8], base: &Document) -> Result<Document> {
+    let mut content = import_docx(bytes)?;
+    reconcile_sections(&mut content.sections, &base.content.sections);
+
+    if let (Some(clauses), Some(base_clauses)) = (
+        content.mandatory_clauses.as_mut(),
+        base.content.mandatory_clauses.as_ref(),
+    ) {
+        for (clause, original) in clauses.iter_mut().zip(base_clauses) {
+            clause.id = original.id.clone();
+            clause.mandatory = original.mandatory;
+            clause.category = original.category.clone();
+        }
+    }
+    for (annex, original) in content.annexes.iter_mut().zip(&base.content.annexes) {
+        annex.id = original.id.clone();
+        annex.format = original.format.clone();
+    }
+    for (reference, original) in content.references.iter_mut().zip(&base.content.references) {
+        if reference.url.is_none() {
+            reference.url = original.url.clone();
+        }
+    }
+
+    let mut document = base.clone();
+    document.content = content;
+    document.metadata.title = document.content.title.clone();
+    document.metadata.updated_at = chrono::Utc::now();
+    Ok(document)
+}
+
+/// Sections appariées par titre, puis par position pour les titres modifiés
+fn reconcile_sections(sections: &mut [Section], originals: &[Section]) {
+    let mut used = vec![false; originals.len()];
+    let mut matches: Vec<Option<usize>> = sections
+        .iter()
+        .map(|section| {
+            let found =
+                (0..originals.len()).find(|&i| !used[i] && originals[i].title == section.title)?;
+            used[found] = true;
+            Some(found)
+        })
+        .collect();
+    for (index, matched) in matches.iter_mut().enumerate() {
+        if matched.is_none() && index < originals.len() && !used[index] {
+            used[index] = true;
+            *matched = Some(index);
+        }
+    }
+
+    for (section, matched) in sections.iter_mut().zip(matches) {
+        if let Some(original) = matched.map(|i| &originals[i]) {
+            section.id = original.id.clone();
+            reconcile_sections(&mut section.subsections, &original.subsections);
+        }
+    }
+}
+
+fn import_error(e: impl std::fmt::Display) -> DocEngineError {
+    DocEngineError::ImportError(format!("DOCX illisible: {}", e))
+}
+
+fn read_part(archive: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Option<String>> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(import_error(e)),
+    };
+    let mut xml = String::new();
+    file.read_to_string(&mut xml).map_err(import_error)?;
+    Ok(Some(xml))
+}
+
+/// Valeur d'un attribut, préfixe d'espace de noms ignoré
+fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == name)
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|value| value.into_owned())
+}
+
+/// Identifiant de style → nom canonique en minuscules
+fn parse_styles(xml: &str) -> Result<HashMap<String, String>> {
+    let mut reader = Reader::from_str(xml);
+    let mut styles = HashMap::new();
+    let mut current: Option<String> = None;
+    loop {
+        match reader.read_event().map_err(import_error)? {
+            Event::Start(e) if e.local_name().as_ref() == b"style" => {
+                current = attribute(&e, b"styleId")
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"name" => {
+                if let (Some(id), Some(name)) = (current.as_ref(), attribute(&e, b"val")) {
+                    styles.insert(id.clone(), name.to_lowercase());
+                }
+            }
+            Event::End(e) if e.local_name().as_ref() == b"style" => current = None,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(styles)
+}
+
+/// numId → (niveau → liste ordonnée)
+fn parse_numbering(xml: &str) -> Result<HashMap<String, Vec<bool>>> {
+    let mut reader = Reader::from_str(xml);
+    let mut abstracts: HashMap<String, Vec<bool>> = HashMap::new();
+    let mut instances: HashMap<String, String> = HashMap::new();
+    let mut current_abstract: Option<String> = None;
+    let mut current_num: Option<String> = None;
+    loop {
+        match reader.read_event().map_err(import_error)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"abstractNum" => current_abstract = attribute(&e, b"abstractNumId"),
+                b"num" => current_num = attribute(&e, b"numId"),
+                _ => {}
+            },
+            Event::Empty(e) => match e.local_name().as_ref() {
+                b"numFmt" => {
+                    if let Some(id) = current_abstract.as_ref() {
+                        let ordered =
+                            attribute(&e, b"val").is_some_and(|f| f != "bullet" && f != "none");
+                        abstracts.entry(id.clone()).or_default().push(ordered);
+                    }
+                }
+                b"abstractNumId" => {
+                    if let (Some(num), Some(id)) = (current_num.as_ref(), attribute(&e, b"val")) {
+                        instances.insert(num.clone(), id);
+                    }
+                }
+                _ => {}
+            },
+            Event::End(e) => match e.local_name().as_ref() {
+                b"abstractNum" => current_abstract = None,
+                b"num" => current_num = None,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(instances
+        .into_iter()
+        .filter_map(|(num, id)| abstracts.get(&id).map(|levels| (num, levels.clone())))
+        .collect())
+}
+
+/// rId → URL des liens externes
+fn parse_links(xml: &str) -> Result<HashMap<String, String>> {
+    let mut reader = Reader::from_str(xml);
+    let mut links = HashMap::new();
+    loop {
+        match reader.read_event().map_err(import_error)? {
+            Event::Empty(e) | Event::Start(e) if e.local_name().as_ref() == b"Relationship" => {
+                let is_link = attribute(&e, b"Type").is_some_and(|t| t.ends_with("/hyperlink"));
+                if let (true, Some(id), Some(target)) =
+                    (is_link, attribute(&e, b"Id"), attribute(&e, b"Target"))
+                {
+                    links.insert(id, target);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(links)
+}
+
+/// Paragraphe ou tableau lu dans le corps du document
+#[derive(Debug, Default)]
+struct ImportedParagraph {
+    style: Option<String>,
+    numbering: Option<(String, usize)>,
+    spans: Vec<Span>,
+    /// Tableau : lignes → cellules → texte Markdown
+    table: Option<Vec<Vec<String>>>,
+}
+
+impl ImportedParagraph {
+    fn text(&self) -> String {
+        self.spans
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+
+    /// Texte du paragraphe en Markdown (gras, italique, code, liens)
+    fn markdown(&self) -> String {
+        let mut out = String::new();
+        for span in &self.spans {
+            let core = span.text.trim();
+            if core.is_empty() {
+                out.push_str(&span.text);
+                continue;
+            }
+            let mut text = if span.code {
+                format!("`{}`", core)
+            } else {
+                core.to_string()
+            };
+            if span.italic {
+                text = format!("*{}*", text);
+            }
+            if span.bold {
+                text = format!("**{}**", text);
+            }
+            if let Some(url) = &span.link {
+                text = format!("[{}]({})", text, url);
+            }
+            let leading = &span.text[..span.text.len() - span.text.trim_start().len()];
+            let trailing = &span.text[span.text.trim_end().len()..];
+            out.push_str(leading);
+            out.push_str(&text);
+            out.push_str(trailing);
+        }
+        out.trim().to_string()
+    }
+}
+
+/// Tableau en cours de lecture : lignes terminées, ligne et cellule en cours
+#[derive(Default)]
+struct TableState {
+    rows: Vec<Vec<String>>,
+    row: Vec<String>,
+    cell: Vec<String>,
+}
+
+#[derive(Default)]
+struct RunState {
+    bold: bool,
+    italic: bool,
+    code: bool,
+}
+
+/// Lit les paragraphes (et tableaux) de `word/document.xml`
+fn parse_body(xml: &str, links: &HashMap<String, String>) -> Result<Vec<ImportedParagraph>> {
+    let mut reader = Reader::from_str(xml);
+    let mut paragraphs = Vec::new();
+    let mut paragraph: Option<ImportedParagraph> = None;
+    let mut run: Option<RunState> = None;
+    let mut in_run_properties = false;
+    let mut in_text = false;
+    let mut link: Option<String> = None;
+    let mut numbering_id: Option<String> = None;
+    let mut numbering_level = 0usize;
+    // Suppressions suivies : texte ignoré
+    let mut deleted = 0usize;
+    // Anciennes propriétés d'une modification de mise en forme suivie : ignorées
+    let mut property_change = 0usize;
+    // Tableaux ouverts (imbrication possible)
+    let mut tables: Vec<TableState> = Vec::new();
+
+    let push_text = |paragraph: &mut Option<ImportedParagraph>,
+                     run: &Option<RunState>,
+                     link: &Option<String>,
+                     text: &str| {
+        let (Some(paragraph), Some(run)) = (paragraph.as_mut(), run.as_ref()) else {
+            return;
+        };
+        let span = Span {
+            text: text.to_string(),
+            bold: run.bold,
+            italic: run.italic,
+            code: run.code,
+            link: link.clone(),
+        };
+        match paragraph.spans.last_mut() {
+            Some(last)
+                if last.bold == span.bold
+                    && last.italic == span.italic
+                    && last.code == span.code
+                    && last.link == span.link =>
+            {
+                last.text.push_str(text)
+            }
+            _ => paragraph.spans.push(span),
+        }
+    };
+
+    loop {
+        let event = reader.read_event().map_err(import_error)?;
+        let empty = matches!(event, Event::Empty(_));
+        match event {
+            Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                b"p" if !empty => paragraph = Some(ImportedParagraph::default()),
+                b"rPrChange" | b"pPrChange" if !empty => property_change += 1,
+                _ if property_change > 0 => {}
+                b"pStyle" => {
+                    if let Some(paragraph) = paragraph.as_mut() {
+                        paragraph.style = attribute(&e, b"val");
+                    }
+                }
+                b"ilvl" => {
+                    numbering_level = attribute(&e, b"val")
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(0)
+                }
+                b"numId" => numbering_id = attribute(&e, b"val"),
+                b"r" if !empty => run = Some(RunState::default()),
+                b"rPr" if !empty && run.is_some() => in_run_properties = true,
+                b"b" if in_run_properties => {
+                    if let Some(run) = run.as_mut() {
+                        run.bold = is_on(&e);
+                    }
+                }
+                b"i" if in_run_properties => {
+                    if let Some(run) = run.as_mut() {
+                        run.italic = is_on(&e);
+                    }
+                }
+                b"rStyle" if in_run_properties => {
+                    if let Some(run) = run.as_mut() {
+                        run.code = attribute(&e, b"val")
+                            .is_some_and(|s| s.to_lowercase().contains("code"));
+                    }
+                }
+                b"t" if !empty && deleted == 0 => in_text = true,
+                b"tab" if run.is_some() && deleted == 0 => {
+                    push_text(&mut paragraph, &run, &link, "\t")
+                }
+                // Les sauts de page et de colonne ne sont pas du texte
+                b"br"
+                    if run.is_some()
+                        && deleted == 0
+                        && attribute(&e, b"type").map_or(true, |t| t == "textWrapping") =>
+                {
+                    push_text(&mut paragraph, &run, &link, "\n")
+                }
+                b"hyperlink" if !empty => {
+                    link = attribute(&e, b"id").and_then(|id| links.get(&id).cloned())
+                }
+                b"del" | b"moveFrom" if !empty => deleted += 1,
+                b"tbl" if !empty => tables.push(TableState::default()),
+                _ => {}
+            },
+            Event::Text(e) if in_text => {
+                let text = e.unescape().map_err(import_error)?;
+                push_text(&mut paragraph, &run, &link, &text);
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"rPrChange" | b"pPrChange" => property_change = property_change.saturating_sub(1),
+                _ if property_change > 0 => {}
+                b"rPr" => in_run_properties = false,
+                b"r" => run = None,
+                b"hyperlink" => link = None,
+                b"del" | b"moveFrom" => deleted = deleted.saturating_sub(1),
+                b"p" => {
+                    let Some(mut finished) = paragraph.take() else {
+                        continue;
+                    };
+                    if let Some(id) = numbering_id.take().filter(|id| id != "0") {
+                        finished.numbering = Some((id, numbering_level));
+                    }
+                    numbering_level = 0;
+                    match tables.last_mut() {
+                        Some(table) => table.cell.push(finished.markdown()),
+                        None => paragraphs.push(finished),
+                    }
+                }
+                b"tc" => {
+                    if let Some(table) = tables.last_mut() {
+                        let cell = std::mem::take(&mut table.cell)
+                            .join(" ")
+                            .replace('|', "\\|");
+                        table.row.push(cell);
+                    }
+                }
+                b"tr" => {
+                    if let Some(table) = tables.last_mut() {
+                        let row = std::mem::take(&mut table.row);
+                        table.rows.push(row);
+                    }
+                }
+                b"tbl" => {
+                    if let Some(finished) = tables.pop() {
+                        match tables.last_mut() {
+                            // Tableau imbriqué : aplati dans la cellule parente
+                            Some(parent) => parent.cell.push(table_markdown(&finished.rows)),
+                            None => paragraphs.push(ImportedParagraph {
+                                table: Some(finished.rows),
+                                ..ImportedParagraph::default()
+                            }),
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(paragraphs)
+}
+
+/// `<w:b/>` actif sauf `w:val="0"` / `"false"`
+fn is_on(element: &BytesStart) -> bool {
+    attribute(element, b"val").map_or(true, |v| v != "0" && v != "false")
+}
+
+fn table_markdown(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return String::new();
+    }
+    let line = |cells: &[String]| {
+        let mut cells = cells.to_vec();
+        cells.resize(columns, String::new());
+        format!("| {} |", cells.join(" | "))
+    };
+    let mut lines = vec![line(&rows[0]), format!("|{}", " --- |".repeat(columns))];
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Reconstruction du contenu
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Role {
+    Title,
+    Subtitle,
+    Heading(usize),
+    ClauseTitle,
+    AnnexTitle,
+    Code,
+    Body,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    Preamble,
+    Summary,
+    Objectives,
+    /// Chemin d'indices dans l'arbre des sections
+    Section(Vec<usize>),
+    Clauses,
+    Annexes,
+    References,
+}
+
+struct ContentAssembler<'a> {
+    styles: &'a HashMap<String, String>,
+    numbering: &'a HashMap<String, Vec<bool>>,
+    content: DocumentContent,
+    target: Target,
+    /// Blocs Markdown de la cible courante ; `true` pour un élément de liste
+    buffer: Vec<(String, bool)>,
+    code: Vec<String>,
+}
+
+impl<'a> ContentAssembler<'a> {
+    fn new(styles: &'a HashMap<String, String>, numbering: &'a HashMap<String, Vec<bool>>) -> Self {
+        Self {
+            styles,
+            numbering,
+            content: DocumentContent {
+                title: String::new(),
+                executive_summary: String::new(),
+                objectives: Vec::new(),
+                sections: Vec::new(),
+                mandatory_clauses: None,
+                annexes: Vec::new(),
+                references: Vec::new(),
+            },
+            target: Target::Preamble,
+            buffer: Vec::new(),
+            code: Vec::new(),
+        }
+    }
+
+    fn role(&self, paragraph: &ImportedParagraph) -> Role {
+        let Some(style) = paragraph.style.as_ref() else {
+            return Role::Body;
+        };
+        let name = self
+            .styles
+            .get(style)
+            .cloned()
+            .unwrap_or_else(|| style.to_lowercase());
+        let compact = name.replace(' ', "");
+        if compact == "title" {
+            Role::Title
+        } else if compact == "subtitle" {
+            Role::Subtitle
+        } else if compact == "clausetitle" {
+            Role::ClauseTitle
+        } else if compact == "annextitle" {
+            Role::AnnexTitle
+        } else if compact == "code" {
+            Role::Code
+        } else if let Some(level) = compact.strip_prefix("heading").and_then(|l| l.parse().ok()) {
+            Role::Heading(level)
+        } else {
+            Role::Body
+        }
+    }
+
+    fn assemble(mut self, paragraphs: Vec<ImportedParagraph>) -> DocumentContent {
+        for paragraph in &paragraphs {
+            if let Some(rows) = &paragraph.table {
+                self.push_block(table_markdown(rows), false);
+                continue;
+            }
+            let role = self.role(paragraph);
+            if role != Role::Code {
+                self.flush_code();
+            }
+            match role {
+                Role::Title => self.content.title = paragraph.text(),
+                Role::Subtitle => {}
+                Role::Heading(level) => self.heading(level.max(1), paragraph.text()),
+                Role::ClauseTitle => {
+                    self.switch(Target::Clauses);
+                    let clauses = self.content.mandatory_clauses.get_or_insert_with(Vec::new);
+                    clauses.push(Clause {
+                        id: format!("clause_{}", clauses.len() + 1),
+                        title: paragraph.text(),
+                        content: String::new(),
+                        mandatory: true,
+                        category: ClauseCategory::General,
+                    });
+                }
+                Role::AnnexTitle => {
+                    self.switch(Target::Annexes);
+                    self.content.annexes.push(Annex {
+                        id: format!("annex_{}", self.content.annexes.len() + 1),
+                        title: paragraph.text(),
+                        content: String::new(),
+                        format: "markdown".to_string(),
+                    });
+                }
+                Role::Code => self
+                    .code
+                    .push(paragraph.spans.iter().map(|s| s.text.as_str()).collect()),
+                Role::Body => self.body(paragraph),
+            }
+        }
+        self.flush_code();
+        self.switch(Target::Preamble);
+        self.content
+    }
+
+    fn heading(&mut self, level: usize, text: String) {
+        if level == 1 {
+            let fixed = match text.as_str() {
+                office::SUMMARY_HEADING => Some(Target::Summary),
+                office::OBJECTIVES_HEADING => Some(Target::Objectives),
+                office::CLAUSES_HEADING => Some(Target::Clauses),
+                office::REFERENCES_HEADING => Some(Target::References),
+                _ => None,
+            };
+            if let Some(target) = fixed {
+                self.switch(target);
+                return;
+            }
+        }
+
+        // Titre dans une clause ou une annexe : conservé dans son contenu
+        if matches!(self.target, Target::Clauses | Target::Annexes) && level > 1 {
+            self.push_block(format!("{} {}", "#".repeat(level.min(6)), text), false);
+            return;
+        }
+
+        let mut path = match &self.target {
+            Target::Section(path) => path.clone(),
+            _ => Vec::new(),
+        };
+        path.truncate(level - 1);
+        let siblings = match section_at(&mut self.content.sections, &path) {
+            Some(parent) => &mut parent.subsections,
+            None => &mut self.content.sections,
+        };
+        path.push(siblings.len());
+        siblings.push(Section {
+            id: format!(
+                "section_{}",
+                path.iter()
+                    .map(|i| (i + 1).to_string())
+                    .collect::<Vec<_>>()
+                    .join("_")
+            ),
+            title: text,
+            content: String::new(),
+            subsections: Vec::new(),
+            level: path.len() as u8,
+        });
+        self.switch(Target::Section(path));
+    }
+
+    fn body(&mut self, paragraph: &ImportedParagraph) {
+        let text = paragraph.markdown();
+        if text.is_empty() {
+            return;
+        }
+        let Some((num_id, level)) = paragraph.numbering.as_ref() else {
+            self.push_block(text, false);
+            return;
+        };
+        match self.target {
+            Target::Objectives if *level == 0 => self.content.objectives.push(text),
+            Target::References if *level == 0 => {
+                self.content.references.push(parse_reference(paragraph))
+            }
+            _ => {
+                let ordered = self
+                    .numbering
+                    .get(num_id)
+                    .and_then(|levels| levels.get(*level).copied())
+                    .unwrap_or(false);
+                let marker = if ordered { "1." } else { "-" };
+                self.push_block(format!("{}{} {}", "   ".repeat(*level), marker, text), true);
+            }
+        }
+    }
+
+    fn push_block(&mut self, text: String, list_item: bool) {
+        self.buffer.push((text, list_item));
+    }
+
+    fn flush_code(&mut self) {
+        if !self.code.is_empty() {
+            let code = std::mem::take(&mut self.code).join("\n");
+            self.push_block(format!("```\n{}\n```", code), false);
+        }
+    }
+
+    /// Vide le tampon dans la cible courante puis change de cible
+    fn switch(&mut self, target: Target) {
+        self.flush_code();
+        let mut markdown = String::new();
+        let mut previous_list = false;
+        for (text, list_item) in std::mem::take(&mut self.buffer) {
+            if !markdown.is_empty() {
+                markdown.push_str(if list_item && previous_list {
+                    "\n"
+                } else {
+                    "\n\n"
+                });
+            }
+            markdown.push_str(&text);
+            previous_list = list_item;
+        }
+
+        if !markdown.is_empty() {
+            let slot = match &self.target {
+                Target::Preamble | Target::Summary => Some(&mut self.content.executive_summary),
+                Target::Section(path) => {
+                    section_at(&mut self.content.sections, path).map(|s| &mut s.content)
+                }
+                Target::Clauses => self
+                    .content
+                    .mandatory_clauses
+                    .as_mut()
+                    .and_then(|clauses| clauses.last_mut())
+                    .map(|c| &mut c.content),
+                Target::Annexes => self.content.annexes.last_mut().map(|a| &mut a.content),
+                Target::Objectives | Target::References => {
+                    Some(&mut self.content.executive_summary)
+                }
+            };
+            if let Some(slot) = slot {
+                if !slot.is_empty() {
+                    slot.push_str("\n\n");
+                }
+                slot.push_str(&markdown);
+            }
+        }
+
+        // Annexe uniquement composée de code : contenu brut
+        if let Target::Annexes = self.target {
+            if let Some(annex) = self.content.annexes.last_mut() {
+                if let Some(raw) = annex
+                    .content
+                    .strip_prefix("```\n")
+                    .and_then(|c| c.strip_suffix("\n```"))
+                {
+                    if !raw.contains("\n```") {
+                        annex.content = raw.to_string();
+                        annex.format = "code".to_string();
+                    }
+                }
+            }
+        }
+        self.target = target;
+    }
+}
+
+fn section_at<'s>(sections: &'s mut [Section], path: &[usize]) -> Option<&'s mut Section> {
+    let (first, rest) = path.split_first()?;
+    let section = sections.get_mut(*first)?;
+    if rest.is_empty() {
+        Some(section)
+    } else {
+        section_at(&mut section.subsections, rest)
+    }
+}
+
+/// « titre — source (date) », lien éventuel sur le titre
+fn parse_reference(paragraph: &ImportedParagraph) -> Reference {
+    let text = paragraph.text();
+    let (title, rest) = text.split_once(" — ").unwrap_or((text.as_str(), ""));
+    let (source, date) = match rest.strip_suffix(')').and_then(|r| r.rsplit_once(" (")) {
+        Some((source, date)) => (source.to_string(), Some(date.to_string())),
+        None => (rest.to_string(), None),
+    };
+    Reference {
+        title: title.trim().to_string(),
+        source: source.trim().to_string(),
+        url: paragraph.spans.iter().find_map(|s| s.link.clone()),
+        date,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc_engine::versioning::diff_documents;
+
+    fn body(paragraphs: &str) -> String {
+        format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+            paragraphs
+        )
+    }
+
+    #[test]
+    fn test_render_then_import_round_trips_without_diff() {
+        let mut document = test_document(DocumentType::Contract, DocumentStyle::Legal);
+        document.content.mandatory_clauses = Some(vec![Clause {
+            id: "confidentialite".to_string(),
+            title: "Confidentialité".to_string(),
+            content: "Les parties gardent **secrètes** les informations échangées.".to_string(),
+            mandatory: true,
+            category: ClauseCategory::Confidentiality,
+        }]);
+        document.content.annexes.push(Annex {
+            id: "tarifs".to_string(),
+            title: "Tarifs".to_string(),
+            content: "Tarif horaire : *100 €*.".to_string(),
+            format: "markdown".to_string(),
+        });
+
+        let bytes = render_docx(&document).unwrap();
+        let imported = import_docx_into(&bytes, &document).unwrap();
+        let diff = diff_documents(&document, &imported);
+        assert!(diff.is_empty(), "{:#?}", diff);
+    }
+
+    #[test]
+    fn test_tracked_deletions_are_dropped() {
+        let xml = body(concat!(
+            "<w:p><w:r><w:t xml:space=\"preserve\">Prix : </w:t></w:r>",
+            "<w:del w:id=\"1\" w:author=\"A\"><w:r><w:delText>100</w:delText></w:r><w:r><w:t>ancien</w:t></w:r></w:del>",
+            "<w:ins w:id=\"2\" w:author=\"A\"><w:r><w:t>120</w:t></w:r></w:ins>",
+            "<w:r><w:t xml:space=\"preserve\"> €</w:t></w:r></w:p>",
+            "<w:p><w:moveFrom w:id=\"3\"><w:r><w:t>déplacé</w:t></w:r></w:moveFrom></w:p>",
+        ));
+        let paragraphs = parse_body(&xml, &HashMap::new()).unwrap();
+        assert_eq!(paragraphs[0].text(), "Prix : 120 €");
+        assert_eq!(paragraphs[1].text(), "");
+    }
+
+    #[test]
+    fn test_previous_formatting_of_tracked_change_does_not_leak() {
+        // Texte passé de gras+italique à normal : seul le nouveau rPr compte
+        let xml = body(concat!(
+            "<w:p><w:pPr><w:pStyle w:val=\"Normal\"/><w:pPrChange w:id=\"1\"><w:pPr><w:pStyle w:val=\"Heading1\"/></w:pPr></w:pPrChange></w:pPr>",
+            "<w:r><w:rPr><w:rPrChange w:id=\"2\"><w:rPr><w:b/><w:i/></w:rPr></w:rPrChange></w:rPr><w:t>normal</w:t></w:r>",
+            "<w:r><w:rPr><w:b/><w:rPrChange w:id=\"3\"><w:rPr><w:i/></w:rPr></w:rPrChange></w:rPr><w:t>gras</w:t></w:r></w:p>",
+        ));
+        let paragraphs = parse_body(&xml, &HashMap::new()).unwrap();
+        assert_eq!(paragraphs[0].style.as_deref(), Some("Normal"));
+        assert_eq!(paragraphs[0].markdown(), "normal**gras**");
+        assert!(paragraphs[0].spans.iter().all(|span| !span.italic));
+    }
+}
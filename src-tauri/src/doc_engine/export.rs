@@ -21,6 +21,8 @@ impl ExportEngine {
             ExportFormat::Text => self.export_text(document).await,
             ExportFormat::Json => self.export_json(document).await,
             ExportFormat::Pdf => self.export_pdf(document).await,
+            ExportFormat::Docx => self.export_docx(document).await,
+            ExportFormat::Odt => self.export_odt(document).await,
         }
     }
     
@@ -123,7 +125,7 @@ impl ExportEngine {
     }
     
     async fn export_html(&self, document: &Document) -> Result<ExportResult> {
-        let content = self.generate_html(document)?;
+        let content = html::render_document(document)?;
         let filename = format!("{}.html", self.sanitize_filename(&document.metadata.title));
         let path = Path::new(&self.output_dir).join(&filename);
         
@@ -138,53 +140,6 @@ impl ExportEngine {
         })
     }
     
-    fn generate_html(&self, document: &Document) -> Result<String> {
-        let mut html = String::from("<!DOCTYPE html>\n<html lang=\"fr\">\n<head>\n");
-        html.push_str(&format!("    <meta charset=\"UTF-8\">\n"));
-        html.push_str(&format!("    <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">\n"));
-        html.push_str(&format!("    <title>{}</title>\n", document.content.title));
-        html.push_str(r#"    <style>
-        body { font-family: 'Segoe UI', Tahoma, Geneva, Verdana, sans-serif; line-height: 1.6; max-width: 900px; margin: 0 auto; padding: 20px; }
-        h1 { color: #2c3e50; border-bottom: 3px solid #3498db; padding-bottom: 10px; }
-        h2 { color: #34495e; margin-top: 30px; }
-        h3 { color: #7f8c8d; }
-        .metadata { background: #ecf0f1; padding: 15px; border-radius: 5px; margin-bottom: 20px; }
-        .section { margin: 20px 0; }
-        .clause { background: #fff3cd; padding: 15px; border-left: 4px solid #ffc107; margin: 10px 0; }
-        ul { list-style-type: none; padding-left: 0; }
-        li:before { content: "▸ "; color: #3498db; font-weight: bold; }
-    </style>
-</head>
-<body>
-"#);
-        
-        // Contenu
-        html.push_str(&format!("    <h1>{}</h1>\n", document.content.title));
-        html.push_str(&format!("    <div class=\"metadata\">\n"));
-        html.push_str(&format!("        <strong>Version:</strong> {} | <strong>Date:</strong> {}\n", 
-            document.metadata.version,
-            document.metadata.created_at.format("%Y-%m-%d")
-        ));
-        html.push_str("    </div>\n");
-        
-        html.push_str(&format!("    <h2>Résumé Exécutif</h2>\n    <p>{}</p>\n", document.content.executive_summary));
-        
-        // Sections
-        for section in &document.content.sections {
-            html.push_str(&self.format_section_html(section));
-        }
-        
-        html.push_str("</body>\n</html>");
-        
-        Ok(html)
-    }
-    
-    fn format_section_html(&self, section: &Section) -> String {
-        let heading_level = (section.level + 1).min(6);
-        format!("    <div class=\"section\">\n        <h{}>{}</h{}>\n        <p>{}</p>\n    </div>\n",
-            heading_level, section.title, heading_level, section.content)
-    }
-    
     async fn export_text(&self, document: &Document) -> Result<ExportResult> {
         let content = self.generate_text(document)?;
         let filename = format!("{}.txt", self.sanitize_filename(&document.metadata.title));
@@ -248,9 +203,55 @@ impl ExportEngine {
         })
     }
     
-    async fn export_pdf(&self, _document: &Document) -> Result<ExportResult> {
-        // TODO: Implémentation PDF (nécessite une bibliothèque comme printpdf ou wkhtmltopdf)
-        Err(DocEngineError::ExportError("Export PDF non encore implémenté".to_string()))
+    async fn export_pdf(&self, document: &Document) -> Result<ExportResult> {
+        let bytes = pdf::render_pdf(document)?;
+        
+        let filename = format!("{}.pdf", self.sanitize_filename(&document.metadata.title));
+        let path = Path::new(&self.output_dir).join(&filename);
+        
+        fs::write(&path, bytes)
+            .map_err(|e| DocEngineError::ExportError(format!("Erreur d'écriture PDF: {}", e)))?;
+        
+        Ok(ExportResult {
+            format: ExportFormat::Pdf,
+            path: path.to_string_lossy().to_string(),
+            size: path.metadata().map(|m| m.len()).unwrap_or(0),
+            success: true,
+        })
+    }
+    
+    async fn export_docx(&self, document: &Document) -> Result<ExportResult> {
+        let bytes = docx::render_docx(document)?;
+        
+        let filename = format!("{}.docx", self.sanitize_filename(&document.metadata.title));
+        let path = Path::new(&self.output_dir).join(&filename);
+        
+        fs::write(&path, bytes)
+            .map_err(|e| DocEngineError::ExportError(format!("Erreur d'écriture DOCX: {}", e)))?;
+        
+        Ok(ExportResult {
+            format: ExportFormat::Docx,
+            path: path.to_string_lossy().to_string(),
+            size: path.metadata().map(|m| m.len()).unwrap_or(0),
+            success: true,
+        })
+    }
+    
+    async fn export_odt(&self, document: &Document) -> Result<ExportResult> {
+        let bytes = odt::render_odt(document)?;
+        
+        let filename = format!("{}.odt", self.sanitize_filename(&document.metadata.title));
+        let path = Path::new(&self.output_dir).join(&filename);
+        
+        fs::write(&path, bytes)
+            .map_err(|e| DocEngineError::ExportError(format!("Erreur d'écriture ODT: {}", e)))?;
+        
+        Ok(ExportResult {
+            format: ExportFormat::Odt,
+            path: path.to_string_lossy().to_string(),
+            size: path.metadata().map(|m| m.len()).unwrap_or(0),
+            success: true,
+        })
     }
     
     fn sanitize_filename(&self, title: &str) -> String {
@@ -7,6 +7,8 @@ pub struct DocumentGenerator {
     templates: templates::TemplateEngine,
     validator: validator::DocumentValidator,
     formatter: formatter::DocumentFormatter,
+    /// Passe IA optionnelle ; `None` = génération déterministe (défaut)
+    assistant: Option<assist::DocumentAssistant>,
 }
 
 impl DocumentGenerator {
@@ -15,22 +17,64 @@ impl DocumentGenerator {
             templates: templates::TemplateEngine::new(),
             validator: validator::DocumentValidator::new(),
             formatter: formatter::DocumentFormatter::new(),
+            assistant: None,
         }
     }
     
+    /// Générateur utilisant un moteur de templates déjà configuré
+    /// (par exemple `TemplateEngine::with_directory`)
+    pub fn with_templates(templates: templates::TemplateEngine) -> Self {
+        Self {
+            templates,
+            ..Self::new()
+        }
+    }
+    
+    /// Remplace le validateur (par exemple pour charger d'autres packs juridiques)
+    pub fn with_validator(mut self, validator: validator::DocumentValidator) -> Self {
+        self.validator = validator;
+        self
+    }
+    
+    /// Active l'assistance IA (rédaction, réécriture, relecture)
+    pub fn with_assistant(mut self, assistant: assist::DocumentAssistant) -> Self {
+        self.assistant = Some(assistant);
+        self
+    }
+    
+    pub fn templates(&self) -> &templates::TemplateEngine {
+        &self.templates
+    }
+    
+    pub fn templates_mut(&mut self) -> &mut templates::TemplateEngine {
+        &mut self.templates
+    }
+    
     /// Génère un document complet selon la configuration
     pub async fn generate(&self, config: GenerationConfig, params: HashMap<String, String>) -> Result<Document> {
-        // 1. Sélection du template approprié
-        let template = self.templates.get_template(&config.doc_type)?;
+        // 1. Sélection du template : `params["template"]` sinon par type
+        let template = match params.get("template") {
+            Some(id) => self.templates.get_template_by_id(id)?,
+            None => self.templates.get_template(&config.doc_type)?,
+        };
         
         // 2. Génération du contenu structuré
-        let content = self.generate_content(&config, &template, params)?;
+        let mut content = self.generate_content(&config, template, params.clone())?;
+        
+        // 2 bis. Rédaction / réécriture assistées (optionnel)
+        let mut ai_contributions = Vec::new();
+        let mut ai_warnings = Vec::new();
+        if let Some(assistant) = &self.assistant {
+            let (contributions, failures) = assistant.compose(&mut content, &config, &params).await;
+            ai_contributions = contributions;
+            ai_warnings = failures;
+        }
         
         // 3. Application du style et formatage
         let formatted_content = self.formatter.format(content, &config)?;
         
         // 4. Validation complète
-        let validation_status = self.validator.validate(&formatted_content, &config)?;
+        let mut validation_status = self.validator.validate(&formatted_content, &config)?;
         
         // 5. Correction automatique si nécessaire
         let final_content = if !validation_status.is_valid {
@@ -39,8 +83,25 @@ impl DocumentGenerator {
             formatted_content
         };
         
+        // 5 bis. Relecture IA : constats ajoutés au statut de validation
+        if let Some(assistant) = self.assistant.as_ref().filter(|a| a.options().review) {
+            match assistant.review(&final_content, &config).await {
+                Ok((outcome, contribution)) => {
+                    validation_status.warnings.extend(outcome.warnings);
+                    validation_status.suggestions.extend(outcome.suggestions);
+                    ai_contributions.push(contribution);
+                }
+                Err(e) => ai_warnings.push(ValidationWarning {
+                    message: format!("Relecture IA indisponible : {}", e),
+                    suggestion: None,
+                    target: None,
+                }),
+            }
+        }
+        validation_status.warnings.extend(ai_warnings);
+        
         // 6. Création du document final
-        let metadata = DocumentMetadata {
+        let mut metadata = DocumentMetadata {
             id: Uuid::new_v4().to_string(),
             title: final_content.title.clone(),
             version: "1.0.0".to_string(),
@@ -49,7 +110,11 @@ impl DocumentGenerator {
             author: "TITANE∞ v13".to_string(),
             tags: self.extract_tags(&config),
             category: self.get_category(&config.doc_type),
+            ai_contributions,
         };
+        if !metadata.ai_contributions.is_empty() {
+            metadata.tags.push("ai-assisted".to_string());
+        }
         
         Ok(Document {
             metadata,
@@ -66,6 +131,11 @@ impl DocumentGenerator {
         template: &templates::Template,
         params: HashMap<String, String>
     ) -> Result<DocumentContent> {
+        // Template fichier : le contenu vient du rendu de sa source
+        if template.source.is_some() {
+            return self.templates.render(template, &params);
+        }
+        
         match config.doc_type {
             DocumentType::Contract | DocumentType::NDA => {
                 legal::generate_legal_content(config, template, params)
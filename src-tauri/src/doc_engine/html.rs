@@ -1,9 +1,571 @@
-// Placeholder pour HTML generator (intégration future)
+// TITANE∞ v13 - Rendu HTML
+// Markdown → HTML assaini et export HTML autonome (thèmes CSS embarqués, hors ligne)
 
 use super::*;
+use pulldown_cmark::{html as cmark_html, CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
 
-pub fn generate_html_from_markdown(_markdown: &str) -> Result<String> {
-    // TODO: Implémenter la conversion Markdown -> HTML
-    // Utiliser une lib comme pulldown-cmark ou comrak
-    Ok(String::from("<html><body>HTML generation à implémenter</body></html>"))
+/// Convertit du Markdown en fragment HTML assaini
+pub fn generate_html_from_markdown(markdown: &str) -> Result<String> {
+    Ok(render_markdown(markdown, &MarkdownOptions::default()))
+}
+
+/// Options de rendu d'un fragment Markdown
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownOptions {
+    /// Décalage des titres (le contenu d'une section h2 commence en h3)
+    pub heading_offset: u8,
+    /// Préfixe des ancres de notes, unique par fragment d'un même document
+    pub anchor_prefix: String,
+}
+
+/// Markdown (titres, listes, tableaux, code, notes, liens) → HTML assaini
+pub fn render_markdown(markdown: &str, options: &MarkdownOptions) -> String {
+    let mut parser_options = Options::empty();
+    parser_options.insert(Options::ENABLE_TABLES);
+    parser_options.insert(Options::ENABLE_FOOTNOTES);
+    parser_options.insert(Options::ENABLE_STRIKETHROUGH);
+
+    let prefix = if options.anchor_prefix.is_empty() {
+        "fn".to_string()
+    } else {
+        format!("{}-fn", options.anchor_prefix)
+    };
+    let mut footnotes: Vec<String> = Vec::new();
+    let mut footnote_number = |label: &str| match footnotes.iter().position(|l| l == label) {
+        Some(i) => i + 1,
+        None => {
+            footnotes.push(label.to_string());
+            footnotes.len()
+        }
+    };
+
+    let events = Parser::new_ext(markdown, parser_options).map(|event| match event {
+        Event::Start(Tag::Heading { level, id, classes, attrs }) => Event::Start(Tag::Heading {
+            level: shift_heading(level, options.heading_offset),
+            id,
+            classes,
+            attrs,
+        }),
+        Event::End(TagEnd::Heading(level)) => Event::End(TagEnd::Heading(shift_heading(level, options.heading_offset))),
+        Event::FootnoteReference(label) => {
+            let number = footnote_number(&label);
+            let anchor = slugify(&label);
+            Event::InlineHtml(CowStr::from(format!(
+                "<sup class=\"footnote-ref\"><a href=\"#{p}-{a}\" id=\"{p}-ref-{a}\">{n}</a></sup>",
+                p = prefix,
+                a = anchor,
+                n = number
+            )))
+        }
+        Event::Start(Tag::FootnoteDefinition(label)) => {
+            let number = footnote_number(&label);
+            let anchor = slugify(&label);
+            Event::Html(CowStr::from(format!(
+                "<div class=\"footnote\" id=\"{p}-{a}\"><a class=\"footnote-back\" href=\"#{p}-ref-{a}\">{n}.</a>",
+                p = prefix,
+                a = anchor,
+                n = number
+            )))
+        }
+        Event::End(TagEnd::FootnoteDefinition) => Event::Html(CowStr::from("</div>\n")),
+        other => other,
+    });
+
+    let mut html = String::with_capacity(markdown.len() * 3 / 2);
+    cmark_html::push_html(&mut html, events);
+    sanitize_html(&html)
+}
+
+/// Assainit du HTML : balises de mise en forme uniquement, aucun script,
+/// images limitées aux données embarquées (`data:image/...`)
+pub fn sanitize_html(html: &str) -> String {
+    ammonia::Builder::default()
+        .add_generic_attributes(&["id", "class", "style"])
+        .filter_style_properties(["text-align"].into_iter().collect())
+        .add_url_schemes(&["data"])
+        .attribute_filter(|element, attribute, value| {
+            let is_data = value.trim_start().to_ascii_lowercase().starts_with("data:");
+            match (element, attribute) {
+                ("img", "src") if !value.starts_with("data:image/") => None,
+                (_, "href") if is_data => None,
+                _ => Some(value.into()),
+            }
+        })
+        .clean(html)
+        .to_string()
+}
+
+fn shift_heading(level: HeadingLevel, offset: u8) -> HeadingLevel {
+    match (level as u8 + offset).min(6) {
+        1 => HeadingLevel::H1,
+        2 => HeadingLevel::H2,
+        3 => HeadingLevel::H3,
+        4 => HeadingLevel::H4,
+        5 => HeadingLevel::H5,
+        _ => HeadingLevel::H6,
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Document complet
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Rend un document en page HTML autonome : CSS du thème embarqué,
+/// table des matières, clauses numérotées (documents légaux), annexes
+pub fn render_document(document: &Document) -> Result<String> {
+    let content = &document.content;
+    let mut body = String::new();
+
+    // En-tête
+    body.push_str("<header class=\"document-header\">\n");
+    body.push_str(&format!("<h1>{}</h1>\n", escape_html(&content.title)));
+    body.push_str(&format!(
+        "<p class=\"metadata\">Version {} · {}{}</p>\n",
+        escape_html(&document.metadata.version),
+        document.metadata.created_at.format("%d/%m/%Y"),
+        if document.metadata.author.is_empty() {
+            String::new()
+        } else {
+            format!(" · {}", escape_html(&document.metadata.author))
+        }
+    ));
+    body.push_str("</header>\n");
+
+    body.push_str(&render_toc(document));
+
+    if !content.executive_summary.trim().is_empty() {
+        body.push_str("<section id=\"resume\" class=\"summary\">\n<h2>Résumé exécutif</h2>\n");
+        body.push_str(&render_markdown(&content.executive_summary, &fragment_options(2, "resume")));
+        body.push_str("</section>\n");
+    }
+
+    if !content.objectives.is_empty() {
+        body.push_str("<section id=\"objectifs\">\n<h2>Objectifs</h2>\n<ul class=\"objectives\">\n");
+        for objective in &content.objectives {
+            body.push_str(&format!("<li>{}</li>\n", render_inline(objective)));
+        }
+        body.push_str("</ul>\n</section>\n");
+    }
+
+    for (i, section) in content.sections.iter().enumerate() {
+        render_section(&mut body, section, &(i + 1).to_string(), 2);
+    }
+
+    if let Some(clauses) = content.mandatory_clauses.as_ref().filter(|c| !c.is_empty()) {
+        let numbered = numbers_clauses(document);
+        body.push_str("<section id=\"clauses\" class=\"clauses\">\n<h2>Clauses</h2>\n");
+        for (i, clause) in clauses.iter().enumerate() {
+            let title = if numbered {
+                format!("Article {} — {}", i + 1, escape_html(&clause.title))
+            } else {
+                escape_html(&clause.title)
+            };
+            body.push_str(&format!(
+                "<div class=\"clause{}\" id=\"article-{}\">\n<h3>{}</h3>\n",
+                if clause.mandatory { " mandatory" } else { "" },
+                i + 1,
+                title
+            ));
+            body.push_str(&render_markdown(&clause.content, &fragment_options(3, &format!("article-{}", i + 1))));
+            body.push_str("</div>\n");
+        }
+        body.push_str("</section>\n");
+    }
+
+    if !content.annexes.is_empty() {
+        body.push_str("<section id=\"annexes\" class=\"appendices\">\n<h2>Annexes</h2>\n");
+        for (i, annex) in content.annexes.iter().enumerate() {
+            let anchor = format!("annexe-{}", annex_letter(i).to_ascii_lowercase());
+            body.push_str(&format!(
+                "<section class=\"appendix\" id=\"{}\">\n<h3>{}</h3>\n",
+                anchor,
+                escape_html(&annex_title(annex, i))
+            ));
+            body.push_str(&render_annex(annex, &anchor));
+            body.push_str("</section>\n");
+        }
+        body.push_str("</section>\n");
+    }
+
+    if !content.references.is_empty() {
+        body.push_str("<section id=\"references\" class=\"references\">\n<h2>Références</h2>\n<ol>\n");
+        for reference in &content.references {
+            let title = match &reference.url {
+                Some(url) => sanitize_html(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape_html(url),
+                    escape_html(&reference.title)
+                )),
+                None => format!("<strong>{}</strong>", escape_html(&reference.title)),
+            };
+            body.push_str(&format!("<li>{} — {}", title, escape_html(&reference.source)));
+            if let Some(date) = &reference.date {
+                body.push_str(&format!(" ({})", escape_html(date)));
+            }
+            body.push_str("</li>\n");
+        }
+        body.push_str("</ol>\n</section>\n");
+    }
+
+    let theme = Theme::for_style(&document.config.style);
+    Ok(format!(
+        "<!DOCTYPE html>\n<html lang=\"{lang}\">\n<head>\n<meta charset=\"UTF-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">\n\
+         <meta name=\"generator\" content=\"TITANE∞ doc_engine\">\n<title>{title}</title>\n\
+         <style>\n{base}\n{theme}\n</style>\n</head>\n<body class=\"theme-{name}\">\n<article class=\"document\">\n{body}</article>\n</body>\n</html>\n",
+        lang = html_lang(&document.config.language),
+        title = escape_html(&content.title),
+        base = BASE_CSS,
+        theme = theme.css(),
+        name = theme.name(),
+        body = body
+    ))
+}
+
+fn render_section(out: &mut String, section: &Section, number: &str, depth: u8) {
+    let level = depth.min(6);
+    out.push_str(&format!(
+        "<section id=\"{}\" class=\"section level-{}\">\n<h{lvl}><span class=\"number\">{}</span> {}</h{lvl}>\n",
+        section_anchor(number),
+        depth - 1,
+        number,
+        escape_html(&section.title),
+        lvl = level
+    ));
+    out.push_str(&render_markdown(&section.content, &fragment_options(level, &section_anchor(number))));
+    for (i, subsection) in section.subsections.iter().enumerate() {
+        render_section(out, subsection, &format!("{}.{}", number, i + 1), depth + 1);
+    }
+    out.push_str("</section>\n");
+}
+
+fn render_toc(document: &Document) -> String {
+    fn push_sections(out: &mut String, sections: &[Section], prefix: &str) {
+        out.push_str("<ol>\n");
+        for (i, section) in sections.iter().enumerate() {
+            let number = if prefix.is_empty() {
+                (i + 1).to_string()
+            } else {
+                format!("{}.{}", prefix, i + 1)
+            };
+            out.push_str(&format!(
+                "<li><a href=\"#{}\"><span class=\"number\">{}</span> {}</a>",
+                section_anchor(&number),
+                number,
+                escape_html(&section.title)
+            ));
+            if !section.subsections.is_empty() {
+                push_sections(out, &section.subsections, &number);
+            }
+            out.push_str("</li>\n");
+        }
+        out.push_str("</ol>\n");
+    }
+
+    let content = &document.content;
+    let mut toc = String::from("<nav class=\"toc\">\n<h2>Table des matières</h2>\n");
+    let mut extras = Vec::new();
+    if !content.executive_summary.trim().is_empty() {
+        extras.push(("resume", "Résumé exécutif"));
+    }
+    if !content.objectives.is_empty() {
+        extras.push(("objectifs", "Objectifs"));
+    }
+    toc.push_str("<ul>\n");
+    for (anchor, label) in extras {
+        toc.push_str(&format!("<li><a href=\"#{}\">{}</a></li>\n", anchor, label));
+    }
+    toc.push_str("</ul>\n");
+
+    push_sections(&mut toc, &content.sections, "");
+
+    toc.push_str("<ul>\n");
+    if content.mandatory_clauses.as_ref().is_some_and(|c| !c.is_empty()) {
+        toc.push_str("<li><a href=\"#clauses\">Clauses</a></li>\n");
+    }
+    if !content.annexes.is_empty() {
+        toc.push_str("<li><a href=\"#annexes\">Annexes</a><ul>\n");
+        for (i, annex) in content.annexes.iter().enumerate() {
+            toc.push_str(&format!(
+                "<li><a href=\"#annexe-{}\">{}</a></li>\n",
+                annex_letter(i).to_ascii_lowercase(),
+                escape_html(&annex_title(annex, i))
+            ));
+        }
+        toc.push_str("</ul></li>\n");
+    }
+    if !content.references.is_empty() {
+        toc.push_str("<li><a href=\"#references\">Références</a></li>\n");
+    }
+    toc.push_str("</ul>\n</nav>\n");
+    toc
+}
+
+/// Contenu d'annexe selon son format déclaré
+fn render_annex(annex: &Annex, anchor: &str) -> String {
+    match annex.format.to_lowercase().as_str() {
+        "html" => sanitize_html(&annex.content),
+        "yaml" | "json" | "code" | "csv" => format!(
+            "<pre><code class=\"language-{}\">{}</code></pre>\n",
+            escape_html(&annex.format.to_lowercase()),
+            escape_html(&annex.content)
+        ),
+        _ => render_markdown(&annex.content, &fragment_options(3, anchor)),
+    }
+}
+
+pub(crate) fn annex_title(annex: &Annex, index: usize) -> String {
+    if annex.title.to_lowercase().starts_with("annexe") {
+        annex.title.clone()
+    } else {
+        format!("Annexe {} — {}", annex_letter(index), annex.title)
+    }
+}
+
+/// Lettre d'annexe : A…Z puis AA, AB… (numérotation bijective en base 26)
+fn annex_letter(index: usize) -> String {
+    let mut letters = Vec::new();
+    let mut n = index + 1;
+    while n > 0 {
+        n -= 1;
+        letters.push((b'A' + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// Markdown d'une ligne sans paragraphe englobant
+fn render_inline(text: &str) -> String {
+    let html = render_markdown(text, &MarkdownOptions::default());
+    let trimmed = html.trim();
+    trimmed
+        .strip_prefix("<p>")
+        .and_then(|s| s.strip_suffix("</p>"))
+        .unwrap_or(trimmed)
+        .to_string()
+}
+
+fn fragment_options(heading_offset: u8, anchor_prefix: &str) -> MarkdownOptions {
+    MarkdownOptions {
+        heading_offset,
+        anchor_prefix: anchor_prefix.to_string(),
+    }
+}
+
+fn numbers_clauses(document: &Document) -> bool {
+    matches!(document.config.style, DocumentStyle::Legal)
+        || matches!(
+            document.config.doc_type,
+            DocumentType::Contract
+                | DocumentType::NDA
+                | DocumentType::ServiceAgreement
+                | DocumentType::Partnership
+                | DocumentType::TermsOfService
+                | DocumentType::PrivacyPolicy
+                | DocumentType::LegalNotice
+        )
+}
+
+fn section_anchor(number: &str) -> String {
+    format!("section-{}", number.replace('.', "-"))
+}
+
+fn html_lang(language: &str) -> String {
+    let code: String = language.chars().take_while(|c| c.is_ascii_alphabetic()).take(2).collect();
+    if code.len() == 2 {
+        code.to_lowercase()
+    } else {
+        "fr".to_string()
+    }
+}
+
+fn slugify(label: &str) -> String {
+    let slug: String = label
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    slug.trim_matches('-').to_string()
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Thèmes
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Thème CSS embarqué, dérivé du `DocumentStyle`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Formal,
+    Legal,
+    Technical,
+    Editorial,
+}
+
+impl Theme {
+    pub fn for_style(style: &DocumentStyle) -> Self {
+        match style {
+            DocumentStyle::Formal | DocumentStyle::Professional => Theme::Formal,
+            DocumentStyle::Legal | DocumentStyle::Academic => Theme::Legal,
+            DocumentStyle::Technical => Theme::Technical,
+            DocumentStyle::Editorial | DocumentStyle::Pedagogical => Theme::Editorial,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Formal => "formal",
+            Theme::Legal => "legal",
+            Theme::Technical => "technical",
+            Theme::Editorial => "editorial",
+        }
+    }
+
+    pub fn css(&self) -> &'static str {
+        match self {
+            Theme::Formal => FORMAL_CSS,
+            Theme::Legal => LEGAL_CSS,
+            Theme::Technical => TECHNICAL_CSS,
+            Theme::Editorial => EDITORIAL_CSS,
+        }
+    }
+}
+
+const BASE_CSS: &str = r#"
+*, *::before, *::after { box-sizing: border-box; }
+body { margin: 0; padding: 2rem 1rem; background: #fff; color: #222; line-height: 1.6; }
+.document { max-width: 860px; margin: 0 auto; }
+.document-header { margin-bottom: 2rem; }
+.metadata { color: #666; font-size: 0.9rem; }
+.toc { margin: 2rem 0; padding: 1rem 1.5rem; border: 1px solid #ddd; }
+.toc ol, .toc ul { margin: 0.2rem 0; padding-left: 1.2rem; }
+.toc a { text-decoration: none; color: inherit; }
+.number { color: #888; margin-right: 0.4rem; }
+table { border-collapse: collapse; width: 100%; margin: 1rem 0; }
+th, td { border: 1px solid #ccc; padding: 0.4rem 0.6rem; vertical-align: top; }
+th { background: #f4f4f4; }
+pre { padding: 0.8rem 1rem; overflow-x: auto; background: #f6f8fa; border-radius: 4px; }
+code { font-family: "SFMono-Regular", Consolas, "Liberation Mono", monospace; font-size: 0.9em; }
+blockquote { margin: 1rem 0; padding: 0.2rem 1rem; border-left: 4px solid #ccc; color: #555; }
+img { max-width: 100%; }
+.footnote { font-size: 0.85rem; color: #555; margin-top: 0.5rem; }
+.footnote p { display: inline; }
+.footnote-back { margin-right: 0.3rem; }
+.clause.mandatory > h3::after { content: " ●"; font-size: 0.7em; vertical-align: middle; }
+@media print {
+  body { padding: 0; }
+  .toc { page-break-after: always; border: none; }
+  .appendix { page-break-before: always; }
+  a { color: inherit; }
+}
+"#;
+
+const FORMAL_CSS: &str = r#"
+body { font-family: "Segoe UI", "Helvetica Neue", Arial, sans-serif; }
+h1 { color: #1f2d3d; border-bottom: 3px solid #2f6fad; padding-bottom: 0.5rem; }
+h2 { color: #2f3e4e; margin-top: 2.2rem; }
+h3, h4 { color: #4b5a69; }
+.clause { padding: 0.5rem 1rem; border-left: 4px solid #2f6fad; background: #f5f8fb; margin: 1rem 0; }
+"#;
+
+const LEGAL_CSS: &str = r#"
+body { font-family: "Times New Roman", Georgia, serif; font-size: 1.05rem; text-align: justify; }
+h1 { text-align: center; text-transform: uppercase; letter-spacing: 0.05em; }
+h2 { text-transform: uppercase; font-size: 1.1rem; border-bottom: 1px solid #000; margin-top: 2rem; }
+h3 { font-size: 1rem; }
+.metadata { text-align: center; }
+.clause { margin: 1.2rem 0; }
+.clause > h3 { font-variant: small-caps; }
+.appendix > h3 { text-align: center; text-transform: uppercase; }
+"#;
+
+const TECHNICAL_CSS: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Roboto, "Helvetica Neue", sans-serif; }
+h1 { font-weight: 600; border-bottom: 1px solid #d0d7de; padding-bottom: 0.4rem; }
+h2 { font-weight: 600; border-bottom: 1px solid #eaeef2; padding-bottom: 0.3rem; margin-top: 2rem; }
+.number { font-family: "SFMono-Regular", Consolas, monospace; }
+pre { border: 1px solid #d0d7de; }
+:not(pre) > code { background: #eff1f3; padding: 0.1em 0.3em; border-radius: 3px; }
+.clause { border: 1px solid #d0d7de; border-radius: 4px; padding: 0.5rem 1rem; margin: 1rem 0; }
+"#;
+
+const EDITORIAL_CSS: &str = r#"
+body { font-family: Georgia, "Iowan Old Style", "Palatino Linotype", serif; font-size: 1.1rem; line-height: 1.75; }
+.document { max-width: 720px; }
+h1 { font-size: 2.4rem; font-weight: normal; line-height: 1.2; }
+h2 { font-weight: normal; font-style: italic; margin-top: 2.5rem; }
+.summary p:first-of-type::first-letter { float: left; font-size: 3.2em; line-height: 0.9; padding-right: 0.1em; }
+.toc { border: none; border-top: 1px solid #ccc; border-bottom: 1px solid #ccc; }
+.clause { font-style: italic; margin: 1rem 2rem; }
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annex_letters_continue_past_z() {
+        let letters: Vec<String> = [0, 1, 25, 26, 27, 51, 52, 701, 702].iter().map(|&i| annex_letter(i)).collect();
+        assert_eq!(letters, vec!["A", "B", "Z", "AA", "AB", "AZ", "BA", "ZZ", "AAA"]);
+    }
+
+    #[test]
+    fn test_annex_anchors_are_unique() {
+        let mut document = test_document(DocumentType::Guide, DocumentStyle::Formal);
+        document.content.annexes = (0..28)
+            .map(|i| Annex {
+                id: format!("annexe-{}", i),
+                title: format!("Pièce {}", i),
+                content: "Contenu".to_string(),
+                format: "markdown".to_string(),
+            })
+            .collect();
+
+        let html = render_document(&document).unwrap();
+        assert!(html.contains("id=\"annexe-z\""));
+        assert!(html.contains("id=\"annexe-ab\""));
+        assert!(html.contains("Annexe AB — Pièce 27"));
+        assert_eq!(html.matches("id=\"annexe-a\"").count(), 1);
+    }
+
+    #[test]
+    fn test_sanitize_strips_scripts_handlers_and_styles() {
+        let dirty = concat!(
+            "<p style=\"color: red; text-align: center\" onclick=\"alert(1)\">Texte</p>",
+            "<script>alert('x')</script>",
+            "<img src=\"https://exemple.fr/pixel.png\" onerror=\"alert(2)\">",
+            "<img src=\"data:image/png;base64,AAAA\">",
+            "<a href=\"javascript:alert(3)\" onmouseover=\"alert(4)\">lien</a>",
+        );
+        let clean = sanitize_html(dirty);
+
+        assert!(!clean.contains("<script"), "{}", clean);
+        assert!(!clean.contains("alert"), "{}", clean);
+        assert!(!clean.contains("onclick") && !clean.contains("onerror") && !clean.contains("onmouseover"));
+        assert!(!clean.contains("color"), "{}", clean);
+        assert!(clean.contains("text-align"), "{}", clean);
+        assert!(!clean.contains("exemple.fr"), "{}", clean);
+        assert!(clean.contains("data:image/png;base64,AAAA"), "{}", clean);
+        assert!(clean.contains("Texte") && clean.contains("lien"));
+    }
+
+    #[test]
+    fn test_markdown_raw_html_is_sanitized() {
+        let html = generate_html_from_markdown("# Titre\n\n<div onload=\"x()\">ok</div><script>y()</script>").unwrap();
+        assert!(html.contains("<h1>Titre</h1>"));
+        assert!(!html.contains("onload") && !html.contains("<script"), "{}", html);
+    }
 }
@@ -0,0 +1,931 @@
+// TITANE∞ v13 - Contrôle juridique par juridiction
+// Règles déclaratives regroupées en packs versionnés (`<id>.rules.json`) :
+// un pack par juridiction, pouvant en étendre d'autres (`fr` étend `eu`).
+// Les packs FR et UE sont embarqués ; un pack fichier du même id les remplace
+// s'il est de version supérieure ou égale.
+//
+// Trois familles de contrôles :
+//   - `mentions` : informations obligatoires présentes dans le document
+//     (ex. coordonnées du responsable de traitement, hébergeur) ;
+//   - `clause`   : clause ou section attendue, avec son contenu minimal
+//     (ex. loi applicable, juridiction compétente) ;
+//   - `wording`  : formulations interdites dans les clauses ciblées, sauf
+//     réserve explicite (ex. exclusion de responsabilité sans réserve de la
+//     faute lourde).
+//
+// Les motifs sont des expressions régulières insensibles à la casse,
+// compilées au chargement : un pack invalide est refusé.
+
+use super::*;
+use regex::{Regex, RegexBuilder};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+pub const RULES_FILE_EXTENSION: &str = ".rules.json";
+
+/// Version du format de pack comprise par ce moteur
+pub const RULES_FORMAT_VERSION: u32 = 1;
+
+const BUILTIN_PACKS: &[(&str, &str)] = &[
+    ("eu", include_str!("../../assets/legal_rules/eu.rules.json")),
+    ("fr", include_str!("../../assets/legal_rules/fr.rules.json")),
+];
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Format des packs
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Pack de règles d'une juridiction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulePack {
+    #[serde(default = "default_format_version")]
+    pub format_version: u32,
+    pub id: String,
+    pub name: String,
+    /// Code de juridiction (`FR`, `EU`…)
+    pub jurisdiction: String,
+    /// Version du pack (`2026.1`), reportée sur chaque violation
+    pub version: String,
+    /// Packs dont les règles s'appliquent aussi
+    #[serde(default)]
+    pub extends: Vec<String>,
+    pub rules: Vec<LegalRule>,
+}
+
+fn default_format_version() -> u32 {
+    RULES_FORMAT_VERSION
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegalRule {
+    pub id: String,
+    /// Texte de référence (article de loi, règlement…)
+    pub reference: String,
+    pub description: String,
+    /// Types de documents concernés (vide = tous)
+    #[serde(default)]
+    pub applies_to: Vec<DocumentType>,
+    pub severity: ErrorSeverity,
+    pub check: RuleCheck,
+    #[serde(default)]
+    pub remediation: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleCheck {
+    /// Chaque groupe doit être mentionné quelque part dans le document
+    Mentions { required: Vec<MentionGroup> },
+    /// Une clause ciblée doit exister et contenir chaque groupe
+    Clause {
+        selector: ClauseSelector,
+        #[serde(default)]
+        required: Vec<MentionGroup>,
+    },
+    /// Aucune clause ciblée ne doit contenir un motif interdit,
+    /// sauf si elle contient aussi un motif de `unless`
+    Wording {
+        selector: ClauseSelector,
+        forbidden: Vec<String>,
+        #[serde(default)]
+        unless: Vec<String>,
+    },
+}
+
+/// Mention attendue : satisfaite dès qu'un des motifs est trouvé
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MentionGroup {
+    pub label: String,
+    pub patterns: Vec<String>,
+}
+
+/// Clauses visées par une règle : par catégorie ou par titre
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClauseSelector {
+    #[serde(default)]
+    pub categories: Vec<ClauseCategory>,
+    #[serde(default)]
+    pub title_patterns: Vec<String>,
+    /// Cherche aussi parmi les sections (par titre)
+    #[serde(default = "default_true")]
+    pub sections: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Manquement à une règle, rattaché à la clause ou section fautive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleViolation {
+    pub pack: String,
+    pub pack_version: String,
+    pub rule_id: String,
+    pub reference: String,
+    pub severity: ErrorSeverity,
+    pub message: String,
+    pub clause_id: Option<String>,
+    pub section_id: Option<String>,
+    pub remediation: Option<String>,
+}
+
+impl RuleViolation {
+    /// Élément visé, au format des avertissements (`clause:<id>`, `section:<id>`)
+    pub fn target(&self) -> Option<String> {
+        self.clause_id
+            .as_ref()
+            .map(|id| format!("clause:{}", id))
+            .or_else(|| self.section_id.as_ref().map(|id| format!("section:{}", id)))
+    }
+}
+
+/// Lit et valide un pack (format, motifs)
+pub fn parse_pack(json: &str) -> Result<RulePack> {
+    let pack = deserialize_pack(json)?;
+    compile_pack(&pack)?;
+    Ok(pack)
+}
+
+fn deserialize_pack(json: &str) -> Result<RulePack> {
+    serde_json::from_str(json)
+        .map_err(|e| DocEngineError::ValidationError(format!("Pack de règles invalide: {}", e)))
+}
+
+pub fn default_rules_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("titane")
+        .join("legal_rules")
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Registre
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PackOrigin {
+    Builtin,
+    File(PathBuf),
+    /// Ajouté par programme (`add_pack`)
+    Added,
+}
+
+/// Résumé d'un pack chargé
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackInfo {
+    pub id: String,
+    pub name: String,
+    pub jurisdiction: String,
+    pub version: String,
+    pub extends: Vec<String>,
+    pub rules: usize,
+    pub origin: PackOrigin,
+}
+
+struct LoadedPack {
+    pack: RulePack,
+    origin: PackOrigin,
+    rules: Vec<CompiledRule>,
+}
+
+/// Packs de règles disponibles, indexés par id
+pub struct RuleRegistry {
+    packs: BTreeMap<String, LoadedPack>,
+    directory: Option<PathBuf>,
+    load_errors: Vec<String>,
+}
+
+impl RuleRegistry {
+    /// Registre vide
+    pub fn new() -> Self {
+        Self {
+            packs: BTreeMap::new(),
+            directory: None,
+            load_errors: Vec::new(),
+        }
+    }
+
+    /// Packs embarqués (FR, UE)
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.load_builtin();
+        registry
+    }
+
+    /// Packs embarqués complétés par les `*.rules.json` du répertoire
+    pub fn with_directory(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            DocEngineError::ValidationError(format!("Création de {}: {}", dir.display(), e))
+        })?;
+
+        let mut registry = Self::new();
+        registry.directory = Some(dir);
+        registry.reload();
+        Ok(registry)
+    }
+
+    /// Recharge les packs embarqués puis ceux du répertoire
+    pub fn reload(&mut self) {
+        self.packs.clear();
+        self.load_errors.clear();
+        self.load_builtin();
+
+        let Some(dir) = self.directory.clone() else {
+            return;
+        };
+        for path in rule_files(&dir, &mut self.load_errors) {
+            let loaded = std::fs::read_to_string(&path)
+                .map_err(|e| DocEngineError::ValidationError(e.to_string()))
+                .and_then(|json| deserialize_pack(&json))
+                .and_then(|pack| self.insert(pack, PackOrigin::File(path.clone())));
+            if let Err(e) = loaded {
+                self.load_errors.push(format!("{}: {}", path.display(), e));
+            }
+        }
+    }
+
+    fn load_builtin(&mut self) {
+        for (id, json) in BUILTIN_PACKS {
+            if let Err(e) = deserialize_pack(json).and_then(|p| self.insert(p, PackOrigin::Builtin))
+            {
+                self.load_errors.push(format!("pack intégré {}: {}", id, e));
+            }
+        }
+    }
+
+    /// Ajoute ou remplace un pack, quelle que soit sa version
+    pub fn add_pack(&mut self, pack: RulePack) -> Result<()> {
+        let rules = compile_pack(&pack)?;
+        self.packs.insert(
+            pack.id.clone(),
+            LoadedPack {
+                pack,
+                origin: PackOrigin::Added,
+                rules,
+            },
+        );
+        Ok(())
+    }
+
+    /// Insertion au chargement : un pack ne remplace pas une version plus récente
+    fn insert(&mut self, pack: RulePack, origin: PackOrigin) -> Result<()> {
+        if let Some(existing) = self.packs.get(&pack.id) {
+            if compare_versions(&pack.version, &existing.pack.version).is_lt() {
+                return Err(DocEngineError::ValidationError(format!(
+                    "pack '{}' {} ignoré : version {} déjà chargée",
+                    pack.id, pack.version, existing.pack.version
+                )));
+            }
+        }
+        let rules = compile_pack(&pack)?;
+        self.packs.insert(
+            pack.id.clone(),
+            LoadedPack {
+                pack,
+                origin,
+                rules,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn directory(&self) -> Option<&Path> {
+        self.directory.as_deref()
+    }
+
+    pub fn load_errors(&self) -> &[String] {
+        &self.load_errors
+    }
+
+    pub fn get_pack(&self, id: &str) -> Option<&RulePack> {
+        self.find(id).map(|loaded| &loaded.pack)
+    }
+
+    /// Vrai si la juridiction (id de pack ou code) est connue
+    pub fn contains(&self, jurisdiction: &str) -> bool {
+        self.find(jurisdiction).is_some()
+    }
+
+    pub fn list(&self) -> Vec<PackInfo> {
+        self.packs
+            .values()
+            .map(|loaded| PackInfo {
+                id: loaded.pack.id.clone(),
+                name: loaded.pack.name.clone(),
+                jurisdiction: loaded.pack.jurisdiction.clone(),
+                version: loaded.pack.version.clone(),
+                extends: loaded.pack.extends.clone(),
+                rules: loaded.pack.rules.len(),
+                origin: loaded.origin.clone(),
+            })
+            .collect()
+    }
+
+    fn find(&self, jurisdiction: &str) -> Option<&LoadedPack> {
+        let wanted = jurisdiction.trim().to_lowercase();
+        self.packs.get(&wanted).or_else(|| {
+            self.packs
+                .values()
+                .find(|loaded| loaded.pack.jurisdiction.to_lowercase() == wanted)
+        })
+    }
+
+    /// Packs applicables aux juridictions, parents d'abord, sans doublon
+    pub fn resolve(&self, jurisdictions: &[&str]) -> Result<Vec<&RulePack>> {
+        let mut ordered = Vec::new();
+        let mut seen = HashSet::new();
+        for jurisdiction in jurisdictions {
+            let loaded = self.find(jurisdiction).ok_or_else(|| {
+                DocEngineError::ValidationError(format!("Juridiction inconnue: {}", jurisdiction))
+            })?;
+            self.collect(loaded, &mut Vec::new(), &mut seen, &mut ordered)?;
+        }
+        Ok(ordered.into_iter().map(|loaded| &loaded.pack).collect())
+    }
+
+    fn collect<'a>(
+        &'a self,
+        loaded: &'a LoadedPack,
+        stack: &mut Vec<String>,
+        seen: &mut HashSet<String>,
+        ordered: &mut Vec<&'a LoadedPack>,
+    ) -> Result<()> {
+        let id = &loaded.pack.id;
+        if seen.contains(id) {
+            return Ok(());
+        }
+        if stack.contains(id) {
+            stack.push(id.clone());
+            return Err(DocEngineError::ValidationError(format!(
+                "Héritage circulaire entre packs: {}",
+                stack.join(" → ")
+            )));
+        }
+
+        stack.push(id.clone());
+        for parent in &loaded.pack.extends {
+            let parent = self.packs.get(parent).ok_or_else(|| {
+                DocEngineError::ValidationError(format!(
+                    "Pack '{}' : parent '{}' introuvable",
+                    id, parent
+                ))
+            })?;
+            self.collect(parent, stack, seen, ordered)?;
+        }
+        stack.pop();
+
+        seen.insert(id.clone());
+        ordered.push(loaded);
+        Ok(())
+    }
+
+    /// Applique les règles des juridictions au document
+    pub fn check(
+        &self,
+        jurisdictions: &[&str],
+        doc_type: &DocumentType,
+        content: &DocumentContent,
+    ) -> Result<Vec<RuleViolation>> {
+        let packs = self.resolve(jurisdictions)?;
+        let elements = Element::collect(content);
+        let mut violations = Vec::new();
+
+        for pack in packs {
+            let loaded = &self.packs[&pack.id];
+            for compiled in &loaded.rules {
+                let rule = &compiled.rule;
+                if !rule.applies_to.is_empty() && !rule.applies_to.contains(doc_type) {
+                    continue;
+                }
+                for finding in compiled.matcher.check(&elements) {
+                    violations.push(RuleViolation {
+                        pack: pack.id.clone(),
+                        pack_version: pack.version.clone(),
+                        rule_id: rule.id.clone(),
+                        reference: rule.reference.clone(),
+                        severity: rule.severity.clone(),
+                        message: format!("{} : {}", rule.description, finding.detail),
+                        clause_id: finding.clause_id,
+                        section_id: finding.section_id,
+                        remediation: rule.remediation.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(violations)
+    }
+}
+
+impl Default for RuleRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+fn rule_files(dir: &Path, errors: &mut Vec<String>) -> Vec<PathBuf> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            errors.push(format!("{}: {}", dir.display(), e));
+            return Vec::new();
+        }
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with(RULES_FILE_EXTENSION))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Compare deux versions pointées (`2026.1` < `2026.10`)
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parts = |v: &str| -> Vec<u64> {
+        v.split(['.', '-'])
+            .map(|p| p.trim().parse().unwrap_or(0))
+            .collect()
+    };
+    parts(a).cmp(&parts(b))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Compilation des règles
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct CompiledRule {
+    rule: LegalRule,
+    matcher: Matcher,
+}
+
+struct CompiledGroup {
+    label: String,
+    patterns: Vec<Regex>,
+}
+
+impl CompiledGroup {
+    fn is_match(&self, text: &str) -> bool {
+        self.patterns.iter().any(|p| p.is_match(text))
+    }
+}
+
+struct CompiledSelector {
+    categories: Vec<ClauseCategory>,
+    titles: Vec<Regex>,
+    sections: bool,
+}
+
+enum Matcher {
+    Mentions(Vec<CompiledGroup>),
+    Clause {
+        selector: CompiledSelector,
+        required: Vec<CompiledGroup>,
+    },
+    Wording {
+        selector: CompiledSelector,
+        forbidden: Vec<Regex>,
+        unless: Vec<Regex>,
+    },
+}
+
+fn compile_pack(pack: &RulePack) -> Result<Vec<CompiledRule>> {
+    if pack.format_version > RULES_FORMAT_VERSION {
+        return Err(DocEngineError::ValidationError(format!(
+            "Pack '{}' : format {} non supporté (max {})",
+            pack.id, pack.format_version, RULES_FORMAT_VERSION
+        )));
+    }
+    if pack.id.trim().is_empty() || pack.id != pack.id.to_lowercase() {
+        return Err(DocEngineError::ValidationError(format!(
+            "Identifiant de pack invalide: '{}' (minuscules attendues)",
+            pack.id
+        )));
+    }
+
+    let mut ids = HashSet::new();
+    pack.rules
+        .iter()
+        .map(|rule| {
+            if !ids.insert(rule.id.as_str()) {
+                return Err(DocEngineError::ValidationError(format!(
+                    "Pack '{}' : règle '{}' en double",
+                    pack.id, rule.id
+                )));
+            }
+            let matcher = compile_check(&rule.check).map_err(|e| {
+                DocEngineError::ValidationError(format!("Règle '{}' : {}", rule.id, e))
+            })?;
+            Ok(CompiledRule {
+                rule: rule.clone(),
+                matcher,
+            })
+        })
+        .collect()
+}
+
+fn compile_check(check: &RuleCheck) -> std::result::Result<Matcher, String> {
+    Ok(match check {
+        RuleCheck::Mentions { required } => {
+            if required.is_empty() {
+                return Err("aucune mention requise".to_string());
+            }
+            Matcher::Mentions(compile_groups(required)?)
+        }
+        RuleCheck::Clause { selector, required } => Matcher::Clause {
+            selector: compile_selector(selector)?,
+            required: compile_groups(required)?,
+        },
+        RuleCheck::Wording {
+            selector,
+            forbidden,
+            unless,
+        } => {
+            if forbidden.is_empty() {
+                return Err("aucune formulation interdite".to_string());
+            }
+            Matcher::Wording {
+                selector: compile_selector(selector)?,
+                forbidden: compile_patterns(forbidden)?,
+                unless: compile_patterns(unless)?,
+            }
+        }
+    })
+}
+
+fn compile_groups(groups: &[MentionGroup]) -> std::result::Result<Vec<CompiledGroup>, String> {
+    groups
+        .iter()
+        .map(|group| {
+            if group.patterns.is_empty() {
+                return Err(format!("mention '{}' sans motif", group.label));
+            }
+            Ok(CompiledGroup {
+                label: group.label.clone(),
+                patterns: compile_patterns(&group.patterns)?,
+            })
+        })
+        .collect()
+}
+
+fn compile_selector(selector: &ClauseSelector) -> std::result::Result<CompiledSelector, String> {
+    if selector.categories.is_empty() && selector.title_patterns.is_empty() {
+        return Err("sélecteur vide".to_string());
+    }
+    Ok(CompiledSelector {
+        categories: selector.categories.clone(),
+        titles: compile_patterns(&selector.title_patterns)?,
+        sections: selector.sections,
+    })
+}
+
+fn compile_patterns(patterns: &[String]) -> std::result::Result<Vec<Regex>, String> {
+    patterns
+        .iter()
+        .map(|p| {
+            RegexBuilder::new(p)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| format!("motif '{}' invalide: {}", p, e))
+        })
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Évaluation
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Élément contrôlable du document : clause ou section (à plat)
+struct Element<'a> {
+    clause: Option<&'a Clause>,
+    section_id: Option<&'a str>,
+    title: &'a str,
+    content: &'a str,
+}
+
+impl<'a> Element<'a> {
+    fn collect(content: &'a DocumentContent) -> Vec<Element<'a>> {
+        fn walk<'a>(sections: &'a [Section], out: &mut Vec<Element<'a>>) {
+            for section in sections {
+                out.push(Element {
+                    clause: None,
+                    section_id: Some(&section.id),
+                    title: &section.title,
+                    content: &section.content,
+                });
+                walk(&section.subsections, out);
+            }
+        }
+
+        let mut elements = vec![Element {
+            clause: None,
+            section_id: None,
+            title: &content.title,
+            content: &content.executive_summary,
+        }];
+        elements.extend(content.objectives.iter().map(|o| Element {
+            clause: None,
+            section_id: None,
+            title: "",
+            content: o,
+        }));
+        walk(&content.sections, &mut elements);
+        for clause in content.mandatory_clauses.iter().flatten() {
+            elements.push(Element {
+                clause: Some(clause),
+                section_id: None,
+                title: &clause.title,
+                content: &clause.content,
+            });
+        }
+        elements.extend(content.annexes.iter().map(|a| Element {
+            clause: None,
+            section_id: None,
+            title: &a.title,
+            content: &a.content,
+        }));
+        elements
+    }
+
+    fn text(&self) -> String {
+        format!("{}\n{}", self.title, self.content)
+    }
+
+    fn clause_id(&self) -> Option<String> {
+        self.clause.map(|c| c.id.clone())
+    }
+
+    fn section_id(&self) -> Option<String> {
+        self.section_id.map(str::to_string)
+    }
+}
+
+impl CompiledSelector {
+    fn matches(&self, element: &Element) -> bool {
+        let by_title = || self.titles.iter().any(|t| t.is_match(element.title));
+        match element.clause {
+            Some(clause) => self.categories.contains(&clause.category) || by_title(),
+            None => self.sections && element.section_id.is_some() && by_title(),
+        }
+    }
+}
+
+struct Finding {
+    detail: String,
+    clause_id: Option<String>,
+    section_id: Option<String>,
+}
+
+impl Matcher {
+    fn check(&self, elements: &[Element]) -> Vec<Finding> {
+        match self {
+            Matcher::Mentions(groups) => {
+                let text: String = elements
+                    .iter()
+                    .map(Element::text)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                missing(groups, &text)
+                    .map(|labels| Finding {
+                        detail: format!("mention(s) manquante(s) : {}", labels),
+                        clause_id: None,
+                        section_id: None,
+                    })
+                    .into_iter()
+                    .collect()
+            }
+            Matcher::Clause { selector, required } => {
+                let targeted: Vec<&Element> =
+                    elements.iter().filter(|e| selector.matches(e)).collect();
+                let Some(first) = targeted.first() else {
+                    return vec![Finding {
+                        detail: "clause absente".to_string(),
+                        clause_id: None,
+                        section_id: None,
+                    }];
+                };
+                let text: String = targeted
+                    .iter()
+                    .map(|e| e.text())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                // Rattachée à la clause ciblée si elle existe, sinon à la section
+                let anchor = targeted
+                    .iter()
+                    .find(|e| e.clause.is_some())
+                    .unwrap_or(first);
+                missing(required, &text)
+                    .map(|labels| Finding {
+                        detail: format!("clause incomplète, manque : {}", labels),
+                        clause_id: anchor.clause_id(),
+                        section_id: anchor.section_id(),
+                    })
+                    .into_iter()
+                    .collect()
+            }
+            Matcher::Wording {
+                selector,
+                forbidden,
+                unless,
+            } => elements
+                .iter()
+                .filter(|e| selector.matches(e))
+                .filter(|e| !unless.iter().any(|u| u.is_match(e.content)))
+                .filter_map(|e| {
+                    let found = forbidden.iter().find_map(|f| f.find(e.content))?;
+                    Some(Finding {
+                        detail: format!("formulation « {} » dans « {} »", found.as_str(), e.title),
+                        clause_id: e.clause_id(),
+                        section_id: e.section_id(),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Libellés des mentions absentes du texte, `None` si tout est présent
+fn missing(groups: &[CompiledGroup], text: &str) -> Option<String> {
+    let labels: Vec<&str> = groups
+        .iter()
+        .filter(|g| !g.is_match(text))
+        .map(|g| g.label.as_str())
+        .collect();
+    (!labels.is_empty()).then(|| labels.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn pack(id: &str, version: &str, extends: &[&str]) -> RulePack {
+        parse_pack(
+            &serde_json::json!({
+                "id": id,
+                "name": format!("Pack {}", id),
+                "jurisdiction": id.to_uppercase(),
+                "version": version,
+                "extends": extends,
+                "rules": [{
+                    "id": format!("{}-1", id),
+                    "reference": "Art. 1",
+                    "description": "Mention test",
+                    "severity": "Low",
+                    "check": { "type": "mentions", "required": [{ "label": "test", "patterns": ["test"] }] }
+                }]
+            })
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    fn contract(clauses: Vec<Clause>) -> DocumentContent {
+        let mut content = test_document(DocumentType::Contract, DocumentStyle::Legal).content;
+        content.mandatory_clauses = Some(clauses);
+        content
+    }
+
+    fn clause(id: &str, title: &str, category: ClauseCategory, content: &str) -> Clause {
+        Clause {
+            id: id.to_string(),
+            title: title.to_string(),
+            content: content.to_string(),
+            mandatory: true,
+            category,
+        }
+    }
+
+    #[test]
+    fn test_builtin_packs_load() {
+        let registry = RuleRegistry::builtin();
+        assert!(registry.load_errors().is_empty(), "{:?}", registry.load_errors());
+
+        let ids: Vec<String> = registry.list().into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, ["eu", "fr"]);
+        assert!(registry.contains("FR") && registry.contains(" eu "));
+        assert!(!registry.contains("de"));
+
+        // `fr` étend `eu` : parents d'abord, sans doublon
+        let resolved: Vec<&str> = registry
+            .resolve(&["fr", "eu"])
+            .unwrap()
+            .into_iter()
+            .map(|p| p.id.as_str())
+            .collect();
+        assert_eq!(resolved, ["eu", "fr"]);
+        assert!(registry.resolve(&["de"]).is_err());
+    }
+
+    #[test]
+    fn test_cyclic_extends_are_rejected() {
+        let mut registry = RuleRegistry::new();
+        registry.add_pack(pack("a", "1", &["b"])).unwrap();
+        registry.add_pack(pack("b", "1", &["c"])).unwrap();
+        registry.add_pack(pack("c", "1", &["a"])).unwrap();
+
+        let error = registry.resolve(&["a"]).unwrap_err().to_string();
+        assert!(error.contains("a → b → c → a"), "{}", error);
+        let content = contract(vec![]);
+        assert!(registry.check(&["b"], &DocumentType::Contract, &content).is_err());
+
+        registry.add_pack(pack("d", "1", &["absent"])).unwrap();
+        assert!(registry.resolve(&["d"]).unwrap_err().to_string().contains("'absent'"));
+    }
+
+    #[test]
+    fn test_insert_keeps_the_newest_version() {
+        let mut registry = RuleRegistry::new();
+        registry.insert(pack("fr", "2026.2", &[]), PackOrigin::Builtin).unwrap();
+
+        let error = registry.insert(pack("fr", "2026.1", &[]), PackOrigin::Added).unwrap_err();
+        assert!(error.to_string().contains("version 2026.2 déjà chargée"), "{}", error);
+        assert_eq!(registry.get_pack("fr").unwrap().version, "2026.2");
+
+        // Égale ou supérieure (comparaison numérique : 2026.10 > 2026.2)
+        registry.insert(pack("fr", "2026.2", &[]), PackOrigin::Added).unwrap();
+        registry.insert(pack("fr", "2026.10", &[]), PackOrigin::Added).unwrap();
+        assert_eq!(registry.get_pack("fr").unwrap().version, "2026.10");
+        assert!(matches!(registry.list()[0].origin, PackOrigin::Added));
+    }
+
+    #[test]
+    fn test_directory_packs_override_builtin_by_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let write = |name: &str, pack: &RulePack| {
+            std::fs::write(temp_dir.path().join(name), serde_json::to_string(pack).unwrap()).unwrap();
+        };
+        write("fr.rules.json", &pack("fr", "2025.9", &["eu"]));
+        write("local.rules.json", &pack("local", "1", &["fr"]));
+
+        let mut registry = RuleRegistry::with_directory(temp_dir.path()).unwrap();
+        assert_eq!(registry.load_errors().len(), 1);
+        assert!(registry.load_errors()[0].contains("ignoré"));
+        assert_eq!(registry.get_pack("fr").unwrap().version, "2026.1");
+        assert_eq!(registry.resolve(&["local"]).unwrap().len(), 3);
+
+        write("fr.rules.json", &pack("fr", "2027.1", &["eu"]));
+        registry.reload();
+        assert!(registry.load_errors().is_empty());
+        assert_eq!(registry.get_pack("fr").unwrap().rules.len(), 1);
+    }
+
+    #[test]
+    fn test_violation_points_at_the_faulty_clause() {
+        let registry = RuleRegistry::builtin();
+        let content = contract(vec![
+            clause(
+                "resp",
+                "Limitation de responsabilité",
+                ClauseCategory::Liability,
+                "Le prestataire exclut toute responsabilité pour les dommages indirects.",
+            ),
+            clause(
+                "litiges",
+                "Règlement des litiges",
+                ClauseCategory::Dispute,
+                "Tout différend sera soumis à la compétence exclusive des tribunaux de Paris.",
+            ),
+        ]);
+
+        let violations = registry.check(&["fr"], &DocumentType::Contract, &content).unwrap();
+        let find = |rule_id: &str| {
+            violations
+                .iter()
+                .find(|v| v.rule_id == rule_id)
+                .unwrap_or_else(|| panic!("{} absente de {:?}", rule_id, violations))
+        };
+
+        let liability = find("FR-CCIV-1231-3");
+        let rule = registry.get_pack("fr").unwrap().rules.iter().find(|r| r.id == liability.rule_id).unwrap();
+        assert_eq!(liability.reference, rule.reference);
+        assert_eq!((liability.pack.as_str(), liability.pack_version.as_str()), ("fr", "2026.1"));
+        assert_eq!(liability.clause_id.as_deref(), Some("resp"));
+        assert_eq!(liability.target().as_deref(), Some("clause:resp"));
+        assert!(liability.message.contains("exclut toute responsabilité"), "{}", liability.message);
+
+        // Règle héritée de `eu`, rattachée à la clause de litiges incomplète
+        let governing_law = find("EU-ROME-I-3");
+        assert_eq!(governing_law.pack, "eu");
+        assert_eq!(governing_law.clause_id.as_deref(), Some("litiges"));
+
+        assert_eq!(find("FR-CPC-48").clause_id.as_deref(), Some("litiges"));
+
+        // Réserve de la faute lourde : plus de violation sur la clause
+        let content = contract(vec![clause(
+            "resp",
+            "Responsabilité",
+            ClauseCategory::Liability,
+            "Le prestataire exclut toute responsabilité, sauf faute lourde ou dol.",
+        )]);
+        let violations = registry.check(&["fr"], &DocumentType::Contract, &content).unwrap();
+        assert!(violations.iter().all(|v| v.rule_id != "FR-CCIV-1231-3"));
+        let missing = violations.iter().find(|v| v.rule_id == "EU-ROME-I-3").unwrap();
+        assert!(missing.message.ends_with("clause absente"));
+        assert_eq!(missing.target(), None);
+    }
+}
@@ -7,12 +7,20 @@ pub mod admin;
 pub mod technical;
 pub mod editorial;
 pub mod html;
+pub mod pdf;
+mod office;
+pub mod docx;
+pub mod odt;
 pub mod templates;
+pub mod template_files;
 pub mod validator;
+pub mod legal_rules;
 pub mod formatter;
 pub mod export;
 pub mod storage;
+pub mod diff;
 pub mod versioning;
+pub mod assist;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -94,6 +102,9 @@ pub struct DocumentMetadata {
     pub author: String,
     pub tags: Vec<String>,
     pub category: String,
+    /// Contenus produits ou revus par l'IA, à valider par un humain
+    #[serde(default)]
+    pub ai_contributions: Vec<assist::AiContribution>,
 }
 
 /// Structure d'un document généré
@@ -137,7 +148,7 @@ pub struct Clause {
     pub category: ClauseCategory,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ClauseCategory {
     Confidentiality,
     Liability,
@@ -172,6 +183,9 @@ pub struct ValidationStatus {
     pub errors: Vec<ValidationError>,
     pub warnings: Vec<ValidationWarning>,
     pub suggestions: Vec<Suggestion>,
+    /// Manquements aux règles juridiques (référence, pack, clause visée)
+    #[serde(default)]
+    pub rule_violations: Vec<legal_rules::RuleViolation>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -179,6 +193,8 @@ pub struct ValidationError {
     pub code: String,
     pub message: String,
     pub severity: ErrorSeverity,
+    #[serde(default)]
+    pub target: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -193,6 +209,9 @@ pub enum ErrorSeverity {
 pub struct ValidationWarning {
     pub message: String,
     pub suggestion: Option<String>,
+    /// Élément visé : `section:<id>`, `clause:<id>`…
+    #[serde(default)]
+    pub target: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -200,6 +219,8 @@ pub struct Suggestion {
     pub category: String,
     pub message: String,
     pub priority: u8,
+    #[serde(default)]
+    pub target: Option<String>,
 }
 
 /// Format d'export
@@ -210,6 +231,8 @@ pub enum ExportFormat {
     Pdf,
     Json,
     Text,
+    Docx,
+    Odt,
 }
 
 /// Résultat d'export
@@ -233,6 +256,9 @@ pub enum DocEngineError {
     #[error("Erreur d'export: {0}")]
     ExportError(String),
     
+    #[error("Erreur d'import: {0}")]
+    ImportError(String),
+    
     #[error("Erreur de stockage: {0}")]
     StorageError(String),
     
@@ -244,3 +270,55 @@ pub enum DocEngineError {
 }
 
 pub type Result<T> = std::result::Result<T, DocEngineError>;
+
+/// Document minimal (une section avec sous-section) pour les tests des exports
+#[cfg(test)]
+pub(crate) fn test_document(doc_type: DocumentType, style: DocumentStyle) -> Document {
+    let section = |id: &str, title: &str, content: &str, level: u8| Section {
+        id: id.to_string(),
+        title: title.to_string(),
+        content: content.to_string(),
+        subsections: Vec::new(),
+        level,
+    };
+    let mut introduction = section("intro", "Introduction", "Le **présent** document décrit le *périmètre*.", 1);
+    introduction.subsections.push(section("contexte", "Contexte", "- premier point\n- second point", 2));
+
+    Document {
+        metadata: DocumentMetadata {
+            id: "doc-test".to_string(),
+            title: "Document de test".to_string(),
+            version: "1.0.0".to_string(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            author: "Équipe".to_string(),
+            tags: Vec::new(),
+            category: "test".to_string(),
+            ai_contributions: Vec::new(),
+        },
+        config: GenerationConfig {
+            doc_type,
+            style,
+            detail_level: DetailLevel::Standard,
+            tone: "neutre".to_string(),
+            language: "fr".to_string(),
+            custom_params: HashMap::new(),
+        },
+        content: DocumentContent {
+            title: "Document de test".to_string(),
+            executive_summary: "Synthèse du document.".to_string(),
+            objectives: vec!["Décrire le périmètre".to_string()],
+            sections: vec![introduction],
+            mandatory_clauses: None,
+            annexes: Vec::new(),
+            references: Vec::new(),
+        },
+        validation_status: ValidationStatus {
+            is_valid: true,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            suggestions: Vec::new(),
+            rule_violations: Vec::new(),
+        },
+    }
+}
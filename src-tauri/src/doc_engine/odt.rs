@@ -0,0 +1,446 @@
+// TITANE∞ v13 - Export ODT (OpenDocument Text)
+// Même structure que l'export DOCX : titres à niveaux de plan, listes et clauses
+// numérotées, tableaux, en-têtes/pieds de page avec pagination.
+
+use super::office::{self, escape_xml, Block, ParagraphKind, Span};
+use super::*;
+use std::io::{Cursor, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const MIMETYPE: &str = "application/vnd.oasis.opendocument.text";
+
+const NAMESPACES: &str = r##"xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" xmlns:svg="urn:oasis:names:tc:opendocument:xmlns:svg-compatible:1.0" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:meta="urn:oasis:names:tc:opendocument:xmlns:meta:1.0""##;
+
+/// Génère le fichier ODT d'un document
+pub fn render_odt(document: &Document) -> Result<Vec<u8>> {
+    let blocks = office::document_blocks(document);
+    let mut body = OdtBody::default();
+    let mut index = 0;
+    while index < blocks.len() {
+        index = body.block(&blocks, index);
+    }
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    // Le type MIME doit être la première entrée, non compressée
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    zip.start_file("mimetype", stored).map_err(export_error)?;
+    zip.write_all(MIMETYPE.as_bytes()).map_err(export_error)?;
+
+    let parts = [
+        ("META-INF/manifest.xml", MANIFEST.to_string()),
+        ("meta.xml", meta_xml(document)),
+        ("styles.xml", styles_xml(document)),
+        ("content.xml", body.content_xml()),
+    ];
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, xml) in parts {
+        zip.start_file(name, options).map_err(export_error)?;
+        zip.write_all(xml.as_bytes()).map_err(export_error)?;
+    }
+    Ok(zip.finish().map_err(export_error)?.into_inner())
+}
+
+fn export_error(e: impl std::fmt::Display) -> DocEngineError {
+    DocEngineError::ExportError(format!("Erreur ODT: {}", e))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Corps du document
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+struct OdtBody {
+    xml: String,
+    tables: usize,
+    clauses: usize,
+    annexes: usize,
+}
+
+impl OdtBody {
+    /// Écrit le bloc `index` (ou la liste qui y commence) ; renvoie l'indice suivant
+    fn block(&mut self, blocks: &[Block], index: usize) -> usize {
+        match &blocks[index] {
+            Block::Title(text) => self.paragraph("Title", &[office::plain(text)]),
+            Block::Heading { level, text } => {
+                self.heading(&format!("Heading_20_{}", level), *level, text)
+            }
+            Block::Paragraph { kind, spans } => {
+                let style = match kind {
+                    ParagraphKind::Body => "Text_20_body",
+                    ParagraphKind::Quote => "Quotations",
+                    ParagraphKind::Subtitle => "Subtitle",
+                };
+                self.paragraph(style, spans)
+            }
+            Block::ListItem { list, .. } => {
+                let end = blocks[index..]
+                    .iter()
+                    .position(|b| !matches!(b, Block::ListItem { list: l, level, .. } if l == list || *level > 0))
+                    .map_or(blocks.len(), |offset| index + offset);
+                self.list(&blocks[index..end]);
+                return end;
+            }
+            Block::ClauseTitle(text) => {
+                // Une liste par clause, la numérotation continue d'une clause à l'autre
+                let continued = if self.clauses > 0 {
+                    r##" text:continue-numbering="true""##
+                } else {
+                    ""
+                };
+                self.clauses += 1;
+                self.xml.push_str(&format!(
+                    r##"<text:list text:style-name="Clauses"{}><text:list-item><text:h text:style-name="Clause_20_Title" text:outline-level="3">{}</text:h></text:list-item></text:list>"##,
+                    continued,
+                    odt_text(text)
+                ));
+            }
+            Block::AnnexTitle { text, numbered } => {
+                if *numbered {
+                    let continued = if self.annexes > 0 {
+                        r##" text:continue-numbering="true""##
+                    } else {
+                        ""
+                    };
+                    self.annexes += 1;
+                    self.xml.push_str(&format!(
+                        r##"<text:list text:style-name="Annexes"{}><text:list-item><text:h text:style-name="Annex_20_Title" text:outline-level="1">{}</text:h></text:list-item></text:list>"##,
+                        continued,
+                        odt_text(text)
+                    ));
+                } else {
+                    self.heading("Annex_20_Title", 1, text);
+                }
+            }
+            Block::Table { header, rows } => self.table(header, rows),
+            Block::Code(lines) => {
+                for line in lines {
+                    self.paragraph("Preformatted_20_Text", &[office::plain(line)]);
+                }
+            }
+            Block::PageBreak => self
+                .xml
+                .push_str(r##"<text:p text:style-name="PageBreak"/>"##),
+        }
+        index + 1
+    }
+
+    fn heading(&mut self, style: &str, level: u8, text: &str) {
+        self.xml.push_str(&format!(
+            r##"<text:h text:style-name="{}" text:outline-level="{}">{}</text:h>"##,
+            style,
+            level,
+            odt_text(text)
+        ));
+    }
+
+    fn paragraph(&mut self, style: &str, spans: &[Span]) {
+        self.xml
+            .push_str(&format!(r##"<text:p text:style-name="{}">"##, style));
+        self.spans(spans);
+        self.xml.push_str("</text:p>");
+    }
+
+    fn spans(&mut self, spans: &[Span]) {
+        for span in spans {
+            let style = match (span.code, span.bold, span.italic) {
+                (true, _, _) => Some("Source_20_Text"),
+                (false, true, true) => Some("Strong_20_Emphasis_20_Italic"),
+                (false, true, false) => Some("Strong_20_Emphasis"),
+                (false, false, true) => Some("Emphasis"),
+                (false, false, false) => None,
+            };
+            if let Some(url) = &span.link {
+                self.xml.push_str(&format!(
+                    r##"<text:a xlink:type="simple" xlink:href="{}">"##,
+                    escape_xml(url)
+                ));
+            }
+            match style {
+                Some(style) => self.xml.push_str(&format!(
+                    r##"<text:span text:style-name="{}">{}</text:span>"##,
+                    style,
+                    odt_text(&span.text)
+                )),
+                None => self.xml.push_str(&odt_text(&span.text)),
+            }
+            if span.link.is_some() {
+                self.xml.push_str("</text:a>");
+            }
+        }
+    }
+
+    /// Liste (éventuellement imbriquée) formée d'éléments consécutifs
+    fn list(&mut self, items: &[Block]) {
+        let ordered = matches!(items.first(), Some(Block::ListItem { ordered: true, .. }));
+        let style = if ordered {
+            "Numbering_20_123"
+        } else {
+            "List_20_1"
+        };
+        self.xml
+            .push_str(&format!(r##"<text:list text:style-name="{}">"##, style));
+
+        // Niveau ouvert le plus profond ; chaque niveau est une liste dans l'élément parent
+        let mut depth = 0u8;
+        for (i, item) in items.iter().enumerate() {
+            let Block::ListItem { level, spans, .. } = item else {
+                continue;
+            };
+            if i > 0 {
+                while depth > *level {
+                    self.xml.push_str("</text:list-item></text:list>");
+                    depth -= 1;
+                }
+                if *level > depth {
+                    while depth < *level {
+                        self.xml.push_str("<text:list>");
+                        depth += 1;
+                        if depth < *level {
+                            self.xml.push_str("<text:list-item>");
+                        }
+                    }
+                } else {
+                    self.xml.push_str("</text:list-item>");
+                }
+            } else {
+                while depth < *level {
+                    self.xml.push_str("<text:list-item><text:list>");
+                    depth += 1;
+                }
+            }
+            self.xml
+                .push_str(r##"<text:list-item><text:p text:style-name="List_20_Contents">"##);
+            self.spans(spans);
+            self.xml.push_str("</text:p>");
+        }
+        while depth > 0 {
+            self.xml.push_str("</text:list-item></text:list>");
+            depth -= 1;
+        }
+        self.xml.push_str("</text:list-item></text:list>");
+    }
+
+    fn table(&mut self, header: &[Vec<Span>], rows: &[Vec<Vec<Span>>]) {
+        let columns = rows
+            .iter()
+            .map(Vec::len)
+            .chain([header.len()])
+            .max()
+            .unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        self.tables += 1;
+        self.xml.push_str(&format!(
+            r##"<table:table table:name="Tableau{}" table:style-name="Table"><table:table-column table:number-columns-repeated="{}"/>"##,
+            self.tables, columns
+        ));
+        if !header.is_empty() {
+            self.xml.push_str("<table:table-header-rows>");
+            self.table_row(header, columns, "Table_20_Heading");
+            self.xml.push_str("</table:table-header-rows>");
+        }
+        for row in rows {
+            self.table_row(row, columns, "Table_20_Contents");
+        }
+        self.xml.push_str("</table:table>");
+    }
+
+    fn table_row(&mut self, cells: &[Vec<Span>], columns: usize, style: &str) {
+        self.xml.push_str("<table:table-row>");
+        for index in 0..columns {
+            self.xml.push_str(
+                r##"<table:table-cell table:style-name="TableCell" office:value-type="string">"##,
+            );
+            let spans = cells.get(index).cloned().unwrap_or_default();
+            self.paragraph(style, &spans);
+            self.xml.push_str("</table:table-cell>");
+        }
+        self.xml.push_str("</table:table-row>");
+    }
+
+    fn content_xml(&self) -> String {
+        format!(
+            r##"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content {ns} office:version="1.3"><office:automatic-styles><style:style style:name="PageBreak" style:family="paragraph" style:parent-style-name="Standard"><style:paragraph-properties fo:break-before="page"/></style:style><style:style style:name="Table" style:family="table"><style:table-properties style:width="17cm" table:align="margins" fo:margin-top="0.2cm" fo:margin-bottom="0.2cm"/></style:style><style:style style:name="TableCell" style:family="table-cell"><style:table-cell-properties fo:padding="0.1cm" fo:border="0.5pt solid #bbbbbb"/></style:style></office:automatic-styles><office:body><office:text>{body}</office:text></office:body></office:document-content>"##,
+            ns = NAMESPACES,
+            body = self.xml
+        )
+    }
+}
+
+/// Texte ODF : espaces multiples, tabulations et retours à la ligne explicites
+fn odt_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut spaces = 0usize;
+    let flush_spaces = |out: &mut String, spaces: &mut usize| {
+        match *spaces {
+            0 => {}
+            1 => out.push(' '),
+            n => out.push_str(&format!(r##" <text:s text:c="{}"/>"##, n - 1)),
+        }
+        *spaces = 0;
+    };
+    for c in text.chars() {
+        if c == ' ' {
+            spaces += 1;
+            continue;
+        }
+        flush_spaces(&mut out, &mut spaces);
+        match c {
+            '\t' => out.push_str("<text:tab/>"),
+            '\n' => out.push_str("<text:line-break/>"),
+            c => out.push_str(&escape_xml(&c.to_string())),
+        }
+    }
+    flush_spaces(&mut out, &mut spaces);
+    // Espace initial : ODF ignore les espaces en début de paragraphe
+    if let Some(rest) = out.strip_prefix(' ') {
+        return format!(r##"<text:s/>{}"##, rest);
+    }
+    out
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Styles, métadonnées et manifeste
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn styles_xml(document: &Document) -> String {
+    let heading = |level: u8, size: &str| {
+        format!(
+            r##"<style:style style:name="Heading_20_{l}" style:display-name="Heading {l}" style:family="paragraph" style:parent-style-name="Heading" style:next-style-name="Text_20_body" style:default-outline-level="{l}" style:class="text"><style:text-properties fo:font-size="{s}" fo:font-weight="bold"/></style:style>"##,
+            l = level,
+            s = size
+        )
+    };
+    let headings: String = [
+        (1, "16pt"),
+        (2, "14pt"),
+        (3, "12pt"),
+        (4, "11pt"),
+        (5, "11pt"),
+        (6, "10.5pt"),
+    ]
+    .iter()
+    .map(|(level, size)| heading(*level, size))
+    .collect();
+
+    let bullet_levels: String = (1..=10)
+        .map(|level| {
+            format!(
+                r##"<text:list-level-style-bullet text:level="{l}" text:bullet-char="{c}"><style:list-level-properties text:list-level-position-and-space-mode="label-alignment"><style:list-level-label-alignment text:label-followed-by="listtab" fo:text-indent="-0.635cm" fo:margin-left="{m:.3}cm"/></style:list-level-properties></text:list-level-style-bullet>"##,
+                l = level,
+                c = ["•", "◦", "▪"][(level - 1) % 3],
+                m = 0.635 * level as f32 + 0.635
+            )
+        })
+        .collect();
+    let number_levels: String = (1..=10)
+        .map(|level| {
+            format!(
+                r##"<text:list-level-style-number text:level="{l}" style:num-suffix="." style:num-format="1"><style:list-level-properties text:list-level-position-and-space-mode="label-alignment"><style:list-level-label-alignment text:label-followed-by="listtab" fo:text-indent="-0.635cm" fo:margin-left="{m:.3}cm"/></style:list-level-properties></text:list-level-style-number>"##,
+                l = level,
+                m = 0.635 * level as f32 + 0.635
+            )
+        })
+        .collect();
+    let (clause_prefix, clause_suffix) = if office::numbers_clauses(document) {
+        ("Article ", " —")
+    } else {
+        ("", ".")
+    };
+
+    format!(
+        r##"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-styles {ns} office:version="1.3"><office:styles><style:default-style style:family="paragraph"><style:paragraph-properties fo:margin-bottom="0.21cm"/><style:text-properties style:font-name="Liberation Sans" fo:font-family="'Liberation Sans', Arial, sans-serif" fo:font-size="11pt" fo:language="fr" fo:country="FR"/></style:default-style><style:style style:name="Standard" style:family="paragraph" style:class="text"/><style:style style:name="Text_20_body" style:display-name="Text body" style:family="paragraph" style:parent-style-name="Standard" style:class="text"><style:paragraph-properties fo:margin-bottom="0.25cm" fo:line-height="115%"/></style:style><style:style style:name="Title" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="Subtitle" style:class="chapter"><style:text-properties fo:font-size="24pt" fo:font-weight="bold" fo:color="#1f2d3d"/></style:style><style:style style:name="Subtitle" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="Text_20_body" style:class="chapter"><style:paragraph-properties fo:margin-bottom="0.6cm" fo:border-bottom="0.75pt solid #2f6fad" fo:padding-bottom="0.1cm"/><style:text-properties fo:font-size="9pt" fo:color="#666666"/></style:style><style:style style:name="Heading" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="Text_20_body" style:class="text"><style:paragraph-properties fo:margin-top="0.42cm" fo:margin-bottom="0.21cm" fo:keep-with-next="always"/><style:text-properties fo:color="#1f2d3d"/></style:style>{headings}<style:style style:name="Clause_20_Title" style:display-name="Clause Title" style:family="paragraph" style:parent-style-name="Heading_20_3" style:next-style-name="Text_20_body" style:default-outline-level="3"/><style:style style:name="Annex_20_Title" style:display-name="Annex Title" style:family="paragraph" style:parent-style-name="Heading_20_1" style:next-style-name="Text_20_body" style:default-outline-level="1"/><style:style style:name="Quotations" style:family="paragraph" style:parent-style-name="Standard" style:class="html"><style:paragraph-properties fo:margin-left="1cm" fo:border-left="2pt solid #cccccc" fo:padding-left="0.2cm"/><style:text-properties fo:font-style="italic" fo:color="#555555"/></style:style><style:style style:name="List_20_Contents" style:display-name="List Contents" style:family="paragraph" style:parent-style-name="Standard" style:class="list"><style:paragraph-properties fo:margin-bottom="0.1cm"/></style:style><style:style style:name="Preformatted_20_Text" style:display-name="Preformatted Text" style:family="paragraph" style:parent-style-name="Standard" style:class="html"><style:paragraph-properties fo:margin-bottom="0cm" fo:background-color="#f6f8fa"/><style:text-properties style:font-name="Liberation Mono" fo:font-family="'Liberation Mono', Consolas, monospace" fo:font-size="9pt"/></style:style><style:style style:name="Table_20_Contents" style:display-name="Table Contents" style:family="paragraph" style:parent-style-name="Standard" style:class="extra"><style:paragraph-properties fo:margin-bottom="0cm"/></style:style><style:style style:name="Table_20_Heading" style:display-name="Table Heading" style:family="paragraph" style:parent-style-name="Table_20_Contents" style:class="extra"><style:text-properties fo:font-weight="bold"/></style:style><style:style style:name="Header" style:family="paragraph" style:parent-style-name="Standard" style:class="extra"><style:paragraph-properties><style:tab-stops><style:tab-stop style:position="17cm" style:type="right"/></style:tab-stops></style:paragraph-properties><style:text-properties fo:font-size="8pt" fo:color="#666666"/></style:style><style:style style:name="Footer" style:family="paragraph" style:parent-style-name="Header" style:class="extra"/><style:style style:name="Strong_20_Emphasis" style:display-name="Strong Emphasis" style:family="text"><style:text-properties fo:font-weight="bold"/></style:style><style:style style:name="Emphasis" style:family="text"><style:text-properties fo:font-style="italic"/></style:style><style:style style:name="Strong_20_Emphasis_20_Italic" style:display-name="Strong Emphasis Italic" style:family="text"><style:text-properties fo:font-weight="bold" fo:font-style="italic"/></style:style><style:style style:name="Source_20_Text" style:display-name="Source Text" style:family="text"><style:text-properties style:font-name="Liberation Mono" fo:font-family="'Liberation Mono', Consolas, monospace"/></style:style><text:list-style style:name="List_20_1" style:display-name="List 1">{bullets}</text:list-style><text:list-style style:name="Numbering_20_123" style:display-name="Numbering 123">{numbers}</text:list-style><text:list-style style:name="Clauses"><text:list-level-style-number text:level="1" style:num-prefix="{clause_prefix}" style:num-suffix="{clause_suffix}" style:num-format="1"><style:list-level-properties text:list-level-position-and-space-mode="label-alignment"><style:list-level-label-alignment text:label-followed-by="space"/></style:list-level-properties></text:list-level-style-number></text:list-style><text:list-style style:name="Annexes"><text:list-level-style-number text:level="1" style:num-prefix="Annexe " style:num-suffix=" —" style:num-format="A"><style:list-level-properties text:list-level-position-and-space-mode="label-alignment"><style:list-level-label-alignment text:label-followed-by="space"/></style:list-level-properties></text:list-level-style-number></text:list-style></office:styles><office:automatic-styles><style:page-layout style:name="A4"><style:page-layout-properties fo:page-width="21cm" fo:page-height="29.7cm" style:print-orientation="portrait" fo:margin-top="1.5cm" fo:margin-bottom="1.5cm" fo:margin-left="2cm" fo:margin-right="2cm"/><style:header-style><style:header-footer-properties fo:min-height="0.6cm" fo:margin-bottom="0.5cm"/></style:header-style><style:footer-style><style:header-footer-properties fo:min-height="0.6cm" fo:margin-top="0.5cm"/></style:footer-style></style:page-layout></office:automatic-styles><office:master-styles><style:master-page style:name="Standard" style:page-layout-name="A4"><style:header><text:p text:style-name="Header">{title}<text:tab/>{date}</text:p></style:header><style:footer><text:p text:style-name="Footer">Version {version}<text:tab/>Page <text:page-number text:select-page="current">1</text:page-number> / <text:page-count>1</text:page-count></text:p></style:footer></style:master-page></office:master-styles></office:document-styles>"##,
+        ns = NAMESPACES,
+        headings = headings,
+        bullets = bullet_levels,
+        numbers = number_levels,
+        clause_prefix = clause_prefix,
+        clause_suffix = clause_suffix,
+        title = odt_text(&document.content.title),
+        date = document.metadata.updated_at.format("%d/%m/%Y"),
+        version = escape_xml(&document.metadata.version),
+    )
+}
+
+fn meta_xml(document: &Document) -> String {
+    let metadata = &document.metadata;
+    let keywords: String = metadata
+        .tags
+        .iter()
+        .map(|tag| format!("<meta:keyword>{}</meta:keyword>", escape_xml(tag)))
+        .collect();
+    format!(
+        r##"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-meta {ns} office:version="1.3"><office:meta><meta:generator>TITANE∞ doc_engine</meta:generator><dc:title>{title}</dc:title><dc:subject>{subject}</dc:subject><meta:initial-creator>{author}</meta:initial-creator><dc:creator>{author}</dc:creator>{keywords}<meta:creation-date>{created}</meta:creation-date><dc:date>{modified}</dc:date></office:meta></office:document-meta>"##,
+        ns = NAMESPACES,
+        title = escape_xml(&document.content.title),
+        subject = escape_xml(&metadata.category),
+        author = escape_xml(&metadata.author),
+        keywords = keywords,
+        created = metadata.created_at.format("%Y-%m-%dT%H:%M:%S"),
+        modified = metadata.updated_at.format("%Y-%m-%dT%H:%M:%S"),
+    )
+}
+
+const MANIFEST: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.3"><manifest:file-entry manifest:full-path="/" manifest:version="1.3" manifest:media-type="application/vnd.oasis.opendocument.text"/><manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/><manifest:file-entry manifest:full-path="styles.xml" manifest:media-type="text/xml"/><manifest:file-entry manifest:full-path="meta.xml" manifest:media-type="text/xml"/></manifest:manifest>"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    fn well_formed(xml: &str) -> bool {
+        let mut reader = Reader::from_str(xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Eof) => return true,
+                Ok(_) => {}
+                Err(_) => return false,
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_odt_package() {
+        let mut document = test_document(DocumentType::Contract, DocumentStyle::Legal);
+        document.content.sections[0].content = "Prix < 100 € & **remise** de 5 %".to_string();
+        document.content.mandatory_clauses = Some(vec![Clause {
+            id: "objet".to_string(),
+            title: "Objet".to_string(),
+            content: "Le contrat a pour objet la prestation.".to_string(),
+            mandatory: true,
+            category: ClauseCategory::General,
+        }]);
+
+        let bytes = render_odt(&document).unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(bytes.as_slice())).unwrap();
+
+        let mut mimetype = archive.by_index(0).unwrap();
+        assert_eq!(mimetype.name(), "mimetype");
+        assert_eq!(mimetype.compression(), CompressionMethod::Stored);
+        let mut value = String::new();
+        mimetype.read_to_string(&mut value).unwrap();
+        assert_eq!(value, MIMETYPE);
+        drop(mimetype);
+
+        for name in ["META-INF/manifest.xml", "meta.xml", "styles.xml", "content.xml"] {
+            let mut xml = String::new();
+            archive.by_name(name).unwrap().read_to_string(&mut xml).unwrap();
+            assert!(well_formed(&xml), "{} mal formé", name);
+            if name == "content.xml" {
+                assert!(xml.contains("Introduction") && xml.contains("Contexte"));
+                assert!(xml.contains("Prix &lt; 100 € &amp;"), "{}", xml);
+                assert!(xml.contains("remise"));
+                assert!(xml.contains("Objet"));
+            }
+        }
+    }
+}
@@ -0,0 +1,446 @@
+// TITANE∞ v13 - Modèle commun des formats bureautiques (DOCX, ODT)
+// Le document est aplati en blocs typés (titres, paragraphes, listes, tableaux,
+// clauses numérotées, annexes) que chaque writer sérialise dans son format.
+
+use super::*;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+/// Segment de texte avec sa mise en forme
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Span {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    pub link: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ParagraphKind {
+    Body,
+    Quote,
+    /// Ligne de métadonnées sous le titre
+    Subtitle,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Block {
+    Title(String),
+    /// Titre de niveau 1 à 6 (styles « Titre 1 »…)
+    Heading {
+        level: u8,
+        text: String,
+    },
+    Paragraph {
+        kind: ParagraphKind,
+        spans: Vec<Span>,
+    },
+    /// Élément de liste ; `list` identifie l'instance (la numérotation repart à 1)
+    ListItem {
+        list: usize,
+        ordered: bool,
+        level: u8,
+        spans: Vec<Span>,
+    },
+    /// Intitulé de clause, numéroté automatiquement (« Article n — »)
+    ClauseTitle(String),
+    /// Intitulé d'annexe ; `numbered` ajoute « Annexe A — »
+    AnnexTitle {
+        text: String,
+        numbered: bool,
+    },
+    /// Tableau ; la ligne d'en-tête est mise en gras par son style de paragraphe
+    Table {
+        header: Vec<Vec<Span>>,
+        rows: Vec<Vec<Vec<Span>>>,
+    },
+    Code(Vec<String>),
+    PageBreak,
+}
+
+/// Intitulés des parties fixes, reconnus à l'import
+pub(crate) const SUMMARY_HEADING: &str = "Résumé exécutif";
+pub(crate) const OBJECTIVES_HEADING: &str = "Objectifs";
+pub(crate) const CLAUSES_HEADING: &str = "Clauses";
+pub(crate) const REFERENCES_HEADING: &str = "Références";
+
+/// Aplatit un document en blocs, dans l'ordre de rendu
+pub(crate) fn document_blocks(document: &Document) -> Vec<Block> {
+    let content = &document.content;
+    let mut builder = BlockBuilder::default();
+    builder.blocks.push(Block::Title(content.title.clone()));
+    builder.blocks.push(Block::Paragraph {
+        kind: ParagraphKind::Subtitle,
+        spans: vec![plain(&metadata_line(document))],
+    });
+
+    if !content.executive_summary.trim().is_empty() {
+        builder.heading(1, SUMMARY_HEADING);
+        builder.markdown(&content.executive_summary, 1);
+    }
+
+    if !content.objectives.is_empty() {
+        builder.heading(1, OBJECTIVES_HEADING);
+        let list = builder.next_list();
+        for objective in &content.objectives {
+            let spans = inline_spans(objective);
+            builder.blocks.push(Block::ListItem {
+                list,
+                ordered: false,
+                level: 0,
+                spans,
+            });
+        }
+    }
+
+    for section in &content.sections {
+        builder.section(section, 1);
+    }
+
+    if let Some(clauses) = content.mandatory_clauses.as_ref().filter(|c| !c.is_empty()) {
+        builder.heading(1, CLAUSES_HEADING);
+        for clause in clauses {
+            builder
+                .blocks
+                .push(Block::ClauseTitle(clause.title.clone()));
+            builder.markdown(&clause.content, 3);
+        }
+    }
+
+    for annex in &content.annexes {
+        builder.blocks.push(Block::PageBreak);
+        builder.blocks.push(Block::AnnexTitle {
+            text: annex.title.clone(),
+            numbered: !annex.title.to_lowercase().starts_with("annexe"),
+        });
+        match annex.format.to_lowercase().as_str() {
+            "yaml" | "json" | "code" | "csv" => builder.blocks.push(Block::Code(
+                annex.content.lines().map(String::from).collect(),
+            )),
+            _ => builder.markdown(&annex.content, 1),
+        }
+    }
+
+    if !content.references.is_empty() {
+        builder.heading(1, REFERENCES_HEADING);
+        let list = builder.next_list();
+        for reference in &content.references {
+            builder.blocks.push(Block::ListItem {
+                list,
+                ordered: true,
+                level: 0,
+                spans: reference_spans(reference),
+            });
+        }
+    }
+
+    builder.blocks
+}
+
+/// Documents dont les clauses sont numérotées « Article n »
+pub(crate) fn numbers_clauses(document: &Document) -> bool {
+    matches!(document.config.style, DocumentStyle::Legal)
+        || matches!(
+            document.config.doc_type,
+            DocumentType::Contract
+                | DocumentType::NDA
+                | DocumentType::ServiceAgreement
+                | DocumentType::Partnership
+                | DocumentType::TermsOfService
+                | DocumentType::PrivacyPolicy
+                | DocumentType::LegalNotice
+        )
+}
+
+pub(crate) fn metadata_line(document: &Document) -> String {
+    let mut line = format!(
+        "Version {} — {}",
+        document.metadata.version,
+        document.metadata.created_at.format("%d/%m/%Y")
+    );
+    if !document.metadata.author.is_empty() {
+        line.push_str(&format!(" — {}", document.metadata.author));
+    }
+    line
+}
+
+/// Référence : « titre — source (date) », lien sur le titre
+fn reference_spans(reference: &Reference) -> Vec<Span> {
+    let mut spans = vec![Span {
+        text: reference.title.clone(),
+        link: reference.url.clone(),
+        ..Span::default()
+    }];
+    let mut tail = format!(" — {}", reference.source);
+    if let Some(date) = &reference.date {
+        tail.push_str(&format!(" ({})", date));
+    }
+    spans.push(plain(&tail));
+    spans
+}
+
+pub(crate) fn plain(text: &str) -> Span {
+    Span {
+        text: text.to_string(),
+        ..Span::default()
+    }
+}
+
+/// Markdown d'une ligne → segments
+fn inline_spans(text: &str) -> Vec<Span> {
+    let mut builder = BlockBuilder::default();
+    builder.markdown(text, 0);
+    builder
+        .blocks
+        .into_iter()
+        .find_map(|block| match block {
+            Block::Paragraph { spans, .. } => Some(spans),
+            _ => None,
+        })
+        .unwrap_or_else(|| vec![plain(text)])
+}
+
+/// Échappe le texte pour un contenu ou un attribut XML
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Caractères de contrôle interdits en XML 1.0
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Markdown → blocs
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Ligne de tableau : cellules → segments
+type Row = Vec<Vec<Span>>;
+
+#[derive(Default)]
+struct BlockBuilder {
+    blocks: Vec<Block>,
+    spans: Vec<Span>,
+    lists_created: usize,
+    /// Listes ouvertes : (instance, ordonnée)
+    lists: Vec<(usize, bool)>,
+    in_item: bool,
+    quote: usize,
+    heading: Option<u8>,
+    bold: usize,
+    italic: usize,
+    link: Option<String>,
+    code: Option<String>,
+    /// Tableau en cours : (en-tête, lignes)
+    table: Option<(Row, Vec<Row>)>,
+    row: Row,
+    footnotes: Vec<String>,
+}
+
+impl BlockBuilder {
+    fn next_list(&mut self) -> usize {
+        self.lists_created += 1;
+        self.lists_created
+    }
+
+    fn heading(&mut self, level: u8, text: &str) {
+        self.blocks.push(Block::Heading {
+            level,
+            text: text.to_string(),
+        });
+    }
+
+    fn section(&mut self, section: &Section, depth: u8) {
+        self.heading(depth.min(6), &section.title);
+        self.markdown(&section.content, depth);
+        for subsection in &section.subsections {
+            self.section(subsection, depth + 1);
+        }
+    }
+
+    /// Ajoute un fragment Markdown ; ses titres sont décalés sous `depth`
+    fn markdown(&mut self, markdown: &str, depth: u8) {
+        let mut options = Options::empty();
+        options.insert(Options::ENABLE_TABLES);
+        options.insert(Options::ENABLE_FOOTNOTES);
+        options.insert(Options::ENABLE_STRIKETHROUGH);
+
+        self.footnotes.clear();
+        for event in Parser::new_ext(markdown, options) {
+            self.event(event, depth);
+        }
+        self.flush();
+    }
+
+    fn event(&mut self, event: Event, depth: u8) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                self.flush();
+                self.heading = Some((depth + level as u8).min(6));
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                let level = self.heading.take().unwrap_or(1);
+                let text: String = self.spans.drain(..).map(|s| s.text).collect();
+                self.heading(level, text.trim());
+            }
+            Event::End(TagEnd::Paragraph) => self.flush(),
+            Event::Start(Tag::BlockQuote(_)) => {
+                self.flush();
+                self.quote += 1;
+            }
+            Event::End(TagEnd::BlockQuote(_)) => {
+                self.flush();
+                self.quote = self.quote.saturating_sub(1);
+            }
+            Event::Start(Tag::List(start)) => {
+                self.flush();
+                let list = self.next_list();
+                self.lists.push((list, start.is_some()));
+            }
+            Event::End(TagEnd::List(_)) => {
+                self.flush();
+                self.lists.pop();
+            }
+            Event::Start(Tag::Item) => {
+                self.flush();
+                self.in_item = true;
+            }
+            Event::End(TagEnd::Item) => self.flush(),
+            Event::Start(Tag::CodeBlock(_)) => {
+                self.flush();
+                self.code = Some(String::new());
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(code) = self.code.take() {
+                    let lines = code
+                        .trim_end_matches('\n')
+                        .lines()
+                        .map(String::from)
+                        .collect();
+                    self.blocks.push(Block::Code(lines));
+                }
+            }
+            Event::Start(Tag::Strong) => self.bold += 1,
+            Event::End(TagEnd::Strong) => self.bold = self.bold.saturating_sub(1),
+            Event::Start(Tag::Emphasis) => self.italic += 1,
+            Event::End(TagEnd::Emphasis) => self.italic = self.italic.saturating_sub(1),
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                self.link = Some(dest_url.to_string()).filter(|url| !url.starts_with('#'))
+            }
+            Event::End(TagEnd::Link) => self.link = None,
+            Event::Start(Tag::Table(_)) => {
+                self.flush();
+                self.table = Some((Vec::new(), Vec::new()));
+            }
+            Event::End(TagEnd::TableHead) => {
+                if let Some((header, _)) = self.table.as_mut() {
+                    *header = std::mem::take(&mut self.row);
+                }
+            }
+            Event::End(TagEnd::TableCell) => {
+                let cell = std::mem::take(&mut self.spans);
+                self.row.push(cell);
+            }
+            Event::End(TagEnd::TableRow) => {
+                let row = std::mem::take(&mut self.row);
+                if let Some((_, rows)) = self.table.as_mut() {
+                    rows.push(row);
+                }
+            }
+            Event::End(TagEnd::Table) => {
+                if let Some((header, rows)) = self.table.take() {
+                    self.blocks.push(Block::Table { header, rows });
+                }
+            }
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                self.flush();
+                let number = self.footnote_number(&label);
+                self.text(&format!("[{}] ", number), false);
+            }
+            Event::End(TagEnd::FootnoteDefinition) => self.flush(),
+            Event::FootnoteReference(label) => {
+                let number = self.footnote_number(&label);
+                self.text(&format!("[{}]", number), false);
+            }
+            Event::Text(text) => self.text(&text, false),
+            Event::Code(text) => self.text(&text, true),
+            Event::SoftBreak => self.text(" ", false),
+            Event::HardBreak => self.text("\n", false),
+            Event::TaskListMarker(done) => self.text(if done { "☑ " } else { "☐ " }, false),
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str, code: bool) {
+        if let Some(block) = self.code.as_mut() {
+            block.push_str(text);
+            return;
+        }
+        let span = Span {
+            text: text.to_string(),
+            bold: self.bold > 0,
+            italic: self.italic > 0,
+            code,
+            link: self.link.clone(),
+        };
+        match self.spans.last_mut() {
+            Some(last)
+                if last.bold == span.bold
+                    && last.italic == span.italic
+                    && last.code == span.code
+                    && last.link == span.link =>
+            {
+                last.text.push_str(text)
+            }
+            _ => self.spans.push(span),
+        }
+    }
+
+    fn footnote_number(&mut self, label: &str) -> usize {
+        match self.footnotes.iter().position(|l| l == label) {
+            Some(i) => i + 1,
+            None => {
+                self.footnotes.push(label.to_string());
+                self.footnotes.len()
+            }
+        }
+    }
+
+    /// Termine le paragraphe ou l'élément de liste en cours
+    fn flush(&mut self) {
+        if self.table.is_some() {
+            return;
+        }
+        let spans = std::mem::take(&mut self.spans);
+        if spans.iter().all(|s| s.text.trim().is_empty()) {
+            return;
+        }
+        match self.lists.last() {
+            Some(&(list, ordered)) if self.in_item => {
+                self.blocks.push(Block::ListItem {
+                    list,
+                    ordered,
+                    level: (self.lists.len() - 1).min(8) as u8,
+                    spans,
+                });
+                self.in_item = false;
+            }
+            _ => {
+                let kind = if self.quote > 0 {
+                    ParagraphKind::Quote
+                } else {
+                    ParagraphKind::Body
+                };
+                self.blocks.push(Block::Paragraph { kind, spans });
+            }
+        }
+    }
+}
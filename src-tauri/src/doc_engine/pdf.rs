@@ -0,0 +1,1081 @@
+// TITANE∞ v13 - Export PDF natif
+// Mise en page pure Rust (printpdf) : polices DejaVu embarquées, en-têtes et
+// pieds de page (version, date, pagination), saut de page avant chaque annexe,
+// bloc de signature pour les contrats et métadonnées PDF.
+
+use super::*;
+use printpdf::{
+    IndirectFontRef, Line, Mm, OffsetDateTime, PdfDocument, PdfDocumentReference,
+    PdfLayerReference, Point,
+};
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use std::io::Cursor;
+
+static FONT_REGULAR: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+static FONT_BOLD: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf");
+
+// A4 portrait, dimensions en millimètres
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN_X: f32 = 22.0;
+const MARGIN_TOP: f32 = 25.0;
+const MARGIN_BOTTOM: f32 = 22.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN_X;
+const PT_TO_MM: f32 = 0.352_778;
+
+const BODY_SIZE: f32 = 10.5;
+const SMALL_SIZE: f32 = 8.5;
+const CODE_SIZE: f32 = 9.0;
+const LINE_SPACING: f32 = 1.4;
+
+/// Génère le PDF d'un document (octets du fichier)
+pub fn render_pdf(document: &Document) -> Result<Vec<u8>> {
+    let metrics = FontMetrics::load()?;
+    let blocks = document_blocks(document);
+
+    let mut layout = Layout::new(&metrics);
+    for block in &blocks {
+        layout.place(block);
+    }
+
+    let pages = layout.finish();
+    write_pdf(document, &metrics, pages)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Blocs de contenu
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Weight {
+    Regular,
+    Bold,
+}
+
+#[derive(Debug, Clone)]
+struct Run {
+    text: String,
+    weight: Weight,
+}
+
+impl Run {
+    fn new(text: impl Into<String>, weight: Weight) -> Self {
+        Self {
+            text: text.into(),
+            weight,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Block {
+    /// Titre ; `bookmark` ajoute un signet PDF
+    Heading {
+        text: String,
+        size: f32,
+        bookmark: bool,
+    },
+    /// Paragraphe ; `prefix` (puce, numéro) est suspendu dans le retrait
+    Paragraph {
+        runs: Vec<Run>,
+        size: f32,
+        indent: f32,
+        prefix: Option<String>,
+    },
+    Code {
+        lines: Vec<String>,
+    },
+    Rule,
+    Spacer(f32),
+    PageBreak,
+    Signature {
+        place: String,
+        parties: Vec<(String, String)>,
+    },
+}
+
+fn heading_size(depth: usize) -> f32 {
+    match depth {
+        0 => 20.0,
+        1 => 15.0,
+        2 => 13.0,
+        3 => 11.5,
+        _ => 11.0,
+    }
+}
+
+fn document_blocks(document: &Document) -> Vec<Block> {
+    let content = &document.content;
+    let mut blocks = vec![
+        Block::Heading {
+            text: content.title.clone(),
+            size: heading_size(0),
+            bookmark: false,
+        },
+        Block::Paragraph {
+            runs: vec![Run::new(metadata_line(document), Weight::Regular)],
+            size: SMALL_SIZE,
+            indent: 0.0,
+            prefix: None,
+        },
+        Block::Rule,
+    ];
+
+    if !content.executive_summary.trim().is_empty() {
+        blocks.push(section_heading("Résumé exécutif", 1));
+        blocks.extend(markdown_blocks(&content.executive_summary, 1));
+    }
+
+    if !content.objectives.is_empty() {
+        blocks.push(section_heading("Objectifs", 1));
+        for objective in &content.objectives {
+            blocks.push(Block::Paragraph {
+                runs: inline_runs(objective),
+                size: BODY_SIZE,
+                indent: 5.0,
+                prefix: Some("•".to_string()),
+            });
+        }
+    }
+
+    for (i, section) in content.sections.iter().enumerate() {
+        push_section(&mut blocks, section, &(i + 1).to_string(), 1);
+    }
+
+    if let Some(clauses) = content.mandatory_clauses.as_ref().filter(|c| !c.is_empty()) {
+        let numbered = is_legal(document);
+        blocks.push(section_heading("Clauses", 1));
+        for (i, clause) in clauses.iter().enumerate() {
+            let title = if numbered {
+                format!("Article {} — {}", i + 1, clause.title)
+            } else {
+                clause.title.clone()
+            };
+            blocks.push(Block::Heading {
+                text: title,
+                size: heading_size(2),
+                bookmark: false,
+            });
+            blocks.extend(markdown_blocks(&clause.content, 2));
+        }
+    }
+
+    if needs_signature(&document.config.doc_type) {
+        blocks.push(signature_block(document));
+    }
+
+    for (i, annex) in content.annexes.iter().enumerate() {
+        blocks.push(Block::PageBreak);
+        blocks.push(section_heading(&html::annex_title(annex, i), 1));
+        match annex.format.to_lowercase().as_str() {
+            "yaml" | "json" | "code" | "csv" => blocks.push(Block::Code {
+                lines: annex.content.lines().map(String::from).collect(),
+            }),
+            _ => blocks.extend(markdown_blocks(&annex.content, 1)),
+        }
+    }
+
+    if !content.references.is_empty() {
+        blocks.push(section_heading("Références", 1));
+        for (i, reference) in content.references.iter().enumerate() {
+            let mut runs = vec![
+                Run::new(reference.title.clone(), Weight::Bold),
+                Run::new(format!(" — {}", reference.source), Weight::Regular),
+            ];
+            if let Some(date) = &reference.date {
+                runs.push(Run::new(format!(" ({})", date), Weight::Regular));
+            }
+            if let Some(url) = &reference.url {
+                runs.push(Run::new(format!(" {}", url), Weight::Regular));
+            }
+            blocks.push(Block::Paragraph {
+                runs,
+                size: BODY_SIZE,
+                indent: 7.0,
+                prefix: Some(format!("{}.", i + 1)),
+            });
+        }
+    }
+
+    blocks
+}
+
+fn push_section(blocks: &mut Vec<Block>, section: &Section, number: &str, depth: usize) {
+    blocks.push(Block::Heading {
+        text: format!("{} {}", number, section.title),
+        size: heading_size(depth),
+        bookmark: depth == 1,
+    });
+    blocks.extend(markdown_blocks(&section.content, depth));
+    for (i, subsection) in section.subsections.iter().enumerate() {
+        push_section(
+            blocks,
+            subsection,
+            &format!("{}.{}", number, i + 1),
+            depth + 1,
+        );
+    }
+}
+
+fn section_heading(title: &str, depth: usize) -> Block {
+    Block::Heading {
+        text: title.to_string(),
+        size: heading_size(depth),
+        bookmark: depth == 1,
+    }
+}
+
+fn metadata_line(document: &Document) -> String {
+    let mut line = format!(
+        "Version {} — {}",
+        document.metadata.version,
+        document.metadata.created_at.format("%d/%m/%Y")
+    );
+    if !document.metadata.author.is_empty() {
+        line.push_str(&format!(" — {}", document.metadata.author));
+    }
+    line
+}
+
+fn is_legal(document: &Document) -> bool {
+    matches!(document.config.style, DocumentStyle::Legal)
+        || matches!(
+            document.config.doc_type,
+            DocumentType::Contract
+                | DocumentType::NDA
+                | DocumentType::ServiceAgreement
+                | DocumentType::Partnership
+                | DocumentType::TermsOfService
+                | DocumentType::PrivacyPolicy
+                | DocumentType::LegalNotice
+        )
+}
+
+fn needs_signature(doc_type: &DocumentType) -> bool {
+    matches!(
+        doc_type,
+        DocumentType::Contract | DocumentType::NDA | DocumentType::ServiceAgreement
+    )
+}
+
+/// Parties signataires : mêmes paramètres que `legal::extract_parties`
+fn signature_block(document: &Document) -> Block {
+    let params = &document.config.custom_params;
+    let party = |n: u8, default_name: &str, default_role: &str| {
+        (
+            params
+                .get(&format!("party{}_name", n))
+                .cloned()
+                .unwrap_or_else(|| default_name.to_string()),
+            params
+                .get(&format!("party{}_role", n))
+                .cloned()
+                .unwrap_or_else(|| default_role.to_string()),
+        )
+    };
+
+    Block::Signature {
+        place: params.get("place").cloned().unwrap_or_default(),
+        parties: vec![
+            party(1, "Partie 1", "Fournisseur"),
+            party(2, "Partie 2", "Client"),
+        ],
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Markdown → blocs
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Markdown d'une ligne → segments (gras conservé)
+fn inline_runs(text: &str) -> Vec<Run> {
+    markdown_blocks(text, 1)
+        .into_iter()
+        .find_map(|block| match block {
+            Block::Paragraph { runs, .. } => Some(runs),
+            _ => None,
+        })
+        .unwrap_or_else(|| vec![Run::new(text, Weight::Regular)])
+}
+
+/// Convertit un fragment Markdown ; les titres sont décalés sous `depth`
+fn markdown_blocks(markdown: &str, depth: usize) -> Vec<Block> {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+
+    let mut builder = BlockBuilder::default();
+    for event in Parser::new_ext(markdown, options) {
+        builder.event(event, depth);
+    }
+    builder.blocks
+}
+
+#[derive(Default)]
+struct BlockBuilder {
+    blocks: Vec<Block>,
+    runs: Vec<Run>,
+    bold: usize,
+    /// Listes ouvertes : prochain numéro (ou `None` pour une liste à puces)
+    lists: Vec<Option<u64>>,
+    item_prefix: Option<String>,
+    quote: usize,
+    heading: Option<HeadingLevel>,
+    code: Option<String>,
+    table_row: Vec<String>,
+    table_cell: Option<String>,
+    table_head: bool,
+    footnotes: Vec<String>,
+    link: Option<String>,
+}
+
+impl BlockBuilder {
+    fn event(&mut self, event: Event, depth: usize) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                self.flush();
+                self.heading = Some(level);
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                let level = self.heading.take().map(|l| l as usize).unwrap_or(1);
+                let text: String = self.runs.drain(..).map(|r| r.text).collect();
+                self.blocks.push(Block::Heading {
+                    text,
+                    size: heading_size(depth + level),
+                    bookmark: false,
+                });
+            }
+            Event::Start(Tag::Paragraph) => {}
+            Event::End(TagEnd::Paragraph) => self.flush(),
+            Event::Start(Tag::BlockQuote(_)) => {
+                self.flush();
+                self.quote += 1;
+            }
+            Event::End(TagEnd::BlockQuote(_)) => {
+                self.flush();
+                self.quote = self.quote.saturating_sub(1);
+            }
+            Event::Start(Tag::List(start)) => {
+                self.flush();
+                self.lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                self.flush();
+                self.lists.pop();
+            }
+            Event::Start(Tag::Item) => {
+                self.flush();
+                self.item_prefix = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        let prefix = format!("{}.", n);
+                        *n += 1;
+                        Some(prefix)
+                    }
+                    _ => Some("•".to_string()),
+                };
+            }
+            Event::End(TagEnd::Item) => self.flush(),
+            Event::Start(Tag::CodeBlock(_)) => {
+                self.flush();
+                self.code = Some(String::new());
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(code) = self.code.take() {
+                    self.blocks.push(Block::Code {
+                        lines: code
+                            .trim_end_matches('\n')
+                            .lines()
+                            .map(String::from)
+                            .collect(),
+                    });
+                }
+            }
+            Event::Start(Tag::Strong) | Event::Start(Tag::TableHead) => {
+                self.bold += 1;
+                self.table_head |= matches!(event, Event::Start(Tag::TableHead));
+            }
+            Event::End(TagEnd::Strong) => self.bold = self.bold.saturating_sub(1),
+            Event::End(TagEnd::TableHead) => {
+                self.bold = self.bold.saturating_sub(1);
+                self.push_table_row();
+                self.table_head = false;
+            }
+            Event::Start(Tag::TableCell) => self.table_cell = Some(String::new()),
+            Event::End(TagEnd::TableCell) => {
+                if let Some(cell) = self.table_cell.take() {
+                    self.table_row.push(cell.trim().to_string());
+                }
+            }
+            Event::End(TagEnd::TableRow) => self.push_table_row(),
+            Event::End(TagEnd::Table) => self.blocks.push(Block::Spacer(1.5)),
+            Event::Start(Tag::Link { dest_url, .. }) => self.link = Some(dest_url.to_string()),
+            Event::End(TagEnd::Link) => {
+                if let Some(url) = self.link.take() {
+                    let label: String = self.runs.iter().map(|r| r.text.as_str()).collect();
+                    if !url.starts_with('#') && !label.ends_with(url.as_str()) {
+                        self.text(&format!(" ({})", url));
+                    }
+                }
+            }
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                self.flush();
+                let number = self.footnote_number(&label);
+                self.item_prefix = Some(format!("[{}]", number));
+            }
+            Event::End(TagEnd::FootnoteDefinition) => self.flush(),
+            Event::FootnoteReference(label) => {
+                let number = self.footnote_number(&label);
+                self.text(&format!("[{}]", number));
+            }
+            Event::Text(text) | Event::Code(text) => self.text(&text),
+            Event::SoftBreak => self.text(" "),
+            Event::HardBreak => self.text("\n"),
+            Event::Rule => {
+                self.flush();
+                self.blocks.push(Block::Rule);
+            }
+            Event::TaskListMarker(done) => self.text(if done { "☑ " } else { "☐ " }),
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some(code) = self.code.as_mut() {
+            code.push_str(text);
+        } else if let Some(cell) = self.table_cell.as_mut() {
+            cell.push_str(text);
+        } else {
+            let weight = if self.bold > 0 || self.heading.is_some() {
+                Weight::Bold
+            } else {
+                Weight::Regular
+            };
+            match self.runs.last_mut() {
+                Some(last) if last.weight == weight => last.text.push_str(text),
+                _ => self.runs.push(Run::new(text, weight)),
+            }
+        }
+    }
+
+    fn push_table_row(&mut self) {
+        if self.table_row.is_empty() {
+            return;
+        }
+        let weight = if self.table_head {
+            Weight::Bold
+        } else {
+            Weight::Regular
+        };
+        let row = std::mem::take(&mut self.table_row).join("  |  ");
+        self.blocks.push(Block::Paragraph {
+            runs: vec![Run::new(row, weight)],
+            size: SMALL_SIZE + 0.5,
+            indent: 2.0,
+            prefix: None,
+        });
+    }
+
+    fn footnote_number(&mut self, label: &str) -> usize {
+        match self.footnotes.iter().position(|l| l == label) {
+            Some(i) => i + 1,
+            None => {
+                self.footnotes.push(label.to_string());
+                self.footnotes.len()
+            }
+        }
+    }
+
+    /// Termine le paragraphe en cours (liste, citation ou texte simple)
+    fn flush(&mut self) {
+        let runs: Vec<Run> = std::mem::take(&mut self.runs);
+        if runs.iter().all(|r| r.text.trim().is_empty()) {
+            return;
+        }
+        let list_indent = self.lists.len().saturating_sub(1) as f32 * 5.0;
+        let prefix = self.item_prefix.take();
+        let indent = self.quote as f32 * 6.0
+            + list_indent
+            + if prefix.is_some() || !self.lists.is_empty() {
+                6.0
+            } else {
+                0.0
+            };
+        self.blocks.push(Block::Paragraph {
+            runs,
+            size: BODY_SIZE,
+            indent,
+            prefix,
+        });
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Mise en page
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct FontMetrics {
+    regular: ttf_parser::Face<'static>,
+    bold: ttf_parser::Face<'static>,
+}
+
+impl FontMetrics {
+    fn load() -> Result<Self> {
+        let parse = |bytes: &'static [u8]| {
+            ttf_parser::Face::parse(bytes, 0)
+                .map_err(|e| DocEngineError::ExportError(format!("Police PDF invalide: {}", e)))
+        };
+        Ok(Self {
+            regular: parse(FONT_REGULAR)?,
+            bold: parse(FONT_BOLD)?,
+        })
+    }
+
+    /// Largeur d'un texte en millimètres
+    fn width(&self, text: &str, weight: Weight, size: f32) -> f32 {
+        let face = match weight {
+            Weight::Regular => &self.regular,
+            Weight::Bold => &self.bold,
+        };
+        let fallback = face.glyph_index('?');
+        let units: u32 = text
+            .chars()
+            .filter_map(|c| face.glyph_index(c).or(fallback))
+            .map(|glyph| face.glyph_hor_advance(glyph).unwrap_or(0) as u32)
+            .sum();
+        units as f32 / face.units_per_em() as f32 * size * PT_TO_MM
+    }
+}
+
+struct PlacedText {
+    x: f32,
+    /// Ligne de base, depuis le haut de la page
+    y: f32,
+    text: String,
+    size: f32,
+    weight: Weight,
+}
+
+#[derive(Default)]
+struct PageLayout {
+    texts: Vec<PlacedText>,
+    /// Segments (x1, y1, x2, y2) depuis le haut de la page
+    lines: Vec<(f32, f32, f32, f32)>,
+    bookmark: Option<String>,
+}
+
+struct Layout<'a> {
+    metrics: &'a FontMetrics,
+    pages: Vec<PageLayout>,
+    /// Position verticale courante depuis le haut de la page
+    y: f32,
+}
+
+impl<'a> Layout<'a> {
+    fn new(metrics: &'a FontMetrics) -> Self {
+        Self {
+            metrics,
+            pages: vec![PageLayout::default()],
+            y: MARGIN_TOP,
+        }
+    }
+
+    fn finish(self) -> Vec<PageLayout> {
+        self.pages
+    }
+
+    fn page(&mut self) -> &mut PageLayout {
+        self.pages.last_mut().expect("au moins une page")
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(PageLayout::default());
+        self.y = MARGIN_TOP;
+    }
+
+    fn remaining(&self) -> f32 {
+        PAGE_HEIGHT - MARGIN_BOTTOM - self.y
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if height > self.remaining() && self.y > MARGIN_TOP {
+            self.new_page();
+        }
+    }
+
+    fn line_height(size: f32) -> f32 {
+        size * LINE_SPACING * PT_TO_MM
+    }
+
+    fn place(&mut self, block: &Block) {
+        match block {
+            Block::Heading {
+                text,
+                size,
+                bookmark,
+            } => {
+                let runs = [Run::new(text.clone(), Weight::Bold)];
+                let lines = self.wrap(&runs, *size, CONTENT_WIDTH);
+                // Un titre reste avec au moins deux lignes du texte qui le suit
+                let height = lines.len() as f32 * Self::line_height(*size)
+                    + 2.0 * Self::line_height(BODY_SIZE);
+                self.y += size * 0.45 * PT_TO_MM;
+                self.ensure_space(height);
+                if *bookmark && self.page().bookmark.is_none() {
+                    self.page().bookmark = Some(text.clone());
+                }
+                self.draw_lines(lines, *size, MARGIN_X);
+                self.y += 1.5;
+            }
+            Block::Paragraph {
+                runs,
+                size,
+                indent,
+                prefix,
+            } => {
+                let x = MARGIN_X + indent;
+                let lines = self.wrap(runs, *size, CONTENT_WIDTH - indent);
+                for (i, line) in lines.into_iter().enumerate() {
+                    self.ensure_space(Self::line_height(*size));
+                    if i == 0 {
+                        if let Some(prefix) = prefix {
+                            let width = self.metrics.width(prefix, Weight::Regular, *size);
+                            let baseline = self.baseline(*size);
+                            self.page().texts.push(PlacedText {
+                                x: x - width - 1.5,
+                                y: baseline,
+                                text: prefix.clone(),
+                                size: *size,
+                                weight: Weight::Regular,
+                            });
+                        }
+                    }
+                    self.draw_lines(vec![line], *size, x);
+                }
+                self.y += size * 0.5 * PT_TO_MM;
+            }
+            Block::Code { lines } => {
+                for line in lines {
+                    let runs = [Run::new(line.clone(), Weight::Regular)];
+                    for wrapped in self.wrap(&runs, CODE_SIZE, CONTENT_WIDTH - 6.0) {
+                        self.ensure_space(Self::line_height(CODE_SIZE));
+                        self.draw_lines(vec![wrapped], CODE_SIZE, MARGIN_X + 4.0);
+                    }
+                }
+                self.y += 2.0;
+            }
+            Block::Rule => {
+                self.ensure_space(4.0);
+                self.y += 1.5;
+                let y = self.y;
+                self.page()
+                    .lines
+                    .push((MARGIN_X, y, PAGE_WIDTH - MARGIN_X, y));
+                self.y += 3.0;
+            }
+            Block::Spacer(height) => self.y += height,
+            Block::PageBreak => {
+                if self.y > MARGIN_TOP {
+                    self.new_page();
+                }
+            }
+            Block::Signature { place, parties } => self.place_signature(place, parties),
+        }
+    }
+
+    /// Bloc de signature, jamais coupé entre deux pages
+    fn place_signature(&mut self, place: &str, parties: &[(String, String)]) {
+        let line = Self::line_height(BODY_SIZE);
+        self.ensure_space(line * 9.0 + 20.0);
+        self.y += line;
+
+        let place = if place.is_empty() {
+            "____________________"
+        } else {
+            place
+        };
+        let text = format!(
+            "Fait à {}, le ____________________, en {} exemplaires originaux.",
+            place,
+            parties.len()
+        );
+        let runs = [Run::new(text, Weight::Regular)];
+        let lines = self.wrap(&runs, BODY_SIZE, CONTENT_WIDTH);
+        self.draw_lines(lines, BODY_SIZE, MARGIN_X);
+        self.y += line;
+
+        let column_width = CONTENT_WIDTH / parties.len().max(1) as f32;
+        let top = self.y;
+        for (i, (name, role)) in parties.iter().enumerate() {
+            let x = MARGIN_X + i as f32 * column_width;
+            self.y = top;
+            self.draw_lines(
+                self.wrap(
+                    &[Run::new(format!("Pour {}", name), Weight::Bold)],
+                    BODY_SIZE,
+                    column_width - 6.0,
+                ),
+                BODY_SIZE,
+                x,
+            );
+            self.draw_lines(
+                self.wrap(
+                    &[Run::new(format!("({})", role), Weight::Regular)],
+                    SMALL_SIZE,
+                    column_width - 6.0,
+                ),
+                SMALL_SIZE,
+                x,
+            );
+            self.draw_lines(
+                self.wrap(
+                    &[Run::new("Nom, qualité :", Weight::Regular)],
+                    SMALL_SIZE,
+                    column_width - 6.0,
+                ),
+                SMALL_SIZE,
+                x,
+            );
+            self.y += 4.0;
+            self.draw_lines(
+                self.wrap(
+                    &[Run::new(
+                        "Signature, précédée de « Lu et approuvé » :",
+                        Weight::Regular,
+                    )],
+                    SMALL_SIZE,
+                    column_width - 6.0,
+                ),
+                SMALL_SIZE,
+                x,
+            );
+            self.y += 18.0;
+            let y = self.y;
+            self.page().lines.push((x, y, x + column_width - 8.0, y));
+        }
+        self.y += 4.0;
+    }
+
+    fn baseline(&self, size: f32) -> f32 {
+        self.y + size * 0.95 * PT_TO_MM
+    }
+
+    fn draw_lines(&mut self, lines: Vec<Vec<(f32, Run)>>, size: f32, x: f32) {
+        for line in lines {
+            let baseline = self.baseline(size);
+            for (offset, run) in line {
+                self.page().texts.push(PlacedText {
+                    x: x + offset,
+                    y: baseline,
+                    text: run.text,
+                    size,
+                    weight: run.weight,
+                });
+            }
+            self.y += Self::line_height(size);
+        }
+    }
+
+    /// Coupe des segments en lignes ; chaque ligne est une suite de (décalage x, segment).
+    /// Les mots accolés d'un segment à l'autre (« **gras**, ») restent collés,
+    /// sans espace ni coupure de ligne entre eux.
+    fn wrap(&self, runs: &[Run], size: f32, width: f32) -> Vec<Vec<(f32, Run)>> {
+        let mut lines: Vec<Vec<(f32, Run)>> = vec![Vec::new()];
+        let mut cursor = 0.0;
+        let space = self.metrics.width(" ", Weight::Regular, size);
+        // Vrai si un blanc sépare le prochain mot du précédent
+        let mut separated = false;
+
+        for run in runs {
+            for (i, segment) in run.text.split('\n').enumerate() {
+                if i > 0 {
+                    lines.push(Vec::new());
+                    cursor = 0.0;
+                }
+                if segment.starts_with(char::is_whitespace) {
+                    separated = true;
+                }
+                for word in segment.split_whitespace() {
+                    let word_width = self.metrics.width(word, run.weight, size);
+                    let glued = cursor > 0.0 && !separated;
+                    separated = true;
+                    let gap = if cursor > 0.0 && !glued { space } else { 0.0 };
+                    if cursor > 0.0 && !glued && cursor + gap + word_width > width {
+                        lines.push(Vec::new());
+                        cursor = 0.0;
+                    }
+
+                    // Mot plus long qu'une ligne : coupé au caractère
+                    if word_width > width {
+                        if cursor > 0.0 {
+                            lines.push(Vec::new());
+                        }
+                        let mut chunk = String::new();
+                        for c in word.chars() {
+                            chunk.push(c);
+                            if self.metrics.width(&chunk, run.weight, size) > width
+                                && chunk.chars().count() > 1
+                            {
+                                chunk.pop();
+                                let line = lines.last_mut().expect("ligne courante");
+                                line.push((0.0, Run::new(chunk.clone(), run.weight)));
+                                lines.push(Vec::new());
+                                chunk = c.to_string();
+                            }
+                        }
+                        cursor = self.metrics.width(&chunk, run.weight, size);
+                        lines
+                            .last_mut()
+                            .expect("ligne courante")
+                            .push((0.0, Run::new(chunk, run.weight)));
+                        continue;
+                    }
+
+                    let gap = if cursor > 0.0 && !glued { space } else { 0.0 };
+                    let line = lines.last_mut().expect("ligne courante");
+                    match line.last_mut() {
+                        // Fusionne les mots consécutifs de même graisse
+                        Some((_, last)) if last.weight == run.weight => {
+                            if gap > 0.0 {
+                                last.text.push(' ');
+                            }
+                            last.text.push_str(word);
+                        }
+                        _ => line.push((cursor + gap, Run::new(word, run.weight))),
+                    }
+                    cursor += gap + word_width;
+                }
+                if !segment.is_empty() {
+                    separated = segment.ends_with(char::is_whitespace);
+                }
+            }
+        }
+
+        lines.retain(|line| !line.is_empty());
+        lines
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Écriture du PDF
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn write_pdf(
+    document: &Document,
+    metrics: &FontMetrics,
+    pages: Vec<PageLayout>,
+) -> Result<Vec<u8>> {
+    let pdf_error = |e: printpdf::Error| DocEngineError::ExportError(format!("Erreur PDF: {}", e));
+
+    let (pdf, first_page, first_layer) = PdfDocument::new(
+        document.content.title.clone(),
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+        "Contenu",
+    );
+    let pdf = with_metadata(pdf, document);
+
+    let regular = pdf
+        .add_external_font(Cursor::new(FONT_REGULAR))
+        .map_err(pdf_error)?;
+    let bold = pdf
+        .add_external_font(Cursor::new(FONT_BOLD))
+        .map_err(pdf_error)?;
+
+    let total = pages.len();
+    for (index, page) in pages.into_iter().enumerate() {
+        let (page_index, layer) = if index == 0 {
+            (first_page, pdf.get_page(first_page).get_layer(first_layer))
+        } else {
+            let (page_index, layer_index) =
+                pdf.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Contenu");
+            (page_index, pdf.get_page(page_index).get_layer(layer_index))
+        };
+
+        if let Some(bookmark) = &page.bookmark {
+            pdf.add_bookmark(bookmark.clone(), page_index);
+        }
+
+        draw_header_footer(&layer, metrics, document, index + 1, total, &regular);
+        for text in page.texts {
+            let font = match text.weight {
+                Weight::Regular => &regular,
+                Weight::Bold => &bold,
+            };
+            layer.use_text(
+                text.text,
+                text.size,
+                Mm(text.x),
+                Mm(PAGE_HEIGHT - text.y),
+                font,
+            );
+        }
+        layer.set_outline_thickness(0.5);
+        for (x1, y1, x2, y2) in page.lines {
+            layer.add_line(segment(x1, PAGE_HEIGHT - y1, x2, PAGE_HEIGHT - y2));
+        }
+    }
+
+    pdf.save_to_bytes().map_err(pdf_error)
+}
+
+/// Titre, auteur, mots-clés et dates issus de `DocumentMetadata`
+fn with_metadata(pdf: PdfDocumentReference, document: &Document) -> PdfDocumentReference {
+    let metadata = &document.metadata;
+    let mut pdf = pdf
+        .with_title(document.content.title.clone())
+        .with_author(metadata.author.clone())
+        .with_subject(metadata.category.clone())
+        .with_keywords(metadata.tags.clone())
+        .with_creator("TITANE∞ doc_engine")
+        .with_producer("TITANE∞ doc_engine (printpdf)");
+    if let Ok(created) = OffsetDateTime::from_unix_timestamp(metadata.created_at.timestamp()) {
+        pdf = pdf.with_creation_date(created);
+    }
+    if let Ok(updated) = OffsetDateTime::from_unix_timestamp(metadata.updated_at.timestamp()) {
+        pdf = pdf.with_mod_date(updated).with_metadata_date(updated);
+    }
+    pdf
+}
+
+fn draw_header_footer(
+    layer: &PdfLayerReference,
+    metrics: &FontMetrics,
+    document: &Document,
+    page: usize,
+    total: usize,
+    font: &IndirectFontRef,
+) {
+    let right_aligned =
+        |text: &str| PAGE_WIDTH - MARGIN_X - metrics.width(text, Weight::Regular, SMALL_SIZE);
+
+    // En-tête : titre et date
+    let header_y = PAGE_HEIGHT - 13.0;
+    let title: String = document.content.title.chars().take(80).collect();
+    layer.use_text(title, SMALL_SIZE, Mm(MARGIN_X), Mm(header_y), font);
+    let date = document.metadata.updated_at.format("%d/%m/%Y").to_string();
+    layer.use_text(
+        date.clone(),
+        SMALL_SIZE,
+        Mm(right_aligned(&date)),
+        Mm(header_y),
+        font,
+    );
+
+    // Pied de page : version et pagination
+    let footer_y = 11.0;
+    let version = format!("Version {}", document.metadata.version);
+    layer.use_text(version, SMALL_SIZE, Mm(MARGIN_X), Mm(footer_y), font);
+    let pagination = format!("Page {} / {}", page, total);
+    layer.use_text(
+        pagination.clone(),
+        SMALL_SIZE,
+        Mm(right_aligned(&pagination)),
+        Mm(footer_y),
+        font,
+    );
+
+    layer.set_outline_thickness(0.3);
+    layer.add_line(segment(MARGIN_X, 15.5, PAGE_WIDTH - MARGIN_X, 15.5));
+    layer.add_line(segment(
+        MARGIN_X,
+        PAGE_HEIGHT - 17.0,
+        PAGE_WIDTH - MARGIN_X,
+        PAGE_HEIGHT - 17.0,
+    ));
+}
+
+/// Segment en coordonnées PDF (origine en bas à gauche)
+fn segment(x1: f32, y1: f32, x2: f32, y2: f32) -> Line {
+    Line {
+        points: vec![
+            (Point::new(Mm(x1), Mm(y1)), false),
+            (Point::new(Mm(x2), Mm(y2)), false),
+        ],
+        is_closed: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(line: &[(f32, Run)]) -> Vec<&str> {
+        line.iter().map(|(_, run)| run.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_render_pdf_produces_a_pdf_file() {
+        let mut document = test_document(DocumentType::Contract, DocumentStyle::Legal);
+        document.content.mandatory_clauses = Some(vec![Clause {
+            id: "objet".to_string(),
+            title: "Objet".to_string(),
+            content: "Le contrat a pour **objet** la prestation.".to_string(),
+            mandatory: true,
+            category: ClauseCategory::General,
+        }]);
+        document.content.annexes.push(Annex {
+            id: "tarifs".to_string(),
+            title: "Tarifs".to_string(),
+            content: "| Poste | Prix |\n|---|---|\n| Audit | 100 |".to_string(),
+            format: "markdown".to_string(),
+        });
+
+        let bytes = render_pdf(&document).unwrap();
+        assert!(bytes.starts_with(b"%PDF-"));
+        let tail = String::from_utf8_lossy(&bytes[bytes.len().saturating_sub(32)..]).into_owned();
+        assert!(tail.trim_end().ends_with("%%EOF"), "{:?}", tail);
+    }
+
+    #[test]
+    fn test_wrap_splits_word_wider_than_line() {
+        let metrics = FontMetrics::load().unwrap();
+        let layout = Layout::new(&metrics);
+        let word = "x".repeat(120);
+        let runs = [Run::new(format!("début {}", word), Weight::Regular)];
+
+        let lines = layout.wrap(&runs, BODY_SIZE, 40.0);
+        assert!(lines.len() > 2);
+        assert_eq!(texts(&lines[0]), vec!["début"]);
+        for line in &lines[1..] {
+            assert_eq!(line.len(), 1);
+            assert_eq!(line[0].0, 0.0);
+            assert!(metrics.width(&line[0].1.text, Weight::Regular, BODY_SIZE) <= 40.0);
+        }
+        let rebuilt: String = lines[1..].iter().map(|line| line[0].1.text.as_str()).collect();
+        assert_eq!(rebuilt, word);
+    }
+
+    #[test]
+    fn test_wrap_mixed_weights_keeps_spacing() {
+        let metrics = FontMetrics::load().unwrap();
+        let layout = Layout::new(&metrics);
+        let space = metrics.width(" ", Weight::Regular, BODY_SIZE);
+
+        // « Le **gras**, puis suite » : espace avant le gras, aucun avant la virgule
+        let runs = inline_runs("Le **gras**, puis suite");
+        let lines = layout.wrap(&runs, BODY_SIZE, CONTENT_WIDTH);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(texts(&lines[0]), vec!["Le", "gras", ", puis suite"]);
+
+        let le = metrics.width("Le", Weight::Regular, BODY_SIZE);
+        let gras = metrics.width("gras", Weight::Bold, BODY_SIZE);
+        assert!((lines[0][1].0 - (le + space)).abs() < 1e-3);
+        assert!((lines[0][2].0 - (le + space + gras)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_wrap_merges_same_weight_runs_without_adding_spaces() {
+        let metrics = FontMetrics::load().unwrap();
+        let layout = Layout::new(&metrics);
+
+        let glued = [Run::new("mot", Weight::Bold), Run::new("clé", Weight::Bold)];
+        assert_eq!(texts(&layout.wrap(&glued, BODY_SIZE, CONTENT_WIDTH)[0]), vec!["motclé"]);
+
+        let spaced = [Run::new("mot ", Weight::Bold), Run::new("clé", Weight::Bold)];
+        assert_eq!(texts(&layout.wrap(&spaced, BODY_SIZE, CONTENT_WIDTH)[0]), vec!["mot clé"]);
+
+        let leading = [Run::new("mot", Weight::Regular), Run::new(" clé", Weight::Regular)];
+        assert_eq!(texts(&layout.wrap(&leading, BODY_SIZE, CONTENT_WIDTH)[0]), vec!["mot clé"]);
+    }
+}
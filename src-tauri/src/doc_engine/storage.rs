@@ -1,30 +1,96 @@
 // Module de stockage chiffré des documents
+//
+// Chiffrement par défaut : la clé est dérivée du mot de passe de l'utilisateur
+// (Argon2id) avec le sel de `.keyring.json`, qui contient aussi une valeur de
+// contrôle pour rejeter un mauvais mot de passe avant toute lecture. Chaque
+// fichier est `TDE1 || nonce || texte chiffré`, le nom du fichier en AAD.
+// Le stockage en clair n'existe que sur demande explicite (`plaintext`).
 
 use super::*;
-use aes_gcm::{
-    aead::{Aead, KeyInit, OsRng},
-    Aes256Gcm, Nonce
-};
-use argon2::{
-    password_hash::{PasswordHasher, SaltString},
-    Argon2
-};
+use super::versioning::Version;
+use crate::shared::crypto::{self, KdfConfig, KdfParams, KEY_SIZE};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+const KEYRING_FILE: &str = ".keyring.json";
+/// En-tête des fichiers chiffrés : magic + version
+const FILE_MAGIC: &[u8; 4] = b"TDE1";
+const CHECK_PLAINTEXT: &[u8] = b"titane-documents-keyring";
+const CHECK_AAD: &[u8] = b"__check__";
+
+/// En-tête persistant : paramètres KDF (sel) et valeur de contrôle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyringHeader {
+    kdf: KdfParams,
+    key_check: String,
+}
+
 pub struct StorageEngine {
     storage_path: PathBuf,
-    encryption_enabled: bool,
+    /// Clé dérivée du mot de passe ; `None` uniquement via `plaintext`
+    key: Option<[u8; KEY_SIZE]>,
 }
 
 impl StorageEngine {
-    pub fn new(storage_path: PathBuf) -> Self {
+    /// Stockage chiffré ; le trousseau est créé au premier usage, sinon le
+    /// mot de passe est vérifié contre sa valeur de contrôle
+    pub fn new(storage_path: PathBuf, password: &str) -> Result<Self> {
+        Self::with_kdf_config(storage_path, password, KdfConfig::default())
+    }
+    
+    /// Comme `new`, avec des paramètres KDF pour un nouveau trousseau
+    pub fn with_kdf_config(storage_path: PathBuf, password: &str, kdf: KdfConfig) -> Result<Self> {
+        let header_path = storage_path.join(KEYRING_FILE);
+        
+        let key = if header_path.exists() {
+            let content = fs::read_to_string(&header_path)
+                .map_err(|e| DocEngineError::StorageError(format!("Erreur de lecture du trousseau: {}", e)))?;
+            let header: KeyringHeader = serde_json::from_str(&content)
+                .map_err(|e| DocEngineError::StorageError(format!("Trousseau invalide: {}", e)))?;
+            let mut key = derive_key(password, &header.kdf)?;
+            match crypto::open_sealed(&key, &header.key_check, CHECK_AAD) {
+                Ok(check) if check == CHECK_PLAINTEXT => key,
+                _ => {
+                    crypto::wipe(&mut key);
+                    return Err(DocEngineError::StorageError("Mot de passe incorrect".to_string()));
+                }
+            }
+        } else {
+            let kdf = KdfParams::generate(kdf);
+            let key = derive_key(password, &kdf)?;
+            let header = KeyringHeader {
+                key_check: crypto::seal(&key, CHECK_PLAINTEXT, CHECK_AAD)
+                    .map_err(|e| DocEngineError::StorageError(e.to_string()))?,
+                kdf,
+            };
+            let json = serde_json::to_string_pretty(&header)
+                .map_err(|e| DocEngineError::StorageError(format!("Erreur de sérialisation du trousseau: {}", e)))?;
+            fs::create_dir_all(&storage_path)
+                .map_err(|e| DocEngineError::StorageError(format!("Impossible de créer le répertoire: {}", e)))?;
+            fs::write(&header_path, json)
+                .map_err(|e| DocEngineError::StorageError(format!("Erreur d'écriture du trousseau: {}", e)))?;
+            key
+        };
+        
+        Ok(Self {
+            storage_path,
+            key: Some(key),
+        })
+    }
+    
+    /// Stockage en clair (JSON lisible, historique compris) : à réserver aux
+    /// exports et données non sensibles, jamais utilisé implicitement
+    pub fn plaintext(storage_path: PathBuf) -> Self {
         Self {
             storage_path,
-            encryption_enabled: true,
+            key: None,
         }
     }
     
+    pub fn is_encrypted(&self) -> bool {
+        self.key.is_some()
+    }
+    
     /// Sauvegarde un document de manière sécurisée
     pub async fn save(&self, document: &Document) -> Result<String> {
         // Créer le répertoire si nécessaire
@@ -35,20 +101,9 @@ impl StorageEngine {
         let json_data = serde_json::to_string_pretty(document)
             .map_err(|e| DocEngineError::StorageError(format!("Erreur de sérialisation: {}", e)))?;
         
-        // Chiffrement si activé
-        let data_to_store = if self.encryption_enabled {
-            self.encrypt_data(json_data.as_bytes())?
-        } else {
-            json_data.into_bytes()
-        };
-        
-        // Chemin du fichier
-        let filename = format!("{}.{}", document.metadata.id, if self.encryption_enabled { "enc" } else { "json" });
-        let file_path = self.storage_path.join(&filename);
-        
-        // Écriture
-        fs::write(&file_path, &data_to_store)
-            .map_err(|e| DocEngineError::StorageError(format!("Erreur d'écriture: {}", e)))?;
+        // Écriture (chiffrée si activé)
+        let file_path = self.document_path(&document.metadata.id)?;
+        self.write_payload(&file_path, json_data)?;
         
         // Sauvegarde des métadonnées
         self.save_metadata(document)?;
@@ -58,26 +113,13 @@ impl StorageEngine {
     
     /// Charge un document depuis le stockage
     pub async fn load(&self, document_id: &str) -> Result<Document> {
-        let filename = format!("{}.{}", document_id, if self.encryption_enabled { "enc" } else { "json" });
-        let file_path = self.storage_path.join(&filename);
+        let file_path = self.document_path(document_id)?;
         
         if !file_path.exists() {
             return Err(DocEngineError::StorageError(format!("Document {} introuvable", document_id)));
         }
         
-        // Lecture
-        let data = fs::read(&file_path)
-            .map_err(|e| DocEngineError::StorageError(format!("Erreur de lecture: {}", e)))?;
-        
-        // Déchiffrement si nécessaire
-        let json_data = if self.encryption_enabled {
-            let decrypted = self.decrypt_data(&data)?;
-            String::from_utf8(decrypted)
-                .map_err(|e| DocEngineError::StorageError(format!("Erreur de décodage: {}", e)))?
-        } else {
-            String::from_utf8(data)
-                .map_err(|e| DocEngineError::StorageError(format!("Erreur de décodage: {}", e)))?
-        };
+        let json_data = self.read_payload(&file_path)?;
         
         // Désérialisation
         let document: Document = serde_json::from_str(&json_data)
@@ -103,67 +145,97 @@ impl StorageEngine {
         Ok(metadata_list)
     }
     
-    /// Supprime un document
-    pub async fn delete(&self, document_id: &str) -> Result<()> {
-        let filename = format!("{}.{}", document_id, if self.encryption_enabled { "enc" } else { "json" });
-        let file_path = self.storage_path.join(&filename);
+    /// Sauvegarde l'historique des versions d'un document
+    pub async fn save_versions(&self, document_id: &str, versions: &[Version]) -> Result<String> {
+        fs::create_dir_all(&self.storage_path)
+            .map_err(|e| DocEngineError::StorageError(format!("Impossible de créer le répertoire: {}", e)))?;
         
-        if file_path.exists() {
-            fs::remove_file(&file_path)
-                .map_err(|e| DocEngineError::StorageError(format!("Erreur de suppression: {}", e)))?;
-        }
+        let json_data = serde_json::to_string(versions)
+            .map_err(|e| DocEngineError::StorageError(format!("Erreur de sérialisation de l'historique: {}", e)))?;
         
-        // Mise à jour des métadonnées
-        self.remove_from_metadata(document_id)?;
+        let file_path = self.history_path(document_id)?;
+        self.write_payload(&file_path, json_data)?;
         
-        Ok(())
+        Ok(file_path.to_string_lossy().to_string())
     }
     
-    fn encrypt_data(&self, data: &[u8]) -> Result<Vec<u8>> {
-        // Génération d'une clé à partir d'un master password (devrait être fourni par l'utilisateur)
-        let password = b"titane_infinity_master_key_v13"; // TODO: Utiliser une vraie clé utilisateur
-        let salt = SaltString::generate(&mut OsRng);
-        
-        let argon2 = Argon2::default();
-        let password_hash = argon2.hash_password(password, &salt)
-            .map_err(|e| DocEngineError::StorageError(format!("Erreur de dérivation de clé: {}", e)))?;
-        
-        // Extraction des 32 premiers octets pour AES-256
-        let key_bytes = password_hash.hash.unwrap().as_bytes();
-        let key = &key_bytes[..32];
-        
-        // Chiffrement AES-256-GCM
-        let cipher = Aes256Gcm::new_from_slice(key)
-            .map_err(|e| DocEngineError::StorageError(format!("Erreur de création du cipher: {}", e)))?;
-        
-        let nonce = Nonce::from_slice(b"unique_nonce"); // TODO: Générer un nonce unique par document
+    /// Charge l'historique des versions (vide si jamais sauvegardé)
+    pub async fn load_versions(&self, document_id: &str) -> Result<Vec<Version>> {
+        let file_path = self.history_path(document_id)?;
         
-        let ciphertext = cipher.encrypt(nonce, data)
-            .map_err(|e| DocEngineError::StorageError(format!("Erreur de chiffrement: {}", e)))?;
+        if !file_path.exists() {
+            return Ok(Vec::new());
+        }
         
-        Ok(ciphertext)
+        let json_data = self.read_payload(&file_path)?;
+        serde_json::from_str(&json_data)
+            .map_err(|e| DocEngineError::StorageError(format!("Erreur de désérialisation de l'historique: {}", e)))
     }
     
-    fn decrypt_data(&self, encrypted_data: &[u8]) -> Result<Vec<u8>> {
-        let password = b"titane_infinity_master_key_v13";
-        let salt = SaltString::generate(&mut OsRng);
-        
-        let argon2 = Argon2::default();
-        let password_hash = argon2.hash_password(password, &salt)
-            .map_err(|e| DocEngineError::StorageError(format!("Erreur de dérivation de clé: {}", e)))?;
+    /// Supprime un document et son historique
+    pub async fn delete(&self, document_id: &str) -> Result<()> {
+        for file_path in [self.document_path(document_id)?, self.history_path(document_id)?] {
+            if file_path.exists() {
+                fs::remove_file(&file_path)
+                    .map_err(|e| DocEngineError::StorageError(format!("Erreur de suppression: {}", e)))?;
+            }
+        }
         
-        let key_bytes = password_hash.hash.unwrap().as_bytes();
-        let key = &key_bytes[..32];
+        // Mise à jour des métadonnées
+        self.remove_from_metadata(document_id)?;
         
-        let cipher = Aes256Gcm::new_from_slice(key)
-            .map_err(|e| DocEngineError::StorageError(format!("Erreur de création du cipher: {}", e)))?;
+        Ok(())
+    }
+    
+    fn extension(&self) -> &'static str {
+        if self.key.is_some() { "enc" } else { "json" }
+    }
+    
+    fn document_path(&self, document_id: &str) -> Result<PathBuf> {
+        validate_document_id(document_id)?;
+        Ok(self.storage_path.join(format!("{}.{}", document_id, self.extension())))
+    }
+    
+    fn history_path(&self, document_id: &str) -> Result<PathBuf> {
+        validate_document_id(document_id)?;
+        Ok(self.storage_path.join(format!("{}.history.{}", document_id, self.extension())))
+    }
+    
+    /// Écrit un contenu JSON, chiffré si activé
+    fn write_payload(&self, file_path: &Path, json_data: String) -> Result<()> {
+        let data_to_store = match &self.key {
+            Some(key) => {
+                let sealed = crypto::encrypt(key, json_data.as_bytes(), file_aad(file_path))
+                    .map_err(|e| DocEngineError::StorageError(e.to_string()))?;
+                let mut data = Vec::with_capacity(FILE_MAGIC.len() + sealed.len());
+                data.extend_from_slice(FILE_MAGIC);
+                data.extend_from_slice(&sealed);
+                data
+            }
+            None => json_data.into_bytes(),
+        };
         
-        let nonce = Nonce::from_slice(b"unique_nonce");
+        fs::write(file_path, &data_to_store)
+            .map_err(|e| DocEngineError::StorageError(format!("Erreur d'écriture: {}", e)))
+    }
+    
+    fn read_payload(&self, file_path: &Path) -> Result<String> {
+        let data = fs::read(file_path)
+            .map_err(|e| DocEngineError::StorageError(format!("Erreur de lecture: {}", e)))?;
         
-        let plaintext = cipher.decrypt(nonce, encrypted_data)
-            .map_err(|e| DocEngineError::StorageError(format!("Erreur de déchiffrement: {}", e)))?;
+        let bytes = match &self.key {
+            Some(key) => {
+                let sealed = data.strip_prefix(FILE_MAGIC.as_slice()).ok_or_else(|| {
+                    DocEngineError::StorageError(format!("Format chiffré inconnu: {}", file_path.display()))
+                })?;
+                crypto::decrypt(key, sealed, file_aad(file_path))
+                    .map_err(|e| DocEngineError::StorageError(e.to_string()))?
+            }
+            None => data,
+        };
         
-        Ok(plaintext)
+        String::from_utf8(bytes)
+            .map_err(|e| DocEngineError::StorageError(format!("Erreur de décodage: {}", e)))
     }
     
     fn save_metadata(&self, document: &Document) -> Result<()> {
@@ -217,8 +289,129 @@ impl StorageEngine {
     }
 }
 
-impl Default for StorageEngine {
-    fn default() -> Self {
-        Self::new(PathBuf::from("./data/titane/memory/documents"))
+impl Drop for StorageEngine {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.as_mut() {
+            crypto::wipe(key);
+        }
+    }
+}
+
+/// Identifiant utilisable comme nom de fichier : `A-Za-z0-9_-` (UUID en pratique)
+fn validate_document_id(document_id: &str) -> Result<()> {
+    let valid = !document_id.is_empty()
+        && document_id.len() <= 128
+        && document_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(DocEngineError::StorageError(format!(
+            "Identifiant de document invalide: '{}'",
+            document_id
+        )))
+    }
+}
+
+/// Le nom du fichier lie le contenu chiffré à son document
+fn file_aad(file_path: &Path) -> &[u8] {
+    file_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .as_bytes()
+}
+
+fn derive_key(password: &str, kdf: &KdfParams) -> Result<[u8; KEY_SIZE]> {
+    kdf.derive_key(password)
+        .map_err(|e| DocEngineError::StorageError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const TEST_KDF: KdfConfig = KdfConfig {
+        memory_kib: 256,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    fn open(dir: &Path, password: &str) -> Result<StorageEngine> {
+        StorageEngine::with_kdf_config(dir.to_path_buf(), password, TEST_KDF)
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_round_trip_across_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        let document = test_document(DocumentType::Analysis, DocumentStyle::Professional);
+        let id = document.metadata.id.clone();
+
+        let path = open(temp_dir.path(), "secret").unwrap().save(&document).await.unwrap();
+        let raw = fs::read(&path).unwrap();
+        assert!(raw.starts_with(FILE_MAGIC));
+        assert!(!String::from_utf8_lossy(&raw).contains("Introduction"));
+
+        let storage = open(temp_dir.path(), "secret").unwrap();
+        assert!(storage.is_encrypted());
+        let loaded = storage.load(&id).await.unwrap();
+        assert_eq!(loaded.content.title, document.content.title);
+
+        match open(temp_dir.path(), "autre") {
+            Err(DocEngineError::StorageError(message)) => assert_eq!(message, "Mot de passe incorrect"),
+            other => panic!("mot de passe accepté: {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_file_is_bound_to_its_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = open(temp_dir.path(), "secret").unwrap();
+        let first = test_document(DocumentType::Analysis, DocumentStyle::Professional);
+        let mut second = test_document(DocumentType::Analysis, DocumentStyle::Professional);
+        second.metadata.id = "doc-autre".to_string();
+        let first_path = storage.save(&first).await.unwrap();
+        let second_path = storage.save(&second).await.unwrap();
+
+        // Un fichier substitué à un autre ne se déchiffre pas
+        fs::copy(&first_path, &second_path).unwrap();
+        assert!(storage.load(&second.metadata.id).await.is_err());
+        assert!(storage.load(&first.metadata.id).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_document_ids_cannot_escape_the_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = open(&temp_dir.path().join("documents"), "secret").unwrap();
+        fs::write(temp_dir.path().join("cible.json"), "{}").unwrap();
+
+        for id in ["", "../cible", "..", "a/b", "a\\b", "/etc/passwd", "doc.history", "é"] {
+            assert!(storage.load(id).await.is_err(), "{}", id);
+            assert!(storage.load_versions(id).await.is_err(), "{}", id);
+            assert!(storage.save_versions(id, &[]).await.is_err(), "{}", id);
+            assert!(storage.delete(id).await.is_err(), "{}", id);
+        }
+        assert!(temp_dir.path().join("cible.json").exists());
+
+        let mut document = test_document(DocumentType::Analysis, DocumentStyle::Professional);
+        document.metadata.id = "../cible".to_string();
+        assert!(storage.save(&document).await.is_err());
+        assert_eq!(fs::read_to_string(temp_dir.path().join("cible.json")).unwrap(), "{}");
+    }
+
+    #[tokio::test]
+    async fn test_plain_storage_writes_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = StorageEngine::plaintext(temp_dir.path().to_path_buf());
+        assert!(!storage.is_encrypted());
+        let document = test_document(DocumentType::Analysis, DocumentStyle::Professional);
+
+        let path = storage.save(&document).await.unwrap();
+        assert!(path.ends_with(".json"));
+        let parsed: Document = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(parsed.metadata.id, document.metadata.id);
+        assert_eq!(storage.list_documents().await.unwrap().len(), 1);
     }
 }
@@ -0,0 +1,1381 @@
+// TITANE∞ v13 - Templates de documents persistés en fichiers
+// `<id>.template.md` : frontmatter (métadonnées, paramètres typés, clauses)
+// + corps Markdown écrit en langage de template (minijinja : variables,
+// conditions, boucles, inclusions). Les clauses réutilisables sont des partiels
+// `partials/<nom>.partial.md`, partageables sous forme de bibliothèque JSON.
+//
+// Format :
+//
+//   ---
+//   id: prestation
+//   name: Contrat de prestation
+//   doc_type: Contract
+//   style: Legal
+//   title: Contrat de prestation — {{ client }}
+//   summary: Prestations fournies à {{ client }} pour {{ duree }} mois.
+//   objective: Encadrer les prestations de {{ prestataire }}
+//   clause: clauses/confidentialite
+//   param: client | string | required | Nom du client
+//   param: duree | integer | default=12 | Durée en mois
+//   param: juridiction | choice(Paris, Lyon) | default=Paris
+//   param: services | list | required | Services séparés par des ;
+//   param: date_effet | date | required
+//   param: renouvellement | boolean | default=false
+//   ---
+//   # Objet
+//
+//   {% for service in services %}- {{ service }}
+//   {% endfor %}
+//   {% if renouvellement %}Renouvellement tacite.{% endif %}
+//
+//   {% include "clauses/responsabilite" %}
+//
+// Chaque titre `#` du corps rendu ouvre une section, `##` une sous-section.
+// Une ligne `param:` = nom | type (string|integer|number|boolean|date|list|
+// choice(a, b)) | required ou default=<valeur> (optionnel) | description.
+// Un partiel peut déclarer `title`, `category` et `mandatory` : il devient
+// alors une clause obligatoire quand un template le cite dans `clause:`. Il
+// n'utilise que les paramètres déclarés par les templates qui le citent.
+
+use super::templates::{SectionTemplate, Template};
+use super::*;
+use minijinja::{AutoEscape, Environment, UndefinedBehavior, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const TEMPLATE_FILE_EXTENSION: &str = ".template.md";
+pub const PARTIAL_FILE_EXTENSION: &str = ".partial.md";
+pub const PARTIALS_DIR: &str = "partials";
+const FRONTMATTER_DELIMITER: &str = "---";
+const LIBRARY_FORMAT_VERSION: u32 = 1;
+
+/// Variables fournies par le moteur en plus des paramètres déclarés
+const BUILTIN_VARIABLES: &[&str] = &["today", "doc_type", "params"];
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Types
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ParameterType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    /// Date `AAAA-MM-JJ` (ou `JJ/MM/AAAA`), normalisée en `AAAA-MM-JJ`
+    Date,
+    /// Valeurs séparées par des `;`
+    List,
+    Choice(Vec<String>),
+}
+
+impl ParameterType {
+    fn as_str(&self) -> String {
+        match self {
+            ParameterType::String => "string".to_string(),
+            ParameterType::Integer => "integer".to_string(),
+            ParameterType::Number => "number".to_string(),
+            ParameterType::Boolean => "boolean".to_string(),
+            ParameterType::Date => "date".to_string(),
+            ParameterType::List => "list".to_string(),
+            ParameterType::Choice(choices) => format!("choice({})", choices.join(", ")),
+        }
+    }
+}
+
+/// Paramètre typé déclaré par un template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateParameter {
+    pub name: String,
+    pub param_type: ParameterType,
+    pub required: bool,
+    pub default: Option<String>,
+    pub description: String,
+}
+
+/// Source d'un template fichier (sections générées par rendu du corps)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateSource {
+    pub title: String,
+    pub summary: Option<String>,
+    pub objectives: Vec<String>,
+    /// Partiels rendus comme clauses obligatoires
+    pub clauses: Vec<String>,
+    pub body: String,
+    pub path: Option<String>,
+}
+
+/// Fragment réutilisable (clause de bibliothèque)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Partial {
+    pub name: String,
+    pub title: Option<String>,
+    pub category: Option<ClauseCategory>,
+    pub mandatory: bool,
+    pub body: String,
+}
+
+/// Bibliothèque partageable : fichiers sources des templates et partiels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateLibrary {
+    pub format_version: u32,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    pub templates: Vec<LibraryFile>,
+    pub partials: Vec<LibraryFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryFile {
+    pub name: String,
+    pub content: String,
+}
+
+fn template_error(message: impl Into<String>) -> DocEngineError {
+    DocEngineError::TemplateError(message.into())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Parsing / écriture
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Sépare le frontmatter (paires clé/valeur, dans l'ordre) du corps
+fn split_frontmatter(content: &str, required: bool) -> Result<(Vec<(String, String)>, String)> {
+    let content = content.trim_start_matches('\u{feff}');
+    let mut lines = content.lines();
+
+    if lines.clone().next().map(str::trim) != Some(FRONTMATTER_DELIMITER) {
+        if required {
+            return Err(template_error(
+                "Frontmatter manquant (le fichier doit commencer par ---)",
+            ));
+        }
+        return Ok((Vec::new(), content.trim().to_string()));
+    }
+    lines.next();
+
+    let mut entries = Vec::new();
+    let mut closed = false;
+    for (line_no, line) in lines.by_ref().enumerate() {
+        let line = line.trim();
+        if line == FRONTMATTER_DELIMITER {
+            closed = true;
+            break;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| template_error(format!("Ligne {} invalide: '{}'", line_no + 2, line)))?;
+        entries.push((key.trim().to_string(), value.trim().to_string()));
+    }
+
+    if !closed {
+        return Err(template_error("Frontmatter non terminé (--- manquant)"));
+    }
+    Ok((
+        entries,
+        lines.collect::<Vec<_>>().join("\n").trim().to_string(),
+    ))
+}
+
+/// Variante d'énumération sérialisée (`Contract`, `Legal`…)
+fn parse_variant<T: serde::de::DeserializeOwned>(key: &str, value: &str) -> Result<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| template_error(format!("{} inconnu: '{}'", key, value)))
+}
+
+pub fn parse_template_file(content: &str) -> Result<Template> {
+    let (entries, body) = split_frontmatter(content, true)?;
+
+    let mut id = None;
+    let mut name = None;
+    let mut doc_type = None;
+    let mut style = None;
+    let mut title = None;
+    let mut summary = None;
+    let mut objectives = Vec::new();
+    let mut clauses = Vec::new();
+    let mut parameters: Vec<TemplateParameter> = Vec::new();
+
+    for (key, value) in entries {
+        match key.as_str() {
+            "id" => id = Some(value),
+            "name" => name = Some(value),
+            "doc_type" => doc_type = Some(parse_variant::<DocumentType>("doc_type", &value)?),
+            "style" => style = Some(parse_variant::<DocumentStyle>("style", &value)?),
+            "title" => title = Some(value),
+            "summary" => summary = Some(value),
+            "objective" => objectives.push(value),
+            "clause" => {
+                validate_partial_name(&value)?;
+                clauses.push(value);
+            }
+            "param" => {
+                let parameter = parse_parameter_line(&value)?;
+                if parameters.iter().any(|p| p.name == parameter.name) {
+                    return Err(template_error(format!(
+                        "Paramètre '{}' déclaré deux fois",
+                        parameter.name
+                    )));
+                }
+                parameters.push(parameter);
+            }
+            other => {
+                return Err(template_error(format!(
+                    "Clé de frontmatter inconnue: '{}'",
+                    other
+                )))
+            }
+        }
+    }
+
+    let id = id.ok_or_else(|| template_error("Champ 'id' manquant"))?;
+    validate_template_id(&id)?;
+    let doc_type = doc_type
+        .ok_or_else(|| template_error(format!("Champ 'doc_type' manquant pour '{}'", id)))?;
+    if body.is_empty() {
+        return Err(template_error(format!(
+            "Corps vide pour le template '{}'",
+            id
+        )));
+    }
+
+    let source = TemplateSource {
+        title: title.unwrap_or_else(|| "{{ title }}".to_string()),
+        summary,
+        objectives,
+        clauses,
+        body,
+        path: None,
+    };
+    check_sources(&id, &source, &parameters, &HashMap::new())?;
+
+    Ok(Template {
+        name: name.unwrap_or_else(|| id.clone()),
+        sections: outline(&source.body),
+        default_style: style.unwrap_or(DocumentStyle::Professional),
+        id,
+        doc_type,
+        parameters,
+        source: Some(source),
+    })
+}
+
+fn parse_parameter_line(value: &str) -> Result<TemplateParameter> {
+    // Les virgules de `choice(a, b)` ne séparent pas les champs : seul `|` compte
+    let parts: Vec<&str> = value.split('|').map(str::trim).collect();
+    let name = parts.first().copied().unwrap_or_default();
+    if !is_identifier(name) {
+        return Err(template_error(format!(
+            "Nom de paramètre invalide: '{}'",
+            value
+        )));
+    }
+
+    let type_spec = parts.get(1).copied().unwrap_or("string");
+    let param_type = match type_spec {
+        "string" | "" => ParameterType::String,
+        "integer" => ParameterType::Integer,
+        "number" => ParameterType::Number,
+        "boolean" => ParameterType::Boolean,
+        "date" => ParameterType::Date,
+        "list" => ParameterType::List,
+        spec => match spec
+            .strip_prefix("choice(")
+            .and_then(|s| s.strip_suffix(')'))
+        {
+            Some(choices) => {
+                let choices: Vec<String> = choices
+                    .split(',')
+                    .map(|c| c.trim().to_string())
+                    .filter(|c| !c.is_empty())
+                    .collect();
+                if choices.is_empty() {
+                    return Err(template_error(format!("choice() vide pour '{}'", name)));
+                }
+                ParameterType::Choice(choices)
+            }
+            None => {
+                return Err(template_error(format!(
+                    "Type de paramètre inconnu pour '{}': {}",
+                    name, spec
+                )))
+            }
+        },
+    };
+
+    let mut required = false;
+    let mut default = None;
+    match parts.get(2).copied().unwrap_or("") {
+        "" | "optional" => {}
+        "required" => required = true,
+        flag => match flag.strip_prefix("default=") {
+            Some(d) => default = Some(d.to_string()),
+            None => {
+                return Err(template_error(format!(
+                    "Option de paramètre inconnue pour '{}': {}",
+                    name, flag
+                )))
+            }
+        },
+    }
+
+    let parameter = TemplateParameter {
+        name: name.to_string(),
+        param_type,
+        required,
+        default,
+        description: parts.get(3).map(|d| d.to_string()).unwrap_or_default(),
+    };
+    // La valeur par défaut doit elle-même respecter le type
+    if let Some(default) = &parameter.default {
+        convert_value(&parameter, default)
+            .map_err(|e| template_error(format!("Défaut invalide: {}", e)))?;
+    }
+    Ok(parameter)
+}
+
+pub fn render_template_file(template: &Template) -> Result<String> {
+    let source = template.source.as_ref().ok_or_else(|| {
+        template_error(format!(
+            "Le template '{}' n'est pas un template fichier",
+            template.id
+        ))
+    })?;
+
+    let mut out = String::new();
+    out.push_str(FRONTMATTER_DELIMITER);
+    out.push('\n');
+    out.push_str(&format!("id: {}\n", template.id));
+    out.push_str(&format!("name: {}\n", template.name));
+    out.push_str(&format!("doc_type: {:?}\n", template.doc_type));
+    out.push_str(&format!("style: {:?}\n", template.default_style));
+    out.push_str(&format!("title: {}\n", source.title));
+    if let Some(summary) = &source.summary {
+        out.push_str(&format!("summary: {}\n", summary));
+    }
+    for objective in &source.objectives {
+        out.push_str(&format!("objective: {}\n", objective));
+    }
+    for clause in &source.clauses {
+        out.push_str(&format!("clause: {}\n", clause));
+    }
+    for parameter in &template.parameters {
+        let flag = match (&parameter.default, parameter.required) {
+            (Some(d), _) => format!("default={}", d),
+            (None, true) => "required".to_string(),
+            (None, false) => "optional".to_string(),
+        };
+        let mut line = format!(
+            "param: {} | {} | {}",
+            parameter.name,
+            parameter.param_type.as_str(),
+            flag
+        );
+        if !parameter.description.is_empty() {
+            line.push_str(&format!(" | {}", parameter.description));
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.push_str(FRONTMATTER_DELIMITER);
+    out.push('\n');
+    out.push_str(&source.body);
+    out.push('\n');
+    Ok(out)
+}
+
+/// Partiel : frontmatter facultatif (`title`, `category`, `mandatory`)
+pub fn parse_partial_file(name: &str, content: &str) -> Result<Partial> {
+    validate_partial_name(name)?;
+    let (entries, body) = split_frontmatter(content, false)?;
+
+    let mut partial = Partial {
+        name: name.to_string(),
+        title: None,
+        category: None,
+        mandatory: true,
+        body,
+    };
+    for (key, value) in entries {
+        match key.as_str() {
+            "title" => partial.title = Some(value),
+            "category" => partial.category = Some(parse_variant("category", &value)?),
+            "mandatory" => {
+                partial.mandatory = value
+                    .parse()
+                    .map_err(|_| template_error(format!("mandatory invalide: '{}'", value)))?
+            }
+            other => {
+                return Err(template_error(format!(
+                    "Clé de frontmatter inconnue: '{}'",
+                    other
+                )))
+            }
+        }
+    }
+
+    Environment::new()
+        .template_from_str(&partial.body)
+        .map_err(|e| template_error(format!("Partiel '{}' invalide: {}", name, e)))?;
+    Ok(partial)
+}
+
+pub fn render_partial_file(partial: &Partial) -> String {
+    let mut out = String::new();
+    if partial.title.is_some() || partial.category.is_some() || !partial.mandatory {
+        out.push_str(FRONTMATTER_DELIMITER);
+        out.push('\n');
+        if let Some(title) = &partial.title {
+            out.push_str(&format!("title: {}\n", title));
+        }
+        if let Some(category) = &partial.category {
+            out.push_str(&format!("category: {:?}\n", category));
+        }
+        out.push_str(&format!("mandatory: {}\n", partial.mandatory));
+        out.push_str(FRONTMATTER_DELIMITER);
+        out.push('\n');
+    }
+    out.push_str(&partial.body);
+    out.push('\n');
+    out
+}
+
+/// Compile chaque fragment et vérifie que les variables utilisées sont déclarées.
+/// Les partiels cités (clauses et `include`, récursivement) n'ont accès qu'aux
+/// paramètres du template qui les cite : leurs corps sont vérifiés avec lui.
+fn check_sources(
+    id: &str,
+    source: &TemplateSource,
+    parameters: &[TemplateParameter],
+    partials: &HashMap<String, Partial>,
+) -> Result<()> {
+    let env = Environment::new();
+    let mut undeclared = Vec::new();
+    let fragments = [&source.title, &source.body]
+        .into_iter()
+        .chain(source.summary.as_ref())
+        .chain(source.objectives.iter())
+        .map(|fragment| (None, fragment))
+        .chain(
+            cited_partials(source, partials)
+                .into_iter()
+                .map(|partial| (Some(&partial.name), &partial.body)),
+        );
+
+    for (partial, fragment) in fragments {
+        let compiled = env.template_from_str(fragment).map_err(|e| match partial {
+            Some(name) => template_error(format!("Partiel '{}' invalide: {}", name, e)),
+            None => template_error(format!("Template '{}' invalide: {}", id, e)),
+        })?;
+        for variable in compiled.undeclared_variables(false) {
+            let declared = parameters.iter().any(|p| p.name == variable)
+                || BUILTIN_VARIABLES.contains(&variable.as_str())
+                || variable == "title";
+            let entry = match partial {
+                Some(name) => format!("{} (partiel '{}')", variable, name),
+                None => variable,
+            };
+            if !declared && !undeclared.contains(&entry) {
+                undeclared.push(entry);
+            }
+        }
+    }
+
+    if undeclared.is_empty() {
+        Ok(())
+    } else {
+        undeclared.sort();
+        Err(template_error(format!(
+            "Variables utilisées mais non déclarées dans '{}': {}",
+            id,
+            undeclared.join(", ")
+        )))
+    }
+}
+
+/// Vérifie un template fichier avec les partiels disponibles (sans effet
+/// sur les templates intégrés)
+pub fn check_template(template: &Template, partials: &HashMap<String, Partial>) -> Result<()> {
+    match template.source.as_ref() {
+        Some(source) => check_sources(&template.id, source, &template.parameters, partials),
+        None => Ok(()),
+    }
+}
+
+/// Partiels atteints depuis les clauses et les `include` du corps, puis depuis
+/// leurs propres `include` ; les noms absents sont ignorés (`missing_partials`)
+fn cited_partials<'a>(
+    source: &TemplateSource,
+    partials: &'a HashMap<String, Partial>,
+) -> Vec<&'a Partial> {
+    let mut pending: Vec<String> = source.clauses.clone();
+    pending.extend(included_names(&source.body));
+    let mut seen = HashSet::new();
+    let mut cited = Vec::new();
+    while let Some(name) = pending.pop() {
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+        if let Some(partial) = partials.get(&name) {
+            pending.extend(included_names(&partial.body));
+            cited.push(partial);
+        }
+    }
+    cited.sort_by(|a, b| a.name.cmp(&b.name));
+    cited
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+pub fn validate_template_id(id: &str) -> Result<()> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(template_error(format!(
+            "Identifiant de template invalide '{}' (a-z, 0-9, _ et - uniquement)",
+            id
+        )))
+    }
+}
+
+/// Nom de partiel : segments `a-z0-9_-` séparés par `/` (sous-dossiers)
+pub fn validate_partial_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(template_error(format!(
+            "Nom de partiel invalide '{}' (a-z, 0-9, _, - et / uniquement)",
+            name
+        )))
+    }
+}
+
+/// Plan indicatif du template : titres `#` de son corps
+fn outline(body: &str) -> Vec<SectionTemplate> {
+    body.lines()
+        .filter_map(|line| line.strip_prefix("# "))
+        .enumerate()
+        .map(|(i, title)| SectionTemplate {
+            id: format!("section_{}", i + 1),
+            title: title.trim().to_string(),
+            required: true,
+            default_content: String::new(),
+            order: (i + 1).min(u8::MAX as usize) as u8,
+        })
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Paramètres
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn convert_value(parameter: &TemplateParameter, raw: &str) -> std::result::Result<Value, String> {
+    let raw = raw.trim();
+    match &parameter.param_type {
+        ParameterType::String => Ok(Value::from(raw)),
+        ParameterType::Integer => raw
+            .parse::<i64>()
+            .map(Value::from)
+            .map_err(|_| format!("'{}' attend un entier, reçu '{}'", parameter.name, raw)),
+        ParameterType::Number => raw
+            .replace(',', ".")
+            .parse::<f64>()
+            .map(Value::from)
+            .map_err(|_| format!("'{}' attend un nombre, reçu '{}'", parameter.name, raw)),
+        ParameterType::Boolean => match raw.to_lowercase().as_str() {
+            "true" | "oui" | "yes" | "1" => Ok(Value::from(true)),
+            "false" | "non" | "no" | "0" | "" => Ok(Value::from(false)),
+            _ => Err(format!(
+                "'{}' attend un booléen, reçu '{}'",
+                parameter.name, raw
+            )),
+        },
+        ParameterType::Date => chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+            .or_else(|_| chrono::NaiveDate::parse_from_str(raw, "%d/%m/%Y"))
+            .map(|date| Value::from(date.format("%Y-%m-%d").to_string()))
+            .map_err(|_| {
+                format!(
+                    "'{}' attend une date AAAA-MM-JJ, reçu '{}'",
+                    parameter.name, raw
+                )
+            }),
+        ParameterType::List => Ok(Value::from(
+            raw.split(';')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(String::from)
+                .collect::<Vec<_>>(),
+        )),
+        ParameterType::Choice(choices) => {
+            if choices.iter().any(|c| c == raw) {
+                Ok(Value::from(raw))
+            } else {
+                Err(format!(
+                    "'{}' doit valoir {}, reçu '{}'",
+                    parameter.name,
+                    choices.join(" | "),
+                    raw
+                ))
+            }
+        }
+    }
+}
+
+/// Valide `params` contre les paramètres déclarés et construit le contexte
+/// de rendu ; toutes les erreurs sont rapportées ensemble
+pub fn validate_params(
+    template: &Template,
+    params: &HashMap<String, String>,
+) -> Result<BTreeMap<String, Value>> {
+    let mut context: BTreeMap<String, Value> = BTreeMap::new();
+    // Paramètres non déclarés : transmis tels quels, accessibles via `params`
+    context.insert("params".to_string(), Value::from_serialize(params));
+    if let Some(title) = params.get("title") {
+        context.insert("title".to_string(), Value::from(title.as_str()));
+    }
+
+    let mut errors = Vec::new();
+    for parameter in &template.parameters {
+        let raw = params
+            .get(&parameter.name)
+            .filter(|v| !v.trim().is_empty())
+            .or(parameter.default.as_ref());
+        let value = match raw {
+            Some(raw) => convert_value(parameter, raw),
+            None if parameter.required => Err(format!("'{}' est requis", parameter.name)),
+            None => Ok(Value::from(())),
+        };
+        match value {
+            Ok(value) => {
+                context.insert(parameter.name.clone(), value);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(template_error(format!(
+            "Paramètres invalides pour '{}': {}",
+            template.id,
+            errors.join("; ")
+        )));
+    }
+
+    context.insert(
+        "today".to_string(),
+        Value::from(chrono::Local::now().format("%Y-%m-%d").to_string()),
+    );
+    context.insert(
+        "doc_type".to_string(),
+        Value::from(format!("{:?}", template.doc_type)),
+    );
+    Ok(context)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Rendu
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Filtre `date` : `{{ date_effet | date("%d/%m/%Y") }}`
+fn format_date(
+    value: String,
+    format: Option<String>,
+) -> std::result::Result<String, minijinja::Error> {
+    let date = chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|_| {
+        minijinja::Error::new(
+            minijinja::ErrorKind::InvalidOperation,
+            format!("date invalide: '{}'", value),
+        )
+    })?;
+    Ok(date
+        .format(format.as_deref().unwrap_or("%d/%m/%Y"))
+        .to_string())
+}
+
+fn environment(partials: &HashMap<String, Partial>) -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::SemiStrict);
+    env.set_auto_escape_callback(|_| AutoEscape::None);
+    env.set_keep_trailing_newline(true);
+    env.add_filter("date", format_date);
+
+    let sources: HashMap<String, String> = partials
+        .iter()
+        .map(|(name, partial)| (name.clone(), partial.body.clone()))
+        .collect();
+    env.set_loader(move |name| Ok(sources.get(name).cloned()));
+    env
+}
+
+/// Rend un template fichier en contenu de document
+pub fn render_content(
+    template: &Template,
+    partials: &HashMap<String, Partial>,
+    params: &HashMap<String, String>,
+) -> Result<DocumentContent> {
+    let source = template.source.as_ref().ok_or_else(|| {
+        template_error(format!(
+            "Le template '{}' n'est pas un template fichier",
+            template.id
+        ))
+    })?;
+    let context = validate_params(template, params)?;
+    let env = environment(partials);
+    let render = |label: &str, fragment: &str| {
+        env.render_str(fragment, &context)
+            .map_err(|e| template_error(format!("Rendu de '{}' ({}): {}", template.id, label, e)))
+    };
+
+    let title = render("title", &source.title)?.trim().to_string();
+    let body = render("corps", &source.body)?;
+    let (preamble, sections) = split_sections(&body);
+
+    let mut executive_summary = match &source.summary {
+        Some(summary) => render("summary", summary)?.trim().to_string(),
+        None => String::new(),
+    };
+    if !preamble.is_empty() {
+        if !executive_summary.is_empty() {
+            executive_summary.push_str("\n\n");
+        }
+        executive_summary.push_str(&preamble);
+    }
+
+    let mut objectives = Vec::new();
+    for objective in &source.objectives {
+        let rendered = render("objective", objective)?.trim().to_string();
+        if !rendered.is_empty() {
+            objectives.push(rendered);
+        }
+    }
+
+    let mut clauses = Vec::new();
+    for (i, name) in source.clauses.iter().enumerate() {
+        let partial = partials.get(name).ok_or_else(|| {
+            template_error(format!(
+                "Partiel '{}' introuvable (template '{}')",
+                name, template.id
+            ))
+        })?;
+        let content = env
+            .get_template(name)
+            .and_then(|t| t.render(&context))
+            .map_err(|e| template_error(format!("Rendu de la clause '{}': {}", name, e)))?;
+        clauses.push(Clause {
+            id: format!("clause_{}", i + 1),
+            title: partial
+                .title
+                .clone()
+                .unwrap_or_else(|| name.rsplit('/').next().unwrap_or(name).to_string()),
+            content: content.trim().to_string(),
+            mandatory: partial.mandatory,
+            category: partial.category.clone().unwrap_or(ClauseCategory::General),
+        });
+    }
+
+    Ok(DocumentContent {
+        title,
+        executive_summary,
+        objectives,
+        sections,
+        mandatory_clauses: if clauses.is_empty() {
+            None
+        } else {
+            Some(clauses)
+        },
+        annexes: vec![],
+        references: vec![],
+    })
+}
+
+/// Découpe le Markdown rendu : `#` ouvre une section, `##` une sous-section.
+/// Le texte précédant le premier titre est renvoyé à part.
+fn split_sections(markdown: &str) -> (String, Vec<Section>) {
+    let mut preamble = Vec::new();
+    let mut sections: Vec<Section> = Vec::new();
+    let mut in_fence = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        let heading = if in_fence {
+            None
+        } else if let Some(title) = line.strip_prefix("# ") {
+            Some((1, title))
+        } else {
+            line.strip_prefix("## ").map(|title| (2, title))
+        };
+
+        match heading {
+            Some((1, title)) => sections.push(Section {
+                id: format!("section_{}", sections.len() + 1),
+                title: title.trim().to_string(),
+                content: String::new(),
+                subsections: vec![],
+                level: 1,
+            }),
+            Some((_, title)) if !sections.is_empty() => {
+                let parent = sections.last_mut().expect("section parente");
+                parent.subsections.push(Section {
+                    id: format!("{}_{}", parent.id, parent.subsections.len() + 1),
+                    title: title.trim().to_string(),
+                    content: String::new(),
+                    subsections: vec![],
+                    level: 2,
+                });
+            }
+            _ => {
+                let target = match sections.last_mut() {
+                    Some(section) => match section.subsections.last_mut() {
+                        Some(subsection) => &mut subsection.content,
+                        None => &mut section.content,
+                    },
+                    None => {
+                        preamble.push(line);
+                        continue;
+                    }
+                };
+                target.push_str(line);
+                target.push('\n');
+            }
+        }
+    }
+
+    for section in &mut sections {
+        section.content = section.content.trim().to_string();
+        for subsection in &mut section.subsections {
+            subsection.content = subsection.content.trim().to_string();
+        }
+    }
+    (preamble.join("\n").trim().to_string(), sections)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Répertoire de templates
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub fn default_templates_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("titane")
+        .join("templates")
+}
+
+pub fn template_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}{}", id, TEMPLATE_FILE_EXTENSION))
+}
+
+pub fn partial_path(dir: &Path, name: &str) -> PathBuf {
+    let mut path = dir.join(PARTIALS_DIR);
+    for segment in name.split('/') {
+        path.push(segment);
+    }
+    let file_name = format!(
+        "{}{}",
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default(),
+        PARTIAL_FILE_EXTENSION
+    );
+    path.set_file_name(file_name);
+    path
+}
+
+fn files_with_suffix(dir: &Path, suffix: &str, recursive: bool, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.is_dir() {
+            if recursive {
+                files_with_suffix(&path, suffix, recursive, files);
+            }
+        } else if path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.ends_with(suffix))
+        {
+            files.push(path);
+        }
+    }
+    files.sort();
+}
+
+/// Templates et partiels d'un répertoire ; les fichiers invalides sont
+/// signalés dans la liste d'erreurs sans bloquer les autres
+pub fn load_directory(
+    dir: &Path,
+) -> (
+    HashMap<String, Template>,
+    HashMap<String, Partial>,
+    Vec<String>,
+) {
+    let mut templates = HashMap::new();
+    let mut partials = HashMap::new();
+    let mut errors = Vec::new();
+
+    let mut partial_files = Vec::new();
+    let partials_root = dir.join(PARTIALS_DIR);
+    files_with_suffix(
+        &partials_root,
+        PARTIAL_FILE_EXTENSION,
+        true,
+        &mut partial_files,
+    );
+    for path in partial_files {
+        let name = path
+            .strip_prefix(&partials_root)
+            .ok()
+            .and_then(|relative| relative.to_str())
+            .map(|relative| {
+                relative
+                    .trim_end_matches(PARTIAL_FILE_EXTENSION)
+                    .replace('\\', "/")
+            })
+            .unwrap_or_default();
+        let result = fs::read_to_string(&path)
+            .map_err(|e| template_error(e.to_string()))
+            .and_then(|content| parse_partial_file(&name, &content));
+        match result {
+            Ok(partial) => {
+                partials.insert(partial.name.clone(), partial);
+            }
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    let mut template_files = Vec::new();
+    files_with_suffix(dir, TEMPLATE_FILE_EXTENSION, false, &mut template_files);
+    for path in template_files {
+        let result = fs::read_to_string(&path)
+            .map_err(|e| template_error(e.to_string()))
+            .and_then(|content| parse_template_file(&content));
+        match result {
+            Ok(mut template) => {
+                if let Some(source) = template.source.as_mut() {
+                    source.path = Some(path.to_string_lossy().to_string());
+                }
+                if templates.contains_key(&template.id) {
+                    errors.push(format!(
+                        "{}: template '{}' déjà défini",
+                        path.display(),
+                        template.id
+                    ));
+                    continue;
+                }
+                if let Err(e) = check_template(&template, &partials) {
+                    errors.push(format!("{}: {}", path.display(), e));
+                    continue;
+                }
+                templates.insert(template.id.clone(), template);
+            }
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    (templates, partials, errors)
+}
+
+fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| template_error(e.to_string()))?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content).map_err(|e| template_error(e.to_string()))?;
+    fs::rename(&tmp, path).map_err(|e| template_error(e.to_string()))
+}
+
+pub fn write_template(dir: &Path, template: &Template) -> Result<PathBuf> {
+    validate_template_id(&template.id)?;
+    let path = template_path(dir, &template.id);
+    write_atomic(&path, &render_template_file(template)?)?;
+    Ok(path)
+}
+
+pub fn write_partial(dir: &Path, partial: &Partial) -> Result<PathBuf> {
+    validate_partial_name(&partial.name)?;
+    let path = partial_path(dir, &partial.name);
+    write_atomic(&path, &render_partial_file(partial))?;
+    Ok(path)
+}
+
+pub fn delete_file(path: &Path) -> Result<()> {
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| template_error(e.to_string()))?;
+    }
+    Ok(())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Bibliothèque (import / export)
+// ─────────────────────────────────────────────────────────────────────────────
+
+impl TemplateLibrary {
+    pub fn new() -> Self {
+        Self {
+            format_version: LIBRARY_FORMAT_VERSION,
+            exported_at: chrono::Utc::now(),
+            templates: vec![],
+            partials: vec![],
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| template_error(format!("Sérialisation de la bibliothèque: {}", e)))?;
+        write_atomic(path, &json)
+    }
+
+    /// Lit et vérifie une bibliothèque : chaque fichier est reparsé
+    pub fn read(path: &Path) -> Result<(Vec<Template>, Vec<Partial>)> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| template_error(format!("Lecture de la bibliothèque: {}", e)))?;
+        let library: TemplateLibrary = serde_json::from_str(&json)
+            .map_err(|e| template_error(format!("Bibliothèque invalide: {}", e)))?;
+        if library.format_version > LIBRARY_FORMAT_VERSION {
+            return Err(template_error(format!(
+                "Version de bibliothèque non supportée: {}",
+                library.format_version
+            )));
+        }
+
+        let partials = library
+            .partials
+            .iter()
+            .map(|file| parse_partial_file(&file.name, &file.content))
+            .collect::<Result<Vec<_>>>()?;
+        let templates = library
+            .templates
+            .iter()
+            .map(|file| {
+                let template = parse_template_file(&file.content)?;
+                if template.id != file.name {
+                    return Err(template_error(format!(
+                        "Fichier '{}' : identifiant '{}' différent",
+                        file.name, template.id
+                    )));
+                }
+                Ok(template)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((templates, partials))
+    }
+}
+
+impl Default for TemplateLibrary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Partiels cités par un template (clauses et `include`) absents de `available`
+pub fn missing_partials(template: &Template, available: &HashSet<String>) -> Vec<String> {
+    let Some(source) = template.source.as_ref() else {
+        return vec![];
+    };
+    let mut names: Vec<String> = source.clauses.clone();
+    names.extend(included_names(&source.body));
+    let mut missing: Vec<String> = names
+        .into_iter()
+        .filter(|n| !available.contains(n))
+        .collect();
+    missing.sort();
+    missing.dedup();
+    missing
+}
+
+/// Noms littéraux des `{% include "…" %}` / `{% import "…" %}` / `{% from "…" %}`
+fn included_names(body: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("{%") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("%}") else {
+            break;
+        };
+        let tag = after[..end].trim().trim_start_matches('-').trim();
+        let keyword = tag.split_whitespace().next().unwrap_or_default();
+        if matches!(keyword, "include" | "import" | "from") {
+            let quoted = tag
+                .split(['"', '\''])
+                .nth(1)
+                .filter(|name| !name.is_empty());
+            if let Some(name) = quoted {
+                names.push(name.to_string());
+            }
+        }
+        rest = &after[end + 2..];
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PRESTATION: &str = "---
+id: prestation
+name: Contrat de prestation
+doc_type: Contract
+style: Legal
+title: Contrat — {{ client }}
+summary: Prestations pour {{ client }} sur {{ duree }} mois.
+objective: Encadrer les prestations
+clause: clauses/confidentialite
+param: client | string | required | Nom du client
+param: duree | integer | default=12 | Durée en mois
+param: juridiction | choice(Paris, Lyon) | default=Paris
+param: services | list | required
+param: date_effet | date | required
+param: renouvellement | boolean | default=false
+---
+# Objet
+
+{% for service in services %}- {{ service }}
+{% endfor %}
+## Date d'effet
+
+{{ date_effet | date }}{% if renouvellement %}, renouvellement tacite{% endif %}.
+
+# Juridiction
+
+{% include \"clauses/tribunal\" %}";
+
+    fn partial(name: &str, content: &str) -> Partial {
+        parse_partial_file(name, content).unwrap()
+    }
+
+    fn partials() -> HashMap<String, Partial> {
+        [
+            partial(
+                "clauses/confidentialite",
+                "---\ntitle: Confidentialité\ncategory: Confidentiality\n---\n{{ client }} garde le secret.",
+            ),
+            partial("clauses/tribunal", "Tribunal de {{ juridiction }}."),
+        ]
+        .into_iter()
+        .map(|p| (p.name.clone(), p))
+        .collect()
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_template_file_round_trip() {
+        let template = parse_template_file(PRESTATION).unwrap();
+        assert_eq!(template.parameters.len(), 6);
+        assert_eq!(
+            template.parameters[2].param_type,
+            ParameterType::Choice(vec!["Paris".to_string(), "Lyon".to_string()])
+        );
+
+        let rendered = render_template_file(&template).unwrap();
+        let reparsed = parse_template_file(&rendered).unwrap();
+        assert_eq!(render_template_file(&reparsed).unwrap(), rendered);
+        assert_eq!(reparsed.source.unwrap().body, template.source.unwrap().body);
+    }
+
+    #[test]
+    fn test_partial_file_round_trip() {
+        for (name, content) in [
+            ("clauses/tribunal", "Tribunal de {{ juridiction }}."),
+            (
+                "clauses/option",
+                "---\ntitle: Option\ncategory: Termination\nmandatory: false\n---\nRésiliable.",
+            ),
+        ] {
+            let parsed = partial(name, content);
+            let reparsed = partial(name, &render_partial_file(&parsed));
+            assert_eq!(reparsed.title, parsed.title);
+            assert_eq!(reparsed.category, parsed.category);
+            assert_eq!(reparsed.mandatory, parsed.mandatory);
+            assert_eq!(reparsed.body, parsed.body);
+        }
+    }
+
+    #[test]
+    fn test_render_content_with_partials() {
+        let template = parse_template_file(PRESTATION).unwrap();
+        let content = render_content(
+            &template,
+            &partials(),
+            &params(&[
+                ("client", "ACME"),
+                ("services", "Audit; Conseil"),
+                ("date_effet", "2026-03-01"),
+                ("renouvellement", "oui"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(content.title, "Contrat — ACME");
+        assert_eq!(content.executive_summary, "Prestations pour ACME sur 12 mois.");
+        assert_eq!(content.sections.len(), 2);
+        assert!(content.sections[0].content.contains("- Audit\n- Conseil"));
+        assert_eq!(content.sections[0].subsections.len(), 1);
+        assert!(content.sections[0].subsections[0]
+            .content
+            .contains("01/03/2026, renouvellement tacite."));
+        assert!(content.sections[1].content.contains("Tribunal de Paris."));
+        let clauses = content.mandatory_clauses.unwrap();
+        assert_eq!(clauses.len(), 1);
+        assert_eq!(clauses[0].title, "Confidentialité");
+        assert_eq!(clauses[0].content, "ACME garde le secret.");
+    }
+
+    #[test]
+    fn test_validate_params_reports_every_type_error() {
+        let template = parse_template_file(PRESTATION).unwrap();
+        let error = validate_params(
+            &template,
+            &params(&[
+                ("duree", "douze"),
+                ("juridiction", "Marseille"),
+                ("date_effet", "2026-13-40"),
+                ("renouvellement", "peut-être"),
+            ]),
+        )
+        .unwrap_err()
+        .to_string();
+
+        for name in ["client", "duree", "juridiction", "services", "date_effet", "renouvellement"] {
+            assert!(error.contains(&format!("'{}'", name)), "{} absent de: {}", name, error);
+        }
+
+        let context = validate_params(
+            &template,
+            &params(&[
+                ("client", "ACME"),
+                ("services", "a;b"),
+                ("date_effet", "01/03/2026"),
+                ("extra", "libre"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(context["date_effet"].to_string(), "2026-03-01");
+        assert_eq!(context["duree"], Value::from(12));
+        assert_eq!(context["params"].get_attr("extra").unwrap().to_string(), "libre");
+    }
+
+    #[test]
+    fn test_undeclared_variables_are_rejected() {
+        let error = parse_template_file(&PRESTATION.replace("{{ client }} sur", "{{ inconnu }} sur"))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("inconnu"), "{}", error);
+
+        let template = parse_template_file(PRESTATION).unwrap();
+        assert!(check_template(&template, &partials()).is_ok());
+
+        // Variables d'un partiel cité directement ou via un autre partiel
+        let mut partials = partials();
+        partials.insert(
+            "clauses/tribunal".to_string(),
+            partial("clauses/tribunal", "{% include \"clauses/appel\" %}"),
+        );
+        partials.insert(
+            "clauses/appel".to_string(),
+            partial("clauses/appel", "Cour d'appel de {{ ville }}."),
+        );
+        partials.insert(
+            "clauses/confidentialite".to_string(),
+            partial("clauses/confidentialite", "{{ duree_secret }} ans."),
+        );
+        let error = check_template(&template, &partials).unwrap_err().to_string();
+        assert!(error.contains("ville (partiel 'clauses/appel')"), "{}", error);
+        assert!(
+            error.contains("duree_secret (partiel 'clauses/confidentialite')"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_load_directory_rejects_undeclared_partial_variables() {
+        let dir = TempDir::new().unwrap();
+        let template = parse_template_file(PRESTATION).unwrap();
+        write_template(dir.path(), &template).unwrap();
+        write_partial(dir.path(), &partial("clauses/confidentialite", "Secret.")).unwrap();
+        write_partial(dir.path(), &partial("clauses/tribunal", "{{ ville }}")).unwrap();
+
+        let (templates, partials, errors) = load_directory(dir.path());
+        assert!(templates.is_empty());
+        assert_eq!(partials.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("ville"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn test_library_import_rejects_mismatched_id() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("bibliotheque.json");
+        let mut library = TemplateLibrary::new();
+        library.templates.push(LibraryFile {
+            name: "prestation".to_string(),
+            content: PRESTATION.to_string(),
+        });
+        library.partials.push(LibraryFile {
+            name: "clauses/tribunal".to_string(),
+            content: "Tribunal de {{ juridiction }}.".to_string(),
+        });
+        library.write(&path).unwrap();
+        let (templates, partials) = TemplateLibrary::read(&path).unwrap();
+        assert_eq!(templates[0].id, "prestation");
+        assert_eq!(partials[0].name, "clauses/tribunal");
+
+        library.templates[0].name = "autre".to_string();
+        library.write(&path).unwrap();
+        let error = TemplateLibrary::read(&path).unwrap_err().to_string();
+        assert!(error.contains("'autre'") && error.contains("'prestation'"), "{}", error);
+
+        library.templates.clear();
+        library.partials[0].name = "../evasion".to_string();
+        library.write(&path).unwrap();
+        assert!(TemplateLibrary::read(&path).is_err());
+    }
+
+    #[test]
+    fn test_validate_partial_name_rejects_traversal() {
+        for name in ["clauses/tribunal", "nda", "a-b/c_d/e1"] {
+            assert!(validate_partial_name(name).is_ok(), "{}", name);
+        }
+        for name in [
+            "", "..", "../x", "a/../b", "/x", "x/", "a//b", "A", "a\\b", "a/./b", "c:x",
+        ] {
+            assert!(validate_partial_name(name).is_err(), "{}", name);
+        }
+        assert!(parse_template_file(&PRESTATION.replace(
+            "clause: clauses/confidentialite",
+            "clause: ../secrets"
+        ))
+        .is_err());
+    }
+}
@@ -1,7 +1,11 @@
 // Moteur de templates pour documents
+// Templates intégrés + templates fichiers (voir `template_files`)
 
+use super::template_files::{self, Partial, TemplateLibrary, TemplateParameter, TemplateSource};
 use super::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Template {
@@ -10,6 +14,12 @@ pub struct Template {
     pub doc_type: DocumentType,
     pub sections: Vec<SectionTemplate>,
     pub default_style: DocumentStyle,
+    /// Paramètres typés attendus dans `DocumentGenerator::generate`
+    #[serde(default)]
+    pub parameters: Vec<TemplateParameter>,
+    /// Source du template fichier ; `None` pour les templates intégrés
+    #[serde(default)]
+    pub source: Option<TemplateSource>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,24 +33,292 @@ pub struct SectionTemplate {
 
 pub struct TemplateEngine {
     templates: std::collections::HashMap<String, Template>,
+    partials: std::collections::HashMap<String, Partial>,
+    directory: Option<PathBuf>,
+    load_errors: Vec<String>,
+}
+
+/// Bilan d'un import de bibliothèque
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryImportReport {
+    pub templates_installed: Vec<String>,
+    pub partials_installed: Vec<String>,
+    /// Éléments déjà présents, conservés faute de `overwrite`
+    pub skipped: Vec<String>,
 }
 
 impl TemplateEngine {
     pub fn new() -> Self {
         let mut engine = Self {
             templates: std::collections::HashMap::new(),
+            partials: std::collections::HashMap::new(),
+            directory: None,
+            load_errors: Vec::new(),
         };
         engine.load_default_templates();
         engine
     }
     
+    /// Templates intégrés + templates du répertoire `dir` (créé si absent).
+    /// Un template fichier remplace le template intégré de même id ; les
+    /// fichiers invalides sont ignorés et listés dans `load_errors()`.
+    pub fn with_directory(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            DocEngineError::TemplateError(format!("Création de {}: {}", dir.display(), e))
+        })?;
+        
+        let mut engine = Self::new();
+        engine.directory = Some(dir);
+        engine.reload();
+        Ok(engine)
+    }
+    
+    /// Relit le répertoire de templates
+    pub fn reload(&mut self) {
+        let Some(dir) = self.directory.clone() else {
+            return;
+        };
+        let (templates, partials, errors) = template_files::load_directory(&dir);
+        self.templates.retain(|_, t| t.source.is_none());
+        self.load_default_templates_missing();
+        self.templates.extend(templates);
+        self.partials = partials;
+        self.load_errors = errors;
+    }
+    
+    pub fn directory(&self) -> Option<&Path> {
+        self.directory.as_deref()
+    }
+    
+    pub fn load_errors(&self) -> &[String] {
+        &self.load_errors
+    }
+    
     pub fn get_template(&self, doc_type: &DocumentType) -> Result<&Template> {
         let template_id = format!("{:?}", doc_type).to_lowercase();
-        self.templates
-            .get(&template_id)
+        if let Some(template) = self.templates.get(&template_id) {
+            return Ok(template);
+        }
+        // Sinon, premier template fichier (par id) déclarant ce type
+        let mut candidates: Vec<&Template> = self
+            .templates
+            .values()
+            .filter(|t| &t.doc_type == doc_type)
+            .collect();
+        candidates.sort_by(|a, b| a.id.cmp(&b.id));
+        candidates
+            .into_iter()
+            .next()
             .ok_or_else(|| DocEngineError::TemplateError(format!("Template not found for {:?}", doc_type)))
     }
     
+    pub fn get_template_by_id(&self, id: &str) -> Result<&Template> {
+        self.templates
+            .get(id)
+            .ok_or_else(|| DocEngineError::TemplateError(format!("Template '{}' introuvable", id)))
+    }
+    
+    /// Templates triés par id
+    pub fn list_templates(&self) -> Vec<&Template> {
+        let mut templates: Vec<&Template> = self.templates.values().collect();
+        templates.sort_by(|a, b| a.id.cmp(&b.id));
+        templates
+    }
+    
+    pub fn list_partials(&self) -> Vec<&Partial> {
+        let mut partials: Vec<&Partial> = self.partials.values().collect();
+        partials.sort_by(|a, b| a.name.cmp(&b.name));
+        partials
+    }
+    
+    /// Rend un template fichier avec les paramètres fournis
+    pub fn render(&self, template: &Template, params: &HashMap<String, String>) -> Result<DocumentContent> {
+        template_files::render_content(template, &self.partials, params)
+    }
+    
+    fn require_directory(&self) -> Result<&Path> {
+        self.directory.as_deref().ok_or_else(|| {
+            DocEngineError::TemplateError("Aucun répertoire de templates configuré".to_string())
+        })
+    }
+    
+    fn check_partials(&self, template: &Template, extra: &[Partial]) -> Result<()> {
+        let available: HashSet<String> = self
+            .partials
+            .keys()
+            .cloned()
+            .chain(extra.iter().map(|p| p.name.clone()))
+            .collect();
+        let missing = template_files::missing_partials(template, &available);
+        if !missing.is_empty() {
+            return Err(DocEngineError::TemplateError(format!(
+                "Partiels introuvables pour '{}': {}",
+                template.id,
+                missing.join(", ")
+            )));
+        }
+        
+        // Les partiels de `extra` remplacent ceux déjà installés
+        let mut partials = self.partials.clone();
+        partials.extend(extra.iter().map(|p| (p.name.clone(), p.clone())));
+        template_files::check_template(template, &partials)
+    }
+    
+    /// Revérifie les templates fichiers installés avec les partiels `extra`
+    fn check_installed(&self, extra: &[Partial]) -> Result<()> {
+        let mut installed: Vec<&Template> = self
+            .templates
+            .values()
+            .filter(|t| t.source.is_some())
+            .collect();
+        installed.sort_by(|a, b| a.id.cmp(&b.id));
+        installed
+            .into_iter()
+            .try_for_each(|template| self.check_partials(template, extra))
+    }
+    
+    /// Parse, vérifie et enregistre un template fichier (remplace l'existant)
+    pub fn install_template(&mut self, content: &str) -> Result<&Template> {
+        let template = template_files::parse_template_file(content)?;
+        self.save_template(template)
+    }
+    
+    pub fn save_template(&mut self, mut template: Template) -> Result<&Template> {
+        if template.source.is_none() {
+            return Err(DocEngineError::TemplateError(format!(
+                "Le template '{}' n'a pas de source fichier",
+                template.id
+            )));
+        }
+        self.check_partials(&template, &[])?;
+        let path = template_files::write_template(self.require_directory()?, &template)?;
+        if let Some(source) = template.source.as_mut() {
+            source.path = Some(path.to_string_lossy().to_string());
+        }
+        let id = template.id.clone();
+        self.templates.insert(id.clone(), template);
+        Ok(&self.templates[&id])
+    }
+    
+    /// Enregistre un partiel ; les templates installés qui le citent doivent
+    /// en déclarer les variables
+    pub fn save_partial(&mut self, partial: Partial) -> Result<()> {
+        self.check_installed(std::slice::from_ref(&partial))?;
+        template_files::write_partial(self.require_directory()?, &partial)?;
+        self.partials.insert(partial.name.clone(), partial);
+        Ok(())
+    }
+    
+    /// Supprime un template fichier ; le template intégré de même id est rétabli
+    pub fn remove_template(&mut self, id: &str) -> Result<()> {
+        let is_file = self.templates.get(id).is_some_and(|t| t.source.is_some());
+        if !is_file {
+            return Err(DocEngineError::TemplateError(format!(
+                "'{}' n'est pas un template fichier",
+                id
+            )));
+        }
+        template_files::delete_file(&template_files::template_path(self.require_directory()?, id))?;
+        self.templates.remove(id);
+        self.load_default_templates_missing();
+        Ok(())
+    }
+    
+    pub fn remove_partial(&mut self, name: &str) -> Result<()> {
+        template_files::validate_partial_name(name)?;
+        let users: Vec<&str> = self
+            .templates
+            .values()
+            .filter(|t| t.source.as_ref().is_some_and(|s| s.clauses.iter().any(|c| c == name)))
+            .map(|t| t.id.as_str())
+            .collect();
+        if !users.is_empty() {
+            return Err(DocEngineError::TemplateError(format!(
+                "Partiel '{}' utilisé par: {}",
+                name,
+                users.join(", ")
+            )));
+        }
+        template_files::delete_file(&template_files::partial_path(self.require_directory()?, name))?;
+        self.partials.remove(name);
+        Ok(())
+    }
+    
+    /// Exporte des templates fichiers (tous si `ids` est vide) et les
+    /// partiels disponibles vers une bibliothèque JSON
+    pub fn export_library(&self, path: &Path, ids: &[String]) -> Result<TemplateLibrary> {
+        let mut library = TemplateLibrary::new();
+        for template in self.list_templates() {
+            if template.source.is_none() || (!ids.is_empty() && !ids.contains(&template.id)) {
+                continue;
+            }
+            library.templates.push(template_files::LibraryFile {
+                name: template.id.clone(),
+                content: template_files::render_template_file(template)?,
+            });
+        }
+        for id in ids {
+            if !library.templates.iter().any(|f| &f.name == id) {
+                return Err(DocEngineError::TemplateError(format!(
+                    "Template fichier '{}' introuvable",
+                    id
+                )));
+            }
+        }
+        for partial in self.list_partials() {
+            library.partials.push(template_files::LibraryFile {
+                name: partial.name.clone(),
+                content: template_files::render_partial_file(partial),
+            });
+        }
+        
+        library.write(path)?;
+        Ok(library)
+    }
+    
+    /// Importe une bibliothèque : tout est vérifié avant la moindre écriture
+    pub fn import_library(&mut self, path: &Path, overwrite: bool) -> Result<LibraryImportReport> {
+        let (templates, partials) = TemplateLibrary::read(path)?;
+        self.require_directory()?;
+        
+        let mut report = LibraryImportReport::default();
+        let (partials, kept): (Vec<Partial>, Vec<Partial>) = partials
+            .into_iter()
+            .partition(|p| overwrite || !self.partials.contains_key(&p.name));
+        report.skipped.extend(
+            kept.iter()
+                .map(|p| format!("{}/{}", template_files::PARTIALS_DIR, p.name)),
+        );
+        let (templates, kept): (Vec<Template>, Vec<Template>) = templates.into_iter().partition(|t| {
+            overwrite || !self.templates.get(&t.id).is_some_and(|t| t.source.is_some())
+        });
+        report.skipped.extend(kept.into_iter().map(|t| t.id));
+        
+        for template in &templates {
+            self.check_partials(template, &partials)?;
+        }
+        if !partials.is_empty() {
+            let replaced: HashSet<&str> = templates.iter().map(|t| t.id.as_str()).collect();
+            for template in self.templates.values() {
+                if template.source.is_some() && !replaced.contains(template.id.as_str()) {
+                    self.check_partials(template, &partials)?;
+                }
+            }
+        }
+        
+        for partial in partials {
+            report.partials_installed.push(partial.name.clone());
+            template_files::write_partial(self.require_directory()?, &partial)?;
+            self.partials.insert(partial.name.clone(), partial);
+        }
+        for template in templates {
+            report.templates_installed.push(template.id.clone());
+            self.save_template(template)?;
+        }
+        Ok(report)
+    }
+    
     fn load_default_templates(&mut self) {
         // Template contrat
         self.templates.insert("contract".to_string(), Template {
@@ -64,6 +342,8 @@ impl TemplateEngine {
                 },
             ],
             default_style: DocumentStyle::Legal,
+            parameters: vec![],
+            source: None,
         });
         
         // Template NDA
@@ -73,6 +353,8 @@ impl TemplateEngine {
             doc_type: DocumentType::NDA,
             sections: vec![],
             default_style: DocumentStyle::Legal,
+            parameters: vec![],
+            source: None,
         });
         
         // Template chapitre
@@ -82,6 +364,8 @@ impl TemplateEngine {
             doc_type: DocumentType::BookChapter,
             sections: vec![],
             default_style: DocumentStyle::Editorial,
+            parameters: vec![],
+            source: None,
         });
         
         // Template audit
@@ -91,6 +375,8 @@ impl TemplateEngine {
             doc_type: DocumentType::Audit,
             sections: vec![],
             default_style: DocumentStyle::Professional,
+            parameters: vec![],
+            source: None,
         });
         
         // Template architecture
@@ -100,9 +386,18 @@ impl TemplateEngine {
             doc_type: DocumentType::Architecture,
             sections: vec![],
             default_style: DocumentStyle::Technical,
+            parameters: vec![],
+            source: None,
         });
     }
     
+    /// Rétablit les templates intégrés absents (après suppression d'un fichier)
+    fn load_default_templates_missing(&mut self) {
+        let current = std::mem::take(&mut self.templates);
+        self.load_default_templates();
+        self.templates.extend(current);
+    }
+    
     pub fn add_custom_template(&mut self, template: Template) {
         self.templates.insert(template.id.clone(), template);
     }
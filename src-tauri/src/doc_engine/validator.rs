@@ -4,6 +4,7 @@ use super::*;
 
 pub struct DocumentValidator {
     rules: Vec<ValidationRule>,
+    legal_rules: legal_rules::RuleRegistry,
 }
 
 #[derive(Debug, Clone)]
@@ -18,14 +19,27 @@ impl DocumentValidator {
     pub fn new() -> Self {
         Self {
             rules: Self::initialize_rules(),
+            legal_rules: legal_rules::RuleRegistry::builtin(),
         }
     }
     
+    /// Remplace les packs de règles juridiques (par exemple
+    /// `RuleRegistry::with_directory`)
+    pub fn with_rule_registry(mut self, registry: legal_rules::RuleRegistry) -> Self {
+        self.legal_rules = registry;
+        self
+    }
+    
+    pub fn rule_registry(&self) -> &legal_rules::RuleRegistry {
+        &self.legal_rules
+    }
+    
     /// Valide un document complet
     pub fn validate(&self, content: &DocumentContent, config: &GenerationConfig) -> Result<ValidationStatus> {
         let mut errors = Vec::new();
         let mut warnings = Vec::new();
         let mut suggestions = Vec::new();
+        let mut rule_violations = Vec::new();
         
         // Validation structurelle
         self.validate_structure(content, &mut errors, &mut warnings)?;
@@ -44,6 +58,9 @@ impl DocumentValidator {
             _ => {}
         }
         
+        // Validation juridique selon la juridiction
+        self.validate_jurisdiction(content, config, &mut errors, &mut warnings, &mut rule_violations);
+        
         // Validation du contenu
         self.validate_content_quality(content, &mut warnings, &mut suggestions)?;
         
@@ -52,6 +69,7 @@ impl DocumentValidator {
             errors,
             warnings,
             suggestions,
+            rule_violations,
         })
     }
     
@@ -62,6 +80,7 @@ impl DocumentValidator {
                 code: "MISSING_TITLE".to_string(),
                 message: "Le document doit avoir un titre".to_string(),
                 severity: ErrorSeverity::Critical,
+                target: None,
             });
         }
         
@@ -71,6 +90,7 @@ impl DocumentValidator {
                 code: "NO_SECTIONS".to_string(),
                 message: "Le document doit contenir au moins une section".to_string(),
                 severity: ErrorSeverity::High,
+                target: None,
             });
         }
         
@@ -79,6 +99,7 @@ impl DocumentValidator {
             warnings.push(ValidationWarning {
                 message: "Le résumé exécutif est vide".to_string(),
                 suggestion: Some("Ajouter un résumé pour faciliter la compréhension".to_string()),
+                target: None,
             });
         }
         
@@ -98,6 +119,7 @@ impl DocumentValidator {
                 warnings.push(ValidationWarning {
                     message: "Aucune clause obligatoire définie".to_string(),
                     suggestion: Some("Ajouter les clauses essentielles (confidentialité, responsabilité, etc.)".to_string()),
+                    target: None,
                 });
             }
             
@@ -110,6 +132,7 @@ impl DocumentValidator {
                     category: "legal".to_string(),
                     message: "Ajouter une clause de confidentialité".to_string(),
                     priority: 8,
+                    target: None,
                 });
             }
             
@@ -118,6 +141,7 @@ impl DocumentValidator {
                     category: "legal".to_string(),
                     message: "Ajouter une clause de limitation de responsabilité".to_string(),
                     priority: 9,
+                    target: None,
                 });
             }
         } else {
@@ -125,6 +149,7 @@ impl DocumentValidator {
                 code: "MISSING_CLAUSES".to_string(),
                 message: "Document légal sans clauses définies".to_string(),
                 severity: ErrorSeverity::High,
+                target: None,
             });
         }
         
@@ -136,6 +161,7 @@ impl DocumentValidator {
                 category: "legal".to_string(),
                 message: "Ajouter une section sur les responsabilités".to_string(),
                 priority: 9,
+                target: None,
             });
         }
         
@@ -144,12 +170,75 @@ impl DocumentValidator {
                 category: "legal".to_string(),
                 message: "Ajouter une section sur la durée et résiliation".to_string(),
                 priority: 8,
+                target: None,
             });
         }
         
         Ok(())
     }
     
+    /// Applique les packs des juridictions de `custom_params["jurisdiction"]`
+    /// (séparées par des virgules). Critique/haute = erreur, sinon avertissement.
+    /// Sans juridiction demandée, aucun pack n'est appliqué.
+    fn validate_jurisdiction(
+        &self,
+        content: &DocumentContent,
+        config: &GenerationConfig,
+        errors: &mut Vec<ValidationError>,
+        warnings: &mut Vec<ValidationWarning>,
+        rule_violations: &mut Vec<legal_rules::RuleViolation>
+    ) {
+        let Some(requested) = config.custom_params.get("jurisdiction") else {
+            return;
+        };
+        
+        let mut jurisdictions = Vec::new();
+        for jurisdiction in requested.split(',').map(str::trim).filter(|j| !j.is_empty()) {
+            if self.legal_rules.contains(jurisdiction) {
+                jurisdictions.push(jurisdiction);
+            } else {
+                warnings.push(ValidationWarning {
+                    message: format!("Aucun pack de règles pour la juridiction '{}'", jurisdiction),
+                    suggestion: Some("Installer un pack de règles pour cette juridiction".to_string()),
+                    target: None,
+                });
+            }
+        }
+        if jurisdictions.is_empty() {
+            return;
+        }
+        
+        let violations = match self.legal_rules.check(&jurisdictions, &config.doc_type, content) {
+            Ok(violations) => violations,
+            Err(e) => {
+                warnings.push(ValidationWarning {
+                    message: format!("Contrôle juridique impossible: {}", e),
+                    suggestion: None,
+                    target: None,
+                });
+                return;
+            }
+        };
+        
+        for violation in &violations {
+            let message = format!("{} ({})", violation.message, violation.reference);
+            match violation.severity {
+                ErrorSeverity::Critical | ErrorSeverity::High => errors.push(ValidationError {
+                    code: violation.rule_id.clone(),
+                    message,
+                    severity: violation.severity.clone(),
+                    target: violation.target(),
+                }),
+                ErrorSeverity::Medium | ErrorSeverity::Low => warnings.push(ValidationWarning {
+                    message,
+                    suggestion: violation.remediation.clone(),
+                    target: violation.target(),
+                }),
+            }
+        }
+        rule_violations.extend(violations);
+    }
+    
     fn validate_editorial_document(
         &self,
         content: &DocumentContent,
@@ -162,6 +251,7 @@ impl DocumentValidator {
             warnings.push(ValidationWarning {
                 message: "Aucun objectif défini".to_string(),
                 suggestion: Some("Ajouter des objectifs d'apprentissage clairs".to_string()),
+                target: None,
             });
         }
         
@@ -171,6 +261,7 @@ impl DocumentValidator {
                 category: "editorial".to_string(),
                 message: "Développer davantage le contenu avec des sections supplémentaires".to_string(),
                 priority: 6,
+                target: None,
             });
         }
         
@@ -186,6 +277,7 @@ impl DocumentValidator {
                 category: "editorial".to_string(),
                 message: "Ajouter des exemples ou cas pratiques pour illustrer les concepts".to_string(),
                 priority: 7,
+                target: None,
             });
         }
         
@@ -200,6 +292,7 @@ impl DocumentValidator {
                 category: "editorial".to_string(),
                 message: "Ajouter une conclusion ou synthèse".to_string(),
                 priority: 8,
+                target: None,
             });
         }
         
@@ -225,6 +318,7 @@ impl DocumentValidator {
                 category: "technical".to_string(),
                 message: "Ajouter des diagrammes ou schémas pour illustrer l'architecture".to_string(),
                 priority: 8,
+                target: None,
             });
         }
         
@@ -235,6 +329,7 @@ impl DocumentValidator {
             warnings.push(ValidationWarning {
                 message: "Aucun exemple de code détecté".to_string(),
                 suggestion: Some("Ajouter des exemples de code pour faciliter la compréhension".to_string()),
+                target: None,
             });
         }
         
@@ -253,6 +348,7 @@ impl DocumentValidator {
                 warnings.push(ValidationWarning {
                     message: format!("Section '{}' très courte", section.title),
                     suggestion: Some("Développer davantage cette section".to_string()),
+                    target: None,
                 });
             }
         }
@@ -263,6 +359,7 @@ impl DocumentValidator {
                 category: "quality".to_string(),
                 message: "Ajouter des références pour renforcer la crédibilité".to_string(),
                 priority: 5,
+                target: None,
             });
         }
         
@@ -292,3 +389,32 @@ impl Default for DocumentValidator {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legal_notice() -> (DocumentContent, GenerationConfig) {
+        let document = test_document(DocumentType::LegalNotice, DocumentStyle::Legal);
+        (document.content, document.config)
+    }
+
+    #[test]
+    fn test_jurisdiction_packs_only_when_requested() {
+        let validator = DocumentValidator::new();
+        let (content, mut config) = legal_notice();
+
+        let status = validator.validate(&content, &config).unwrap();
+        assert!(status.rule_violations.is_empty());
+
+        config.custom_params.insert("jurisdiction".to_string(), "fr".to_string());
+        let status = validator.validate(&content, &config).unwrap();
+        assert!(status.rule_violations.iter().any(|v| v.rule_id == "FR-LCEN-6-III-1-a"));
+        assert!(status.errors.iter().any(|e| e.code == "FR-LCEN-6-III-1-a"));
+
+        config.custom_params.insert("jurisdiction".to_string(), "xx, ".to_string());
+        let status = validator.validate(&content, &config).unwrap();
+        assert!(status.rule_violations.is_empty());
+        assert!(status.warnings.iter().any(|w| w.message.contains("'xx'")));
+    }
+}
@@ -1,5 +1,9 @@
 // Module de versionnement des documents
+// Historique persisté via `StorageEngine`, diff par section (appariement par
+// id, détail lignes / mots), numérotation sémantique et fusion à trois voies.
 
+use super::diff::{self, LineChange, MergeLabels, WordChange};
+use super::storage::StorageEngine;
 use super::*;
 use std::collections::HashMap;
 
@@ -11,6 +15,11 @@ pub struct Version {
     pub author: String,
     pub changes: Vec<Change>,
     pub snapshot: Document,
+    /// Version dont celle-ci est issue (`None` pour la première)
+    #[serde(default)]
+    pub parent_version: Option<String>,
+    #[serde(default)]
+    pub bump: Option<VersionBump>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,42 +35,811 @@ pub enum ChangeType {
     Modified,
     Deleted,
     Merged,
+    Moved,
 }
 
+/// Incrément sémantique déduit de la nature des changements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum VersionBump {
+    /// Corrections de texte dans l'existant
+    Patch,
+    /// Ajouts, déplacements, renommages, titre ou objectifs
+    Minor,
+    /// Suppression de section ou de clause, modification d'une clause
+    Major,
+}
+
+impl VersionBump {
+    pub fn apply(&self, current: &str) -> String {
+        let parts: Vec<u32> = current.split('.').map(|p| p.parse().unwrap_or(0)).collect();
+        if parts.len() != 3 {
+            return "1.0.0".to_string();
+        }
+        let (major, minor, patch) = (parts[0], parts[1], parts[2]);
+
+        match self {
+            VersionBump::Major => format!("{}.0.0", major + 1),
+            VersionBump::Minor => format!("{}.{}.0", major, minor + 1),
+            VersionBump::Patch => format!("{}.{}.{}", major, minor, patch + 1),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Diff
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ItemKind {
+    Section,
+    Clause,
+    Annex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ItemStatus {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// Différence sur une section, clause ou annexe, appariée par id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionDiff {
+    pub kind: ItemKind,
+    pub id: String,
+    pub status: ItemStatus,
+    pub old_title: Option<String>,
+    pub new_title: Option<String>,
+    /// Parent (sous-sections) dans la nouvelle version, sinon l'ancienne
+    pub parent_id: Option<String>,
+    pub old_position: Option<usize>,
+    pub new_position: Option<usize>,
+    /// Ordre relatif changé parmi ses voisines, ou changement de parent
+    pub moved: bool,
+    pub renamed: bool,
+    pub lines: Vec<LineChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextChange {
+    pub old: String,
+    pub new: String,
+    pub words: Vec<WordChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentDiff {
+    pub from_version: Option<String>,
+    pub to_version: Option<String>,
+    pub title: Option<TextChange>,
+    pub executive_summary: Vec<LineChange>,
+    pub objectives: Vec<LineChange>,
+    pub items: Vec<SectionDiff>,
+}
+
+impl DocumentDiff {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.executive_summary.is_empty()
+            && self.objectives.is_empty()
+            && self.items.is_empty()
+    }
+
+    pub fn suggested_bump(&self) -> VersionBump {
+        let major = self.items.iter().any(|item| {
+            (item.status == ItemStatus::Removed && item.kind != ItemKind::Annex)
+                || (item.kind == ItemKind::Clause && item.status == ItemStatus::Changed && !item.lines.is_empty())
+        });
+        if major {
+            return VersionBump::Major;
+        }
+
+        let minor = self.title.is_some()
+            || !self.objectives.is_empty()
+            || self
+                .items
+                .iter()
+                .any(|item| item.status == ItemStatus::Added || item.moved || item.renamed);
+        if minor {
+            VersionBump::Minor
+        } else {
+            VersionBump::Patch
+        }
+    }
+
+    /// Journal des changements lisible, une entrée par élément touché
+    pub fn to_changes(&self) -> Vec<Change> {
+        let mut changes = Vec::new();
+        let mut push = |change_type, description: String, section_id: Option<String>| {
+            changes.push(Change {
+                change_type,
+                description,
+                section_id,
+            });
+        };
+
+        if let Some(title) = &self.title {
+            push(ChangeType::Modified, format!("Titre : « {} » → « {} »", title.old, title.new), None);
+        }
+        if !self.executive_summary.is_empty() {
+            push(ChangeType::Modified, "Résumé modifié".to_string(), None);
+        }
+        if !self.objectives.is_empty() {
+            push(ChangeType::Modified, "Objectifs modifiés".to_string(), None);
+        }
+
+        for item in &self.items {
+            let label = match item.kind {
+                ItemKind::Section => "Section",
+                ItemKind::Clause => "Clause",
+                ItemKind::Annex => "Annexe",
+            };
+            let title = item.new_title.as_ref().or(item.old_title.as_ref()).cloned().unwrap_or_default();
+            let id = Some(item.id.clone());
+            match item.status {
+                ItemStatus::Added => push(ChangeType::Created, format!("{} ajoutée : {}", label, title), id),
+                ItemStatus::Removed => push(ChangeType::Deleted, format!("{} supprimée : {}", label, title), id),
+                ItemStatus::Changed => {
+                    if item.moved {
+                        push(ChangeType::Moved, format!("{} déplacée : {}", label, title), id.clone());
+                    }
+                    if item.renamed {
+                        push(
+                            ChangeType::Modified,
+                            format!(
+                                "{} renommée : « {} » → « {} »",
+                                label,
+                                item.old_title.as_deref().unwrap_or_default(),
+                                title
+                            ),
+                            id.clone(),
+                        );
+                    }
+                    if !item.lines.is_empty() {
+                        push(
+                            ChangeType::Modified,
+                            format!("{} modifiée : {} ({} ligne(s))", label, title, item.lines.len()),
+                            id,
+                        );
+                    }
+                }
+            }
+        }
+        changes
+    }
+}
+
+/// Élément versionné : section, clause ou annexe
+trait Item: Clone {
+    fn key(&self) -> &str;
+    fn title(&self) -> &str;
+    fn body(&self) -> &str;
+    fn set_title(&mut self, title: String);
+    fn set_body(&mut self, body: String);
+
+    fn children(&self) -> &[Section] {
+        &[]
+    }
+
+    fn set_children(&mut self, _children: Vec<Section>) {}
+}
+
+impl Item for Section {
+    fn key(&self) -> &str {
+        &self.id
+    }
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn body(&self) -> &str {
+        &self.content
+    }
+    fn set_title(&mut self, title: String) {
+        self.title = title;
+    }
+    fn set_body(&mut self, body: String) {
+        self.content = body;
+    }
+    fn children(&self) -> &[Section] {
+        &self.subsections
+    }
+    fn set_children(&mut self, children: Vec<Section>) {
+        self.subsections = children;
+    }
+}
+
+impl Item for Clause {
+    fn key(&self) -> &str {
+        &self.id
+    }
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn body(&self) -> &str {
+        &self.content
+    }
+    fn set_title(&mut self, title: String) {
+        self.title = title;
+    }
+    fn set_body(&mut self, body: String) {
+        self.content = body;
+    }
+}
+
+impl Item for Annex {
+    fn key(&self) -> &str {
+        &self.id
+    }
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn body(&self) -> &str {
+        &self.content
+    }
+    fn set_title(&mut self, title: String) {
+        self.title = title;
+    }
+    fn set_body(&mut self, body: String) {
+        self.content = body;
+    }
+}
+
+fn same_item<T: Item>(a: &T, b: &T) -> bool {
+    a.title() == b.title()
+        && a.body() == b.body()
+        && a.children().len() == b.children().len()
+        && a.children().iter().zip(b.children()).all(|(x, y)| x.key() == y.key() && same_item(x, y))
+}
+
+struct FlatItem<'a> {
+    id: &'a str,
+    parent: Option<&'a str>,
+    position: usize,
+    title: &'a str,
+    body: &'a str,
+}
+
+fn flatten<'a, T: Item>(items: &'a [T], parent: Option<&'a str>, out: &mut Vec<FlatItem<'a>>) {
+    for (position, item) in items.iter().enumerate() {
+        out.push(FlatItem {
+            id: item.key(),
+            parent,
+            position,
+            title: item.title(),
+            body: item.body(),
+        });
+        flatten(item.children(), Some(item.key()), out);
+    }
+}
+
+fn diff_items<T: Item>(kind: ItemKind, old: &[T], new: &[T]) -> Vec<SectionDiff> {
+    let (mut old_flat, mut new_flat) = (Vec::new(), Vec::new());
+    flatten(old, None, &mut old_flat);
+    flatten(new, None, &mut new_flat);
+
+    let mut old_by_id: HashMap<&str, &FlatItem> = HashMap::new();
+    for item in &old_flat {
+        old_by_id.entry(item.id).or_insert(item);
+    }
+    let mut new_by_id: HashMap<&str, &FlatItem> = HashMap::new();
+    for item in &new_flat {
+        new_by_id.entry(item.id).or_insert(item);
+    }
+
+    // Déplacements : éléments hors de la LCS des voisines communes (même parent)
+    let mut moved: Vec<&str> = Vec::new();
+    let mut parents: Vec<Option<&str>> = new_flat.iter().map(|item| item.parent).collect();
+    parents.dedup();
+    for parent in parents {
+        let siblings = |flat: &[FlatItem<'_>], other: &HashMap<&str, &FlatItem>| -> Vec<String> {
+            flat.iter()
+                .filter(|item| item.parent == parent)
+                .filter(|item| other.get(item.id).is_some_and(|o| o.parent == parent))
+                .map(|item| item.id.to_string())
+                .collect()
+        };
+        let old_order = siblings(&old_flat, &new_by_id);
+        let new_order = siblings(&new_flat, &old_by_id);
+        for edit in diff::edit_script(&old_order, &new_order) {
+            if let diff::Edit::Add(j) = edit {
+                if let Some(item) = new_flat.iter().find(|item| item.id == new_order[j]) {
+                    moved.push(item.id);
+                }
+            }
+        }
+    }
+
+    let mut diffs = Vec::new();
+    for item in &new_flat {
+        if new_by_id.get(item.id).is_some_and(|first| !std::ptr::eq(*first, item)) {
+            continue;
+        }
+        match old_by_id.get(item.id) {
+            None => diffs.push(SectionDiff {
+                kind,
+                id: item.id.to_string(),
+                status: ItemStatus::Added,
+                old_title: None,
+                new_title: Some(item.title.to_string()),
+                parent_id: item.parent.map(String::from),
+                old_position: None,
+                new_position: Some(item.position),
+                moved: false,
+                renamed: false,
+                lines: diff::diff_lines("", item.body),
+            }),
+            Some(old_item) => {
+                let lines = diff::diff_lines(old_item.body, item.body);
+                let is_moved = old_item.parent != item.parent || moved.contains(&item.id);
+                let renamed = old_item.title != item.title;
+                if lines.is_empty() && !is_moved && !renamed {
+                    continue;
+                }
+                diffs.push(SectionDiff {
+                    kind,
+                    id: item.id.to_string(),
+                    status: ItemStatus::Changed,
+                    old_title: Some(old_item.title.to_string()),
+                    new_title: Some(item.title.to_string()),
+                    parent_id: item.parent.map(String::from),
+                    old_position: Some(old_item.position),
+                    new_position: Some(item.position),
+                    moved: is_moved,
+                    renamed,
+                    lines,
+                });
+            }
+        }
+    }
+
+    for item in &old_flat {
+        if new_by_id.contains_key(item.id) || old_by_id.get(item.id).is_some_and(|first| !std::ptr::eq(*first, item)) {
+            continue;
+        }
+        diffs.push(SectionDiff {
+            kind,
+            id: item.id.to_string(),
+            status: ItemStatus::Removed,
+            old_title: Some(item.title.to_string()),
+            new_title: None,
+            parent_id: item.parent.map(String::from),
+            old_position: Some(item.position),
+            new_position: None,
+            moved: false,
+            renamed: false,
+            lines: diff::diff_lines(item.body, ""),
+        });
+    }
+
+    diffs
+}
+
+/// Diff structuré entre deux états d'un document
+pub fn diff_documents(old: &Document, new: &Document) -> DocumentDiff {
+    let (a, b) = (&old.content, &new.content);
+    let title = (a.title != b.title).then(|| TextChange {
+        old: a.title.clone(),
+        new: b.title.clone(),
+        words: diff::diff_words(&a.title, &b.title),
+    });
+
+    let mut items = diff_items(ItemKind::Section, &a.sections, &b.sections);
+    items.extend(diff_items(
+        ItemKind::Clause,
+        a.mandatory_clauses.as_deref().unwrap_or_default(),
+        b.mandatory_clauses.as_deref().unwrap_or_default(),
+    ));
+    items.extend(diff_items(ItemKind::Annex, &a.annexes, &b.annexes));
+
+    DocumentDiff {
+        from_version: None,
+        to_version: None,
+        title,
+        executive_summary: diff::diff_lines(&a.executive_summary, &b.executive_summary),
+        objectives: diff::diff_lines(&a.objectives.join("\n"), &b.objectives.join("\n")),
+        items,
+    }
+}
+
+/// Représentation ligne à ligne servant au diff unifié
+fn document_lines(document: &Document) -> Vec<String> {
+    fn push_text(lines: &mut Vec<String>, text: &str) {
+        lines.extend(text.lines().map(String::from));
+    }
+    fn push_sections(lines: &mut Vec<String>, sections: &[Section], depth: usize) {
+        for section in sections {
+            lines.push(String::new());
+            lines.push(format!("{} {} {{#{}}}", "#".repeat(depth + 1), section.title, section.id));
+            push_text(lines, &section.content);
+            push_sections(lines, &section.subsections, depth + 1);
+        }
+    }
+
+    let content = &document.content;
+    let mut lines = vec![format!("# {}", content.title)];
+    if !content.executive_summary.is_empty() {
+        lines.push(String::new());
+        push_text(&mut lines, &content.executive_summary);
+    }
+    if !content.objectives.is_empty() {
+        lines.push(String::new());
+        lines.push("## Objectifs".to_string());
+        lines.extend(content.objectives.iter().map(|o| format!("- {}", o)));
+    }
+    push_sections(&mut lines, &content.sections, 1);
+    for clause in content.mandatory_clauses.as_deref().unwrap_or_default() {
+        lines.push(String::new());
+        lines.push(format!("## Clause : {} {{#{}}}", clause.title, clause.id));
+        push_text(&mut lines, &clause.content);
+    }
+    for annex in &content.annexes {
+        lines.push(String::new());
+        lines.push(format!("## Annexe : {} {{#{}}}", annex.title, annex.id));
+        push_text(&mut lines, &annex.content);
+    }
+    lines
+}
+
+/// Diff unifié (`diff -u`) entre deux états d'un document
+pub fn unified_diff(old: &Document, new: &Document, old_label: &str, new_label: &str, context: usize) -> String {
+    diff::unified_diff(&document_lines(old), &document_lines(new), old_label, new_label, context)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Fusion à trois voies
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeConflict {
+    /// `title`, `section:<id>`, `clause:<id>`, `annexe:<id>`…
+    pub location: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeResult {
+    /// Document fusionné ; les conflits de texte y figurent avec marqueurs
+    pub document: Document,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeResult {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+struct Merger<'a> {
+    labels: MergeLabels<'a>,
+    conflicts: Vec<MergeConflict>,
+}
+
+impl Merger<'_> {
+    fn conflict(&mut self, location: &str, description: String) {
+        self.conflicts.push(MergeConflict {
+            location: location.to_string(),
+            description,
+        });
+    }
+
+    /// Valeur sans marqueurs (titres) : en conflit, la version `ours` est gardée
+    fn scalar(&mut self, location: &str, base: &str, ours: &str, theirs: &str) -> String {
+        if ours == theirs || theirs == base {
+            ours.to_string()
+        } else if ours == base {
+            theirs.to_string()
+        } else {
+            self.conflict(
+                location,
+                format!("Titre modifié des deux côtés : « {} » / « {} »", ours, theirs),
+            );
+            ours.to_string()
+        }
+    }
+
+    fn text(&mut self, location: &str, base: &str, ours: &str, theirs: &str) -> String {
+        let (merged, conflicts) = diff::merge_text(base, ours, theirs, &self.labels);
+        if conflicts > 0 {
+            self.conflict(location, format!("{} zone(s) modifiée(s) des deux côtés", conflicts));
+        }
+        merged
+    }
+
+    fn item<T: Item>(&mut self, location: &str, base: Option<&T>, ours: &T, theirs: &T) -> T {
+        let mut merged = ours.clone();
+        let (base_title, base_body) = base.map(|b| (b.title(), b.body())).unwrap_or_default();
+        merged.set_title(self.scalar(location, base_title, ours.title(), theirs.title()));
+        merged.set_body(self.text(location, base_body, ours.body(), theirs.body()));
+        let base_children = base.map(|b| b.children()).unwrap_or_default();
+        let children = self.items("section", base_children, ours.children(), theirs.children());
+        merged.set_children(children);
+        merged
+    }
+
+    fn items<T: Item>(&mut self, prefix: &str, base: &[T], ours: &[T], theirs: &[T]) -> Vec<T> {
+        let find = |items: &'_ [T], id: &str| items.iter().position(|item| item.key() == id);
+        let order_within = |items: &[T], other: &[T]| -> Vec<String> {
+            items
+                .iter()
+                .filter(|item| find(other, item.key()).is_some())
+                .map(|item| item.key().to_string())
+                .collect()
+        };
+
+        // Squelette : l'ordre de `theirs` si lui seul a réordonné, sinon `ours`
+        let ours_reordered = order_within(ours, base) != order_within(base, ours);
+        let theirs_reordered = order_within(theirs, base) != order_within(base, theirs);
+        let (skeleton, other) = if theirs_reordered && !ours_reordered {
+            (theirs, ours)
+        } else {
+            (ours, theirs)
+        };
+
+        let mut order: Vec<&str> = Vec::new();
+        for item in skeleton {
+            if !order.contains(&item.key()) {
+                order.push(item.key());
+            }
+        }
+        for (idx, item) in other.iter().enumerate() {
+            if order.contains(&item.key()) {
+                continue;
+            }
+            let position = other[..idx]
+                .iter()
+                .rev()
+                .find_map(|previous| order.iter().position(|id| *id == previous.key()))
+                .map_or(0, |p| p + 1);
+            order.insert(position, item.key());
+        }
+
+        let mut merged = Vec::new();
+        for id in order {
+            let location = format!("{}:{}", prefix, id);
+            let b = find(base, id).map(|i| &base[i]);
+            let o = find(ours, id).map(|i| &ours[i]);
+            let t = find(theirs, id).map(|i| &theirs[i]);
+            match (b, o, t) {
+                (_, Some(o), Some(t)) => merged.push(self.item(&location, b, o, t)),
+                // Supprimé d'un côté, modifié de l'autre : la version modifiée est gardée
+                (Some(b), Some(o), None) if !same_item(b, o) => {
+                    self.conflict(
+                        &location,
+                        format!("Supprimé par {}, modifié par {}", self.labels.theirs, self.labels.ours),
+                    );
+                    merged.push(o.clone());
+                }
+                (Some(b), None, Some(t)) if !same_item(b, t) => {
+                    self.conflict(
+                        &location,
+                        format!("Supprimé par {}, modifié par {}", self.labels.ours, self.labels.theirs),
+                    );
+                    merged.push(t.clone());
+                }
+                (None, Some(added), None) | (None, None, Some(added)) => merged.push(added.clone()),
+                _ => {}
+            }
+        }
+        merged
+    }
+}
+
+fn merge_with_labels(base: &Document, ours: &Document, theirs: &Document, labels: MergeLabels) -> MergeResult {
+    let mut merger = Merger {
+        labels,
+        conflicts: Vec::new(),
+    };
+    let (b, o, t) = (&base.content, &ours.content, &theirs.content);
+    let mut document = ours.clone();
+
+    document.content.title = merger.scalar("title", &b.title, &o.title, &t.title);
+    document.content.executive_summary =
+        merger.text("executive_summary", &b.executive_summary, &o.executive_summary, &t.executive_summary);
+    document.content.objectives = merger
+        .text("objectives", &b.objectives.join("\n"), &o.objectives.join("\n"), &t.objectives.join("\n"))
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect();
+    document.content.sections = merger.items("section", &b.sections, &o.sections, &t.sections);
+
+    let clauses = merger.items(
+        "clause",
+        b.mandatory_clauses.as_deref().unwrap_or_default(),
+        o.mandatory_clauses.as_deref().unwrap_or_default(),
+        t.mandatory_clauses.as_deref().unwrap_or_default(),
+    );
+    document.content.mandatory_clauses = if clauses.is_empty() && o.mandatory_clauses.is_none() {
+        None
+    } else {
+        Some(clauses)
+    };
+    document.content.annexes = merger.items("annexe", &b.annexes, &o.annexes, &t.annexes);
+    document.metadata.updated_at = chrono::Utc::now();
+
+    MergeResult {
+        document,
+        conflicts: merger.conflicts,
+    }
+}
+
+/// Fusionne deux éditions concurrentes issues de `base`
+pub fn merge_documents(base: &Document, ours: &Document, theirs: &Document) -> MergeResult {
+    merge_with_labels(
+        base,
+        ours,
+        theirs,
+        MergeLabels {
+            ours: "ours",
+            base: "base",
+            theirs: "theirs",
+        },
+    )
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Moteur de versionnement
+// ─────────────────────────────────────────────────────────────────────────────
+
 pub struct VersioningEngine {
     versions: HashMap<String, Vec<Version>>,
+    storage: Option<StorageEngine>,
 }
 
 impl VersioningEngine {
     pub fn new() -> Self {
         Self {
             versions: HashMap::new(),
+            storage: None,
         }
     }
-    
-    /// Crée une nouvelle version d'un document
-    pub fn create_version(&mut self, document: Document, changes: Vec<Change>) -> Result<Version> {
+
+    /// Historique persisté : chargé à la demande, réécrit à chaque version
+    pub fn with_storage(storage: StorageEngine) -> Self {
+        Self {
+            versions: HashMap::new(),
+            storage: Some(storage),
+        }
+    }
+
+    /// Crée une nouvelle version d'un document (en mémoire).
+    /// Le numéro est incrémenté selon le diff avec la version précédente ;
+    /// sans `changes` fournis, le journal est déduit de ce diff.
+    pub fn create_version(&mut self, mut document: Document, changes: Vec<Change>) -> Result<Version> {
         let document_id = document.metadata.id.clone();
-        let current_version = self.get_latest_version_number(&document_id);
-        let new_version_number = self.increment_version(&current_version);
-        
+        let latest = self.latest_version(&document_id);
+
+        let (version_number, parent_version, bump, changes) = match latest {
+            None => {
+                let changes = if changes.is_empty() {
+                    vec![Change {
+                        change_type: ChangeType::Created,
+                        description: "Création du document".to_string(),
+                        section_id: None,
+                    }]
+                } else {
+                    changes
+                };
+                ("1.0.0".to_string(), None, None, changes)
+            }
+            Some(previous) => {
+                let diff = diff_documents(&previous.snapshot, &document);
+                let bump = diff.suggested_bump();
+                let changes = if changes.is_empty() { diff.to_changes() } else { changes };
+                (
+                    bump.apply(&previous.version_number),
+                    Some(previous.version_number.clone()),
+                    Some(bump),
+                    changes,
+                )
+            }
+        };
+
+        document.metadata.version = version_number.clone();
         let version = Version {
-            version_number: new_version_number.clone(),
+            version_number,
             document_id: document_id.clone(),
             created_at: chrono::Utc::now(),
             author: document.metadata.author.clone(),
             changes,
             snapshot: document,
+            parent_version,
+            bump,
         };
-        
+
         self.versions
             .entry(document_id)
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(version.clone());
-        
+
         Ok(version)
     }
-    
+
+    /// Crée une version à partir d'une édition commencée sur `base_version`.
+    /// Si d'autres versions ont été créées entre-temps, les deux éditions sont
+    /// fusionnées ; en cas de conflit rien n'est créé (voir `merge_versions`).
+    pub fn create_version_from(&mut self, document: Document, base_version: &str, changes: Vec<Change>) -> Result<Version> {
+        let document_id = document.metadata.id.clone();
+        let latest = match self.latest_version(&document_id) {
+            Some(latest) if latest.version_number != base_version => latest,
+            // Historique vide ou édition à jour : pas de fusion
+            _ => return self.create_version(document, changes),
+        };
+        let base = self.get_version(&document_id, base_version)
+            .ok_or_else(|| DocEngineError::ValidationError(format!("Version {} introuvable", base_version)))?;
+
+        let latest_label = format!("v{}", latest.version_number);
+        let base_label = format!("v{}", base_version);
+        let result = merge_with_labels(
+            &base.snapshot,
+            &latest.snapshot,
+            &document,
+            MergeLabels {
+                ours: &latest_label,
+                base: &base_label,
+                theirs: "édition",
+            },
+        );
+        if !result.is_clean() {
+            let locations: Vec<&str> = result.conflicts.iter().map(|c| c.location.as_str()).collect();
+            return Err(DocEngineError::ValidationError(format!(
+                "Conflits de fusion avec {} : {}",
+                latest_label,
+                locations.join(", ")
+            )));
+        }
+
+        let mut changes = if changes.is_empty() {
+            diff_documents(&latest.snapshot, &result.document).to_changes()
+        } else {
+            changes
+        };
+        changes.push(Change {
+            change_type: ChangeType::Merged,
+            description: format!("Fusion d'une édition basée sur {} avec {}", base_label, latest_label),
+            section_id: None,
+        });
+        self.create_version(result.document, changes)
+    }
+
+    /// Charge l'historique persisté d'un document ; renvoie le nombre de versions
+    pub async fn load_history(&mut self, document_id: &str) -> Result<usize> {
+        let storage = self.storage.as_ref()
+            .ok_or_else(|| DocEngineError::StorageError("Aucun stockage configuré".to_string()))?;
+        let versions = storage.load_versions(document_id).await?;
+        let count = versions.len();
+        self.versions.insert(document_id.to_string(), versions);
+        Ok(count)
+    }
+
+    /// Écrit l'historique d'un document (sans effet sans stockage)
+    pub async fn persist(&self, document_id: &str) -> Result<()> {
+        if let Some(storage) = &self.storage {
+            let versions = self.versions.get(document_id).map(Vec::as_slice).unwrap_or_default();
+            storage.save_versions(document_id, versions).await?;
+        }
+        Ok(())
+    }
+
+    /// `create_version` puis persistance ; la version est retirée si
+    /// l'écriture échoue
+    pub async fn record_version(&mut self, document: Document, changes: Vec<Change>) -> Result<Version> {
+        let document_id = document.metadata.id.clone();
+        if self.storage.is_some() && !self.versions.contains_key(&document_id) {
+            self.load_history(&document_id).await?;
+        }
+
+        let version = self.create_version(document, changes)?;
+        if let Err(e) = self.persist(&document_id).await {
+            if let Some(versions) = self.versions.get_mut(&document_id) {
+                versions.pop();
+            }
+            return Err(e);
+        }
+        Ok(version)
+    }
+
     /// Récupère toutes les versions d'un document
     pub fn get_versions(&self, document_id: &str) -> Vec<&Version> {
         self.versions
@@ -69,7 +847,7 @@ impl VersioningEngine {
             .map(|versions| versions.iter().collect())
             .unwrap_or_default()
     }
-    
+
     /// Récupère une version spécifique
     pub fn get_version(&self, document_id: &str, version_number: &str) -> Option<&Version> {
         self.versions
@@ -77,84 +855,320 @@ impl VersioningEngine {
             .iter()
             .find(|v| v.version_number == version_number)
     }
-    
+
     /// Restaure un document à une version antérieure
     pub fn restore_version(&self, document_id: &str, version_number: &str) -> Result<Document> {
         let version = self.get_version(document_id, version_number)
             .ok_or_else(|| DocEngineError::ValidationError("Version introuvable".to_string()))?;
-        
+
         Ok(version.snapshot.clone())
     }
-    
+
     /// Compare deux versions
-    pub fn diff_versions(&self, document_id: &str, version_a: &str, version_b: &str) -> Result<Vec<Difference>> {
+    pub fn diff_versions(&self, document_id: &str, version_a: &str, version_b: &str) -> Result<DocumentDiff> {
+        let (v_a, v_b) = self.version_pair(document_id, version_a, version_b)?;
+        let mut diff = diff_documents(&v_a.snapshot, &v_b.snapshot);
+        diff.from_version = Some(v_a.version_number.clone());
+        diff.to_version = Some(v_b.version_number.clone());
+        Ok(diff)
+    }
+
+    /// Diff unifié texte entre deux versions
+    pub fn unified_diff_versions(&self, document_id: &str, version_a: &str, version_b: &str, context: usize) -> Result<String> {
+        let (v_a, v_b) = self.version_pair(document_id, version_a, version_b)?;
+        Ok(unified_diff(
+            &v_a.snapshot,
+            &v_b.snapshot,
+            &format!("{}@{}", document_id, v_a.version_number),
+            &format!("{}@{}", document_id, v_b.version_number),
+            context,
+        ))
+    }
+
+    /// Fusion à trois voies de deux versions issues de `base`
+    pub fn merge_versions(&self, document_id: &str, base: &str, ours: &str, theirs: &str) -> Result<MergeResult> {
+        let base_version = self.get_version(document_id, base)
+            .ok_or_else(|| DocEngineError::ValidationError(format!("Version {} introuvable", base)))?;
+        let (v_ours, v_theirs) = self.version_pair(document_id, ours, theirs)?;
+
+        let labels = (format!("v{}", ours), format!("v{}", base), format!("v{}", theirs));
+        Ok(merge_with_labels(
+            &base_version.snapshot,
+            &v_ours.snapshot,
+            &v_theirs.snapshot,
+            MergeLabels {
+                ours: &labels.0,
+                base: &labels.1,
+                theirs: &labels.2,
+            },
+        ))
+    }
+
+    fn version_pair(&self, document_id: &str, version_a: &str, version_b: &str) -> Result<(&Version, &Version)> {
         let v_a = self.get_version(document_id, version_a)
             .ok_or_else(|| DocEngineError::ValidationError("Version A introuvable".to_string()))?;
         let v_b = self.get_version(document_id, version_b)
             .ok_or_else(|| DocEngineError::ValidationError("Version B introuvable".to_string()))?;
-        
-        Ok(self.compute_diff(&v_a.snapshot, &v_b.snapshot))
+        Ok((v_a, v_b))
     }
-    
-    fn get_latest_version_number(&self, document_id: &str) -> String {
-        self.versions
-            .get(document_id)
-            .and_then(|versions| versions.last())
-            .map(|v| v.version_number.clone())
-            .unwrap_or_else(|| "0.0.0".to_string())
+
+    fn latest_version(&self, document_id: &str) -> Option<&Version> {
+        self.versions.get(document_id).and_then(|versions| versions.last())
     }
-    
-    fn increment_version(&self, current: &str) -> String {
-        let parts: Vec<&str> = current.split('.').collect();
-        if parts.len() != 3 {
-            return "1.0.0".to_string();
+}
+
+impl Default for VersioningEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn document() -> Document {
+        test_document(DocumentType::Analysis, DocumentStyle::Professional)
+    }
+
+    fn section(id: &str, title: &str, content: &str) -> Section {
+        Section {
+            id: id.to_string(),
+            title: title.to_string(),
+            content: content.to_string(),
+            subsections: Vec::new(),
+            level: 1,
         }
-        
-        let major: u32 = parts[0].parse().unwrap_or(0);
-        let minor: u32 = parts[1].parse().unwrap_or(0);
-        let patch: u32 = parts[2].parse().unwrap_or(0);
-        
-        format!("{}.{}.{}", major, minor, patch + 1)
-    }
-    
-    fn compute_diff(&self, doc_a: &Document, doc_b: &Document) -> Vec<Difference> {
-        let mut diffs = Vec::new();
-        
-        // Comparaison du titre
-        if doc_a.content.title != doc_b.content.title {
-            diffs.push(Difference {
-                field: "title".to_string(),
-                old_value: doc_a.content.title.clone(),
-                new_value: doc_b.content.title.clone(),
-            });
+    }
+
+    fn clause(id: &str, content: &str) -> Clause {
+        Clause {
+            id: id.to_string(),
+            title: "Confidentialité".to_string(),
+            content: content.to_string(),
+            mandatory: true,
+            category: ClauseCategory::Confidentiality,
         }
-        
-        // Comparaison des sections
-        for (idx, section_a) in doc_a.content.sections.iter().enumerate() {
-            if let Some(section_b) = doc_b.content.sections.get(idx) {
-                if section_a.content != section_b.content {
-                    diffs.push(Difference {
-                        field: format!("section_{}", section_a.id),
-                        old_value: section_a.content.clone(),
-                        new_value: section_b.content.clone(),
-                    });
-                }
-            }
+    }
+
+    fn item<'a>(diff: &'a DocumentDiff, id: &str) -> &'a SectionDiff {
+        diff.items
+            .iter()
+            .find(|item| item.id == id)
+            .unwrap_or_else(|| panic!("{} absent du diff", id))
+    }
+
+    #[test]
+    fn test_version_bump_apply() {
+        assert_eq!(VersionBump::Patch.apply("1.2.3"), "1.2.4");
+        assert_eq!(VersionBump::Minor.apply("1.2.3"), "1.3.0");
+        assert_eq!(VersionBump::Major.apply("1.2.3"), "2.0.0");
+        assert_eq!(VersionBump::Minor.apply("brouillon"), "1.0.0");
+    }
+
+    #[test]
+    fn test_suggested_bump_by_change_kind() {
+        let base = document();
+        let bump = |edit: &dyn Fn(&mut Document)| {
+            let mut edited = base.clone();
+            edit(&mut edited);
+            diff_documents(&base, &edited).suggested_bump()
+        };
+
+        assert_eq!(bump(&|d| d.content.sections[0].content.push_str(" Correction.")), VersionBump::Patch);
+        assert_eq!(bump(&|d| d.content.executive_summary = "Autre synthèse.".to_string()), VersionBump::Patch);
+        assert_eq!(bump(&|d| d.content.title = "Nouveau titre".to_string()), VersionBump::Minor);
+        assert_eq!(bump(&|d| d.content.sections[0].title = "Préambule".to_string()), VersionBump::Minor);
+        assert_eq!(bump(&|d| d.content.sections.push(section("suite", "Suite", "Texte"))), VersionBump::Minor);
+        assert_eq!(bump(&|d| d.content.objectives.push("Conclure".to_string())), VersionBump::Minor);
+        assert_eq!(bump(&|d| d.content.sections[0].subsections.clear()), VersionBump::Major);
+
+        let mut with_clause = base.clone();
+        with_clause.content.mandatory_clauses = Some(vec![clause("nda", "Secret pendant 2 ans.")]);
+        assert_eq!(diff_documents(&base, &with_clause).suggested_bump(), VersionBump::Minor);
+        let mut edited_clause = with_clause.clone();
+        edited_clause.content.mandatory_clauses = Some(vec![clause("nda", "Secret pendant 5 ans.")]);
+        assert_eq!(diff_documents(&with_clause, &edited_clause).suggested_bump(), VersionBump::Major);
+    }
+
+    #[test]
+    fn test_diff_detects_added_removed_and_moved_sections() {
+        let mut old = document();
+        old.content.sections.push(section("a", "A", "Texte A"));
+        old.content.sections.push(section("b", "B", "Texte B"));
+
+        // Nouvel ordre : b, intro, c ; « a » supprimée, « contexte » passe sous b
+        let mut new = old.clone();
+        let mut sections = std::mem::take(&mut new.content.sections);
+        let mut b = sections.pop().unwrap();
+        sections.pop();
+        let mut intro = sections.pop().unwrap();
+        b.subsections = std::mem::take(&mut intro.subsections);
+        new.content.sections = vec![b, intro, section("c", "C", "Texte C")];
+
+        let diff = diff_documents(&old, &new);
+        assert_eq!(diff.items.len(), 4);
+
+        let added = item(&diff, "c");
+        assert_eq!((added.status, added.new_position), (ItemStatus::Added, Some(2)));
+        let removed = item(&diff, "a");
+        assert_eq!((removed.status, removed.old_position), (ItemStatus::Removed, Some(1)));
+        assert_eq!(removed.lines, diff::diff_lines("Texte A", ""));
+
+        let intro = item(&diff, "intro");
+        assert!(intro.moved && !intro.renamed && intro.lines.is_empty());
+        assert!(diff.items.iter().all(|i| i.id != "b"));
+
+        let contexte = item(&diff, "contexte");
+        assert!(contexte.moved);
+        assert_eq!(contexte.parent_id.as_deref(), Some("b"));
+
+        let journal: Vec<String> = diff.to_changes().into_iter().map(|c| c.description).collect();
+        assert!(journal.contains(&"Section ajoutée : C".to_string()));
+        assert!(journal.contains(&"Section supprimée : A".to_string()));
+        assert!(journal.contains(&"Section déplacée : Introduction".to_string()));
+    }
+
+    #[test]
+    fn test_clean_merge_keeps_both_edits() {
+        let base = document();
+        let mut ours = base.clone();
+        ours.content.sections[0].content.push_str("\nAjout de notre côté.");
+        let mut theirs = base.clone();
+        theirs.content.title = "Titre révisé".to_string();
+        theirs.content.sections.push(section("annexe", "Calendrier", "Avril"));
+
+        let result = merge_documents(&base, &ours, &theirs);
+        assert!(result.is_clean(), "{:?}", result.conflicts);
+        let content = &result.document.content;
+        assert_eq!(content.title, "Titre révisé");
+        assert!(content.sections[0].content.ends_with("Ajout de notre côté."));
+        assert_eq!(content.sections[0].subsections.len(), 1);
+        assert_eq!(content.sections[1].id, "annexe");
+    }
+
+    #[test]
+    fn test_conflicting_merge_reports_locations() {
+        let base = document();
+        let mut ours = base.clone();
+        ours.content.sections[0].content = "Version A.".to_string();
+        ours.content.title = "Titre A".to_string();
+        ours.content.sections[0].subsections.clear();
+        let mut theirs = base.clone();
+        theirs.content.sections[0].content = "Version B.".to_string();
+        theirs.content.title = "Titre B".to_string();
+        theirs.content.sections[0].subsections[0].content.push_str("\n- troisième point");
+
+        let result = merge_documents(&base, &ours, &theirs);
+        let locations: Vec<&str> = result.conflicts.iter().map(|c| c.location.as_str()).collect();
+        assert_eq!(locations, ["title", "section:intro", "section:contexte"]);
+
+        let content = &result.document.content;
+        assert_eq!(content.title, "Titre A");
+        assert_eq!(
+            content.sections[0].content,
+            "<<<<<<< ours\nVersion A.\n||||||| base\nLe **présent** document décrit le *périmètre*.\n=======\nVersion B.\n>>>>>>> theirs"
+        );
+        // Supprimée d'un côté, modifiée de l'autre : la version modifiée reste
+        assert!(content.sections[0].subsections[0].content.ends_with("troisième point"));
+    }
+
+    #[test]
+    fn test_unified_diff_output() {
+        let old = document();
+        let mut new = old.clone();
+        new.content.title = "Nouveau titre".to_string();
+        new.content.sections[0].subsections[0].content = "- premier point\n- point révisé".to_string();
+
+        let output = unified_diff(&old, &new, "v1.0.0", "v1.1.0", 1);
+        assert_eq!(
+            output,
+            "--- v1.0.0\n+++ v1.1.0\n\
+             @@ -1,2 +1,2 @@\n-# Document de test\n+# Nouveau titre\n \n\
+             @@ -12,2 +12,2 @@\n - premier point\n-- second point\n+- point révisé\n"
+        );
+        assert!(unified_diff(&old, &new, "a", "b", 3).contains(" ### Contexte {#contexte}\n"));
+        assert_eq!(unified_diff(&old, &old, "a", "b", 3), "");
+    }
+
+    #[test]
+    fn test_create_version_numbers_follow_the_diff() {
+        let mut engine = VersioningEngine::new();
+        let mut document = document();
+        let v1 = engine.create_version(document.clone(), vec![]).unwrap();
+        assert_eq!((v1.version_number.as_str(), v1.parent_version.as_ref()), ("1.0.0", None));
+        assert!(matches!(v1.changes[0].change_type, ChangeType::Created));
+
+        document.content.sections[0].content.push_str(" Correction.");
+        let v2 = engine.create_version(document.clone(), vec![]).unwrap();
+        assert_eq!(v2.version_number, "1.0.1");
+        assert_eq!(v2.bump, Some(VersionBump::Patch));
+        assert_eq!(v2.parent_version.as_deref(), Some("1.0.0"));
+        assert_eq!(v2.snapshot.metadata.version, "1.0.1");
+
+        document.content.sections.push(section("suite", "Suite", "Texte"));
+        assert_eq!(engine.create_version(document.clone(), vec![]).unwrap().version_number, "1.1.0");
+        document.content.sections.remove(0);
+        assert_eq!(engine.create_version(document.clone(), vec![]).unwrap().version_number, "2.0.0");
+
+        let diff = engine.diff_versions("doc-test", "1.0.0", "2.0.0").unwrap();
+        assert_eq!(diff.from_version.as_deref(), Some("1.0.0"));
+        assert_eq!(item(&diff, "intro").status, ItemStatus::Removed);
+        assert!(engine.diff_versions("doc-test", "1.0.0", "9.9.9").is_err());
+        assert_eq!(engine.get_versions("doc-test").len(), 4);
+    }
+
+    #[test]
+    fn test_create_version_from_merges_stale_edits() {
+        let mut engine = VersioningEngine::new();
+
+        // Historique vide : simple création
+        let base = document();
+        assert_eq!(engine.create_version_from(base.clone(), "1.0.0", vec![]).unwrap().version_number, "1.0.0");
+
+        // Une autre édition est enregistrée entre-temps
+        let mut concurrent = base.clone();
+        concurrent.content.sections[0].content.push_str("\nPrécision concurrente.");
+        engine.create_version(concurrent, vec![]).unwrap();
+
+        let mut edition = base.clone();
+        edition.content.sections.push(section("suite", "Suite", "Texte"));
+        let merged = engine.create_version_from(edition, "1.0.0", vec![]).unwrap();
+        assert_eq!(merged.version_number, "1.1.0");
+        assert_eq!(merged.parent_version.as_deref(), Some("1.0.1"));
+        assert!(merged.snapshot.content.sections[0].content.ends_with("Précision concurrente."));
+        assert_eq!(merged.snapshot.content.sections[1].id, "suite");
+        assert!(matches!(merged.changes.last().unwrap().change_type, ChangeType::Merged));
+
+        // Édition à jour : pas de fusion
+        let mut current = merged.snapshot.clone();
+        current.content.sections[1].content.push_str(" Fin.");
+        let next = engine.create_version_from(current, "1.1.0", vec![]).unwrap();
+        assert!(!next.changes.iter().any(|c| matches!(c.change_type, ChangeType::Merged)));
+
+        // Conflit : rien n'est créé
+        let mut conflicting = base.clone();
+        conflicting.content.sections[0].content = "Réécriture complète.".to_string();
+        match engine.create_version_from(conflicting.clone(), "1.0.0", vec![]) {
+            Err(DocEngineError::ValidationError(message)) => assert!(message.contains("section:intro"), "{}", message),
+            other => panic!("conflit attendu: {:?}", other.map(|v| v.version_number)),
         }
-        
-        diffs
+        assert!(engine.create_version_from(conflicting, "0.9.0", vec![]).is_err());
+        assert_eq!(engine.get_versions("doc-test").len(), 4);
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Difference {
-    pub field: String,
-    pub old_value: String,
-    pub new_value: String,
-}
+    #[tokio::test]
+    async fn test_record_version_persists_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut engine = VersioningEngine::with_storage(StorageEngine::new(temp_dir.path().to_path_buf(), "secret").unwrap());
+        let mut document = document();
+        engine.record_version(document.clone(), vec![]).await.unwrap();
+        document.content.title = "Titre révisé".to_string();
+        engine.record_version(document, vec![]).await.unwrap();
 
-impl Default for VersioningEngine {
-    fn default() -> Self {
-        Self::new()
+        let mut reloaded = VersioningEngine::with_storage(StorageEngine::new(temp_dir.path().to_path_buf(), "secret").unwrap());
+        assert_eq!(reloaded.load_history("doc-test").await.unwrap(), 2);
+        assert_eq!(reloaded.restore_version("doc-test", "1.1.0").unwrap().content.title, "Titre révisé");
     }
 }
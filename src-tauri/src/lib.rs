@@ -8,9 +8,11 @@
 #![allow(unused_imports)]
 
 // Global modules export
+pub mod ai;
 pub mod api;
 pub mod core;
 pub mod engine;
+pub mod semantic;
 pub mod services;
 pub mod shared;
 pub mod system;
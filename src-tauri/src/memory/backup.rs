@@ -0,0 +1,995 @@
+// TITANE∞ v12 - Memory Backup
+// Archive de sauvegarde chiffrée, portable et vérifiable
+//
+// Une archive regroupe en un seul fichier les conversations (en clair, donc
+// restaurables avec un autre mot de passe mémoire), l'index mémoire, l'historique
+// MemoryCore (snapshots, timeline), les fichiers de l'index sémantique et le
+// stockage documentaire. Un manifeste liste chaque entrée avec sa taille et son
+// SHA-256 ; l'ensemble est chiffré par une clé dérivée de la phrase de passe.
+//
+// Fichier : "TMB" | version | m_cost | t_cost | p | sel | nonce | GCM(clé, charge JSON, aad = en-tête)
+
+use super::encryption::KdfParams;
+use super::model::{Conversation, MemoryIndex};
+use super::storage::{sibling_path, swap_directory, MemoryStorage};
+use super::{MemoryError, MemoryResult};
+use crate::core::MemoryCore;
+use crate::shared::crypto::{self, NONCE_SIZE, SALT_SIZE};
+use crate::types::{Snapshot, TimelineEvent};
+use base64::{engine::general_purpose, Engine as _};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Version du format d'archive écrit par cette build
+pub const BACKUP_FORMAT_VERSION: u8 = 1;
+/// Plus ancienne version encore lisible
+const MIN_FORMAT_VERSION: u8 = 1;
+const ARCHIVE_MAGIC: &[u8; 3] = b"TMB";
+/// magic + version + m_cost + t_cost + p + sel
+const HEADER_SIZE: usize = 4 + 12 + SALT_SIZE;
+
+const SNAPSHOTS_ENTRY: &str = "snapshots.json";
+const TIMELINE_ENTRY: &str = "timeline.json";
+
+const BACKUP_PREFIX: &str = "titane-backup-";
+pub const BACKUP_EXTENSION: &str = "tmbak";
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Manifeste
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Catégorie d'une entrée d'archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupSection {
+    Conversations,
+    MemoryIndex,
+    CoreSnapshots,
+    CoreTimeline,
+    SemanticIndex,
+    Documents,
+}
+
+impl BackupSection {
+    /// Préfixe des chemins de la section dans l'archive
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            BackupSection::Conversations => "conversations",
+            BackupSection::MemoryIndex => "memory",
+            BackupSection::CoreSnapshots | BackupSection::CoreTimeline => "core",
+            BackupSection::SemanticIndex => "semantic",
+            BackupSection::Documents => "documents",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub section: BackupSection,
+    /// Chemin relatif dans l'archive (`<préfixe>/...`)
+    pub path: String,
+    pub size: u64,
+    /// SHA-256 hexadécimal du contenu en clair
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u8,
+    pub app_version: String,
+    pub created_at: i64,
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl BackupManifest {
+    pub fn entries_in(&self, section: BackupSection) -> impl Iterator<Item = &ManifestEntry> {
+        self.entries.iter().filter(move |e| e.section == section)
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.entries.iter().map(|e| e.size).sum()
+    }
+}
+
+/// Charge chiffrée : manifeste + contenus (base64, dans l'ordre du manifeste)
+#[derive(Serialize, Deserialize)]
+struct BackupPayload {
+    manifest: BackupManifest,
+    blobs: Vec<String>,
+}
+
+/// Résultat d'une vérification à blanc
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupVerification {
+    pub manifest: BackupManifest,
+    /// Entrées dont la taille ou l'empreinte ne correspond pas
+    pub corrupted: Vec<String>,
+}
+
+impl BackupVerification {
+    pub fn is_valid(&self) -> bool {
+        self.corrupted.is_empty()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Création
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Assemble le contenu d'une archive avant écriture
+#[derive(Default)]
+pub struct BackupBuilder {
+    entries: Vec<ManifestEntry>,
+    blobs: Vec<Vec<u8>>,
+}
+
+impl BackupBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ajoute un contenu brut ; `name` est relatif au préfixe de la section
+    pub fn add_bytes(&mut self, section: BackupSection, name: &str, data: Vec<u8>) -> MemoryResult<()> {
+        let path = format!("{}/{}", section.prefix(), name.trim_start_matches('/'));
+        check_relative(&path)?;
+        if self.entries.iter().any(|e| e.path == path) {
+            return Err(MemoryError::InvalidData(format!("Duplicate backup entry: {}", path)));
+        }
+
+        self.entries.push(ManifestEntry {
+            section,
+            path,
+            size: data.len() as u64,
+            sha256: sha256_hex(&data),
+        });
+        self.blobs.push(data);
+        Ok(())
+    }
+
+    pub fn add_json<T: Serialize>(&mut self, section: BackupSection, name: &str, value: &T) -> MemoryResult<()> {
+        let data = serde_json::to_vec_pretty(value).map_err(|e| MemoryError::InvalidData(e.to_string()))?;
+        self.add_bytes(section, name, data)
+    }
+
+    /// Ajoute l'index et chaque conversation, déchiffrés
+    pub fn add_memory_storage(&mut self, storage: &MemoryStorage) -> MemoryResult<usize> {
+        let index = storage.load_index()?;
+        for summary in &index.conversations {
+            let conversation = storage.load_conversation(&summary.id)?;
+            self.add_json(BackupSection::Conversations, &format!("{}.json", conversation.id), &conversation)?;
+        }
+        self.add_json(BackupSection::MemoryIndex, "index.json", &index)?;
+        Ok(index.conversations.len())
+    }
+
+    /// Ajoute l'historique MemoryCore (snapshots et timeline)
+    pub async fn add_core_history(&mut self, core: &MemoryCore) -> MemoryResult<usize> {
+        let (snapshots, timeline) = core.export_history().await;
+        self.add_json(BackupSection::CoreSnapshots, SNAPSHOTS_ENTRY, &snapshots)?;
+        self.add_json(BackupSection::CoreTimeline, TIMELINE_ENTRY, &timeline)?;
+        Ok(snapshots.len() + timeline.len())
+    }
+
+    /// Ajoute récursivement les fichiers d'un répertoire (index sémantique, documents)
+    pub fn add_directory(&mut self, section: BackupSection, dir: &Path) -> MemoryResult<usize> {
+        if !dir.exists() {
+            return Ok(0);
+        }
+
+        let files = walk_files(dir)?;
+        for file in &files {
+            let relative = file
+                .strip_prefix(dir)
+                .map_err(|e| MemoryError::StorageError(e.to_string()))?
+                .to_string_lossy()
+                .replace('\\', "/");
+            let data = fs::read(file).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+            self.add_bytes(section, &relative, data)?;
+        }
+        Ok(files.len())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Chiffre et écrit l'archive (écriture atomique)
+    pub fn write(self, path: &Path, passphrase: &str, params: KdfParams) -> MemoryResult<BackupManifest> {
+        let manifest = BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: chrono::Utc::now().timestamp(),
+            entries: self.entries,
+        };
+        let payload = BackupPayload {
+            manifest: manifest.clone(),
+            blobs: self.blobs.iter().map(|b| general_purpose::STANDARD.encode(b)).collect(),
+        };
+
+        let sealed = seal(&payload, passphrase, params)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, sealed).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+        fs::rename(&tmp, path).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+        Ok(manifest)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Lecture et restauration
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestoreMode {
+    /// Ajoute les éléments absents ; une conversation locale n'est remplacée
+    /// que si la sauvegarde est plus récente, un fichier local est conservé
+    Merge,
+    /// Remplace la destination par la sauvegarde telle quelle (préparée à
+    /// côté puis substituée, la destination reste intacte en cas d'erreur)
+    Replace,
+}
+
+/// Bilan d'une restauration (ou de ce qu'elle ferait en mode `dry_run`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RestoreReport {
+    pub dry_run: bool,
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub skipped: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Archive déchiffrée dont toutes les empreintes ont été vérifiées
+pub struct BackupArchive {
+    manifest: BackupManifest,
+    blobs: Vec<Vec<u8>>,
+}
+
+impl BackupArchive {
+    /// Déchiffre l'archive ; échoue si une entrée est corrompue
+    pub fn open(path: &Path, passphrase: &str) -> MemoryResult<Self> {
+        let (manifest, blobs) = read_archive(path, passphrase)?;
+        let corrupted = corrupted_entries(&manifest, &blobs);
+        if !corrupted.is_empty() {
+            return Err(MemoryError::InvalidData(format!(
+                "Backup checksum mismatch: {}",
+                corrupted.join(", ")
+            )));
+        }
+        Ok(Self { manifest, blobs })
+    }
+
+    /// Vérification à blanc : version, phrase de passe et empreintes
+    pub fn verify(path: &Path, passphrase: &str) -> MemoryResult<BackupVerification> {
+        let (manifest, blobs) = read_archive(path, passphrase)?;
+        let corrupted = corrupted_entries(&manifest, &blobs);
+        Ok(BackupVerification { manifest, corrupted })
+    }
+
+    pub fn manifest(&self) -> &BackupManifest {
+        &self.manifest
+    }
+
+    /// Entrées d'une section avec leur contenu
+    pub fn section(&self, section: BackupSection) -> impl Iterator<Item = (&ManifestEntry, &[u8])> {
+        self.manifest
+            .entries
+            .iter()
+            .zip(self.blobs.iter())
+            .filter(move |(entry, _)| entry.section == section)
+            .map(|(entry, blob)| (entry, blob.as_slice()))
+    }
+
+    /// Désérialise une entrée JSON (`name` relatif au préfixe de la section)
+    pub fn read_json<T: DeserializeOwned>(&self, section: BackupSection, name: &str) -> MemoryResult<Option<T>> {
+        let path = format!("{}/{}", section.prefix(), name);
+        self.section(section)
+            .find(|(entry, _)| entry.path == path)
+            .map(|(_, data)| serde_json::from_slice(data).map_err(|e| MemoryError::InvalidData(e.to_string())))
+            .transpose()
+    }
+
+    /// Restaure les conversations ; l'index est reconstruit par le stockage
+    pub fn restore_memory(&self, storage: &MemoryStorage, mode: RestoreMode, dry_run: bool) -> MemoryResult<RestoreReport> {
+        let conversations = self
+            .section(BackupSection::Conversations)
+            .map(|(entry, data)| {
+                serde_json::from_slice::<Conversation>(data)
+                    .map_err(|e| MemoryError::InvalidData(format!("{}: {}", entry.path, e)))
+            })
+            .collect::<MemoryResult<Vec<_>>>()?;
+
+        let local: MemoryIndex = storage.load_index()?;
+        let mut report = RestoreReport {
+            dry_run,
+            ..RestoreReport::default()
+        };
+
+        if mode == RestoreMode::Replace {
+            let restored: HashSet<&str> = conversations.iter().map(|c| c.id.as_str()).collect();
+            report.removed = local
+                .conversations
+                .iter()
+                .filter(|s| !restored.contains(s.id.as_str()))
+                .map(|s| s.id.clone())
+                .collect();
+        }
+
+        for conversation in &conversations {
+            let existing = local.conversations.iter().find(|s| s.id == conversation.id);
+            let target = match (existing, mode) {
+                (None, _) => &mut report.created,
+                (Some(_), RestoreMode::Replace) => &mut report.updated,
+                (Some(summary), RestoreMode::Merge) if conversation.updated_at > summary.updated_at => &mut report.updated,
+                (Some(_), RestoreMode::Merge) => {
+                    report.skipped.push(conversation.id.clone());
+                    continue;
+                }
+            };
+            target.push(conversation.id.clone());
+            if !dry_run && mode == RestoreMode::Merge {
+                storage.save_conversation(conversation)?;
+            }
+        }
+
+        // Remplacement préparé à part puis substitué : rien n'est effacé avant
+        if !dry_run && mode == RestoreMode::Replace {
+            storage.replace_all(&conversations)?;
+        }
+
+        Ok(report)
+    }
+
+    /// Restaure les snapshots et la timeline MemoryCore (fusion par identifiant)
+    pub async fn restore_core_history(&self, core: &MemoryCore, mode: RestoreMode, dry_run: bool) -> MemoryResult<RestoreReport> {
+        let snapshots: Vec<Snapshot> = self.read_json(BackupSection::CoreSnapshots, SNAPSHOTS_ENTRY)?.unwrap_or_default();
+        let timeline: Vec<TimelineEvent> = self.read_json(BackupSection::CoreTimeline, TIMELINE_ENTRY)?.unwrap_or_default();
+
+        let (local_snapshots, local_timeline) = core.export_history().await;
+        let local: HashSet<String> = local_snapshots
+            .iter()
+            .map(|s| format!("snapshot:{}", s.id))
+            .chain(local_timeline.iter().map(|e| format!("timeline:{}", e.id)))
+            .collect();
+        let restored: Vec<String> = snapshots
+            .iter()
+            .map(|s| format!("snapshot:{}", s.id))
+            .chain(timeline.iter().map(|e| format!("timeline:{}", e.id)))
+            .collect();
+
+        let mut report = RestoreReport {
+            dry_run,
+            ..RestoreReport::default()
+        };
+        for id in &restored {
+            match (local.contains(id), mode) {
+                (false, _) => report.created.push(id.clone()),
+                (true, RestoreMode::Replace) => report.updated.push(id.clone()),
+                (true, RestoreMode::Merge) => report.skipped.push(id.clone()),
+            }
+        }
+        if mode == RestoreMode::Replace {
+            let restored: HashSet<&String> = restored.iter().collect();
+            report.removed = local.iter().filter(|id| !restored.contains(id)).cloned().collect();
+            report.removed.sort();
+        }
+
+        if !dry_run {
+            core.import_history(snapshots, timeline, mode == RestoreMode::Replace)
+                .await
+                .map_err(|e| MemoryError::StorageError(e.to_string()))?;
+        }
+        Ok(report)
+    }
+
+    /// Restaure une section de fichiers (index sémantique, documents) dans `dir`
+    pub fn restore_directory(
+        &self,
+        section: BackupSection,
+        dir: &Path,
+        mode: RestoreMode,
+        dry_run: bool,
+    ) -> MemoryResult<RestoreReport> {
+        let prefix = format!("{}/", section.prefix());
+        let mut report = RestoreReport {
+            dry_run,
+            ..RestoreReport::default()
+        };
+
+        let files: Vec<(&str, &[u8])> = self
+            .section(section)
+            .map(|(entry, data)| (entry.path.strip_prefix(&prefix).unwrap_or(&entry.path), data))
+            .collect();
+        // Toutes les entrées sont validées avant de toucher à la destination
+        for (name, _) in &files {
+            check_relative(name)?;
+        }
+
+        let existing: HashSet<String> = if dir.exists() {
+            walk_files(dir)?
+                .iter()
+                .map(|file| file.strip_prefix(dir).unwrap_or(file).to_string_lossy().replace('\\', "/"))
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
+        if mode == RestoreMode::Replace {
+            let restored: HashSet<&str> = files.iter().map(|(name, _)| *name).collect();
+            report.removed = existing.iter().filter(|name| !restored.contains(name.as_str())).cloned().collect();
+            report.removed.sort();
+        }
+
+        let mut pending = Vec::new();
+        for (name, data) in files {
+            match (existing.contains(name), mode) {
+                (false, _) => report.created.push(name.to_string()),
+                (true, RestoreMode::Replace) => report.updated.push(name.to_string()),
+                (true, RestoreMode::Merge) => {
+                    let target = dir.join(name);
+                    if fs::read(&target).map(|local| local != data).unwrap_or(true) {
+                        log::warn!("Fichier local conservé lors de la fusion: {}", target.display());
+                    }
+                    report.skipped.push(name.to_string());
+                    continue;
+                }
+            }
+            pending.push((name, data));
+        }
+        if dry_run {
+            return Ok(report);
+        }
+
+        match mode {
+            RestoreMode::Merge => write_files(dir, &pending)?,
+            // Extraction complète à côté, puis substitution de la destination
+            RestoreMode::Replace => {
+                let staging = sibling_path(dir, "restore");
+                if let Err(e) = write_files(&staging, &pending) {
+                    let _ = fs::remove_dir_all(&staging);
+                    return Err(e);
+                }
+                swap_directory(&staging, dir)?;
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Sauvegarde automatique
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSchedule {
+    pub directory: PathBuf,
+    pub interval_secs: u64,
+    /// Nombre d'archives conservées (0 = illimité)
+    pub keep_last: usize,
+    pub kdf: KdfParams,
+}
+
+impl BackupSchedule {
+    pub fn new(directory: PathBuf) -> Self {
+        Self {
+            directory,
+            interval_secs: 24 * 3600,
+            keep_last: 7,
+            kdf: KdfParams::default(),
+        }
+    }
+}
+
+/// Écrit une archive horodatée dans le répertoire du planning puis applique la rétention
+pub fn run_scheduled_backup(schedule: &BackupSchedule, passphrase: &str, builder: BackupBuilder) -> MemoryResult<PathBuf> {
+    let name = format!(
+        "{}{}.{}",
+        BACKUP_PREFIX,
+        chrono::Utc::now().format("%Y%m%d-%H%M%S%3f"),
+        BACKUP_EXTENSION
+    );
+    let path = schedule.directory.join(name);
+    builder.write(&path, passphrase, schedule.kdf)?;
+    apply_retention(&schedule.directory, schedule.keep_last)?;
+    Ok(path)
+}
+
+/// Supprime les archives automatiques les plus anciennes au-delà de `keep_last`
+pub fn apply_retention(dir: &Path, keep_last: usize) -> MemoryResult<Vec<PathBuf>> {
+    if keep_last == 0 || !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(dir).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+    let mut archives: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with(BACKUP_PREFIX) && path.extension().is_some_and(|ext| ext == BACKUP_EXTENSION)
+        })
+        .collect();
+    // Horodatage dans le nom : l'ordre lexicographique est chronologique
+    archives.sort();
+
+    let excess = archives.len().saturating_sub(keep_last);
+    let removed: Vec<PathBuf> = archives.into_iter().take(excess).collect();
+    for path in &removed {
+        fs::remove_file(path).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+    }
+    Ok(removed)
+}
+
+/// Sauvegarde périodique en arrière-plan ; `collect` assemble le contenu à chaque passe
+pub struct BackupScheduler {
+    last_result: Arc<Mutex<Option<Result<PathBuf, String>>>>,
+    running: Arc<AtomicBool>,
+    trigger: Arc<AtomicBool>,
+}
+
+impl BackupScheduler {
+    pub fn spawn<F>(schedule: BackupSchedule, passphrase: String, collect: F) -> Self
+    where
+        F: Fn() -> MemoryResult<BackupBuilder> + Send + 'static,
+    {
+        let interval = Duration::from_secs(schedule.interval_secs.max(1));
+        let scheduler = Self {
+            last_result: Arc::new(Mutex::new(None)),
+            running: Arc::new(AtomicBool::new(true)),
+            trigger: Arc::new(AtomicBool::new(false)),
+        };
+
+        let last_result = scheduler.last_result.clone();
+        let running = scheduler.running.clone();
+        let trigger = scheduler.trigger.clone();
+
+        std::thread::spawn(move || {
+            let mut last_run = Instant::now();
+
+            while running.load(Ordering::Relaxed) {
+                if last_run.elapsed() >= interval || trigger.swap(false, Ordering::Relaxed) {
+                    let result = collect().and_then(|builder| run_scheduled_backup(&schedule, &passphrase, builder));
+                    match &result {
+                        Ok(path) => log::info!("Sauvegarde mémoire écrite: {}", path.display()),
+                        Err(e) => log::error!("Sauvegarde mémoire échouée: {}", e),
+                    }
+                    *last_result.lock().unwrap() = Some(result.map_err(|e| e.to_string()));
+                    last_run = Instant::now();
+                }
+                std::thread::sleep(Duration::from_millis(250));
+            }
+        });
+
+        scheduler
+    }
+
+    /// Demande une sauvegarde immédiate
+    pub fn run_now(&self) {
+        self.trigger.store(true, Ordering::Relaxed);
+    }
+
+    pub fn last_result(&self) -> Option<Result<PathBuf, String>> {
+        self.last_result.lock().unwrap().clone()
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl Drop for BackupScheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Chiffrement de l'archive
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn seal(payload: &BackupPayload, passphrase: &str, params: KdfParams) -> MemoryResult<Vec<u8>> {
+    let plaintext = serde_json::to_vec(payload).map_err(|e| MemoryError::EncryptionError(e.to_string()))?;
+    let salt: [u8; SALT_SIZE] = rand::random();
+
+    let mut sealed = Vec::with_capacity(HEADER_SIZE + NONCE_SIZE + plaintext.len() + 16);
+    sealed.extend_from_slice(ARCHIVE_MAGIC);
+    sealed.push(BACKUP_FORMAT_VERSION);
+    sealed.extend_from_slice(&params.memory_kib.to_le_bytes());
+    sealed.extend_from_slice(&params.iterations.to_le_bytes());
+    sealed.extend_from_slice(&params.parallelism.to_le_bytes());
+    sealed.extend_from_slice(&salt);
+
+    let mut key = crypto::derive_key(passphrase, &salt, params).map_err(|e| MemoryError::EncryptionError(e.to_string()))?;
+    let ciphertext = crypto::encrypt(&key, &plaintext, &sealed);
+    crypto::wipe(&mut key);
+
+    sealed.extend_from_slice(&ciphertext.map_err(|e| MemoryError::EncryptionError(e.to_string()))?);
+    Ok(sealed)
+}
+
+fn unseal(bytes: &[u8], passphrase: &str) -> MemoryResult<BackupPayload> {
+    if bytes.len() < HEADER_SIZE + NONCE_SIZE || &bytes[..3] != ARCHIVE_MAGIC {
+        return Err(MemoryError::InvalidData("Not a TITANE backup archive".to_string()));
+    }
+    check_version(bytes[3])?;
+
+    let read_u32 = |offset: usize| u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]]);
+    let params = KdfParams {
+        memory_kib: read_u32(4),
+        iterations: read_u32(8),
+        parallelism: read_u32(12),
+    };
+    let (header, sealed) = bytes.split_at(HEADER_SIZE);
+
+    let mut key =
+        crypto::derive_key(passphrase, &header[16..], params).map_err(|e| MemoryError::DecryptionError(e.to_string()))?;
+    let plaintext = crypto::decrypt(&key, sealed, header);
+    crypto::wipe(&mut key);
+
+    let plaintext = plaintext
+        .map_err(|_| MemoryError::DecryptionError("Invalid backup passphrase or corrupted archive".to_string()))?;
+    let payload: BackupPayload = serde_json::from_slice(&plaintext)
+        .map_err(|e| MemoryError::InvalidData(e.to_string()))?;
+    check_version(payload.manifest.format_version)?;
+    Ok(payload)
+}
+
+fn read_archive(path: &Path, passphrase: &str) -> MemoryResult<(BackupManifest, Vec<Vec<u8>>)> {
+    let bytes = fs::read(path).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+    let payload = unseal(&bytes, passphrase)?;
+    if payload.blobs.len() != payload.manifest.entries.len() {
+        return Err(MemoryError::InvalidData("Backup manifest does not match its contents".to_string()));
+    }
+
+    let blobs = payload
+        .blobs
+        .iter()
+        .map(|blob| general_purpose::STANDARD.decode(blob).map_err(|e| MemoryError::InvalidData(e.to_string())))
+        .collect::<MemoryResult<Vec<_>>>()?;
+    Ok((payload.manifest, blobs))
+}
+
+fn check_version(version: u8) -> MemoryResult<()> {
+    if version > BACKUP_FORMAT_VERSION {
+        return Err(MemoryError::InvalidData(format!(
+            "Backup format v{} is newer than supported v{}",
+            version, BACKUP_FORMAT_VERSION
+        )));
+    }
+    if version < MIN_FORMAT_VERSION {
+        return Err(MemoryError::InvalidData(format!("Unsupported backup format v{}", version)));
+    }
+    Ok(())
+}
+
+fn corrupted_entries(manifest: &BackupManifest, blobs: &[Vec<u8>]) -> Vec<String> {
+    manifest
+        .entries
+        .iter()
+        .zip(blobs)
+        .filter(|(entry, blob)| entry.size != blob.len() as u64 || entry.sha256 != sha256_hex(blob))
+        .map(|(entry, _)| entry.path.clone())
+        .collect()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Refuse les chemins absolus ou remontant hors de la destination
+fn check_relative(path: &str) -> MemoryResult<()> {
+    let safe = Path::new(path).components().all(|c| matches!(c, Component::Normal(_)));
+    if path.is_empty() || !safe {
+        return Err(MemoryError::InvalidData(format!("Unsafe backup path: {}", path)));
+    }
+    Ok(())
+}
+
+fn write_files(dir: &Path, files: &[(&str, &[u8])]) -> MemoryResult<()> {
+    fs::create_dir_all(dir).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+    for (name, data) in files {
+        let target = dir.join(name);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+        }
+        fs::write(&target, data).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn walk_files(dir: &Path) -> MemoryResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let entries = fs::read_dir(&current).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+        for entry in entries {
+            let path = entry.map_err(|e| MemoryError::StorageError(e.to_string()))?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::encryption::test_params;
+    use super::super::MessageRole;
+    use super::*;
+    use tempfile::TempDir;
+
+    fn storage(dir: &Path) -> MemoryStorage {
+        MemoryStorage::with_kdf_params(dir.to_path_buf(), "mémoire".to_string(), test_params()).unwrap()
+    }
+
+    fn conversation(title: &str, updated_at: i64) -> Conversation {
+        let mut conv = Conversation::new(title.to_string());
+        conv.add_entry(MessageRole::User, format!("{} : premier message", title), 4);
+        conv.updated_at = updated_at;
+        conv
+    }
+
+    #[test]
+    fn test_backup_roundtrip_with_directories() {
+        let temp = TempDir::new().unwrap();
+        let source = storage(&temp.path().join("source"));
+        let conv = conversation("Contrat", 100);
+        source.save_conversation(&conv).unwrap();
+
+        let documents = temp.path().join("documents");
+        fs::create_dir_all(documents.join("2024")).unwrap();
+        fs::write(documents.join("2024/bail.json"), b"{\"titre\":\"Bail\"}").unwrap();
+
+        let mut builder = BackupBuilder::new();
+        assert_eq!(builder.add_memory_storage(&source).unwrap(), 1);
+        builder.add_directory(BackupSection::Documents, &documents).unwrap();
+        builder.add_json(BackupSection::CoreTimeline, "timeline.json", &vec!["démarrage"]).unwrap();
+
+        let archive_path = temp.path().join("backup.tmbak");
+        builder.write(&archive_path, "phrase", test_params()).unwrap();
+
+        let verification = BackupArchive::verify(&archive_path, "phrase").unwrap();
+        assert!(verification.is_valid());
+        assert!(BackupArchive::open(&archive_path, "mauvaise").is_err());
+
+        // Restauration vers un stockage protégé par un autre mot de passe
+        let archive = BackupArchive::open(&archive_path, "phrase").unwrap();
+        let target = MemoryStorage::with_kdf_params(temp.path().join("target"), "autre".to_string(), test_params()).unwrap();
+        let report = archive.restore_memory(&target, RestoreMode::Replace, false).unwrap();
+        assert_eq!(report.created, vec![conv.id.clone()]);
+        assert_eq!(target.load_conversation(&conv.id).unwrap().entries.len(), 1);
+
+        let restored_docs = temp.path().join("restored");
+        archive.restore_directory(BackupSection::Documents, &restored_docs, RestoreMode::Replace, false).unwrap();
+        assert_eq!(fs::read(restored_docs.join("2024/bail.json")).unwrap(), b"{\"titre\":\"Bail\"}");
+
+        let timeline: Vec<String> = archive.read_json(BackupSection::CoreTimeline, "timeline.json").unwrap().unwrap();
+        assert_eq!(timeline, vec!["démarrage"]);
+    }
+
+    #[test]
+    fn test_merge_replace_and_dry_run() {
+        let temp = TempDir::new().unwrap();
+        let local = storage(&temp.path().join("local"));
+        let mut shared = conversation("Partagée", 200);
+        local.save_conversation(&shared).unwrap();
+        let local_only = conversation("Locale", 150);
+        local.save_conversation(&local_only).unwrap();
+
+        shared.updated_at = 100;
+        let remote = conversation("Distante", 120);
+        let mut builder = BackupBuilder::new();
+        builder.add_json(BackupSection::Conversations, &format!("{}.json", shared.id), &shared).unwrap();
+        builder.add_json(BackupSection::Conversations, &format!("{}.json", remote.id), &remote).unwrap();
+        let path = temp.path().join("backup.tmbak");
+        builder.write(&path, "phrase", test_params()).unwrap();
+        let archive = BackupArchive::open(&path, "phrase").unwrap();
+
+        let dry = archive.restore_memory(&local, RestoreMode::Merge, true).unwrap();
+        assert_eq!(dry.created, vec![remote.id.clone()]);
+        assert_eq!(dry.skipped, vec![shared.id.clone()]);
+        assert!(local.load_conversation(&remote.id).is_err());
+
+        archive.restore_memory(&local, RestoreMode::Merge, false).unwrap();
+        assert!(local.load_conversation(&remote.id).is_ok());
+        assert_eq!(local.load_conversation(&shared.id).unwrap().updated_at, 200);
+
+        let replace = archive.restore_memory(&local, RestoreMode::Replace, false).unwrap();
+        assert_eq!(replace.removed, vec![local_only.id.clone()]);
+        assert!(local.load_conversation(&local_only.id).is_err());
+        assert_eq!(local.list_conversations().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_checksum_and_version_checks() {
+        let temp = TempDir::new().unwrap();
+        let mut builder = BackupBuilder::new();
+        builder.add_bytes(BackupSection::SemanticIndex, "points.bin", vec![1, 2, 3]).unwrap();
+        assert!(builder.add_bytes(BackupSection::Documents, "../evasion", vec![]).is_err());
+
+        let mut manifest = BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            app_version: "test".to_string(),
+            created_at: 0,
+            entries: builder.entries.clone(),
+        };
+        manifest.entries[0].sha256 = sha256_hex(b"autre");
+        let payload = BackupPayload {
+            manifest,
+            blobs: vec![general_purpose::STANDARD.encode([1, 2, 3])],
+        };
+        let corrupted = temp.path().join("corrupted.tmbak");
+        fs::write(&corrupted, seal(&payload, "phrase", test_params()).unwrap()).unwrap();
+
+        let verification = BackupArchive::verify(&corrupted, "phrase").unwrap();
+        assert_eq!(verification.corrupted, vec!["semantic/points.bin".to_string()]);
+        assert!(BackupArchive::open(&corrupted, "phrase").is_err());
+
+        let mut future = fs::read(&corrupted).unwrap();
+        future[3] = BACKUP_FORMAT_VERSION + 1;
+        let future_path = temp.path().join("future.tmbak");
+        fs::write(&future_path, future).unwrap();
+        let error = BackupArchive::verify(&future_path, "phrase").err().unwrap().to_string();
+        assert!(error.contains("newer"), "{}", error);
+    }
+
+    #[test]
+    fn test_replace_validates_every_entry_before_touching_destination() {
+        let temp = TempDir::new().unwrap();
+        let documents = temp.path().join("documents");
+        fs::create_dir_all(&documents).unwrap();
+        fs::write(documents.join("local.txt"), b"local").unwrap();
+
+        let entries: Vec<ManifestEntry> = [("documents/nouveau.txt", b"ok".as_slice()), ("documents/../evasion", b"x")]
+            .iter()
+            .map(|(path, data)| ManifestEntry {
+                section: BackupSection::Documents,
+                path: path.to_string(),
+                size: data.len() as u64,
+                sha256: sha256_hex(data),
+            })
+            .collect();
+        let payload = BackupPayload {
+            manifest: BackupManifest {
+                format_version: BACKUP_FORMAT_VERSION,
+                app_version: "test".to_string(),
+                created_at: 0,
+                entries,
+            },
+            blobs: vec![general_purpose::STANDARD.encode(b"ok"), general_purpose::STANDARD.encode(b"x")],
+        };
+        let path = temp.path().join("unsafe.tmbak");
+        fs::write(&path, seal(&payload, "phrase", test_params()).unwrap()).unwrap();
+        let archive = BackupArchive::open(&path, "phrase").unwrap();
+
+        assert!(archive
+            .restore_directory(BackupSection::Documents, &documents, RestoreMode::Replace, false)
+            .is_err());
+        assert_eq!(fs::read(documents.join("local.txt")).unwrap(), b"local");
+        assert!(!documents.join("nouveau.txt").exists());
+        // Aucun répertoire de préparation laissé à côté
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_replace_directory_swaps_in_staged_copy() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("source");
+        fs::create_dir_all(source.join("sous")).unwrap();
+        fs::write(source.join("sous/a.bin"), b"a").unwrap();
+        let mut builder = BackupBuilder::new();
+        builder.add_directory(BackupSection::SemanticIndex, &source).unwrap();
+        let path = temp.path().join("backup.tmbak");
+        builder.write(&path, "phrase", test_params()).unwrap();
+        let archive = BackupArchive::open(&path, "phrase").unwrap();
+
+        let target = temp.path().join("semantic");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("obsolete.bin"), b"old").unwrap();
+
+        let report = archive
+            .restore_directory(BackupSection::SemanticIndex, &target, RestoreMode::Replace, false)
+            .unwrap();
+        assert_eq!(report.created, vec!["sous/a.bin".to_string()]);
+        assert_eq!(report.removed, vec!["obsolete.bin".to_string()]);
+        assert_eq!(fs::read(target.join("sous/a.bin")).unwrap(), b"a");
+        assert!(!target.join("obsolete.bin").exists());
+        let siblings: Vec<String> = fs::read_dir(temp.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with('.'))
+            .collect();
+        assert!(siblings.is_empty(), "{:?}", siblings);
+    }
+
+    #[tokio::test]
+    async fn test_core_history_backup_and_restore() {
+        use crate::services::StorageService;
+        use crate::types::memory::EventType;
+        use std::collections::HashMap;
+
+        let temp = TempDir::new().unwrap();
+        let snapshot = |id: &str, timestamp: i64| Snapshot {
+            id: id.to_string(),
+            timestamp,
+            helios: None,
+            nexus: None,
+            harmonia: None,
+            sentinel: None,
+            metadata: HashMap::new(),
+        };
+        let event = |id: &str, timestamp: i64| TimelineEvent {
+            id: id.to_string(),
+            timestamp,
+            event_type: EventType::SystemStart,
+            description: "démarrage".to_string(),
+            data: HashMap::new(),
+        };
+
+        let source = MemoryCore::new(StorageService::new(temp.path().join("source")).unwrap());
+        source.write_snapshot(snapshot("s1", 10)).await.unwrap();
+        source.add_event(event("e1", 10)).await.unwrap();
+
+        let mut builder = BackupBuilder::new();
+        assert_eq!(builder.add_core_history(&source).await.unwrap(), 2);
+        let path = temp.path().join("backup.tmbak");
+        builder.write(&path, "phrase", test_params()).unwrap();
+        let archive = BackupArchive::open(&path, "phrase").unwrap();
+
+        let target = MemoryCore::new(StorageService::new(temp.path().join("target")).unwrap());
+        target.write_snapshot(snapshot("local", 5)).await.unwrap();
+
+        let dry = archive.restore_core_history(&target, RestoreMode::Replace, true).await.unwrap();
+        assert_eq!(dry.created, vec!["snapshot:s1".to_string(), "timeline:e1".to_string()]);
+        assert_eq!(dry.removed, vec!["snapshot:local".to_string()]);
+        assert_eq!(target.export_history().await.0.len(), 1);
+
+        archive.restore_core_history(&target, RestoreMode::Merge, false).await.unwrap();
+        let (snapshots, timeline) = target.export_history().await;
+        assert_eq!(snapshots.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), vec!["local", "s1"]);
+        assert_eq!(timeline.len(), 1);
+
+        archive.restore_core_history(&target, RestoreMode::Replace, false).await.unwrap();
+        let (snapshots, _) = target.export_history().await;
+        assert_eq!(snapshots.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), vec!["s1"]);
+    }
+
+    #[test]
+    fn test_scheduled_backup_retention() {
+        let temp = TempDir::new().unwrap();
+        let mut schedule = BackupSchedule::new(temp.path().join("auto"));
+        schedule.keep_last = 2;
+        schedule.kdf = test_params();
+
+        let mut written = Vec::new();
+        for _ in 0..4 {
+            written.push(run_scheduled_backup(&schedule, "phrase", BackupBuilder::new()).unwrap());
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        let mut remaining: Vec<PathBuf> = fs::read_dir(&schedule.directory)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, written[2..].to_vec());
+    }
+}
@@ -1,105 +1,234 @@
 // TITANE∞ v12 - Memory Encryption
 // AES-256-GCM encryption with Argon2id key derivation
+//
+// Une clé de données (DEK) aléatoire chiffre les conversations ; elle est
+// elle-même chiffrée (« wrappée ») par une clé dérivée du mot de passe avec
+// Argon2id. Changer de mot de passe ne ré-chiffre donc que la DEK.
+//
+// En-tête de clé (base64) : "TMK" | version | m_cost | t_cost | p | sel | nonce | DEK chiffrée
+// Données (base64)        : "TME" | version | nonce | texte chiffré
+// Format hérité (v1)      : sel(32) | nonce | texte chiffré, clé = SHA-256(mot de passe || sel)
 
 use super::{MemoryError, MemoryResult};
-use aes_gcm::{
-    aead::{Aead, KeyInit, OsRng},
-    Aes256Gcm, Nonce,
-};
+use crate::shared::crypto::{self, KEY_SIZE, NONCE_SIZE, SALT_SIZE as KDF_SALT_SIZE};
 use base64::{engine::general_purpose, Engine as _};
 use sha2::{Digest, Sha256};
 
-const SALT_SIZE: usize = 32;
-const NONCE_SIZE: usize = 12;
+/// Coûts Argon2id, enregistrés dans l'en-tête de clé
+pub use crate::shared::crypto::KdfConfig as KdfParams;
+
+const FORMAT_VERSION: u8 = 2;
+const KEY_MAGIC: &[u8; 3] = b"TMK";
+const DATA_MAGIC: &[u8; 3] = b"TME";
+/// magic + version + m_cost + t_cost + p + sel
+const KEY_HEADER_SIZE: usize = 4 + 12 + KDF_SALT_SIZE;
+const DATA_HEADER_SIZE: usize = 4;
+
+const LEGACY_SALT_SIZE: usize = 32;
 
 pub struct MemoryEncryption {
-    password: String,
+    /// Clé de données en clair (effacée au drop)
+    dek: [u8; KEY_SIZE],
+    /// En-tête de clé persistant (DEK wrappée + paramètres KDF)
+    key_header: String,
 }
 
 impl MemoryEncryption {
-    pub fn new(password: String) -> Self {
-        Self { password }
+    /// Crée un trousseau : nouvelle DEK wrappée par le mot de passe
+    pub fn create(password: &str, params: KdfParams) -> MemoryResult<Self> {
+        let dek: [u8; KEY_SIZE] = rand::random();
+        let key_header = wrap_dek(password, &dek, params)?;
+        Ok(Self { dek, key_header })
     }
 
-    fn derive_key(&self, salt: &[u8]) -> Vec<u8> {
-        // Using Argon2id for key derivation
-        // For production, use argon2 crate properly
-        // This is a simplified version using SHA256 for now
-        let mut hasher = Sha256::new();
-        hasher.update(self.password.as_bytes());
-        hasher.update(salt);
-        hasher.finalize().to_vec()
+    /// Déverrouille un trousseau existant à partir de son en-tête
+    pub fn unlock(password: &str, key_header: &str) -> MemoryResult<Self> {
+        let dek = unwrap_dek(password, key_header)?;
+        Ok(Self {
+            dek,
+            key_header: key_header.trim().to_string(),
+        })
     }
 
-    pub fn encrypt(&self, data: &[u8]) -> MemoryResult<String> {
-        // Generate random salt
-        let salt: [u8; SALT_SIZE] = rand::random();
+    /// En-tête à persister pour rouvrir le trousseau
+    pub fn key_header(&self) -> &str {
+        &self.key_header
+    }
 
-        // Derive key from password + salt
-        let key = self.derive_key(&salt);
+    /// Paramètres Argon2id de l'en-tête courant
+    pub fn kdf_params(&self) -> MemoryResult<KdfParams> {
+        let bytes = decode(&self.key_header, MemoryError::EncryptionError)?;
+        Ok(parse_key_header(&bytes).map_err(MemoryError::EncryptionError)?.0)
+    }
 
-        // Create cipher
-        let cipher = Aes256Gcm::new_from_slice(&key)
-            .map_err(|e| MemoryError::EncryptionError(e.to_string()))?;
+    /// Change le mot de passe : seule la DEK est re-wrappée
+    pub fn change_password(&mut self, new_password: &str, params: KdfParams) -> MemoryResult<()> {
+        self.key_header = wrap_dek(new_password, &self.dek, params)?;
+        Ok(())
+    }
 
-        // Generate random nonce
-        let nonce_bytes: [u8; NONCE_SIZE] = rand::random();
-        let nonce = Nonce::from_slice(&nonce_bytes);
+    pub fn encrypt(&self, data: &[u8]) -> MemoryResult<String> {
+        let mut result = Vec::with_capacity(DATA_HEADER_SIZE + NONCE_SIZE + data.len() + 16);
+        result.extend_from_slice(DATA_MAGIC);
+        result.push(FORMAT_VERSION);
 
-        // Encrypt
-        let ciphertext = cipher
-            .encrypt(nonce, data)
+        // Combine: en-tête || nonce || ciphertext
+        let sealed = crypto::encrypt(&self.dek, data, &result)
             .map_err(|e| MemoryError::EncryptionError(e.to_string()))?;
+        result.extend_from_slice(&sealed);
 
-        // Combine: salt || nonce || ciphertext
-        let mut result = Vec::new();
-        result.extend_from_slice(&salt);
-        result.extend_from_slice(&nonce_bytes);
-        result.extend_from_slice(&ciphertext);
-
-        // Encode to base64
         Ok(general_purpose::STANDARD.encode(&result))
     }
 
     pub fn decrypt(&self, encrypted: &str) -> MemoryResult<Vec<u8>> {
-        // Decode from base64
-        let data = general_purpose::STANDARD
-            .decode(encrypted)
-            .map_err(|e| MemoryError::DecryptionError(e.to_string()))?;
+        let data = decode(encrypted, MemoryError::DecryptionError)?;
 
-        if data.len() < SALT_SIZE + NONCE_SIZE {
+        if !is_current_format(&data) {
+            return Err(MemoryError::DecryptionError(
+                "Legacy SHA-256 format: rekey required".to_string(),
+            ));
+        }
+        if data.len() < DATA_HEADER_SIZE + NONCE_SIZE {
             return Err(MemoryError::DecryptionError(
                 "Invalid encrypted data".to_string(),
             ));
         }
 
-        // Extract components
-        let salt = &data[0..SALT_SIZE];
-        let nonce_bytes = &data[SALT_SIZE..SALT_SIZE + NONCE_SIZE];
-        let ciphertext = &data[SALT_SIZE + NONCE_SIZE..];
+        let (header, rest) = data.split_at(DATA_HEADER_SIZE);
+        crypto::decrypt(&self.dek, rest, header).map_err(|e| MemoryError::DecryptionError(e.to_string()))
+    }
+
+    /// Vrai si la donnée a été produite par l'ancien format SHA-256
+    pub fn is_legacy(encrypted: &str) -> bool {
+        match general_purpose::STANDARD.decode(encrypted.trim()) {
+            Ok(data) => !is_current_format(&data),
+            Err(_) => false,
+        }
+    }
 
-        // Derive key
-        let key = self.derive_key(salt);
+    /// Déchiffre une donnée de l'ancien format (clé = SHA-256(mot de passe || sel))
+    pub fn decrypt_legacy(password: &str, encrypted: &str) -> MemoryResult<Vec<u8>> {
+        let data = decode(encrypted, MemoryError::DecryptionError)?;
 
-        // Create cipher
-        let cipher = Aes256Gcm::new_from_slice(&key)
-            .map_err(|e| MemoryError::DecryptionError(e.to_string()))?;
+        if data.len() < LEGACY_SALT_SIZE + NONCE_SIZE {
+            return Err(MemoryError::DecryptionError(
+                "Invalid encrypted data".to_string(),
+            ));
+        }
 
-        // Decrypt
-        let nonce = Nonce::from_slice(nonce_bytes);
-        cipher
-            .decrypt(nonce, ciphertext)
+        // sel || nonce || ciphertext, sans données associées
+        let (salt, sealed) = data.split_at(LEGACY_SALT_SIZE);
+        crypto::decrypt(&legacy_key(password, salt), sealed, &[])
             .map_err(|e| MemoryError::DecryptionError(e.to_string()))
     }
 }
 
+impl Drop for MemoryEncryption {
+    fn drop(&mut self) {
+        crypto::wipe(&mut self.dek);
+    }
+}
+
+fn is_current_format(data: &[u8]) -> bool {
+    data.len() >= DATA_HEADER_SIZE && &data[..3] == DATA_MAGIC && data[3] == FORMAT_VERSION
+}
+
+fn legacy_key(password: &str, salt: &[u8]) -> [u8; KEY_SIZE] {
+    let mut hasher = Sha256::new();
+    hasher.update(password.as_bytes());
+    hasher.update(salt);
+    hasher.finalize().into()
+}
+
+fn decode(encoded: &str, error: fn(String) -> MemoryError) -> MemoryResult<Vec<u8>> {
+    general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| error(e.to_string()))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// En-tête de clé
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn wrap_dek(password: &str, dek: &[u8; KEY_SIZE], params: KdfParams) -> MemoryResult<String> {
+    let salt: [u8; KDF_SALT_SIZE] = rand::random();
+
+    let mut header = Vec::with_capacity(KEY_HEADER_SIZE + NONCE_SIZE + KEY_SIZE + 16);
+    header.extend_from_slice(KEY_MAGIC);
+    header.push(FORMAT_VERSION);
+    header.extend_from_slice(&params.memory_kib.to_le_bytes());
+    header.extend_from_slice(&params.iterations.to_le_bytes());
+    header.extend_from_slice(&params.parallelism.to_le_bytes());
+    header.extend_from_slice(&salt);
+
+    let mut kek =
+        crypto::derive_key(password, &salt, params).map_err(|e| MemoryError::EncryptionError(e.to_string()))?;
+    let wrapped = crypto::encrypt(&kek, dek, &header);
+    crypto::wipe(&mut kek);
+
+    header.extend_from_slice(&wrapped.map_err(|e| MemoryError::EncryptionError(e.to_string()))?);
+    Ok(general_purpose::STANDARD.encode(&header))
+}
+
+fn unwrap_dek(password: &str, key_header: &str) -> MemoryResult<[u8; KEY_SIZE]> {
+    let bytes = decode(key_header, MemoryError::DecryptionError)?;
+    let (params, salt) = parse_key_header(&bytes).map_err(MemoryError::DecryptionError)?;
+    let (authenticated, wrapped) = bytes.split_at(KEY_HEADER_SIZE);
+
+    let mut kek =
+        crypto::derive_key(password, salt, params).map_err(|e| MemoryError::DecryptionError(e.to_string()))?;
+    let dek = crypto::decrypt(&kek, wrapped, authenticated);
+    crypto::wipe(&mut kek);
+
+    let mut dek = dek.map_err(|_| MemoryError::DecryptionError("Invalid password".to_string()))?;
+    let key = <[u8; KEY_SIZE]>::try_from(dek.as_slice())
+        .map_err(|_| MemoryError::DecryptionError("Invalid data key size".to_string()));
+    crypto::wipe(&mut dek);
+    key
+}
+
+fn parse_key_header(bytes: &[u8]) -> Result<(KdfParams, &[u8]), String> {
+    if bytes.len() < KEY_HEADER_SIZE + NONCE_SIZE || &bytes[..3] != KEY_MAGIC {
+        return Err("Invalid key header".to_string());
+    }
+    if bytes[3] != FORMAT_VERSION {
+        return Err(format!("Unsupported key header version: {}", bytes[3]));
+    }
+
+    let read_u32 = |offset: usize| u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]]);
+    let params = KdfParams {
+        memory_kib: read_u32(4),
+        iterations: read_u32(8),
+        parallelism: read_u32(12),
+    };
+    Ok((params, &bytes[16..KEY_HEADER_SIZE]))
+}
+
+/// Chiffre au format hérité (SHA-256) pour les tests de migration
+#[cfg(test)]
+pub(crate) fn encrypt_legacy(password: &str, data: &[u8]) -> String {
+    let salt: [u8; LEGACY_SALT_SIZE] = rand::random();
+    let mut result = salt.to_vec();
+    result.extend_from_slice(&crypto::encrypt(&legacy_key(password, &salt), data, &[]).unwrap());
+    general_purpose::STANDARD.encode(&result)
+}
+
+#[cfg(test)]
+pub(crate) fn test_params() -> KdfParams {
+    KdfParams {
+        memory_kib: 1024,
+        iterations: 1,
+        parallelism: 1,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_encryption_decryption() {
-        let encryption = MemoryEncryption::new("test-password".to_string());
+        let encryption = MemoryEncryption::create("test-password", test_params()).unwrap();
         let data = b"Hello, TITANE!";
 
         let encrypted = encryption.encrypt(data).unwrap();
@@ -110,13 +239,44 @@ mod tests {
 
     #[test]
     fn test_different_password_fails() {
-        let enc1 = MemoryEncryption::new("password1".to_string());
-        let enc2 = MemoryEncryption::new("password2".to_string());
+        let enc1 = MemoryEncryption::create("password1", test_params()).unwrap();
+        let enc2 = MemoryEncryption::create("password2", test_params()).unwrap();
 
         let data = b"Secret data";
         let encrypted = enc1.encrypt(data).unwrap();
 
         // Should fail with wrong password
         assert!(enc2.decrypt(&encrypted).is_err());
+        assert!(MemoryEncryption::unlock("password2", enc1.key_header()).is_err());
+    }
+
+    #[test]
+    fn test_unlock_and_change_password_rewraps_only() {
+        let mut encryption = MemoryEncryption::create("ancien", test_params()).unwrap();
+        let encrypted = encryption.encrypt(b"journal").unwrap();
+        assert_eq!(encryption.kdf_params().unwrap(), test_params());
+
+        let reopened = MemoryEncryption::unlock("ancien", encryption.key_header()).unwrap();
+        assert_eq!(reopened.decrypt(&encrypted).unwrap(), b"journal");
+
+        encryption.change_password("nouveau", test_params()).unwrap();
+        assert!(MemoryEncryption::unlock("ancien", encryption.key_header()).is_err());
+        let reopened = MemoryEncryption::unlock("nouveau", encryption.key_header()).unwrap();
+        assert_eq!(reopened.decrypt(&encrypted).unwrap(), b"journal");
+    }
+
+    #[test]
+    fn test_legacy_format_decryption() {
+        let legacy = encrypt_legacy("test-password", b"ancienne conversation");
+        assert!(MemoryEncryption::is_legacy(&legacy));
+        assert_eq!(
+            MemoryEncryption::decrypt_legacy("test-password", &legacy).unwrap(),
+            b"ancienne conversation"
+        );
+        assert!(MemoryEncryption::decrypt_legacy("mauvais", &legacy).is_err());
+
+        let encryption = MemoryEncryption::create("test-password", test_params()).unwrap();
+        assert!(encryption.decrypt(&legacy).is_err());
+        assert!(!MemoryEncryption::is_legacy(&encryption.encrypt(b"x").unwrap()));
     }
 }
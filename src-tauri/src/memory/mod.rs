@@ -1,6 +1,7 @@
 // TITANE∞ v12 - Memory Module
 // Encrypted persistent conversational memory with AES-256-GCM + Argon2id
 
+pub mod backup;
 pub mod encryption;
 pub mod model;
 pub mod storage;
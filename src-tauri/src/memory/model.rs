@@ -20,8 +20,29 @@ pub struct ConversationMetadata {
     pub message_count: usize,
     pub tags: Vec<String>,
     pub is_archived: bool,
+    /// Conversation d'origine si celle-ci est une branche
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// Message de la conversation d'origine à partir duquel la branche diverge
+    #[serde(default)]
+    pub branch_point: Option<String>,
+    /// Vrai une fois le titre généré automatiquement (ou fixé par l'utilisateur)
+    #[serde(default)]
+    pub title_locked: bool,
 }
 
+/// Page de messages d'une conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagePage {
+    pub conversation_id: String,
+    pub entries: Vec<MemoryEntry>,
+    pub offset: usize,
+    pub total: usize,
+    pub has_more: bool,
+}
+
+const AUTO_TITLE_MAX_CHARS: usize = 60;
+
 impl Conversation {
     pub fn new(title: String) -> Self {
         let now = chrono::Utc::now().timestamp();
@@ -36,6 +57,9 @@ impl Conversation {
                 message_count: 0,
                 tags: Vec::new(),
                 is_archived: false,
+                parent_id: None,
+                branch_point: None,
+                title_locked: false,
             },
         }
     }
@@ -73,6 +97,69 @@ impl Conversation {
         result
     }
 
+    /// Page de messages, du plus ancien au plus récent
+    pub fn page(&self, offset: usize, limit: usize) -> MessagePage {
+        let total = self.entries.len();
+        let start = offset.min(total);
+        let end = (start + limit).min(total);
+
+        MessagePage {
+            conversation_id: self.id.clone(),
+            entries: self.entries[start..end].to_vec(),
+            offset: start,
+            total,
+            has_more: end < total,
+        }
+    }
+
+    /// Génère le titre après le premier échange utilisateur/assistant.
+    /// Retourne vrai si le titre a changé.
+    pub fn auto_title(&mut self) -> bool {
+        if self.metadata.title_locked {
+            return false;
+        }
+
+        let has_reply = self.entries.iter().any(|e| e.role == MessageRole::Assistant);
+        let first_user = self.entries.iter().find(|e| e.role == MessageRole::User);
+
+        match (has_reply, first_user) {
+            (true, Some(entry)) => {
+                let title = title_from_text(&entry.content);
+                if title.is_empty() {
+                    return false;
+                }
+                self.title = title;
+                self.metadata.title_locked = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Crée une branche : messages antérieurs à `entry_id` copiés,
+    /// puis `entry_id` remplacé par `new_content` (même rôle)
+    pub fn fork_at(&self, entry_id: &str, new_content: String) -> Option<Conversation> {
+        let position = self.entries.iter().position(|e| e.id == entry_id)?;
+        let edited = &self.entries[position];
+
+        let mut branch = Conversation::new(format!("{} (branche)", self.title));
+        branch.metadata.parent_id = Some(self.id.clone());
+        branch.metadata.branch_point = Some(entry_id.to_string());
+        branch.metadata.title_locked = true;
+        branch.metadata.tags = self.metadata.tags.clone();
+
+        for entry in &self.entries[..position] {
+            branch.entries.push(entry.clone());
+            branch.metadata.total_tokens += entry.tokens;
+            branch.metadata.message_count += 1;
+        }
+
+        let tokens = new_content.split_whitespace().count();
+        branch.add_entry(edited.role.clone(), new_content, tokens);
+
+        Some(branch)
+    }
+
     pub fn clear(&mut self) {
         self.entries.clear();
         self.metadata.total_tokens = 0;
@@ -96,6 +183,8 @@ pub struct ConversationSummary {
     pub updated_at: i64,
     pub message_count: usize,
     pub is_archived: bool,
+    #[serde(default)]
+    pub parent_id: Option<String>,
 }
 
 impl From<&Conversation> for ConversationSummary {
@@ -107,10 +196,34 @@ impl From<&Conversation> for ConversationSummary {
             updated_at: conv.updated_at,
             message_count: conv.metadata.message_count,
             is_archived: conv.metadata.is_archived,
+            parent_id: conv.metadata.parent_id.clone(),
         }
     }
 }
 
+/// Titre court dérivé du premier message (première ligne, coupée sur un mot)
+fn title_from_text(text: &str) -> String {
+    let first_line = text.lines().find(|l| !l.trim().is_empty()).unwrap_or("").trim();
+    if first_line.chars().count() <= AUTO_TITLE_MAX_CHARS {
+        return first_line.to_string();
+    }
+
+    let mut title = String::new();
+    for word in first_line.split_whitespace() {
+        if title.chars().count() + word.chars().count() + 1 > AUTO_TITLE_MAX_CHARS {
+            break;
+        }
+        if !title.is_empty() {
+            title.push(' ');
+        }
+        title.push_str(word);
+    }
+    if title.is_empty() {
+        title = first_line.chars().take(AUTO_TITLE_MAX_CHARS).collect();
+    }
+    format!("{}…", title)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,4 +253,45 @@ mod tests {
         let context = conv.get_context(30);
         assert_eq!(context.len(), 2); // Should get last 2 messages
     }
+
+    #[test]
+    fn test_page() {
+        let mut conv = Conversation::new("Test".to_string());
+        for i in 0..5 {
+            conv.add_entry(MessageRole::User, format!("Message {}", i), 1);
+        }
+
+        let page = conv.page(3, 10);
+        assert_eq!(page.entries.len(), 2);
+        assert_eq!(page.total, 5);
+        assert!(!page.has_more);
+        assert!(conv.page(0, 2).has_more);
+    }
+
+    #[test]
+    fn test_auto_title_after_first_exchange() {
+        let mut conv = Conversation::new("Nouvelle conversation".to_string());
+        conv.add_entry(MessageRole::User, "Plan de migration vers Tauri v2".to_string(), 6);
+        assert!(!conv.auto_title());
+
+        conv.add_entry(MessageRole::Assistant, "Voici un plan".to_string(), 3);
+        assert!(conv.auto_title());
+        assert_eq!(conv.title, "Plan de migration vers Tauri v2");
+        assert!(!conv.auto_title());
+    }
+
+    #[test]
+    fn test_fork_at() {
+        let mut conv = Conversation::new("Test".to_string());
+        conv.add_entry(MessageRole::User, "Question 1".to_string(), 2);
+        conv.add_entry(MessageRole::Assistant, "Réponse 1".to_string(), 2);
+        conv.add_entry(MessageRole::User, "Question 2".to_string(), 2);
+        let edited_id = conv.entries[2].id.clone();
+
+        let branch = conv.fork_at(&edited_id, "Question 2 bis".to_string()).unwrap();
+        assert_eq!(branch.entries.len(), 3);
+        assert_eq!(branch.entries[2].content, "Question 2 bis");
+        assert_eq!(branch.metadata.parent_id.as_deref(), Some(conv.id.as_str()));
+        assert!(conv.fork_at("inconnu", String::new()).is_none());
+    }
 }
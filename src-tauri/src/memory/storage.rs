@@ -1,29 +1,154 @@
 // TITANE∞ v12 - Memory Storage
 // Encrypted persistent storage for conversations
 
-use super::encryption::MemoryEncryption;
-use super::model::{Conversation, ConversationSummary, MemoryIndex};
+use super::encryption::{KdfParams, MemoryEncryption};
+use super::model::{Conversation, ConversationSummary, MemoryIndex, MessagePage};
 use super::{MemoryError, MemoryResult};
-use std::fs;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// En-tête de clé (DEK wrappée par le mot de passe)
+const KEYRING_FILE: &str = "keyring.key";
+const ENCRYPTED_SUFFIX: &str = ".json.enc";
+/// Fichiers ré-chiffrés en attente de validation du nouveau trousseau
+const REKEY_SUFFIX: &str = ".rekey";
+/// Journal d'index : une ligne chiffrée par opération, ajout seul
+const INDEX_LOG_FILE: &str = "index.log";
+/// Ancien index : un seul blob chiffré réécrit à chaque sauvegarde
+const LEGACY_INDEX_FILE: &str = "index.json.enc";
+/// Vecteurs des messages d'une conversation : une ligne chiffrée par message, ajout seul
+const VECTORS_SUFFIX: &str = ".vectors.log";
+/// Compaction dès que le journal dépasse ce nombre de lignes et le double des entrées vivantes
+const INDEX_COMPACTION_MIN_RECORDS: usize = 256;
+
+/// Opération du journal d'index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum IndexRecord {
+    Upsert { summary: ConversationSummary },
+    Remove { id: String },
+}
+
+/// État du journal connu en mémoire, pour décider de la compaction sans le relire
+#[derive(Debug, Default)]
+struct IndexLogState {
+    records: usize,
+    live: HashSet<String>,
+}
+
+/// Résultat de recherche plein texte dans les conversations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationSearchHit {
+    pub conversation_id: String,
+    pub conversation_title: String,
+    pub entry_id: String,
+    pub snippet: String,
+    pub score: f32,
+    pub timestamp: i64,
+}
+
+const SNIPPET_RADIUS: usize = 60;
+
+/// Embedding d'un message, écrit à côté de sa conversation pour la recherche sémantique
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageVector {
+    pub entry_id: String,
+    pub snippet: String,
+    pub vector: Vec<f32>,
+}
+
+/// Bilan d'une reconstruction de l'index
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexRebuildReport {
+    pub indexed: usize,
+    /// Fichiers de conversation illisibles (déchiffrement ou JSON), laissés hors index
+    pub skipped: Vec<String>,
+}
 
 pub struct MemoryStorage {
     storage_dir: PathBuf,
     encryption: MemoryEncryption,
+    index_log: Mutex<IndexLogState>,
 }
 
 impl MemoryStorage {
     pub fn new(storage_dir: PathBuf, password: String) -> MemoryResult<Self> {
+        Self::with_kdf_params(storage_dir, password, KdfParams::default())
+    }
+
+    /// Ouvre le stockage ; `params` ne sert qu'à la création du trousseau.
+    /// Au premier déverrouillage, les fichiers de l'ancien format SHA-256
+    /// sont migrés vers la nouvelle DEK.
+    pub fn with_kdf_params(storage_dir: PathBuf, password: String, params: KdfParams) -> MemoryResult<Self> {
         // Create storage directory if it doesn't exist
         if !storage_dir.exists() {
             fs::create_dir_all(&storage_dir)
                 .map_err(|e| MemoryError::StorageError(e.to_string()))?;
         }
 
-        Ok(Self {
+        let keyring_path = storage_dir.join(KEYRING_FILE);
+        let encryption = if keyring_path.exists() {
+            let header = fs::read_to_string(&keyring_path)
+                .map_err(|e| MemoryError::StorageError(e.to_string()))?;
+            let encryption = MemoryEncryption::unlock(&password, &header)?;
+            finish_pending_rekey(&storage_dir, &encryption)?;
+            if has_legacy_files(&storage_dir)? {
+                reencrypt_files(&storage_dir, &password, Some(&encryption), &encryption)?;
+            }
+            encryption
+        } else {
+            discard_pending_rekey(&storage_dir)?;
+            let encryption = MemoryEncryption::create(&password, params)?;
+            let migrated = reencrypt_files(&storage_dir, &password, None, &encryption)?;
+            if migrated > 0 {
+                log::info!("{} fichiers mémoire migrés vers Argon2id", migrated);
+            }
+            encryption
+        };
+
+        let storage = Self {
             storage_dir,
-            encryption: MemoryEncryption::new(password),
-        })
+            encryption,
+            index_log: Mutex::new(IndexLogState::default()),
+        };
+        storage.open_index()?;
+        Ok(storage)
+    }
+
+    /// Génère une nouvelle DEK et ré-chiffre tous les fichiers (rotation de clé) ;
+    /// les fichiers encore à l'ancien format sont migrés au passage
+    pub fn rekey(&mut self, password: &str) -> MemoryResult<usize> {
+        // Vérifie le mot de passe avant de wrapper la nouvelle clé avec
+        MemoryEncryption::unlock(password, self.encryption.key_header())?;
+
+        let target = MemoryEncryption::create(password, self.encryption.kdf_params()?)?;
+        // Le journal est chiffré ligne à ligne : il est reconstruit plutôt que ré-chiffré
+        remove_file_if_exists(&self.index_log_path())?;
+        let count = match reencrypt_files(&self.storage_dir, password, Some(&self.encryption), &target) {
+            Ok(count) => count,
+            Err(e) => {
+                self.rebuild_index()?;
+                return Err(e);
+            }
+        };
+        self.encryption = target;
+        // Vecteurs chiffrés sous l'ancienne clé : recalculés à la prochaine recherche
+        for path in encrypted_files(&self.storage_dir, VECTORS_SUFFIX)? {
+            remove_file_if_exists(&path)?;
+        }
+        self.rebuild_index()?;
+        Ok(count)
+    }
+
+    /// Change le mot de passe sans ré-chiffrer les conversations
+    pub fn change_password(&mut self, old_password: &str, new_password: &str, params: KdfParams) -> MemoryResult<()> {
+        MemoryEncryption::unlock(old_password, self.encryption.key_header())?;
+        self.encryption.change_password(new_password, params)?;
+        write_atomic(&self.storage_dir.join(KEYRING_FILE), self.encryption.key_header())
     }
 
     fn get_conversation_path(&self, conversation_id: &str) -> PathBuf {
@@ -31,8 +156,12 @@ impl MemoryStorage {
             .join(format!("{}.json.enc", conversation_id))
     }
 
-    fn get_index_path(&self) -> PathBuf {
-        self.storage_dir.join("index.json.enc")
+    fn index_log_path(&self) -> PathBuf {
+        self.storage_dir.join(INDEX_LOG_FILE)
+    }
+
+    fn vectors_path(&self, conversation_id: &str) -> PathBuf {
+        self.storage_dir.join(format!("{}{}", conversation_id, VECTORS_SUFFIX))
     }
 
     pub fn save_conversation(&self, conversation: &Conversation) -> MemoryResult<()> {
@@ -48,7 +177,9 @@ impl MemoryStorage {
         fs::write(path, encrypted).map_err(|e| MemoryError::StorageError(e.to_string()))?;
 
         // Update index
-        self.update_index(conversation)?;
+        self.append_index_record(&IndexRecord::Upsert {
+            summary: ConversationSummary::from(conversation),
+        })?;
 
         Ok(())
     }
@@ -63,18 +194,7 @@ impl MemoryStorage {
             )));
         }
 
-        // Read encrypted data
-        let encrypted =
-            fs::read_to_string(path).map_err(|e| MemoryError::StorageError(e.to_string()))?;
-
-        // Decrypt
-        let decrypted = self.encryption.decrypt(&encrypted)?;
-
-        // Deserialize
-        let conversation: Conversation = serde_json::from_slice(&decrypted)
-            .map_err(|e| MemoryError::InvalidData(e.to_string()))?;
-
-        Ok(conversation)
+        self.read_conversation_file(&path)
     }
 
     pub fn delete_conversation(&self, conversation_id: &str) -> MemoryResult<()> {
@@ -83,76 +203,323 @@ impl MemoryStorage {
         if path.exists() {
             fs::remove_file(path).map_err(|e| MemoryError::StorageError(e.to_string()))?;
         }
+        remove_file_if_exists(&self.vectors_path(conversation_id))?;
 
-        Ok(())
+        let indexed = self.index_log.lock().unwrap().live.contains(conversation_id);
+        if !indexed {
+            return Ok(());
+        }
+        self.append_index_record(&IndexRecord::Remove {
+            id: conversation_id.to_string(),
+        })
     }
 
-    pub fn list_conversations(&self) -> MemoryResult<Vec<ConversationSummary>> {
-        match self.load_index() {
-            Ok(index) => Ok(index.conversations),
-            Err(_) => Ok(Vec::new()),
+    /// Ajoute l'embedding d'un message au journal de vecteurs de sa conversation
+    pub fn append_message_vector(&self, conversation_id: &str, vector: &MessageVector) -> MemoryResult<()> {
+        let json = serde_json::to_vec(vector).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+        let line = self.encryption.encrypt(&json)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.vectors_path(conversation_id))
+            .map_err(|e| MemoryError::StorageError(e.to_string()))?;
+        writeln!(file, "{}", line).map_err(|e| MemoryError::StorageError(e.to_string()))
+    }
+
+    /// Vecteurs enregistrés d'une conversation (vide si aucun) ; une ligne
+    /// illisible (écriture interrompue, ancienne clé) est ignorée et sera recalculée
+    pub fn load_message_vectors(&self, conversation_id: &str) -> MemoryResult<Vec<MessageVector>> {
+        let content = match fs::read_to_string(self.vectors_path(conversation_id)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(MemoryError::StorageError(e.to_string())),
+        };
+
+        Ok(content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|line| {
+                let plain = self.encryption.decrypt(line).ok()?;
+                serde_json::from_slice::<MessageVector>(&plain).ok()
+            })
+            .collect())
+    }
+
+    pub fn load_messages_page(
+        &self,
+        conversation_id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> MemoryResult<MessagePage> {
+        Ok(self.load_conversation(conversation_id)?.page(offset, limit))
+    }
+
+    /// Recherche plein texte (tous les termes, insensible à la casse) dans
+    /// toutes les conversations indexées ; score = occurrences / longueur
+    pub fn search_conversations(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> MemoryResult<Vec<ConversationSearchHit>> {
+        let terms = search_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
         }
+
+        let mut hits = Vec::new();
+        for summary in self.load_index()?.conversations {
+            let conversation = match self.load_conversation(&summary.id) {
+                Ok(c) => c,
+                Err(e) => {
+                    log::warn!("Conversation {} ignorée pendant la recherche: {}", summary.id, e);
+                    continue;
+                }
+            };
+            push_text_hits(&conversation, &terms, &mut hits);
+        }
+
+        Ok(rank_hits(hits, limit))
     }
 
-    fn load_index(&self) -> MemoryResult<MemoryIndex> {
-        let path = self.get_index_path();
+    /// Conversations indexées ; un index illisible est une erreur, pas un historique vide
+    pub fn list_conversations(&self) -> MemoryResult<Vec<ConversationSummary>> {
+        Ok(self.load_index()?.conversations)
+    }
 
-        if !path.exists() {
-            return Ok(MemoryIndex {
-                conversations: Vec::new(),
-                total_conversations: 0,
-                total_messages: 0,
-            });
+    /// Index déchiffré des conversations (rejeu du journal)
+    pub fn load_index(&self) -> MemoryResult<MemoryIndex> {
+        let summaries = self.replay_index_log()?;
+        Ok(MemoryIndex {
+            total_conversations: summaries.len(),
+            total_messages: summaries.iter().map(|c| c.message_count).sum(),
+            conversations: summaries,
+        })
+    }
+
+    /// Reconstruit l'index à partir des fichiers de conversation ; un fichier
+    /// illisible est écarté de l'index (et signalé) sans faire échouer les autres
+    pub fn rebuild_index(&self) -> MemoryResult<IndexRebuildReport> {
+        let mut summaries = Vec::new();
+        let mut skipped = Vec::new();
+        for path in encrypted_files(&self.storage_dir, ENCRYPTED_SUFFIX)? {
+            if path.file_name().is_some_and(|name| name == LEGACY_INDEX_FILE) {
+                continue;
+            }
+            match self.read_conversation_file(&path) {
+                Ok(conversation) => summaries.push(ConversationSummary::from(&conversation)),
+                Err(e) => {
+                    log::warn!("Conversation illisible ignorée par l'index {}: {}", path.display(), e);
+                    skipped.push(path.file_name().unwrap_or_default().to_string_lossy().into_owned());
+                }
+            }
         }
+        summaries.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
 
-        let encrypted =
-            fs::read_to_string(path).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+        let indexed = summaries.len();
+        self.write_compacted_index(summaries)?;
+        Ok(IndexRebuildReport { indexed, skipped })
+    }
+
+    /// Réécrit le journal avec une seule ligne par conversation vivante ; le
+    /// verrou est tenu du rejeu à la réécriture pour ne perdre aucun ajout
+    pub fn compact_index(&self) -> MemoryResult<()> {
+        let mut state = self.index_log.lock().unwrap();
+        let summaries = self.replay_locked(&mut state)?;
+        self.write_compacted_locked(&mut state, summaries)
+    }
 
+    /// Lit, déchiffre et désérialise un fichier de conversation
+    fn read_conversation_file(&self, path: &Path) -> MemoryResult<Conversation> {
+        let encrypted = fs::read_to_string(path).map_err(|e| MemoryError::StorageError(e.to_string()))?;
         let decrypted = self.encryption.decrypt(&encrypted)?;
+        serde_json::from_slice(&decrypted).map_err(|e| MemoryError::InvalidData(e.to_string()))
+    }
+
+    /// Prépare le journal à l'ouverture : migration de l'ancien blob,
+    /// reconstruction si le journal a disparu, sinon vérification par rejeu
+    fn open_index(&self) -> MemoryResult<()> {
+        let legacy_path = self.storage_dir.join(LEGACY_INDEX_FILE);
+        if legacy_path.exists() {
+            let encrypted = fs::read_to_string(&legacy_path).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+            let index: MemoryIndex = serde_json::from_slice(&self.encryption.decrypt(&encrypted)?)
+                .map_err(|e| MemoryError::InvalidData(e.to_string()))?;
+            self.write_compacted_index(index.conversations)?;
+            return remove_file_if_exists(&legacy_path);
+        }
 
-        let index: MemoryIndex = serde_json::from_slice(&decrypted)
-            .map_err(|e| MemoryError::InvalidData(e.to_string()))?;
+        if !self.index_log_path().exists() {
+            let rebuilt = self.rebuild_index()?;
+            if rebuilt.indexed > 0 || !rebuilt.skipped.is_empty() {
+                log::warn!(
+                    "Index mémoire absent : reconstruit depuis {} conversations ({} illisibles)",
+                    rebuilt.indexed,
+                    rebuilt.skipped.len()
+                );
+            }
+            return Ok(());
+        }
 
-        Ok(index)
+        self.replay_index_log()?;
+        // Une ligne tronquée serait collée à l'ajout suivant : on réécrit le journal
+        let content = fs::read(self.index_log_path()).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+        if content.last().is_some_and(|&b| b != b'\n') {
+            self.compact_index()?;
+        }
+        Ok(())
     }
 
-    fn update_index(&self, conversation: &Conversation) -> MemoryResult<()> {
-        let mut index = self.load_index().unwrap_or_else(|_| MemoryIndex {
-            conversations: Vec::new(),
-            total_conversations: 0,
-            total_messages: 0,
-        });
+    /// Rejoue le journal ; seule une dernière ligne tronquée (écriture
+    /// interrompue) est tolérée, toute autre ligne illisible est une erreur
+    fn replay_index_log(&self) -> MemoryResult<Vec<ConversationSummary>> {
+        let mut state = self.index_log.lock().unwrap();
+        self.replay_locked(&mut state)
+    }
 
-        // Update or add conversation summary
-        let summary = ConversationSummary::from(conversation);
-        if let Some(existing) = index
-            .conversations
-            .iter_mut()
-            .find(|s| s.id == conversation.id)
-        {
-            *existing = summary;
-        } else {
-            index.conversations.push(summary);
-            index.total_conversations += 1;
+    fn replay_locked(&self, state: &mut IndexLogState) -> MemoryResult<Vec<ConversationSummary>> {
+        let path = self.index_log_path();
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(MemoryError::StorageError(e.to_string())),
+        };
+
+        let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+        let torn_tail = !content.is_empty() && !content.ends_with('\n');
+
+        let mut slots: Vec<Option<ConversationSummary>> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for (i, line) in lines.iter().enumerate() {
+            let record = self
+                .encryption
+                .decrypt(line)
+                .and_then(|plain| {
+                    serde_json::from_slice::<IndexRecord>(&plain).map_err(|e| MemoryError::InvalidData(e.to_string()))
+                });
+            let record = match record {
+                Ok(record) => record,
+                Err(_) if torn_tail && i == lines.len() - 1 => {
+                    log::warn!("Dernière entrée du journal d'index tronquée, ignorée");
+                    break;
+                }
+                Err(e) => {
+                    return Err(MemoryError::DecryptionError(format!(
+                        "Index record {} unreadable: {}",
+                        i + 1,
+                        e
+                    )))
+                }
+            };
+
+            match record {
+                IndexRecord::Upsert { summary } => match positions.get(&summary.id) {
+                    Some(&pos) => slots[pos] = Some(summary),
+                    None => {
+                        positions.insert(summary.id.clone(), slots.len());
+                        slots.push(Some(summary));
+                    }
+                },
+                IndexRecord::Remove { id } => {
+                    if let Some(pos) = positions.remove(&id) {
+                        slots[pos] = None;
+                    }
+                }
+            }
         }
 
-        // Recalculate totals
-        index.total_messages = index
-            .conversations
-            .iter()
-            .map(|c| c.message_count)
-            .sum();
+        let summaries: Vec<ConversationSummary> = slots.into_iter().flatten().collect();
+        state.records = lines.len();
+        state.live = summaries.iter().map(|s| s.id.clone()).collect();
+        Ok(summaries)
+    }
 
-        // Serialize and encrypt
-        let json =
-            serde_json::to_string(&index).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+    /// Ajoute une opération chiffrée au journal, puis compacte si nécessaire
+    fn append_index_record(&self, record: &IndexRecord) -> MemoryResult<()> {
+        let json = serde_json::to_vec(record).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+        let line = self.encryption.encrypt(&json)?;
+
+        let needs_compaction = {
+            let mut state = self.index_log.lock().unwrap();
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.index_log_path())
+                .map_err(|e| MemoryError::StorageError(e.to_string()))?;
+            writeln!(file, "{}", line).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+
+            state.records += 1;
+            match record {
+                IndexRecord::Upsert { summary } => state.live.insert(summary.id.clone()),
+                IndexRecord::Remove { id } => state.live.remove(id),
+            };
+            state.records > INDEX_COMPACTION_MIN_RECORDS && state.records > 2 * state.live.len()
+        };
+
+        if needs_compaction {
+            self.compact_index()?;
+        }
+        Ok(())
+    }
 
-        let encrypted = self.encryption.encrypt(json.as_bytes())?;
+    fn write_compacted_index(&self, summaries: Vec<ConversationSummary>) -> MemoryResult<()> {
+        let mut state = self.index_log.lock().unwrap();
+        self.write_compacted_locked(&mut state, summaries)
+    }
 
-        // Write to file
-        fs::write(self.get_index_path(), encrypted)
-            .map_err(|e| MemoryError::StorageError(e.to_string()))?;
+    fn write_compacted_locked(&self, state: &mut IndexLogState, summaries: Vec<ConversationSummary>) -> MemoryResult<()> {
+        let content = self.compacted_index(&summaries)?;
+        write_atomic(&self.index_log_path(), &content)?;
+        state.records = summaries.len();
+        state.live = summaries.into_iter().map(|s| s.id).collect();
+        Ok(())
+    }
+
+    /// Journal compacté : une ligne chiffrée par conversation
+    fn compacted_index(&self, summaries: &[ConversationSummary]) -> MemoryResult<String> {
+        let mut content = String::new();
+        for summary in summaries {
+            let record = IndexRecord::Upsert { summary: summary.clone() };
+            let json = serde_json::to_vec(&record).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+            content.push_str(&self.encryption.encrypt(&json)?);
+            content.push('\n');
+        }
+        Ok(content)
+    }
 
+    /// Remplace tout le contenu par `conversations`. Le nouveau répertoire est
+    /// préparé à côté (même trousseau) puis substitué à l'ancien : une erreur
+    /// en cours de route laisse le stockage intact.
+    pub fn replace_all(&self, conversations: &[Conversation]) -> MemoryResult<()> {
+        let staging = sibling_path(&self.storage_dir, "restore");
+        let staged = (|| {
+            fs::create_dir_all(&staging).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+            fs::write(staging.join(KEYRING_FILE), self.encryption.key_header())
+                .map_err(|e| MemoryError::StorageError(e.to_string()))?;
+            for conversation in conversations {
+                let json = serde_json::to_vec(conversation).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+                fs::write(
+                    staging.join(format!("{}{}", conversation.id, ENCRYPTED_SUFFIX)),
+                    self.encryption.encrypt(&json)?,
+                )
+                .map_err(|e| MemoryError::StorageError(e.to_string()))?;
+            }
+            let summaries: Vec<ConversationSummary> = conversations.iter().map(ConversationSummary::from).collect();
+            fs::write(staging.join(INDEX_LOG_FILE), self.compacted_index(&summaries)?)
+                .map_err(|e| MemoryError::StorageError(e.to_string()))?;
+            Ok(summaries)
+        })();
+
+        let summaries = match staged {
+            Ok(summaries) => summaries,
+            Err(e) => {
+                let _ = fs::remove_dir_all(&staging);
+                return Err(e);
+            }
+        };
+
+        let mut state = self.index_log.lock().unwrap();
+        swap_directory(&staging, &self.storage_dir)?;
+        state.records = summaries.len();
+        state.live = summaries.into_iter().map(|s| s.id).collect();
         Ok(())
     }
 
@@ -168,16 +535,233 @@ impl MemoryStorage {
                 .map_err(|e| MemoryError::StorageError(e.to_string()))?;
             fs::create_dir_all(&self.storage_dir)
                 .map_err(|e| MemoryError::StorageError(e.to_string()))?;
+            // Le trousseau reste valide pour les écritures suivantes
+            write_atomic(&self.storage_dir.join(KEYRING_FILE), self.encryption.key_header())?;
         }
+        *self.index_log.lock().unwrap() = IndexLogState::default();
         Ok(())
     }
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Trousseau et ré-chiffrement
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Ré-chiffre chaque fichier sous `target`, puis valide le trousseau.
+/// Les fichiers sont d'abord écrits à côté (`.rekey`) ; le trousseau n'est
+/// remplacé qu'une fois tous écrits, puis ils remplacent les originaux.
+/// Une interruption est rattrapée à l'ouverture suivante.
+fn reencrypt_files(
+    dir: &Path,
+    password: &str,
+    current: Option<&MemoryEncryption>,
+    target: &MemoryEncryption,
+) -> MemoryResult<usize> {
+    let files = encrypted_files(dir, ENCRYPTED_SUFFIX)?;
+
+    for path in &files {
+        let staged = (|| {
+            let encrypted = fs::read_to_string(path).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+            let plaintext = match current {
+                Some(current) if !MemoryEncryption::is_legacy(&encrypted) => current.decrypt(&encrypted)?,
+                _ => MemoryEncryption::decrypt_legacy(password, &encrypted)?,
+            };
+            fs::write(rekey_path(path), target.encrypt(&plaintext)?)
+                .map_err(|e| MemoryError::StorageError(e.to_string()))
+        })();
+
+        if let Err(e) = staged {
+            discard_pending_rekey(dir)?;
+            return Err(MemoryError::DecryptionError(format!(
+                "{}: {}",
+                path.file_name().unwrap_or_default().to_string_lossy(),
+                e
+            )));
+        }
+    }
+
+    write_atomic(&dir.join(KEYRING_FILE), target.key_header())?;
+    finish_pending_rekey(dir, target)?;
+    Ok(files.len())
+}
+
+/// Remplace les originaux par les fichiers `.rekey` lisibles avec le trousseau
+/// validé ; les autres proviennent d'une rotation abandonnée
+fn finish_pending_rekey(dir: &Path, encryption: &MemoryEncryption) -> MemoryResult<()> {
+    for staged in encrypted_files(dir, REKEY_SUFFIX)? {
+        let readable = fs::read_to_string(&staged)
+            .map(|content| encryption.decrypt(&content).is_ok())
+            .unwrap_or(false);
+        let result = if readable {
+            fs::rename(&staged, staged.with_extension(""))
+        } else {
+            fs::remove_file(&staged)
+        };
+        result.map_err(|e| MemoryError::StorageError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn discard_pending_rekey(dir: &Path) -> MemoryResult<()> {
+    for staged in encrypted_files(dir, REKEY_SUFFIX)? {
+        fs::remove_file(staged).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn has_legacy_files(dir: &Path) -> MemoryResult<bool> {
+    Ok(encrypted_files(dir, ENCRYPTED_SUFFIX)?.iter().any(|path| {
+        fs::read_to_string(path)
+            .map(|content| MemoryEncryption::is_legacy(&content))
+            .unwrap_or(false)
+    }))
+}
+
+fn encrypted_files(dir: &Path, suffix: &str) -> MemoryResult<Vec<PathBuf>> {
+    let entries = fs::read_dir(dir).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.to_string_lossy().ends_with(suffix))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn rekey_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(REKEY_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn remove_file_if_exists(path: &Path) -> MemoryResult<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(MemoryError::StorageError(e.to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// Chemin temporaire à côté de `dir` (même système de fichiers, pour `rename`)
+pub(super) fn sibling_path(dir: &Path, purpose: &str) -> PathBuf {
+    let name = dir.file_name().unwrap_or_default().to_string_lossy();
+    dir.with_file_name(format!(".{}.{}-{}", name, purpose, uuid::Uuid::new_v4()))
+}
+
+/// Remplace `dir` par le répertoire préparé `staging` ; en cas d'échec du
+/// second renommage, l'ancien contenu est remis en place
+pub(super) fn swap_directory(staging: &Path, dir: &Path) -> MemoryResult<()> {
+    if !dir.exists() {
+        if let Some(parent) = dir.parent() {
+            fs::create_dir_all(parent).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+        }
+        return fs::rename(staging, dir).map_err(|e| MemoryError::StorageError(e.to_string()));
+    }
+
+    let previous = sibling_path(dir, "old");
+    fs::rename(dir, &previous).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+    if let Err(e) = fs::rename(staging, dir) {
+        let _ = fs::rename(&previous, dir);
+        return Err(MemoryError::StorageError(e.to_string()));
+    }
+    if let Err(e) = fs::remove_dir_all(&previous) {
+        log::warn!("Ancien répertoire non supprimé {}: {}", previous.display(), e);
+    }
+    Ok(())
+}
+
+fn write_atomic(path: &Path, content: &str) -> MemoryResult<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content).map_err(|e| MemoryError::StorageError(e.to_string()))?;
+    fs::rename(&tmp, path).map_err(|e| MemoryError::StorageError(e.to_string()))
+}
+
+/// Même recherche plein texte que [`MemoryStorage::search_conversations`],
+/// sur des conversations déjà en mémoire (stockage non déverrouillé)
+pub fn search_loaded_conversations<'a>(
+    conversations: impl IntoIterator<Item = &'a Conversation>,
+    query: &str,
+    limit: usize,
+) -> Vec<ConversationSearchHit> {
+    let terms = search_terms(query);
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits = Vec::new();
+    for conversation in conversations {
+        push_text_hits(conversation, &terms, &mut hits);
+    }
+    rank_hits(hits, limit)
+}
+
+fn search_terms(query: &str) -> Vec<String> {
+    query.to_lowercase().split_whitespace().map(String::from).collect()
+}
+
+/// Messages contenant tous les termes ; score = occurrences / racine de la longueur
+fn push_text_hits(conversation: &Conversation, terms: &[String], hits: &mut Vec<ConversationSearchHit>) {
+    for entry in &conversation.entries {
+        let haystack = entry.content.to_lowercase();
+        if !terms.iter().all(|t| haystack.contains(t.as_str())) {
+            continue;
+        }
+
+        let occurrences: usize = terms.iter().map(|t| haystack.matches(t.as_str()).count()).sum();
+        let length = haystack.split_whitespace().count().max(1) as f32;
+
+        hits.push(ConversationSearchHit {
+            conversation_id: conversation.id.clone(),
+            conversation_title: conversation.title.clone(),
+            entry_id: entry.id.clone(),
+            snippet: make_snippet(&entry.content, &haystack, &terms[0]),
+            score: occurrences as f32 / length.sqrt(),
+            timestamp: entry.timestamp,
+        });
+    }
+}
+
+fn rank_hits(mut hits: Vec<ConversationSearchHit>, limit: usize) -> Vec<ConversationSearchHit> {
+    hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    hits.truncate(limit);
+    hits
+}
+
+/// Extrait autour de la première occurrence du terme
+fn make_snippet(original: &str, lowered: &str, term: &str) -> String {
+    let byte_pos = lowered.find(term).unwrap_or(0);
+    // Positions en caractères : la mise en minuscules peut changer la taille en octets
+    let char_pos = lowered[..byte_pos].chars().count();
+    let start = char_pos.saturating_sub(SNIPPET_RADIUS);
+    let total = original.chars().count();
+    let end = (char_pos + term.chars().count() + SNIPPET_RADIUS).min(total);
+
+    let mut snippet: String = original.chars().skip(start).take(end - start).collect();
+    if start > 0 {
+        snippet = format!("…{}", snippet);
+    }
+    if end < total {
+        snippet.push('…');
+    }
+    snippet
+}
+
 #[cfg(test)]
 mod tests {
+    use super::super::encryption::{encrypt_legacy, test_params};
     use super::*;
     use tempfile::TempDir;
 
+    fn open(dir: &Path, password: &str) -> MemoryResult<MemoryStorage> {
+        MemoryStorage::with_kdf_params(dir.to_path_buf(), password.to_string(), test_params())
+    }
+
+    fn write_legacy_conversation(dir: &Path, password: &str) -> Conversation {
+        let mut conv = Conversation::new("Ancien format".to_string());
+        conv.add_entry(super::super::MessageRole::User, "Clé SHA-256".to_string(), 3);
+        let json = serde_json::to_vec(&conv).unwrap();
+        fs::write(dir.join(format!("{}.json.enc", conv.id)), encrypt_legacy(password, &json)).unwrap();
+        conv
+    }
+
     #[test]
     fn test_storage_creation() {
         let temp_dir = TempDir::new().unwrap();
@@ -204,4 +788,198 @@ mod tests {
         assert_eq!(conv.id, loaded.id);
         assert_eq!(conv.entries.len(), loaded.entries.len());
     }
+
+    #[test]
+    fn test_message_vectors_follow_their_conversation() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut storage = open(temp_dir.path(), "test").unwrap();
+        let conv = Conversation::new("Vecteurs".to_string());
+        storage.save_conversation(&conv).unwrap();
+        assert!(storage.load_message_vectors(&conv.id).unwrap().is_empty());
+
+        for (i, entry_id) in ["m1", "m2"].iter().enumerate() {
+            let vector = MessageVector {
+                entry_id: entry_id.to_string(),
+                snippet: format!("Message {}", i),
+                vector: vec![i as f32, 1.0],
+            };
+            storage.append_message_vector(&conv.id, &vector).unwrap();
+        }
+        let vectors = storage.load_message_vectors(&conv.id).unwrap();
+        assert_eq!(vectors.len(), 2);
+        assert_eq!(vectors[1].entry_id, "m2");
+        assert_eq!(vectors[1].vector, vec![1.0, 1.0]);
+
+        storage.rekey("test").unwrap();
+        assert!(storage.load_message_vectors(&conv.id).unwrap().is_empty());
+
+        storage.append_message_vector(&conv.id, &vectors[0]).unwrap();
+        storage.delete_conversation(&conv.id).unwrap();
+        assert!(!storage.vectors_path(&conv.id).exists());
+    }
+
+    #[test]
+    fn test_search_and_delete_updates_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage =
+            MemoryStorage::new(temp_dir.path().to_path_buf(), "test".to_string()).unwrap();
+
+        let mut conv = Conversation::new("Facturation".to_string());
+        conv.add_entry(super::super::MessageRole::User, "Où est la facture F-2024-118 ?".to_string(), 6);
+        storage.save_conversation(&conv).unwrap();
+
+        let hits = storage.search_conversations("f-2024-118", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].conversation_id, conv.id);
+
+        storage.delete_conversation(&conv.id).unwrap();
+        assert!(storage.list_conversations().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_legacy_files_migrated_on_first_unlock() {
+        let temp_dir = TempDir::new().unwrap();
+        let conv = write_legacy_conversation(temp_dir.path(), "secret");
+
+        let storage = open(temp_dir.path(), "secret").unwrap();
+        assert_eq!(storage.load_conversation(&conv.id).unwrap().entries.len(), 1);
+        assert!(temp_dir.path().join(KEYRING_FILE).exists());
+        assert!(!has_legacy_files(temp_dir.path()).unwrap());
+        drop(storage);
+
+        let reopened = open(temp_dir.path(), "secret").unwrap();
+        assert_eq!(reopened.load_conversation(&conv.id).unwrap().id, conv.id);
+    }
+
+    #[test]
+    fn test_wrong_password_leaves_legacy_files_untouched() {
+        let temp_dir = TempDir::new().unwrap();
+        let conv = write_legacy_conversation(temp_dir.path(), "secret");
+
+        assert!(open(temp_dir.path(), "erreur").is_err());
+        assert!(!temp_dir.path().join(KEYRING_FILE).exists());
+        assert!(encrypted_files(temp_dir.path(), REKEY_SUFFIX).unwrap().is_empty());
+
+        let storage = open(temp_dir.path(), "secret").unwrap();
+        assert!(storage.load_conversation(&conv.id).is_ok());
+    }
+
+    #[test]
+    fn test_change_password_and_rekey() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut storage = open(temp_dir.path(), "ancien").unwrap();
+        let conv = Conversation::new("Rotation".to_string());
+        storage.save_conversation(&conv).unwrap();
+
+        let before = fs::read_to_string(temp_dir.path().join(format!("{}.json.enc", conv.id))).unwrap();
+        storage.change_password("ancien", "nouveau", test_params()).unwrap();
+        let after = fs::read_to_string(temp_dir.path().join(format!("{}.json.enc", conv.id))).unwrap();
+        assert_eq!(before, after);
+        assert!(storage.change_password("ancien", "autre", test_params()).is_err());
+
+        assert!(open(temp_dir.path(), "ancien").is_err());
+        let mut storage = open(temp_dir.path(), "nouveau").unwrap();
+
+        assert_eq!(storage.rekey("nouveau").unwrap(), 1);
+        assert_eq!(storage.list_conversations().unwrap().len(), 1);
+        let rekeyed = fs::read_to_string(temp_dir.path().join(format!("{}.json.enc", conv.id))).unwrap();
+        assert_ne!(after, rekeyed);
+        assert!(storage.load_conversation(&conv.id).is_ok());
+        assert!(open(temp_dir.path(), "nouveau").unwrap().load_conversation(&conv.id).is_ok());
+    }
+
+    #[test]
+    fn test_index_is_append_only_and_compacts() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = open(temp_dir.path(), "test").unwrap();
+        let log_lines = || fs::read_to_string(temp_dir.path().join(INDEX_LOG_FILE)).unwrap().lines().count();
+
+        let mut conv = Conversation::new("Journal".to_string());
+        storage.save_conversation(&conv).unwrap();
+        conv.add_entry(super::super::MessageRole::User, "Suite".to_string(), 2);
+        storage.save_conversation(&conv).unwrap();
+        let other = Conversation::new("Autre".to_string());
+        storage.save_conversation(&other).unwrap();
+        storage.delete_conversation(&other.id).unwrap();
+        assert_eq!(log_lines(), 4);
+
+        let index = storage.load_index().unwrap();
+        assert_eq!(index.total_conversations, 1);
+        assert_eq!(index.total_messages, 1);
+
+        storage.compact_index().unwrap();
+        assert_eq!(log_lines(), 1);
+        assert_eq!(storage.list_conversations().unwrap()[0].id, conv.id);
+    }
+
+    #[test]
+    fn test_lost_index_is_rebuilt_and_corruption_surfaces() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = open(temp_dir.path(), "test").unwrap();
+        let conv = Conversation::new("Reconstruite".to_string());
+        storage.save_conversation(&conv).unwrap();
+        storage.save_conversation(&Conversation::new("Seconde".to_string())).unwrap();
+        drop(storage);
+
+        fs::remove_file(temp_dir.path().join(INDEX_LOG_FILE)).unwrap();
+        let storage = open(temp_dir.path(), "test").unwrap();
+        assert_eq!(storage.list_conversations().unwrap().len(), 2);
+
+        let log_path = temp_dir.path().join(INDEX_LOG_FILE);
+        let content = fs::read_to_string(&log_path).unwrap();
+        fs::write(&log_path, format!("bruit\n{}", content)).unwrap();
+        assert!(storage.list_conversations().is_err());
+        assert!(open(temp_dir.path(), "test").is_err());
+
+        // Une dernière ligne tronquée (écriture interrompue) est tolérée puis réparée
+        fs::write(&log_path, format!("{}{}", content, &content[..20])).unwrap();
+        let storage = open(temp_dir.path(), "test").unwrap();
+        assert_eq!(storage.list_conversations().unwrap().len(), 2);
+        storage.save_conversation(&conv).unwrap();
+        assert_eq!(storage.list_conversations().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_rebuild_skips_unreadable_conversations() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = open(temp_dir.path(), "test").unwrap();
+        let conv = Conversation::new("Lisible".to_string());
+        storage.save_conversation(&conv).unwrap();
+        drop(storage);
+
+        fs::write(temp_dir.path().join(format!("illisible{}", ENCRYPTED_SUFFIX)), "bruit").unwrap();
+        fs::remove_file(temp_dir.path().join(INDEX_LOG_FILE)).unwrap();
+
+        let storage = open(temp_dir.path(), "test").unwrap();
+        let summaries = storage.list_conversations().unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].id, conv.id);
+
+        let report = storage.rebuild_index().unwrap();
+        assert_eq!(report.indexed, 1);
+        assert_eq!(report.skipped, vec![format!("illisible{}", ENCRYPTED_SUFFIX)]);
+    }
+
+    #[test]
+    fn test_concurrent_saves_survive_compaction() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = open(temp_dir.path(), "test").unwrap();
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for i in 0..20 {
+                        storage.save_conversation(&Conversation::new(format!("Conversation {}", i))).unwrap();
+                    }
+                });
+            }
+            for _ in 0..20 {
+                storage.compact_index().unwrap();
+            }
+        });
+
+        assert_eq!(storage.list_conversations().unwrap().len(), 80);
+        drop(storage);
+        assert_eq!(open(temp_dir.path(), "test").unwrap().list_conversations().unwrap().len(), 80);
+    }
 }
@@ -9,6 +9,8 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tauri::State;
 
+use super::secrets_vault::SecretsVaultState;
+
 // ─────────────────────────────────────────────────────────────────────────────
 // STRUCTURES
 // ─────────────────────────────────────────────────────────────────────────────
@@ -17,7 +19,9 @@ use tauri::State;
 pub struct ApiConfig {
     pub name: String,
     pub base_url: String,
-    pub api_key: Option<String>,
+    /// Nom du secret contenant la clé dans le coffre (jamais la clé elle-même)
+    #[serde(default)]
+    pub secret_name: Option<String>,
     pub headers: HashMap<String, String>,
     pub timeout_ms: u64,
     pub enabled: bool,
@@ -56,17 +60,19 @@ pub struct ApiBridgeState {
     configs: Arc<Mutex<HashMap<String, ApiConfig>>>,
     stats: Arc<Mutex<HashMap<String, ApiStats>>>,
     cache: Arc<Mutex<HashMap<String, (String, u64)>>>, // (response, timestamp)
+    vault: SecretsVaultState,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
 // INITIALISATION
 // ─────────────────────────────────────────────────────────────────────────────
 
-pub fn init() -> ApiBridgeState {
+pub fn init(vault: SecretsVaultState) -> ApiBridgeState {
     let state = ApiBridgeState {
         configs: Arc::new(Mutex::new(HashMap::new())),
         stats: Arc::new(Mutex::new(HashMap::new())),
         cache: Arc::new(Mutex::new(HashMap::new())),
+        vault,
     };
 
     // Configurer APIs par défaut
@@ -84,7 +90,7 @@ fn initialize_default_apis(state: &ApiBridgeState) {
         ApiConfig {
             name: "gemini".to_string(),
             base_url: "https://generativelanguage.googleapis.com/v1beta".to_string(),
-            api_key: None, // À configurer
+            secret_name: Some(default_secret_name("gemini")), // À renseigner dans le coffre
             headers: HashMap::new(),
             timeout_ms: 30000,
             enabled: false,
@@ -97,7 +103,7 @@ fn initialize_default_apis(state: &ApiBridgeState) {
         ApiConfig {
             name: "ollama".to_string(),
             base_url: "http://localhost:11434".to_string(),
-            api_key: None,
+            secret_name: None,
             headers: HashMap::new(),
             timeout_ms: 60000,
             enabled: true,
@@ -110,7 +116,7 @@ fn initialize_default_apis(state: &ApiBridgeState) {
         ApiConfig {
             name: "github".to_string(),
             base_url: "https://api.github.com".to_string(),
-            api_key: None,
+            secret_name: None,
             headers: {
                 let mut h = HashMap::new();
                 h.insert("Accept".to_string(), "application/vnd.github+json".to_string());
@@ -167,8 +173,18 @@ pub async fn api_request(
         }
     }
 
+    // Résoudre la clé depuis le coffre (accès journalisé)
+    let api_key = match &config.secret_name {
+        Some(secret) => Some(
+            state
+                .vault
+                .get_secret(secret, &format!("api_bridge:{}", config.name))?,
+        ),
+        None => None,
+    };
+
     // Effectuer requête
-    let response = execute_http_request(&url, &request, &config).await?;
+    let response = execute_http_request(&url, &request, &config, api_key.as_deref()).await?;
 
     let latency_ms = start.elapsed().as_millis() as u64;
 
@@ -190,6 +206,7 @@ async fn execute_http_request(
     url: &str,
     request: &ApiRequest,
     config: &ApiConfig,
+    api_key: Option<&str>,
 ) -> Result<ApiResponse, String> {
     // TODO: Implémenter reqwest HTTP client
     // let client = reqwest::Client::new();
@@ -202,7 +219,7 @@ async fn execute_http_request(
     // };
     //
     // // Ajouter headers
-    // if let Some(api_key) = &config.api_key {
+    // if let Some(api_key) = api_key {
     //     req = req.header("Authorization", format!("Bearer {}", api_key));
     // }
     // for (k, v) in &config.headers {
@@ -228,7 +245,11 @@ async fn execute_http_request(
     // let body = response.text().await.map_err(|e| e.to_string())?;
 
     // Simulation pour l'instant
-    println!("[API_BRIDGE] Requête simulée vers: {}", url);
+    println!(
+        "[API_BRIDGE] Requête simulée vers: {} ({})",
+        url,
+        if api_key.is_some() { "authentifiée" } else { "anonyme" }
+    );
 
     Ok(ApiResponse {
         status: 200,
@@ -261,6 +282,7 @@ pub fn api_update_config(
     Ok(name)
 }
 
+/// Enregistre la clé dans le coffre (déverrouillé) et la référence par nom
 #[tauri::command]
 pub fn api_set_key(
     api_name: String,
@@ -269,7 +291,12 @@ pub fn api_set_key(
 ) -> Result<String, String> {
     let mut configs = state.configs.lock().unwrap();
     if let Some(config) = configs.get_mut(&api_name) {
-        config.api_key = Some(api_key);
+        let secret_name = config
+            .secret_name
+            .clone()
+            .unwrap_or_else(|| default_secret_name(&api_name));
+        state.vault.set_secret(&secret_name, &api_key, "frontend")?;
+        config.secret_name = Some(secret_name);
         config.enabled = true;
         println!("[API_BRIDGE] API key configurée: {}", api_name);
         Ok("API key configurée".to_string())
@@ -478,6 +505,11 @@ pub async fn api_test_connection(
 // UTILITAIRES
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Nom de secret par défaut d'une API : `<api>_api_key`
+pub fn default_secret_name(api_name: &str) -> String {
+    format!("{}_api_key", api_name.to_lowercase())
+}
+
 fn get_timestamp() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
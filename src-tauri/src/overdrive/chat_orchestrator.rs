@@ -5,10 +5,28 @@
 // ═══════════════════════════════════════════════════════════════════════════
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tauri::State;
 
+use crate::memory::model::{Conversation, MessagePage};
+use crate::memory::model::ConversationSummary;
+use crate::memory::storage::{search_loaded_conversations, ConversationSearchHit, MemoryStorage, MessageVector};
+use crate::memory::{MemoryEntry, MessageRole};
+use crate::semantic::embedder::Embedder;
+
+use super::api_bridge::default_secret_name;
+use super::secrets_vault::SecretsVaultState;
+
+const DEFAULT_CONVERSATION_TITLE: &str = "Nouvelle conversation";
+const DEFAULT_PAGE_SIZE: usize = 50;
+/// Longueur des extraits de la recherche sémantique (caractères)
+const SEMANTIC_SNIPPET_CHARS: usize = 160;
+/// Variable d'environnement fournissant le mot de passe du stockage chiffré
+const MEMORY_PASSWORD_ENV: &str = "TITANE_MEMORY_PASSWORD";
+
 // ─────────────────────────────────────────────────────────────────────────────
 // STRUCTURES
 // ─────────────────────────────────────────────────────────────────────────────
@@ -60,24 +78,248 @@ pub struct ConversationMemory {
     pub context_tokens: u32,
     pub created_at: u64,
     pub last_updated: u64,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub parent_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessagePage {
+    pub conversation_id: String,
+    pub messages: Vec<ChatMessage>,
+    pub offset: usize,
+    pub total: usize,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationSearchResult {
+    pub conversation_id: String,
+    pub conversation_title: String,
+    pub message_id: String,
+    pub snippet: String,
+    pub score: f32,
+    pub match_type: String,     // text|semantic
 }
 
 pub struct ChatOrchestratorState {
-    conversations: Arc<RwLock<Vec<ConversationMemory>>>,
+    /// Cache des conversations chargées (chargement paresseux depuis `storage`) ;
+    /// seule copie des conversations tant que le stockage est verrouillé
+    conversations: Arc<RwLock<HashMap<String, Conversation>>>,
+    /// `None` tant qu'aucun mot de passe n'a déverrouillé le stockage
+    storage: Arc<RwLock<Option<Arc<MemoryStorage>>>>,
+    storage_dir: PathBuf,
+    /// Sérialise les lectures-modifications-écritures d'une conversation
+    write_lock: Arc<Mutex<()>>,
+    embedder: Arc<Embedder>,
+    /// Vecteurs des messages (id conversation → vecteurs) tant que le stockage
+    /// est verrouillé ; ensuite ils sont écrits à côté des conversations
+    embeddings: Arc<RwLock<HashMap<String, Vec<MessageVector>>>>,
     provider_status: Arc<RwLock<Vec<ProviderStatus>>>,
-    gemini_api_key: Arc<RwLock<Option<String>>>,
+    /// Coffre contenant la clé Gemini (secret `gemini_api_key`)
+    vault: SecretsVaultState,
     default_provider: Arc<RwLock<String>>,
 }
 
+impl ChatOrchestratorState {
+    /// Clé Gemini lue dans le coffre (None si verrouillé ou absente)
+    pub(crate) async fn gemini_api_key(&self) -> Option<String> {
+        self.vault.get_secret(&default_secret_name("gemini"), "chat_orchestrator").ok()
+    }
+
+    /// Ajoute un message à une conversation existante
+    pub(crate) async fn append_message(&self, conversation_id: &str, message: &ChatMessage) {
+        store_message(self, conversation_id, message).await;
+    }
+
+    /// Stockage chiffré, `None` s'il n'est pas déverrouillé
+    async fn storage(&self) -> Option<Arc<MemoryStorage>> {
+        self.storage.read().await.clone()
+    }
+
+    /// Déverrouille le stockage chiffré ; les conversations tenues en mémoire
+    /// jusque-là y sont écrites
+    pub(crate) async fn unlock_storage(&self, password: &str) -> Result<usize, String> {
+        let storage = Arc::new(
+            MemoryStorage::new(self.storage_dir.clone(), password.to_string()).map_err(|e| e.to_string())?,
+        );
+
+        let _guard = self.write_lock.lock().await;
+        let pending: Vec<Conversation> = self.conversations.read().await.values().cloned().collect();
+        for conversation in &pending {
+            storage.save_conversation(conversation).map_err(|e| e.to_string())?;
+        }
+        let mut embeddings = self.embeddings.write().await;
+        for (conversation_id, vectors) in embeddings.iter() {
+            for vector in vectors {
+                storage.append_message_vector(conversation_id, vector).map_err(|e| e.to_string())?;
+            }
+        }
+        embeddings.clear();
+        *self.storage.write().await = Some(storage);
+        Ok(pending.len())
+    }
+
+    /// Conversation depuis le cache, ou chargée et déchiffrée depuis le disque
+    async fn load(&self, conversation_id: &str) -> Result<Conversation, String> {
+        if let Some(conv) = self.conversations.read().await.get(conversation_id) {
+            return Ok(conv.clone());
+        }
+
+        let storage = self
+            .storage()
+            .await
+            .ok_or_else(|| format!("Conversation introuvable: {}", conversation_id))?;
+        let conv = storage
+            .load_conversation(conversation_id)
+            .map_err(|e| format!("Conversation introuvable: {}", e))?;
+        self.conversations
+            .write()
+            .await
+            .insert(conversation_id.to_string(), conv.clone());
+        Ok(conv)
+    }
+
+    /// Écrit la conversation sur disque si le stockage est déverrouillé,
+    /// sinon la garde seulement en mémoire
+    async fn persist(&self, conversation: Conversation) -> Result<(), String> {
+        if let Some(storage) = self.storage().await {
+            storage.save_conversation(&conversation).map_err(|e| e.to_string())?;
+        }
+        self.conversations
+            .write()
+            .await
+            .insert(conversation.id.clone(), conversation);
+        Ok(())
+    }
+
+    /// Résumés depuis l'index chiffré, ou depuis la mémoire sans stockage
+    async fn list_summaries(&self) -> Result<Vec<ConversationSummary>, String> {
+        let mut summaries = match self.storage().await {
+            Some(storage) => storage.list_conversations().map_err(|e| e.to_string())?,
+            None => self.conversations.read().await.values().map(ConversationSummary::from).collect(),
+        };
+        summaries.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+        Ok(summaries)
+    }
+
+    /// Enregistre le vecteur d'un message : sur disque, ou en mémoire sans stockage
+    async fn record_vector(&self, conversation_id: &str, vector: MessageVector) -> Result<(), String> {
+        match self.storage().await {
+            Some(storage) => storage
+                .append_message_vector(conversation_id, &vector)
+                .map_err(|e| e.to_string()),
+            None => {
+                self.embeddings
+                    .write()
+                    .await
+                    .entry(conversation_id.to_string())
+                    .or_default()
+                    .push(vector);
+                Ok(())
+            }
+        }
+    }
+
+    /// Vecteurs d'une conversation ; les messages sans vecteur (écrits avant
+    /// leur vectorisation, branche, rotation de clé) sont vectorisés une fois.
+    /// La conversation n'est lue que dans ce cas, et n'entre pas dans le cache.
+    async fn message_vectors(&self, summary: &ConversationSummary) -> Result<Vec<MessageVector>, String> {
+        let storage = self.storage().await;
+        let mut vectors = match &storage {
+            Some(storage) => storage.load_message_vectors(&summary.id).map_err(|e| e.to_string())?,
+            None => self.embeddings.read().await.get(&summary.id).cloned().unwrap_or_default(),
+        };
+        if vectors.len() >= summary.message_count {
+            return Ok(vectors);
+        }
+
+        let cached = self.conversations.read().await.get(&summary.id).cloned();
+        let conversation = match (cached, &storage) {
+            (Some(conversation), _) => conversation,
+            (None, Some(storage)) => storage.load_conversation(&summary.id).map_err(|e| e.to_string())?,
+            (None, None) => return Ok(vectors),
+        };
+        for entry in &conversation.entries {
+            if vectors.iter().any(|v| v.entry_id == entry.id) {
+                continue;
+            }
+            // Un message non vectorisable est ignoré, la recherche continue
+            match self.embed_entry(entry).await {
+                Ok(vector) => {
+                    self.record_vector(&summary.id, vector.clone()).await?;
+                    vectors.push(vector);
+                }
+                Err(e) => println!("[CHAT] Message {} ignoré: {}", entry.id, e),
+            }
+        }
+        Ok(vectors)
+    }
+
+    async fn embed_entry(&self, entry: &MemoryEntry) -> Result<MessageVector, String> {
+        let vector = self.embedder.embed(&entry.content).await.map_err(|e| e.to_string())?;
+        Ok(MessageVector {
+            entry_id: entry.id.clone(),
+            snippet: entry.content.chars().take(SEMANTIC_SNIPPET_CHARS).collect(),
+            vector,
+        })
+    }
+
+    async fn search_text(&self, query: &str, limit: usize) -> Result<Vec<ConversationSearchHit>, String> {
+        match self.storage().await {
+            Some(storage) => storage.search_conversations(query, limit).map_err(|e| e.to_string()),
+            None => Ok(search_loaded_conversations(self.conversations.read().await.values(), query, limit)),
+        }
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // INITIALISATION
 // ─────────────────────────────────────────────────────────────────────────────
 
-pub fn init() -> ChatOrchestratorState {
+pub fn init(vault: SecretsVaultState) -> ChatOrchestratorState {
+    let storage_dir = dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("titane")
+        .join("conversations");
+
+    // Pas de mot de passe par défaut : sans clé, les conversations restent en
+    // mémoire jusqu'à `chat_unlock_storage`
+    let storage = match std::env::var(MEMORY_PASSWORD_ENV) {
+        Ok(password) if !password.is_empty() => match MemoryStorage::new(storage_dir.clone(), password) {
+            Ok(storage) => Some(storage),
+            Err(e) => {
+                println!("[CHAT] Stockage des conversations indisponible: {}", e);
+                None
+            }
+        },
+        _ => {
+            println!(
+                "[CHAT] {} non défini : conversations en mémoire jusqu'au déverrouillage",
+                MEMORY_PASSWORD_ENV
+            );
+            None
+        }
+    };
+
+    build_state(storage, storage_dir, vault)
+}
+
+pub fn init_with_storage(storage: MemoryStorage, storage_dir: PathBuf, vault: SecretsVaultState) -> ChatOrchestratorState {
+    build_state(Some(storage), storage_dir, vault)
+}
+
+fn build_state(storage: Option<MemoryStorage>, storage_dir: PathBuf, vault: SecretsVaultState) -> ChatOrchestratorState {
     let state = ChatOrchestratorState {
-        conversations: Arc::new(RwLock::new(Vec::new())),
+        conversations: Arc::new(RwLock::new(HashMap::new())),
+        storage: Arc::new(RwLock::new(storage.map(Arc::new))),
+        storage_dir,
+        write_lock: Arc::new(Mutex::new(())),
+        embedder: Arc::new(Embedder::default()),
+        embeddings: Arc::new(RwLock::new(HashMap::new())),
         provider_status: Arc::new(RwLock::new(Vec::new())),
-        gemini_api_key: Arc::new(RwLock::new(None)),
+        vault,
         default_provider: Arc::new(RwLock::new("auto".to_string())),
     };
 
@@ -132,6 +374,21 @@ pub async fn chat_send_message(
 ) -> Result<ChatResponse, String> {
     let start = std::time::Instant::now();
 
+    // Stocker le message utilisateur avant l'appel
+    if let Some(conv_id) = &request.conversation_id {
+        let user_message = ChatMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            role: "user".to_string(),
+            content: request.message.clone(),
+            timestamp: get_timestamp(),
+            provider: request.provider.clone(),
+            model: request.model.clone().unwrap_or_default(),
+            tokens: Some(request.message.split_whitespace().count() as u32),
+            multimodal: request.images.is_some(),
+        };
+        store_message(&state, conv_id, &user_message).await;
+    }
+
     // Liste des providers à essayer (ordre de priorité)
     let providers_to_try: Vec<String> = if request.provider == "auto" {
         vec!["gemini".to_string(), "ollama".to_string(), "local".to_string()]
@@ -212,9 +469,8 @@ async fn send_to_gemini(
     request: &ChatRequest,
     state: &ChatOrchestratorState,
 ) -> Result<ChatMessage, String> {
-    let api_key = state.gemini_api_key.read().await;
-    if api_key.is_none() {
-        return Err("Gemini API key non configurée".to_string());
+    if state.gemini_api_key().await.is_none() {
+        return Err("Gemini API key non configurée (coffre verrouillé ou secret absent)".to_string());
     }
 
     // TODO: Implémenter appel API Gemini
@@ -278,23 +534,27 @@ async fn send_to_local(
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
-// GESTION CONVERSATIONS
+// GESTION CONVERSATIONS (persistance chiffrée via memory::storage)
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Déverrouille le stockage chiffré des conversations (alternative à
+/// TITANE_MEMORY_PASSWORD) ; renvoie le nombre de conversations écrites
+#[tauri::command]
+pub async fn chat_unlock_storage(
+    password: String,
+    state: State<'_, ChatOrchestratorState>,
+) -> Result<usize, String> {
+    let written = state.unlock_storage(&password).await?;
+    println!("[CHAT] Stockage déverrouillé ({} conversations écrites)", written);
+    Ok(written)
+}
+
 #[tauri::command]
 pub async fn chat_create_conversation(state: State<'_, ChatOrchestratorState>) -> Result<String, String> {
-    let conversation_id = uuid::Uuid::new_v4().to_string();
-
-    let conversation = ConversationMemory {
-        conversation_id: conversation_id.clone(),
-        messages: Vec::new(),
-        context_tokens: 0,
-        created_at: get_timestamp(),
-        last_updated: get_timestamp(),
-    };
+    let conversation = Conversation::new(DEFAULT_CONVERSATION_TITLE.to_string());
+    let conversation_id = conversation.id.clone();
 
-    let mut conversations = state.conversations.write().await;
-    conversations.push(conversation);
+    state.persist(conversation).await?;
 
     println!("[CHAT] Conversation créée: {}", conversation_id);
     Ok(conversation_id)
@@ -305,12 +565,28 @@ pub async fn chat_get_conversation(
     conversation_id: String,
     state: State<'_, ChatOrchestratorState>,
 ) -> Result<ConversationMemory, String> {
-    let conversations = state.conversations.read().await;
-    conversations
-        .iter()
-        .find(|c| c.conversation_id == conversation_id)
-        .cloned()
-        .ok_or_else(|| "Conversation introuvable".to_string())
+    let conversation = state.load(&conversation_id).await?;
+    Ok(to_conversation_memory(&conversation))
+}
+
+#[tauri::command]
+pub async fn chat_list_conversations(
+    state: State<'_, ChatOrchestratorState>,
+) -> Result<Vec<crate::memory::model::ConversationSummary>, String> {
+    state.list_summaries().await
+}
+
+/// Messages paginés : `offset` depuis le début, `limit` par défaut 50
+#[tauri::command]
+pub async fn chat_get_messages(
+    conversation_id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    state: State<'_, ChatOrchestratorState>,
+) -> Result<ChatMessagePage, String> {
+    let conversation = state.load(&conversation_id).await?;
+    let page = conversation.page(offset.unwrap_or(0), limit.unwrap_or(DEFAULT_PAGE_SIZE));
+    Ok(to_chat_page(page))
 }
 
 #[tauri::command]
@@ -318,20 +594,226 @@ pub async fn chat_delete_conversation(
     conversation_id: String,
     state: State<'_, ChatOrchestratorState>,
 ) -> Result<String, String> {
-    let mut conversations = state.conversations.write().await;
-    conversations.retain(|c| c.conversation_id != conversation_id);
+    let cached = state.conversations.write().await.remove(&conversation_id);
+    state.embeddings.write().await.remove(&conversation_id);
+    match state.storage().await {
+        Some(storage) => storage.delete_conversation(&conversation_id).map_err(|e| e.to_string())?,
+        None if cached.is_none() => return Err(format!("Conversation introuvable: {}", conversation_id)),
+        None => {}
+    }
     Ok("Conversation supprimée".to_string())
 }
 
+#[tauri::command]
+pub async fn chat_rename_conversation(
+    conversation_id: String,
+    title: String,
+    state: State<'_, ChatOrchestratorState>,
+) -> Result<String, String> {
+    let _guard = state.write_lock.lock().await;
+    let mut conversation = state.load(&conversation_id).await?;
+    conversation.title = title;
+    conversation.metadata.title_locked = true;
+    state.persist(conversation).await?;
+    Ok("Conversation renommée".to_string())
+}
+
+/// Édite un message antérieur en créant une branche de la conversation
+#[tauri::command]
+pub async fn chat_fork_conversation(
+    conversation_id: String,
+    message_id: String,
+    new_content: String,
+    state: State<'_, ChatOrchestratorState>,
+) -> Result<ConversationMemory, String> {
+    let conversation = state.load(&conversation_id).await?;
+    let branch = conversation
+        .fork_at(&message_id, new_content)
+        .ok_or_else(|| "Message introuvable dans la conversation".to_string())?;
+
+    println!("[CHAT] Branche {} créée depuis {}", branch.id, conversation_id);
+    let memory = to_conversation_memory(&branch);
+    state.persist(branch).await?;
+    Ok(memory)
+}
+
+/// Recherche dans toutes les conversations. `mode` : text | semantic | hybrid (défaut)
+#[tauri::command]
+pub async fn chat_search_conversations(
+    query: String,
+    mode: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, ChatOrchestratorState>,
+) -> Result<Vec<ConversationSearchResult>, String> {
+    let limit = limit.unwrap_or(20);
+    let mode = mode.unwrap_or_else(|| "hybrid".to_string());
+    let mut results: Vec<ConversationSearchResult> = Vec::new();
+
+    if mode != "semantic" {
+        let hits = state.search_text(&query, limit).await?;
+        results.extend(hits.into_iter().map(text_hit_to_result));
+    }
+
+    if mode != "text" {
+        for hit in semantic_search(&state, &query, limit).await? {
+            if !results.iter().any(|r| r.message_id == hit.message_id) {
+                results.push(hit);
+            }
+        }
+    }
+
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    results.truncate(limit);
+    Ok(results)
+}
+
+/// Similarité cosinus sur les vecteurs enregistrés, conversation par conversation
+async fn semantic_search(
+    state: &ChatOrchestratorState,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<ConversationSearchResult>, String> {
+    let query_embedding = state.embedder.embed(query).await.map_err(|e| e.to_string())?;
+    let summaries = state.list_summaries().await?;
+    let mut results = Vec::new();
+
+    for summary in summaries {
+        let vectors = match state.message_vectors(&summary).await {
+            Ok(vectors) => vectors,
+            Err(e) => {
+                println!("[CHAT] Conversation {} ignorée: {}", summary.id, e);
+                continue;
+            }
+        };
+
+        for vector in vectors {
+            let score = state.embedder.cosine_similarity(&query_embedding, &vector.vector);
+            results.push(ConversationSearchResult {
+                conversation_id: summary.id.clone(),
+                conversation_title: summary.title.clone(),
+                message_id: vector.entry_id,
+                snippet: vector.snippet,
+                score,
+                match_type: "semantic".to_string(),
+            });
+        }
+        // Seuls les meilleurs résultats sont gardés d'une conversation à l'autre
+        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        results.truncate(limit);
+    }
+
+    Ok(results)
+}
+
+fn text_hit_to_result(hit: ConversationSearchHit) -> ConversationSearchResult {
+    ConversationSearchResult {
+        conversation_id: hit.conversation_id,
+        conversation_title: hit.conversation_title,
+        message_id: hit.entry_id,
+        snippet: hit.snippet,
+        // Bonus pour les correspondances exactes par rapport au score cosinus
+        score: 1.0 + hit.score,
+        match_type: "text".to_string(),
+    }
+}
+
 async fn store_message(state: &ChatOrchestratorState, conversation_id: &str, message: &ChatMessage) {
-    let mut conversations = state.conversations.write().await;
-    if let Some(conv) = conversations
-        .iter_mut()
-        .find(|c| c.conversation_id == conversation_id)
-    {
-        conv.messages.push(message.clone());
-        conv.last_updated = get_timestamp();
-        conv.context_tokens += message.tokens.unwrap_or(0);
+    let guard = state.write_lock.lock().await;
+    let mut conversation = match state.load(conversation_id).await {
+        Ok(c) => c,
+        Err(e) => {
+            println!("[CHAT] Message non stocké: {}", e);
+            return;
+        }
+    };
+
+    let entry = to_memory_entry(message);
+    conversation.entries.push(entry.clone());
+    conversation.metadata.total_tokens += message.tokens.unwrap_or(0) as usize;
+    conversation.metadata.message_count += 1;
+    conversation.updated_at = chrono::Utc::now().timestamp();
+
+    if conversation.auto_title() {
+        println!("[CHAT] Titre généré: {}", conversation.title);
+    }
+
+    if let Err(e) = state.persist(conversation).await {
+        println!("[CHAT] Échec persistance {}: {}", conversation_id, e);
+        return;
+    }
+    drop(guard);
+
+    // Vectorisé dès l'écriture : la recherche sémantique lit les vecteurs enregistrés
+    let recorded = match state.embed_entry(&entry).await {
+        Ok(vector) => state.record_vector(conversation_id, vector).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = recorded {
+        println!("[CHAT] Embedding différé pour {}: {}", entry.id, e);
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// CONVERSIONS ChatMessage ⇄ MemoryEntry
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn to_memory_entry(message: &ChatMessage) -> MemoryEntry {
+    let role = match message.role.as_str() {
+        "user" => MessageRole::User,
+        "system" => MessageRole::System,
+        _ => MessageRole::Assistant,
+    };
+
+    MemoryEntry {
+        id: message.id.clone(),
+        role,
+        content: message.content.clone(),
+        timestamp: message.timestamp as i64,
+        tokens: message.tokens.unwrap_or(0) as usize,
+        metadata: Some(serde_json::json!({
+            "provider": message.provider,
+            "model": message.model,
+            "multimodal": message.multimodal,
+            "tokens_known": message.tokens.is_some(),
+        })),
+    }
+}
+
+fn to_chat_message(entry: &MemoryEntry) -> ChatMessage {
+    let meta = entry.metadata.clone().unwrap_or_default();
+    let tokens_known = meta["tokens_known"].as_bool().unwrap_or(entry.tokens > 0);
+
+    ChatMessage {
+        id: entry.id.clone(),
+        role: entry.role.to_string(),
+        content: entry.content.clone(),
+        timestamp: entry.timestamp.max(0) as u64,
+        provider: meta["provider"].as_str().unwrap_or("local").to_string(),
+        model: meta["model"].as_str().unwrap_or_default().to_string(),
+        tokens: if tokens_known { Some(entry.tokens as u32) } else { None },
+        multimodal: meta["multimodal"].as_bool().unwrap_or(false),
+    }
+}
+
+fn to_conversation_memory(conversation: &Conversation) -> ConversationMemory {
+    ConversationMemory {
+        conversation_id: conversation.id.clone(),
+        messages: conversation.entries.iter().map(to_chat_message).collect(),
+        context_tokens: conversation.metadata.total_tokens as u32,
+        created_at: conversation.created_at.max(0) as u64,
+        last_updated: conversation.updated_at.max(0) as u64,
+        title: conversation.title.clone(),
+        parent_id: conversation.metadata.parent_id.clone(),
+    }
+}
+
+fn to_chat_page(page: MessagePage) -> ChatMessagePage {
+    ChatMessagePage {
+        conversation_id: page.conversation_id,
+        messages: page.entries.iter().map(to_chat_message).collect(),
+        offset: page.offset,
+        total: page.total,
+        has_more: page.has_more,
     }
 }
 
@@ -344,8 +826,9 @@ pub async fn chat_set_gemini_key(
     api_key: String,
     state: State<'_, ChatOrchestratorState>,
 ) -> Result<String, String> {
-    let mut key = state.gemini_api_key.write().await;
-    *key = Some(api_key);
+    state
+        .vault
+        .set_secret(&default_secret_name("gemini"), &api_key, "frontend")?;
 
     // Vérifier disponibilité
     update_provider_status(&state, "gemini", true, 0, None).await;
@@ -413,3 +896,97 @@ fn get_timestamp() -> u64 {
         .unwrap()
         .as_secs()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn chat_message(content: &str) -> ChatMessage {
+        ChatMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            role: "user".to_string(),
+            content: content.to_string(),
+            timestamp: get_timestamp(),
+            provider: "local".to_string(),
+            model: "echo".to_string(),
+            tokens: Some(3),
+            multimodal: false,
+        }
+    }
+
+    #[test]
+    fn locked_storage_keeps_conversations_in_memory_until_unlock() {
+        let storage_dir = TempDir::new().unwrap();
+        let vault_dir = TempDir::new().unwrap();
+        let state = build_state(
+            None,
+            storage_dir.path().to_path_buf(),
+            super::super::secrets_vault::init_with_dir(vault_dir.path().to_path_buf()),
+        );
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let conversation = Conversation::new(DEFAULT_CONVERSATION_TITLE.to_string());
+            let id = conversation.id.clone();
+            state.persist(conversation).await.unwrap();
+            store_message(&state, &id, &chat_message("Où en est le devis Durand ?")).await;
+
+            let summaries = state.list_summaries().await.unwrap();
+            assert_eq!(summaries.len(), 1);
+            assert_eq!(summaries[0].message_count, 1);
+            let hits = state.search_text("durand", 10).await.unwrap();
+            assert_eq!(hits.len(), 1);
+            assert_eq!(hits[0].conversation_id, id);
+
+            assert_eq!(state.unlock_storage("secret").await.unwrap(), 1);
+            store_message(&state, &id, &chat_message("Relance envoyée")).await;
+
+            let reopened = MemoryStorage::new(storage_dir.path().to_path_buf(), "secret".to_string()).unwrap();
+            assert_eq!(reopened.load_conversation(&id).unwrap().entries.len(), 2);
+            assert!(state.unlock_storage("erreur").await.is_err());
+        });
+    }
+
+    #[test]
+    fn semantic_search_reads_stored_vectors_without_caching_conversations() {
+        let storage_dir = TempDir::new().unwrap();
+        let vault_dir = TempDir::new().unwrap();
+        let storage = MemoryStorage::new(storage_dir.path().to_path_buf(), "secret".to_string()).unwrap();
+
+        // Conversation écrite avant la vectorisation à l'écriture : aucun vecteur
+        let mut older = Conversation::new("Ancienne".to_string());
+        older.add_entry(MessageRole::User, "Planning du chantier de toiture".to_string(), 5);
+        storage.save_conversation(&older).unwrap();
+
+        let state = init_with_storage(
+            storage,
+            storage_dir.path().to_path_buf(),
+            super::super::secrets_vault::init_with_dir(vault_dir.path().to_path_buf()),
+        );
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let conversation = Conversation::new(DEFAULT_CONVERSATION_TITLE.to_string());
+            let id = conversation.id.clone();
+            state.persist(conversation).await.unwrap();
+            let message = chat_message("Facture d'électricité du mois de mars");
+            store_message(&state, &id, &message).await;
+            state.conversations.write().await.clear();
+
+            let storage = state.storage().await.unwrap();
+            assert_eq!(storage.load_message_vectors(&id).unwrap().len(), 1);
+            assert!(storage.load_message_vectors(&older.id).unwrap().is_empty());
+
+            let results = semantic_search(&state, "facture électricité mars", 1).await.unwrap();
+            assert_eq!(results[0].message_id, message.id);
+            assert!(state.conversations.read().await.is_empty());
+
+            // Les messages sans vecteur sont vectorisés une fois puis relus du disque
+            let results = semantic_search(&state, "chantier toiture", 1).await.unwrap();
+            assert_eq!(results[0].conversation_id, older.id);
+            assert_eq!(storage.load_message_vectors(&older.id).unwrap().len(), 1);
+            assert!(state.conversations.read().await.is_empty());
+        });
+    }
+}
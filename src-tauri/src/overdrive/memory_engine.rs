@@ -55,6 +55,7 @@ pub struct MemoryStats {
     pub facts_stored: usize,
 }
 
+#[derive(Clone)]
 pub struct MemoryEngineState {
     entries: Arc<Mutex<Vec<MemoryEntry>>>,
     index_built: Arc<Mutex<bool>>,
@@ -145,45 +146,52 @@ pub async fn memory_search(
     query: MemoryQuery,
     state: State<'_, MemoryEngineState>,
 ) -> Result<Vec<MemoryResult>, String> {
-    println!("[MEMORY] Recherche: '{}' (limit: {})", query.query, query.limit);
+    state.search(&query).await
+}
 
-    // Générer embedding de la requête
-    let query_embedding = generate_embedding(&query.query, &state).await?;
+impl MemoryEngineState {
+    /// Recherche par similarité cosine (partagée par la commande et les outils du chat)
+    pub async fn search(&self, query: &MemoryQuery) -> Result<Vec<MemoryResult>, String> {
+        println!("[MEMORY] Recherche: '{}' (limit: {})", query.query, query.limit);
 
-    // Recherche par similarité cosine
-    let entries = state.entries.lock().unwrap();
-    let mut results: Vec<MemoryResult> = Vec::new();
+        // Générer embedding de la requête
+        let query_embedding = generate_embedding(&query.query, self).await?;
 
-    for entry in entries.iter() {
-        // Appliquer filtres
-        if let Some(filters) = &query.filters {
-            if !filters.contains(&entry.metadata.entry_type) {
-                continue;
+        // Recherche par similarité cosine
+        let entries = self.entries.lock().unwrap();
+        let mut results: Vec<MemoryResult> = Vec::new();
+
+        for entry in entries.iter() {
+            // Appliquer filtres
+            if let Some(filters) = &query.filters {
+                if !filters.contains(&entry.metadata.entry_type) {
+                    continue;
+                }
             }
-        }
 
-        // Calculer similarité
-        let similarity = cosine_similarity(&query_embedding, &entry.embedding);
+            // Calculer similarité
+            let similarity = cosine_similarity(&query_embedding, &entry.embedding);
 
-        if similarity >= query.min_similarity {
-            let mut entry_clone = entry.clone();
-            entry_clone.access_count += 1;
+            if similarity >= query.min_similarity {
+                let mut entry_clone = entry.clone();
+                entry_clone.access_count += 1;
 
-            results.push(MemoryResult {
-                entry: entry_clone,
-                similarity,
-            });
+                results.push(MemoryResult {
+                    entry: entry_clone,
+                    similarity,
+                });
+            }
         }
-    }
 
-    // Trier par similarité décroissante
-    results.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap());
+        // Trier par similarité décroissante
+        results.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap());
 
-    // Limiter résultats
-    results.truncate(query.limit);
+        // Limiter résultats
+        results.truncate(query.limit);
 
-    println!("[MEMORY] {} résultats trouvés", results.len());
-    Ok(results)
+        println!("[MEMORY] {} résultats trouvés", results.len());
+        Ok(results)
+    }
 }
 
 #[tauri::command]
@@ -12,6 +12,10 @@ pub mod semantic_kernel;
 pub mod exp_engine;
 pub mod project_autopilot;
 pub mod api_bridge;
+pub mod skill_files;
+pub mod tool_calling;
+pub mod skill_pipeline;
+pub mod secrets_vault;
 
 use tauri::State;
 
@@ -28,6 +32,8 @@ pub struct OverdriveState {
     pub exp: exp_engine::ExpEngineState,
     pub projects: project_autopilot::ProjectAutoPilotState,
     pub api: api_bridge::ApiBridgeState,
+    pub tools: tool_calling::ToolCallingState,
+    pub vault: secrets_vault::SecretsVaultState,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -45,7 +51,10 @@ pub fn init() -> OverdriveState {
     let voice_state = voice_engine::init();
     println!("✅ Voice Engine initialisé");
 
-    let chat_state = chat_orchestrator::init();
+    let vault_state = secrets_vault::init();
+    println!("✅ Secrets Vault initialisé (verrouillé)");
+
+    let chat_state = chat_orchestrator::init(vault_state.clone());
     println!("✅ Chat Orchestrator initialisé");
 
     let memory_state = memory_engine::init();
@@ -60,9 +69,12 @@ pub fn init() -> OverdriveState {
     let projects_state = project_autopilot::init();
     println!("✅ Project AutoPilot initialisé");
 
-    let api_state = api_bridge::init();
+    let api_state = api_bridge::init(vault_state.clone());
     println!("✅ API Bridge initialisé");
 
+    let tools_state = tool_calling::init(&memory_state, &projects_state, &vault_state);
+    println!("✅ Tool Calling initialisé");
+
     // Setup panic handler désactivé (AutoHealState n'implémente pas Clone)
     // TODO: Refactoriser pour utiliser Arc<Mutex<>> si nécessaire
     // auto_heal::setup_panic_handler(auto_heal_state);
@@ -81,6 +93,8 @@ pub fn init() -> OverdriveState {
         exp: exp_state,
         projects: projects_state,
         api: api_state,
+        tools: tools_state,
+        vault: vault_state,
     }
 }
 
@@ -101,6 +115,8 @@ pub fn overdrive_health_check(state: State<OverdriveState>) -> Result<OverdriveH
         exp_engine_status: "operational".to_string(),
         project_autopilot_status: "operational".to_string(),
         api_bridge_status: "operational".to_string(),
+        tool_calling_status: "operational".to_string(),
+        secrets_vault_status: if state.vault.is_unlocked() { "unlocked" } else { "locked" }.to_string(),
         overall_health: "100%".to_string(),
     };
 
@@ -117,6 +133,8 @@ pub struct OverdriveHealthReport {
     pub exp_engine_status: String,
     pub project_autopilot_status: String,
     pub api_bridge_status: String,
+    pub tool_calling_status: String,
+    pub secrets_vault_status: String,
     pub overall_health: String,
 }
 
@@ -139,6 +157,8 @@ pub fn overdrive_get_version() -> Result<OverdriveVersion, String> {
             "exp_engine".to_string(),
             "project_autopilot".to_string(),
             "api_bridge".to_string(),
+            "tool_calling".to_string(),
+            "secrets_vault".to_string(),
         ],
     })
 }
@@ -72,6 +72,7 @@ pub struct AutoPilotReport {
     pub errors: Vec<String>,
 }
 
+#[derive(Clone)]
 pub struct ProjectAutoPilotState {
     projects: Arc<Mutex<HashMap<String, Project>>>,
     tasks: Arc<Mutex<Vec<Task>>>,
@@ -246,28 +247,40 @@ pub fn task_create(
     description: String,
     state: State<ProjectAutoPilotState>,
 ) -> Result<Task, String> {
-    let task_id = uuid::Uuid::new_v4().to_string();
-
-    let task = Task {
-        id: task_id.clone(),
-        project_id: project_id.clone(),
-        title: title.clone(),
-        description,
-        status: "todo".to_string(),
-        priority: 3,
-        estimated_hours: 0.0,
-        dependencies: vec![],
-        assigned_to: None,
-        created_at: get_timestamp(),
-        completed_at: None,
-    };
+    state.create_task(project_id, title, description)
+}
 
-    let mut tasks = state.tasks.lock().unwrap();
-    tasks.push(task.clone());
+impl ProjectAutoPilotState {
+    /// Crée une tâche (partagé par la commande et les outils du chat)
+    pub fn create_task(
+        &self,
+        project_id: String,
+        title: String,
+        description: String,
+    ) -> Result<Task, String> {
+        let task_id = uuid::Uuid::new_v4().to_string();
+
+        let task = Task {
+            id: task_id.clone(),
+            project_id: project_id.clone(),
+            title: title.clone(),
+            description,
+            status: "todo".to_string(),
+            priority: 3,
+            estimated_hours: 0.0,
+            dependencies: vec![],
+            assigned_to: None,
+            created_at: get_timestamp(),
+            completed_at: None,
+        };
 
-    println!("[PROJECT] Tâche créée: {} [{}]", title, project_id);
+        let mut tasks = self.tasks.lock().unwrap();
+        tasks.push(task.clone());
 
-    Ok(task)
+        println!("[PROJECT] Tâche créée: {} [{}]", title, project_id);
+
+        Ok(task)
+    }
 }
 
 #[tauri::command]
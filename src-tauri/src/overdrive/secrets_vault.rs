@@ -0,0 +1,590 @@
+// ═══════════════════════════════════════════════════════════════════════════
+// TITANE∞ v16 — OVERDRIVE SECRETS VAULT
+// ═══════════════════════════════════════════════════════════════════════════
+// Coffre local pour les clés d'API : AES-256-GCM, clé dérivée par Argon2id,
+// déverrouillé une fois par session, journal d'audit de chaque accès
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Les secrets sont référencés par nom (ex. `ApiConfig::secret_name`) et ne
+// sont jamais renvoyés au frontend : aucune commande ne lit une valeur.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use crate::shared::crypto::{self, KdfConfig, KdfParams, KEY_SIZE};
+
+const VAULT_VERSION: u32 = 1;
+const CHECK_PLAINTEXT: &[u8] = b"titane-vault-check";
+const CHECK_AAD: &[u8] = b"__check__";
+
+const FRONTEND_ACCESSOR: &str = "frontend";
+
+// ─────────────────────────────────────────────────────────────────────────────
+// STRUCTURES
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredSecret {
+    ciphertext: String, // base64(nonce || ciphertext), AAD = nom du secret
+    created_at: u64,
+    updated_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    kdf: KdfParams,
+    check: String,
+    secrets: BTreeMap<String, StoredSecret>,
+}
+
+struct UnlockedVault {
+    key: [u8; KEY_SIZE],
+    file: VaultFile,
+}
+
+impl Drop for UnlockedVault {
+    fn drop(&mut self) {
+        crypto::wipe(&mut self.key);
+    }
+}
+
+/// Métadonnées d'un secret (jamais la valeur)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretInfo {
+    pub name: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultStatus {
+    pub initialized: bool,
+    pub unlocked: bool,
+    pub secret_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultAuditEntry {
+    pub timestamp: u64,
+    pub action: String,          // unlock|lock|read|write|delete|import|change_password
+    pub secret: Option<String>,
+    pub accessor: String,        // frontend|chat_orchestrator|api_bridge:<api>|...
+    pub success: bool,
+    pub detail: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct SecretsVaultState {
+    vault_path: PathBuf,
+    audit_path: PathBuf,
+    unlocked: Arc<Mutex<Option<UnlockedVault>>>,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// INITIALISATION
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub fn init() -> SecretsVaultState {
+    let dir = dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("titane")
+        .join("vault");
+    init_with_dir(dir)
+}
+
+pub fn init_with_dir(dir: PathBuf) -> SecretsVaultState {
+    let state = SecretsVaultState {
+        vault_path: dir.join("secrets.vault.json"),
+        audit_path: dir.join("audit.log"),
+        unlocked: Arc::new(Mutex::new(None)),
+    };
+
+    println!(
+        "[VAULT] Coffre {} ({})",
+        if state.vault_path.exists() { "existant" } else { "non initialisé" },
+        dir.display()
+    );
+
+    state
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// API DU COFFRE
+// ─────────────────────────────────────────────────────────────────────────────
+
+impl SecretsVaultState {
+    pub fn status(&self) -> VaultStatus {
+        let unlocked = self.unlocked.lock().unwrap();
+        VaultStatus {
+            initialized: self.vault_path.exists(),
+            unlocked: unlocked.is_some(),
+            secret_count: unlocked.as_ref().map(|v| v.file.secrets.len()).unwrap_or(0),
+        }
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked.lock().unwrap().is_some()
+    }
+
+    /// Crée un coffre vide et le déverrouille ; refusé si un coffre existe déjà
+    pub fn create_vault(&self, password: &str, accessor: &str) -> Result<(), String> {
+        let result = if password.is_empty() {
+            Err("Mot de passe vide".to_string())
+        } else if self.vault_path.exists() {
+            Err(format!("Un coffre existe déjà: {}", self.vault_path.display()))
+        } else {
+            self.create(password)
+        };
+        self.finish_unlock("create", result, accessor)
+    }
+
+    /// Déverrouille le coffre existant pour la session ; un fichier absent
+    /// est une erreur (la création passe par `create_vault`)
+    pub fn unlock(&self, password: &str, accessor: &str) -> Result<(), String> {
+        let result = if password.is_empty() {
+            Err("Mot de passe vide".to_string())
+        } else if !self.vault_path.exists() {
+            Err(format!(
+                "Coffre introuvable: {} (vault_create requis)",
+                self.vault_path.display()
+            ))
+        } else {
+            self.open_existing(password)
+        };
+        self.finish_unlock("unlock", result, accessor)
+    }
+
+    fn finish_unlock(
+        &self,
+        action: &str,
+        result: Result<UnlockedVault, String>,
+        accessor: &str,
+    ) -> Result<(), String> {
+        let success = result.is_ok();
+        let detail = result.as_ref().err().cloned();
+        if let Ok(vault) = result {
+            *self.unlocked.lock().unwrap() = Some(vault);
+        }
+        self.audit(action, None, accessor, success, detail.clone());
+
+        match detail {
+            None => Ok(()),
+            Some(e) => Err(e),
+        }
+    }
+
+    pub fn lock(&self, accessor: &str) {
+        // Le Drop de UnlockedVault efface la clé
+        *self.unlocked.lock().unwrap() = None;
+        self.audit("lock", None, accessor, true, None);
+    }
+
+    /// Lit un secret ; chaque accès (réussi ou non) est journalisé
+    pub fn get_secret(&self, name: &str, accessor: &str) -> Result<String, String> {
+        let result = self.with_unlocked(|vault| {
+            let stored = vault
+                .file
+                .secrets
+                .get(name)
+                .ok_or_else(|| format!("Secret introuvable: {}", name))?;
+            let plaintext = open_sealed(&vault.key, &stored.ciphertext, name.as_bytes())?;
+            String::from_utf8(plaintext).map_err(|_| "Secret corrompu".to_string())
+        });
+
+        self.audit("read", Some(name), accessor, result.is_ok(), result.as_ref().err().cloned());
+        result
+    }
+
+    pub fn has_secret(&self, name: &str) -> bool {
+        self.unlocked
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|v| v.file.secrets.contains_key(name))
+            .unwrap_or(false)
+    }
+
+    pub fn set_secret(&self, name: &str, value: &str, accessor: &str) -> Result<(), String> {
+        let result = validate_secret_name(name).and_then(|_| {
+            self.with_unlocked(|vault| {
+                let ciphertext = seal(&vault.key, value.as_bytes(), name.as_bytes())?;
+                let now = get_timestamp();
+                let created_at = vault.file.secrets.get(name).map(|s| s.created_at).unwrap_or(now);
+                vault.file.secrets.insert(
+                    name.to_string(),
+                    StoredSecret { ciphertext, created_at, updated_at: now },
+                );
+                self.save(&vault.file)
+            })
+        });
+
+        self.audit("write", Some(name), accessor, result.is_ok(), result.as_ref().err().cloned());
+        result
+    }
+
+    pub fn delete_secret(&self, name: &str, accessor: &str) -> Result<bool, String> {
+        let result = self.with_unlocked(|vault| {
+            let removed = vault.file.secrets.remove(name).is_some();
+            if removed {
+                self.save(&vault.file)?;
+            }
+            Ok(removed)
+        });
+
+        self.audit("delete", Some(name), accessor, result.is_ok(), result.as_ref().err().cloned());
+        result
+    }
+
+    pub fn list_secrets(&self) -> Result<Vec<SecretInfo>, String> {
+        self.with_unlocked(|vault| {
+            Ok(vault
+                .file
+                .secrets
+                .iter()
+                .map(|(name, s)| SecretInfo {
+                    name: name.clone(),
+                    created_at: s.created_at,
+                    updated_at: s.updated_at,
+                })
+                .collect())
+        })
+    }
+
+    /// Re-chiffre tous les secrets avec une clé dérivée du nouveau mot de passe
+    pub fn change_password(&self, old_password: &str, new_password: &str, accessor: &str) -> Result<(), String> {
+        let result = (|| {
+            if new_password.is_empty() {
+                return Err("Nouveau mot de passe vide".to_string());
+            }
+            let mut current = self.open_existing(old_password)?;
+
+            let kdf = KdfParams::generate(KdfConfig::default());
+            let mut key = derive_key(new_password, &kdf)?;
+            let mut secrets = BTreeMap::new();
+            for (name, stored) in &current.file.secrets {
+                let plaintext = open_sealed(&current.key, &stored.ciphertext, name.as_bytes())?;
+                secrets.insert(
+                    name.clone(),
+                    StoredSecret {
+                        ciphertext: seal(&key, &plaintext, name.as_bytes())?,
+                        ..stored.clone()
+                    },
+                );
+            }
+
+            let file = VaultFile {
+                version: VAULT_VERSION,
+                check: seal(&key, CHECK_PLAINTEXT, CHECK_AAD)?,
+                kdf,
+                secrets,
+            };
+            self.save(&file)?;
+
+            // L'ancienne clé est effacée avant d'être remplacée
+            crypto::wipe(&mut current.key);
+            current.key = key;
+            crypto::wipe(&mut key);
+            current.file = file;
+            *self.unlocked.lock().unwrap() = Some(current);
+            Ok(())
+        })();
+
+        self.audit("change_password", None, accessor, result.is_ok(), result.as_ref().err().cloned());
+        result
+    }
+
+    /// Dernières entrées du journal d'audit (plus récentes en premier)
+    pub fn audit_log(&self, limit: usize) -> Vec<VaultAuditEntry> {
+        let content = fs::read_to_string(&self.audit_path).unwrap_or_default();
+        content
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .take(limit)
+            .collect()
+    }
+
+    fn with_unlocked<T>(
+        &self,
+        f: impl FnOnce(&mut UnlockedVault) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut guard = self.unlocked.lock().unwrap();
+        let vault = guard
+            .as_mut()
+            .ok_or_else(|| "Coffre verrouillé (vault_unlock requis)".to_string())?;
+        f(vault)
+    }
+
+    fn open_existing(&self, password: &str) -> Result<UnlockedVault, String> {
+        let content = fs::read_to_string(&self.vault_path).map_err(|e| e.to_string())?;
+        let file: VaultFile =
+            serde_json::from_str(&content).map_err(|e| format!("Coffre illisible: {}", e))?;
+        if file.version != VAULT_VERSION {
+            return Err(format!("Version de coffre non supportée: {}", file.version));
+        }
+
+        let key = derive_key(password, &file.kdf)?;
+        match open_sealed(&key, &file.check, CHECK_AAD) {
+            Ok(check) if check == CHECK_PLAINTEXT => Ok(UnlockedVault { key, file }),
+            _ => Err("Mot de passe incorrect".to_string()),
+        }
+    }
+
+    fn create(&self, password: &str) -> Result<UnlockedVault, String> {
+        let kdf = KdfParams::generate(KdfConfig::default());
+        let key = derive_key(password, &kdf)?;
+        let file = VaultFile {
+            version: VAULT_VERSION,
+            check: seal(&key, CHECK_PLAINTEXT, CHECK_AAD)?,
+            kdf,
+            secrets: BTreeMap::new(),
+        };
+        self.save(&file)?;
+        println!("[VAULT] Nouveau coffre créé");
+        Ok(UnlockedVault { key, file })
+    }
+
+    fn save(&self, file: &VaultFile) -> Result<(), String> {
+        if let Some(parent) = self.vault_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+        let tmp = self.vault_path.with_extension("tmp");
+        fs::write(&tmp, json).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, &self.vault_path).map_err(|e| e.to_string())
+    }
+
+    fn audit(&self, action: &str, secret: Option<&str>, accessor: &str, success: bool, detail: Option<String>) {
+        let entry = VaultAuditEntry {
+            timestamp: get_timestamp(),
+            action: action.to_string(),
+            secret: secret.map(str::to_string),
+            accessor: accessor.to_string(),
+            success,
+            detail,
+        };
+
+        let written = serde_json::to_string(&entry).map_err(|e| e.to_string()).and_then(|line| {
+            if let Some(parent) = self.audit_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.audit_path)
+                .map_err(|e| e.to_string())?;
+            writeln!(file, "{}", line).map_err(|e| e.to_string())
+        });
+
+        if let Err(e) = written {
+            println!("[VAULT] ⚠️ Journal d'audit indisponible: {}", e);
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// CRYPTOGRAPHIE
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn derive_key(password: &str, kdf: &KdfParams) -> Result<[u8; KEY_SIZE], String> {
+    kdf.derive_key(password).map_err(|e| e.to_string())
+}
+
+fn seal(key: &[u8; KEY_SIZE], plaintext: &[u8], aad: &[u8]) -> Result<String, String> {
+    crypto::seal(key, plaintext, aad).map_err(|e| e.to_string())
+}
+
+fn open_sealed(key: &[u8; KEY_SIZE], sealed: &str, aad: &[u8]) -> Result<Vec<u8>, String> {
+    crypto::open_sealed(key, sealed, aad).map_err(|_| "Secret corrompu".to_string())
+}
+
+fn validate_secret_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Nom de secret invalide '{}' (a-z, 0-9, _ - . uniquement)", name))
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// COMMANDES TAURI (aucune ne renvoie de valeur de secret)
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn vault_status(state: State<SecretsVaultState>) -> Result<VaultStatus, String> {
+    Ok(state.status())
+}
+
+#[tauri::command]
+pub fn vault_create(password: String, state: State<SecretsVaultState>) -> Result<VaultStatus, String> {
+    state.create_vault(&password, FRONTEND_ACCESSOR)?;
+    Ok(state.status())
+}
+
+#[tauri::command]
+pub fn vault_unlock(password: String, state: State<SecretsVaultState>) -> Result<VaultStatus, String> {
+    state.unlock(&password, FRONTEND_ACCESSOR)?;
+    Ok(state.status())
+}
+
+#[tauri::command]
+pub fn vault_lock(state: State<SecretsVaultState>) -> Result<VaultStatus, String> {
+    state.lock(FRONTEND_ACCESSOR);
+    Ok(state.status())
+}
+
+#[tauri::command]
+pub fn vault_set_secret(
+    name: String,
+    value: String,
+    state: State<SecretsVaultState>,
+) -> Result<SecretInfo, String> {
+    state.set_secret(&name, &value, FRONTEND_ACCESSOR)?;
+    state
+        .list_secrets()?
+        .into_iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| "Secret introuvable après écriture".to_string())
+}
+
+#[tauri::command]
+pub fn vault_delete_secret(name: String, state: State<SecretsVaultState>) -> Result<bool, String> {
+    state.delete_secret(&name, FRONTEND_ACCESSOR)
+}
+
+#[tauri::command]
+pub fn vault_list_secrets(state: State<SecretsVaultState>) -> Result<Vec<SecretInfo>, String> {
+    state.list_secrets()
+}
+
+#[tauri::command]
+pub fn vault_change_password(
+    old_password: String,
+    new_password: String,
+    state: State<SecretsVaultState>,
+) -> Result<String, String> {
+    state.change_password(&old_password, &new_password, FRONTEND_ACCESSOR)?;
+    Ok("Mot de passe du coffre modifié".to_string())
+}
+
+/// Importe une variable d'environnement (ou du fichier `.env`) dans le coffre
+#[tauri::command]
+pub fn vault_import_env(
+    var_name: String,
+    secret_name: String,
+    state: State<SecretsVaultState>,
+) -> Result<SecretInfo, String> {
+    dotenv::dotenv().ok();
+    let value = std::env::var(&var_name).map_err(|_| format!("Variable {} absente", var_name));
+    let result = value.and_then(|v| state.set_secret(&secret_name, &v, FRONTEND_ACCESSOR));
+    state.audit(
+        "import",
+        Some(&secret_name),
+        FRONTEND_ACCESSOR,
+        result.is_ok(),
+        Some(format!("depuis {}", var_name)),
+    );
+    result?;
+
+    state
+        .list_secrets()?
+        .into_iter()
+        .find(|s| s.name == secret_name)
+        .ok_or_else(|| "Secret introuvable après import".to_string())
+}
+
+#[tauri::command]
+pub fn vault_get_audit_log(
+    limit: Option<usize>,
+    state: State<SecretsVaultState>,
+) -> Result<Vec<VaultAuditEntry>, String> {
+    Ok(state.audit_log(limit.unwrap_or(100)))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// UTILITAIRES
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn get_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCESSOR: &str = "test";
+
+    #[test]
+    fn unlock_requires_an_existing_vault() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = init_with_dir(dir.path().to_path_buf());
+
+        assert!(vault.unlock("secret", ACCESSOR).unwrap_err().contains("vault_create"));
+        assert!(!vault.status().initialized);
+
+        vault.create_vault("secret", ACCESSOR).unwrap();
+        assert!(vault.status().initialized && vault.is_unlocked());
+        assert!(vault.create_vault("autre", ACCESSOR).is_err());
+    }
+
+    #[test]
+    fn wrong_password_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = init_with_dir(dir.path().to_path_buf());
+        vault.create_vault("correct", ACCESSOR).unwrap();
+        vault.lock(ACCESSOR);
+
+        assert_eq!(vault.unlock("faux", ACCESSOR).unwrap_err(), "Mot de passe incorrect");
+        assert!(!vault.is_unlocked());
+        assert!(vault.get_secret("x", ACCESSOR).is_err());
+    }
+
+    #[test]
+    fn secrets_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = init_with_dir(dir.path().to_path_buf());
+        vault.create_vault("pw", ACCESSOR).unwrap();
+        vault.set_secret("gemini_api_key", "sk-123", ACCESSOR).unwrap();
+
+        let reopened = init_with_dir(dir.path().to_path_buf());
+        reopened.unlock("pw", ACCESSOR).unwrap();
+        assert_eq!(reopened.get_secret("gemini_api_key", ACCESSOR).unwrap(), "sk-123");
+        assert_eq!(reopened.list_secrets().unwrap().len(), 1);
+
+        let log = reopened.audit_log(10);
+        assert_eq!(log[0].action, "read");
+        assert!(log.iter().any(|e| e.action == "create"));
+    }
+
+    #[test]
+    fn change_password_reencrypts_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = init_with_dir(dir.path().to_path_buf());
+        vault.create_vault("ancien", ACCESSOR).unwrap();
+        vault.set_secret("token", "valeur", ACCESSOR).unwrap();
+
+        assert!(vault.change_password("faux", "nouveau", ACCESSOR).is_err());
+        vault.change_password("ancien", "nouveau", ACCESSOR).unwrap();
+        assert_eq!(vault.get_secret("token", ACCESSOR).unwrap(), "valeur");
+
+        let reopened = init_with_dir(dir.path().to_path_buf());
+        assert!(reopened.unlock("ancien", ACCESSOR).is_err());
+        reopened.unlock("nouveau", ACCESSOR).unwrap();
+        assert_eq!(reopened.get_secret("token", ACCESSOR).unwrap(), "valeur");
+    }
+
+}
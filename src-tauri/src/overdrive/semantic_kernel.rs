@@ -6,9 +6,13 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
 use tokio::sync::RwLock;
 use tauri::State;
 
+use super::skill_files::{self, SkillWatcher};
+
 // ─────────────────────────────────────────────────────────────────────────────
 // STRUCTURES
 // ─────────────────────────────────────────────────────────────────────────────
@@ -19,8 +23,60 @@ pub struct SemanticSkill {
     pub description: String,
     pub prompt_template: String,
     pub input_variables: Vec<String>,
+    /// Entrées typées ; `input_variables` en est la liste des noms
+    #[serde(default)]
+    pub inputs: Vec<SkillInput>,
     pub examples: Vec<SkillExample>,
     pub enabled: bool,
+    /// Fichier source dans le répertoire de skills
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillInput {
+    pub name: String,
+    pub input_type: SkillInputType,
+    pub required: bool,
+    pub default: Option<String>,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SkillInputType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    List,       // valeurs séparées par des virgules
+}
+
+impl SkillInputType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkillInputType::String => "string",
+            SkillInputType::Number => "number",
+            SkillInputType::Integer => "integer",
+            SkillInputType::Boolean => "boolean",
+            SkillInputType::List => "list",
+        }
+    }
+
+    fn accepts(&self, value: &str) -> bool {
+        match self {
+            SkillInputType::String | SkillInputType::List => true,
+            SkillInputType::Number => value.trim().parse::<f64>().is_ok(),
+            SkillInputType::Integer => value.trim().parse::<i64>().is_ok(),
+            SkillInputType::Boolean => matches!(value.trim(), "true" | "false"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillValidationIssue {
+    pub variable: String,
+    pub message: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,8 +109,91 @@ pub struct IntentAnalysis {
 }
 
 pub struct SemanticKernelState {
-    skills: RwLock<HashMap<String, SemanticSkill>>,
+    skills: Arc<RwLock<HashMap<String, SemanticSkill>>>,
     intent_cache: RwLock<HashMap<String, IntentAnalysis>>,
+    skills_dir: PathBuf,
+    load_errors: Arc<RwLock<Vec<String>>>,
+    _watcher: SkillWatcher,
+}
+
+impl SemanticKernelState {
+    /// Récupère le skill, vérifie qu'il est actif et construit son prompt
+    pub(crate) async fn prepare_prompt(&self, request: &SemanticRequest) -> Result<String, String> {
+        let skill = {
+            let skills = self.skills.read().await;
+            skills
+                .get(&request.skill_name)
+                .ok_or_else(|| format!("Skill introuvable: {}", request.skill_name))?
+                .clone()
+        };
+
+        if !skill.enabled {
+            return Err(format!("Skill désactivé: {}", skill.name));
+        }
+
+        build_prompt(&skill, request)
+    }
+
+    pub(crate) async fn get_skill(&self, name: &str) -> Option<SemanticSkill> {
+        self.skills.read().await.get(name).cloned()
+    }
+
+    pub(crate) fn skills_dir(&self) -> &std::path::Path {
+        &self.skills_dir
+    }
+}
+
+impl SemanticSkill {
+    /// Aligne `inputs` et `input_variables` (skills créés avec la seule liste de noms)
+    pub fn normalized(mut self) -> Self {
+        for var in &self.input_variables {
+            if !self.inputs.iter().any(|i| &i.name == var) {
+                self.inputs.push(SkillInput {
+                    name: var.clone(),
+                    input_type: SkillInputType::String,
+                    required: true,
+                    default: None,
+                    description: String::new(),
+                });
+            }
+        }
+        self.input_variables = self.inputs.iter().map(|i| i.name.clone()).collect();
+        self
+    }
+
+    /// Vérifie les entrées fournies et complète avec les valeurs par défaut
+    pub fn resolve_inputs(
+        &self,
+        provided: &HashMap<String, String>,
+    ) -> Result<HashMap<String, String>, Vec<SkillValidationIssue>> {
+        let mut resolved = HashMap::new();
+        let mut issues = Vec::new();
+
+        for input in &self.inputs {
+            match provided.get(&input.name).or(input.default.as_ref()) {
+                Some(value) if !input.input_type.accepts(value) => issues.push(SkillValidationIssue {
+                    variable: input.name.clone(),
+                    message: format!("valeur '{}' invalide pour le type {}", value, input.input_type.as_str()),
+                }),
+                Some(value) => {
+                    resolved.insert(input.name.clone(), value.clone());
+                }
+                None if input.required => issues.push(SkillValidationIssue {
+                    variable: input.name.clone(),
+                    message: "entrée requise manquante".to_string(),
+                }),
+                None => {
+                    resolved.insert(input.name.clone(), String::new());
+                }
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(resolved)
+        } else {
+            Err(issues)
+        }
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -62,89 +201,130 @@ pub struct SemanticKernelState {
 // ─────────────────────────────────────────────────────────────────────────────
 
 pub fn init() -> SemanticKernelState {
-    let state = SemanticKernelState {
-        skills: RwLock::new(HashMap::new()),
-        intent_cache: RwLock::new(HashMap::new()),
-    };
+    init_with_dir(skill_files::default_skills_dir())
+}
 
-    // Charger skills par défaut (bloquer pour init synchrone)
-    let rt = tokio::runtime::Runtime::new().unwrap();
-    rt.block_on(async {
-        load_default_skills(&state).await;
-    });
+pub fn init_with_dir(skills_dir: PathBuf) -> SemanticKernelState {
+    // Premier lancement : matérialiser les skills par défaut en fichiers
+    let (mut skills, mut errors) = skill_files::load_skills_dir(&skills_dir);
+    if skills.is_empty() && errors.is_empty() {
+        for skill in default_skills() {
+            if let Err(e) = skill_files::write_skill(&skills_dir, &skill) {
+                println!("[SEMANTIC] ⚠️ Impossible d'écrire le skill {}: {}", skill.name, e);
+            }
+        }
+        let reloaded = skill_files::load_skills_dir(&skills_dir);
+        skills = reloaded.0;
+        errors = reloaded.1;
 
-    state
+        // Répertoire non accessible en écriture : skills par défaut en mémoire
+        if skills.is_empty() {
+            skills = default_skills().into_iter().map(|s| (s.name.clone(), s)).collect();
+        }
+    }
+
+    for error in &errors {
+        println!("[SEMANTIC] ⚠️ {}", error);
+    }
+    println!("[SEMANTIC] {} skills chargés depuis {}", skills.len(), skills_dir.display());
+
+    let skills = Arc::new(RwLock::new(skills));
+    let load_errors = Arc::new(RwLock::new(errors));
+    let watcher = SkillWatcher::spawn(skills_dir.clone(), skills.clone(), load_errors.clone());
+
+    SemanticKernelState {
+        skills,
+        intent_cache: RwLock::new(HashMap::new()),
+        skills_dir,
+        load_errors,
+        _watcher: watcher,
+    }
 }
 
-async fn load_default_skills(state: &SemanticKernelState) {
-    let mut skills = state.skills.write().await;
+fn required_input(name: &str, description: &str) -> SkillInput {
+    SkillInput {
+        name: name.to_string(),
+        input_type: SkillInputType::String,
+        required: true,
+        default: None,
+        description: description.to_string(),
+    }
+}
 
-    // Skill: Summarization
-    skills.insert(
-        "summarize".to_string(),
+fn default_skills() -> Vec<SemanticSkill> {
+    vec![
+        // Skill: Summarization
         SemanticSkill {
             name: "summarize".to_string(),
             description: "Résume un texte long en conservant les points clés".to_string(),
             prompt_template: "Résume le texte suivant de manière concise :\n\n{{$input}}\n\nRésumé:".to_string(),
-            input_variables: vec!["input".to_string()],
+            input_variables: vec![],
+            inputs: vec![required_input("input", "Texte à résumer")],
             examples: vec![],
             enabled: true,
+            source: None,
         },
-    );
-
-    // Skill: Code Generation
-    skills.insert(
-        "generate_code".to_string(),
+        // Skill: Code Generation
         SemanticSkill {
             name: "generate_code".to_string(),
             description: "Génère du code à partir d'une description".to_string(),
             prompt_template: "Génère du code {{$language}} pour : {{$task}}\n\nCode:".to_string(),
-            input_variables: vec!["language".to_string(), "task".to_string()],
+            input_variables: vec![],
+            inputs: vec![
+                SkillInput {
+                    name: "language".to_string(),
+                    input_type: SkillInputType::String,
+                    required: false,
+                    default: Some("Rust".to_string()),
+                    description: "Langage cible".to_string(),
+                },
+                required_input("task", "Description de la tâche"),
+            ],
             examples: vec![],
             enabled: true,
+            source: None,
         },
-    );
-
-    // Skill: Sentiment Analysis
-    skills.insert(
-        "analyze_sentiment".to_string(),
+        // Skill: Sentiment Analysis
         SemanticSkill {
             name: "analyze_sentiment".to_string(),
             description: "Analyse le sentiment d'un texte".to_string(),
             prompt_template: "Analyse le sentiment du texte suivant (positif/neutre/négatif) :\n\n{{$text}}\n\nSentiment:".to_string(),
-            input_variables: vec!["text".to_string()],
+            input_variables: vec![],
+            inputs: vec![required_input("text", "Texte à analyser")],
             examples: vec![],
             enabled: true,
+            source: None,
         },
-    );
-
-    // Skill: Translation
-    skills.insert(
-        "translate".to_string(),
+        // Skill: Translation
         SemanticSkill {
             name: "translate".to_string(),
             description: "Traduit un texte d'une langue à une autre".to_string(),
             prompt_template: "Traduis le texte suivant de {{$from}} vers {{$to}} :\n\n{{$text}}\n\nTraduction:".to_string(),
-            input_variables: vec!["from".to_string(), "to".to_string(), "text".to_string()],
+            input_variables: vec![],
+            inputs: vec![
+                required_input("from", "Langue source"),
+                required_input("to", "Langue cible"),
+                required_input("text", "Texte à traduire"),
+            ],
             examples: vec![],
             enabled: true,
+            source: None,
         },
-    );
-
-    // Skill: Intent Recognition
-    skills.insert(
-        "recognize_intent".to_string(),
+        // Skill: Intent Recognition
         SemanticSkill {
             name: "recognize_intent".to_string(),
             description: "Identifie l'intention derrière une requête utilisateur".to_string(),
             prompt_template: "Identifie l'intention de l'utilisateur dans : {{$query}}\n\nIntention:".to_string(),
-            input_variables: vec!["query".to_string()],
+            input_variables: vec![],
+            inputs: vec![required_input("query", "Requête utilisateur")],
             examples: vec![],
             enabled: true,
+            source: None,
         },
-    );
-
-    println!("[SEMANTIC] {} skills chargés", skills.len());
+    ]
+    .into_iter()
+    .map(SemanticSkill::normalized)
+    .collect()
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -160,21 +340,7 @@ pub async fn semantic_execute_skill(
 
     println!("[SEMANTIC] Exécution skill: {}", request.skill_name);
 
-    // Récupérer le skill
-    let skill = {
-        let skills = state.skills.read().await;
-        skills
-            .get(&request.skill_name)
-            .ok_or("Skill introuvable")?
-            .clone()
-    };
-
-    if !skill.enabled {
-        return Err("Skill désactivé".to_string());
-    }
-
-    // Construire prompt
-    let prompt = build_prompt(&skill, &request)?;
+    let prompt = state.prepare_prompt(&request).await?;
 
     // Exécuter via Chat Orchestrator (TODO: intégration)
     let output = execute_prompt(&prompt).await?;
@@ -190,17 +356,33 @@ pub async fn semantic_execute_skill(
 }
 
 fn build_prompt(skill: &SemanticSkill, request: &SemanticRequest) -> Result<String, String> {
-    let mut prompt = skill.prompt_template.clone();
-
-    // Remplacer variables
-    for var in &skill.input_variables {
-        let value = request
-            .inputs
-            .get(var)
-            .ok_or(format!("Variable manquante: {}", var))?;
-        prompt = prompt.replace(&format!("{{{{${}}}}}",var), value);
+    let inputs = skill.resolve_inputs(&request.inputs).map_err(|issues| {
+        let details: Vec<String> = issues
+            .iter()
+            .map(|i| format!("{}: {}", i.variable, i.message))
+            .collect();
+        format!("Entrées invalides pour '{}' — {}", skill.name, details.join("; "))
+    })?;
+
+    let mut prompt = String::new();
+
+    // Exemples few-shot : template rendu avec les entrées de l'exemple + sortie attendue
+    for (i, example) in skill.examples.iter().enumerate() {
+        let mut example_inputs = inputs.clone();
+        example_inputs.extend(example.input.clone());
+        prompt.push_str(&format!(
+            "### Exemple {}\n{}\n{}\n\n",
+            i + 1,
+            render_template(&skill.prompt_template, &example_inputs),
+            example.output.trim()
+        ));
     }
 
+    if !skill.examples.is_empty() {
+        prompt.push_str("### Tâche\n");
+    }
+    prompt.push_str(&render_template(&skill.prompt_template, &inputs));
+
     // Ajouter contexte si présent
     if let Some(context) = &request.context {
         prompt = format!("Contexte: {}\n\n{}", context, prompt);
@@ -209,7 +391,32 @@ fn build_prompt(skill: &SemanticSkill, request: &SemanticRequest) -> Result<Stri
     Ok(prompt)
 }
 
-async fn execute_prompt(prompt: &str) -> Result<String, String> {
+/// Substitution en une seule passe : une valeur contenant elle-même
+/// `{{$autre}}` est insérée telle quelle, sans être ré-expansée
+fn render_template(template: &str, inputs: &HashMap<String, String>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{$") {
+        let after = &rest[start + 3..];
+        let end = match after.find("}}") {
+            Some(e) => e,
+            None => break,
+        };
+        rendered.push_str(&rest[..start]);
+        match inputs.get(after[..end].trim()) {
+            Some(value) => rendered.push_str(value),
+            // Variable inconnue : placeholder conservé
+            None => rendered.push_str(&rest[start..start + 3 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+pub(crate) async fn execute_prompt(prompt: &str) -> Result<String, String> {
     // TODO: Appeler Chat Orchestrator
     println!("[SEMANTIC] Prompt: {}", prompt);
     Ok("Réponse simulée du kernel".to_string())
@@ -327,10 +534,19 @@ pub async fn semantic_add_skill(
     skill: SemanticSkill,
     state: State<'_, SemanticKernelState>,
 ) -> Result<String, String> {
+    let mut skill = skill.normalized();
+    let undeclared = skill_files::undeclared_variables(&skill);
+    if !undeclared.is_empty() {
+        return Err(format!("Variables non déclarées: {}", undeclared.join(", ")));
+    }
+
+    let path = skill_files::write_skill(&state.skills_dir, &skill)?;
+    skill.source = Some(path.to_string_lossy().to_string());
+
     let mut skills = state.skills.write().await;
     let name = skill.name.clone();
     skills.insert(name.clone(), skill);
-    println!("[SEMANTIC] Skill ajouté: {}", name);
+    println!("[SEMANTIC] Skill ajouté: {} ({})", name, path.display());
     Ok(name)
 }
 
@@ -339,6 +555,7 @@ pub async fn semantic_remove_skill(
     skill_name: String,
     state: State<'_, SemanticKernelState>,
 ) -> Result<String, String> {
+    skill_files::delete_skill(&state.skills_dir, &skill_name)?;
     let mut skills = state.skills.write().await;
     skills.remove(&skill_name);
     Ok("Skill supprimé".to_string())
@@ -353,16 +570,53 @@ pub async fn semantic_toggle_skill(
     let mut skills = state.skills.write().await;
     if let Some(skill) = skills.get_mut(&skill_name) {
         skill.enabled = enabled;
+        skill_files::write_skill(&state.skills_dir, skill)?;
         Ok(format!("Skill {} {}", skill_name, if enabled { "activé" } else { "désactivé" }))
     } else {
         Err("Skill introuvable".to_string())
     }
 }
 
+/// Valide des entrées sans exécuter le skill (retourne la liste des problèmes)
+#[tauri::command]
+pub async fn semantic_validate_inputs(
+    skill_name: String,
+    inputs: HashMap<String, String>,
+    state: State<'_, SemanticKernelState>,
+) -> Result<Vec<SkillValidationIssue>, String> {
+    let skills = state.skills.read().await;
+    let skill = skills.get(&skill_name).ok_or("Skill introuvable")?;
+    Ok(skill.resolve_inputs(&inputs).err().unwrap_or_default())
+}
+
+/// Recharge immédiatement le répertoire de skills
+#[tauri::command]
+pub async fn semantic_reload_skills(state: State<'_, SemanticKernelState>) -> Result<usize, String> {
+    let (loaded, errors) = skill_files::load_skills_dir(&state.skills_dir);
+    let count = loaded.len();
+    *state.skills.write().await = loaded;
+    *state.load_errors.write().await = errors;
+    println!("[SEMANTIC] {} skills rechargés", count);
+    Ok(count)
+}
+
+/// Erreurs de chargement des fichiers de skills (fichiers ignorés)
+#[tauri::command]
+pub async fn semantic_get_load_errors(state: State<'_, SemanticKernelState>) -> Result<Vec<String>, String> {
+    Ok(state.load_errors.read().await.clone())
+}
+
+#[tauri::command]
+pub fn semantic_get_skills_dir(state: State<'_, SemanticKernelState>) -> Result<String, String> {
+    Ok(state.skills_dir.to_string_lossy().to_string())
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // CHAÎNAGE DE SKILLS
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Chaînage linéaire : la sortie de chaque skill alimente la première entrée
+/// déclarée du suivant (pipeline à une branche, voir `skill_pipeline`)
 #[tauri::command]
 pub async fn semantic_chain_skills(
     skill_names: Vec<String>,
@@ -371,25 +625,15 @@ pub async fn semantic_chain_skills(
 ) -> Result<String, String> {
     println!("[SEMANTIC] Chaînage de {} skills", skill_names.len());
 
-    let mut current_output = initial_input;
-
-    for skill_name in skill_names {
-        let mut inputs = HashMap::new();
-        inputs.insert("input".to_string(), current_output.clone());
-
-        let request = SemanticRequest {
-            skill_name: skill_name.clone(),
-            inputs,
-            context: None,
-        };
-
-        let response = semantic_execute_skill(request, state.clone()).await?;
-        current_output = response.output;
+    let pipeline = super::skill_pipeline::linear_pipeline(&state, &skill_names).await?;
+    let mut inputs = HashMap::new();
+    inputs.insert("input".to_string(), initial_input);
 
-        println!("[SEMANTIC] {} → output: {}", skill_name, current_output);
+    let trace = super::skill_pipeline::run_pipeline(&state, &pipeline, inputs, None).await?;
+    match (trace.success, trace.final_output) {
+        (true, Some(output)) => Ok(output),
+        _ => Err(trace.error.unwrap_or_else(|| "Chaînage échoué".to_string())),
     }
-
-    Ok(current_output)
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -409,3 +653,36 @@ pub async fn semantic_get_cache_size(state: State<'_, SemanticKernelState>) -> R
     let cache = state.intent_cache.read().await;
     Ok(cache.len())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn render_template_substitutes_all_placeholders() {
+        let rendered = render_template(
+            "Traduis de {{$from}} vers {{$to }} : {{$text}}",
+            &inputs(&[("from", "fr"), ("to", "en"), ("text", "Bonjour")]),
+        );
+        assert_eq!(rendered, "Traduis de fr vers en : Bonjour");
+    }
+
+    #[test]
+    fn render_template_does_not_expand_substituted_values() {
+        let rendered = render_template(
+            "{{$a}} / {{$b}}",
+            &inputs(&[("a", "{{$b}}"), ("b", "secret")]),
+        );
+        assert_eq!(rendered, "{{$b}} / secret");
+    }
+
+    #[test]
+    fn render_template_keeps_unknown_and_unterminated_placeholders() {
+        let rendered = render_template("{{$inconnu}} et {{$x", &inputs(&[("x", "1")]));
+        assert_eq!(rendered, "{{$inconnu}} et {{$x");
+    }
+}
@@ -0,0 +1,466 @@
+// ═══════════════════════════════════════════════════════════════════════════
+// TITANE∞ v16 — OVERDRIVE SKILL FILES
+// ═══════════════════════════════════════════════════════════════════════════
+// Skills du Semantic Kernel persistés en fichiers `<nom>.skill.md` :
+// frontmatter (métadonnées, entrées typées, exemples) + corps = template
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Format :
+//
+//   ---
+//   name: translate
+//   description: Traduit un texte d'une langue à une autre
+//   enabled: true
+//   input: from | string | required | Langue source
+//   input: to | string | default=anglais | Langue cible
+//   input: text | string | required
+//   example: {"input": {"from": "fr", "to": "en", "text": "Bonjour"}, "output": "Hello"}
+//   ---
+//   Traduis le texte suivant de {{$from}} vers {{$to}} :
+//
+//   {{$text}}
+//
+// Une ligne `input:` = nom | type (string|number|integer|boolean|list)
+// | required ou default=<valeur> (optionnel) | description (optionnelle).
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::RwLock;
+
+use super::semantic_kernel::{SemanticSkill, SkillExample, SkillInput, SkillInputType};
+
+pub const SKILL_FILE_EXTENSION: &str = ".skill.md";
+const FRONTMATTER_DELIMITER: &str = "---";
+const WATCH_INTERVAL_SECONDS: u64 = 2;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// PARSING / RENDU
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub fn parse_skill_file(content: &str) -> Result<SemanticSkill, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let mut lines = content.lines();
+
+    if lines.next().map(str::trim) != Some(FRONTMATTER_DELIMITER) {
+        return Err("Frontmatter manquant (le fichier doit commencer par ---)".to_string());
+    }
+
+    let mut name = None;
+    let mut description = String::new();
+    let mut enabled = true;
+    let mut inputs = Vec::new();
+    let mut examples = Vec::new();
+    let mut closed = false;
+
+    for (line_no, line) in lines.by_ref().enumerate() {
+        let line = line.trim();
+        if line == FRONTMATTER_DELIMITER {
+            closed = true;
+            break;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Ligne {} invalide: '{}'", line_no + 2, line))?;
+        let value = value.trim();
+
+        match key.trim() {
+            "name" => name = Some(value.to_string()),
+            "description" => description = value.to_string(),
+            "enabled" => {
+                enabled = value
+                    .parse()
+                    .map_err(|_| format!("enabled invalide: '{}'", value))?
+            }
+            "input" => inputs.push(parse_input_line(value)?),
+            "example" => {
+                let example: SkillExample = serde_json::from_str(value)
+                    .map_err(|e| format!("Exemple invalide (ligne {}): {}", line_no + 2, e))?;
+                examples.push(example);
+            }
+            other => return Err(format!("Clé de frontmatter inconnue: '{}'", other)),
+        }
+    }
+
+    if !closed {
+        return Err("Frontmatter non terminé (--- manquant)".to_string());
+    }
+
+    let name = name.ok_or("Champ 'name' manquant")?;
+    validate_skill_name(&name)?;
+
+    let prompt_template = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+    if prompt_template.is_empty() {
+        return Err(format!("Template vide pour le skill '{}'", name));
+    }
+
+    let skill = SemanticSkill {
+        input_variables: inputs.iter().map(|i: &SkillInput| i.name.clone()).collect(),
+        name,
+        description,
+        prompt_template,
+        inputs,
+        examples,
+        enabled,
+        source: None,
+    };
+
+    let undeclared = undeclared_variables(&skill);
+    if !undeclared.is_empty() {
+        return Err(format!(
+            "Variables utilisées mais non déclarées dans '{}': {}",
+            skill.name,
+            undeclared.join(", ")
+        ));
+    }
+
+    Ok(skill)
+}
+
+fn parse_input_line(value: &str) -> Result<SkillInput, String> {
+    let parts: Vec<&str> = value.split('|').map(str::trim).collect();
+    let name = parts.first().copied().unwrap_or_default();
+    if name.is_empty() {
+        return Err(format!("Entrée sans nom: '{}'", value));
+    }
+
+    let input_type = match parts.get(1).copied().unwrap_or("string") {
+        "string" | "" => SkillInputType::String,
+        "number" => SkillInputType::Number,
+        "integer" => SkillInputType::Integer,
+        "boolean" => SkillInputType::Boolean,
+        "list" => SkillInputType::List,
+        other => return Err(format!("Type d'entrée inconnu pour '{}': {}", name, other)),
+    };
+
+    let mut required = false;
+    let mut default = None;
+    match parts.get(2).copied().unwrap_or("") {
+        "" | "optional" => {}
+        "required" => required = true,
+        flag => match flag.strip_prefix("default=") {
+            Some(d) => default = Some(d.to_string()),
+            None => return Err(format!("Option d'entrée inconnue pour '{}': {}", name, flag)),
+        },
+    }
+
+    Ok(SkillInput {
+        name: name.to_string(),
+        input_type,
+        required,
+        default,
+        description: parts.get(3).map(|d| d.to_string()).unwrap_or_default(),
+    })
+}
+
+pub fn render_skill_file(skill: &SemanticSkill) -> String {
+    let mut out = String::new();
+    out.push_str(FRONTMATTER_DELIMITER);
+    out.push('\n');
+    out.push_str(&format!("name: {}\n", skill.name));
+    out.push_str(&format!("description: {}\n", skill.description.replace('\n', " ")));
+    out.push_str(&format!("enabled: {}\n", skill.enabled));
+
+    for input in &skill.inputs {
+        let flag = match (&input.default, input.required) {
+            (Some(d), _) => format!("default={}", d),
+            (None, true) => "required".to_string(),
+            (None, false) => "optional".to_string(),
+        };
+        let mut line = format!("input: {} | {} | {}", input.name, input.input_type.as_str(), flag);
+        if !input.description.is_empty() {
+            line.push_str(&format!(" | {}", input.description));
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+
+    for example in &skill.examples {
+        if let Ok(json) = serde_json::to_string(example) {
+            out.push_str(&format!("example: {}\n", json));
+        }
+    }
+
+    out.push_str(FRONTMATTER_DELIMITER);
+    out.push('\n');
+    out.push_str(&skill.prompt_template);
+    out.push('\n');
+    out
+}
+
+/// Variables `{{$var}}` présentes dans le template mais absentes des entrées
+pub fn undeclared_variables(skill: &SemanticSkill) -> Vec<String> {
+    let mut missing = Vec::new();
+    let mut rest = skill.prompt_template.as_str();
+
+    while let Some(start) = rest.find("{{$") {
+        let after = &rest[start + 3..];
+        let end = match after.find("}}") {
+            Some(e) => e,
+            None => break,
+        };
+        let var = after[..end].trim().to_string();
+        if !skill.inputs.iter().any(|i| i.name == var) && !missing.contains(&var) {
+            missing.push(var);
+        }
+        rest = &after[end + 2..];
+    }
+
+    missing
+}
+
+pub fn validate_skill_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Nom de skill invalide '{}' (a-z, 0-9, _ et - uniquement)",
+            name
+        ))
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// RÉPERTOIRE DE SKILLS
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub fn default_skills_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("titane")
+        .join("skills")
+}
+
+pub fn skill_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}{}", name, SKILL_FILE_EXTENSION))
+}
+
+fn skill_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .map(|n| n.ends_with(SKILL_FILE_EXTENSION))
+                        .unwrap_or(false)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Charge tous les skills du répertoire ; les fichiers invalides sont
+/// signalés dans la liste d'erreurs sans bloquer les autres
+pub fn load_skills_dir(dir: &Path) -> (HashMap<String, SemanticSkill>, Vec<String>) {
+    let mut skills = HashMap::new();
+    let mut errors = Vec::new();
+
+    for path in skill_files(dir) {
+        let result = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| parse_skill_file(&content));
+
+        match result {
+            Ok(mut skill) => {
+                skill.source = Some(path.to_string_lossy().to_string());
+                if skills.contains_key(&skill.name) {
+                    errors.push(format!("{}: skill '{}' déjà défini", path.display(), skill.name));
+                    continue;
+                }
+                skills.insert(skill.name.clone(), skill);
+            }
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    (skills, errors)
+}
+
+pub fn write_skill(dir: &Path, skill: &SemanticSkill) -> Result<PathBuf, String> {
+    validate_skill_name(&skill.name)?;
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    let path = skill_path(dir, &skill.name);
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, render_skill_file(skill)).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+pub fn delete_skill(dir: &Path, name: &str) -> Result<(), String> {
+    validate_skill_name(name)?;
+    let path = skill_path(dir, name);
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn snapshot(dir: &Path) -> HashMap<PathBuf, SystemTime> {
+    skill_files(dir)
+        .into_iter()
+        .filter_map(|p| {
+            let modified = fs::metadata(&p).and_then(|m| m.modified()).ok()?;
+            Some((p, modified))
+        })
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// HOT-RELOAD (polling des dates de modification)
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub struct SkillWatcher {
+    running: Arc<AtomicBool>,
+}
+
+impl SkillWatcher {
+    /// Surveille `dir` et recharge `skills` dès qu'un fichier est ajouté,
+    /// modifié ou supprimé
+    pub fn spawn(
+        dir: PathBuf,
+        skills: Arc<RwLock<HashMap<String, SemanticSkill>>>,
+        load_errors: Arc<RwLock<Vec<String>>>,
+    ) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = running.clone();
+
+        std::thread::spawn(move || {
+            let mut last = snapshot(&dir);
+
+            while flag.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_secs(WATCH_INTERVAL_SECONDS));
+
+                let current = snapshot(&dir);
+                if current == last {
+                    continue;
+                }
+                last = current;
+
+                let (loaded, errors) = load_skills_dir(&dir);
+                println!(
+                    "[SEMANTIC] Hot-reload: {} skills ({} erreurs)",
+                    loaded.len(),
+                    errors.len()
+                );
+                for error in &errors {
+                    println!("[SEMANTIC] ⚠️ {}", error);
+                }
+                *skills.blocking_write() = loaded;
+                *load_errors.blocking_write() = errors;
+            }
+        });
+
+        Self { running }
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl Drop for SkillWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSLATE: &str = r#"---
+name: translate
+description: Traduit un texte
+enabled: true
+input: from | string | required | Langue source
+input: to | string | default=anglais | Langue cible
+input: count | integer | optional
+example: {"input": {"from": "fr", "text": "Bonjour"}, "output": "Hello"}
+---
+Traduis de {{$from}} vers {{$to}} ({{$count}}) :
+
+texte"#;
+
+    #[test]
+    fn parse_reads_frontmatter_and_body() {
+        let skill = parse_skill_file(TRANSLATE).unwrap();
+        assert_eq!(skill.name, "translate");
+        assert_eq!(skill.input_variables, vec!["from", "to", "count"]);
+        assert!(skill.inputs[0].required);
+        assert_eq!(skill.inputs[1].default.as_deref(), Some("anglais"));
+        assert_eq!(skill.inputs[2].input_type, SkillInputType::Integer);
+        assert_eq!(skill.examples.len(), 1);
+        assert!(skill.prompt_template.ends_with("\n\ntexte"));
+    }
+
+    #[test]
+    fn render_then_parse_round_trips() {
+        let skill = parse_skill_file(TRANSLATE).unwrap();
+        let reparsed = parse_skill_file(&render_skill_file(&skill)).unwrap();
+
+        assert_eq!(reparsed.name, skill.name);
+        assert_eq!(reparsed.description, skill.description);
+        assert_eq!(reparsed.enabled, skill.enabled);
+        assert_eq!(reparsed.prompt_template, skill.prompt_template);
+        assert_eq!(reparsed.input_variables, skill.input_variables);
+        for (a, b) in reparsed.inputs.iter().zip(&skill.inputs) {
+            assert_eq!((&a.name, a.input_type, a.required), (&b.name, b.input_type, b.required));
+            assert_eq!((&a.default, &a.description), (&b.default, &b.description));
+        }
+        assert_eq!(reparsed.examples[0].input, skill.examples[0].input);
+        assert_eq!(reparsed.examples[0].output, skill.examples[0].output);
+    }
+
+    #[test]
+    fn parse_rejects_undeclared_variables() {
+        let content = "---\nname: x\ninput: a\n---\n{{$a}} {{$b}}";
+        let err = parse_skill_file(content).unwrap_err();
+        assert!(err.contains('b'), "{}", err);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_names() {
+        let content = "---\nname: ../x\n---\ncorps";
+        assert!(parse_skill_file(content).is_err());
+    }
+
+    #[test]
+    fn delete_rejects_path_traversal() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("skills");
+        fs::create_dir_all(&dir).unwrap();
+        let outside = root.path().join(format!("x{}", SKILL_FILE_EXTENSION));
+        fs::write(&outside, "ne pas supprimer").unwrap();
+
+        assert!(delete_skill(&dir, "../x").is_err());
+        assert!(outside.exists());
+    }
+
+    #[test]
+    fn write_then_delete_removes_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let skill = parse_skill_file(TRANSLATE).unwrap();
+        let path = write_skill(dir.path(), &skill).unwrap();
+        assert!(path.exists());
+
+        delete_skill(dir.path(), "translate").unwrap();
+        assert!(!path.exists());
+    }
+}
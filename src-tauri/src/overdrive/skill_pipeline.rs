@@ -0,0 +1,866 @@
+// ═══════════════════════════════════════════════════════════════════════════
+// TITANE∞ v16 — OVERDRIVE SKILL PIPELINES
+// ═══════════════════════════════════════════════════════════════════════════
+// Pipelines de skills (DAG) : sorties nommées, constantes, conditions,
+// exécution parallèle des étapes indépendantes, trace rejouable
+// ═══════════════════════════════════════════════════════════════════════════
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use tauri::State;
+
+use super::semantic_kernel::{execute_prompt, SemanticKernelState, SemanticRequest};
+use super::skill_files;
+
+const PIPELINE_FILE_EXTENSION: &str = ".pipeline.json";
+
+// ─────────────────────────────────────────────────────────────────────────────
+// STRUCTURES
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillPipeline {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub steps: Vec<PipelineStep>,
+    /// Étape dont la sortie est le résultat du pipeline (défaut : dernière exécutée)
+    #[serde(default)]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineStep {
+    pub id: String,
+    pub skill: String,
+    /// Entrée du skill → source de la valeur
+    #[serde(default)]
+    pub inputs: HashMap<String, InputBinding>,
+    #[serde(default)]
+    pub condition: Option<StepCondition>,
+    /// Dépendances explicites en plus de celles déduites des entrées
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+/// Source d'une entrée : `{"step": "id"}` ou `{"step": "id.champ"}` (champ d'une
+/// sortie JSON), `{"const": "valeur"}`, `{"input": "nom"}` (entrée du pipeline)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputBinding {
+    Step(String),
+    Const(String),
+    Input(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepCondition {
+    /// Référence de sortie testée (même syntaxe que `InputBinding::Step`)
+    pub step: String,
+    pub operator: ConditionOperator,
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+/// Comparaisons sur la sortie nettoyée (trim), insensibles à la casse
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionOperator {
+    Equals,
+    NotEquals,
+    Contains,
+    NotContains,
+    NotEmpty,
+    Matches,    // expression régulière
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Succeeded,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepTrace {
+    pub step_id: String,
+    pub skill: String,
+    pub status: StepStatus,
+    pub wave: usize,
+    pub inputs: HashMap<String, String>,
+    pub prompt: Option<String>,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub started_offset_ms: u64,
+    pub duration_ms: u64,
+    /// Lors d'un rejeu : vrai si la sortie diffère de l'exécution d'origine
+    #[serde(default)]
+    pub changed_from_original: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineTrace {
+    pub run_id: String,
+    pub pipeline: SkillPipeline,
+    pub inputs: HashMap<String, String>,
+    pub steps: Vec<StepTrace>,
+    pub final_output: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub started_at: u64,
+    pub total_ms: u64,
+    #[serde(default)]
+    pub replay_of: Option<String>,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// VALIDATION & PLANIFICATION
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn step_ref(reference: &str) -> (&str, Option<&str>) {
+    match reference.split_once('.') {
+        Some((step, field)) => (step, Some(field)),
+        None => (reference, None),
+    }
+}
+
+fn dependencies(step: &PipelineStep) -> HashSet<String> {
+    let mut deps: HashSet<String> = step.depends_on.iter().cloned().collect();
+    for binding in step.inputs.values() {
+        if let InputBinding::Step(reference) = binding {
+            deps.insert(step_ref(reference).0.to_string());
+        }
+    }
+    if let Some(condition) = &step.condition {
+        deps.insert(step_ref(&condition.step).0.to_string());
+    }
+    deps
+}
+
+/// Vérifie le DAG et le découpe en vagues : les étapes d'une même vague
+/// ne dépendent pas les unes des autres et s'exécutent en parallèle
+pub fn plan_waves(pipeline: &SkillPipeline) -> Result<Vec<Vec<String>>, String> {
+    if pipeline.steps.is_empty() {
+        return Err("Pipeline vide".to_string());
+    }
+
+    let mut ids = HashSet::new();
+    for step in &pipeline.steps {
+        if !ids.insert(step.id.as_str()) {
+            return Err(format!("Identifiant d'étape dupliqué: {}", step.id));
+        }
+    }
+
+    let mut remaining: HashMap<String, HashSet<String>> = HashMap::new();
+    for step in &pipeline.steps {
+        let deps = dependencies(step);
+        if let Some(unknown) = deps.iter().find(|d| !ids.contains(d.as_str())) {
+            return Err(format!("Étape '{}' : référence inconnue '{}'", step.id, unknown));
+        }
+        if deps.contains(&step.id) {
+            return Err(format!("Étape '{}' : dépend d'elle-même", step.id));
+        }
+        remaining.insert(step.id.clone(), deps);
+    }
+
+    if let Some(output) = &pipeline.output {
+        if !ids.contains(output.as_str()) {
+            return Err(format!("Sortie du pipeline inconnue: {}", output));
+        }
+    }
+
+    let mut waves = Vec::new();
+    let mut done: HashSet<String> = HashSet::new();
+    while !remaining.is_empty() {
+        // Ordre stable : ordre de déclaration des étapes
+        let wave: Vec<String> = pipeline
+            .steps
+            .iter()
+            .filter(|s| {
+                remaining
+                    .get(&s.id)
+                    .map(|deps| deps.iter().all(|d| done.contains(d)))
+                    .unwrap_or(false)
+            })
+            .map(|s| s.id.clone())
+            .collect();
+
+        if wave.is_empty() {
+            let mut cycle: Vec<&String> = remaining.keys().collect();
+            cycle.sort();
+            return Err(format!("Cycle détecté entre les étapes: {:?}", cycle));
+        }
+
+        for id in &wave {
+            remaining.remove(id);
+            done.insert(id.clone());
+        }
+        waves.push(wave);
+    }
+
+    Ok(waves)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// EXÉCUTION
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn lookup_output(outputs: &HashMap<String, String>, reference: &str) -> Option<String> {
+    let (step, field) = step_ref(reference);
+    let output = outputs.get(step)?;
+    match field {
+        None => Some(output.clone()),
+        Some(field) => {
+            let json: serde_json::Value = serde_json::from_str(extract_json(output)).ok()?;
+            match json.get(field)? {
+                serde_json::Value::String(s) => Some(s.clone()),
+                other => Some(other.to_string()),
+            }
+        }
+    }
+}
+
+/// Tolère une sortie JSON entourée de texte ou d'un bloc ```json```
+fn extract_json(output: &str) -> &str {
+    match (output.find('{'), output.rfind('}')) {
+        (Some(start), Some(end)) if end > start => &output[start..=end],
+        _ => output,
+    }
+}
+
+fn evaluate_condition(
+    condition: &StepCondition,
+    outputs: &HashMap<String, String>,
+) -> Result<bool, String> {
+    let actual = lookup_output(outputs, &condition.step)
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    let expected = condition.value.clone().unwrap_or_default().trim().to_lowercase();
+
+    Ok(match condition.operator {
+        ConditionOperator::Equals => actual == expected,
+        ConditionOperator::NotEquals => actual != expected,
+        ConditionOperator::Contains => actual.contains(&expected),
+        ConditionOperator::NotContains => !actual.contains(&expected),
+        ConditionOperator::NotEmpty => !actual.is_empty(),
+        ConditionOperator::Matches => {
+            let pattern = condition.value.as_deref().unwrap_or_default();
+            regex::RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| format!("Expression régulière invalide '{}': {}", pattern, e))?
+                .is_match(&actual)
+        }
+    })
+}
+
+fn resolve_inputs(
+    step: &PipelineStep,
+    outputs: &HashMap<String, String>,
+    pipeline_inputs: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    let mut resolved = HashMap::new();
+    for (name, binding) in &step.inputs {
+        let value = match binding {
+            InputBinding::Const(value) => value.clone(),
+            InputBinding::Input(key) => pipeline_inputs
+                .get(key)
+                .cloned()
+                .ok_or_else(|| format!("Entrée du pipeline manquante: {}", key))?,
+            InputBinding::Step(reference) => lookup_output(outputs, reference)
+                .ok_or_else(|| format!("Sortie introuvable: {}", reference))?,
+        };
+        resolved.insert(name.clone(), value);
+    }
+    Ok(resolved)
+}
+
+fn skipped(step: &PipelineStep, wave: usize, offset_ms: u64, reason: String) -> StepTrace {
+    StepTrace {
+        step_id: step.id.clone(),
+        skill: step.skill.clone(),
+        status: StepStatus::Skipped,
+        wave,
+        inputs: HashMap::new(),
+        prompt: None,
+        output: None,
+        error: Some(reason),
+        started_offset_ms: offset_ms,
+        duration_ms: 0,
+        changed_from_original: None,
+    }
+}
+
+/// Exécute le pipeline vague par vague ; une étape dont une dépendance n'a
+/// pas réussi est ignorée, une étape en échec rend le pipeline en échec
+pub async fn run_pipeline(
+    state: &SemanticKernelState,
+    pipeline: &SkillPipeline,
+    inputs: HashMap<String, String>,
+    replay_of: Option<String>,
+) -> Result<PipelineTrace, String> {
+    let waves = plan_waves(pipeline)?;
+    let steps: HashMap<&str, &PipelineStep> =
+        pipeline.steps.iter().map(|s| (s.id.as_str(), s)).collect();
+
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let started_at = get_timestamp();
+    let start = Instant::now();
+    let mut outputs: HashMap<String, String> = HashMap::new();
+    let mut traces: HashMap<String, StepTrace> = HashMap::new();
+    let mut execution_order: Vec<String> = Vec::new();
+
+    println!(
+        "[PIPELINE] {} — {} étapes en {} vagues",
+        pipeline.name,
+        pipeline.steps.len(),
+        waves.len()
+    );
+
+    for (wave_index, wave) in waves.iter().enumerate() {
+        let mut tasks = tokio::task::JoinSet::new();
+        let mut prepared: HashMap<String, (HashMap<String, String>, String)> = HashMap::new();
+
+        for step_id in wave {
+            let step = steps[step_id.as_str()];
+            let offset_ms = start.elapsed().as_millis() as u64;
+
+            // Dépendances non satisfaites → étape ignorée
+            let blocked = dependencies(step)
+                .into_iter()
+                .find(|d| traces.get(d).map(|t| t.status != StepStatus::Succeeded).unwrap_or(true));
+            if let Some(dep) = blocked {
+                let reason = format!("Dépendance '{}' non exécutée", dep);
+                traces.insert(step.id.clone(), skipped(step, wave_index, offset_ms, reason));
+                continue;
+            }
+
+            if let Some(condition) = &step.condition {
+                match evaluate_condition(condition, &outputs) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        let reason = format!("Condition non remplie sur '{}'", condition.step);
+                        traces.insert(step.id.clone(), skipped(step, wave_index, offset_ms, reason));
+                        continue;
+                    }
+                    Err(e) => {
+                        let mut trace = skipped(step, wave_index, offset_ms, e);
+                        trace.status = StepStatus::Failed;
+                        traces.insert(step.id.clone(), trace);
+                        continue;
+                    }
+                }
+            }
+
+            let prepared_prompt = match resolve_inputs(step, &outputs, &inputs) {
+                Ok(step_inputs) => {
+                    let request = SemanticRequest {
+                        skill_name: step.skill.clone(),
+                        inputs: step_inputs.clone(),
+                        context: None,
+                    };
+                    state.prepare_prompt(&request).await.map(|p| (step_inputs, p))
+                }
+                Err(e) => Err(e),
+            };
+
+            match prepared_prompt {
+                Ok((step_inputs, prompt)) => {
+                    prepared.insert(step.id.clone(), (step_inputs, prompt.clone()));
+                    let id = step.id.clone();
+                    tasks.spawn(async move {
+                        let step_start = Instant::now();
+                        let result = execute_prompt(&prompt).await;
+                        (id, offset_ms, step_start.elapsed().as_millis() as u64, result)
+                    });
+                }
+                Err(e) => {
+                    let mut trace = skipped(step, wave_index, offset_ms, e);
+                    trace.status = StepStatus::Failed;
+                    traces.insert(step.id.clone(), trace);
+                }
+            }
+        }
+
+        while let Some(joined) = tasks.join_next().await {
+            let (id, offset_ms, duration_ms, result) =
+                joined.map_err(|e| format!("Tâche de pipeline interrompue: {}", e))?;
+            let step = steps[id.as_str()];
+            let (step_inputs, prompt) = prepared.remove(&id).unwrap_or_default();
+
+            let (status, output, error) = match result {
+                Ok(output) => {
+                    outputs.insert(id.clone(), output.clone());
+                    (StepStatus::Succeeded, Some(output), None)
+                }
+                Err(e) => (StepStatus::Failed, None, Some(e)),
+            };
+
+            println!("[PIPELINE] {} ({}) → {:?} en {} ms", id, step.skill, status, duration_ms);
+            traces.insert(
+                id.clone(),
+                StepTrace {
+                    step_id: id,
+                    skill: step.skill.clone(),
+                    status,
+                    wave: wave_index,
+                    inputs: step_inputs,
+                    prompt: Some(prompt),
+                    output,
+                    error,
+                    started_offset_ms: offset_ms,
+                    duration_ms,
+                    changed_from_original: None,
+                },
+            );
+        }
+
+        execution_order.extend(wave.iter().cloned());
+    }
+
+    let ordered: Vec<StepTrace> = execution_order
+        .iter()
+        .filter_map(|id| traces.remove(id))
+        .collect();
+
+    let failed: Vec<&str> = ordered
+        .iter()
+        .filter(|t| t.status == StepStatus::Failed)
+        .map(|t| t.step_id.as_str())
+        .collect();
+
+    let final_output = match &pipeline.output {
+        Some(id) => outputs.get(id).cloned(),
+        None => ordered
+            .iter()
+            .rev()
+            .find(|t| t.status == StepStatus::Succeeded)
+            .and_then(|t| t.output.clone()),
+    };
+
+    let error = if !failed.is_empty() {
+        Some(format!("Étapes en échec: {}", failed.join(", ")))
+    } else if final_output.is_none() {
+        Some("Aucune sortie produite".to_string())
+    } else {
+        None
+    };
+
+    Ok(PipelineTrace {
+        run_id,
+        pipeline: pipeline.clone(),
+        inputs,
+        steps: ordered,
+        success: error.is_none(),
+        final_output,
+        error,
+        started_at,
+        total_ms: start.elapsed().as_millis() as u64,
+        replay_of,
+    })
+}
+
+/// Pipeline linéaire : chaque skill reçoit la sortie précédente sur sa
+/// première entrée déclarée (la première étape lit l'entrée `input`)
+pub async fn linear_pipeline(
+    state: &SemanticKernelState,
+    skill_names: &[String],
+) -> Result<SkillPipeline, String> {
+    let mut steps = Vec::<PipelineStep>::new();
+
+    for (i, name) in skill_names.iter().enumerate() {
+        let skill = state
+            .get_skill(name)
+            .await
+            .ok_or_else(|| format!("Skill introuvable: {}", name))?;
+        let first_input = skill
+            .input_variables
+            .first()
+            .cloned()
+            .ok_or_else(|| format!("Le skill '{}' n'a aucune entrée", name))?;
+
+        let source = match steps.last() {
+            None => InputBinding::Input("input".to_string()),
+            Some(previous) => InputBinding::Step(previous.id.clone()),
+        };
+
+        let mut inputs = HashMap::new();
+        inputs.insert(first_input, source);
+        steps.push(PipelineStep {
+            id: format!("{}_{}", i + 1, name),
+            skill: name.clone(),
+            inputs,
+            condition: None,
+            depends_on: vec![],
+        });
+    }
+
+    Ok(SkillPipeline {
+        name: "chain".to_string(),
+        description: format!("Chaînage: {}", skill_names.join(" → ")),
+        output: steps.last().map(|s: &PipelineStep| s.id.clone()),
+        steps,
+    })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// PERSISTANCE (pipelines et traces)
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn pipelines_dir(state: &SemanticKernelState) -> PathBuf {
+    state.skills_dir().join("pipelines")
+}
+
+fn traces_dir(state: &SemanticKernelState) -> PathBuf {
+    state.skills_dir().join("traces")
+}
+
+fn write_json<T: Serialize>(path: PathBuf, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: PathBuf) -> Result<T, String> {
+    let content = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn load_pipeline(state: &SemanticKernelState, name: &str) -> Result<SkillPipeline, String> {
+    skill_files::validate_skill_name(name)?;
+    read_json(pipelines_dir(state).join(format!("{}{}", name, PIPELINE_FILE_EXTENSION)))
+}
+
+fn load_trace(state: &SemanticKernelState, run_id: &str) -> Result<PipelineTrace, String> {
+    uuid::Uuid::parse_str(run_id).map_err(|_| "Identifiant d'exécution invalide".to_string())?;
+    read_json(traces_dir(state).join(format!("{}.json", run_id)))
+}
+
+fn save_trace(state: &SemanticKernelState, trace: &PipelineTrace) {
+    let path = traces_dir(state).join(format!("{}.json", trace.run_id));
+    if let Err(e) = write_json(path, trace) {
+        println!("[PIPELINE] ⚠️ Trace non sauvegardée: {}", e);
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// COMMANDES TAURI
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn semantic_pipeline_save(
+    pipeline: SkillPipeline,
+    state: State<'_, SemanticKernelState>,
+) -> Result<String, String> {
+    skill_files::validate_skill_name(&pipeline.name)?;
+    plan_waves(&pipeline)?;
+    for step in &pipeline.steps {
+        if state.get_skill(&step.skill).await.is_none() {
+            return Err(format!("Étape '{}' : skill inconnu '{}'", step.id, step.skill));
+        }
+    }
+
+    let path = pipelines_dir(&state).join(format!("{}{}", pipeline.name, PIPELINE_FILE_EXTENSION));
+    write_json(path, &pipeline)?;
+    println!("[PIPELINE] Pipeline sauvegardé: {}", pipeline.name);
+    Ok(pipeline.name)
+}
+
+#[tauri::command]
+pub fn semantic_pipeline_list(state: State<'_, SemanticKernelState>) -> Result<Vec<SkillPipeline>, String> {
+    let entries = match fs::read_dir(pipelines_dir(&state)) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut pipelines: Vec<SkillPipeline> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.to_string_lossy().ends_with(PIPELINE_FILE_EXTENSION))
+        .filter_map(|p| read_json(p).ok())
+        .collect();
+    pipelines.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(pipelines)
+}
+
+#[tauri::command]
+pub fn semantic_pipeline_get(
+    name: String,
+    state: State<'_, SemanticKernelState>,
+) -> Result<SkillPipeline, String> {
+    load_pipeline(&state, &name)
+}
+
+#[tauri::command]
+pub fn semantic_pipeline_delete(
+    name: String,
+    state: State<'_, SemanticKernelState>,
+) -> Result<String, String> {
+    skill_files::validate_skill_name(&name)?;
+    let path = pipelines_dir(&state).join(format!("{}{}", name, PIPELINE_FILE_EXTENSION));
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    Ok("Pipeline supprimé".to_string())
+}
+
+/// Exécute un pipeline sauvegardé (`name`) ou fourni directement (`pipeline`)
+#[tauri::command]
+pub async fn semantic_pipeline_run(
+    name: Option<String>,
+    pipeline: Option<SkillPipeline>,
+    inputs: HashMap<String, String>,
+    state: State<'_, SemanticKernelState>,
+) -> Result<PipelineTrace, String> {
+    let pipeline = match (pipeline, name) {
+        (Some(p), _) => p,
+        (None, Some(name)) => load_pipeline(&state, &name)?,
+        (None, None) => return Err("Pipeline ou nom de pipeline requis".to_string()),
+    };
+
+    let trace = run_pipeline(&state, &pipeline, inputs, None).await?;
+    save_trace(&state, &trace);
+    Ok(trace)
+}
+
+#[tauri::command]
+pub fn semantic_pipeline_get_trace(
+    run_id: String,
+    state: State<'_, SemanticKernelState>,
+) -> Result<PipelineTrace, String> {
+    load_trace(&state, &run_id)
+}
+
+#[tauri::command]
+pub fn semantic_pipeline_list_traces(
+    limit: Option<usize>,
+    state: State<'_, SemanticKernelState>,
+) -> Result<Vec<PipelineTrace>, String> {
+    let entries = match fs::read_dir(traces_dir(&state)) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut traces: Vec<PipelineTrace> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| read_json(e.path()).ok())
+        .collect();
+    traces.sort_by_key(|t| std::cmp::Reverse(t.started_at));
+    traces.truncate(limit.unwrap_or(50));
+    Ok(traces)
+}
+
+/// Rejoue une exécution avec la même définition et les mêmes entrées,
+/// en signalant les étapes dont la sortie a changé
+#[tauri::command]
+pub async fn semantic_pipeline_replay(
+    run_id: String,
+    state: State<'_, SemanticKernelState>,
+) -> Result<PipelineTrace, String> {
+    let original = load_trace(&state, &run_id)?;
+    let mut trace = run_pipeline(&state, &original.pipeline, original.inputs.clone(), Some(run_id)).await?;
+    mark_replay_changes(&original, &mut trace);
+
+    save_trace(&state, &trace);
+    Ok(trace)
+}
+
+/// Compare chaque étape rejouée à l'exécution d'origine
+fn mark_replay_changes(original: &PipelineTrace, replay: &mut PipelineTrace) {
+    for step in &mut replay.steps {
+        let before = original
+            .steps
+            .iter()
+            .find(|s| s.step_id == step.step_id)
+            .and_then(|s| s.output.as_ref());
+        step.changed_from_original = Some(before != step.output.as_ref());
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// UTILITAIRES
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn get_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(id: &str, skill: &str, inputs: &[(&str, InputBinding)]) -> PipelineStep {
+        PipelineStep {
+            id: id.to_string(),
+            skill: skill.to_string(),
+            inputs: inputs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+            condition: None,
+            depends_on: vec![],
+        }
+    }
+
+    fn from_step(reference: &str) -> InputBinding {
+        InputBinding::Step(reference.to_string())
+    }
+
+    fn pipeline(steps: Vec<PipelineStep>) -> SkillPipeline {
+        SkillPipeline {
+            name: "test".to_string(),
+            description: String::new(),
+            steps,
+            output: None,
+        }
+    }
+
+    fn condition(step: &str, operator: ConditionOperator, value: Option<&str>) -> StepCondition {
+        StepCondition {
+            step: step.to_string(),
+            operator,
+            value: value.map(str::to_string),
+        }
+    }
+
+    fn outputs(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn plan_waves_groups_independent_steps() {
+        let mut report = step("report", "summarize", &[("input", from_step("a"))]);
+        report.depends_on = vec!["b".to_string()];
+        let waves = plan_waves(&pipeline(vec![
+            step("a", "summarize", &[("input", InputBinding::Input("input".to_string()))]),
+            step("b", "analyze_sentiment", &[("text", InputBinding::Const("x".to_string()))]),
+            report,
+            step("c", "summarize", &[("input", from_step("a.title"))]),
+        ]))
+        .unwrap();
+
+        assert_eq!(waves, vec![vec!["a", "b"], vec!["report", "c"]]);
+    }
+
+    #[test]
+    fn plan_waves_rejects_invalid_graphs() {
+        assert!(plan_waves(&pipeline(vec![])).is_err());
+
+        let duplicated = pipeline(vec![step("a", "s", &[]), step("a", "s", &[])]);
+        assert!(plan_waves(&duplicated).unwrap_err().contains("dupliqué"));
+
+        let unknown = pipeline(vec![step("a", "s", &[("x", from_step("z"))])]);
+        assert!(plan_waves(&unknown).unwrap_err().contains("'z'"));
+
+        let itself = pipeline(vec![step("a", "s", &[("x", from_step("a"))])]);
+        assert!(plan_waves(&itself).unwrap_err().contains("elle-même"));
+
+        let cycle = pipeline(vec![
+            step("a", "s", &[("x", from_step("b"))]),
+            step("b", "s", &[("x", from_step("a"))]),
+        ]);
+        assert!(plan_waves(&cycle).unwrap_err().contains("Cycle"));
+
+        let mut bad_output = pipeline(vec![step("a", "s", &[])]);
+        bad_output.output = Some("z".to_string());
+        assert!(plan_waves(&bad_output).is_err());
+    }
+
+    #[test]
+    fn condition_dependencies_are_planned_first() {
+        let mut gated = step("gated", "s", &[]);
+        gated.condition = Some(condition("check", ConditionOperator::NotEmpty, None));
+        let waves = plan_waves(&pipeline(vec![gated, step("check", "s", &[])])).unwrap();
+        assert_eq!(waves, vec![vec!["check"], vec!["gated"]]);
+    }
+
+    #[test]
+    fn conditions_compare_trimmed_output_case_insensitively() {
+        let out = outputs(&[("sentiment", "  Positif \n"), ("json", "```json\n{\"label\": \"NEG\"}\n```")]);
+
+        let eval = |c: StepCondition| evaluate_condition(&c, &out).unwrap();
+        assert!(eval(condition("sentiment", ConditionOperator::Equals, Some("positif"))));
+        assert!(!eval(condition("sentiment", ConditionOperator::NotEquals, Some("POSITIF"))));
+        assert!(eval(condition("sentiment", ConditionOperator::Contains, Some("sit"))));
+        assert!(eval(condition("sentiment", ConditionOperator::NotContains, Some("négatif"))));
+        assert!(eval(condition("sentiment", ConditionOperator::NotEmpty, None)));
+        assert!(!eval(condition("absent", ConditionOperator::NotEmpty, None)));
+        assert!(eval(condition("sentiment", ConditionOperator::Matches, Some("^pos"))));
+        assert!(eval(condition("json.label", ConditionOperator::Equals, Some("neg"))));
+    }
+
+    #[test]
+    fn invalid_regex_condition_is_an_error() {
+        let c = condition("a", ConditionOperator::Matches, Some("("));
+        assert!(evaluate_condition(&c, &outputs(&[("a", "x")])).is_err());
+    }
+
+    #[test]
+    fn resolve_inputs_reports_missing_sources() {
+        let s = step("b", "s", &[("x", InputBinding::Input("absent".to_string()))]);
+        assert!(resolve_inputs(&s, &HashMap::new(), &HashMap::new()).is_err());
+
+        let s = step("b", "s", &[("x", from_step("a.field")), ("y", InputBinding::Const("c".to_string()))]);
+        let resolved = resolve_inputs(&s, &outputs(&[("a", "{\"field\": 3}")]), &HashMap::new()).unwrap();
+        assert_eq!(resolved["x"], "3");
+        assert_eq!(resolved["y"], "c");
+    }
+
+    #[tokio::test]
+    async fn unmet_condition_skips_step_and_its_dependents() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = crate::overdrive::semantic_kernel::init_with_dir(dir.path().to_path_buf());
+
+        let mut gated = step("gated", "summarize", &[("input", InputBinding::Const("x".to_string()))]);
+        gated.condition = Some(condition("first", ConditionOperator::Equals, Some("jamais")));
+        let p = pipeline(vec![
+            step("first", "summarize", &[("input", InputBinding::Input("input".to_string()))]),
+            gated,
+            step("after", "summarize", &[("input", from_step("gated"))]),
+        ]);
+
+        let inputs = outputs(&[("input", "texte")]);
+        let trace = run_pipeline(&state, &p, inputs, None).await.unwrap();
+
+        let status: Vec<_> = trace.steps.iter().map(|s| (s.step_id.as_str(), s.status.clone())).collect();
+        assert_eq!(
+            status,
+            vec![
+                ("first", StepStatus::Succeeded),
+                ("gated", StepStatus::Skipped),
+                ("after", StepStatus::Skipped),
+            ]
+        );
+        assert!(trace.success);
+        assert_eq!(trace.final_output, trace.steps[0].output);
+    }
+
+    #[tokio::test]
+    async fn replay_flags_changed_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = crate::overdrive::semantic_kernel::init_with_dir(dir.path().to_path_buf());
+        let p = pipeline(vec![
+            step("a", "summarize", &[("input", InputBinding::Input("input".to_string()))]),
+            step("b", "summarize", &[("input", from_step("a"))]),
+        ]);
+
+        let mut original = run_pipeline(&state, &p, outputs(&[("input", "t")]), None).await.unwrap();
+        original.steps[1].output = Some("ancienne sortie".to_string());
+
+        let mut replay = run_pipeline(&state, &original.pipeline, original.inputs.clone(), Some(original.run_id.clone()))
+            .await
+            .unwrap();
+        mark_replay_changes(&original, &mut replay);
+
+        assert_eq!(replay.replay_of.as_deref(), Some(original.run_id.as_str()));
+        assert_eq!(replay.steps[0].changed_from_original, Some(false));
+        assert_eq!(replay.steps[1].changed_from_original, Some(true));
+    }
+}
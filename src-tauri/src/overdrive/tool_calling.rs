@@ -0,0 +1,1583 @@
+// ═══════════════════════════════════════════════════════════════════════════
+// TITANE∞ v16 — OVERDRIVE TOOL CALLING
+// ═══════════════════════════════════════════════════════════════════════════
+// Registre d'outils (schéma JSON + handler Rust) appelables par le modèle,
+// adaptateurs Ollama / Gemini, fallback JSON-mode et boucle d'exécution
+// ═══════════════════════════════════════════════════════════════════════════
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tauri::State;
+
+use super::api_bridge::default_secret_name;
+use super::chat_orchestrator::{ChatMessage, ChatOrchestratorState, ChatRequest};
+use super::memory_engine::{MemoryEngineState, MemoryQuery};
+use super::project_autopilot::ProjectAutoPilotState;
+use super::secrets_vault::SecretsVaultState;
+
+const OLLAMA_CHAT_URL: &str = "http://localhost:11434/api/chat";
+const GEMINI_API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+const DEFAULT_OLLAMA_MODEL: &str = "llama3.1";
+const DEFAULT_GEMINI_MODEL: &str = "gemini-2.0-flash-exp";
+const DEFAULT_MAX_ITERATIONS: u32 = 5;
+const REQUEST_TIMEOUT_SECONDS: u64 = 60;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// STRUCTURES
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Politique d'exécution d'un outil, réglable depuis l'UI
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolPermission {
+    Auto,       // exécuté sans confirmation
+    Confirm,    // suspend la boucle jusqu'à validation utilisateur
+    Deny,       // jamais exécuté, le modèle reçoit un refus
+}
+
+/// Description d'un outil telle qu'exposée au modèle et à l'UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,          // JSON Schema (type: object)
+    pub permission: ToolPermission,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolResult {
+    pub call_id: String,
+    pub name: String,
+    pub success: bool,
+    pub output: Value,
+    pub error: Option<String>,
+    pub latency_ms: u64,
+}
+
+/// Message neutre de la boucle, converti vers le format de chaque provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolConversationMessage {
+    pub role: String,               // system|user|assistant|tool
+    pub content: String,
+    pub tool_calls: Vec<ToolCall>,
+    pub tool_result: Option<ToolResult>,
+}
+
+/// Format d'appel d'outils utilisé pour un provider donné
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCallFormat {
+    Ollama,     // champ `tools` + `message.tool_calls` de /api/chat
+    Gemini,     // `functionDeclarations` + parts `functionCall`
+    JsonMode,   // prompt système + JSON dans le texte (modèles sans outils natifs)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolAdapterConfig {
+    pub format: ToolCallFormat,
+    pub model: String,
+    #[serde(skip_serializing)]
+    pub api_key: Option<String>,
+}
+
+/// Tour de modèle normalisé : texte final et/ou appels d'outils
+#[derive(Debug, Clone)]
+pub struct ModelTurn {
+    pub content: String,
+    pub tool_calls: Vec<ToolCall>,
+}
+
+/// Exécution suspendue en attente de confirmation utilisateur
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingToolRun {
+    pub run_id: String,
+    pub conversation_id: Option<String>,
+    pub adapter: ToolAdapterConfig,
+    pub messages: Vec<ToolConversationMessage>,
+    pub queued_calls: Vec<ToolCall>,
+    pub iterations: u32,
+    pub max_iterations: u32,
+    pub results: Vec<ToolResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ToolRunOutcome {
+    Completed {
+        message: ChatMessage,
+        tool_results: Vec<ToolResult>,
+        iterations: u32,
+    },
+    AwaitingConfirmation {
+        run_id: String,
+        call: ToolCall,
+        tool_results: Vec<ToolResult>,
+    },
+    MaxIterationsReached {
+        message: ChatMessage,
+        tool_results: Vec<ToolResult>,
+        iterations: u32,
+    },
+}
+
+pub type ToolFuture = Pin<Box<dyn Future<Output = Result<Value, String>> + Send>>;
+pub type ToolHandler = Arc<dyn Fn(Value) -> ToolFuture + Send + Sync>;
+
+struct RegisteredTool {
+    definition: ToolDefinition,
+    handler: ToolHandler,
+}
+
+pub struct ToolRegistry {
+    tools: HashMap<String, RegisteredTool>,
+}
+
+/// Appel validé dont le handler a été cloné hors du registre
+pub struct PreparedCall {
+    call: ToolCall,
+    handler: Result<ToolHandler, String>,
+}
+
+/// Tour de modèle : réseau en production, simulé dans les tests
+type TurnFuture<'a> = Pin<Box<dyn Future<Output = Result<ModelTurn, String>> + Send + 'a>>;
+type TurnFn = dyn for<'a> Fn(&'a ToolAdapterConfig, &'a [ToolConversationMessage], &'a [ToolDefinition]) -> TurnFuture<'a>
+    + Send
+    + Sync;
+
+pub struct ToolCallingState {
+    registry: Arc<RwLock<ToolRegistry>>,
+    pending: Arc<RwLock<HashMap<String, PendingToolRun>>>,
+    max_iterations: Arc<RwLock<u32>>,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// REGISTRE
+// ─────────────────────────────────────────────────────────────────────────────
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self {
+            tools: HashMap::new(),
+        }
+    }
+
+    /// Enregistre un outil ; remplace un outil existant du même nom
+    pub fn register<F, Fut>(&mut self, definition: ToolDefinition, handler: F)
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value, String>> + Send + 'static,
+    {
+        let handler: ToolHandler = Arc::new(move |args| -> ToolFuture { Box::pin(handler(args)) });
+        println!("[TOOLS] Outil enregistré: {} ({:?})", definition.name, definition.permission);
+        self.tools.insert(
+            definition.name.clone(),
+            RegisteredTool {
+                definition,
+                handler,
+            },
+        );
+    }
+
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        let mut definitions: Vec<ToolDefinition> =
+            self.tools.values().map(|t| t.definition.clone()).collect();
+        definitions.sort_by(|a, b| a.name.cmp(&b.name));
+        definitions
+    }
+
+    /// Outils visibles par le modèle (les outils refusés ne sont pas proposés)
+    pub fn exposed_definitions(&self) -> Vec<ToolDefinition> {
+        self.definitions()
+            .into_iter()
+            .filter(|d| d.permission != ToolPermission::Deny)
+            .collect()
+    }
+
+    pub fn permission(&self, name: &str) -> Option<ToolPermission> {
+        self.tools.get(name).map(|t| t.definition.permission)
+    }
+
+    pub fn set_permission(&mut self, name: &str, permission: ToolPermission) -> Result<(), String> {
+        let tool = self
+            .tools
+            .get_mut(name)
+            .ok_or_else(|| format!("Outil introuvable: {}", name))?;
+        tool.definition.permission = permission;
+        Ok(())
+    }
+
+    /// Valide les arguments et détache le handler du registre : l'appel peut
+    /// ensuite être attendu sans garder le verrou du registre
+    pub fn prepare(&self, call: &ToolCall) -> PreparedCall {
+        let handler = match self.tools.get(&call.name) {
+            None => Err(format!("Outil inconnu: {}", call.name)),
+            Some(tool) => validate_arguments(&tool.definition.parameters, &call.arguments)
+                .map(|()| tool.handler.clone())
+                .map_err(|e| format!("Arguments invalides pour {}: {}", call.name, e)),
+        };
+        PreparedCall {
+            call: call.clone(),
+            handler,
+        }
+    }
+
+    /// Valide les arguments puis exécute le handler
+    pub async fn execute(&self, call: &ToolCall) -> ToolResult {
+        self.prepare(call).run().await
+    }
+}
+
+impl PreparedCall {
+    pub async fn run(self) -> ToolResult {
+        let start = std::time::Instant::now();
+        let call = self.call;
+
+        let outcome = match self.handler {
+            Ok(handler) => handler(call.arguments.clone()).await,
+            Err(e) => Err(e),
+        };
+
+        let latency_ms = start.elapsed().as_millis() as u64;
+        match outcome {
+            Ok(output) => ToolResult {
+                call_id: call.id,
+                name: call.name,
+                success: true,
+                output,
+                error: None,
+                latency_ms,
+            },
+            Err(e) => {
+                println!("[TOOLS] Échec {} - {}", call.name, e);
+                ToolResult {
+                    call_id: call.id,
+                    name: call.name,
+                    success: false,
+                    output: Value::Null,
+                    error: Some(e),
+                    latency_ms,
+                }
+            }
+        }
+    }
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Validation minimale d'un objet d'arguments contre un schéma JSON
+/// (champs `required`, `type` des propriétés déclarées, `enum`)
+pub fn validate_arguments(schema: &Value, args: &Value) -> Result<(), String> {
+    let args_obj = match args {
+        Value::Object(map) => map,
+        Value::Null => return validate_arguments(schema, &json!({})),
+        _ => return Err("les arguments doivent être un objet JSON".to_string()),
+    };
+
+    if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+        for field in required.iter().filter_map(|f| f.as_str()) {
+            if !args_obj.contains_key(field) {
+                return Err(format!("champ requis manquant: {}", field));
+            }
+        }
+    }
+
+    let properties = match schema.get("properties").and_then(|p| p.as_object()) {
+        Some(p) => p,
+        None => return Ok(()),
+    };
+
+    for (key, value) in args_obj {
+        let prop = match properties.get(key) {
+            Some(p) => p,
+            None => continue,
+        };
+
+        if let Some(expected) = prop.get("type").and_then(|t| t.as_str()) {
+            let matches = match expected {
+                "string" => value.is_string(),
+                "number" => value.is_number(),
+                "integer" => value.is_i64() || value.is_u64(),
+                "boolean" => value.is_boolean(),
+                "array" => value.is_array(),
+                "object" => value.is_object(),
+                _ => true,
+            };
+            if !matches {
+                return Err(format!("'{}' doit être de type {}", key, expected));
+            }
+        }
+
+        if let Some(allowed) = prop.get("enum").and_then(|e| e.as_array()) {
+            if !allowed.contains(value) {
+                return Err(format!("'{}' hors des valeurs autorisées", key));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl ToolCallingState {
+    /// Permission courante et appel préparé ; le verrou est relâché au retour
+    async fn prepare(&self, call: &ToolCall) -> (Option<ToolPermission>, PreparedCall) {
+        let registry = self.registry.read().await;
+        (registry.permission(&call.name), registry.prepare(call))
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// INITIALISATION
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub fn init(
+    memory: &MemoryEngineState,
+    projects: &ProjectAutoPilotState,
+    vault: &SecretsVaultState,
+) -> ToolCallingState {
+    let mut registry = ToolRegistry::new();
+    register_builtin_tools(&mut registry, memory.clone(), projects.clone(), vault.clone());
+
+    ToolCallingState {
+        registry: Arc::new(RwLock::new(registry)),
+        pending: Arc::new(RwLock::new(HashMap::new())),
+        max_iterations: Arc::new(RwLock::new(DEFAULT_MAX_ITERATIONS)),
+    }
+}
+
+fn register_builtin_tools(
+    registry: &mut ToolRegistry,
+    memory: MemoryEngineState,
+    projects: ProjectAutoPilotState,
+    vault: SecretsVaultState,
+) {
+    // Helios : métriques système
+    registry.register(
+        ToolDefinition {
+            name: "helios_get_metrics".to_string(),
+            description: "Lit les métriques système actuelles (CPU, RAM, disque, charge)".to_string(),
+            parameters: json!({ "type": "object", "properties": {} }),
+            permission: ToolPermission::Auto,
+        },
+        |_args| async move {
+            let state = crate::core::HeliosCore::new()
+                .collect()
+                .await
+                .map_err(|e| e.to_string())?;
+            serde_json::to_value(state).map_err(|e| e.to_string())
+        },
+    );
+
+    // Sentinel : alertes
+    registry.register(
+        ToolDefinition {
+            name: "sentinel_get_alerts".to_string(),
+            description: "Lance un scan Sentinel et retourne les alertes actives".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "min_severity": {
+                        "type": "string",
+                        "enum": ["Info", "Warning", "Critical"],
+                        "description": "Sévérité minimale des alertes retournées"
+                    }
+                }
+            }),
+            permission: ToolPermission::Auto,
+        },
+        |args| async move {
+            use crate::types::Severity;
+
+            let rank = |s: &Severity| match s {
+                Severity::Info => 0,
+                Severity::Warning => 1,
+                Severity::Critical => 2,
+            };
+            let min_rank = match args.get("min_severity").and_then(|v| v.as_str()) {
+                Some("Critical") => 2,
+                Some("Warning") => 1,
+                _ => 0,
+            };
+
+            let helios = crate::core::HeliosCore::new()
+                .collect()
+                .await
+                .map_err(|e| e.to_string())?;
+            let sentinel = crate::core::SentinelCore::new()
+                .scan(&helios)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let alerts: Vec<_> = sentinel
+                .alerts
+                .iter()
+                .filter(|a| rank(&a.severity) >= min_rank)
+                .collect();
+
+            Ok(json!({
+                "integrity_score": sentinel.integrity_score,
+                "alerts": alerts,
+            }))
+        },
+    );
+
+    // Mémoire : recherche sémantique
+    registry.register(
+        ToolDefinition {
+            name: "memory_search".to_string(),
+            description: "Recherche dans la mémoire conversationnelle de TITANE".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Texte recherché" },
+                    "limit": { "type": "integer", "description": "Nombre maximal de résultats" }
+                },
+                "required": ["query"]
+            }),
+            permission: ToolPermission::Auto,
+        },
+        move |args| {
+            let memory = memory.clone();
+            async move {
+                let query = MemoryQuery {
+                    query: args["query"].as_str().unwrap_or_default().to_string(),
+                    limit: args.get("limit").and_then(|v| v.as_u64()).unwrap_or(5) as usize,
+                    min_similarity: 0.0,
+                    filters: None,
+                };
+                let results = memory.search(&query).await?;
+                let hits: Vec<Value> = results
+                    .into_iter()
+                    .map(|r| {
+                        json!({
+                            "id": r.entry.id,
+                            "content": r.entry.content,
+                            "similarity": r.similarity,
+                            "type": r.entry.metadata.entry_type,
+                        })
+                    })
+                    .collect();
+                Ok(json!({ "results": hits }))
+            }
+        },
+    );
+
+    // Projets : création de tâche
+    registry.register(
+        ToolDefinition {
+            name: "project_create_task".to_string(),
+            description: "Crée une tâche dans un projet suivi par l'AutoPilot".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "project_id": { "type": "string" },
+                    "title": { "type": "string" },
+                    "description": { "type": "string" }
+                },
+                "required": ["project_id", "title"]
+            }),
+            permission: ToolPermission::Confirm,
+        },
+        move |args| {
+            let projects = projects.clone();
+            async move {
+                let task = projects.create_task(
+                    args["project_id"].as_str().unwrap_or_default().to_string(),
+                    args["title"].as_str().unwrap_or_default().to_string(),
+                    args.get("description")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                )?;
+                serde_json::to_value(task).map_err(|e| e.to_string())
+            }
+        },
+    );
+
+    // Documents : génération
+    registry.register(
+        ToolDefinition {
+            name: "document_generate".to_string(),
+            description: "Génère un document structuré (contrat, SOP, spécification, article...)".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "doc_type": {
+                        "type": "string",
+                        "description": "Type de document, ex: Contract, NDA, SOP, TechnicalSpec, Article"
+                    },
+                    "params": {
+                        "type": "object",
+                        "description": "Paramètres du template (titre, parties, sujet...) ; 'template' choisit un template fichier par id, 'jurisdiction' les règles juridiques (fr, eu…), 'brief' guide la rédaction assistée"
+                    },
+                    "assist": {
+                        "type": "boolean",
+                        "description": "Rédaction et relecture assistées par l'IA (désactivées par défaut)"
+                    }
+                },
+                "required": ["doc_type"]
+            }),
+            permission: ToolPermission::Confirm,
+        },
+        move |args| {
+            let vault = vault.clone();
+            async move {
+                use crate::ai::router::AIRouter;
+                use crate::doc_engine::{
+                    assist::{AiAssistOptions, DocumentAssistant},
+                    generator::DocumentGenerator, legal_rules, template_files, templates::TemplateEngine,
+                    validator::DocumentValidator, DetailLevel, DocumentStyle, DocumentType, GenerationConfig,
+                };
+
+                let doc_type: DocumentType = serde_json::from_value(args["doc_type"].clone())
+                    .map_err(|_| format!("Type de document inconnu: {}", args["doc_type"]))?;
+                let params: HashMap<String, String> = args
+                    .get("params")
+                    .and_then(|p| p.as_object())
+                    .map(|map| {
+                        map.iter()
+                            .map(|(k, v)| {
+                                let value = v.as_str().map(String::from).unwrap_or_else(|| v.to_string());
+                                (k.clone(), value)
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let config = GenerationConfig {
+                    doc_type,
+                    style: DocumentStyle::Professional,
+                    detail_level: DetailLevel::Standard,
+                    tone: "professionnel".to_string(),
+                    language: "fr".to_string(),
+                    custom_params: params
+                        .get("jurisdiction")
+                        .map(|j| HashMap::from([("jurisdiction".to_string(), j.clone())]))
+                        .unwrap_or_default(),
+                };
+
+                let generator = match TemplateEngine::with_directory(template_files::default_templates_dir()) {
+                    Ok(engine) => {
+                        for error in engine.load_errors() {
+                            println!("[TOOLS] ⚠️ Template ignoré: {}", error);
+                        }
+                        DocumentGenerator::with_templates(engine)
+                    }
+                    Err(e) => {
+                        println!("[TOOLS] ⚠️ Templates fichiers indisponibles: {}", e);
+                        DocumentGenerator::new()
+                    }
+                };
+                let generator = match legal_rules::RuleRegistry::with_directory(legal_rules::default_rules_dir()) {
+                    Ok(registry) => {
+                        for error in registry.load_errors() {
+                            println!("[TOOLS] ⚠️ Pack de règles ignoré: {}", error);
+                        }
+                        generator.with_validator(DocumentValidator::new().with_rule_registry(registry))
+                    }
+                    Err(e) => {
+                        println!("[TOOLS] ⚠️ Packs de règles fichiers indisponibles: {}", e);
+                        generator
+                    }
+                };
+                // Assistance IA sur demande explicite : la génération reste déterministe sinon
+                let generator = if args.get("assist").and_then(Value::as_bool).unwrap_or(false) {
+                    let gemini_key = vault.get_secret(&default_secret_name("gemini"), "document_generate").ok();
+                    let router = AIRouter::new(gemini_key, std::env::var("OLLAMA_MODEL").ok());
+                    generator.with_assistant(DocumentAssistant::new(Arc::new(router), AiAssistOptions::default()))
+                } else {
+                    generator
+                };
+
+                let document = generator
+                    .generate(config, params)
+                    .await
+                    .map_err(|e| e.to_string())?;
+
+                Ok(json!({
+                    "id": document.metadata.id,
+                    "title": document.content.title,
+                    "version": document.metadata.version,
+                    "sections": document.content.sections.iter().map(|s| s.title.clone()).collect::<Vec<_>>(),
+                    "is_valid": document.validation_status.is_valid,
+                    "rule_violations": document.validation_status.rule_violations,
+                    "warnings": document.validation_status.warnings,
+                    "ai_contributions": document.metadata.ai_contributions,
+                }))
+            }
+        },
+    );
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// ADAPTATEURS PROVIDERS
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Déclarations d'outils au format Ollama (/api/chat)
+pub fn to_ollama_tools(tools: &[ToolDefinition]) -> Value {
+    Value::Array(
+        tools
+            .iter()
+            .map(|t| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": t.name,
+                        "description": t.description,
+                        "parameters": t.parameters,
+                    }
+                })
+            })
+            .collect(),
+    )
+}
+
+fn to_ollama_messages(messages: &[ToolConversationMessage]) -> Value {
+    Value::Array(
+        messages
+            .iter()
+            .map(|m| {
+                if let Some(result) = &m.tool_result {
+                    return json!({ "role": "tool", "content": result_payload(result).to_string() });
+                }
+                let mut msg = json!({ "role": m.role, "content": m.content });
+                if !m.tool_calls.is_empty() {
+                    msg["tool_calls"] = Value::Array(
+                        m.tool_calls
+                            .iter()
+                            .map(|c| json!({ "function": { "name": c.name, "arguments": c.arguments } }))
+                            .collect(),
+                    );
+                }
+                msg
+            })
+            .collect(),
+    )
+}
+
+/// Extrait les appels d'outils d'une réponse Ollama /api/chat
+pub fn parse_ollama_response(body: &Value) -> ModelTurn {
+    let message = &body["message"];
+    let tool_calls = message["tool_calls"]
+        .as_array()
+        .map(|calls| {
+            calls
+                .iter()
+                .filter_map(|c| {
+                    let function = c.get("function")?;
+                    let name = function.get("name")?.as_str()?.to_string();
+                    // Certains modèles renvoient les arguments sous forme de chaîne JSON
+                    let arguments = match function.get("arguments") {
+                        Some(Value::String(raw)) => serde_json::from_str(raw).unwrap_or(json!({})),
+                        Some(v) => v.clone(),
+                        None => json!({}),
+                    };
+                    Some(ToolCall {
+                        id: uuid::Uuid::new_v4().to_string(),
+                        name,
+                        arguments,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    ModelTurn {
+        content: message["content"].as_str().unwrap_or_default().to_string(),
+        tool_calls,
+    }
+}
+
+/// Déclarations d'outils au format Gemini (functionDeclarations)
+pub fn to_gemini_tools(tools: &[ToolDefinition]) -> Value {
+    let declarations: Vec<Value> = tools
+        .iter()
+        .map(|t| {
+            let mut decl = json!({ "name": t.name, "description": t.description });
+            // Gemini refuse un objet `properties` vide
+            let has_properties = t.parameters["properties"]
+                .as_object()
+                .map(|p| !p.is_empty())
+                .unwrap_or(false);
+            if has_properties {
+                decl["parameters"] = t.parameters.clone();
+            }
+            decl
+        })
+        .collect();
+    json!([{ "functionDeclarations": declarations }])
+}
+
+fn to_gemini_contents(messages: &[ToolConversationMessage]) -> (Option<Value>, Value) {
+    let mut system = None;
+    let mut contents = Vec::new();
+
+    for m in messages {
+        if m.role == "system" {
+            system = Some(json!({ "parts": [{ "text": m.content }] }));
+            continue;
+        }
+        if let Some(result) = &m.tool_result {
+            contents.push(json!({
+                "role": "function",
+                "parts": [{
+                    "functionResponse": { "name": result.name, "response": result_payload(result) }
+                }]
+            }));
+            continue;
+        }
+
+        let role = if m.role == "assistant" { "model" } else { "user" };
+        let mut parts = Vec::new();
+        if !m.content.is_empty() {
+            parts.push(json!({ "text": m.content }));
+        }
+        for call in &m.tool_calls {
+            parts.push(json!({ "functionCall": { "name": call.name, "args": call.arguments } }));
+        }
+        contents.push(json!({ "role": role, "parts": parts }));
+    }
+
+    (system, Value::Array(contents))
+}
+
+/// Extrait texte et functionCall d'une réponse Gemini generateContent
+pub fn parse_gemini_response(body: &Value) -> ModelTurn {
+    let mut content = String::new();
+    let mut tool_calls = Vec::new();
+
+    if let Some(parts) = body["candidates"][0]["content"]["parts"].as_array() {
+        for part in parts {
+            if let Some(text) = part["text"].as_str() {
+                content.push_str(text);
+            }
+            if let Some(name) = part["functionCall"]["name"].as_str() {
+                tool_calls.push(ToolCall {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    name: name.to_string(),
+                    arguments: part["functionCall"]["args"].clone(),
+                });
+            }
+        }
+    }
+
+    ModelTurn { content, tool_calls }
+}
+
+fn result_payload(result: &ToolResult) -> Value {
+    if result.success {
+        json!({ "result": result.output })
+    } else {
+        json!({ "error": result.error })
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// FALLBACK JSON-MODE
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Prompt système décrivant les outils pour un modèle sans support natif
+pub fn json_mode_system_prompt(tools: &[ToolDefinition]) -> String {
+    let mut prompt = String::from(
+        "Tu disposes des outils suivants. Pour en appeler un, réponds UNIQUEMENT avec un objet JSON \
+         de la forme {\"tool_call\": {\"name\": \"<nom>\", \"arguments\": {...}}}. \
+         Sinon, réponds normalement en texte.\n\nOutils disponibles :\n",
+    );
+    for tool in tools {
+        prompt.push_str(&format!(
+            "- {} : {}\n  paramètres : {}\n",
+            tool.name, tool.description, tool.parameters
+        ));
+    }
+    prompt
+}
+
+/// Parse une réponse texte en JSON-mode : détecte un appel d'outil
+/// (éventuellement dans un bloc ```json```), sinon renvoie le texte tel quel.
+/// Seule l'enveloppe `{"tool_call": {...}}` visant un outil proposé compte
+/// comme appel : un objet JSON quelconque dans une réponse reste du texte.
+pub fn parse_json_mode_response(text: &str, tools: &[ToolDefinition]) -> ModelTurn {
+    let candidate = extract_json_object(text);
+
+    let call = candidate
+        .and_then(|raw| serde_json::from_str::<Value>(raw).ok())
+        .and_then(|value| {
+            let call = value.get("tool_call")?;
+            let name = call.get("name")?.as_str()?.to_string();
+            if !tools.iter().any(|tool| tool.name == name) {
+                return None;
+            }
+            let arguments = call
+                .get("arguments")
+                .or_else(|| call.get("parameters"))
+                .cloned()
+                .unwrap_or_else(|| json!({}));
+            Some(ToolCall {
+                id: uuid::Uuid::new_v4().to_string(),
+                name,
+                arguments,
+            })
+        });
+
+    match call {
+        Some(call) => ModelTurn {
+            content: String::new(),
+            tool_calls: vec![call],
+        },
+        None => ModelTurn {
+            content: text.trim().to_string(),
+            tool_calls: vec![],
+        },
+    }
+}
+
+/// Premier objet JSON équilibré du texte (accolades hors chaînes)
+fn extract_json_object(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (offset, ch) in text[start..].char_indices() {
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match ch {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..start + offset + 1]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// APPELS MODÈLE
+// ─────────────────────────────────────────────────────────────────────────────
+
+async fn complete_turn(
+    adapter: &ToolAdapterConfig,
+    messages: &[ToolConversationMessage],
+    tools: &[ToolDefinition],
+) -> Result<ModelTurn, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECONDS))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    match adapter.format {
+        ToolCallFormat::Ollama => {
+            let body = json!({
+                "model": adapter.model,
+                "messages": to_ollama_messages(messages),
+                "tools": to_ollama_tools(tools),
+                "stream": false,
+            });
+            let response = post_json(&client, OLLAMA_CHAT_URL, &body).await?;
+            Ok(parse_ollama_response(&response))
+        }
+        ToolCallFormat::Gemini => {
+            let api_key = adapter
+                .api_key
+                .as_ref()
+                .ok_or("Gemini API key non configurée")?;
+            let (system, contents) = to_gemini_contents(messages);
+            let mut body = json!({
+                "contents": contents,
+                "tools": to_gemini_tools(tools),
+            });
+            if let Some(system) = system {
+                body["systemInstruction"] = system;
+            }
+            let url = format!(
+                "{}/{}:generateContent?key={}",
+                GEMINI_API_BASE, adapter.model, api_key
+            );
+            let response = post_json(&client, &url, &body).await?;
+            Ok(parse_gemini_response(&response))
+        }
+        ToolCallFormat::JsonMode => {
+            // Outils décrits dans le prompt système, résultats renvoyés en texte
+            let mut flattened = vec![ToolConversationMessage {
+                role: "system".to_string(),
+                content: json_mode_system_prompt(tools),
+                tool_calls: vec![],
+                tool_result: None,
+            }];
+            for m in messages {
+                let (role, content) = if let Some(result) = &m.tool_result {
+                    (
+                        "user".to_string(),
+                        format!("Résultat de l'outil {} : {}", result.name, result_payload(result)),
+                    )
+                } else if let Some(call) = m.tool_calls.first() {
+                    (
+                        "assistant".to_string(),
+                        json!({ "tool_call": { "name": call.name, "arguments": call.arguments } }).to_string(),
+                    )
+                } else {
+                    (m.role.clone(), m.content.clone())
+                };
+                flattened.push(ToolConversationMessage {
+                    role,
+                    content,
+                    tool_calls: vec![],
+                    tool_result: None,
+                });
+            }
+
+            let body = json!({
+                "model": adapter.model,
+                "messages": to_ollama_messages(&flattened),
+                "stream": false,
+            });
+            let response = post_json(&client, OLLAMA_CHAT_URL, &body).await?;
+            let text = response["message"]["content"].as_str().unwrap_or_default();
+            Ok(parse_json_mode_response(text, tools))
+        }
+    }
+}
+
+fn network_turn<'a>(
+    adapter: &'a ToolAdapterConfig,
+    messages: &'a [ToolConversationMessage],
+    tools: &'a [ToolDefinition],
+) -> TurnFuture<'a> {
+    Box::pin(complete_turn(adapter, messages, tools))
+}
+
+async fn post_json(client: &reqwest::Client, url: &str, body: &Value) -> Result<Value, String> {
+    let response = client
+        .post(url)
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| format!("Erreur réseau: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Erreur API {}: {}", status, error_text));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Réponse invalide: {}", e))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// BOUCLE D'EXÉCUTION
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Alterne tours de modèle et exécutions d'outils jusqu'à une réponse finale,
+/// une demande de confirmation, ou `max_iterations` tours de modèle
+async fn run_tool_loop(
+    state: &ToolCallingState,
+    mut run: PendingToolRun,
+    complete: &TurnFn,
+) -> Result<ToolRunOutcome, String> {
+    loop {
+        // 1. Exécuter les appels en file (reprise après confirmation ou nouveau tour)
+        while !run.queued_calls.is_empty() {
+            let call = run.queued_calls.remove(0);
+            let (permission, prepared) = state.prepare(&call).await;
+
+            let result = match permission {
+                Some(ToolPermission::Confirm) => {
+                    let run_id = run.run_id.clone();
+                    let tool_results = run.results.clone();
+                    run.queued_calls.insert(0, call.clone());
+                    state.pending.write().await.insert(run_id.clone(), run);
+                    println!("[TOOLS] Confirmation requise: {} ({})", call.name, run_id);
+                    return Ok(ToolRunOutcome::AwaitingConfirmation {
+                        run_id,
+                        call,
+                        tool_results,
+                    });
+                }
+                Some(ToolPermission::Deny) => denied_result(&call, "Outil refusé par la configuration"),
+                _ => prepared.run().await,
+            };
+
+            push_tool_result(&mut run, result);
+        }
+
+        // 2. Limite d'itérations
+        if run.iterations >= run.max_iterations {
+            let message = build_message(
+                &run.adapter,
+                format!(
+                    "Limite de {} itérations d'outils atteinte sans réponse finale.",
+                    run.max_iterations
+                ),
+            );
+            return Ok(ToolRunOutcome::MaxIterationsReached {
+                message,
+                tool_results: run.results,
+                iterations: run.iterations,
+            });
+        }
+
+        // 3. Tour de modèle
+        let tools = state.registry.read().await.exposed_definitions();
+        let turn = complete(&run.adapter, &run.messages, &tools).await?;
+        run.iterations += 1;
+
+        if turn.tool_calls.is_empty() {
+            let message = build_message(&run.adapter, turn.content);
+            return Ok(ToolRunOutcome::Completed {
+                message,
+                tool_results: run.results,
+                iterations: run.iterations,
+            });
+        }
+
+        println!(
+            "[TOOLS] Itération {}: {} appel(s) d'outil",
+            run.iterations,
+            turn.tool_calls.len()
+        );
+        run.messages.push(ToolConversationMessage {
+            role: "assistant".to_string(),
+            content: turn.content,
+            tool_calls: turn.tool_calls.clone(),
+            tool_result: None,
+        });
+        run.queued_calls = turn.tool_calls;
+    }
+}
+
+fn push_tool_result(run: &mut PendingToolRun, result: ToolResult) {
+    run.messages.push(ToolConversationMessage {
+        role: "tool".to_string(),
+        content: String::new(),
+        tool_calls: vec![],
+        tool_result: Some(result.clone()),
+    });
+    run.results.push(result);
+}
+
+fn denied_result(call: &ToolCall, reason: &str) -> ToolResult {
+    ToolResult {
+        call_id: call.id.clone(),
+        name: call.name.clone(),
+        success: false,
+        output: Value::Null,
+        error: Some(reason.to_string()),
+        latency_ms: 0,
+    }
+}
+
+fn build_message(adapter: &ToolAdapterConfig, content: String) -> ChatMessage {
+    let provider = match adapter.format {
+        ToolCallFormat::Gemini => "gemini",
+        _ => "ollama",
+    };
+    ChatMessage {
+        id: uuid::Uuid::new_v4().to_string(),
+        role: "assistant".to_string(),
+        content,
+        timestamp: get_timestamp(),
+        provider: provider.to_string(),
+        model: adapter.model.clone(),
+        tokens: None,
+        multimodal: false,
+    }
+}
+
+async fn finish_run(
+    outcome: &ToolRunOutcome,
+    conversation_id: &Option<String>,
+    chat: &ChatOrchestratorState,
+) {
+    let message = match outcome {
+        ToolRunOutcome::Completed { message, .. } => message,
+        ToolRunOutcome::MaxIterationsReached { message, .. } => message,
+        ToolRunOutcome::AwaitingConfirmation { .. } => return,
+    };
+    if let Some(conv_id) = conversation_id {
+        chat.append_message(conv_id, message).await;
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// COMMANDES TAURI
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Envoie un message avec outils. `request.provider` : gemini | ollama | json
+/// (json = Ollama sans outils natifs, via le parseur JSON-mode)
+#[tauri::command]
+pub async fn chat_send_with_tools(
+    request: ChatRequest,
+    chat: State<'_, ChatOrchestratorState>,
+    state: State<'_, ToolCallingState>,
+) -> Result<ToolRunOutcome, String> {
+    let adapter = match request.provider.as_str() {
+        "gemini" => ToolAdapterConfig {
+            format: ToolCallFormat::Gemini,
+            model: request.model.clone().unwrap_or(DEFAULT_GEMINI_MODEL.to_string()),
+            api_key: chat.gemini_api_key().await,
+        },
+        "json" => ToolAdapterConfig {
+            format: ToolCallFormat::JsonMode,
+            model: request.model.clone().unwrap_or(DEFAULT_OLLAMA_MODEL.to_string()),
+            api_key: None,
+        },
+        _ => ToolAdapterConfig {
+            format: ToolCallFormat::Ollama,
+            model: request.model.clone().unwrap_or(DEFAULT_OLLAMA_MODEL.to_string()),
+            api_key: None,
+        },
+    };
+
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = &request.system_prompt {
+        messages.push(ToolConversationMessage {
+            role: "system".to_string(),
+            content: system_prompt.clone(),
+            tool_calls: vec![],
+            tool_result: None,
+        });
+    }
+    messages.push(ToolConversationMessage {
+        role: "user".to_string(),
+        content: request.message.clone(),
+        tool_calls: vec![],
+        tool_result: None,
+    });
+
+    let run = PendingToolRun {
+        run_id: uuid::Uuid::new_v4().to_string(),
+        conversation_id: request.conversation_id.clone(),
+        adapter,
+        messages,
+        queued_calls: vec![],
+        iterations: 0,
+        max_iterations: *state.max_iterations.read().await,
+        results: vec![],
+    };
+
+    if let Some(conv_id) = &run.conversation_id {
+        let user_message = ChatMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            role: "user".to_string(),
+            content: request.message.clone(),
+            timestamp: get_timestamp(),
+            provider: request.provider.clone(),
+            model: run.adapter.model.clone(),
+            tokens: Some(request.message.split_whitespace().count() as u32),
+            multimodal: false,
+        };
+        chat.append_message(conv_id, &user_message).await;
+    }
+
+    println!("[TOOLS] Run {} via {:?}", run.run_id, run.adapter.format);
+    let conversation_id = run.conversation_id.clone();
+    let outcome = run_tool_loop(&state, run, &network_turn).await?;
+    finish_run(&outcome, &conversation_id, &chat).await;
+    Ok(outcome)
+}
+
+/// Valide ou refuse l'appel en attente d'un run, puis reprend la boucle
+#[tauri::command]
+pub async fn tools_confirm_call(
+    run_id: String,
+    approved: bool,
+    chat: State<'_, ChatOrchestratorState>,
+    state: State<'_, ToolCallingState>,
+) -> Result<ToolRunOutcome, String> {
+    let run = state
+        .pending
+        .write()
+        .await
+        .remove(&run_id)
+        .ok_or_else(|| "Exécution introuvable ou expirée".to_string())?;
+
+    let conversation_id = run.conversation_id.clone();
+    let outcome = resume_run(&state, run, approved, &network_turn).await?;
+    finish_run(&outcome, &conversation_id, &chat).await;
+    Ok(outcome)
+}
+
+/// Applique la décision utilisateur au premier appel en file puis reprend
+async fn resume_run(
+    state: &ToolCallingState,
+    mut run: PendingToolRun,
+    approved: bool,
+    complete: &TurnFn,
+) -> Result<ToolRunOutcome, String> {
+    if run.queued_calls.is_empty() {
+        return Err("Aucun appel en attente pour cette exécution".to_string());
+    }
+    let call = run.queued_calls.remove(0);
+
+    // La permission a pu passer à Deny pendant l'attente de confirmation
+    let (permission, prepared) = state.prepare(&call).await;
+    let result = match permission {
+        Some(ToolPermission::Deny) => denied_result(&call, "Outil refusé par la configuration"),
+        _ if approved => prepared.run().await,
+        _ => denied_result(&call, "Appel refusé par l'utilisateur"),
+    };
+    push_tool_result(&mut run, result);
+
+    run_tool_loop(state, run, complete).await
+}
+
+#[tauri::command]
+pub async fn tools_list(state: State<'_, ToolCallingState>) -> Result<Vec<ToolDefinition>, String> {
+    Ok(state.registry.read().await.definitions())
+}
+
+#[tauri::command]
+pub async fn tools_set_permission(
+    tool_name: String,
+    permission: ToolPermission,
+    state: State<'_, ToolCallingState>,
+) -> Result<String, String> {
+    state
+        .registry
+        .write()
+        .await
+        .set_permission(&tool_name, permission)?;
+    println!("[TOOLS] Permission {} → {:?}", tool_name, permission);
+    Ok(format!("Permission de {} mise à jour", tool_name))
+}
+
+#[tauri::command]
+pub async fn tools_get_pending(
+    state: State<'_, ToolCallingState>,
+) -> Result<Vec<PendingToolRun>, String> {
+    Ok(state.pending.read().await.values().cloned().collect())
+}
+
+#[tauri::command]
+pub async fn tools_set_max_iterations(
+    max_iterations: u32,
+    state: State<'_, ToolCallingState>,
+) -> Result<u32, String> {
+    let value = max_iterations.clamp(1, 20);
+    *state.max_iterations.write().await = value;
+    Ok(value)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// UTILITAIRES
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn get_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn echo_definition(permission: ToolPermission) -> ToolDefinition {
+        ToolDefinition {
+            name: "echo".to_string(),
+            description: "Renvoie le texte".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string" },
+                    "mode": { "type": "string", "enum": ["brut", "majuscules"] }
+                },
+                "required": ["text"]
+            }),
+            permission,
+        }
+    }
+
+    /// Registre avec un outil `echo` qui compte ses exécutions
+    fn registry(permission: ToolPermission) -> (ToolRegistry, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mut registry = ToolRegistry::new();
+        registry.register(echo_definition(permission), move |args| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(json!({ "echo": args["text"] }))
+            }
+        });
+        (registry, calls)
+    }
+
+    fn test_state(permission: ToolPermission) -> (ToolCallingState, Arc<AtomicUsize>) {
+        let (registry, calls) = registry(permission);
+        let state = ToolCallingState {
+            registry: Arc::new(RwLock::new(registry)),
+            pending: Arc::new(RwLock::new(HashMap::new())),
+            max_iterations: Arc::new(RwLock::new(DEFAULT_MAX_ITERATIONS)),
+        };
+        (state, calls)
+    }
+
+    fn echo_call(id: &str) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            name: "echo".to_string(),
+            arguments: json!({ "text": "bonjour" }),
+        }
+    }
+
+    fn run_with(queued_calls: Vec<ToolCall>, max_iterations: u32) -> PendingToolRun {
+        PendingToolRun {
+            run_id: "run-1".to_string(),
+            conversation_id: None,
+            adapter: ToolAdapterConfig {
+                format: ToolCallFormat::Ollama,
+                model: DEFAULT_OLLAMA_MODEL.to_string(),
+                api_key: None,
+            },
+            messages: vec![],
+            queued_calls,
+            iterations: 0,
+            max_iterations,
+            results: vec![],
+        }
+    }
+
+    fn final_answer<'a>(
+        _adapter: &'a ToolAdapterConfig,
+        _messages: &'a [ToolConversationMessage],
+        _tools: &'a [ToolDefinition],
+    ) -> TurnFuture<'a> {
+        Box::pin(async {
+            Ok(ModelTurn {
+                content: "Terminé".to_string(),
+                tool_calls: vec![],
+            })
+        })
+    }
+
+    /// Modèle qui redemande l'outil à chaque tour
+    fn always_echo<'a>(
+        _adapter: &'a ToolAdapterConfig,
+        messages: &'a [ToolConversationMessage],
+        _tools: &'a [ToolDefinition],
+    ) -> TurnFuture<'a> {
+        let id = format!("call-{}", messages.len());
+        Box::pin(async move {
+            Ok(ModelTurn {
+                content: String::new(),
+                tool_calls: vec![echo_call(&id)],
+            })
+        })
+    }
+
+    #[test]
+    fn definitions_are_sorted_and_deny_is_hidden() {
+        let (mut registry, _) = registry(ToolPermission::Auto);
+        registry.register(
+            ToolDefinition {
+                name: "alpha".to_string(),
+                description: String::new(),
+                parameters: json!({ "type": "object" }),
+                permission: ToolPermission::Deny,
+            },
+            |_args| async { Ok(Value::Null) },
+        );
+
+        let names: Vec<String> = registry.definitions().into_iter().map(|d| d.name).collect();
+        assert_eq!(names, vec!["alpha", "echo"]);
+        let exposed: Vec<String> = registry.exposed_definitions().into_iter().map(|d| d.name).collect();
+        assert_eq!(exposed, vec!["echo"]);
+
+        assert!(registry.set_permission("inconnu", ToolPermission::Auto).is_err());
+        registry.set_permission("alpha", ToolPermission::Confirm).unwrap();
+        assert_eq!(registry.permission("alpha"), Some(ToolPermission::Confirm));
+    }
+
+    #[test]
+    fn arguments_are_validated_against_schema() {
+        let schema = echo_definition(ToolPermission::Auto).parameters;
+        assert!(validate_arguments(&schema, &json!({ "text": "ok" })).is_ok());
+        assert!(validate_arguments(&schema, &json!({})).unwrap_err().contains("text"));
+        assert!(validate_arguments(&schema, &json!({ "text": 3 })).is_err());
+        assert!(validate_arguments(&schema, &json!({ "text": "ok", "mode": "gras" })).is_err());
+        assert!(validate_arguments(&schema, &json!(["text"])).is_err());
+        assert!(validate_arguments(&json!({ "type": "object" }), &Value::Null).is_ok());
+    }
+
+    #[test]
+    fn json_mode_only_accepts_wrapped_calls_to_known_tools() {
+        let tools = vec![echo_definition(ToolPermission::Auto)];
+
+        let turn = parse_json_mode_response(
+            "```json\n{\"tool_call\": {\"name\": \"echo\", \"arguments\": {\"text\": \"salut\"}}}\n```",
+            &tools,
+        );
+        assert_eq!(turn.tool_calls.len(), 1);
+        assert_eq!(turn.tool_calls[0].name, "echo");
+        assert_eq!(turn.tool_calls[0].arguments, json!({ "text": "salut" }));
+
+        let answer = "Voici la fiche : {\"name\": \"Alice\", \"arguments\": {\"age\": 30}}";
+        let turn = parse_json_mode_response(answer, &tools);
+        assert!(turn.tool_calls.is_empty());
+        assert_eq!(turn.content, answer);
+
+        let turn = parse_json_mode_response("{\"tool_call\": {\"name\": \"inconnu\"}}", &tools);
+        assert!(turn.tool_calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn execute_reports_unknown_tools_and_bad_arguments() {
+        let (registry, calls) = registry(ToolPermission::Auto);
+
+        let ok = registry.execute(&echo_call("1")).await;
+        assert!(ok.success);
+        assert_eq!(ok.output, json!({ "echo": "bonjour" }));
+
+        let unknown = registry
+            .execute(&ToolCall {
+                id: "2".to_string(),
+                name: "absent".to_string(),
+                arguments: json!({}),
+            })
+            .await;
+        assert!(!unknown.success);
+
+        let invalid = registry
+            .execute(&ToolCall {
+                id: "3".to_string(),
+                name: "echo".to_string(),
+                arguments: json!({ "text": 1 }),
+            })
+            .await;
+        assert!(!invalid.success);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn auto_tools_run_and_denied_tools_do_not() {
+        let (state, calls) = test_state(ToolPermission::Auto);
+        let outcome = run_tool_loop(&state, run_with(vec![echo_call("1")], 5), &final_answer)
+            .await
+            .unwrap();
+        match outcome {
+            ToolRunOutcome::Completed { tool_results, iterations, .. } => {
+                assert_eq!(iterations, 1);
+                assert!(tool_results[0].success);
+            }
+            other => panic!("issue inattendue: {:?}", other),
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let (state, calls) = test_state(ToolPermission::Deny);
+        let outcome = run_tool_loop(&state, run_with(vec![echo_call("1")], 5), &final_answer)
+            .await
+            .unwrap();
+        match outcome {
+            ToolRunOutcome::Completed { tool_results, .. } => assert!(!tool_results[0].success),
+            other => panic!("issue inattendue: {:?}", other),
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn confirm_suspends_then_resumes() {
+        let (state, calls) = test_state(ToolPermission::Confirm);
+        let outcome = run_tool_loop(&state, run_with(vec![echo_call("1")], 5), &final_answer)
+            .await
+            .unwrap();
+        assert!(matches!(outcome, ToolRunOutcome::AwaitingConfirmation { .. }));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let run = state.pending.write().await.remove("run-1").unwrap();
+        let outcome = resume_run(&state, run, true, &final_answer).await.unwrap();
+        assert!(matches!(outcome, ToolRunOutcome::Completed { .. }));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn confirmation_rechecks_permission() {
+        let (state, calls) = test_state(ToolPermission::Confirm);
+        run_tool_loop(&state, run_with(vec![echo_call("1")], 5), &final_answer)
+            .await
+            .unwrap();
+
+        // Outil interdit entre la demande et la validation
+        state
+            .registry
+            .write()
+            .await
+            .set_permission("echo", ToolPermission::Deny)
+            .unwrap();
+
+        let run = state.pending.write().await.remove("run-1").unwrap();
+        let outcome = resume_run(&state, run, true, &final_answer).await.unwrap();
+        match outcome {
+            ToolRunOutcome::Completed { tool_results, .. } => {
+                assert!(!tool_results[0].success);
+                assert_eq!(tool_results[0].error.as_deref(), Some("Outil refusé par la configuration"));
+            }
+            other => panic!("issue inattendue: {:?}", other),
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn rejected_call_is_not_executed() {
+        let (state, calls) = test_state(ToolPermission::Confirm);
+        let run = run_with(vec![echo_call("1")], 5);
+        let outcome = resume_run(&state, run, false, &final_answer).await.unwrap();
+        match outcome {
+            ToolRunOutcome::Completed { tool_results, .. } => assert!(!tool_results[0].success),
+            other => panic!("issue inattendue: {:?}", other),
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        assert!(resume_run(&state, run_with(vec![], 5), true, &final_answer).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn loop_stops_at_max_iterations() {
+        let (state, calls) = test_state(ToolPermission::Auto);
+        let outcome = run_tool_loop(&state, run_with(vec![], 3), &always_echo)
+            .await
+            .unwrap();
+        match outcome {
+            ToolRunOutcome::MaxIterationsReached { iterations, tool_results, .. } => {
+                assert_eq!(iterations, 3);
+                assert_eq!(tool_results.len(), 3);
+            }
+            other => panic!("issue inattendue: {:?}", other),
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
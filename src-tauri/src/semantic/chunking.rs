@@ -0,0 +1,1039 @@
+// TITANE∞ v13 - Chunking Strategies
+// Découpage par type de document : code (items de premier niveau avec chemin
+// de symbole) et Markdown (blocs de code et tableaux jamais coupés)
+
+use crate::semantic::indexer::IndexerConfig;
+use crate::semantic::DocumentChunk;
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Séparateur des chemins de symboles (`module::Type::method`)
+pub const SYMBOL_SEPARATOR: &str = "::";
+
+/// Contexte fourni à une stratégie
+pub struct ChunkContext<'a> {
+    pub title: &'a str,
+    pub config: &'a IndexerConfig,
+}
+
+/// Stratégie de découpage enregistrée pour un type de document
+pub trait ChunkStrategy: Send + Sync {
+    fn chunk(&self, content: &str, context: &ChunkContext) -> Vec<DocumentChunk>;
+}
+
+/// Stratégies par défaut, indexées par `doc_type`
+pub fn default_strategies() -> HashMap<String, Arc<dyn ChunkStrategy>> {
+    let mut strategies: HashMap<String, Arc<dyn ChunkStrategy>> = HashMap::new();
+
+    let markdown: Arc<dyn ChunkStrategy> = Arc::new(MarkdownChunker);
+    let rust: Arc<dyn ChunkStrategy> = Arc::new(CodeChunker::new(Language::Rust));
+    let typescript: Arc<dyn ChunkStrategy> = Arc::new(CodeChunker::new(Language::TypeScript));
+    let python: Arc<dyn ChunkStrategy> = Arc::new(CodeChunker::new(Language::Python));
+
+    for key in ["markdown", "md"] {
+        strategies.insert(key.to_string(), markdown.clone());
+    }
+    for key in ["rust", "rs"] {
+        strategies.insert(key.to_string(), rust.clone());
+    }
+    for key in ["typescript", "ts", "tsx", "javascript", "js", "jsx"] {
+        strategies.insert(key.to_string(), typescript.clone());
+    }
+    for key in ["python", "py"] {
+        strategies.insert(key.to_string(), python.clone());
+    }
+
+    strategies
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Table des lignes (offsets en octets)
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct Source<'a> {
+    text: &'a str,
+    lines: Vec<&'a str>,
+    starts: Vec<usize>,
+}
+
+impl<'a> Source<'a> {
+    fn new(text: &'a str) -> Self {
+        let lines: Vec<&str> = text.split('\n').collect();
+        let mut starts = Vec::with_capacity(lines.len());
+        let mut offset = 0;
+        for line in &lines {
+            starts.push(offset);
+            offset += line.len() + 1;
+        }
+        Self { text, lines, starts }
+    }
+
+    fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Plage d'octets des lignes `first..=last`
+    fn span(&self, first: usize, last: usize) -> (usize, usize) {
+        (self.starts[first], self.starts[last] + self.lines[last].len())
+    }
+
+    fn span_len(&self, first: usize, last: usize) -> usize {
+        let (start, end) = self.span(first, last);
+        end - start
+    }
+
+    fn chunk(&self, first: usize, last: usize, section_title: Option<String>, symbol_path: Option<String>) -> DocumentChunk {
+        let (start, end) = self.span(first, last);
+        make_chunk(&self.text[start..end], start, end, section_title, symbol_path)
+    }
+
+    /// Découpe `first..=last` en morceaux de lignes entières d'au plus `max` octets
+    fn split_lines(&self, first: usize, last: usize, max: usize) -> Vec<(usize, usize)> {
+        let mut parts = Vec::new();
+        let mut part_start = first;
+        for line in first..=last {
+            if line > part_start && self.span_len(part_start, line) > max {
+                parts.push((part_start, line - 1));
+                part_start = line;
+            }
+        }
+        parts.push((part_start, last));
+        parts
+    }
+}
+
+fn make_chunk(
+    content: &str,
+    start_pos: usize,
+    end_pos: usize,
+    section_title: Option<String>,
+    symbol_path: Option<String>,
+) -> DocumentChunk {
+    DocumentChunk {
+        id: Uuid::new_v4().to_string(),
+        content: content.trim().to_string(),
+        start_pos,
+        end_pos,
+        embedding: Vec::new(),
+        section_title,
+        symbol_path,
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Code
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    /// TypeScript et JavaScript
+    TypeScript,
+    Python,
+}
+
+/// Nature d'un conteneur dont on découpe aussi le contenu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    Module,
+    Type,
+}
+
+/// Item repéré : lignes `start..=end`, corps `body.0..body.1` pour un conteneur
+#[derive(Debug, Clone)]
+struct Item {
+    name: String,
+    container: Option<Scope>,
+    start: usize,
+    end: usize,
+    body: Option<(usize, usize)>,
+}
+
+/// Source en cours de découpage
+struct Walk<'a> {
+    source: &'a Source<'a>,
+    code: &'a [CodeLine],
+    config: &'a IndexerConfig,
+}
+
+/// Ligne débarrassée des commentaires et du contenu des chaînes
+struct CodeLine {
+    code: String,
+    starts_in_string: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Lex {
+    Code,
+    BlockComment,
+    Str { quote: char, triple: bool },
+}
+
+/// Découpe du code en items de premier niveau (fonctions, impls, classes, modules)
+pub struct CodeChunker {
+    language: Language,
+    patterns: Vec<(Regex, Option<Scope>)>,
+    member_patterns: Vec<(Regex, Option<Scope>)>,
+}
+
+const TS_MEMBER_KEYWORDS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "return", "function", "else", "do", "new", "typeof", "await", "super",
+];
+
+impl CodeChunker {
+    pub fn new(language: Language) -> Self {
+        let compile = |specs: &[(&str, Option<Scope>)]| -> Vec<(Regex, Option<Scope>)> {
+            specs
+                .iter()
+                .map(|(pattern, scope)| (Regex::new(pattern).expect("motif de chunking invalide"), *scope))
+                .collect()
+        };
+
+        let (patterns, member_patterns) = match language {
+            Language::Rust => (
+                compile(&[
+                    (
+                        r#"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:const|async|unsafe|default|extern\s*"[^"]*"|extern)\s+)*fn\s+(?P<name>[A-Za-z_]\w*)"#,
+                        None,
+                    ),
+                    (r"^\s*(?:unsafe\s+)?impl\b(?P<impl>.*)", Some(Scope::Type)),
+                    (
+                        r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:unsafe\s+)?trait\s+(?P<name>[A-Za-z_]\w*)",
+                        Some(Scope::Type),
+                    ),
+                    (r"^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+(?P<name>[A-Za-z_]\w*)", Some(Scope::Module)),
+                    (r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:struct|enum|union)\s+(?P<name>[A-Za-z_]\w*)", None),
+                    (r"^\s*macro_rules!\s*(?P<name>[A-Za-z_]\w*)", None),
+                ]),
+                Vec::new(),
+            ),
+            Language::TypeScript => (
+                compile(&[
+                    (
+                        r"^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?class\s+(?P<name>[A-Za-z_$][\w$]*)",
+                        Some(Scope::Type),
+                    ),
+                    (
+                        r"^\s*(?:export\s+)?(?:declare\s+)?(?:namespace|module)\s+(?P<name>[A-Za-z_$][\w$.]*)",
+                        Some(Scope::Module),
+                    ),
+                    (
+                        r"^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:const\s+)?(?:interface|enum)\s+(?P<name>[A-Za-z_$][\w$]*)",
+                        None,
+                    ),
+                    (
+                        r"^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:async\s+)?function\s*\*?\s*(?P<name>[A-Za-z_$][\w$]*)",
+                        None,
+                    ),
+                    (
+                        r"^\s*(?:export\s+)?(?:const|let|var)\s+(?P<name>[A-Za-z_$][\w$]*)\s*(?::[^=]*)?=\s*(?:async\s+)?(?:function\b|\(|[A-Za-z_$][\w$]*\s*=>)",
+                        None,
+                    ),
+                    (r"^\s*(?:export\s+)?type\s+(?P<name>[A-Za-z_$][\w$]*)\s*(?:<[^=]*>)?\s*=", None),
+                ]),
+                compile(&[
+                    (
+                        r"^\s*(?:(?:public|private|protected|static|async|readonly|override|abstract|get|set)\s+)*\*?(?P<name>[A-Za-z_$#][\w$]*)\s*(?:<[^>]*>)?\s*\(",
+                        None,
+                    ),
+                    (
+                        r"^\s*(?:(?:public|private|protected|static|readonly)\s+)*(?P<name>[A-Za-z_$#][\w$]*)\s*(?::[^=]*)?=\s*(?:async\s+)?(?:\(|[A-Za-z_$][\w$]*\s*=>)",
+                        None,
+                    ),
+                ]),
+            ),
+            Language::Python => (
+                compile(&[
+                    (r"^\s*(?:async\s+)?def\s+(?P<name>\w+)", None),
+                    (r"^\s*class\s+(?P<name>\w+)", Some(Scope::Type)),
+                ]),
+                Vec::new(),
+            ),
+        };
+
+        Self {
+            language,
+            patterns,
+            member_patterns,
+        }
+    }
+
+    /// En-tête d'item : nom et éventuelle portée de conteneur
+    fn match_header(&self, code: &str, scope: Option<Scope>) -> Option<(String, Option<Scope>)> {
+        let patterns = if scope == Some(Scope::Type) && !self.member_patterns.is_empty() {
+            &self.member_patterns
+        } else {
+            &self.patterns
+        };
+
+        for (regex, container) in patterns {
+            let captures = match regex.captures(code) {
+                Some(c) => c,
+                None => continue,
+            };
+            if let Some(target) = captures.name("impl") {
+                return impl_target(target.as_str()).map(|name| (name, *container));
+            }
+            let name = captures.name("name")?.as_str();
+            if self.language == Language::TypeScript && TS_MEMBER_KEYWORDS.contains(&name) {
+                return None;
+            }
+            return Some((name.to_string(), *container));
+        }
+        None
+    }
+
+    /// Commentaires, attributs et décorateurs rattachés à l'item qui suit
+    fn is_leading(&self, raw: &str) -> bool {
+        let line = raw.trim_start();
+        match self.language {
+            Language::Rust => ["//", "#[", "/*", "*"].iter().any(|p| line.starts_with(p)),
+            Language::TypeScript => ["//", "/*", "*", "@"].iter().any(|p| line.starts_with(p)),
+            Language::Python => line.starts_with('#') || line.starts_with('@'),
+        }
+    }
+
+    fn strip_code(&self, lines: &[&str]) -> Vec<CodeLine> {
+        let mut state = Lex::Code;
+        let mut out = Vec::with_capacity(lines.len());
+
+        for line in lines {
+            let starts_in_string = matches!(state, Lex::Str { .. });
+            let chars: Vec<char> = line.chars().collect();
+            let mut code = String::new();
+            let mut i = 0;
+
+            while i < chars.len() {
+                let c = chars[i];
+                let next = chars.get(i + 1).copied();
+                match state {
+                    Lex::BlockComment => {
+                        if c == '*' && next == Some('/') {
+                            state = Lex::Code;
+                            i += 2;
+                        } else {
+                            i += 1;
+                        }
+                    }
+                    Lex::Str { quote, triple } => {
+                        if c == '\\' {
+                            i += 2;
+                        } else if c == quote
+                            && (!triple || (next == Some(quote) && chars.get(i + 2) == Some(&quote)))
+                        {
+                            code.push(quote);
+                            state = Lex::Code;
+                            i += if triple { 3 } else { 1 };
+                        } else {
+                            i += 1;
+                        }
+                    }
+                    Lex::Code => {
+                        if self.language == Language::Python && c == '#' {
+                            break;
+                        }
+                        if self.language != Language::Python && c == '/' && next == Some('/') {
+                            break;
+                        }
+                        if self.language != Language::Python && c == '/' && next == Some('*') {
+                            state = Lex::BlockComment;
+                            i += 2;
+                            continue;
+                        }
+                        if self.language == Language::Rust && c == '\'' {
+                            // Littéral caractère ('{', '\n') ou durée de vie ('a)
+                            if next == Some('\\') {
+                                let close = (i + 2..chars.len()).find(|&j| chars[j] == '\'');
+                                i = close.map(|j| j + 1).unwrap_or(chars.len());
+                                code.push_str("''");
+                            } else if chars.get(i + 2) == Some(&'\'') {
+                                i += 3;
+                                code.push_str("''");
+                            } else {
+                                code.push(c);
+                                i += 1;
+                            }
+                            continue;
+                        }
+                        let is_quote = match self.language {
+                            Language::Rust => c == '"',
+                            Language::TypeScript => matches!(c, '"' | '\'' | '`'),
+                            Language::Python => matches!(c, '"' | '\''),
+                        };
+                        if is_quote {
+                            let triple = self.language == Language::Python
+                                && next == Some(c)
+                                && chars.get(i + 2) == Some(&c);
+                            code.push(c);
+                            state = Lex::Str { quote: c, triple };
+                            i += if triple { 3 } else { 1 };
+                        } else {
+                            code.push(c);
+                            i += 1;
+                        }
+                    }
+                }
+            }
+
+            // Les chaînes simples Python et TS ne traversent pas les lignes
+            if let Lex::Str { quote, triple: false } = state {
+                if self.language == Language::Python || (self.language == Language::TypeScript && quote != '`') {
+                    state = Lex::Code;
+                }
+            }
+
+            out.push(CodeLine { code, starts_in_string });
+        }
+
+        out
+    }
+
+    fn scan_items(&self, source: &Source, code: &[CodeLine], from: usize, to: usize, scope: Option<Scope>) -> Vec<Item> {
+        let mut items = match self.language {
+            Language::Python => self.indent_items(source, code, from, to),
+            _ => self.brace_items(source, code, from, to, scope),
+        };
+
+        // Rattache commentaires et attributs contigus situés juste au-dessus
+        let mut floor = from;
+        for item in &mut items {
+            while item.start > floor && self.is_leading(source.lines[item.start - 1]) {
+                item.start -= 1;
+            }
+            floor = item.end + 1;
+        }
+        items
+    }
+
+    /// Langages à accolades : équilibrage des `{}` hors chaînes et commentaires
+    fn brace_items(&self, source: &Source, code: &[CodeLine], from: usize, to: usize, scope: Option<Scope>) -> Vec<Item> {
+        let mut items = Vec::new();
+        let mut depth: i32 = 0;
+        let mut i = from;
+
+        while i < to {
+            if depth == 0 {
+                if let Some((name, container)) = self.match_header(&code[i].code, scope) {
+                    let (end, open_line) = find_brace_end(source, code, i, to);
+                    let body = match (container, open_line) {
+                        (Some(_), Some(open)) if code[open].code.trim_end().ends_with('{') && end > open => {
+                            Some((open + 1, end))
+                        }
+                        _ => None,
+                    };
+                    items.push(Item {
+                        name,
+                        container,
+                        start: i,
+                        end,
+                        body,
+                    });
+                    i = end + 1;
+                    continue;
+                }
+            }
+            depth = (depth + brace_delta(&code[i].code)).max(0);
+            i += 1;
+        }
+
+        items
+    }
+
+    /// Python : l'item s'étend tant que les lignes sont plus indentées que l'en-tête
+    fn indent_items(&self, source: &Source, code: &[CodeLine], from: usize, to: usize) -> Vec<Item> {
+        let mut items = Vec::new();
+        let base = match (from..to).find(|&i| !code[i].code.trim().is_empty() && !code[i].starts_in_string) {
+            Some(first) => indentation(source.lines[first]),
+            None => return items,
+        };
+        let mut i = from;
+
+        while i < to {
+            let line = &code[i];
+            let header = if !line.starts_in_string && indentation(source.lines[i]) == base {
+                self.match_header(&line.code, None)
+            } else {
+                None
+            };
+
+            let (name, container) = match header {
+                Some(h) => h,
+                None => {
+                    i += 1;
+                    continue;
+                }
+            };
+
+            // Fin de l'en-tête : parenthèses équilibrées et `:` final
+            let mut balance = 0;
+            let mut header_end = i;
+            for (j, line) in code.iter().enumerate().take(to).skip(i) {
+                balance += paren_delta(&line.code);
+                header_end = j;
+                if balance <= 0 && line.code.trim_end().ends_with(':') {
+                    break;
+                }
+            }
+
+            let mut end = header_end;
+            for (j, line) in code.iter().enumerate().take(to).skip(header_end + 1) {
+                let raw = source.lines[j];
+                if raw.trim().is_empty() {
+                    continue;
+                }
+                if line.starts_in_string || indentation(raw) > base {
+                    end = j;
+                } else {
+                    break;
+                }
+            }
+
+            let body = container.filter(|_| end > header_end).map(|_| (header_end + 1, end + 1));
+            items.push(Item {
+                name,
+                container,
+                start: i,
+                end,
+                body,
+            });
+            i = end + 1;
+        }
+
+        items
+    }
+
+    /// Émet les items de `inner` et les lignes restantes de `outer` (en-tête, champs, imports)
+    fn emit(&self, walk: &Walk, outer: (usize, usize), items: &[Item], path: &[String], out: &mut Vec<DocumentChunk>) {
+        let is_container = !path.is_empty() && outer != (0, walk.source.len());
+        let mut cursor = outer.0;
+
+        for item in items {
+            if item.start > cursor {
+                self.emit_gap(walk, (cursor, item.start - 1), path, is_container && cursor == outer.0, out);
+            }
+
+            let mut item_path = path.to_vec();
+            item_path.push(item.name.clone());
+
+            let children = item
+                .body
+                .map(|(from, to)| self.scan_items(walk.source, walk.code, from, to, item.container))
+                .unwrap_or_default();
+
+            if children.is_empty() {
+                self.emit_span(walk, (item.start, item.end), &item_path, out);
+            } else {
+                self.emit(walk, (item.start, item.end + 1), &children, &item_path, out);
+            }
+            cursor = item.end + 1;
+        }
+
+        if cursor < outer.1 {
+            self.emit_gap(walk, (cursor, outer.1 - 1), path, false, out);
+        }
+    }
+
+    /// Lignes hors items (`lines` inclusif)
+    fn emit_gap(
+        &self,
+        walk: &Walk,
+        lines: (usize, usize),
+        path: &[String],
+        container_header: bool,
+        out: &mut Vec<DocumentChunk>,
+    ) {
+        let meaningful = (lines.0..=lines.1)
+            .filter(|&i| {
+                let line = walk.source.lines[i].trim();
+                !line.is_empty() && !matches!(line, "{" | "}" | "};" | "})" | ")" | "});")
+            })
+            .count();
+        // Un en-tête de conteneur seul (`impl Foo {`) n'apporte rien de plus que ses méthodes
+        let required = if container_header { 2 } else { 1 };
+        if meaningful >= required {
+            self.emit_span(walk, lines, path, out);
+        }
+    }
+
+    /// Chunk(s) d'un symbole, coupé par lignes s'il dépasse `max_chunk_size`
+    fn emit_span(&self, walk: &Walk, lines: (usize, usize), path: &[String], out: &mut Vec<DocumentChunk>) {
+        let symbol = if path.is_empty() {
+            None
+        } else {
+            Some(path.join(SYMBOL_SEPARATOR))
+        };
+        for (first, last) in walk.source.split_lines(lines.0, lines.1, walk.config.max_chunk_size) {
+            out.push(walk.source.chunk(first, last, symbol.clone(), symbol.clone()));
+        }
+    }
+}
+
+impl ChunkStrategy for CodeChunker {
+    fn chunk(&self, content: &str, context: &ChunkContext) -> Vec<DocumentChunk> {
+        let source = Source::new(content);
+        let code = self.strip_code(&source.lines);
+        let items = self.scan_items(&source, &code, 0, source.len(), None);
+        let path: Vec<String> = module_name(context.title).into_iter().collect();
+
+        let walk = Walk {
+            source: &source,
+            code: &code,
+            config: context.config,
+        };
+        let mut chunks = Vec::new();
+        self.emit(&walk, (0, source.len()), &items, &path, &mut chunks);
+        chunks.retain(|c| !c.content.is_empty());
+        chunks
+    }
+}
+
+/// Ligne de fin d'un item et ligne de son accolade ouvrante
+fn find_brace_end(source: &Source, code: &[CodeLine], start: usize, to: usize) -> (usize, Option<usize>) {
+    let mut depth = 0;
+    let mut open_line = None;
+
+    for (j, line) in code.iter().enumerate().take(to).skip(start) {
+        for c in line.code.chars() {
+            match c {
+                '{' => {
+                    depth += 1;
+                    open_line.get_or_insert(j);
+                }
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+
+        if open_line.is_some() {
+            if depth <= 0 {
+                return (j, open_line);
+            }
+        } else if line.code.trim_end().ends_with(';') {
+            return (j, None);
+        } else if j + 1 < to && source.lines[j + 1].trim().is_empty() {
+            // Fonction fléchée sans point-virgule
+            return (j, None);
+        }
+    }
+
+    (to.saturating_sub(1).max(start), open_line)
+}
+
+fn brace_delta(code: &str) -> i32 {
+    code.chars()
+        .map(|c| match c {
+            '{' => 1,
+            '}' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+fn paren_delta(code: &str) -> i32 {
+    code.chars()
+        .map(|c| match c {
+            '(' | '[' | '{' => 1,
+            ')' | ']' | '}' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+fn indentation(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Cible d'un `impl` : `impl<T> Trait for Type<T> where ...` → `Type`
+fn impl_target(rest: &str) -> Option<String> {
+    let mut rest = rest.trim_start();
+    if rest.starts_with('<') {
+        let mut depth = 0;
+        for (i, c) in rest.char_indices() {
+            match c {
+                '<' => depth += 1,
+                '>' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        rest = &rest[i + 1..];
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let rest = rest.split('{').next()?.split(" where").next()?;
+    let target = rest.rsplit(" for ").next()?.trim();
+    let target = target.split('<').next()?.trim_start_matches(['&', '(']);
+    let name = target.rsplit("::").next()?.trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Nom de module déduit du nom de fichier (`indexer.rs` → `indexer`)
+fn module_name(title: &str) -> Option<String> {
+    let (stem, extension) = title.rsplit_once('.')?;
+    let stem = stem.rsplit(['/', '\\']).next()?;
+    if extension.is_empty() || matches!(stem, "mod" | "lib" | "main" | "index" | "__init__") {
+        return None;
+    }
+    if !stem.is_empty() && stem.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+        Some(stem.replace('-', "_"))
+    } else {
+        None
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Markdown
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BlockKind {
+    Heading(String),
+    Fence,
+    Table,
+    Paragraph,
+}
+
+#[derive(Debug, Clone)]
+struct Block {
+    kind: BlockKind,
+    first: usize,
+    last: usize,
+}
+
+/// Découpe Markdown par sections ; les blocs de code et tableaux restent entiers
+pub struct MarkdownChunker;
+
+impl MarkdownChunker {
+    fn blocks(source: &Source) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        let mut i = 0;
+
+        while i < source.len() {
+            let line = source.lines[i].trim();
+            if line.is_empty() {
+                i += 1;
+                continue;
+            }
+
+            if let Some(fence) = fence_marker(line) {
+                let last = (i + 1..source.len())
+                    .find(|&j| {
+                        let l = source.lines[j].trim();
+                        l.starts_with(fence.as_str()) && l.trim_start_matches(fence.chars().next().unwrap()).trim().is_empty()
+                    })
+                    .unwrap_or(source.len() - 1);
+                blocks.push(Block { kind: BlockKind::Fence, first: i, last });
+                i = last + 1;
+            } else if let Some(title) = heading_title(line) {
+                blocks.push(Block {
+                    kind: BlockKind::Heading(title),
+                    first: i,
+                    last: i,
+                });
+                i += 1;
+            } else if is_table_row(line) {
+                let mut last = i;
+                while last + 1 < source.len() && is_table_row(source.lines[last + 1].trim()) {
+                    last += 1;
+                }
+                blocks.push(Block { kind: BlockKind::Table, first: i, last });
+                i = last + 1;
+            } else {
+                let mut last = i;
+                while last + 1 < source.len() {
+                    let next = source.lines[last + 1].trim();
+                    if next.is_empty() || fence_marker(next).is_some() || heading_title(next).is_some() || is_table_row(next) {
+                        break;
+                    }
+                    last += 1;
+                }
+                blocks.push(Block {
+                    kind: BlockKind::Paragraph,
+                    first: i,
+                    last,
+                });
+                i = last + 1;
+            }
+        }
+
+        blocks
+    }
+
+    /// Bloc trop grand : coupé par lignes, en répétant la clôture ou l'en-tête
+    fn split_block(source: &Source, block: &Block, section: &Option<String>, max: usize, out: &mut Vec<DocumentChunk>) {
+        let (prefix_lines, suffix) = match block.kind {
+            BlockKind::Fence if block.last > block.first + 1 => {
+                let opening = source.lines[block.first].trim().to_string();
+                let closing = fence_marker(&opening).unwrap_or_else(|| "```".to_string());
+                ((block.first, block.first), Some(closing))
+            }
+            BlockKind::Table if block.last > block.first + 2 => ((block.first, block.first + 1), None),
+            _ => {
+                for (first, last) in source.split_lines(block.first, block.last, max) {
+                    out.push(source.chunk(first, last, section.clone(), None));
+                }
+                return;
+            }
+        };
+
+        let prefix: String = (prefix_lines.0..=prefix_lines.1)
+            .map(|i| source.lines[i].trim_end())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let body_first = prefix_lines.1 + 1;
+        let body_last = if suffix.is_some() { block.last - 1 } else { block.last };
+        let budget = max.saturating_sub(prefix.len() + 8).max(1);
+
+        for (first, last) in source.split_lines(body_first, body_last, budget) {
+            let (start, end) = source.span(first, last);
+            let mut text = format!("{}\n{}", prefix, source.text[start..end].trim_end());
+            if let Some(closing) = &suffix {
+                text.push('\n');
+                text.push_str(closing);
+            }
+            out.push(make_chunk(&text, start, end, section.clone(), None));
+        }
+    }
+}
+
+impl ChunkStrategy for MarkdownChunker {
+    fn chunk(&self, content: &str, context: &ChunkContext) -> Vec<DocumentChunk> {
+        let source = Source::new(content);
+        let max = context.config.max_chunk_size;
+        let mut chunks = Vec::new();
+        let mut section: Option<String> = None;
+        let mut current: Option<(usize, usize)> = None;
+
+        let flush = |current: &mut Option<(usize, usize)>, section: &Option<String>, chunks: &mut Vec<DocumentChunk>| {
+            if let Some((first, last)) = current.take() {
+                chunks.push(source.chunk(first, last, section.clone(), None));
+            }
+        };
+
+        for block in Self::blocks(&source) {
+            if let BlockKind::Heading(title) = &block.kind {
+                flush(&mut current, &section, &mut chunks);
+                section = Some(title.clone());
+                current = Some((block.first, block.last));
+                continue;
+            }
+
+            if source.span_len(block.first, block.last) > max {
+                flush(&mut current, &section, &mut chunks);
+                Self::split_block(&source, &block, &section, max, &mut chunks);
+                continue;
+            }
+
+            current = match current {
+                Some((first, _)) if source.span_len(first, block.last) <= max => Some((first, block.last)),
+                Some(_) => {
+                    flush(&mut current, &section, &mut chunks);
+                    Some((block.first, block.last))
+                }
+                None => Some((block.first, block.last)),
+            };
+        }
+        flush(&mut current, &section, &mut chunks);
+
+        chunks.retain(|c| !c.content.is_empty());
+        chunks
+    }
+}
+
+/// Marqueur d'ouverture de bloc de code (``` ou ~~~, longueur conservée)
+fn fence_marker(line: &str) -> Option<String> {
+    for fence_char in ['`', '~'] {
+        let count = line.chars().take_while(|&c| c == fence_char).count();
+        if count >= 3 {
+            return Some(fence_char.to_string().repeat(count));
+        }
+    }
+    None
+}
+
+fn heading_title(line: &str) -> Option<String> {
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    if hashes == 0 || hashes > 6 {
+        return None;
+    }
+    let rest = &line[hashes..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some(rest.trim().trim_end_matches('#').trim().to_string())
+}
+
+fn is_table_row(line: &str) -> bool {
+    line.starts_with('|') && line.len() > 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk_with(strategy: &dyn ChunkStrategy, title: &str, content: &str) -> Vec<DocumentChunk> {
+        let config = IndexerConfig::default();
+        strategy.chunk(content, &ChunkContext { title, config: &config })
+    }
+
+    fn symbols(chunks: &[DocumentChunk]) -> Vec<String> {
+        chunks.iter().filter_map(|c| c.symbol_path.clone()).collect()
+    }
+
+    #[test]
+    fn test_rust_items_and_symbol_paths() {
+        let content = r#"use std::collections::HashMap;
+
+/// Stockage en mémoire
+pub struct Store {
+    items: HashMap<String, String>,
+}
+
+impl Store {
+    pub fn insert(&mut self, key: &str) {
+        let brace = '{';
+        if key.is_empty() {
+            return;
+        }
+        self.items.insert(key.to_string(), format!("{}", brace));
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+mod helpers {
+    pub fn normalize(s: &str) -> String {
+        s.trim().to_string() // }
+    }
+}
+"#;
+        let chunks = chunk_with(&CodeChunker::new(Language::Rust), "store.rs", content);
+        let paths = symbols(&chunks);
+
+        assert!(paths.contains(&"store::Store::insert".to_string()));
+        assert!(paths.contains(&"store::Store::len".to_string()));
+        assert!(paths.contains(&"store::helpers::normalize".to_string()));
+
+        let insert = chunks.iter().find(|c| c.symbol_path.as_deref() == Some("store::Store::insert")).unwrap();
+        assert!(insert.content.starts_with("pub fn insert"));
+        assert!(insert.content.ends_with('}'));
+        assert_eq!(content[insert.start_pos..insert.end_pos].trim(), insert.content);
+
+        let store = chunks.iter().find(|c| c.symbol_path.as_deref() == Some("store::Store")).unwrap();
+        assert!(store.content.starts_with("/// Stockage en mémoire"));
+    }
+
+    #[test]
+    fn test_impl_target() {
+        assert_eq!(impl_target("<T: Clone> From<T> for Wrapper<T> {").as_deref(), Some("Wrapper"));
+        assert_eq!(impl_target(" fmt::Display for crate::Doc where T: X {").as_deref(), Some("Doc"));
+    }
+
+    #[test]
+    fn test_typescript_class_methods() {
+        let content = r#"import { api } from "./api";
+
+export class Session {
+  private token = "{";
+
+  async refresh(force: boolean): Promise<void> {
+    if (force) {
+      await api.call(`/refresh/${this.token}`);
+    }
+  }
+
+  get active() {
+    return this.token !== "";
+  }
+}
+
+export const helper = (x: number) => {
+  return x * 2;
+};
+"#;
+        let chunks = chunk_with(&CodeChunker::new(Language::TypeScript), "session.ts", content);
+        let paths = symbols(&chunks);
+        assert!(paths.contains(&"session::Session::refresh".to_string()));
+        assert!(paths.contains(&"session::Session::active".to_string()));
+        assert!(paths.contains(&"session::helper".to_string()));
+        assert!(!paths.iter().any(|p| p.ends_with("::if")));
+    }
+
+    #[test]
+    fn test_python_indentation() {
+        let content = r#"import os
+
+
+@dataclass
+class Loader:
+    """Charge les fichiers.
+
+Exemple non indenté dans la docstring.
+    """
+
+    def load(self, path):
+        if os.path.exists(path):
+            return open(path).read()
+        return None
+
+    def close(
+        self,
+    ):
+        pass
+
+
+def main():
+    Loader().load("x")
+"#;
+        let chunks = chunk_with(&CodeChunker::new(Language::Python), "loader.py", content);
+        let paths = symbols(&chunks);
+        assert!(paths.contains(&"loader::Loader::load".to_string()));
+        assert!(paths.contains(&"loader::Loader::close".to_string()));
+        assert!(paths.contains(&"loader::main".to_string()));
+
+        let class_chunk = chunks.iter().find(|c| c.symbol_path.as_deref() == Some("loader::Loader")).unwrap();
+        assert!(class_chunk.content.starts_with("@dataclass"));
+        assert!(class_chunk.content.contains("Exemple non indenté"));
+    }
+
+    #[test]
+    fn test_markdown_keeps_fences_and_tables() {
+        let content = "# Installation\nIntro.\n\n```bash\n# pas un titre\n\necho ok\n```\n\n| Col | Val |\n|-----|-----|\n| a | 1 |\n\n## Usage\nTexte.";
+        let chunks = chunk_with(&MarkdownChunker, "readme.md", content);
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].section_title.as_deref(), Some("Installation"));
+        assert!(chunks[0].content.contains("# pas un titre\n\necho ok\n```"));
+        assert!(chunks[0].content.contains("| a | 1 |"));
+        assert_eq!(chunks[1].section_title.as_deref(), Some("Usage"));
+    }
+
+    #[test]
+    fn test_markdown_splits_large_fence_with_markers() {
+        let body: String = (0..60).map(|i| format!("let value_{} = compute({});\n", i, i)).collect();
+        let content = format!("# Code\n```rust\n{}```\n", body);
+        let config = IndexerConfig {
+            max_chunk_size: 400,
+            ..IndexerConfig::default()
+        };
+        let chunks = MarkdownChunker.chunk(&content, &ChunkContext { title: "code.md", config: &config });
+
+        let fenced: Vec<_> = chunks.iter().filter(|c| c.content.contains("compute")).collect();
+        assert!(fenced.len() > 1);
+        assert!(fenced.iter().all(|c| c.content.starts_with("```rust") && c.content.ends_with("```")));
+    }
+}
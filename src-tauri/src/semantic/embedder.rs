@@ -2,26 +2,101 @@
 
 use super::*;
 
+const OLLAMA_DEFAULT_URL: &str = "http://localhost:11434";
+const OLLAMA_DEFAULT_MODEL: &str = "nomic-embed-text";
+const GEMINI_API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta";
+const GEMINI_DEFAULT_MODEL: &str = "text-embedding-004";
+const LOCAL_MODEL_NAME: &str = "hashed-ngram-v1";
+const REQUEST_TIMEOUT_SECONDS: u64 = 30;
+
+/// Mots vides (FR/EN) fortement sous-pondérés par le vectoriseur local
+pub(crate) const STOPWORDS: &[&str] = &[
+    "le", "la", "les", "un", "une", "des", "de", "du", "et", "ou", "en", "au", "aux", "ce",
+    "ces", "que", "qui", "dans", "pour", "par", "sur", "avec", "est", "sont", "pas", "ne",
+    "se", "sa", "son", "ses", "il", "elle", "ils", "on", "nous", "vous", "je", "tu", "the",
+    "a", "an", "of", "and", "or", "to", "in", "on", "for", "is", "are", "it", "this", "that",
+    "with", "as", "be", "by", "at", "from",
+];
+const STOPWORD_WEIGHT: f32 = 0.1;
+const BIGRAM_WEIGHT: f32 = 0.7;
+const TRIGRAM_WEIGHT: f32 = 0.35;
+
 pub struct Embedder {
     model_type: EmbeddingModel,
     dimensions: usize,
+    model_name: String,
+    ollama_url: String,
+    gemini_api_key: Option<String>,
+    client: reqwest::Client,
 }
 
 #[derive(Debug, Clone)]
 pub enum EmbeddingModel {
-    Local,              // Modèle local (sentence-transformers)
-    Gemini,             // API Gemini
-    Ollama,             // Ollama local
+    Local,              // Vectoriseur local hashé (TF × IDF statique, n-grammes)
+    Gemini,             // API Gemini (embedContent)
+    Ollama,             // Ollama local (/api/embeddings)
+}
+
+/// Embedding accompagné de l'empreinte du modèle qui l'a produit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Embedding {
+    pub vector: Vec<f32>,
+    pub fingerprint: String,
 }
 
 impl Embedder {
     pub fn new(model_type: EmbeddingModel, dimensions: usize) -> Self {
+        let model_name = match model_type {
+            EmbeddingModel::Local => LOCAL_MODEL_NAME,
+            EmbeddingModel::Gemini => GEMINI_DEFAULT_MODEL,
+            EmbeddingModel::Ollama => OLLAMA_DEFAULT_MODEL,
+        }
+        .to_string();
+
         Self {
             model_type,
             dimensions,
+            model_name,
+            ollama_url: OLLAMA_DEFAULT_URL.to_string(),
+            gemini_api_key: None,
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECONDS))
+                .build()
+                .unwrap_or_default(),
         }
     }
-    
+
+    /// Choisit le modèle distant (ex. `mxbai-embed-large` pour Ollama)
+    pub fn with_model_name(mut self, model_name: impl Into<String>) -> Self {
+        self.model_name = model_name.into();
+        self
+    }
+
+    pub fn with_ollama_url(mut self, url: impl Into<String>) -> Self {
+        self.ollama_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_gemini_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.gemini_api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Empreinte `fournisseur:modèle:dimensions` ; deux vecteurs ne sont
+    /// comparables que s'ils partagent la même empreinte
+    pub fn fingerprint(&self) -> String {
+        let provider = match self.model_type {
+            EmbeddingModel::Local => "local",
+            EmbeddingModel::Gemini => "gemini",
+            EmbeddingModel::Ollama => "ollama",
+        };
+        format!("{}:{}:{}", provider, self.model_name, self.dimensions)
+    }
+
     /// Génère un embedding pour un texte
     pub async fn embed(&self, text: &str) -> Result<Vec<f32>> {
         match self.model_type {
@@ -30,82 +105,174 @@ impl Embedder {
             EmbeddingModel::Ollama => self.embed_ollama(text).await,
         }
     }
-    
+
+    /// Génère un embedding marqué de l'empreinte du modèle
+    pub async fn embed_tagged(&self, text: &str) -> Result<Embedding> {
+        Ok(Embedding {
+            vector: self.embed(text).await?,
+            fingerprint: self.fingerprint(),
+        })
+    }
+
     /// Génère des embeddings pour plusieurs textes en batch
     pub async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        if let EmbeddingModel::Gemini = self.model_type {
+            if texts.len() > 1 {
+                return self.embed_gemini_batch(texts).await;
+            }
+        }
+
         let mut embeddings = Vec::new();
         for text in texts {
             embeddings.push(self.embed(text).await?);
         }
         Ok(embeddings)
     }
-    
+
     async fn embed_local(&self, text: &str) -> Result<Vec<f32>> {
-        // TODO: Intégration avec un modèle local (ex: sentence-transformers via ONNX)
-        // Pour l'instant, génération d'un embedding simulé
-        Ok(self.generate_simulated_embedding(text))
+        Ok(hashed_ngram_embedding(text, self.dimensions))
     }
-    
+
     async fn embed_gemini(&self, text: &str) -> Result<Vec<f32>> {
-        // TODO: Appel API Gemini Embedding
-        // Endpoint: https://generativelanguage.googleapis.com/v1/models/embedding-001:embedContent
-        Ok(self.generate_simulated_embedding(text))
+        let api_key = self.gemini_key()?;
+        let url = format!(
+            "{}/models/{}:embedContent?key={}",
+            GEMINI_API_BASE, self.model_name, api_key
+        );
+
+        let response: serde_json::Value = self.post_json(&url, &self.gemini_request(text)).await?;
+        let values = response
+            .get("embedding")
+            .and_then(|e| e.get("values"))
+            .ok_or_else(|| embedding_error("Réponse Gemini sans embedding"))?;
+
+        self.check_dimensions(parse_vector(values)?)
+    }
+
+    async fn embed_gemini_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let api_key = self.gemini_key()?;
+        let url = format!(
+            "{}/models/{}:batchEmbedContents?key={}",
+            GEMINI_API_BASE, self.model_name, api_key
+        );
+        let body = serde_json::json!({
+            "requests": texts.iter().map(|t| self.gemini_request(t)).collect::<Vec<_>>(),
+        });
+
+        let response: serde_json::Value = self.post_json(&url, &body).await?;
+        let embeddings = response
+            .get("embeddings")
+            .and_then(|e| e.as_array())
+            .ok_or_else(|| embedding_error("Réponse Gemini sans embeddings"))?;
+
+        if embeddings.len() != texts.len() {
+            return Err(embedding_error(&format!(
+                "Gemini a renvoyé {} embeddings pour {} textes",
+                embeddings.len(),
+                texts.len()
+            )));
+        }
+
+        embeddings
+            .iter()
+            .map(|e| {
+                let values = e
+                    .get("values")
+                    .ok_or_else(|| embedding_error("Embedding Gemini sans valeurs"))?;
+                self.check_dimensions(parse_vector(values)?)
+            })
+            .collect()
     }
-    
+
     async fn embed_ollama(&self, text: &str) -> Result<Vec<f32>> {
-        // TODO: Appel API Ollama local
-        // Endpoint: http://localhost:11434/api/embeddings
-        Ok(self.generate_simulated_embedding(text))
-    }
-    
-    /// Génère un embedding simulé basé sur des heuristiques simples
-    /// (À remplacer par un vrai modèle en production)
-    fn generate_simulated_embedding(&self, text: &str) -> Vec<f32> {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        
-        let mut hasher = DefaultHasher::new();
-        text.hash(&mut hasher);
-        let hash = hasher.finish();
-        
-        // Génération d'un vecteur déterministe à partir du hash
-        let mut embedding = Vec::with_capacity(self.dimensions);
-        let mut seed = hash;
-        
-        for _ in 0..self.dimensions {
-            // Générateur pseudo-aléatoire simple
-            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
-            let value = ((seed / 65536) % 32768) as f32 / 32768.0;
-            embedding.push(value);
+        let url = format!("{}/api/embeddings", self.ollama_url);
+        let body = serde_json::json!({
+            "model": self.model_name,
+            "prompt": text,
+        });
+
+        let response: serde_json::Value = self.post_json(&url, &body).await?;
+        let values = response
+            .get("embedding")
+            .ok_or_else(|| embedding_error("Réponse Ollama sans embedding"))?;
+
+        self.check_dimensions(parse_vector(values)?)
+    }
+
+    fn gemini_key(&self) -> Result<&str> {
+        self.gemini_api_key
+            .as_deref()
+            .ok_or_else(|| embedding_error("Clé API Gemini non configurée"))
+    }
+
+    fn gemini_request(&self, text: &str) -> serde_json::Value {
+        serde_json::json!({
+            "model": format!("models/{}", self.model_name),
+            "content": { "parts": [{ "text": text }] },
+            "outputDimensionality": self.dimensions,
+        })
+    }
+
+    async fn post_json(&self, url: &str, body: &serde_json::Value) -> Result<serde_json::Value> {
+        let response = self
+            .client
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| embedding_error(&format!("Requête échouée: {}", e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response.text().await.unwrap_or_default();
+            return Err(embedding_error(&format!("HTTP {}: {}", status.as_u16(), detail)));
         }
-        
-        // Normalisation L2
-        let norm: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
-        if norm > 0.0 {
-            for val in &mut embedding {
-                *val /= norm;
-            }
+
+        response
+            .json()
+            .await
+            .map_err(|e| embedding_error(&format!("Réponse invalide: {}", e)))
+    }
+
+    fn check_dimensions(&self, vector: Vec<f32>) -> Result<Vec<f32>> {
+        if vector.len() != self.dimensions {
+            return Err(embedding_error(&format!(
+                "Le modèle {} produit {} dimensions, {} attendues",
+                self.model_name,
+                vector.len(),
+                self.dimensions
+            )));
         }
-        
-        embedding
+        Ok(vector)
     }
-    
+
     /// Calcule la similarité cosinus entre deux embeddings
     pub fn cosine_similarity(&self, a: &[f32], b: &[f32]) -> f32 {
         if a.len() != b.len() {
             return 0.0;
         }
-        
+
         let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
         let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
         let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
-        
+
         if norm_a == 0.0 || norm_b == 0.0 {
             return 0.0;
         }
-        
+
         dot_product / (norm_a * norm_b)
     }
+
+    /// Similarité cosinus refusant les vecteurs issus de modèles différents
+    pub fn compare(&self, a: &Embedding, b: &Embedding) -> Result<f32> {
+        if a.fingerprint != b.fingerprint {
+            return Err(embedding_error(&format!(
+                "Embeddings incomparables: {} vs {}",
+                a.fingerprint, b.fingerprint
+            )));
+        }
+        Ok(self.cosine_similarity(&a.vector, &b.vector))
+    }
 }
 
 impl Default for Embedder {
@@ -113,3 +280,138 @@ impl Default for Embedder {
         Self::new(EmbeddingModel::Local, 384)
     }
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Vectoriseur local : hachage de caractéristiques (mots, bigrammes de mots,
+// trigrammes de caractères) pondérées par TF sous-linéaire × IDF statique.
+// Déterministe, sans dépendance réseau, et conserve la similarité lexicale.
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn hashed_ngram_embedding(text: &str, dimensions: usize) -> Vec<f32> {
+    let mut embedding = vec![0.0f32; dimensions];
+    if dimensions == 0 {
+        return embedding;
+    }
+
+    let tokens = tokenize(text);
+    let mut features: HashMap<String, f32> = HashMap::new();
+
+    for token in &tokens {
+        let weight = if STOPWORDS.contains(&token.as_str()) {
+            STOPWORD_WEIGHT
+        } else {
+            1.0
+        };
+        *features.entry(format!("w:{}", token)).or_insert(0.0) += weight;
+
+        // Trigrammes de caractères : robustesse aux flexions et fautes de frappe
+        let padded: Vec<char> = format!("<{}>", token).chars().collect();
+        if padded.len() > 3 {
+            let trigram_count = (padded.len() - 2) as f32;
+            for window in padded.windows(3) {
+                let trigram: String = window.iter().collect();
+                *features.entry(format!("c:{}", trigram)).or_insert(0.0) +=
+                    weight * TRIGRAM_WEIGHT / trigram_count.sqrt();
+            }
+        }
+    }
+
+    for pair in tokens.windows(2) {
+        if STOPWORDS.contains(&pair[0].as_str()) && STOPWORDS.contains(&pair[1].as_str()) {
+            continue;
+        }
+        *features.entry(format!("b:{} {}", pair[0], pair[1])).or_insert(0.0) += BIGRAM_WEIGHT;
+    }
+
+    for (feature, tf) in features {
+        let hash = fnv1a(feature.as_bytes());
+        let bucket = (hash % dimensions as u64) as usize;
+        // Le bit de signe limite les collisions constructives
+        let sign = if (hash >> 63) & 1 == 0 { 1.0 } else { -1.0 };
+        embedding[bucket] += sign * tf.ln_1p();
+    }
+
+    // Normalisation L2
+    let norm: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for val in &mut embedding {
+            *val /= norm;
+        }
+    }
+
+    embedding
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+/// FNV-1a 64 bits : stable entre versions de Rust, contrairement à `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn parse_vector(values: &serde_json::Value) -> Result<Vec<f32>> {
+    values
+        .as_array()
+        .ok_or_else(|| embedding_error("Vecteur d'embedding invalide"))?
+        .iter()
+        .map(|v| {
+            v.as_f64()
+                .map(|f| f as f32)
+                .ok_or_else(|| embedding_error("Composante d'embedding non numérique"))
+        })
+        .collect()
+}
+
+fn embedding_error(message: &str) -> SemanticError {
+    SemanticError::EmbeddingError(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_embedding_is_deterministic() {
+        let embedder = Embedder::default();
+        let a = embedder.embed("Facture numéro 2024-118").await.unwrap();
+        let b = embedder.embed("Facture numéro 2024-118").await.unwrap();
+
+        assert_eq!(a.len(), 384);
+        assert_eq!(a, b);
+    }
+
+    #[tokio::test]
+    async fn test_local_embedding_keeps_lexical_similarity() {
+        let embedder = Embedder::default();
+        let query = embedder.embed("contrat de location").await.unwrap();
+        let close = embedder.embed("contrats de location meublée").await.unwrap();
+        let far = embedder.embed("recette de la tarte aux pommes").await.unwrap();
+
+        let close_score = embedder.cosine_similarity(&query, &close);
+        let far_score = embedder.cosine_similarity(&query, &far);
+        assert!(close_score > 0.3, "score proche trop bas: {}", close_score);
+        assert!(close_score > far_score + 0.25);
+    }
+
+    #[tokio::test]
+    async fn test_compare_rejects_different_models() {
+        let local = Embedder::default();
+        let other = Embedder::new(EmbeddingModel::Local, 128);
+
+        let a = local.embed_tagged("bonjour").await.unwrap();
+        let b = other.embed_tagged("bonjour").await.unwrap();
+        assert_ne!(a.fingerprint, b.fingerprint);
+        assert!(local.compare(&a, &b).is_err());
+        assert!(local.compare(&a, &a).is_ok());
+    }
+}
@@ -0,0 +1,398 @@
+// TITANE∞ v13 - Retrieval Evaluation
+// Banc d'évaluation de la recherche : recall@k, MRR et nDCG sur des variantes de configuration
+
+use crate::semantic::embedder::{Embedder, EmbeddingModel};
+use crate::semantic::indexer::{embedding_inputs, IndexManager, IndexerConfig};
+use crate::semantic::query::{HybridResult, QueryConfig, QueryEngine};
+use crate::semantic::reranker::{ContextualReranker, RerankerConfig};
+use crate::semantic::vector_store::{SearchResultKNN, VectorStore};
+use crate::semantic::SearchQuery;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Dimensions du vectoriseur local utilisé pour l'évaluation hors ligne
+const EVAL_DIMENSIONS: usize = 384;
+
+/// Séparateur entre document et section/symbole dans les identifiants attendus
+pub const TARGET_SEPARATOR: char = '#';
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Jeux de données
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Document du corpus de référence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusDocument {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    #[serde(default = "default_doc_type")]
+    pub doc_type: String,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+fn default_doc_type() -> String {
+    "markdown".to_string()
+}
+
+/// Corpus de référence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalCorpus {
+    pub documents: Vec<CorpusDocument>,
+}
+
+/// Requête annotée. `relevant` liste des identifiants de document (`contrat`)
+/// ou de chunk désigné par sa section ou son symbole (`contrat#Résiliation`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalQuery {
+    pub id: String,
+    pub query: String,
+    pub relevant: Vec<String>,
+}
+
+/// Jeu de requêtes annotées
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalSet {
+    pub queries: Vec<EvalQuery>,
+}
+
+impl EvalCorpus {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        read_json(path)
+    }
+}
+
+impl EvalSet {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        read_json(path)
+    }
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, String> {
+    let raw = std::fs::read_to_string(path).map_err(|e| format!("Lecture de {}: {}", path.display(), e))?;
+    serde_json::from_str(&raw).map_err(|e| format!("JSON invalide dans {}: {}", path.display(), e))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Variantes et rapports
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Configuration évaluée : query engine, reranking optionnel et découpage
+#[derive(Debug, Clone)]
+pub struct EvalVariant {
+    pub name: String,
+    pub query: QueryConfig,
+    pub reranker: Option<RerankerConfig>,
+    pub indexer: IndexerConfig,
+}
+
+impl EvalVariant {
+    /// Configuration par défaut du moteur (reranking activé)
+    pub fn baseline(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            query: QueryConfig::default(),
+            reranker: Some(RerankerConfig::default()),
+            indexer: IndexerConfig::default(),
+        }
+    }
+}
+
+/// Métriques d'une requête
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryMetrics {
+    pub id: String,
+    pub recall: f32,
+    pub reciprocal_rank: f32,
+    pub ndcg: f32,
+    /// Rang (1-based) du premier résultat pertinent
+    pub first_hit: Option<usize>,
+}
+
+/// Moyennes d'une variante sur le jeu de requêtes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariantReport {
+    pub name: String,
+    pub recall_at_k: f32,
+    pub mrr: f32,
+    pub ndcg_at_k: f32,
+    pub queries: Vec<QueryMetrics>,
+}
+
+/// Rapport comparatif
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalReport {
+    pub k: usize,
+    pub variants: Vec<VariantReport>,
+}
+
+impl EvalReport {
+    pub fn variant(&self, name: &str) -> Option<&VariantReport> {
+        self.variants.iter().find(|v| v.name == name)
+    }
+
+    /// Tableau comparatif, écarts exprimés par rapport à la première variante
+    pub fn to_table(&self) -> String {
+        let width = self
+            .variants
+            .iter()
+            .map(|v| v.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("variante".len());
+        let recall_label = format!("recall@{}", self.k);
+        let ndcg_label = format!("nDCG@{}", self.k);
+
+        let mut table = format!(
+            "{:<width$} | {:>16} | {:>16} | {:>16}\n",
+            "variante",
+            recall_label,
+            "MRR",
+            ndcg_label,
+            width = width
+        );
+        table.push_str(&format!("{}-+-{}-+-{}-+-{}\n", "-".repeat(width), "-".repeat(16), "-".repeat(16), "-".repeat(16)));
+
+        let base = self.variants.first();
+        for (index, variant) in self.variants.iter().enumerate() {
+            let cell = |value: f32, base: Option<f32>| match base {
+                Some(base) if index > 0 => format!("{:.3} ({:+.3})", value, value - base),
+                _ => format!("{:.3}", value),
+            };
+            table.push_str(&format!(
+                "{:<width$} | {:>16} | {:>16} | {:>16}\n",
+                variant.name,
+                cell(variant.recall_at_k, base.map(|r| r.recall_at_k)),
+                cell(variant.mrr, base.map(|r| r.mrr)),
+                cell(variant.ndcg_at_k, base.map(|r| r.ndcg_at_k)),
+                width = width
+            ));
+        }
+        table
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Exécution
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Évalue chaque variante sur le corpus, entièrement hors ligne (vectoriseur local)
+pub async fn evaluate(
+    corpus: &EvalCorpus,
+    queries: &EvalSet,
+    variants: &[EvalVariant],
+    k: usize,
+) -> Result<EvalReport, String> {
+    let embedder = Embedder::new(EmbeddingModel::Local, EVAL_DIMENSIONS);
+    let mut reports = Vec::new();
+
+    for variant in variants {
+        let manager = build_index(corpus, &variant.indexer);
+        let vector_store = build_vector_store(&manager, &embedder).await?;
+        let engine = QueryEngine::new(variant.query.clone());
+        let reranker = variant.reranker.clone().map(ContextualReranker::new);
+
+        let mut metrics = Vec::new();
+        for query in &queries.queries {
+            let embedding = embedder.embed_tagged(&query.query).await.map_err(|e| e.to_string())?;
+            vector_store.check_model(&embedding.fingerprint).map_err(|e| e.to_string())?;
+            let search = SearchQuery {
+                text: query.query.clone(),
+                filters: None,
+                context: None,
+                intent: None,
+            };
+            let hits = engine
+                .hybrid_search(&search, &vector_store, manager.lexical_index(), &embedding.vector)
+                .map_err(|e| e.to_string())?;
+            let ranked_ids = match &reranker {
+                Some(reranker) => rerank_ids(reranker, &manager, &query.query, hits),
+                None => hits.into_iter().map(|h| h.id).collect(),
+            };
+
+            let keys: Vec<Vec<String>> = ranked_ids.iter().map(|id| result_keys(&manager, id)).collect();
+            metrics.push(score_query(&query.id, &keys, &query.relevant, k));
+        }
+
+        reports.push(summarize(&variant.name, metrics));
+    }
+
+    Ok(EvalReport { k, variants: reports })
+}
+
+fn build_index(corpus: &EvalCorpus, config: &IndexerConfig) -> IndexManager {
+    let mut manager = IndexManager::new(config.clone());
+    for doc in &corpus.documents {
+        manager.add_document(
+            doc.id.clone(),
+            doc.title.clone(),
+            doc.content.clone(),
+            doc.doc_type.clone(),
+            doc.metadata.clone(),
+        );
+    }
+    manager
+}
+
+async fn build_vector_store(manager: &IndexManager, embedder: &Embedder) -> Result<VectorStore, String> {
+    let path = std::env::temp_dir().join(format!("titane_eval_{}", uuid::Uuid::new_v4()));
+    let mut store = VectorStore::new(EVAL_DIMENSIONS, path);
+
+    for doc in manager.list_documents() {
+        for (id, text) in embedding_inputs(doc) {
+            let embedding = embedder.embed_tagged(&text).await.map_err(|e| e.to_string())?;
+            let metadata = HashMap::from([("doc_id".to_string(), doc.id.clone())]);
+            store.add_embedding(id, embedding, metadata).map_err(|e| e.to_string())?;
+        }
+    }
+
+    if !store.is_empty() {
+        store.build_index().map_err(|e| e.to_string())?;
+    }
+    Ok(store)
+}
+
+/// Le reranker attend une similarité : le score RRF est normalisé par le meilleur
+fn rerank_ids(reranker: &ContextualReranker, manager: &IndexManager, query: &str, hits: Vec<HybridResult>) -> Vec<String> {
+    let best = hits.iter().map(|h| h.score).fold(0.0f32, f32::max);
+    let candidates = hits
+        .into_iter()
+        .map(|hit| {
+            let similarity = if best > 0.0 { hit.score / best } else { 0.0 };
+            SearchResultKNN {
+                id: hit.id,
+                similarity,
+                distance: 1.0 - similarity,
+                metadata: hit.metadata,
+            }
+        })
+        .collect();
+    reranker
+        .rerank_with_graph(candidates, query, None, manager.graph())
+        .into_iter()
+        .map(|r| r.id)
+        .collect()
+}
+
+/// Identifiants sous lesquels un résultat peut être attendu :
+/// document, `document#section` et `document#symbole`
+fn result_keys(manager: &IndexManager, id: &str) -> Vec<String> {
+    match manager.find_chunk(id) {
+        Some((doc, chunk)) => {
+            let mut keys = vec![doc.id.clone()];
+            for anchor in [&chunk.section_title, &chunk.symbol_path].into_iter().flatten() {
+                keys.push(format!("{}{}{}", doc.id, TARGET_SEPARATOR, anchor));
+            }
+            keys
+        }
+        None => vec![id.to_string()],
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Métriques
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Rangs (1-based) auxquels chaque cible pertinente est trouvée pour la première fois
+pub fn relevant_ranks(results: &[Vec<String>], relevant: &[String]) -> Vec<usize> {
+    let mut found: HashSet<&str> = HashSet::new();
+    let mut ranks = Vec::new();
+    for (index, keys) in results.iter().enumerate() {
+        let new_target = relevant
+            .iter()
+            .find(|target| !found.contains(target.as_str()) && keys.contains(target));
+        if let Some(target) = new_target {
+            found.insert(target.as_str());
+            ranks.push(index + 1);
+        }
+    }
+    ranks
+}
+
+pub fn recall_at_k(ranks: &[usize], relevant_count: usize, k: usize) -> f32 {
+    if relevant_count == 0 {
+        return 0.0;
+    }
+    ranks.iter().filter(|&&r| r <= k).count() as f32 / relevant_count as f32
+}
+
+pub fn reciprocal_rank(ranks: &[usize]) -> f32 {
+    ranks.first().map(|&r| 1.0 / r as f32).unwrap_or(0.0)
+}
+
+/// nDCG binaire : gain 1 par cible trouvée, actualisé par log2(rang + 1)
+pub fn ndcg_at_k(ranks: &[usize], relevant_count: usize, k: usize) -> f32 {
+    let discount = |rank: usize| 1.0 / ((rank + 1) as f32).log2();
+    let dcg: f32 = ranks.iter().filter(|&&r| r <= k).map(|&r| discount(r)).sum();
+    let ideal: f32 = (1..=relevant_count.min(k)).map(discount).sum();
+    if ideal > 0.0 {
+        dcg / ideal
+    } else {
+        0.0
+    }
+}
+
+fn score_query(id: &str, results: &[Vec<String>], relevant: &[String], k: usize) -> QueryMetrics {
+    let ranks = relevant_ranks(results, relevant);
+    QueryMetrics {
+        id: id.to_string(),
+        recall: recall_at_k(&ranks, relevant.len(), k),
+        reciprocal_rank: reciprocal_rank(&ranks),
+        ndcg: ndcg_at_k(&ranks, relevant.len(), k),
+        first_hit: ranks.first().copied(),
+    }
+}
+
+fn summarize(name: &str, queries: Vec<QueryMetrics>) -> VariantReport {
+    let mean = |f: fn(&QueryMetrics) -> f32| {
+        if queries.is_empty() {
+            0.0
+        } else {
+            queries.iter().map(f).sum::<f32>() / queries.len() as f32
+        }
+    };
+    VariantReport {
+        name: name.to_string(),
+        recall_at_k: mean(|q| q.recall),
+        mrr: mean(|q| q.reciprocal_rank),
+        ndcg_at_k: mean(|q| q.ndcg),
+        queries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(ids: &[&str]) -> Vec<Vec<String>> {
+        ids.iter().map(|id| vec![id.to_string()]).collect()
+    }
+
+    #[test]
+    fn test_metrics() {
+        let relevant = vec!["b".to_string(), "d".to_string()];
+        let ranks = relevant_ranks(&keys(&["a", "b", "b", "c", "d"]), &relevant);
+        assert_eq!(ranks, vec![2, 5]);
+
+        assert_eq!(recall_at_k(&ranks, 2, 3), 0.5);
+        assert_eq!(recall_at_k(&ranks, 2, 5), 1.0);
+        assert_eq!(reciprocal_rank(&ranks), 0.5);
+
+        let expected = (1.0 / 3f32.log2() + 1.0 / 6f32.log2()) / (1.0 + 1.0 / 3f32.log2());
+        assert!((ndcg_at_k(&ranks, 2, 5) - expected).abs() < 1e-6);
+        assert_eq!(ndcg_at_k(&[1, 2], 2, 5), 1.0);
+        assert_eq!(ndcg_at_k(&[], 2, 5), 0.0);
+    }
+
+    #[test]
+    fn test_section_targets() {
+        let results = vec![
+            vec!["contrat".to_string(), "contrat#Tarifs".to_string()],
+            vec!["contrat".to_string(), "contrat#Résiliation".to_string()],
+        ];
+        let ranks = relevant_ranks(&results, &["contrat#Résiliation".to_string()]);
+        assert_eq!(ranks, vec![2]);
+    }
+}
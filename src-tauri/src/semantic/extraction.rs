@@ -0,0 +1,424 @@
+// TITANE∞ v13 - Entity Extraction
+// Extraction d'entités (personnes, projets, dates, termes techniques) et de
+// relations pour alimenter le graphe de connaissance ; heuristique par défaut,
+// enrichissement optionnel via le routeur IA
+
+use crate::ai::router::AIRouter;
+use crate::ai::AIRequest;
+use crate::semantic::graph::NodeType;
+use crate::semantic::IndexedDocument;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Entités par chunk au-delà desquelles on ne crée plus de co-occurrences
+const MAX_COOCCURRING_ENTITIES: usize = 12;
+/// Texte maximal envoyé au LLM
+const LLM_MAX_CHARS: usize = 6000;
+
+/// Termes techniques reconnus (comparaison insensible à la casse)
+const TECH_TERMS: &[&str] = &[
+    "rust", "tauri", "tokio", "serde", "python", "typescript", "javascript", "react", "svelte", "vue", "node.js",
+    "deno", "docker", "kubernetes", "postgresql", "postgres", "sqlite", "mysql", "redis", "mongodb", "graphql",
+    "grpc", "rest", "websocket", "ollama", "gemini", "openai", "llm", "rag", "hnsw", "bm25", "embedding",
+    "aes-gcm", "argon2", "oauth", "jwt", "linux", "windows", "macos", "git", "github", "gitlab", "aws", "azure",
+    "gcp", "terraform", "ansible", "nginx", "wasm", "webassembly", "json", "yaml", "toml", "markdown",
+];
+
+/// Mots capitalisés qui ne forment pas un nom de personne
+const NON_PERSON_WORDS: &[&str] = &[
+    "le", "la", "les", "un", "une", "des", "du", "de", "ce", "cette", "ces", "the", "a", "an", "this", "that",
+    "projet", "project", "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche", "monday",
+    "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday", "janvier", "février", "mars", "avril",
+    "mai", "juin", "juillet", "août", "septembre", "octobre", "novembre", "décembre", "january", "february",
+    "march", "april", "may", "june", "july", "august", "september", "october", "november", "december",
+    "introduction", "conclusion", "section", "chapitre", "chapter", "note", "voir", "see",
+];
+
+const FRENCH_MONTHS: &[&str] = &[
+    "janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août", "septembre", "octobre", "novembre",
+    "décembre",
+];
+const ENGLISH_MONTHS: &[&str] = &[
+    "january", "february", "march", "april", "may", "june", "july", "august", "september", "october", "november",
+    "december",
+];
+
+/// Entité extraite d'un chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractedEntity {
+    pub name: String,
+    pub kind: NodeType,
+    pub chunk_id: Option<String>,
+}
+
+/// Relation extraite entre deux entités (par nom)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractedRelation {
+    pub from: String,
+    pub to: String,
+    pub relation: String,
+    pub chunk_id: Option<String>,
+}
+
+/// Résultat d'extraction pour un document
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Extraction {
+    pub entities: Vec<ExtractedEntity>,
+    pub relations: Vec<ExtractedRelation>,
+}
+
+impl Extraction {
+    pub fn merge(&mut self, other: Extraction) {
+        self.entities.extend(other.entities);
+        self.relations.extend(other.relations);
+    }
+}
+
+/// Extracteur heuristique
+pub struct EntityExtractor {
+    person: Regex,
+    project: Regex,
+    iso_date: Regex,
+    numeric_date: Regex,
+    french_date: Regex,
+    english_date: Regex,
+    quarter: Regex,
+}
+
+impl EntityExtractor {
+    pub fn new() -> Self {
+        Self {
+            // Civilité optionnelle puis 1 à 3 mots capitalisés
+            person: Regex::new(r"\b(?:(M\.|Mme|Mlle|Dr\.?|Pr\.?|Mr\.?|Mrs\.?|Ms\.?)\s+)?([A-ZÀ-Ý][a-zà-ÿ'-]+(?:\s+[A-ZÀ-Ý][a-zà-ÿ'-]+){0,2})")
+                .unwrap(),
+            project: Regex::new(
+                r#"\b(?:[Pp]rojet|[Pp]roject|[Pp]rogramme|[Pp]rogram|[Ii]nitiative)\s+(?:"([^"]+)"|«\s*([^»]+?)\s*»|([A-Z][\w-]*))"#,
+            )
+            .unwrap(),
+            iso_date: Regex::new(r"\b(\d{4})-(\d{2})-(\d{2})\b").unwrap(),
+            numeric_date: Regex::new(r"\b(\d{1,2})/(\d{1,2})/(\d{4})\b").unwrap(),
+            french_date: Regex::new(
+                r"(?i)\b(\d{1,2})(?:er)?\s+(janvier|février|fevrier|mars|avril|mai|juin|juillet|août|aout|septembre|octobre|novembre|décembre|decembre)\s+(\d{4})\b",
+            )
+            .unwrap(),
+            english_date: Regex::new(
+                r"\b(January|February|March|April|May|June|July|August|September|October|November|December)\s+(\d{1,2}),?\s+(\d{4})\b",
+            )
+            .unwrap(),
+            quarter: Regex::new(r"\b(Q[1-4])\s+(\d{4})\b").unwrap(),
+        }
+    }
+
+    /// Extrait entités et co-occurrences chunk par chunk
+    pub fn extract(&self, document: &IndexedDocument) -> Extraction {
+        let mut extraction = Extraction::default();
+
+        if document.chunks.is_empty() {
+            self.extract_text(&document.content, None, &mut extraction);
+        } else {
+            for chunk in &document.chunks {
+                self.extract_text(&chunk.content, Some(&chunk.id), &mut extraction);
+            }
+        }
+
+        extraction
+    }
+
+    fn extract_text(&self, text: &str, chunk_id: Option<&String>, extraction: &mut Extraction) {
+        let mut found: Vec<(String, NodeType)> = Vec::new();
+        let mut push = |name: String, kind: NodeType| {
+            let name = name.trim().to_string();
+            if !name.is_empty() && !found.iter().any(|(n, k)| n.eq_ignore_ascii_case(&name) && *k == kind) {
+                found.push((name, kind));
+            }
+        };
+
+        for date in self.dates(text) {
+            push(date, NodeType::Date);
+        }
+
+        let mut project_names = HashSet::new();
+        for captures in self.project.captures_iter(text) {
+            if let Some(name) = captures.get(1).or(captures.get(2)).or(captures.get(3)) {
+                project_names.insert(name.as_str().to_lowercase());
+                push(name.as_str().to_string(), NodeType::Project);
+            }
+        }
+
+        for term in tech_terms(text) {
+            push(term, NodeType::Technology);
+        }
+
+        for captures in self.person.captures_iter(text) {
+            let name = &captures[2];
+            let words: Vec<&str> = name.split_whitespace().collect();
+            let has_honorific = captures.get(1).is_some();
+
+            // Retire les mots non nominaux en tête (« Le », « Projet »...)
+            let start = words
+                .iter()
+                .position(|w| !NON_PERSON_WORDS.contains(&w.to_lowercase().as_str()))
+                .unwrap_or(words.len());
+            let words = &words[start..];
+
+            let plausible = (words.len() >= 2 || (has_honorific && words.len() == 1))
+                && words.iter().all(|w| {
+                    let lower = w.to_lowercase();
+                    !NON_PERSON_WORDS.contains(&lower.as_str())
+                        && !TECH_TERMS.contains(&lower.as_str())
+                        && !project_names.contains(&lower)
+                });
+            if plausible {
+                push(words.join(" "), NodeType::Person);
+            }
+        }
+
+        for (name, kind) in &found {
+            extraction.entities.push(ExtractedEntity {
+                name: name.clone(),
+                kind: kind.clone(),
+                chunk_id: chunk_id.cloned(),
+            });
+        }
+
+        let linked: Vec<&(String, NodeType)> = found.iter().take(MAX_COOCCURRING_ENTITIES).collect();
+        for (i, (from, _)) in linked.iter().enumerate() {
+            for (to, _) in linked.iter().skip(i + 1) {
+                extraction.relations.push(ExtractedRelation {
+                    from: from.clone(),
+                    to: to.clone(),
+                    relation: "related".to_string(),
+                    chunk_id: chunk_id.cloned(),
+                });
+            }
+        }
+    }
+
+    /// Dates normalisées en ISO quand c'est possible
+    fn dates(&self, text: &str) -> Vec<String> {
+        let mut dates = Vec::new();
+
+        for c in self.iso_date.captures_iter(text) {
+            dates.push(format!("{}-{}-{}", &c[1], &c[2], &c[3]));
+        }
+        for c in self.numeric_date.captures_iter(text) {
+            dates.push(format!("{}-{:0>2}-{:0>2}", &c[3], &c[2], &c[1]));
+        }
+        for c in self.french_date.captures_iter(text) {
+            let month = c[2].to_lowercase().replace(['é', 'è'], "e").replace('û', "u");
+            let index = FRENCH_MONTHS
+                .iter()
+                .position(|m| m.replace(['é', 'è'], "e").replace('û', "u") == month)
+                .unwrap_or(0);
+            dates.push(format!("{}-{:02}-{:0>2}", &c[3], index + 1, &c[1]));
+        }
+        for c in self.english_date.captures_iter(text) {
+            let month = c[1].to_lowercase();
+            let index = ENGLISH_MONTHS.iter().position(|m| *m == month).unwrap_or(0);
+            dates.push(format!("{}-{:02}-{:0>2}", &c[3], index + 1, &c[2]));
+        }
+        for c in self.quarter.captures_iter(text) {
+            dates.push(format!("{} {}", &c[1], &c[2]));
+        }
+
+        dates
+    }
+}
+
+impl Default for EntityExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Termes techniques présents dans le texte, en tant que mots entiers
+fn tech_terms(text: &str) -> Vec<String> {
+    let lower = text.to_lowercase();
+    let words: HashSet<&str> = lower
+        .split(|c: char| !(c.is_alphanumeric() || c == '.' || c == '-'))
+        .map(|w| w.trim_end_matches('.'))
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    TECH_TERMS
+        .iter()
+        .filter(|term| words.contains(**term))
+        .map(|term| term.to_string())
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Extraction assistée par LLM
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+struct LlmExtraction {
+    #[serde(default)]
+    entities: Vec<LlmEntity>,
+    #[serde(default)]
+    relations: Vec<LlmRelation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlmEntity {
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlmRelation {
+    from: String,
+    to: String,
+    #[serde(rename = "type", default)]
+    relation: String,
+}
+
+/// Demande au LLM les entités et relations typées du document ; chaque entité
+/// est rattachée aux chunks qui la mentionnent
+pub async fn extract_with_llm(router: &AIRouter, document: &IndexedDocument) -> Result<Extraction, String> {
+    let text: String = document.content.chars().take(LLM_MAX_CHARS).collect();
+    let prompt = format!(
+        "Extrait les entités et relations du texte ci-dessous.\n\
+         Réponds uniquement en JSON : {{\"entities\": [{{\"name\": \"...\", \"type\": \"person|project|date|technology|concept\"}}], \
+         \"relations\": [{{\"from\": \"...\", \"to\": \"...\", \"type\": \"works_on|depends_on|part_of|references|related\"}}]}}\n\n\
+         Titre : {}\n\n{}",
+        document.title, text
+    );
+
+    let response = router
+        .query(AIRequest {
+            prompt,
+            temperature: 0.0,
+            max_tokens: 800,
+            stream: false,
+        })
+        .await
+        .map_err(|e| format!("Extraction LLM échouée: {}", e))?;
+
+    parse_llm_extraction(&response.content, document)
+}
+
+fn parse_llm_extraction(content: &str, document: &IndexedDocument) -> Result<Extraction, String> {
+    let start = content.find('{').ok_or("Réponse LLM sans JSON")?;
+    let end = content.rfind('}').ok_or("Réponse LLM sans JSON")?;
+    let parsed: LlmExtraction = serde_json::from_str(&content[start..=end])
+        .map_err(|e| format!("JSON d'extraction invalide: {}", e))?;
+
+    let chunks_mentioning = |name: &str| -> Vec<Option<String>> {
+        let needle = name.to_lowercase();
+        let ids: Vec<Option<String>> = document
+            .chunks
+            .iter()
+            .filter(|c| c.content.to_lowercase().contains(&needle))
+            .map(|c| Some(c.id.clone()))
+            .collect();
+        if ids.is_empty() {
+            vec![None]
+        } else {
+            ids
+        }
+    };
+
+    let mut extraction = Extraction::default();
+    for entity in parsed.entities {
+        let kind = match entity.kind.to_lowercase().as_str() {
+            "person" | "personne" => NodeType::Person,
+            "project" | "projet" => NodeType::Project,
+            "date" => NodeType::Date,
+            "technology" | "tech" | "technologie" => NodeType::Technology,
+            "concept" => NodeType::Concept,
+            _ => NodeType::Entity,
+        };
+        for chunk_id in chunks_mentioning(&entity.name) {
+            extraction.entities.push(ExtractedEntity {
+                name: entity.name.clone(),
+                kind: kind.clone(),
+                chunk_id,
+            });
+        }
+    }
+    for relation in parsed.relations {
+        extraction.relations.push(ExtractedRelation {
+            from: relation.from,
+            to: relation.to,
+            relation: if relation.relation.is_empty() {
+                "related".to_string()
+            } else {
+                relation.relation
+            },
+            chunk_id: None,
+        });
+    }
+
+    Ok(extraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn document(content: &str) -> IndexedDocument {
+        IndexedDocument {
+            id: "doc1".to_string(),
+            title: "Compte rendu".to_string(),
+            content: content.to_string(),
+            doc_type: "text".to_string(),
+            metadata: HashMap::new(),
+            embedding: Vec::new(),
+            embedding_model: None,
+            chunks: Vec::new(),
+            indexed_at: chrono::Utc::now(),
+        }
+    }
+
+    fn names(extraction: &Extraction, kind: NodeType) -> Vec<String> {
+        extraction
+            .entities
+            .iter()
+            .filter(|e| e.kind == kind)
+            .map(|e| e.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_heuristic_extraction() {
+        let doc = document(
+            "Marie Dupont pilote le projet Atlas depuis le 3 mars 2024. \
+             La migration vers Rust et PostgreSQL est prévue le 15/09/2024 avec Dr Martin.",
+        );
+        let extraction = EntityExtractor::new().extract(&doc);
+
+        let people = names(&extraction, NodeType::Person);
+        assert!(people.contains(&"Marie Dupont".to_string()));
+        assert!(people.contains(&"Martin".to_string()));
+        assert!(!people.iter().any(|p| p.contains("Atlas") || p.contains("Rust")));
+
+        assert_eq!(names(&extraction, NodeType::Project), vec!["Atlas"]);
+        let dates = names(&extraction, NodeType::Date);
+        assert!(dates.contains(&"2024-03-03".to_string()));
+        assert!(dates.contains(&"2024-09-15".to_string()));
+
+        let tech = names(&extraction, NodeType::Technology);
+        assert!(tech.contains(&"rust".to_string()) && tech.contains(&"postgresql".to_string()));
+
+        assert!(extraction
+            .relations
+            .iter()
+            .any(|r| (r.from == "Atlas" && r.to == "Marie Dupont") || (r.from == "Marie Dupont" && r.to == "Atlas")));
+    }
+
+    #[test]
+    fn test_parse_llm_extraction() {
+        let doc = document("Alice travaille sur Orion.");
+        let response = "Voici le résultat :\n{\"entities\": [{\"name\": \"Alice\", \"type\": \"person\"}, \
+                        {\"name\": \"Orion\", \"type\": \"project\"}], \
+                        \"relations\": [{\"from\": \"Alice\", \"to\": \"Orion\", \"type\": \"works_on\"}]}";
+        let extraction = parse_llm_extraction(response, &doc).unwrap();
+        assert_eq!(extraction.entities.len(), 2);
+        assert_eq!(extraction.entities[0].kind, NodeType::Person);
+        assert_eq!(extraction.relations[0].relation, "works_on");
+    }
+}
@@ -1,8 +1,10 @@
 // TITANE∞ v13 - Knowledge Graph
 // Graphe de connaissance pour relations sémantiques
 
+use crate::semantic::extraction::Extraction;
+use crate::semantic::IndexedDocument;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// Nœud du graphe
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +14,16 @@ pub struct KnowledgeNode {
     pub title: String,
     pub node_type: NodeType,
     pub metadata: HashMap<String, String>,
+    /// Chunks d'où l'entité a été extraite
+    #[serde(default)]
+    pub sources: Vec<ChunkRef>,
+}
+
+/// Référence vers un chunk source
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkRef {
+    pub doc_id: String,
+    pub chunk_id: Option<String>,
 }
 
 /// Type de nœud
@@ -21,6 +33,10 @@ pub enum NodeType {
     Concept,
     Entity,
     Topic,
+    Person,
+    Project,
+    Date,
+    Technology,
 }
 
 /// Arête du graphe
@@ -30,6 +46,9 @@ pub struct KnowledgeEdge {
     pub to_id: String,
     pub relation_type: RelationType,
     pub weight: f32,
+    /// Document à l'origine de l'arête (retirée avec lui)
+    #[serde(default)]
+    pub source_doc: Option<String>,
 }
 
 /// Type de relation
@@ -40,12 +59,70 @@ pub enum RelationType {
     DerivedFrom,
     PartOf,
     Related,
+    Mentions,
+    Custom(String),
+}
+
+impl RelationType {
+    /// Relation nommée par l'extraction (`part_of`, `works_on`...)
+    pub fn from_label(label: &str) -> Self {
+        match label.to_lowercase().replace([' ', '-'], "_").as_str() {
+            "similar" => RelationType::Similar,
+            "references" | "cites" => RelationType::References,
+            "derived_from" => RelationType::DerivedFrom,
+            "part_of" => RelationType::PartOf,
+            "related" | "co_occurs" => RelationType::Related,
+            "mentions" => RelationType::Mentions,
+            other => RelationType::Custom(other.to_string()),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            RelationType::Similar => "similar".to_string(),
+            RelationType::References => "references".to_string(),
+            RelationType::DerivedFrom => "derived_from".to_string(),
+            RelationType::PartOf => "part_of".to_string(),
+            RelationType::Related => "related".to_string(),
+            RelationType::Mentions => "mentions".to_string(),
+            RelationType::Custom(label) => label.clone(),
+        }
+    }
+}
+
+/// Sous-graphe exporté pour la visualisation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphExport {
+    pub center: String,
+    pub nodes: Vec<GraphExportNode>,
+    pub edges: Vec<GraphExportEdge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphExportNode {
+    pub id: String,
+    pub label: String,
+    pub node_type: NodeType,
+    pub depth: usize,
+    pub degree: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphExportEdge {
+    pub source: String,
+    pub target: String,
+    pub relation: String,
+    pub weight: f32,
 }
 
 /// Graphe de connaissance
 pub struct KnowledgeGraph {
     nodes: HashMap<String, KnowledgeNode>,
     edges: Vec<KnowledgeEdge>,
+    /// nœud → indices des arêtes incidentes (dans les deux sens)
+    adjacency: HashMap<String, Vec<usize>>,
+    /// libellé en minuscules → nœud entité
+    names: HashMap<String, String>,
 }
 
 impl KnowledgeGraph {
@@ -53,15 +130,41 @@ impl KnowledgeGraph {
         Self {
             nodes: HashMap::new(),
             edges: Vec::new(),
+            adjacency: HashMap::new(),
+            names: HashMap::new(),
         }
     }
 
     pub fn add_node(&mut self, node: KnowledgeNode) {
+        if node.node_type != NodeType::Document {
+            self.names.insert(node.title.to_lowercase(), node.id.clone());
+        }
         self.nodes.insert(node.id.clone(), node);
     }
 
+    /// Ajoute une arête ; une arête identique (mêmes extrémités, relation et source) voit son poids cumulé
     pub fn add_edge(&mut self, edge: KnowledgeEdge) {
-        self.edges.push(edge);
+        let existing = self.adjacency.get(&edge.from_id).and_then(|indices| {
+            indices.iter().copied().find(|&i| {
+                let e = &self.edges[i];
+                e.from_id == edge.from_id
+                    && e.to_id == edge.to_id
+                    && e.relation_type == edge.relation_type
+                    && e.source_doc == edge.source_doc
+            })
+        });
+
+        match existing {
+            Some(index) => self.edges[index].weight += edge.weight,
+            None => {
+                let index = self.edges.len();
+                self.adjacency.entry(edge.from_id.clone()).or_default().push(index);
+                if edge.to_id != edge.from_id {
+                    self.adjacency.entry(edge.to_id.clone()).or_default().push(index);
+                }
+                self.edges.push(edge);
+            }
+        }
     }
 
     pub fn get_node(&self, id: &str) -> Option<&KnowledgeNode> {
@@ -80,6 +183,310 @@ impl KnowledgeGraph {
             .filter_map(|id| self.nodes.get(id))
             .collect()
     }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Nombre d'arêtes incidentes
+    pub fn degree(&self, node_id: &str) -> usize {
+        self.adjacency.get(node_id).map(|a| a.len()).unwrap_or(0)
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Alimentation depuis l'indexation
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Remplace la contribution d'un document par le résultat d'extraction
+    pub fn index_document(&mut self, document: &IndexedDocument, extraction: &Extraction) {
+        self.remove_document(&document.id);
+
+        let doc_node = document_node_id(&document.id);
+        let mut metadata = HashMap::new();
+        metadata.insert("doc_type".to_string(), document.doc_type.clone());
+        self.add_node(KnowledgeNode {
+            id: doc_node.clone(),
+            doc_id: document.id.clone(),
+            title: document.title.clone(),
+            node_type: NodeType::Document,
+            metadata,
+            sources: Vec::new(),
+        });
+
+        let mut local_names: HashMap<String, String> = HashMap::new();
+        for entity in &extraction.entities {
+            let id = entity_node_id(&entity.kind, &entity.name);
+            let source = ChunkRef {
+                doc_id: document.id.clone(),
+                chunk_id: entity.chunk_id.clone(),
+            };
+
+            let node = self.nodes.entry(id.clone()).or_insert_with(|| KnowledgeNode {
+                id: id.clone(),
+                doc_id: document.id.clone(),
+                title: entity.name.clone(),
+                node_type: entity.kind.clone(),
+                metadata: HashMap::new(),
+                sources: Vec::new(),
+            });
+            if !node.sources.contains(&source) {
+                node.sources.push(source);
+            }
+            self.names.insert(entity.name.to_lowercase(), id.clone());
+            local_names.insert(entity.name.to_lowercase(), id.clone());
+
+            self.add_edge(KnowledgeEdge {
+                from_id: doc_node.clone(),
+                to_id: id,
+                relation_type: RelationType::Mentions,
+                weight: 1.0,
+                source_doc: Some(document.id.clone()),
+            });
+        }
+
+        for relation in &extraction.relations {
+            let from = local_names.get(&relation.from.to_lowercase());
+            let to = local_names.get(&relation.to.to_lowercase());
+            if let (Some(from), Some(to)) = (from, to) {
+                if from != to {
+                    self.add_edge(KnowledgeEdge {
+                        from_id: from.clone(),
+                        to_id: to.clone(),
+                        relation_type: RelationType::from_label(&relation.relation),
+                        weight: 1.0,
+                        source_doc: Some(document.id.clone()),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Retire un document, ses arêtes et les entités qu'il était seul à citer
+    pub fn remove_document(&mut self, doc_id: &str) -> bool {
+        let doc_node = document_node_id(doc_id);
+        let existed = self.nodes.remove(&doc_node).is_some();
+
+        let mut orphaned = Vec::new();
+        for node in self.nodes.values_mut() {
+            let before = node.sources.len();
+            node.sources.retain(|s| s.doc_id != doc_id);
+            if before > 0 && node.sources.is_empty() {
+                orphaned.push(node.id.clone());
+            }
+        }
+        for id in &orphaned {
+            if let Some(node) = self.nodes.remove(id) {
+                let key = node.title.to_lowercase();
+                if self.names.get(&key) == Some(id) {
+                    self.names.remove(&key);
+                }
+            }
+        }
+
+        let removed: HashSet<&String> = orphaned.iter().chain(std::iter::once(&doc_node)).collect();
+        let before = self.edges.len();
+        self.edges.retain(|e| {
+            e.source_doc.as_deref() != Some(doc_id) && !removed.contains(&e.from_id) && !removed.contains(&e.to_id)
+        });
+        if self.edges.len() != before {
+            self.rebuild_adjacency();
+        }
+
+        existed
+    }
+
+    fn rebuild_adjacency(&mut self) {
+        self.adjacency.clear();
+        for (index, edge) in self.edges.iter().enumerate() {
+            self.adjacency.entry(edge.from_id.clone()).or_default().push(index);
+            if edge.to_id != edge.from_id {
+                self.adjacency.entry(edge.to_id.clone()).or_default().push(index);
+            }
+        }
+    }
+
+    /// Entités connues mentionnées dans un texte (mots entiers, insensible à la casse)
+    pub fn entities_in_text(&self, text: &str) -> Vec<&KnowledgeNode> {
+        let lower = text.to_lowercase();
+        let mut found: Vec<&KnowledgeNode> = self
+            .names
+            .iter()
+            .filter(|(name, _)| contains_word(&lower, name))
+            .filter_map(|(_, id)| self.nodes.get(id))
+            .collect();
+        found.sort_by(|a, b| a.id.cmp(&b.id));
+        found.dedup_by(|a, b| a.id == b.id);
+        found
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Parcours
+    // ─────────────────────────────────────────────────────────────────────────
+
+    fn neighbours(&self, node_id: &str) -> Vec<&str> {
+        let mut neighbours: Vec<&str> = self
+            .adjacency
+            .get(node_id)
+            .map(|indices| {
+                indices
+                    .iter()
+                    .map(|&i| {
+                        let e = &self.edges[i];
+                        if e.from_id == node_id {
+                            e.to_id.as_str()
+                        } else {
+                            e.from_id.as_str()
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        neighbours.sort_unstable();
+        neighbours.dedup();
+        neighbours
+    }
+
+    /// Parcours en largeur jusqu'à `max_depth` sauts (arêtes non orientées)
+    pub fn traverse(&self, start: &str, max_depth: usize) -> Vec<(&KnowledgeNode, usize)> {
+        self.bfs_depths(start, max_depth)
+            .into_iter()
+            .filter_map(|(id, depth)| self.nodes.get(id).map(|n| (n, depth)))
+            .collect()
+    }
+
+    fn bfs_depths<'a>(&'a self, start: &'a str, max_depth: usize) -> Vec<(&'a str, usize)> {
+        if !self.nodes.contains_key(start) {
+            return Vec::new();
+        }
+
+        let mut visited: HashSet<&str> = HashSet::from([start]);
+        let mut order = vec![(start, 0)];
+        let mut queue = VecDeque::from([(start, 0)]);
+
+        while let Some((current, depth)) = queue.pop_front() {
+            if depth == max_depth {
+                continue;
+            }
+            for next in self.neighbours(current) {
+                if visited.insert(next) && self.nodes.contains_key(next) {
+                    order.push((next, depth + 1));
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+
+        order
+    }
+
+    /// Plus court chemin (en nombre de sauts) entre deux nœuds
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<Vec<&KnowledgeNode>> {
+        if !self.nodes.contains_key(from) || !self.nodes.contains_key(to) {
+            return None;
+        }
+
+        let mut previous: HashMap<&str, &str> = HashMap::new();
+        let mut visited: HashSet<&str> = HashSet::from([from]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(current) = queue.pop_front() {
+            if current == to {
+                let mut path = vec![current];
+                let mut cursor = current;
+                while let Some(&prev) = previous.get(cursor) {
+                    path.push(prev);
+                    cursor = prev;
+                }
+                path.reverse();
+                return path.into_iter().map(|id| self.nodes.get(id)).collect();
+            }
+            for next in self.neighbours(current) {
+                if visited.insert(next) {
+                    previous.insert(next, current);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Voisinage d'un nœud, limité en profondeur et en taille, pour la visualisation
+    pub fn neighbourhood(&self, center: &str, depth: usize, max_nodes: usize) -> GraphExport {
+        let included: Vec<(&str, usize)> = self.bfs_depths(center, depth).into_iter().take(max_nodes).collect();
+        let ids: HashSet<&str> = included.iter().map(|(id, _)| *id).collect();
+
+        let nodes = included
+            .iter()
+            .filter_map(|(id, depth)| {
+                self.nodes.get(*id).map(|node| GraphExportNode {
+                    id: node.id.clone(),
+                    label: node.title.clone(),
+                    node_type: node.node_type.clone(),
+                    depth: *depth,
+                    degree: self.degree(&node.id),
+                })
+            })
+            .collect();
+
+        // Les arêtes parallèles (plusieurs documents sources) sont fusionnées
+        let mut merged: Vec<GraphExportEdge> = Vec::new();
+        for edge in &self.edges {
+            if !ids.contains(edge.from_id.as_str()) || !ids.contains(edge.to_id.as_str()) {
+                continue;
+            }
+            let relation = edge.relation_type.label();
+            match merged
+                .iter_mut()
+                .find(|e| e.source == edge.from_id && e.target == edge.to_id && e.relation == relation)
+            {
+                Some(existing) => existing.weight += edge.weight,
+                None => merged.push(GraphExportEdge {
+                    source: edge.from_id.clone(),
+                    target: edge.to_id.clone(),
+                    relation,
+                    weight: edge.weight,
+                }),
+            }
+        }
+
+        GraphExport {
+            center: center.to_string(),
+            nodes,
+            edges: merged,
+        }
+    }
+
+    /// Proximité (0..1) entre un résultat et des entités : 1 si le chunk les cite,
+    /// 0.8 si le document les cite, puis décroissante avec la distance
+    pub fn proximity(&self, doc_id: &str, chunk_id: Option<&str>, entity_ids: &[String]) -> f32 {
+        let doc_node = document_node_id(doc_id);
+        let depths: HashMap<&str, usize> = self.bfs_depths(&doc_node, 3).into_iter().collect();
+
+        entity_ids
+            .iter()
+            .filter_map(|id| self.nodes.get(id))
+            .map(|entity| {
+                let in_chunk = chunk_id.is_some()
+                    && entity
+                        .sources
+                        .iter()
+                        .any(|s| s.doc_id == doc_id && s.chunk_id.as_deref() == chunk_id);
+                if in_chunk {
+                    return 1.0;
+                }
+                match depths.get(entity.id.as_str()) {
+                    Some(1) => 0.8,
+                    Some(2) => 0.4,
+                    Some(3) => 0.2,
+                    _ => 0.0,
+                }
+            })
+            .fold(0.0, f32::max)
+    }
 }
 
 impl Default for KnowledgeGraph {
@@ -87,3 +494,132 @@ impl Default for KnowledgeGraph {
         Self::new()
     }
 }
+
+pub fn document_node_id(doc_id: &str) -> String {
+    format!("doc:{}", doc_id)
+}
+
+pub fn entity_node_id(kind: &NodeType, name: &str) -> String {
+    format!("{:?}:{}", kind, name.trim().to_lowercase()).to_lowercase()
+}
+
+fn contains_word(haystack: &str, needle: &str) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    haystack.match_indices(needle).any(|(start, _)| {
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[start + needle.len()..].chars().next();
+        !before.map(is_word).unwrap_or(false) && !after.map(is_word).unwrap_or(false)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::extraction::EntityExtractor;
+    use crate::semantic::DocumentChunk;
+
+    fn document(id: &str, chunks: &[&str]) -> IndexedDocument {
+        IndexedDocument {
+            id: id.to_string(),
+            title: id.to_string(),
+            content: chunks.join("\n\n"),
+            doc_type: "text".to_string(),
+            metadata: HashMap::new(),
+            embedding: Vec::new(),
+            embedding_model: None,
+            chunks: chunks
+                .iter()
+                .enumerate()
+                .map(|(i, c)| DocumentChunk {
+                    id: format!("{}-c{}", id, i),
+                    content: c.to_string(),
+                    start_pos: 0,
+                    end_pos: c.len(),
+                    embedding: Vec::new(),
+                    section_title: None,
+                    symbol_path: None,
+                })
+                .collect(),
+            indexed_at: chrono::Utc::now(),
+        }
+    }
+
+    fn graph_with(docs: &[IndexedDocument]) -> KnowledgeGraph {
+        let extractor = EntityExtractor::new();
+        let mut graph = KnowledgeGraph::new();
+        for doc in docs {
+            graph.index_document(doc, &extractor.extract(doc));
+        }
+        graph
+    }
+
+    #[test]
+    fn test_entities_link_to_source_chunks() {
+        let doc = document("cr1", &["Réunion de lancement.", "Marie Dupont pilote le projet Atlas."]);
+        let graph = graph_with(&[doc]);
+
+        let atlas = graph.get_node(&entity_node_id(&NodeType::Project, "Atlas")).unwrap();
+        assert_eq!(
+            atlas.sources,
+            vec![ChunkRef {
+                doc_id: "cr1".to_string(),
+                chunk_id: Some("cr1-c1".to_string())
+            }]
+        );
+        assert_eq!(graph.entities_in_text("où en est atlas ?").len(), 1);
+    }
+
+    #[test]
+    fn test_multi_hop_and_shortest_path() {
+        let graph = graph_with(&[
+            document("a", &["Marie Dupont pilote le projet Atlas."]),
+            document("b", &["Le projet Atlas utilise Rust et Docker."]),
+        ]);
+
+        let marie = entity_node_id(&NodeType::Person, "Marie Dupont");
+        let docker = entity_node_id(&NodeType::Technology, "docker");
+
+        let path = graph.shortest_path(&marie, &docker).unwrap();
+        let ids: Vec<&str> = path.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids.first(), Some(&marie.as_str()));
+        assert_eq!(ids.last(), Some(&docker.as_str()));
+        assert_eq!(ids.len(), 3); // Marie → Atlas → Docker
+
+        let reachable = graph.traverse(&marie, 2);
+        assert!(reachable.iter().any(|(n, d)| n.id == docker && *d == 2));
+
+        let export = graph.neighbourhood(&marie, 1, 50);
+        assert!(export.nodes.iter().all(|n| n.depth <= 1));
+        assert!(export.edges.iter().all(|e| export.nodes.iter().any(|n| n.id == e.source)));
+    }
+
+    #[test]
+    fn test_remove_document_drops_orphans() {
+        let mut graph = graph_with(&[
+            document("a", &["Marie Dupont pilote le projet Atlas."]),
+            document("b", &["Le projet Atlas utilise Docker."]),
+        ]);
+        let marie = entity_node_id(&NodeType::Person, "Marie Dupont");
+        let atlas = entity_node_id(&NodeType::Project, "Atlas");
+
+        assert!(graph.remove_document("a"));
+        assert!(graph.get_node(&marie).is_none());
+        assert!(graph.get_node(&atlas).is_some());
+        assert!(graph.traverse(&atlas, 3).iter().all(|(n, _)| n.doc_id != "a" || n.node_type != NodeType::Document));
+    }
+
+    #[test]
+    fn test_proximity() {
+        let graph = graph_with(&[
+            document("a", &["Intro.", "Le projet Atlas démarre."]),
+            document("b", &["Le projet Atlas utilise Docker."]),
+            document("c", &["Docker en production."]),
+        ]);
+        let atlas = vec![entity_node_id(&NodeType::Project, "Atlas")];
+
+        assert_eq!(graph.proximity("a", Some("a-c1"), &atlas), 1.0);
+        assert_eq!(graph.proximity("a", Some("a-c0"), &atlas), 0.8);
+        assert!(graph.proximity("c", None, &atlas) > 0.0);
+        assert!(graph.proximity("c", None, &atlas) < 0.8);
+    }
+}
@@ -0,0 +1,543 @@
+// TITANE∞ v13 - Incremental HNSW
+// Index HNSW incrémental : insertion/suppression (tombstones), compaction,
+// vecteurs contigus mappables en mémoire
+
+use crate::semantic::vector_store::cosine_distance;
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+
+const VECTORS_MAGIC: &[u8; 4] = b"TVEC";
+const VECTORS_VERSION: u32 = 1;
+/// magic (4) + version (4) + dimensions (4) + réservé (4) + nombre (8)
+const VECTORS_HEADER_SIZE: usize = 24;
+
+/// Paramètres HNSW
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswParams {
+    /// Connexions par nœud (2×M au niveau 0)
+    pub m: usize,
+    pub ef_construction: usize,
+    pub ef_search: usize,
+    pub seed: u64,
+}
+
+impl Default for HnswParams {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 100,
+            ef_search: 64,
+            seed: 42,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HnswNode {
+    id: String,
+    level: usize,
+    neighbors: Vec<Vec<u32>>,
+    deleted: bool,
+}
+
+/// Graphe sérialisable (les vecteurs sont stockés à part)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswGraph {
+    dimensions: usize,
+    params: HnswParams,
+    nodes: Vec<HnswNode>,
+    entry_point: Option<u32>,
+    max_level: usize,
+    rng_state: u64,
+}
+
+/// Vecteurs contigus : en mémoire, ou mappés depuis le disque avec les
+/// insertions postérieures au chargement conservées en mémoire
+enum VectorArena {
+    Owned(Vec<f32>),
+    Mapped {
+        map: Mmap,
+        count: usize,
+        appended: Vec<f32>,
+    },
+}
+
+impl VectorArena {
+    fn get(&self, index: usize, dimensions: usize) -> &[f32] {
+        match self {
+            VectorArena::Owned(data) => &data[index * dimensions..(index + 1) * dimensions],
+            VectorArena::Mapped { map, count, appended } => {
+                if index < *count {
+                    let start = VECTORS_HEADER_SIZE + index * dimensions * 4;
+                    let bytes = &map[start..start + dimensions * 4];
+                    // SAFETY : alignement vérifié au chargement (en-tête multiple de 4,
+                    // mapping aligné sur une page), données f32 little-endian
+                    unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const f32, dimensions) }
+                } else {
+                    let i = index - count;
+                    &appended[i * dimensions..(i + 1) * dimensions]
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, vector: &[f32]) {
+        match self {
+            VectorArena::Owned(data) => data.extend_from_slice(vector),
+            VectorArena::Mapped { appended, .. } => appended.extend_from_slice(vector),
+        }
+    }
+}
+
+/// Distance totalement ordonnée pour les tas
+#[derive(Debug, Clone, Copy)]
+struct Dist(f32);
+
+impl PartialEq for Dist {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Dist {}
+
+impl PartialOrd for Dist {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Dist {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// Index HNSW incrémental
+pub struct HnswIndex {
+    graph: HnswGraph,
+    vectors: VectorArena,
+    id_to_node: HashMap<String, u32>,
+    deleted_count: usize,
+}
+
+impl HnswIndex {
+    pub fn new(dimensions: usize, params: HnswParams) -> Self {
+        let rng_state = params.seed.max(1);
+        Self {
+            graph: HnswGraph {
+                dimensions,
+                params,
+                nodes: Vec::new(),
+                entry_point: None,
+                max_level: 0,
+                rng_state,
+            },
+            vectors: VectorArena::Owned(Vec::new()),
+            id_to_node: HashMap::new(),
+            deleted_count: 0,
+        }
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.graph.dimensions
+    }
+
+    pub fn params(&self) -> &HnswParams {
+        &self.graph.params
+    }
+
+    /// Nombre de points actifs
+    pub fn len(&self) -> usize {
+        self.graph.nodes.len() - self.deleted_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn deleted_count(&self) -> usize {
+        self.deleted_count
+    }
+
+    /// Part des nœuds supprimés encore présents dans le graphe
+    pub fn tombstone_ratio(&self) -> f32 {
+        if self.graph.nodes.is_empty() {
+            0.0
+        } else {
+            self.deleted_count as f32 / self.graph.nodes.len() as f32
+        }
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.id_to_node.contains_key(id)
+    }
+
+    pub fn vector(&self, id: &str) -> Option<&[f32]> {
+        self.id_to_node
+            .get(id)
+            .map(|&node| self.vectors.get(node as usize, self.graph.dimensions))
+    }
+
+    /// Identifiants et vecteurs des points actifs
+    pub fn live_points(&self) -> impl Iterator<Item = (&str, &[f32])> {
+        self.graph
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| !n.deleted)
+            .map(|(i, n)| (n.id.as_str(), self.vectors.get(i, self.graph.dimensions)))
+    }
+
+    /// Insère (ou remplace) un point sans reconstruire l'index
+    pub fn insert(&mut self, id: String, vector: &[f32]) {
+        debug_assert_eq!(vector.len(), self.graph.dimensions);
+        self.remove(&id);
+
+        let node = self.graph.nodes.len() as u32;
+        let level = self.random_level();
+        self.vectors.push(vector);
+        self.graph.nodes.push(HnswNode {
+            id: id.clone(),
+            level,
+            neighbors: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.id_to_node.insert(id, node);
+
+        let entry = match self.graph.entry_point {
+            Some(entry) => entry,
+            None => {
+                self.graph.entry_point = Some(node);
+                self.graph.max_level = level;
+                return;
+            }
+        };
+
+        // Descente gloutonne jusqu'au niveau du nouveau nœud
+        let mut entry_points = vec![entry];
+        for layer in (level + 1..=self.graph.max_level).rev() {
+            if let Some(&(_, closest)) = self.search_layer(vector, &entry_points, 1, layer).first() {
+                entry_points = vec![closest];
+            }
+        }
+
+        for layer in (0..=level.min(self.graph.max_level)).rev() {
+            let candidates =
+                self.search_layer(vector, &entry_points, self.graph.params.ef_construction, layer);
+            let max_connections = self.max_connections(layer);
+
+            let selected: Vec<u32> = candidates
+                .iter()
+                .map(|&(_, n)| n)
+                .filter(|&n| n != node)
+                .take(self.graph.params.m)
+                .collect();
+            self.graph.nodes[node as usize].neighbors[layer] = selected.clone();
+
+            for neighbor in selected {
+                let links = &mut self.graph.nodes[neighbor as usize].neighbors[layer];
+                links.push(node);
+                if links.len() > max_connections {
+                    self.prune(neighbor, layer, max_connections);
+                }
+            }
+
+            entry_points = candidates.into_iter().map(|(_, n)| n).collect();
+        }
+
+        if level > self.graph.max_level {
+            self.graph.max_level = level;
+            self.graph.entry_point = Some(node);
+        }
+    }
+
+    /// Supprime un point (tombstone : le nœud reste navigable jusqu'à la compaction)
+    pub fn remove(&mut self, id: &str) -> bool {
+        match self.id_to_node.remove(id) {
+            Some(node) => {
+                self.graph.nodes[node as usize].deleted = true;
+                self.deleted_count += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// k plus proches voisins actifs : (id, distance cosinus)
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<(String, f32)> {
+        let entry = match self.graph.entry_point {
+            Some(entry) if k > 0 && !self.is_empty() => entry,
+            _ => return Vec::new(),
+        };
+
+        let mut entry_points = vec![entry];
+        for layer in (1..=self.graph.max_level).rev() {
+            if let Some(&(_, closest)) = self.search_layer(query, &entry_points, 1, layer).first() {
+                entry_points = vec![closest];
+            }
+        }
+
+        // Sur-échantillonne en présence de tombstones
+        let ef = if self.deleted_count > 0 {
+            self.graph.params.ef_search.max(k) * 2
+        } else {
+            self.graph.params.ef_search.max(k)
+        };
+
+        self.search_layer(query, &entry_points, ef, 0)
+            .into_iter()
+            .filter(|&(_, n)| !self.graph.nodes[n as usize].deleted)
+            .take(k)
+            .map(|(d, n)| (self.graph.nodes[n as usize].id.clone(), d.0))
+            .collect()
+    }
+
+    /// Nouvel index ne contenant que les points actifs
+    pub fn compacted(&self) -> HnswIndex {
+        let mut index = HnswIndex::new(self.graph.dimensions, self.graph.params.clone());
+        for (id, vector) in self.live_points() {
+            index.insert(id.to_string(), vector);
+        }
+        index
+    }
+
+    fn max_connections(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.graph.params.m * 2
+        } else {
+            self.graph.params.m
+        }
+    }
+
+    fn distance(&self, query: &[f32], node: u32) -> Dist {
+        Dist(cosine_distance(query, self.vectors.get(node as usize, self.graph.dimensions)))
+    }
+
+    /// Recherche gloutonne sur une couche ; résultats triés par distance croissante
+    fn search_layer(&self, query: &[f32], entry_points: &[u32], ef: usize, layer: usize) -> Vec<(Dist, u32)> {
+        let mut visited: HashSet<u32> = entry_points.iter().copied().collect();
+        let mut candidates: BinaryHeap<Reverse<(Dist, u32)>> = BinaryHeap::new();
+        let mut results: BinaryHeap<(Dist, u32)> = BinaryHeap::new();
+
+        for &ep in entry_points {
+            let d = self.distance(query, ep);
+            candidates.push(Reverse((d, ep)));
+            results.push((d, ep));
+        }
+        while results.len() > ef {
+            results.pop();
+        }
+
+        while let Some(Reverse((d, current))) = candidates.pop() {
+            if let Some(&(worst, _)) = results.peek() {
+                if d > worst && results.len() >= ef {
+                    break;
+                }
+            }
+
+            let node = &self.graph.nodes[current as usize];
+            let neighbors = match node.neighbors.get(layer) {
+                Some(n) => n,
+                None => continue,
+            };
+
+            for &neighbor in neighbors {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let d = self.distance(query, neighbor);
+                let worst = results.peek().map(|&(w, _)| w);
+                if results.len() < ef || worst.map(|w| d < w).unwrap_or(true) {
+                    candidates.push(Reverse((d, neighbor)));
+                    results.push((d, neighbor));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        let mut sorted = results.into_vec();
+        sorted.sort();
+        sorted
+    }
+
+    /// Ne conserve que les `max` voisins les plus proches
+    fn prune(&mut self, node: u32, layer: usize, max: usize) {
+        let base = self.vectors.get(node as usize, self.graph.dimensions).to_vec();
+        let mut links: Vec<(Dist, u32)> = self.graph.nodes[node as usize].neighbors[layer]
+            .iter()
+            .map(|&n| (self.distance(&base, n), n))
+            .collect();
+        links.sort();
+        links.truncate(max);
+        self.graph.nodes[node as usize].neighbors[layer] = links.into_iter().map(|(_, n)| n).collect();
+    }
+
+    /// Niveau aléatoire (distribution géométrique, mL = 1/ln(M)), xorshift64 déterministe
+    fn random_level(&mut self) -> usize {
+        let mut x = self.graph.rng_state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.graph.rng_state = x;
+
+        let uniform = ((x >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        let ml = 1.0 / (self.graph.params.m.max(2) as f64).ln();
+        ((-uniform.ln() * ml).floor() as usize).min(16)
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Persistance : graphe via bincode, vecteurs en f32 little-endian bruts
+    // ─────────────────────────────────────────────────────────────────────────
+
+    pub fn graph(&self) -> &HnswGraph {
+        &self.graph
+    }
+
+    /// Écrit les vecteurs (tous les nœuds, y compris supprimés) au format mappable
+    pub fn write_vectors(&self, path: &Path) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(VECTORS_MAGIC)?;
+        writer.write_all(&VECTORS_VERSION.to_le_bytes())?;
+        writer.write_all(&(self.graph.dimensions as u32).to_le_bytes())?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        writer.write_all(&(self.graph.nodes.len() as u64).to_le_bytes())?;
+
+        for i in 0..self.graph.nodes.len() {
+            for value in self.vectors.get(i, self.graph.dimensions) {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        writer.flush()
+    }
+
+    /// Reconstitue l'index depuis son graphe et le fichier de vecteurs, mappé en
+    /// mémoire quand la plateforme le permet (sinon lu intégralement)
+    pub fn from_parts(graph: HnswGraph, vectors_path: &Path) -> std::io::Result<Self> {
+        let file = File::open(vectors_path)?;
+        // SAFETY : un fichier de vecteurs n'est jamais modifié ni remplacé une fois
+        // écrit ; chaque sauvegarde crée une nouvelle génération (voir `VectorStore::save`)
+        let map = unsafe { Mmap::map(&file)? };
+
+        let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string());
+        if map.len() < VECTORS_HEADER_SIZE || &map[0..4] != VECTORS_MAGIC {
+            return Err(invalid("Fichier de vecteurs invalide"));
+        }
+        let version = u32::from_le_bytes(map[4..8].try_into().unwrap());
+        let dimensions = u32::from_le_bytes(map[8..12].try_into().unwrap()) as usize;
+        let count = u64::from_le_bytes(map[16..24].try_into().unwrap()) as usize;
+
+        if version != VECTORS_VERSION {
+            return Err(invalid("Version de fichier de vecteurs non supportée"));
+        }
+        if dimensions != graph.dimensions || count != graph.nodes.len() {
+            return Err(invalid("Vecteurs et graphe incohérents"));
+        }
+        if map.len() < VECTORS_HEADER_SIZE + count * dimensions * 4 {
+            return Err(invalid("Fichier de vecteurs tronqué"));
+        }
+
+        let aligned = (map.as_ptr() as usize + VECTORS_HEADER_SIZE) % std::mem::align_of::<f32>() == 0;
+        let vectors = if aligned && cfg!(target_endian = "little") {
+            VectorArena::Mapped {
+                map,
+                count,
+                appended: Vec::new(),
+            }
+        } else {
+            let mut bytes = Vec::new();
+            File::open(vectors_path)?.read_to_end(&mut bytes)?;
+            let data = bytes[VECTORS_HEADER_SIZE..VECTORS_HEADER_SIZE + count * dimensions * 4]
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect();
+            VectorArena::Owned(data)
+        };
+
+        let mut id_to_node = HashMap::new();
+        let mut deleted_count = 0;
+        for (i, node) in graph.nodes.iter().enumerate() {
+            if node.deleted {
+                deleted_count += 1;
+            } else {
+                id_to_node.insert(node.id.clone(), i as u32);
+            }
+        }
+
+        Ok(Self {
+            graph,
+            vectors,
+            id_to_node,
+            deleted_count,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(angle: f32) -> Vec<f32> {
+        vec![angle.cos(), angle.sin(), 0.0]
+    }
+
+    #[test]
+    fn test_incremental_insert_and_search() {
+        let mut index = HnswIndex::new(3, HnswParams::default());
+        for i in 0..200 {
+            index.insert(format!("p{}", i), &unit(i as f32 * 0.01));
+        }
+
+        let results = index.search(&unit(0.5), 3);
+        assert_eq!(results[0].0, "p50");
+        assert_eq!(index.len(), 200);
+    }
+
+    #[test]
+    fn test_tombstones_and_compaction() {
+        let mut index = HnswIndex::new(3, HnswParams::default());
+        for i in 0..50 {
+            index.insert(format!("p{}", i), &unit(i as f32 * 0.02));
+        }
+
+        assert!(index.remove("p10"));
+        assert!(!index.remove("p10"));
+        let results = index.search(&unit(0.2), 5);
+        assert!(results.iter().all(|(id, _)| id != "p10"));
+        assert_eq!(index.deleted_count(), 1);
+
+        let compacted = index.compacted();
+        assert_eq!(compacted.len(), 49);
+        assert_eq!(compacted.deleted_count(), 0);
+        assert!(!compacted.contains("p10"));
+    }
+
+    #[test]
+    fn test_vectors_roundtrip_mapped() {
+        let mut index = HnswIndex::new(3, HnswParams::default());
+        for i in 0..20 {
+            index.insert(format!("p{}", i), &unit(i as f32 * 0.1));
+        }
+        index.remove("p3");
+
+        let path = std::env::temp_dir().join(format!("hnsw_test_{}.vectors.bin", uuid::Uuid::new_v4()));
+        index.write_vectors(&path).unwrap();
+
+        let mut loaded = HnswIndex::from_parts(index.graph().clone(), &path).unwrap();
+        assert_eq!(loaded.len(), 19);
+        assert_eq!(loaded.vector("p7").unwrap(), index.vector("p7").unwrap());
+
+        // Insertion après chargement mappé
+        loaded.insert("extra".to_string(), &unit(0.75));
+        assert_eq!(loaded.search(&unit(0.75), 1)[0].0, "extra");
+
+        std::fs::remove_file(path).ok();
+    }
+}
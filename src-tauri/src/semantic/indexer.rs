@@ -1,9 +1,14 @@
 // TITANE∞ v13 - Intelligent Document Indexer
 // Indexation intelligente avec chunking sémantique et hiérarchisation
 
+use crate::semantic::chunking::{self, ChunkContext, ChunkStrategy};
+use crate::semantic::extraction::{EntityExtractor, Extraction};
+use crate::semantic::graph::KnowledgeGraph;
+use crate::semantic::lexical::Bm25Index;
 use crate::semantic::{DocumentChunk, IndexedDocument};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
 /// Configuration de l'indexer
@@ -33,11 +38,21 @@ impl Default for IndexerConfig {
 /// Indexer de documents
 pub struct DocumentIndexer {
     config: IndexerConfig,
+    /// Stratégies de découpage par `doc_type` (code, Markdown...)
+    strategies: HashMap<String, Arc<dyn ChunkStrategy>>,
 }
 
 impl DocumentIndexer {
     pub fn new(config: IndexerConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            strategies: chunking::default_strategies(),
+        }
+    }
+
+    /// Enregistre (ou remplace) la stratégie de découpage d'un type de document
+    pub fn register_strategy(&mut self, doc_type: &str, strategy: Arc<dyn ChunkStrategy>) {
+        self.strategies.insert(doc_type.to_lowercase(), strategy);
     }
 
     /// Indexe un document avec chunking intelligent
@@ -49,7 +64,7 @@ impl DocumentIndexer {
         doc_type: String,
         metadata: HashMap<String, String>,
     ) -> IndexedDocument {
-        let chunks = self.chunk_document(&content);
+        let chunks = self.chunk_typed(&content, &doc_type, &title);
 
         IndexedDocument {
             id,
@@ -58,11 +73,26 @@ impl DocumentIndexer {
             doc_type,
             metadata,
             embedding: Vec::new(), // Sera généré plus tard par l'embedder
+            embedding_model: None,
             chunks,
             indexed_at: chrono::Utc::now(),
         }
     }
 
+    /// Découpe selon la stratégie du type de document, sinon par sections et paragraphes
+    pub fn chunk_typed(&self, content: &str, doc_type: &str, title: &str) -> Vec<DocumentChunk> {
+        match self.strategies.get(&doc_type.to_lowercase()) {
+            Some(strategy) => strategy.chunk(
+                content,
+                &ChunkContext {
+                    title,
+                    config: &self.config,
+                },
+            ),
+            None => self.chunk_document(content),
+        }
+    }
+
     /// Découpe un document en chunks intelligents
     pub fn chunk_document(&self, content: &str) -> Vec<DocumentChunk> {
         let mut chunks = Vec::new();
@@ -152,7 +182,7 @@ impl DocumentIndexer {
                     
                     // Démarre un nouveau chunk avec overlap
                     let overlap_text = self.get_overlap(&current_chunk);
-                    current_chunk = overlap_text + "\n\n" + &paragraph;
+                    current_chunk = format!("{}\n\n{}", overlap_text, paragraph);
                     chunk_start += current_chunk.len() - overlap_text.len();
                 } else {
                     if !current_chunk.is_empty() {
@@ -217,6 +247,7 @@ impl DocumentIndexer {
             end_pos: start_pos + content.len(),
             embedding: Vec::new(), // Sera généré plus tard
             section_title,
+            symbol_path: None,
         }
     }
 
@@ -241,11 +272,28 @@ impl DocumentIndexer {
 
     /// Met à jour un document indexé (ré-indexation)
     pub fn reindex_document(&self, document: &mut IndexedDocument) {
-        document.chunks = self.chunk_document(&document.content);
+        document.chunks = self.chunk_typed(&document.content, &document.doc_type, &document.title);
         document.indexed_at = chrono::Utc::now();
     }
 }
 
+/// Textes embeddés par entrée de l'index, alignés sur l'index lexical
+pub fn embedding_inputs(doc: &IndexedDocument) -> Vec<(String, String)> {
+    if doc.chunks.is_empty() {
+        return vec![(doc.id.clone(), format!("{}\n{}", doc.title, doc.content))];
+    }
+    doc.chunks
+        .iter()
+        .map(|chunk| {
+            let text = match &chunk.section_title {
+                Some(section) => format!("{}\n{}\n{}", doc.title, section, chunk.content),
+                None => format!("{}\n{}", doc.title, chunk.content),
+            };
+            (chunk.id.clone(), text)
+        })
+        .collect()
+}
+
 /// Statistiques d'indexation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexStats {
@@ -260,6 +308,11 @@ pub struct IndexStats {
 pub struct IndexManager {
     indexer: DocumentIndexer,
     documents: HashMap<String, IndexedDocument>,
+    /// Index lexical BM25 maintenu en phase avec `documents`
+    lexical: Bm25Index,
+    /// Graphe d'entités alimenté à chaque indexation
+    graph: KnowledgeGraph,
+    extractor: EntityExtractor,
 }
 
 impl IndexManager {
@@ -267,6 +320,9 @@ impl IndexManager {
         Self {
             indexer: DocumentIndexer::new(config),
             documents: HashMap::new(),
+            lexical: Bm25Index::default(),
+            graph: KnowledgeGraph::new(),
+            extractor: EntityExtractor::new(),
         }
     }
 
@@ -280,6 +336,8 @@ impl IndexManager {
         metadata: HashMap<String, String>,
     ) -> String {
         let doc = self.indexer.index_document(id.clone(), title, content, doc_type, metadata);
+        self.lexical.add_document(&doc);
+        self.graph.index_document(&doc, &self.extractor.extract(&doc));
         self.documents.insert(id.clone(), doc);
         id
     }
@@ -289,17 +347,111 @@ impl IndexManager {
         if let Some(doc) = self.documents.get_mut(id) {
             doc.content = new_content;
             self.indexer.reindex_document(doc);
+            // Nouveaux chunks : embeddings à recalculer
+            doc.embedding_model = None;
+            self.lexical.add_document(doc);
+            self.graph.index_document(doc, &self.extractor.extract(doc));
             Ok(())
         } else {
             Err(format!("Document {} not found", id))
         }
     }
 
+    /// Enregistre une stratégie de découpage pour un type de document
+    pub fn register_chunk_strategy(&mut self, doc_type: &str, strategy: Arc<dyn ChunkStrategy>) {
+        self.indexer.register_strategy(doc_type, strategy);
+    }
+
+    /// Met à jour une métadonnée d'un document (prise en compte au prochain ré-indexage)
+    pub fn set_metadata(&mut self, id: &str, key: &str, value: &str) -> bool {
+        match self.documents.get_mut(id) {
+            Some(doc) => {
+                doc.metadata.insert(key.to_string(), value.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Documents dont les embeddings manquent ou viennent d'un autre modèle
+    pub fn stale_embeddings(&self, fingerprint: &str) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .documents
+            .values()
+            .filter(|doc| doc.embedding_model.as_deref() != Some(fingerprint))
+            .map(|doc| doc.id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Enregistre le modèle ayant embeddé les chunks du document
+    pub fn mark_embedded(&mut self, id: &str, fingerprint: &str) -> bool {
+        match self.documents.get_mut(id) {
+            Some(doc) => {
+                doc.embedding_model = Some(fingerprint.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Oublie les modèles enregistrés : tous les documents seront ré-embeddés
+    pub fn clear_embedding_models(&mut self) {
+        for doc in self.documents.values_mut() {
+            doc.embedding_model = None;
+        }
+    }
+
     /// Supprime un document
     pub fn remove_document(&mut self, id: &str) -> Option<IndexedDocument> {
+        self.lexical.remove_document(id);
+        self.graph.remove_document(id);
         self.documents.remove(id)
     }
 
+    /// Index lexical BM25 (utilisé par `QueryEngine::search`)
+    pub fn lexical_index(&self) -> &Bm25Index {
+        &self.lexical
+    }
+
+    /// Graphe de connaissance (utilisé par `ContextualReranker::rerank_with_graph`)
+    pub fn graph(&self) -> &KnowledgeGraph {
+        &self.graph
+    }
+
+    /// Ajoute au graphe une extraction complémentaire (ex. `extraction::extract_with_llm`)
+    pub fn apply_extraction(&mut self, doc_id: &str, extra: Extraction) -> Result<(), String> {
+        let doc = self
+            .documents
+            .get(doc_id)
+            .ok_or_else(|| format!("Document {} not found", doc_id))?;
+        let mut extraction = self.extractor.extract(doc);
+        extraction.merge(extra);
+        self.graph.index_document(doc, &extraction);
+        Ok(())
+    }
+
+    /// Retrouve un chunk et son document à partir de l'identifiant du chunk
+    pub fn find_chunk(&self, chunk_id: &str) -> Option<(&IndexedDocument, &DocumentChunk)> {
+        self.documents.values().find_map(|doc| {
+            doc.chunks
+                .iter()
+                .find(|c| c.id == chunk_id)
+                .map(|chunk| (doc, chunk))
+        })
+    }
+
+    /// Vrai si `entry_id` est une entrée courante de `doc_id` : un de ses
+    /// chunks, ou le document lui-même s'il n'a pas de chunks
+    pub fn has_entry(&self, doc_id: &str, entry_id: &str) -> bool {
+        match self.documents.get(doc_id) {
+            Some(doc) if doc.chunks.is_empty() => entry_id == doc.id,
+            Some(doc) => doc.chunks.iter().any(|c| c.id == entry_id),
+            None => false,
+        }
+    }
+
     /// Récupère un document
     pub fn get_document(&self, id: &str) -> Option<&IndexedDocument> {
         self.documents.get(id)
@@ -414,4 +566,70 @@ mod tests {
         let stats = manager.get_stats();
         assert_eq!(stats.total_documents, 1);
     }
+
+    #[test]
+    fn test_index_manager_maintains_lexical_index() {
+        let mut manager = IndexManager::new(IndexerConfig::default());
+        manager.add_document(
+            "doc1".to_string(),
+            "Facture".to_string(),
+            "Facture FAC-2024-118 pour la maintenance annuelle du serveur.".repeat(3),
+            "text".to_string(),
+            HashMap::new(),
+        );
+        assert_eq!(manager.lexical_index().search("FAC-2024-118", 5)[0].doc_id, "doc1");
+
+        manager
+            .update_document("doc1", "Avoir AV-2024-007 sur la facture précédente.".repeat(3))
+            .unwrap();
+        assert!(manager
+            .lexical_index()
+            .search("FAC-2024-118", 5)
+            .iter()
+            .all(|h| !h.matched_terms.contains(&"fac-2024-118".to_string())));
+        assert!(!manager.lexical_index().search("AV-2024-007", 5).is_empty());
+
+        manager.remove_document("doc1");
+        assert!(manager.lexical_index().is_empty());
+    }
+
+    #[test]
+    fn test_code_documents_use_code_chunker() {
+        let mut manager = IndexManager::new(IndexerConfig::default());
+        manager.add_document(
+            "code1".to_string(),
+            "parser.rs".to_string(),
+            "pub struct Parser;\n\nimpl Parser {\n    pub fn parse_header(&self) -> bool {\n        true\n    }\n}\n".to_string(),
+            "rust".to_string(),
+            HashMap::new(),
+        );
+
+        let doc = manager.get_document("code1").unwrap();
+        assert!(doc.chunks.iter().any(|c| c.symbol_path.as_deref() == Some("parser::Parser::parse_header")));
+
+        let hit = &manager.lexical_index().search("parse_header", 1)[0];
+        assert_eq!(hit.metadata.get("symbol_path").map(String::as_str), Some("parser::Parser::parse_header"));
+    }
+
+    #[test]
+    fn test_stale_embeddings_follow_model_and_content() {
+        let mut manager = IndexManager::new(IndexerConfig::default());
+        for id in ["a", "b"] {
+            manager.add_document(id.to_string(), id.to_string(), "Contenu.".to_string(), "text".to_string(), HashMap::new());
+        }
+        assert_eq!(manager.stale_embeddings("local:m:8"), vec!["a", "b"]);
+
+        manager.mark_embedded("a", "local:m:8");
+        manager.mark_embedded("b", "local:m:8");
+        assert!(manager.stale_embeddings("local:m:8").is_empty());
+        assert_eq!(manager.get_document("a").unwrap().embedding_model.as_deref(), Some("local:m:8"));
+
+        // Autre modèle : tout est à refaire ; contenu modifié : le document seul
+        assert_eq!(manager.stale_embeddings("ollama:x:8").len(), 2);
+        manager.update_document("b", "Nouveau contenu.".to_string()).unwrap();
+        assert_eq!(manager.stale_embeddings("local:m:8"), vec!["b"]);
+
+        manager.clear_embedding_models();
+        assert_eq!(manager.stale_embeddings("local:m:8").len(), 2);
+    }
 }
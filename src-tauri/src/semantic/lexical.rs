@@ -0,0 +1,389 @@
+// TITANE∞ v13 - Lexical Index (BM25)
+// Index inversé BM25 pour les identifiants exacts (fichiers, codes d'erreur, numéros)
+
+use crate::semantic::embedder::STOPWORDS;
+use crate::semantic::IndexedDocument;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Caractères conservés à l'intérieur d'un jeton (`main.rs`, `E-0433`, `2024/118`)
+const JOINERS: &[char] = &['.', '_', '-', '/', ':', '#'];
+
+/// Paramètres BM25
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bm25Params {
+    pub k1: f32,
+    pub b: f32,
+}
+
+impl Default for Bm25Params {
+    fn default() -> Self {
+        Self { k1: 1.2, b: 0.75 }
+    }
+}
+
+/// Entrée indexée (un chunk, ou le document entier s'il n'a pas de chunks)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LexicalEntry {
+    doc_id: String,
+    length: usize,
+    metadata: HashMap<String, String>,
+}
+
+/// Résultat lexical
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LexicalHit {
+    pub id: String,
+    pub doc_id: String,
+    pub score: f32,
+    pub matched_terms: Vec<String>,
+    pub metadata: HashMap<String, String>,
+}
+
+/// Zone surlignée dans un texte (offsets en octets)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighlightSpan {
+    pub start: usize,
+    pub end: usize,
+    pub term: String,
+}
+
+/// Index inversé BM25
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Bm25Index {
+    params: Bm25Params,
+    /// terme → (id d'entrée → fréquence)
+    postings: HashMap<String, HashMap<String, u32>>,
+    entries: HashMap<String, LexicalEntry>,
+    doc_entries: HashMap<String, Vec<String>>,
+    total_length: usize,
+}
+
+impl Bm25Index {
+    pub fn new(params: Bm25Params) -> Self {
+        Self {
+            params,
+            ..Default::default()
+        }
+    }
+
+    /// Indexe (ou ré-indexe) les chunks d'un document
+    pub fn add_document(&mut self, document: &IndexedDocument) {
+        self.remove_document(&document.id);
+
+        let mut metadata = document.metadata.clone();
+        metadata.insert("doc_id".to_string(), document.id.clone());
+        metadata.insert("doc_type".to_string(), document.doc_type.clone());
+        metadata.insert("title".to_string(), document.title.clone());
+
+        let mut ids = Vec::new();
+        if document.chunks.is_empty() {
+            let text = format!("{}\n{}", document.title, document.content);
+            self.add_entry(&document.id, &document.id, &text, metadata);
+            ids.push(document.id.clone());
+        } else {
+            for chunk in &document.chunks {
+                let text = match &chunk.section_title {
+                    Some(section) => format!("{}\n{}\n{}", document.title, section, chunk.content),
+                    None => format!("{}\n{}", document.title, chunk.content),
+                };
+                let mut chunk_metadata = metadata.clone();
+                if let Some(symbol) = &chunk.symbol_path {
+                    chunk_metadata.insert("symbol_path".to_string(), symbol.clone());
+                }
+                self.add_entry(&chunk.id, &document.id, &text, chunk_metadata);
+                ids.push(chunk.id.clone());
+            }
+        }
+
+        self.doc_entries.insert(document.id.clone(), ids);
+    }
+
+    fn add_entry(&mut self, id: &str, doc_id: &str, text: &str, metadata: HashMap<String, String>) {
+        let tokens = tokenize(text);
+        let mut frequencies: HashMap<String, u32> = HashMap::new();
+        for token in &tokens {
+            *frequencies.entry(token.clone()).or_insert(0) += 1;
+        }
+
+        for (term, tf) in frequencies {
+            self.postings.entry(term).or_default().insert(id.to_string(), tf);
+        }
+
+        self.total_length += tokens.len();
+        self.entries.insert(
+            id.to_string(),
+            LexicalEntry {
+                doc_id: doc_id.to_string(),
+                length: tokens.len(),
+                metadata,
+            },
+        );
+    }
+
+    /// Retire toutes les entrées d'un document
+    pub fn remove_document(&mut self, doc_id: &str) -> bool {
+        let ids = match self.doc_entries.remove(doc_id) {
+            Some(ids) => ids,
+            None => return false,
+        };
+
+        let ids: HashSet<String> = ids.into_iter().collect();
+        for id in &ids {
+            if let Some(entry) = self.entries.remove(id) {
+                self.total_length -= entry.length;
+            }
+        }
+
+        self.postings.retain(|_, posting| {
+            posting.retain(|id, _| !ids.contains(id));
+            !posting.is_empty()
+        });
+
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Recherche à partir d'un texte libre
+    pub fn search(&self, query: &str, k: usize) -> Vec<LexicalHit> {
+        let terms: Vec<(String, f32)> = query_terms(query).into_iter().map(|t| (t, 1.0)).collect();
+        self.search_weighted(&terms, k, |_| true)
+    }
+
+    /// Recherche avec des termes pondérés (ex. synonymes à poids réduit) et un filtre
+    pub fn search_weighted(
+        &self,
+        terms: &[(String, f32)],
+        k: usize,
+        filter: impl Fn(&HashMap<String, String>) -> bool,
+    ) -> Vec<LexicalHit> {
+        if self.entries.is_empty() || terms.is_empty() {
+            return Vec::new();
+        }
+
+        let n = self.entries.len() as f32;
+        let avg_length = self.total_length as f32 / n;
+        let mut scores: HashMap<&str, (f32, Vec<String>)> = HashMap::new();
+
+        for (term, weight) in terms {
+            let posting = match self.postings.get(term) {
+                Some(p) => p,
+                None => continue,
+            };
+
+            let df = posting.len() as f32;
+            let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+
+            for (id, tf) in posting {
+                let length = self.entries.get(id).map(|e| e.length).unwrap_or(0) as f32;
+                let tf = *tf as f32;
+                let norm = self.params.k1 * (1.0 - self.params.b + self.params.b * length / avg_length.max(1.0));
+                let score = weight * idf * tf * (self.params.k1 + 1.0) / (tf + norm);
+
+                let slot = scores.entry(id.as_str()).or_insert((0.0, Vec::new()));
+                slot.0 += score;
+                if !slot.1.contains(term) {
+                    slot.1.push(term.clone());
+                }
+            }
+        }
+
+        let mut hits: Vec<LexicalHit> = scores
+            .into_iter()
+            .filter_map(|(id, (score, matched_terms))| {
+                let entry = self.entries.get(id)?;
+                if !filter(&entry.metadata) {
+                    return None;
+                }
+                Some(LexicalHit {
+                    id: id.to_string(),
+                    doc_id: entry.doc_id.clone(),
+                    score,
+                    matched_terms,
+                    metadata: entry.metadata.clone(),
+                })
+            })
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        hits.truncate(k);
+        hits
+    }
+}
+
+/// Jeton avec sa position dans le texte source
+struct Token {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+fn tokens_with_offsets(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        if c.is_alphanumeric() || JOINERS.contains(&c) {
+            if start.is_none() {
+                start = Some(i);
+            }
+        } else if let Some(s) = start.take() {
+            push_token(text, s, i, &mut tokens);
+        }
+    }
+
+    tokens
+}
+
+/// Ajoute le jeton composé (`main.rs`) puis ses parties (`main`, `rs`)
+fn push_token(text: &str, start: usize, end: usize, tokens: &mut Vec<Token>) {
+    let raw = &text[start..end];
+    let core = raw.trim_matches(JOINERS);
+    if core.is_empty() {
+        return;
+    }
+
+    let core_start = start + (raw.len() - raw.trim_start_matches(JOINERS).len());
+    tokens.push(Token {
+        start: core_start,
+        end: core_start + core.len(),
+        text: core.to_lowercase(),
+    });
+
+    if core.contains(JOINERS) {
+        let mut offset = core_start;
+        for part in core.split(JOINERS) {
+            if !part.is_empty() {
+                tokens.push(Token {
+                    start: offset,
+                    end: offset + part.len(),
+                    text: part.to_lowercase(),
+                });
+            }
+            offset += part.len() + 1;
+        }
+    }
+}
+
+/// Découpe un texte en termes indexables
+pub fn tokenize(text: &str) -> Vec<String> {
+    tokens_with_offsets(text).into_iter().map(|t| t.text).collect()
+}
+
+/// Termes significatifs d'une requête (mots vides exclus, sans doublons)
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for token in tokenize(query) {
+        if !STOPWORDS.contains(&token.as_str()) && !terms.contains(&token) {
+            terms.push(token);
+        }
+    }
+    terms
+}
+
+/// Zones du texte correspondant aux termes, fusionnées si elles se chevauchent
+pub fn highlight_spans(text: &str, terms: &[String]) -> Vec<HighlightSpan> {
+    let wanted: HashSet<&str> = terms.iter().map(|t| t.as_str()).collect();
+    let mut spans: Vec<HighlightSpan> = tokens_with_offsets(text)
+        .into_iter()
+        .filter(|t| wanted.contains(t.text.as_str()))
+        .map(|t| HighlightSpan {
+            start: t.start,
+            end: t.end,
+            term: t.text,
+        })
+        .collect();
+
+    spans.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+
+    let mut merged: Vec<HighlightSpan> = Vec::new();
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if span.start < last.end => {
+                if span.end > last.end {
+                    last.end = span.end;
+                }
+            }
+            _ => merged.push(span),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(id: &str, content: &str) -> IndexedDocument {
+        IndexedDocument {
+            id: id.to_string(),
+            title: id.to_string(),
+            content: content.to_string(),
+            doc_type: "text".to_string(),
+            metadata: HashMap::new(),
+            embedding: Vec::new(),
+            embedding_model: None,
+            chunks: Vec::new(),
+            indexed_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_tokenize_keeps_identifiers() {
+        let tokens = tokenize("Voir main.rs, erreur E0433 (facture 2024-118).");
+        assert!(tokens.contains(&"main.rs".to_string()));
+        assert!(tokens.contains(&"main".to_string()));
+        assert!(tokens.contains(&"e0433".to_string()));
+        assert!(tokens.contains(&"2024-118".to_string()));
+    }
+
+    #[test]
+    fn test_bm25_ranks_exact_identifier_first() {
+        let mut index = Bm25Index::default();
+        index.add_document(&document("a", "La facture 2024-118 concerne la maintenance annuelle."));
+        index.add_document(&document("b", "La facture 2024-117 concerne la maintenance mensuelle."));
+        index.add_document(&document("c", "Compte rendu de réunion sans rapport."));
+
+        let hits = index.search("facture 2024-118", 3);
+        assert_eq!(hits[0].doc_id, "a");
+        assert!(hits[0].matched_terms.contains(&"2024-118".to_string()));
+        assert!(hits.iter().all(|h| h.doc_id != "c"));
+    }
+
+    #[test]
+    fn test_remove_and_reindex_document() {
+        let mut index = Bm25Index::default();
+        index.add_document(&document("a", "ancien contenu"));
+        index.add_document(&document("a", "nouveau contenu"));
+        assert_eq!(index.len(), 1);
+        assert!(index.search("ancien", 5).is_empty());
+
+        assert!(index.remove_document("a"));
+        assert!(index.is_empty());
+        assert!(index.search("nouveau", 5).is_empty());
+    }
+
+    #[test]
+    fn test_highlight_spans() {
+        let text = "Erreur dans main.rs : voir Main";
+        let spans = highlight_spans(text, &["main".to_string()]);
+        assert_eq!(spans.len(), 2);
+        assert_eq!(&text[spans[0].start..spans[0].end], "main");
+        assert_eq!(&text[spans[1].start..spans[1].end], "Main");
+
+        let spans = highlight_spans(text, &["main.rs".to_string(), "main".to_string()]);
+        assert_eq!(&text[spans[0].start..spans[0].end], "main.rs");
+    }
+}
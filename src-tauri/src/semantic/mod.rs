@@ -3,8 +3,15 @@
 
 pub mod embedder;
 pub mod vector_store;
+pub mod hnsw;
+pub mod lexical;
+pub mod chunking;
+pub mod extraction;
 pub mod indexer;
+pub mod watcher;
 pub mod query;
+pub mod rag;
+pub mod eval;
 pub mod reranker;
 pub mod graph;
 pub mod context;
@@ -46,6 +53,9 @@ pub struct IndexedDocument {
     pub doc_type: String,
     pub metadata: HashMap<String, String>,
     pub embedding: Vec<f32>,
+    /// Empreinte du modèle ayant produit les embeddings (`Embedder::fingerprint`)
+    #[serde(default)]
+    pub embedding_model: Option<String>,
     pub chunks: Vec<DocumentChunk>,
     pub indexed_at: chrono::DateTime<chrono::Utc>,
 }
@@ -59,6 +69,9 @@ pub struct DocumentChunk {
     pub end_pos: usize,
     pub embedding: Vec<f32>,
     pub section_title: Option<String>,
+    /// Chemin du symbole englobant pour le code (`module::Type::method`)
+    #[serde(default)]
+    pub symbol_path: Option<String>,
 }
 
 /// Requête de recherche
@@ -83,7 +96,7 @@ pub struct DateRange {
     pub end: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SearchIntent {
     Informational,      // Cherche à comprendre
     Navigational,       // Cherche un document spécifique
@@ -106,6 +119,9 @@ pub struct MatchedChunk {
     pub chunk: DocumentChunk,
     pub score: f32,
     pub relevance_explanation: String,
+    /// Zones correspondant aux termes de la requête dans `chunk.content`
+    #[serde(default)]
+    pub highlights: Vec<lexical::HighlightSpan>,
 }
 
 /// Réponse de recherche complète
@@ -1,11 +1,23 @@
 // TITANE∞ v13 - Query Engine
 // Moteur de recherche avec détection d'intention et expansion de requête
 
-use crate::semantic::{SearchFilters, SearchIntent, SearchQuery, SearchResult};
-use crate::semantic::vector_store::{VectorStore, SearchResultKNN};
+use crate::semantic::indexer::IndexManager;
+use crate::semantic::{DocumentChunk, MatchedChunk, SearchFilters, SearchIntent, SearchQuery};
+use crate::semantic::lexical::{highlight_spans, query_terms, Bm25Index, LexicalHit};
+use crate::semantic::vector_store::{VectorStore, SearchResultKNN, VectorStoreError};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Poids des termes issus de l'expansion par synonymes dans la requête BM25
+const SYNONYM_TERM_WEIGHT: f32 = 0.5;
+
+/// Pondération des deux retrievers dans la fusion RRF
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FusionWeights {
+    pub vector: f32,
+    pub lexical: f32,
+}
+
 /// Configuration du query engine
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryConfig {
@@ -14,6 +26,9 @@ pub struct QueryConfig {
     pub enable_expansion: bool,
     pub enable_intent_detection: bool,
     pub max_results: usize,
+    /// Constante k de la fusion RRF : score = Σ poids / (k + rang)
+    pub rrf_k: f32,
+    pub intent_weights: HashMap<SearchIntent, FusionWeights>,
 }
 
 impl Default for QueryConfig {
@@ -24,10 +39,31 @@ impl Default for QueryConfig {
             enable_expansion: true,
             enable_intent_detection: true,
             max_results: 50,
+            rrf_k: 60.0,
+            intent_weights: HashMap::from([
+                // Un document précis : les identifiants exacts priment
+                (SearchIntent::Navigational, FusionWeights { vector: 0.3, lexical: 0.7 }),
+                (SearchIntent::Informational, FusionWeights { vector: 0.6, lexical: 0.4 }),
+                (SearchIntent::Transactional, FusionWeights { vector: 0.5, lexical: 0.5 }),
+                (SearchIntent::Exploratory, FusionWeights { vector: 0.7, lexical: 0.3 }),
+            ]),
         }
     }
 }
 
+/// Résultat fusionné (vectoriel + BM25)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HybridResult {
+    pub id: String,
+    pub score: f32,
+    pub vector_rank: Option<usize>,
+    pub lexical_rank: Option<usize>,
+    pub similarity: Option<f32>,
+    pub bm25_score: Option<f32>,
+    pub matched_terms: Vec<String>,
+    pub metadata: HashMap<String, String>,
+}
+
 /// Moteur de requêtes
 pub struct QueryEngine {
     config: QueryConfig,
@@ -42,18 +78,31 @@ impl QueryEngine {
         }
     }
 
-    /// Exécute une recherche
+    /// Exécute une recherche hybride : kNN vectoriel + BM25, fusionnés par RRF
     pub async fn search(
         &self,
         query: SearchQuery,
         vector_store: &VectorStore,
+        lexical_index: &Bm25Index,
         query_embedding: &[f32],
-    ) -> Result<Vec<SearchResultKNN>, QueryError> {
+    ) -> Result<Vec<HybridResult>, QueryError> {
+        self.hybrid_search(&query, vector_store, lexical_index, query_embedding)
+    }
+
+    /// Variante synchrone de `search`, utilisable sous un verrou sans point d'attente.
+    /// Un embedding vide (embedder indisponible) réduit la recherche au BM25.
+    pub fn hybrid_search(
+        &self,
+        query: &SearchQuery,
+        vector_store: &VectorStore,
+        lexical_index: &Bm25Index,
+        query_embedding: &[f32],
+    ) -> Result<Vec<HybridResult>, QueryError> {
         // Détecte l'intention si activé
         let intent = if self.config.enable_intent_detection {
-            query.intent.clone().or_else(|| Some(self.detect_intent(&query.text)))
+            query.intent.or_else(|| Some(self.detect_intent(&query.text)))
         } else {
-            query.intent.clone()
+            query.intent
         };
 
         // Expand la requête si activé
@@ -67,19 +116,42 @@ impl QueryEngine {
         let k = self.calculate_k(&intent);
 
         // Recherche dans le vector store
-        let mut results = if let Some(filters) = &query.filters {
+        let vector_results = if query_embedding.is_empty() {
+            Ok(Vec::new())
+        } else if let Some(filters) = &query.filters {
             // Recherche avec filtres
             vector_store.search_filtered(query_embedding, k, |metadata| {
                 self.apply_filters(metadata, filters)
-            }).map_err(|e| QueryError::VectorStoreError(e.to_string()))?
+            })
         } else {
             // Recherche sans filtres
             vector_store.search_knn(query_embedding, k)
-                .map_err(|e| QueryError::VectorStoreError(e.to_string()))?
+        };
+
+        // Un index vectoriel vide ou pas encore construit laisse le BM25 seul
+        let mut vector_results = match vector_results {
+            Ok(results) => results,
+            Err(VectorStoreError::IndexNotBuilt) | Err(VectorStoreError::EmptyStore) => Vec::new(),
+            Err(e) => return Err(QueryError::VectorStoreError(e.to_string())),
         };
 
         // Filtre par seuil de similarité
-        results.retain(|r| r.similarity >= self.config.similarity_threshold);
+        vector_results.retain(|r| r.similarity >= self.config.similarity_threshold);
+
+        // Recherche lexicale (termes de la requête + synonymes à poids réduit)
+        let lexical_terms = self.lexical_terms(&query.text, &expanded_terms);
+        let lexical_results = lexical_index.search_weighted(&lexical_terms, k, |metadata| {
+            query
+                .filters
+                .as_ref()
+                .map(|filters| self.apply_filters(metadata, filters))
+                .unwrap_or(true)
+        });
+
+        // Fusion RRF pondérée selon l'intention
+        let weights = self.fusion_weights(&intent);
+        let mut results =
+            reciprocal_rank_fusion(&vector_results, &lexical_results, weights, self.config.rrf_k);
 
         // Limite le nombre de résultats
         results.truncate(self.config.max_results);
@@ -87,6 +159,83 @@ impl QueryEngine {
         Ok(results)
     }
 
+    /// Poids de fusion associés à l'intention (équilibrés par défaut)
+    pub fn fusion_weights(&self, intent: &Option<SearchIntent>) -> FusionWeights {
+        intent
+            .and_then(|i| self.config.intent_weights.get(&i).copied())
+            .unwrap_or(FusionWeights { vector: 0.5, lexical: 0.5 })
+    }
+
+    fn lexical_terms(&self, query: &str, expanded_queries: &[String]) -> Vec<(String, f32)> {
+        let mut terms: Vec<(String, f32)> = query_terms(query).into_iter().map(|t| (t, 1.0)).collect();
+        for expanded in expanded_queries {
+            for term in query_terms(expanded) {
+                if !terms.iter().any(|(t, _)| *t == term) {
+                    terms.push((term, SYNONYM_TERM_WEIGHT));
+                }
+            }
+        }
+        terms
+    }
+
+    /// Recherche hybride résolue en passages : chaque résultat devient un
+    /// `MatchedChunk` avec ses zones surlignées. Un document indexé sans
+    /// chunks est rendu comme un passage couvrant tout son contenu.
+    pub fn search_chunks(
+        &self,
+        query: &SearchQuery,
+        manager: &IndexManager,
+        vector_store: &VectorStore,
+        query_embedding: &[f32],
+    ) -> Result<Vec<MatchedChunk>, QueryError> {
+        let hits = self.hybrid_search(query, vector_store, manager.lexical_index(), query_embedding)?;
+
+        Ok(hits
+            .iter()
+            .filter_map(|hit| {
+                if let Some((_, chunk)) = manager.find_chunk(&hit.id) {
+                    return Some(self.to_matched_chunk(&query.text, hit, chunk));
+                }
+                let doc = manager.get_document(&hit.id)?;
+                let whole = DocumentChunk {
+                    id: doc.id.clone(),
+                    content: doc.content.clone(),
+                    start_pos: 0,
+                    end_pos: doc.content.len(),
+                    embedding: Vec::new(),
+                    section_title: None,
+                    symbol_path: None,
+                };
+                Some(self.to_matched_chunk(&query.text, hit, &whole))
+            })
+            .collect())
+    }
+
+    /// Construit le `MatchedChunk` d'un résultat avec les zones surlignées
+    pub fn to_matched_chunk(&self, query: &str, hit: &HybridResult, chunk: &DocumentChunk) -> MatchedChunk {
+        let mut terms = query_terms(query);
+        for term in &hit.matched_terms {
+            if !terms.contains(term) {
+                terms.push(term.clone());
+            }
+        }
+
+        let mut explanation = Vec::new();
+        if let (Some(rank), Some(similarity)) = (hit.vector_rank, hit.similarity) {
+            explanation.push(format!("vectoriel #{} ({:.2})", rank, similarity));
+        }
+        if let (Some(rank), Some(bm25)) = (hit.lexical_rank, hit.bm25_score) {
+            explanation.push(format!("BM25 #{} ({:.2}) sur {}", rank, bm25, hit.matched_terms.join(", ")));
+        }
+
+        MatchedChunk {
+            chunk: chunk.clone(),
+            score: hit.score,
+            relevance_explanation: explanation.join(" + "),
+            highlights: highlight_spans(&chunk.content, &terms),
+        }
+    }
+
     /// Détecte l'intention de la recherche
     fn detect_intent(&self, query: &str) -> SearchIntent {
         let query_lower = query.to_lowercase();
@@ -251,6 +400,60 @@ impl QueryEngine {
     }
 }
 
+/// Fusion par rang réciproque : chaque liste contribue `poids / (k + rang)`
+pub fn reciprocal_rank_fusion(
+    vector_results: &[SearchResultKNN],
+    lexical_results: &[LexicalHit],
+    weights: FusionWeights,
+    k: f32,
+) -> Vec<HybridResult> {
+    let mut fused: HashMap<String, HybridResult> = HashMap::new();
+
+    for (i, result) in vector_results.iter().enumerate() {
+        let rank = i + 1;
+        let entry = fused.entry(result.id.clone()).or_insert_with(|| HybridResult {
+            id: result.id.clone(),
+            score: 0.0,
+            vector_rank: None,
+            lexical_rank: None,
+            similarity: None,
+            bm25_score: None,
+            matched_terms: Vec::new(),
+            metadata: result.metadata.clone(),
+        });
+        entry.score += weights.vector / (k + rank as f32);
+        entry.vector_rank = Some(rank);
+        entry.similarity = Some(result.similarity);
+    }
+
+    for (i, hit) in lexical_results.iter().enumerate() {
+        let rank = i + 1;
+        let entry = fused.entry(hit.id.clone()).or_insert_with(|| HybridResult {
+            id: hit.id.clone(),
+            score: 0.0,
+            vector_rank: None,
+            lexical_rank: None,
+            similarity: None,
+            bm25_score: None,
+            matched_terms: Vec::new(),
+            metadata: hit.metadata.clone(),
+        });
+        entry.score += weights.lexical / (k + rank as f32);
+        entry.lexical_rank = Some(rank);
+        entry.bm25_score = Some(hit.score);
+        entry.matched_terms = hit.matched_terms.clone();
+    }
+
+    let mut results: Vec<HybridResult> = fused.into_values().collect();
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    results
+}
+
 /// Erreurs du query engine
 #[derive(Debug)]
 pub enum QueryError {
@@ -326,6 +529,116 @@ mod tests {
         assert!(expanded.iter().any(|q| q.contains("générer") || q.contains("fichier")));
     }
 
+    fn knn(id: &str, similarity: f32) -> SearchResultKNN {
+        SearchResultKNN {
+            id: id.to_string(),
+            similarity,
+            distance: 1.0 - similarity,
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn lexical(id: &str, score: f32) -> LexicalHit {
+        LexicalHit {
+            id: id.to_string(),
+            doc_id: id.to_string(),
+            score,
+            matched_terms: vec!["terme".to_string()],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let vector = vec![knn("a", 0.9), knn("b", 0.8)];
+        let lexical_hits = vec![lexical("b", 5.0), lexical("c", 3.0)];
+        let weights = FusionWeights { vector: 0.5, lexical: 0.5 };
+
+        let fused = reciprocal_rank_fusion(&vector, &lexical_hits, weights, 60.0);
+        assert_eq!(fused.len(), 3);
+        // Présent dans les deux listes → premier
+        assert_eq!(fused[0].id, "b");
+        assert_eq!(fused[0].vector_rank, Some(2));
+        assert_eq!(fused[0].lexical_rank, Some(1));
+    }
+
+    #[test]
+    fn test_fusion_weights_per_intent() {
+        let engine = QueryEngine::new(QueryConfig::default());
+        let navigational = engine.fusion_weights(&Some(SearchIntent::Navigational));
+        let exploratory = engine.fusion_weights(&Some(SearchIntent::Exploratory));
+        assert!(navigational.lexical > navigational.vector);
+        assert!(exploratory.vector > exploratory.lexical);
+
+        // Navigationnel : le premier résultat lexical passe devant le premier vectoriel
+        let fused = reciprocal_rank_fusion(&[knn("a", 0.9)], &[lexical("b", 4.0)], navigational, 60.0);
+        assert_eq!(fused[0].id, "b");
+    }
+
+    #[test]
+    fn test_matched_chunk_highlights() {
+        let engine = QueryEngine::new(QueryConfig::default());
+        let chunk = DocumentChunk {
+            id: "c1".to_string(),
+            content: "Le fichier contrat.pdf contient la clause".to_string(),
+            start_pos: 0,
+            end_pos: 41,
+            embedding: Vec::new(),
+            section_title: None,
+            symbol_path: None,
+        };
+        let hit = reciprocal_rank_fusion(&[], &[lexical("c1", 2.0)], FusionWeights { vector: 0.5, lexical: 0.5 }, 60.0)
+            .remove(0);
+
+        let matched = engine.to_matched_chunk("contrat.pdf", &hit, &chunk);
+        assert_eq!(matched.highlights.len(), 1);
+        let span = &matched.highlights[0];
+        assert_eq!(&chunk.content[span.start..span.end], "contrat.pdf");
+        assert!(matched.relevance_explanation.contains("BM25"));
+    }
+
+    #[test]
+    fn test_search_chunks_resolves_highlighted_passages() {
+        use crate::semantic::indexer::IndexerConfig;
+
+        let mut manager = IndexManager::new(IndexerConfig::default());
+        manager.add_document(
+            "factures".to_string(),
+            "Factures".to_string(),
+            "La facture FAC-2024-118 reste impayée depuis mars.".to_string(),
+            "text".to_string(),
+            HashMap::new(),
+        );
+        manager.add_document(
+            "recette".to_string(),
+            "Recette".to_string(),
+            "La pâte à crêpes doit reposer une heure.".to_string(),
+            "text".to_string(),
+            HashMap::new(),
+        );
+        let store = VectorStore::new(8, std::env::temp_dir().join("titane_query_chunks_store"));
+        let engine = QueryEngine::new(QueryConfig::default());
+        let query = SearchQuery {
+            text: "facture impayée".to_string(),
+            filters: None,
+            context: None,
+            intent: None,
+        };
+
+        let matched = engine.search_chunks(&query, &manager, &store, &[]).unwrap();
+        assert_eq!(matched.len(), 1);
+        let first = &matched[0];
+        assert!(first.chunk.content.contains("FAC-2024-118"));
+        let highlighted: Vec<&str> = first
+            .highlights
+            .iter()
+            .map(|span| &first.chunk.content[span.start..span.end])
+            .collect();
+        assert!(highlighted.contains(&"facture"));
+        assert!(highlighted.contains(&"impayée"));
+        assert!(first.relevance_explanation.contains("BM25"));
+    }
+
     #[test]
     fn test_pagination() {
         let paginator = PaginationManager::new(10);
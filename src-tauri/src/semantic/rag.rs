@@ -0,0 +1,854 @@
+// TITANE∞ v13 - RAG Pipeline
+// Réponses augmentées par la recherche : retrieval hybride, reranking, budget de tokens et citations
+
+use crate::ai::router::AIRouter;
+use crate::ai::{AIProvider, AIRequest};
+use crate::semantic::embedder::Embedder;
+use crate::semantic::indexer::{embedding_inputs, IndexManager};
+use crate::semantic::lexical::query_terms;
+use crate::semantic::query::{HybridResult, QueryConfig, QueryEngine};
+use crate::semantic::reranker::{ContextualReranker, RerankerConfig};
+use crate::semantic::vector_store::{SearchResultKNN, VectorStore};
+use crate::semantic::{MatchedChunk, SearchQuery};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::State;
+
+/// Approximation du nombre de caractères par token (texte FR/EN)
+const CHARS_PER_TOKEN: usize = 4;
+
+/// En dessous de ce reste de budget, un passage n'est plus tronqué pour entrer
+const MIN_PASSAGE_TOKENS: usize = 32;
+
+/// Configuration du pipeline RAG
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RagConfig {
+    /// Nombre de passages candidats conservés après reranking
+    pub top_k: usize,
+    /// Budget de tokens réservé aux extraits dans le prompt
+    pub context_token_budget: usize,
+    pub answer_max_tokens: usize,
+    /// Confiance de retrieval minimale (0-1) pour solliciter le LLM
+    pub min_confidence: f32,
+    pub temperature: f32,
+}
+
+impl Default for RagConfig {
+    fn default() -> Self {
+        Self {
+            top_k: 6,
+            context_token_budget: 2000,
+            answer_max_tokens: 800,
+            min_confidence: 0.35,
+            temperature: 0.2,
+        }
+    }
+}
+
+/// Citation numérotée renvoyant à un passage indexé
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Citation {
+    pub number: usize,
+    pub doc_id: String,
+    pub chunk_id: String,
+    pub title: String,
+    pub section: Option<String>,
+    pub symbol_path: Option<String>,
+    /// Offsets (en octets) du passage transmis dans le contenu du document
+    pub start_pos: usize,
+    pub end_pos: usize,
+    pub score: f32,
+    /// Vrai si la réponse du modèle mentionne `[number]`
+    pub cited: bool,
+}
+
+/// Contexte préparé pour le LLM : prompt, sources et décision de refus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RagContext {
+    pub question: String,
+    pub prompt: String,
+    pub citations: Vec<Citation>,
+    pub confidence: f32,
+    pub context_tokens: usize,
+    /// Motif du refus quand la retrieval n'est pas assez fiable
+    pub refusal: Option<String>,
+}
+
+/// Réponse finale du pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RagAnswer {
+    pub question: String,
+    pub answer: String,
+    pub citations: Vec<Citation>,
+    pub confidence: f32,
+    pub refused: bool,
+    pub context_tokens: usize,
+    pub provider: Option<AIProvider>,
+}
+
+/// Passage candidat résolu depuis l'index
+struct Passage {
+    citation: Citation,
+    text: String,
+}
+
+/// Pipeline RAG : QueryEngine → ContextualReranker → prompt borné → AIRouter
+pub struct RagPipeline {
+    config: RagConfig,
+    query_engine: QueryEngine,
+    reranker: ContextualReranker,
+}
+
+impl RagPipeline {
+    pub fn new(config: RagConfig) -> Self {
+        Self::with_components(
+            config,
+            QueryEngine::new(QueryConfig::default()),
+            ContextualReranker::new(RerankerConfig::default()),
+        )
+    }
+
+    pub fn with_components(config: RagConfig, query_engine: QueryEngine, reranker: ContextualReranker) -> Self {
+        Self {
+            config,
+            query_engine,
+            reranker,
+        }
+    }
+
+    pub fn config(&self) -> &RagConfig {
+        &self.config
+    }
+
+    /// Retrieval, reranking et construction du prompt, sans appel au LLM.
+    /// Un embedding vide limite la retrieval au BM25.
+    pub fn prepare(
+        &self,
+        question: &str,
+        manager: &IndexManager,
+        vector_store: &VectorStore,
+        query_embedding: &[f32],
+    ) -> Result<RagContext, RagError> {
+        let question = question.trim();
+        if question.is_empty() {
+            return Err(RagError::InvalidQuestion("question vide".to_string()));
+        }
+
+        let query = SearchQuery {
+            text: question.to_string(),
+            filters: None,
+            context: None,
+            intent: None,
+        };
+        let hits = self
+            .query_engine
+            .hybrid_search(&query, vector_store, manager.lexical_index(), query_embedding)
+            .map_err(|e| RagError::Retrieval(e.to_string()))?;
+
+        let terms = query_terms(question);
+        let mut passages: HashMap<String, Passage> = HashMap::new();
+        let mut candidates = Vec::new();
+        for hit in &hits {
+            let confidence = hit_confidence(hit, &terms);
+            let Some(passage) = resolve_passage(manager, hit, confidence) else {
+                continue;
+            };
+
+            let mut metadata = hit.metadata.clone();
+            metadata.insert("doc_id".to_string(), passage.citation.doc_id.clone());
+            candidates.push(SearchResultKNN {
+                id: hit.id.clone(),
+                similarity: confidence,
+                distance: 1.0 - confidence,
+                metadata,
+            });
+            passages.insert(hit.id.clone(), passage);
+        }
+
+        let confidence = candidates.iter().map(|c| c.similarity).fold(0.0f32, f32::max);
+        if candidates.is_empty() || confidence < self.config.min_confidence {
+            return Ok(RagContext {
+                question: question.to_string(),
+                prompt: String::new(),
+                citations: Vec::new(),
+                confidence,
+                context_tokens: 0,
+                refusal: Some(format!(
+                    "Les documents indexés ne contiennent pas d'éléments assez fiables pour répondre (confiance {:.2} < {:.2}).",
+                    confidence, self.config.min_confidence
+                )),
+            });
+        }
+
+        let ranked = self
+            .reranker
+            .rerank_with_graph(candidates, question, None, manager.graph());
+        let ordered: Vec<Passage> = ranked
+            .into_iter()
+            .take(self.config.top_k)
+            .filter_map(|r| passages.remove(&r.id))
+            .collect();
+
+        let (blocks, citations, context_tokens) = self.pack(ordered);
+
+        Ok(RagContext {
+            question: question.to_string(),
+            prompt: build_prompt(question, &blocks),
+            citations,
+            confidence,
+            context_tokens,
+            refusal: None,
+        })
+    }
+
+    /// Remplit le budget de tokens dans l'ordre du reranking ; seul un passage
+    /// qui ne tient pas en entier alors qu'il reste de la place est tronqué
+    fn pack(&self, passages: Vec<Passage>) -> (Vec<String>, Vec<Citation>, usize) {
+        let budget = self.config.context_token_budget;
+        let mut blocks = Vec::new();
+        let mut citations = Vec::new();
+        let mut used = 0;
+
+        for mut passage in passages {
+            let number = citations.len() + 1;
+            let header = passage_header(number, &passage.citation);
+            let header_tokens = estimate_tokens(&header);
+            let remaining = budget.saturating_sub(used);
+            if header_tokens + MIN_PASSAGE_TOKENS > remaining {
+                continue;
+            }
+
+            let mut text_tokens = estimate_tokens(&passage.text);
+            if header_tokens + text_tokens > remaining {
+                if !blocks.is_empty() {
+                    continue;
+                }
+                let truncated = truncate_to_tokens(&passage.text, remaining - header_tokens);
+                passage.citation.end_pos = passage.citation.start_pos + truncated.len();
+                passage.text = format!("{}…", truncated);
+                text_tokens = estimate_tokens(&passage.text);
+            }
+
+            used += header_tokens + text_tokens;
+            passage.citation.number = number;
+            blocks.push(format!("{}\n{}", header, passage.text));
+            citations.push(passage.citation);
+        }
+
+        (blocks, citations, used)
+    }
+
+    /// Interroge le LLM sur un contexte préparé (aucun appel en cas de refus)
+    pub async fn generate(&self, router: &AIRouter, context: RagContext) -> Result<RagAnswer, RagError> {
+        if let Some(reason) = context.refusal {
+            return Ok(RagAnswer {
+                question: context.question,
+                answer: reason,
+                citations: Vec::new(),
+                confidence: context.confidence,
+                refused: true,
+                context_tokens: 0,
+                provider: None,
+            });
+        }
+
+        let response = router
+            .query(AIRequest {
+                prompt: context.prompt,
+                temperature: self.config.temperature,
+                max_tokens: self.config.answer_max_tokens,
+                stream: false,
+            })
+            .await
+            .map_err(|e| RagError::Generation(e.to_string()))?;
+
+        let mut citations = context.citations;
+        mark_cited(&response.content, &mut citations);
+
+        Ok(RagAnswer {
+            question: context.question,
+            answer: response.content.trim().to_string(),
+            citations,
+            confidence: context.confidence,
+            refused: false,
+            context_tokens: context.context_tokens,
+            provider: Some(response.provider),
+        })
+    }
+}
+
+/// Répond à une question à partir de l'index (point d'entrée hors Tauri)
+pub async fn answer_question(
+    pipeline: &RagPipeline,
+    router: &AIRouter,
+    question: &str,
+    manager: &IndexManager,
+    vector_store: &VectorStore,
+    query_embedding: &[f32],
+) -> Result<RagAnswer, RagError> {
+    let context = pipeline.prepare(question, manager, vector_store, query_embedding)?;
+    pipeline.generate(router, context).await
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Retrieval → passages
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Confiance d'un résultat : similarité cosinus, ou à défaut couverture des
+/// termes de la requête par le BM25
+fn hit_confidence(hit: &HybridResult, terms: &[String]) -> f32 {
+    let coverage = if terms.is_empty() {
+        0.0
+    } else {
+        let matched = terms.iter().filter(|t| hit.matched_terms.contains(t)).count();
+        matched as f32 / terms.len() as f32
+    };
+    hit.similarity.unwrap_or(0.0).max(coverage).clamp(0.0, 1.0)
+}
+
+fn resolve_passage(manager: &IndexManager, hit: &HybridResult, score: f32) -> Option<Passage> {
+    if let Some((doc, chunk)) = manager.find_chunk(&hit.id) {
+        return Some(Passage {
+            citation: Citation {
+                number: 0,
+                doc_id: doc.id.clone(),
+                chunk_id: chunk.id.clone(),
+                title: doc.title.clone(),
+                section: chunk.section_title.clone(),
+                symbol_path: chunk.symbol_path.clone(),
+                start_pos: chunk.start_pos,
+                end_pos: chunk.end_pos,
+                score,
+                cited: false,
+            },
+            text: chunk.content.clone(),
+        });
+    }
+
+    // Document sans chunks : indexé en une seule entrée lexicale
+    manager.get_document(&hit.id).map(|doc| Passage {
+        citation: Citation {
+            number: 0,
+            doc_id: doc.id.clone(),
+            chunk_id: doc.id.clone(),
+            title: doc.title.clone(),
+            section: None,
+            symbol_path: None,
+            start_pos: 0,
+            end_pos: doc.content.len(),
+            score,
+            cited: false,
+        },
+        text: doc.content.clone(),
+    })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Prompt et citations
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Estimation grossière du nombre de tokens d'un texte
+pub fn estimate_tokens(text: &str) -> usize {
+    (text.chars().count() + CHARS_PER_TOKEN - 1) / CHARS_PER_TOKEN
+}
+
+fn truncate_to_tokens(text: &str, tokens: usize) -> &str {
+    let max_chars = tokens.saturating_sub(1) * CHARS_PER_TOKEN;
+    let end = text.char_indices().nth(max_chars).map(|(i, _)| i).unwrap_or(text.len());
+    let cut = &text[..end];
+    // Coupe sur une frontière de mot quand c'est possible
+    match cut.rfind(char::is_whitespace) {
+        Some(i) if end < text.len() && i > end / 2 => &cut[..i],
+        _ => cut,
+    }
+}
+
+fn passage_header(number: usize, citation: &Citation) -> String {
+    let mut header = format!("[{}] {}", number, citation.title);
+    if let Some(location) = citation.symbol_path.as_ref().or(citation.section.as_ref()) {
+        header.push_str(" — ");
+        header.push_str(location);
+    }
+    header
+}
+
+fn build_prompt(question: &str, blocks: &[String]) -> String {
+    format!(
+        "Tu réponds à une question en t'appuyant uniquement sur les extraits numérotés ci-dessous.\n\
+         Cite chaque affirmation avec le numéro de l'extrait entre crochets, par exemple [1] ou [2][3].\n\
+         Si les extraits ne permettent pas de répondre, dis-le explicitement sans inventer.\n\n\
+         EXTRAITS :\n\n{}\n\nQUESTION : {}\n\nRÉPONSE :",
+        blocks.join("\n\n"),
+        question
+    )
+}
+
+/// Numéros `[n]` (ou `[n, m]`) mentionnés dans une réponse
+pub fn cited_numbers(answer: &str) -> BTreeSet<usize> {
+    let mut numbers = BTreeSet::new();
+    let mut rest = answer;
+    while let Some(open) = rest.find('[') {
+        rest = &rest[open + 1..];
+        let Some(close) = rest.find(']') else { break };
+        let inner = &rest[..close];
+        let parsed: Option<Vec<usize>> = inner.split(',').map(|n| n.trim().parse().ok()).collect();
+        if let Some(parsed) = parsed {
+            numbers.extend(parsed);
+        }
+        rest = &rest[close + 1..];
+    }
+    numbers
+}
+
+fn mark_cited(answer: &str, citations: &mut [Citation]) {
+    let numbers = cited_numbers(answer);
+    for citation in citations {
+        citation.cited = numbers.contains(&citation.number);
+    }
+}
+
+/// Erreurs du pipeline RAG
+#[derive(Debug)]
+pub enum RagError {
+    InvalidQuestion(String),
+    Retrieval(String),
+    Generation(String),
+}
+
+impl std::fmt::Display for RagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RagError::InvalidQuestion(e) => write!(f, "Question invalide: {}", e),
+            RagError::Retrieval(e) => write!(f, "Erreur de recherche: {}", e),
+            RagError::Generation(e) => write!(f, "Erreur de génération: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RagError {}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Commandes Tauri
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub struct SemanticRagState {
+    manager: Arc<Mutex<IndexManager>>,
+    vector_store: Arc<RwLock<VectorStore>>,
+    embedder: Embedder,
+    router: Arc<AIRouter>,
+    pipeline: RagPipeline,
+}
+
+impl SemanticRagState {
+    pub fn new(
+        manager: Arc<Mutex<IndexManager>>,
+        vector_store: Arc<RwLock<VectorStore>>,
+        embedder: Embedder,
+        router: Arc<AIRouter>,
+        config: RagConfig,
+    ) -> Self {
+        let state = Self {
+            manager,
+            vector_store,
+            embedder,
+            router,
+            pipeline: RagPipeline::new(config),
+        };
+        if let Err(e) = state.ensure_model() {
+            println!("[RAG] ⚠️ Vérification du modèle d'embedding impossible: {}", e);
+        }
+        state
+    }
+
+    /// Un store construit par un autre modèle est vidé et tous les documents
+    /// sont marqués à ré-embedder : jamais de comparaison entre modèles
+    fn ensure_model(&self) -> Result<(), RagError> {
+        let fingerprint = self.embedder.fingerprint();
+        let mut store = self.vector_store.write().map_err(|e| RagError::Retrieval(e.to_string()))?;
+        if store.check_model(&fingerprint).is_ok() && (store.model_fingerprint().is_some() || store.is_empty()) {
+            return Ok(());
+        }
+
+        println!(
+            "[RAG] Modèle d'embedding changé ({} → {}), ré-indexation vectorielle",
+            store.model_fingerprint().unwrap_or("inconnu"),
+            fingerprint
+        );
+        store.clear();
+        self.manager
+            .lock()
+            .map_err(|e| RagError::Retrieval(e.to_string()))?
+            .clear_embedding_models();
+        Ok(())
+    }
+
+    /// Embedding de la question ; si l'embedder échoue ou renvoie une autre
+    /// dimension que l'index, la recherche passe explicitement en lexical seul
+    async fn query_embedding(&self, question: &str) -> Vec<f32> {
+        let expected = match self.vector_store.read() {
+            Ok(store) => store.dimensions(),
+            Err(_) => self.embedder.dimensions(),
+        };
+
+        match self.embedder.embed(question).await {
+            Ok(vector) if vector.len() == expected => vector,
+            Ok(vector) => {
+                println!(
+                    "[RAG] ⚠️ Dimension d'embedding {} au lieu de {}, recherche lexicale seule",
+                    vector.len(),
+                    expected
+                );
+                Vec::new()
+            }
+            Err(e) => {
+                println!("[RAG] ⚠️ Embedder indisponible, recherche lexicale seule: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Retire les vecteurs des documents supprimés et ceux des anciens chunks
+    /// des documents modifiés (re-découpés sous de nouveaux identifiants)
+    fn prune_embeddings(&self) -> Result<usize, RagError> {
+        let manager = self.manager.lock().map_err(|e| RagError::Retrieval(e.to_string()))?;
+        let mut store = self.vector_store.write().map_err(|e| RagError::Retrieval(e.to_string()))?;
+        let orphans = store.ids_filtered(|id, metadata| {
+            metadata
+                .get("doc_id")
+                .is_some_and(|doc_id| !manager.has_entry(doc_id, id))
+        });
+        for id in &orphans {
+            store.remove_point(id);
+        }
+        Ok(orphans.len())
+    }
+
+    /// Élague les vecteurs orphelins, embedde les documents nouveaux, modifiés
+    /// ou issus d'un autre modèle et enregistre l'empreinte du modèle sur
+    /// chacun ; renvoie le nombre de documents embeddés
+    pub async fn sync_embeddings(&self) -> Result<usize, RagError> {
+        self.ensure_model()?;
+        let pruned = self.prune_embeddings()?;
+        if pruned > 0 {
+            println!("[RAG] {} vecteurs orphelins retirés", pruned);
+        }
+        let fingerprint = self.embedder.fingerprint();
+
+        let pending: Vec<(String, Vec<(String, String)>)> = {
+            let manager = self.manager.lock().map_err(|e| RagError::Retrieval(e.to_string()))?;
+            manager
+                .stale_embeddings(&fingerprint)
+                .into_iter()
+                .filter_map(|id| manager.get_document(&id).map(|doc| (id, embedding_inputs(doc))))
+                .collect()
+        };
+
+        for (doc_id, inputs) in &pending {
+            // Embeddings calculés hors verrou, puis insérés d'un bloc
+            let mut embedded = Vec::with_capacity(inputs.len());
+            for (id, text) in inputs {
+                let embedding = self
+                    .embedder
+                    .embed_tagged(text)
+                    .await
+                    .map_err(|e| RagError::Retrieval(e.to_string()))?;
+                embedded.push((id.clone(), embedding));
+            }
+
+            let mut store = self.vector_store.write().map_err(|e| RagError::Retrieval(e.to_string()))?;
+            for (id, embedding) in embedded {
+                let metadata = HashMap::from([("doc_id".to_string(), doc_id.clone())]);
+                store
+                    .add_embedding(id, embedding, metadata)
+                    .map_err(|e| RagError::Retrieval(e.to_string()))?;
+            }
+            drop(store);
+
+            self.manager
+                .lock()
+                .map_err(|e| RagError::Retrieval(e.to_string()))?
+                .mark_embedded(doc_id, &fingerprint);
+        }
+
+        Ok(pending.len())
+    }
+}
+
+#[tauri::command]
+pub async fn semantic_ask(question: String, state: State<'_, SemanticRagState>) -> Result<RagAnswer, String> {
+    if let Err(e) = state.sync_embeddings().await {
+        println!("[RAG] ⚠️ Index vectoriel non synchronisé: {}", e);
+    }
+
+    let embedding = state.query_embedding(&question).await;
+
+    let context = {
+        let manager = state.manager.lock().map_err(|e| e.to_string())?;
+        let vector_store = state.vector_store.read().map_err(|e| e.to_string())?;
+        vector_store
+            .check_model(&state.embedder.fingerprint())
+            .map_err(|e| e.to_string())?;
+        state
+            .pipeline
+            .prepare(&question, &manager, &vector_store, &embedding)
+            .map_err(|e| e.to_string())?
+    };
+
+    state
+        .pipeline
+        .generate(&state.router, context)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Recherche hybride renvoyant les passages avec leurs zones surlignées
+#[tauri::command]
+pub async fn semantic_search_chunks(
+    query: String,
+    limit: Option<usize>,
+    state: State<'_, SemanticRagState>,
+) -> Result<Vec<MatchedChunk>, String> {
+    if let Err(e) = state.sync_embeddings().await {
+        println!("[RAG] ⚠️ Index vectoriel non synchronisé: {}", e);
+    }
+
+    let embedding = state.query_embedding(&query).await;
+    let search = SearchQuery {
+        text: query,
+        filters: None,
+        context: None,
+        intent: None,
+    };
+
+    let manager = state.manager.lock().map_err(|e| e.to_string())?;
+    let vector_store = state.vector_store.read().map_err(|e| e.to_string())?;
+    vector_store
+        .check_model(&state.embedder.fingerprint())
+        .map_err(|e| e.to_string())?;
+    let mut matched = state
+        .pipeline
+        .query_engine
+        .search_chunks(&search, &manager, &vector_store, &embedding)
+        .map_err(|e| e.to_string())?;
+    matched.truncate(limit.unwrap_or(10));
+    Ok(matched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::indexer::IndexerConfig;
+
+    fn manager() -> IndexManager {
+        let mut manager = IndexManager::new(IndexerConfig::default());
+        manager.add_document(
+            "contrat".to_string(),
+            "Contrat de maintenance".to_string(),
+            "# Résiliation\n\nLe contrat de maintenance peut être résilié avec un préavis de trois mois par lettre recommandée. \
+             La résiliation prend effet à la fin du trimestre civil suivant la réception du courrier, \
+             et les prestations déjà planifiées restent dues jusqu'à cette date sauf accord écrit des deux parties.\n\n# Tarifs\n\nLa redevance annuelle est révisée chaque janvier selon l'indice Syntec.".to_string(),
+            "markdown".to_string(),
+            HashMap::new(),
+        );
+        manager.add_document(
+            "cuisine".to_string(),
+            "Recette".to_string(),
+            "La pâte à crêpes doit reposer une heure avant la cuisson dans une poêle chaude.".to_string(),
+            "text".to_string(),
+            HashMap::new(),
+        );
+        manager
+    }
+
+    fn store() -> VectorStore {
+        VectorStore::new(8, std::env::temp_dir().join("titane_rag_test_store"))
+    }
+
+    #[test]
+    fn test_prepare_cites_sections() {
+        let manager = manager();
+        let pipeline = RagPipeline::new(RagConfig::default());
+
+        let context = pipeline
+            .prepare("Quel préavis pour la résiliation du contrat ?", &manager, &store(), &[])
+            .unwrap();
+
+        assert!(context.refusal.is_none());
+        let first = &context.citations[0];
+        assert_eq!(first.number, 1);
+        assert_eq!(first.doc_id, "contrat");
+        assert_eq!(first.section.as_deref(), Some("Résiliation"));
+        let content = &manager.get_document("contrat").unwrap().content;
+        assert!(content[first.start_pos..first.end_pos].contains("préavis de trois mois"));
+        assert!(context.prompt.contains("[1] Contrat de maintenance — Résiliation"));
+        assert!(context.context_tokens <= pipeline.config().context_token_budget);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_below_confidence() {
+        let manager = manager();
+        let pipeline = RagPipeline::new(RagConfig::default());
+
+        let context = pipeline
+            .prepare("Quelle est la capitale du Pérou ?", &manager, &store(), &[])
+            .unwrap();
+        assert!(context.refusal.is_some());
+
+        // Le refus est rendu sans solliciter de fournisseur
+        let answer = pipeline.generate(&AIRouter::new(None, None), context).await.unwrap();
+        assert!(answer.refused);
+        assert!(answer.citations.is_empty());
+        assert!(answer.provider.is_none());
+    }
+
+    #[test]
+    fn test_token_budget_truncates_first_passage() {
+        let manager = manager();
+        let pipeline = RagPipeline::new(RagConfig {
+            context_token_budget: 60,
+            ..RagConfig::default()
+        });
+
+        let context = pipeline
+            .prepare("résiliation préavis contrat maintenance", &manager, &store(), &[])
+            .unwrap();
+
+        assert_eq!(context.citations.len(), 1);
+        assert!(context.context_tokens <= 60);
+        assert!(context.prompt.contains('…'));
+        let citation = &context.citations[0];
+        let content = &manager.get_document("contrat").unwrap().content;
+        assert!(!content[citation.start_pos..citation.end_pos].contains("accord écrit"));
+    }
+
+    #[tokio::test]
+    async fn test_sync_embeddings_records_model_and_resets_foreign_store() {
+        use crate::semantic::embedder::{Embedding, EmbeddingModel};
+
+        let path = std::env::temp_dir().join(format!("titane_rag_sync_{}", uuid::Uuid::new_v4()));
+        let mut foreign = VectorStore::new(8, path);
+        let vector = vec![1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        foreign
+            .add_embedding("x".to_string(), Embedding { vector, fingerprint: "ollama:autre:8".to_string() }, HashMap::new())
+            .unwrap();
+
+        let manager = Arc::new(Mutex::new(manager()));
+        let store = Arc::new(RwLock::new(foreign));
+        let embedder = Embedder::new(EmbeddingModel::Local, 8);
+        let fingerprint = embedder.fingerprint();
+        let state = SemanticRagState::new(
+            manager.clone(),
+            store.clone(),
+            embedder,
+            Arc::new(AIRouter::new(None, None)),
+            RagConfig::default(),
+        );
+
+        // Le store d'un autre modèle a été vidé à l'ouverture
+        assert!(store.read().unwrap().is_empty());
+
+        assert_eq!(state.sync_embeddings().await.unwrap(), 2);
+        assert_eq!(store.read().unwrap().model_fingerprint(), Some(fingerprint.as_str()));
+        let doc = manager.lock().unwrap().get_document("contrat").unwrap().embedding_model.clone();
+        assert_eq!(doc.as_deref(), Some(fingerprint.as_str()));
+
+        // Rien à refaire tant que ni le modèle ni le contenu ne changent
+        assert_eq!(state.sync_embeddings().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sync_embeddings_prunes_edited_and_deleted_documents() {
+        use crate::semantic::embedder::EmbeddingModel;
+        use crate::semantic::watcher::{FolderSync, WatchRoot, WatcherConfig};
+
+        let root = std::env::temp_dir().join(format!("titane_rag_watch_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("notes.md"), "# Notes\nRelance de la facture FAC-77 prévue lundi.").unwrap();
+        let roots = vec![WatchRoot {
+            path: root.clone(),
+            include: vec![],
+            exclude: vec![],
+        }];
+
+        let manager = Arc::new(Mutex::new(IndexManager::new(IndexerConfig::default())));
+        let store = Arc::new(RwLock::new(VectorStore::new(
+            8,
+            std::env::temp_dir().join(format!("titane_rag_prune_{}", uuid::Uuid::new_v4())),
+        )));
+        let state = SemanticRagState::new(
+            manager.clone(),
+            store.clone(),
+            Embedder::new(EmbeddingModel::Local, 8),
+            Arc::new(AIRouter::new(None, None)),
+            RagConfig::default(),
+        );
+        let mut sync = FolderSync::new(WatcherConfig::default());
+        let file_vectors = || store.read().unwrap().ids_filtered(|_, metadata| metadata.contains_key("doc_id"));
+
+        sync.scan(&roots, &manager, &|_| {});
+        state.sync_embeddings().await.unwrap();
+        let first = file_vectors();
+        assert!(!first.is_empty());
+
+        // Contenu modifié : nouveaux chunks, les anciens vecteurs disparaissent
+        let doc_id = manager.lock().unwrap().list_documents()[0].id.clone();
+        manager
+            .lock()
+            .unwrap()
+            .update_document(&doc_id, "# Notes\nFacture FAC-77 réglée.".to_string())
+            .unwrap();
+        state.sync_embeddings().await.unwrap();
+        let second = file_vectors();
+        assert!(!second.is_empty());
+        assert!(second.iter().all(|id| !first.contains(id)));
+
+        // Fichier supprimé : plus aucun vecteur
+        std::fs::remove_file(root.join("notes.md")).unwrap();
+        assert_eq!(sync.scan(&roots, &manager, &|_| {}).removed, 1);
+        state.sync_embeddings().await.unwrap();
+        assert!(file_vectors().is_empty());
+        assert!(store.read().unwrap().is_empty());
+
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_query_embedding_falls_back_to_lexical() {
+        use crate::semantic::embedder::EmbeddingModel;
+
+        let rag_state = |embedder: Embedder| {
+            SemanticRagState::new(
+                Arc::new(Mutex::new(manager())),
+                Arc::new(RwLock::new(store())),
+                embedder,
+                Arc::new(AIRouter::new(None, None)),
+                RagConfig::default(),
+            )
+        };
+
+        let local = rag_state(Embedder::new(EmbeddingModel::Local, 8));
+        assert_eq!(local.query_embedding("préavis").await.len(), 8);
+
+        // Dimension différente de l'index : pas de vecteur
+        let wider = rag_state(Embedder::new(EmbeddingModel::Local, 16));
+        assert!(wider.query_embedding("préavis").await.is_empty());
+
+        // Fournisseur injoignable : pas de vecteur, pas d'erreur
+        let offline = rag_state(Embedder::new(EmbeddingModel::Ollama, 8).with_ollama_url("http://127.0.0.1:9"));
+        assert!(offline.query_embedding("préavis").await.is_empty());
+    }
+
+    #[test]
+    fn test_estimate_tokens_rounds_up() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+
+    #[test]
+    fn test_cited_numbers() {
+        let numbers = cited_numbers("Préavis de trois mois [1], révisé en janvier [2, 3]. Voir [note].");
+        assert_eq!(numbers.into_iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+}
@@ -1,6 +1,7 @@
 // TITANE∞ v13 - Contextual Reranker
 // Reranking contextuel des résultats de recherche avec scoring composite
 
+use crate::semantic::graph::KnowledgeGraph;
 use crate::semantic::vector_store::SearchResultKNN;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -13,9 +14,16 @@ pub struct RerankerConfig {
     pub recency_weight: f32,
     pub authority_weight: f32,
     pub graph_position_weight: f32,
+    /// Bonus quand la requête cite une entité connue du graphe
+    #[serde(default = "default_graph_boost_weight")]
+    pub graph_boost_weight: f32,
     pub enable_explainability: bool,
 }
 
+fn default_graph_boost_weight() -> f32 {
+    0.15
+}
+
 impl Default for RerankerConfig {
     fn default() -> Self {
         Self {
@@ -24,6 +32,7 @@ impl Default for RerankerConfig {
             recency_weight: 0.15,
             authority_weight: 0.15,
             graph_position_weight: 0.10,
+            graph_boost_weight: default_graph_boost_weight(),
             enable_explainability: true,
         }
     }
@@ -50,17 +59,57 @@ impl ContextualReranker {
 
     /// Reranke les résultats
     pub fn rerank(
+        &self,
+        results: Vec<SearchResultKNN>,
+        query_context: Option<&str>,
+    ) -> Vec<RankedResult> {
+        self.rerank_internal(results, query_context, None)
+    }
+
+    /// Reranke en favorisant les résultats proches des entités citées par la requête
+    pub fn rerank_with_graph(
+        &self,
+        results: Vec<SearchResultKNN>,
+        query: &str,
+        query_context: Option<&str>,
+        graph: &KnowledgeGraph,
+    ) -> Vec<RankedResult> {
+        let entities = graph.entities_in_text(query);
+        if entities.is_empty() {
+            return self.rerank(results, query_context);
+        }
+
+        let entity_ids: Vec<String> = entities.iter().map(|e| e.id.clone()).collect();
+        let labels: Vec<String> = entities.iter().map(|e| e.title.clone()).collect();
+        self.rerank_internal(results, query_context, Some((graph, &entity_ids, &labels)))
+    }
+
+    fn rerank_internal(
         &self,
         mut results: Vec<SearchResultKNN>,
         query_context: Option<&str>,
+        graph: Option<(&KnowledgeGraph, &[String], &[String])>,
     ) -> Vec<RankedResult> {
         let mut ranked_results = Vec::new();
 
         for result in results.iter_mut() {
-            let scores = self.calculate_composite_score(result, query_context);
-            
+            let mut scores = self.calculate_composite_score(result, query_context);
+
+            if let Some((graph, entity_ids, _)) = graph {
+                let doc_id = result.metadata.get("doc_id").cloned().unwrap_or_else(|| result.id.clone());
+                let chunk_id = (doc_id != result.id).then_some(result.id.as_str());
+                let proximity = graph.proximity(&doc_id, chunk_id, entity_ids);
+                scores.graph_boost = proximity * self.config.graph_boost_weight;
+                scores.total += scores.graph_boost;
+            }
+
             let explanation = if self.config.enable_explainability {
-                self.generate_explanation(&scores)
+                self.generate_explanation(&scores).map(|text| match graph {
+                    Some((_, _, labels)) if scores.graph_boost > 0.0 => {
+                        format!("{} (lié à {})", text, labels.join(", "))
+                    }
+                    _ => text,
+                })
             } else {
                 None
             };
@@ -109,6 +158,7 @@ impl ContextualReranker {
             recency: recency_score,
             authority: authority_score,
             graph_position: graph_score,
+            graph_boost: 0.0,
             total,
         }
     }
@@ -167,7 +217,7 @@ impl ContextualReranker {
 
     /// Calcule le score d'autorité
     fn calculate_authority_score(&self, metadata: &HashMap<String, String>) -> f32 {
-        let mut score = 0.5; // Baseline
+        let mut score: f32 = 0.5; // Baseline
 
         // Boost si document officiel
         if let Some(doc_type) = metadata.get("doc_type") {
@@ -237,6 +287,10 @@ impl ContextualReranker {
             parts.push("document central dans le graphe".to_string());
         }
 
+        if scores.graph_boost > 0.0 {
+            parts.push("proche d'une entité citée dans la requête".to_string());
+        }
+
         if parts.is_empty() {
             return None;
         }
@@ -284,6 +338,9 @@ pub struct CompositeScores {
     pub recency: f32,
     pub authority: f32,
     pub graph_position: f32,
+    /// Bonus de proximité aux entités de la requête (0 hors `rerank_with_graph`)
+    #[serde(default)]
+    pub graph_boost: f32,
     pub total: f32,
 }
 
@@ -355,6 +412,39 @@ mod tests {
         assert!(score_recent > score_old);
     }
 
+    #[test]
+    fn test_graph_boost_for_known_entity() {
+        use crate::semantic::extraction::EntityExtractor;
+        use crate::semantic::IndexedDocument;
+
+        let mut graph = KnowledgeGraph::new();
+        for (id, content) in [("atlas", "Le projet Atlas passe en production."), ("other", "Notes diverses.")] {
+            let doc = IndexedDocument {
+                id: id.to_string(),
+                title: id.to_string(),
+                content: content.to_string(),
+                doc_type: "text".to_string(),
+                metadata: HashMap::new(),
+                embedding: Vec::new(),
+                embedding_model: None,
+                chunks: Vec::new(),
+                indexed_at: chrono::Utc::now(),
+            };
+            graph.index_document(&doc, &EntityExtractor::new().extract(&doc));
+        }
+
+        let reranker = ContextualReranker::new(RerankerConfig::default());
+        let results = vec![create_test_result("other", 0.82), create_test_result("atlas", 0.80)];
+
+        let plain = reranker.rerank(results.clone(), None);
+        assert_eq!(plain[0].id, "other");
+
+        let boosted = reranker.rerank_with_graph(results, "statut du projet Atlas", None, &graph);
+        assert_eq!(boosted[0].id, "atlas");
+        assert!(boosted[0].scores.graph_boost > 0.0);
+        assert_eq!(boosted[1].scores.graph_boost, 0.0);
+    }
+
     #[test]
     fn test_filter_false_positives() {
         let reranker = ContextualReranker::new(RerankerConfig::default());
@@ -370,6 +460,7 @@ mod tests {
                     recency: 0.12,
                     authority: 0.10,
                     graph_position: 0.05,
+                    graph_boost: 0.0,
                     total: 0.75,
                 },
                 metadata: HashMap::new(),
@@ -385,6 +476,7 @@ mod tests {
                     recency: 0.02,
                     authority: 0.01,
                     graph_position: 0.01,
+                    graph_boost: 0.0,
                     total: 0.2,
                 },
                 metadata: HashMap::new(),
@@ -1,58 +1,161 @@
 // TITANE∞ v13 - Semantic Storage
 // Stockage persistant chiffré pour l'index sémantique
+//
+// Trousseau : une clé de données (DEK) aléatoire chiffre les fichiers ; elle est
+// elle-même chiffrée par une clé dérivée du mot de passe (Argon2id). L'en-tête
+// `.keyring.json` conserve le sel, les paramètres KDF et une valeur de contrôle,
+// si bien qu'un index écrit dans une session se relit dans la suivante et qu'un
+// changement de mot de passe ne ré-chiffre que la DEK.
 
-use aes_gcm::{
-    aead::{Aead, KeyInit},
-    Aes256Gcm, Nonce,
-};
-use argon2::{Argon2, PasswordHasher};
-use argon2::password_hash::{SaltString, rand_core::OsRng};
+use crate::shared::crypto::{self, KdfParams, KEY_SIZE};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+const KEYRING_FILE: &str = ".keyring.json";
+const KEYRING_VERSION: u32 = 1;
+/// En-tête des fichiers chiffrés : magic + version
+const FILE_MAGIC: &[u8; 4] = b"TSE1";
+const CHECK_PLAINTEXT: &[u8] = b"titane-semantic-keyring";
+const CHECK_AAD: &[u8] = b"__check__";
+const DEK_AAD: &[u8] = b"__dek__";
+
+/// Paramètres Argon2id utilisés à la création du trousseau
+pub use crate::shared::crypto::KdfConfig;
+
+/// En-tête persistant du trousseau
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyringHeader {
+    version: u32,
+    kdf: KdfParams,
+    /// Valeur de contrôle : constante chiffrée par la clé dérivée du mot de passe
+    key_check: String,
+    /// DEK chiffrée par la clé dérivée du mot de passe
+    wrapped_dek: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+struct Keyring {
+    header: KeyringHeader,
+    /// DEK en clair, présente uniquement si déverrouillé
+    dek: Option<[u8; KEY_SIZE]>,
+}
+
+impl Keyring {
+    fn forget_dek(&mut self) {
+        if let Some(dek) = self.dek.as_mut() {
+            crypto::wipe(dek);
+        }
+        self.dek = None;
+    }
+}
+
+impl Drop for Keyring {
+    fn drop(&mut self) {
+        self.forget_dek();
+    }
+}
+
 /// Gestionnaire de stockage sémantique
 pub struct SemanticStorage {
     storage_path: PathBuf,
-    encryption_key: Option<Vec<u8>>,
+    keyring: Option<Keyring>,
 }
 
 impl SemanticStorage {
     pub fn new(storage_path: PathBuf) -> Self {
         Self {
             storage_path,
-            encryption_key: None,
+            keyring: None,
         }
     }
 
+    /// Ouvre (ou crée) le trousseau du stockage et le déverrouille
     pub fn with_encryption(storage_path: PathBuf, password: &str) -> Result<Self, String> {
-        let key = Self::derive_key(password)?;
+        Self::with_encryption_config(storage_path, password, KdfConfig::default())
+    }
+
+    /// Comme `with_encryption`, avec des paramètres KDF pour un nouveau trousseau
+    pub fn with_encryption_config(storage_path: PathBuf, password: &str, kdf: KdfConfig) -> Result<Self, String> {
+        let header_path = storage_path.join(KEYRING_FILE);
+
+        let keyring = if header_path.exists() {
+            let content = fs::read_to_string(&header_path)
+                .map_err(|e| format!("Failed to read keyring: {}", e))?;
+            let header: KeyringHeader = serde_json::from_str(&content)
+                .map_err(|e| format!("Invalid keyring header: {}", e))?;
+            if header.version != KEYRING_VERSION {
+                return Err(format!("Unsupported keyring version: {}", header.version));
+            }
+            let dek = unwrap_dek(&header, password)?;
+            Keyring {
+                header,
+                dek: Some(dek),
+            }
+        } else {
+            let dek: [u8; KEY_SIZE] = rand::random();
+            let header = new_header(password, &dek, kdf)?;
+            write_atomic(&header_path, &serialize_header(&header)?)?;
+            Keyring {
+                header,
+                dek: Some(dek),
+            }
+        };
+
         Ok(Self {
             storage_path,
-            encryption_key: Some(key),
+            keyring: Some(keyring),
         })
     }
 
-    fn derive_key(password: &str) -> Result<Vec<u8>, String> {
-        let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
-        
-        let password_hash = argon2
-            .hash_password(password.as_bytes(), &salt)
-            .map_err(|e| format!("Key derivation failed: {}", e))?;
+    pub fn is_encrypted(&self) -> bool {
+        self.keyring.is_some()
+    }
 
-        let hash_bytes = password_hash.hash.ok_or("No hash generated")?;
-        Ok(hash_bytes.as_bytes()[..32].to_vec())
+    pub fn is_locked(&self) -> bool {
+        self.keyring.as_ref().map(|k| k.dek.is_none()).unwrap_or(false)
+    }
+
+    /// Efface la DEK de la mémoire ; `save`/`load` échouent jusqu'à `unlock`
+    pub fn lock(&mut self) {
+        if let Some(keyring) = self.keyring.as_mut() {
+            keyring.forget_dek();
+        }
+    }
+
+    pub fn unlock(&mut self, password: &str) -> Result<(), String> {
+        let keyring = self.keyring.as_mut().ok_or("Storage is not encrypted")?;
+        let dek = unwrap_dek(&keyring.header, password)?;
+        keyring.dek = Some(dek);
+        Ok(())
+    }
+
+    /// Change le mot de passe : seule la DEK est ré-chiffrée, les données restent intactes
+    pub fn change_password(&mut self, old_password: &str, new_password: &str) -> Result<(), String> {
+        let header_path = self.storage_path.join(KEYRING_FILE);
+        let keyring = self.keyring.as_mut().ok_or("Storage is not encrypted")?;
+
+        let dek = unwrap_dek(&keyring.header, old_password)?;
+        let mut header = new_header(new_password, &dek, keyring.header.kdf.config())?;
+        header.created_at = keyring.header.created_at;
+
+        write_atomic(&header_path, &serialize_header(&header)?)?;
+        keyring.header = header;
+        keyring.dek = Some(dek);
+        Ok(())
     }
 
     pub fn save<T: Serialize>(&self, filename: &str, data: &T) -> Result<(), String> {
         let json = serde_json::to_string(data)
             .map_err(|e| format!("Serialization failed: {}", e))?;
 
-        let data_bytes = if let Some(key) = &self.encryption_key {
-            self.encrypt(json.as_bytes(), key)?
-        } else {
-            json.into_bytes()
+        let data_bytes = match &self.keyring {
+            Some(keyring) => {
+                let dek = keyring.dek.as_ref().ok_or("Storage is locked")?;
+                self.encrypt(json.as_bytes(), dek, filename)?
+            }
+            None => json.into_bytes(),
         };
 
         let filepath = self.storage_path.join(filename);
@@ -61,22 +164,27 @@ impl SemanticStorage {
                 .map_err(|e| format!("Failed to create directory: {}", e))?;
         }
 
-        fs::write(&filepath, data_bytes)
-            .map_err(|e| format!("Failed to write file: {}", e))?;
-
-        Ok(())
+        write_atomic(&filepath, &data_bytes)
     }
 
+    /// Charge un fichier ; un fichier JSON en clair est accepté et sera chiffré au prochain `save`
     pub fn load<T: for<'de> Deserialize<'de>>(&self, filename: &str) -> Result<T, String> {
         let filepath = self.storage_path.join(filename);
-        
+
         let data_bytes = fs::read(&filepath)
             .map_err(|e| format!("Failed to read file: {}", e))?;
 
-        let json_bytes = if let Some(key) = &self.encryption_key {
-            self.decrypt(&data_bytes, key)?
-        } else {
-            data_bytes
+        let json_bytes = match &self.keyring {
+            Some(keyring) if data_bytes.starts_with(FILE_MAGIC) => {
+                let dek = keyring.dek.as_ref().ok_or("Storage is locked")?;
+                self.decrypt(&data_bytes, dek, filename)?
+            }
+            Some(_) if !looks_like_json(&data_bytes) => {
+                return Err(
+                    "Unreadable legacy encrypted file (salt was never persisted); re-index required".to_string(),
+                );
+            }
+            _ => data_bytes,
         };
 
         let json = String::from_utf8(json_bytes)
@@ -86,25 +194,185 @@ impl SemanticStorage {
             .map_err(|e| format!("Deserialization failed: {}", e))
     }
 
-    fn encrypt(&self, data: &[u8], key: &[u8]) -> Result<Vec<u8>, String> {
-        let cipher = Aes256Gcm::new_from_slice(key)
-            .map_err(|e| format!("Cipher creation failed: {}", e))?;
+    /// Format : magic || nonce aléatoire || ciphertext (AAD = nom du fichier)
+    fn encrypt(&self, data: &[u8], key: &[u8; KEY_SIZE], filename: &str) -> Result<Vec<u8>, String> {
+        let sealed = crypto::encrypt(key, data, filename.as_bytes())
+            .map_err(|e| format!("Encryption failed: {}", e))?;
 
-        let nonce = Nonce::from_slice(b"unique nonce"); // TODO: Generate random nonce
-        
-        cipher
-            .encrypt(nonce, data)
-            .map_err(|e| format!("Encryption failed: {}", e))
+        let mut output = Vec::with_capacity(FILE_MAGIC.len() + sealed.len());
+        output.extend_from_slice(FILE_MAGIC);
+        output.extend_from_slice(&sealed);
+        Ok(output)
     }
 
-    fn decrypt(&self, data: &[u8], key: &[u8]) -> Result<Vec<u8>, String> {
-        let cipher = Aes256Gcm::new_from_slice(key)
-            .map_err(|e| format!("Cipher creation failed: {}", e))?;
-
-        let nonce = Nonce::from_slice(b"unique nonce");
-        
-        cipher
-            .decrypt(nonce, data)
+    fn decrypt(&self, data: &[u8], key: &[u8; KEY_SIZE], filename: &str) -> Result<Vec<u8>, String> {
+        crypto::decrypt(key, &data[FILE_MAGIC.len()..], filename.as_bytes())
             .map_err(|e| format!("Decryption failed: {}", e))
     }
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Trousseau
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn new_header(password: &str, dek: &[u8; KEY_SIZE], kdf: KdfConfig) -> Result<KeyringHeader, String> {
+    let params = KdfParams::generate(kdf);
+
+    let mut kek = derive_key(password, &params)?;
+    let key_check = seal(&kek, CHECK_PLAINTEXT, CHECK_AAD);
+    let wrapped_dek = seal(&kek, dek, DEK_AAD);
+    crypto::wipe(&mut kek);
+
+    let now = chrono::Utc::now();
+    Ok(KeyringHeader {
+        version: KEYRING_VERSION,
+        kdf: params,
+        key_check: key_check?,
+        wrapped_dek: wrapped_dek?,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+fn unwrap_dek(header: &KeyringHeader, password: &str) -> Result<[u8; KEY_SIZE], String> {
+    let mut kek = derive_key(password, &header.kdf)?;
+
+    let result = match crypto::open_sealed(&kek, &header.key_check, CHECK_AAD) {
+        Ok(check) if check == CHECK_PLAINTEXT => crypto::open_sealed(&kek, &header.wrapped_dek, DEK_AAD)
+            .map_err(|_| "Corrupted keyring: data key cannot be unwrapped".to_string())
+            .and_then(|bytes| {
+                <[u8; KEY_SIZE]>::try_from(bytes.as_slice())
+                    .map_err(|_| "Corrupted keyring: invalid data key size".to_string())
+            }),
+        _ => Err("Invalid password".to_string()),
+    };
+
+    crypto::wipe(&mut kek);
+    result
+}
+
+fn derive_key(password: &str, kdf: &KdfParams) -> Result<[u8; KEY_SIZE], String> {
+    kdf.derive_key(password).map_err(|e| format!("Key derivation failed: {}", e))
+}
+
+fn seal(key: &[u8; KEY_SIZE], plaintext: &[u8], aad: &[u8]) -> Result<String, String> {
+    crypto::seal(key, plaintext, aad).map_err(|e| format!("Encryption failed: {}", e))
+}
+
+fn serialize_header(header: &KeyringHeader) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(header).map_err(|e| format!("Serialization failed: {}", e))
+}
+
+fn looks_like_json(data: &[u8]) -> bool {
+    std::str::from_utf8(data)
+        .map(|s| matches!(s.trim_start().chars().next(), Some('{' | '[' | '"')) || s.trim() == "null")
+        .unwrap_or(false)
+}
+
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data).map_err(|e| format!("Failed to write file: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to write file: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Paramètres KDF allégés pour les tests
+    const TEST_KDF: KdfConfig = KdfConfig {
+        memory_kib: 256,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("titane_semantic_storage_{}", uuid::Uuid::new_v4()))
+    }
+
+    fn sample() -> HashMap<String, Vec<f32>> {
+        let mut data = HashMap::new();
+        data.insert("doc1".to_string(), vec![0.1, 0.2, 0.3]);
+        data
+    }
+
+    #[test]
+    fn test_round_trip_across_instances() {
+        let dir = temp_dir();
+        {
+            let storage = SemanticStorage::with_encryption_config(dir.clone(), "secret", TEST_KDF).unwrap();
+            storage.save("index.json", &sample()).unwrap();
+        }
+
+        let raw = fs::read(dir.join("index.json")).unwrap();
+        assert!(raw.starts_with(FILE_MAGIC));
+        assert!(!String::from_utf8_lossy(&raw).contains("doc1"));
+
+        let reopened = SemanticStorage::with_encryption(dir.clone(), "secret").unwrap();
+        let loaded: HashMap<String, Vec<f32>> = reopened.load("index.json").unwrap();
+        assert_eq!(loaded, sample());
+
+        assert_eq!(
+            SemanticStorage::with_encryption(dir.clone(), "wrong").err().as_deref(),
+            Some("Invalid password")
+        );
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_change_password_keeps_data() {
+        let dir = temp_dir();
+        let mut storage = SemanticStorage::with_encryption_config(dir.clone(), "old", TEST_KDF).unwrap();
+        storage.save("index.json", &sample()).unwrap();
+        let before = fs::read(dir.join("index.json")).unwrap();
+
+        assert!(storage.change_password("bad", "new").is_err());
+        storage.change_password("old", "new").unwrap();
+        assert_eq!(fs::read(dir.join("index.json")).unwrap(), before);
+
+        assert!(SemanticStorage::with_encryption(dir.clone(), "old").is_err());
+        let reopened = SemanticStorage::with_encryption(dir.clone(), "new").unwrap();
+        let loaded: HashMap<String, Vec<f32>> = reopened.load("index.json").unwrap();
+        assert_eq!(loaded, sample());
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_lock_unlock() {
+        let dir = temp_dir();
+        let mut storage = SemanticStorage::with_encryption_config(dir.clone(), "secret", TEST_KDF).unwrap();
+        storage.save("index.json", &sample()).unwrap();
+
+        storage.lock();
+        assert!(storage.is_locked());
+        assert!(storage.load::<HashMap<String, Vec<f32>>>("index.json").is_err());
+        assert!(storage.save("other.json", &sample()).is_err());
+        assert!(!dir.join("other.json").exists());
+
+        assert!(storage.unlock("wrong").is_err());
+        storage.unlock("secret").unwrap();
+        let loaded: HashMap<String, Vec<f32>> = storage.load("index.json").unwrap();
+        assert_eq!(loaded, sample());
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_plaintext_store_migrates_to_encrypted() {
+        let dir = temp_dir();
+        SemanticStorage::new(dir.clone()).save("index.json", &sample()).unwrap();
+
+        let storage = SemanticStorage::with_encryption_config(dir.clone(), "secret", TEST_KDF).unwrap();
+        let loaded: HashMap<String, Vec<f32>> = storage.load("index.json").unwrap();
+        storage.save("index.json", &loaded).unwrap();
+        assert!(fs::read(dir.join("index.json")).unwrap().starts_with(FILE_MAGIC));
+
+        let reopened = SemanticStorage::with_encryption(dir.clone(), "secret").unwrap();
+        let loaded: HashMap<String, Vec<f32>> = reopened.load("index.json").unwrap();
+        assert_eq!(loaded, sample());
+        fs::remove_dir_all(dir).ok();
+    }
+}
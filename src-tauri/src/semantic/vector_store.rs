@@ -1,12 +1,18 @@
 // TITANE∞ v13 - Vector Store with HNSW
 // Stockage vectoriel haute performance avec index HNSW (Hierarchical Navigable Small World)
+// incrémental, persisté en binaire (graphe bincode + vecteurs mappés en mémoire)
 
-use instant_distance::{Builder, HnswMap, Point, Search};
+use crate::semantic::embedder::Embedding;
+use crate::semantic::hnsw::{HnswGraph, HnswIndex, HnswParams};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+
+/// Au-delà de cette part de tombstones, `optimize` lance une compaction
+const COMPACTION_THRESHOLD: f32 = 0.2;
 
 /// Point vectoriel avec identifiant
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,32 +22,149 @@ pub struct VectorPoint {
     pub metadata: HashMap<String, String>,
 }
 
-impl Point for VectorPoint {
-    fn distance(&self, other: &Self) -> f32 {
-        cosine_distance(&self.vector, &other.vector)
-    }
-}
-
 /// Store vectoriel HNSW
 pub struct VectorStore {
     dimensions: usize,
-    points: HashMap<String, VectorPoint>,
-    hnsw_index: Option<HnswMap<VectorPoint, String>>,
+    /// Métadonnées par point (les vecteurs vivent dans l'index)
+    metadata: HashMap<String, HashMap<String, String>>,
+    index: HnswIndex,
     index_path: PathBuf,
+    /// Empreinte du modèle d'embedding des points (voir `Embedder::fingerprint`)
+    model_fingerprint: Option<String>,
+    rebuild: Option<PendingRebuild>,
+}
+
+/// Reconstruction en arrière-plan : les écritures survenues pendant la
+/// reconstruction sont journalisées puis rejouées sur le nouvel index
+struct PendingRebuild {
+    handle: JoinHandle<HnswIndex>,
+    journal: Vec<JournalOp>,
+}
+
+enum JournalOp {
+    Upsert(String, Vec<f32>),
+    Remove(String),
+}
+
+/// Instantané persisté (bincode) ; les vecteurs sont dans `<index>.vectors.bin`
+#[derive(Serialize, Deserialize)]
+struct StoreSnapshot {
+    dimensions: usize,
+    model_fingerprint: Option<String>,
+    metadata: HashMap<String, HashMap<String, String>>,
+    graph: HnswGraph,
+}
+
+/// Manifeste désignant la génération courante : graphe et vecteurs sont
+/// écrits sous des noms neufs, puis validés ensemble par un seul renommage
+#[derive(Serialize, Deserialize)]
+struct StoreManifest {
+    generation: String,
+    graph: String,
+    vectors: String,
+}
+
+/// Métadonnées de l'ancien format (`points.json` + `meta.json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoreMeta {
+    dimensions: usize,
+    model_fingerprint: Option<String>,
 }
 
 impl VectorStore {
     /// Crée un nouveau vector store
     pub fn new(dimensions: usize, index_path: PathBuf) -> Self {
+        Self::with_params(dimensions, index_path, HnswParams::default())
+    }
+
+    pub fn with_params(dimensions: usize, index_path: PathBuf, params: HnswParams) -> Self {
         Self {
             dimensions,
-            points: HashMap::new(),
-            hnsw_index: None,
+            metadata: HashMap::new(),
+            index: HnswIndex::new(dimensions, params),
             index_path,
+            model_fingerprint: None,
+            rebuild: None,
+        }
+    }
+
+    /// Associe le store à un modèle d'embedding ; refuse de changer de modèle
+    /// tant que le store contient des points
+    pub fn set_model_fingerprint(&mut self, fingerprint: &str) -> Result<(), VectorStoreError> {
+        match &self.model_fingerprint {
+            Some(current) if current != fingerprint && !self.is_empty() => {
+                Err(VectorStoreError::ModelMismatch {
+                    expected: current.clone(),
+                    got: fingerprint.to_string(),
+                })
+            }
+            _ => {
+                self.model_fingerprint = Some(fingerprint.to_string());
+                Ok(())
+            }
         }
     }
 
-    /// Ajoute un point vectoriel
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    pub fn model_fingerprint(&self) -> Option<&str> {
+        self.model_fingerprint.as_deref()
+    }
+
+    /// Vérifie qu'un vecteur de requête provient du même modèle que le store
+    pub fn check_model(&self, fingerprint: &str) -> Result<(), VectorStoreError> {
+        match &self.model_fingerprint {
+            Some(current) if current != fingerprint => Err(VectorStoreError::ModelMismatch {
+                expected: current.clone(),
+                got: fingerprint.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Vide le store et le détache de son modèle (avant ré-indexation complète)
+    pub fn clear(&mut self) {
+        self.rebuild = None;
+        self.index = HnswIndex::new(self.dimensions, self.index.params().clone());
+        self.metadata.clear();
+        self.model_fingerprint = None;
+    }
+
+    /// Ajoute un point embeddé : le premier point fixe le modèle du store,
+    /// un embedding d'un autre modèle est refusé
+    pub fn add_embedding(
+        &mut self,
+        id: String,
+        embedding: Embedding,
+        metadata: HashMap<String, String>,
+    ) -> Result<(), VectorStoreError> {
+        if embedding.vector.len() != self.dimensions {
+            return Err(VectorStoreError::DimensionMismatch {
+                expected: self.dimensions,
+                got: embedding.vector.len(),
+            });
+        }
+        self.set_model_fingerprint(&embedding.fingerprint)?;
+        self.add_point(VectorPoint {
+            id,
+            vector: embedding.vector,
+            metadata,
+        })
+    }
+
+    /// Recherche kNN après vérification du modèle de la requête
+    pub fn search_embedding(
+        &self,
+        query: &Embedding,
+        k: usize,
+    ) -> Result<Vec<SearchResultKNN>, VectorStoreError> {
+        self.check_model(&query.fingerprint)?;
+        self.search_knn(&query.vector, k)
+    }
+
+    /// Ajoute un point vectoriel (insertion incrémentale dans l'index)
     pub fn add_point(&mut self, point: VectorPoint) -> Result<(), VectorStoreError> {
         if point.vector.len() != self.dimensions {
             return Err(VectorStoreError::DimensionMismatch {
@@ -50,9 +173,12 @@ impl VectorStore {
             });
         }
 
-        self.points.insert(point.id.clone(), point);
-        // L'index sera reconstruit lors du prochain build()
-        self.hnsw_index = None;
+        self.poll_rebuild();
+        self.index.insert(point.id.clone(), &point.vector);
+        if let Some(rebuild) = &mut self.rebuild {
+            rebuild.journal.push(JournalOp::Upsert(point.id.clone(), point.vector));
+        }
+        self.metadata.insert(point.id, point.metadata);
         Ok(())
     }
 
@@ -64,20 +190,15 @@ impl VectorStore {
         Ok(())
     }
 
-    /// Construit l'index HNSW
+    /// Reconstruit entièrement l'index (synchrone) ; l'index étant maintenu
+    /// incrémentalement, ce n'est utile que pour compacter immédiatement
     pub fn build_index(&mut self) -> Result<(), VectorStoreError> {
-        if self.points.is_empty() {
+        if self.is_empty() {
             return Err(VectorStoreError::EmptyStore);
         }
 
-        let points_vec: Vec<VectorPoint> = self.points.values().cloned().collect();
-        let values: Vec<String> = points_vec.iter().map(|p| p.id.clone()).collect();
-
-        let hnsw = Builder::default()
-            .seed(42)
-            .build(points_vec, values);
-
-        self.hnsw_index = Some(hnsw);
+        self.wait_for_rebuild();
+        self.index = self.index.compacted();
         Ok(())
     }
 
@@ -94,37 +215,40 @@ impl VectorStore {
             });
         }
 
-        let hnsw = self
-            .hnsw_index
-            .as_ref()
-            .ok_or(VectorStoreError::IndexNotBuilt)?;
-
-        // Crée un point temporaire pour la recherche
-        let query_point = VectorPoint {
-            id: "__query__".to_string(),
-            vector: query_vector.to_vec(),
-            metadata: HashMap::new(),
-        };
-
-        let mut search = Search::default();
-        let results = hnsw.search(&query_point, &mut search);
-
-        let mut knn_results = Vec::new();
-        for item in results.into_iter().take(k) {
-            if let Some(point) = self.points.get(item.value) {
-                let distance = cosine_distance(query_vector, &point.vector);
-                let similarity = 1.0 - distance;
-
-                knn_results.push(SearchResultKNN {
-                    id: point.id.clone(),
-                    similarity,
-                    distance,
-                    metadata: point.metadata.clone(),
-                });
-            }
-        }
-
-        Ok(knn_results)
+        // L'index explore de toute façon `ef_search` candidats : on les garde
+        // tous pour départager correctement les égalités avant de tronquer
+        let candidates = k.max(self.index.params().ef_search);
+        let mut knn_results: Vec<(SearchResultKNN, f32)> = self
+            .index
+            .search(query_vector, candidates)
+            .into_iter()
+            .map(|(id, distance)| {
+                // Départage les égalités de direction par la distance euclidienne
+                let l2 = self
+                    .index
+                    .vector(&id)
+                    .map(|v| euclidean_distance(query_vector, v))
+                    .unwrap_or(f32::MAX);
+                let metadata = self.metadata.get(&id).cloned().unwrap_or_default();
+                (
+                    SearchResultKNN {
+                        id,
+                        similarity: 1.0 - distance,
+                        distance,
+                        metadata,
+                    },
+                    l2,
+                )
+            })
+            .collect();
+
+        knn_results.sort_by(|(a, a_l2), (b, b_l2)| {
+            a.distance
+                .total_cmp(&b.distance)
+                .then_with(|| a_l2.total_cmp(b_l2))
+        });
+
+        Ok(knn_results.into_iter().take(k).map(|(r, _)| r).collect())
     }
 
     /// Recherche avec filtre
@@ -140,34 +264,128 @@ impl VectorStore {
         Ok(all_results)
     }
 
-    /// Sauvegarde l'index sur disque
+    /// Sauvegarde l'index sur disque : graphe + métadonnées en bincode,
+    /// vecteurs en f32 bruts (mappables au chargement). Chaque sauvegarde écrit
+    /// une nouvelle génération ; un fichier éventuellement mappé n'est jamais
+    /// réécrit ni remplacé, et le renommage du manifeste valide la paire
     pub fn save(&self) -> Result<(), VectorStoreError> {
+        let io_err = |e: std::io::Error| VectorStoreError::IoError(e.to_string());
+
         // Crée le répertoire si nécessaire
         if let Some(parent) = self.index_path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| VectorStoreError::IoError(e.to_string()))?;
+            fs::create_dir_all(parent).map_err(io_err)?;
         }
 
-        // Sauvegarde les points
-        let points_path = self.index_path.with_extension("points.json");
-        let file = File::create(&points_path).map_err(|e| VectorStoreError::IoError(e.to_string()))?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer(writer, &self.points)
+        let generation = uuid::Uuid::new_v4().simple().to_string();
+        let vectors_path = generation_file(&self.index_path, "vectors", &generation);
+        let graph_path = generation_file(&self.index_path, "graph", &generation);
+
+        self.index.write_vectors(&vectors_path).map_err(io_err)?;
+
+        let snapshot = StoreSnapshot {
+            dimensions: self.dimensions,
+            model_fingerprint: self.model_fingerprint.clone(),
+            metadata: self.metadata.clone(),
+            graph: self.index.graph().clone(),
+        };
+        let file = File::create(&graph_path).map_err(io_err)?;
+        let mut writer = BufWriter::new(file);
+        bincode::serialize_into(&mut writer, &snapshot)
             .map_err(|e| VectorStoreError::SerializationError(e.to_string()))?;
+        writer.flush().map_err(io_err)?;
 
-        // Sauvegarde l'index HNSW (si construit)
-        if let Some(_hnsw) = &self.hnsw_index {
-            // Note: instant-distance ne supporte pas la sérialisation directe
-            // On reconstruira l'index au chargement
-            // Alternativement, on pourrait utiliser bincode pour sérialiser les points
-        }
+        let manifest = StoreManifest {
+            generation,
+            graph: file_name(&graph_path),
+            vectors: file_name(&vectors_path),
+        };
+        let manifest_path = manifest_file(&self.index_path);
+        let manifest_tmp = manifest_path.with_extension("json.tmp");
+        let json = serde_json::to_vec(&manifest)
+            .map_err(|e| VectorStoreError::SerializationError(e.to_string()))?;
+        fs::write(&manifest_tmp, json).map_err(io_err)?;
+        fs::rename(&manifest_tmp, &manifest_path).map_err(io_err)?;
 
+        self.remove_stale_generations(&manifest);
         Ok(())
     }
 
-    /// Charge l'index depuis le disque
+    /// Supprime les générations précédentes ; un fichier encore mappé (refus
+    /// sous Windows) est laissé en place et retenté à la sauvegarde suivante
+    fn remove_stale_generations(&self, current: &StoreManifest) {
+        let (Some(dir), Some(stem)) = (self.index_path.parent(), self.index_path.file_stem()) else {
+            return;
+        };
+        let prefix = format!("{}.", stem.to_string_lossy());
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let stale = name.starts_with(&prefix)
+                && name.ends_with(".bin")
+                && (name.contains(".graph.") || name.contains(".vectors."))
+                && name != current.graph
+                && name != current.vectors;
+            if stale {
+                fs::remove_file(entry.path()).ok();
+            }
+        }
+    }
+
+    /// Charge l'index depuis le disque sans reconstruction (vecteurs mappés) ;
+    /// l'ancien format `points.json` est reconstruit puis converti au prochain `save`
     pub fn load(index_path: PathBuf) -> Result<Self, VectorStoreError> {
+        let (graph_path, vectors_path) = match read_manifest(&index_path)? {
+            Some(manifest) => (
+                index_path.with_file_name(&manifest.graph),
+                index_path.with_file_name(&manifest.vectors),
+            ),
+            // Format sans manifeste (fichiers à nom fixe)
+            None => (graph_file(&index_path), vectors_file(&index_path)),
+        };
+        if !graph_path.exists() {
+            return Self::load_legacy(index_path);
+        }
+
+        let file = File::open(&graph_path).map_err(|e| VectorStoreError::IoError(e.to_string()))?;
+        let snapshot: StoreSnapshot = bincode::deserialize_from(BufReader::new(file))
+            .map_err(|e| VectorStoreError::SerializationError(e.to_string()))?;
+
+        let index = HnswIndex::from_parts(snapshot.graph, &vectors_path)
+            .map_err(|e| VectorStoreError::IoError(e.to_string()))?;
+
+        Ok(Self {
+            dimensions: snapshot.dimensions,
+            metadata: snapshot.metadata,
+            index,
+            index_path,
+            model_fingerprint: snapshot.model_fingerprint,
+            rebuild: None,
+        })
+    }
+
+    /// Charge l'index et vérifie qu'il a été construit par le modèle attendu ;
+    /// un store non vide sans empreinte est aussi refusé (à ré-indexer)
+    pub fn load_for_model(index_path: PathBuf, fingerprint: &str) -> Result<Self, VectorStoreError> {
+        let store = Self::load(index_path)?;
+        match store.model_fingerprint() {
+            Some(current) if current != fingerprint => Err(VectorStoreError::ModelMismatch {
+                expected: current.to_string(),
+                got: fingerprint.to_string(),
+            }),
+            None if !store.is_empty() => Err(VectorStoreError::ModelMismatch {
+                expected: "inconnu".to_string(),
+                got: fingerprint.to_string(),
+            }),
+            _ => Ok(store),
+        }
+    }
+
+    fn load_legacy(index_path: PathBuf) -> Result<Self, VectorStoreError> {
         let points_path = index_path.with_extension("points.json");
-        
+
         if !points_path.exists() {
             return Err(VectorStoreError::IoError("Index file not found".to_string()));
         }
@@ -177,65 +395,188 @@ impl VectorStore {
         let points: HashMap<String, VectorPoint> = serde_json::from_reader(reader)
             .map_err(|e| VectorStoreError::SerializationError(e.to_string()))?;
 
-        // Détermine les dimensions depuis le premier point
-        let dimensions = points
-            .values()
-            .next()
-            .map(|p| p.vector.len())
-            .unwrap_or(384);
+        let meta: Option<StoreMeta> = File::open(index_path.with_extension("meta.json"))
+            .ok()
+            .and_then(|f| serde_json::from_reader(BufReader::new(f)).ok());
 
-        let mut store = Self {
-            dimensions,
-            points,
-            hnsw_index: None,
-            index_path,
-        };
+        // Détermine les dimensions depuis les métadonnées ou le premier point
+        let dimensions = meta
+            .as_ref()
+            .map(|m| m.dimensions)
+            .or_else(|| points.values().next().map(|p| p.vector.len()))
+            .unwrap_or(384);
 
-        // Reconstruit l'index HNSW
-        if !store.points.is_empty() {
-            store.build_index()?;
-        }
+        let mut store = Self::new(dimensions, index_path);
+        store.model_fingerprint = meta.and_then(|m| m.model_fingerprint);
+        store.add_points(points.into_values().collect())?;
 
         Ok(store)
     }
 
     /// Retourne le nombre de points
     pub fn len(&self) -> usize {
-        self.points.len()
+        self.index.len()
     }
 
     /// Vérifie si le store est vide
     pub fn is_empty(&self) -> bool {
-        self.points.is_empty()
+        self.index.is_empty()
     }
 
-    /// Supprime un point
+    /// Supprime un point (tombstone dans l'index)
     pub fn remove_point(&mut self, id: &str) -> Option<VectorPoint> {
-        let removed = self.points.remove(id);
-        if removed.is_some() {
-            self.hnsw_index = None; // Invalide l'index
+        self.poll_rebuild();
+        let vector = self.index.vector(id)?.to_vec();
+        self.index.remove(id);
+        if let Some(rebuild) = &mut self.rebuild {
+            rebuild.journal.push(JournalOp::Remove(id.to_string()));
         }
-        removed
+
+        Some(VectorPoint {
+            id: id.to_string(),
+            vector,
+            metadata: self.metadata.remove(id).unwrap_or_default(),
+        })
+    }
+
+    /// Identifiants des points retenus par `filter` (identifiant, métadonnées)
+    pub fn ids_filtered<F>(&self, filter: F) -> Vec<String>
+    where
+        F: Fn(&str, &HashMap<String, String>) -> bool,
+    {
+        let mut ids: Vec<String> = self
+            .metadata
+            .iter()
+            .filter(|(id, metadata)| filter(id, metadata))
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+        ids
     }
 
     /// Met à jour un point
     pub fn update_point(&mut self, point: VectorPoint) -> Result<(), VectorStoreError> {
-        if point.vector.len() != self.dimensions {
-            return Err(VectorStoreError::DimensionMismatch {
-                expected: self.dimensions,
-                got: point.vector.len(),
-            });
-        }
+        self.add_point(point)
+    }
 
-        self.points.insert(point.id.clone(), point);
-        self.hnsw_index = None; // Invalide l'index
+    /// Optimise l'index : lance une compaction en arrière-plan si les
+    /// tombstones dépassent le seuil, et intègre une compaction terminée
+    pub fn optimize(&mut self) -> Result<(), VectorStoreError> {
+        self.poll_rebuild();
+        if self.rebuild.is_none() && self.index.tombstone_ratio() > COMPACTION_THRESHOLD {
+            self.start_background_rebuild();
+        }
         Ok(())
     }
 
-    /// Optimise l'index (reconstruction complète)
-    pub fn optimize(&mut self) -> Result<(), VectorStoreError> {
-        self.build_index()
+    /// Reconstruit l'index sur un instantané dans un thread dédié ; les
+    /// recherches continuent sur l'index courant jusqu'à l'échange
+    pub fn start_background_rebuild(&mut self) -> bool {
+        if self.rebuild.is_some() {
+            return false;
+        }
+
+        let dimensions = self.dimensions;
+        let params = self.index.params().clone();
+        let snapshot: Vec<(String, Vec<f32>)> = self
+            .index
+            .live_points()
+            .map(|(id, v)| (id.to_string(), v.to_vec()))
+            .collect();
+
+        let handle = std::thread::spawn(move || {
+            let mut index = HnswIndex::new(dimensions, params);
+            for (id, vector) in snapshot {
+                index.insert(id, &vector);
+            }
+            index
+        });
+
+        self.rebuild = Some(PendingRebuild {
+            handle,
+            journal: Vec::new(),
+        });
+        true
+    }
+
+    pub fn is_rebuilding(&self) -> bool {
+        self.rebuild.is_some()
     }
+
+    /// Échange l'index si la reconstruction est terminée (non bloquant)
+    pub fn poll_rebuild(&mut self) -> bool {
+        match &self.rebuild {
+            Some(rebuild) if rebuild.handle.is_finished() => {
+                self.wait_for_rebuild();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Attend la fin de la reconstruction en cours et l'intègre
+    pub fn wait_for_rebuild(&mut self) {
+        let rebuild = match self.rebuild.take() {
+            Some(rebuild) => rebuild,
+            None => return,
+        };
+
+        match rebuild.handle.join() {
+            Ok(mut index) => {
+                for op in rebuild.journal {
+                    match op {
+                        JournalOp::Upsert(id, vector) => index.insert(id, &vector),
+                        JournalOp::Remove(id) => {
+                            index.remove(&id);
+                        }
+                    }
+                }
+                self.index = index;
+            }
+            Err(_) => println!("[VECTOR_STORE] ⚠️ Reconstruction interrompue, index courant conservé"),
+        }
+    }
+
+    /// Part de tombstones dans l'index courant
+    pub fn tombstone_ratio(&self) -> f32 {
+        self.index.tombstone_ratio()
+    }
+}
+
+fn manifest_file(index_path: &Path) -> PathBuf {
+    index_path.with_extension("manifest.json")
+}
+
+fn read_manifest(index_path: &Path) -> Result<Option<StoreManifest>, VectorStoreError> {
+    match fs::read(manifest_file(index_path)) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| VectorStoreError::SerializationError(e.to_string())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(VectorStoreError::IoError(e.to_string())),
+    }
+}
+
+fn generation_file(index_path: &Path, kind: &str, generation: &str) -> PathBuf {
+    index_path.with_extension(format!("{}.{}.bin", kind, generation))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn graph_file(index_path: &Path) -> PathBuf {
+    index_path.with_extension("graph.bin")
+}
+
+fn vectors_file(index_path: &Path) -> PathBuf {
+    index_path.with_extension("vectors.bin")
+}
+
+fn euclidean_distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt()
 }
 
 /// Résultat de recherche kNN
@@ -251,6 +592,7 @@ pub struct SearchResultKNN {
 #[derive(Debug)]
 pub enum VectorStoreError {
     DimensionMismatch { expected: usize, got: usize },
+    ModelMismatch { expected: String, got: String },
     IndexNotBuilt,
     EmptyStore,
     IoError(String),
@@ -263,6 +605,9 @@ impl std::fmt::Display for VectorStoreError {
             VectorStoreError::DimensionMismatch { expected, got } => {
                 write!(f, "Dimension mismatch: expected {}, got {}", expected, got)
             }
+            VectorStoreError::ModelMismatch { expected, got } => {
+                write!(f, "Embedding model mismatch: expected {}, got {}", expected, got)
+            }
             VectorStoreError::IndexNotBuilt => write!(f, "HNSW index not built"),
             VectorStoreError::EmptyStore => write!(f, "Vector store is empty"),
             VectorStoreError::IoError(e) => write!(f, "IO error: {}", e),
@@ -353,4 +698,199 @@ mod tests {
         assert_eq!(results.len(), 3);
         assert_eq!(results[0].id, "doc5");
     }
+
+    fn angle_point(id: &str, angle: f32) -> VectorPoint {
+        VectorPoint {
+            id: id.to_string(),
+            vector: vec![angle.cos(), angle.sin(), 0.0],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_incremental_updates_without_rebuild() {
+        let mut store = VectorStore::new(3, std::env::temp_dir().join("test_vector_incremental"));
+        for i in 0..30 {
+            store.add_point(angle_point(&format!("doc{}", i), i as f32 * 0.05)).unwrap();
+        }
+
+        // Recherche immédiate, sans build_index
+        let query = vec![(0.5f32).cos(), (0.5f32).sin(), 0.0];
+        assert_eq!(store.search_knn(&query, 1).unwrap()[0].id, "doc10");
+
+        // Suppression et mise à jour visibles immédiatement
+        store.remove_point("doc10").unwrap();
+        assert!(store.search_knn(&query, 5).unwrap().iter().all(|r| r.id != "doc10"));
+
+        store.update_point(angle_point("doc0", 0.5)).unwrap();
+        assert_eq!(store.search_knn(&query, 1).unwrap()[0].id, "doc0");
+        assert_eq!(store.len(), 29);
+    }
+
+    #[test]
+    fn test_save_and_load_binary() {
+        let index_path = std::env::temp_dir().join(format!("test_vector_binary_{}", uuid::Uuid::new_v4()));
+        let mut store = VectorStore::new(3, index_path.clone());
+        for i in 0..20 {
+            let mut point = angle_point(&format!("doc{}", i), i as f32 * 0.1);
+            point.metadata.insert("doc_type".to_string(), "text".to_string());
+            store.add_point(point).unwrap();
+        }
+        store.remove_point("doc3");
+        store.set_model_fingerprint("local:test:3").unwrap();
+        store.save().unwrap();
+
+        let mut loaded = VectorStore::load(index_path.clone()).unwrap();
+        assert_eq!(loaded.len(), 19);
+        assert_eq!(loaded.model_fingerprint(), Some("local:test:3"));
+
+        let query = vec![(0.7f32).cos(), (0.7f32).sin(), 0.0];
+        let best = &loaded.search_knn(&query, 1).unwrap()[0];
+        assert_eq!(best.id, "doc7");
+        assert_eq!(best.metadata.get("doc_type").map(String::as_str), Some("text"));
+
+        // Le store chargé reste modifiable
+        loaded.add_point(angle_point("new", 0.7)).unwrap();
+        assert_eq!(loaded.len(), 20);
+
+        remove_store_files(&index_path);
+    }
+
+    fn store_files(index_path: &Path) -> Vec<String> {
+        let stem = index_path.file_name().unwrap().to_string_lossy().to_string();
+        let mut names: Vec<String> = fs::read_dir(index_path.parent().unwrap())
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|n| n.starts_with(&format!("{}.", stem)))
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn remove_store_files(index_path: &Path) {
+        for name in store_files(index_path) {
+            fs::remove_file(index_path.with_file_name(name)).ok();
+        }
+    }
+
+    #[test]
+    fn test_save_over_mapped_generation() {
+        let index_path = std::env::temp_dir().join(format!("test_vector_generations_{}", uuid::Uuid::new_v4()));
+        let mut store = VectorStore::new(3, index_path.clone());
+        for i in 0..10 {
+            store.add_point(angle_point(&format!("doc{}", i), i as f32 * 0.1)).unwrap();
+        }
+        store.save().unwrap();
+
+        // Le store chargé mappe la première génération et se sauvegarde par-dessus
+        let mut loaded = VectorStore::load(index_path.clone()).unwrap();
+        loaded.add_point(angle_point("late", 2.0)).unwrap();
+        loaded.save().unwrap();
+
+        let reloaded = VectorStore::load(index_path.clone()).unwrap();
+        assert_eq!(reloaded.len(), 11);
+        let late = vec![(2.0f32).cos(), (2.0f32).sin(), 0.0];
+        assert_eq!(reloaded.search_knn(&late, 1).unwrap()[0].id, "late");
+
+        // Manifeste + une seule paire graphe/vecteurs
+        assert_eq!(store_files(&index_path).len(), 3);
+        remove_store_files(&index_path);
+    }
+
+    #[test]
+    fn test_uncommitted_generation_is_ignored() {
+        let index_path = std::env::temp_dir().join(format!("test_vector_crash_{}", uuid::Uuid::new_v4()));
+        let mut store = VectorStore::new(3, index_path.clone());
+        store.add_point(angle_point("a", 0.1)).unwrap();
+        store.save().unwrap();
+
+        // Sauvegarde interrompue avant le manifeste : vecteurs d'une autre taille écrits
+        store.add_point(angle_point("b", 0.2)).unwrap();
+        store
+            .index
+            .write_vectors(&generation_file(&index_path, "vectors", "interrompue"))
+            .unwrap();
+
+        let loaded = VectorStore::load(index_path.clone()).unwrap();
+        assert_eq!(loaded.len(), 1);
+        remove_store_files(&index_path);
+    }
+
+    #[test]
+    fn test_background_compaction_replays_journal() {
+        let mut store = VectorStore::new(3, std::env::temp_dir().join("test_vector_compaction"));
+        for i in 0..40 {
+            store.add_point(angle_point(&format!("doc{}", i), i as f32 * 0.03)).unwrap();
+        }
+        for i in 0..15 {
+            store.remove_point(&format!("doc{}", i));
+        }
+        assert!(store.tombstone_ratio() > COMPACTION_THRESHOLD);
+
+        store.optimize().unwrap();
+        assert!(store.is_rebuilding());
+
+        // Écritures pendant la reconstruction
+        store.add_point(angle_point("late", 2.0)).unwrap();
+        store.remove_point("doc20");
+
+        store.wait_for_rebuild();
+        assert!(!store.is_rebuilding());
+        assert!(store.tombstone_ratio() < COMPACTION_THRESHOLD);
+        assert_eq!(store.len(), 25);
+
+        let late = vec![(2.0f32).cos(), (2.0f32).sin(), 0.0];
+        assert_eq!(store.search_knn(&late, 1).unwrap()[0].id, "late");
+    }
+
+    fn embedding(angle: f32, fingerprint: &str) -> Embedding {
+        Embedding {
+            vector: vec![angle.cos(), angle.sin(), 0.0],
+            fingerprint: fingerprint.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_embeddings_from_another_model_are_rejected() {
+        let mut store = VectorStore::new(3, std::env::temp_dir().join("test_vector_models"));
+        store.add_embedding("a".to_string(), embedding(0.1, "local:a:3"), HashMap::new()).unwrap();
+        assert_eq!(store.model_fingerprint(), Some("local:a:3"));
+
+        let add = store.add_embedding("b".to_string(), embedding(0.2, "ollama:b:3"), HashMap::new());
+        assert!(matches!(add, Err(VectorStoreError::ModelMismatch { .. })));
+        assert_eq!(store.len(), 1);
+
+        let search = store.search_embedding(&embedding(0.1, "ollama:b:3"), 1);
+        assert!(matches!(search, Err(VectorStoreError::ModelMismatch { .. })));
+        assert_eq!(store.search_embedding(&embedding(0.1, "local:a:3"), 1).unwrap()[0].id, "a");
+
+        // Après `clear`, le store accepte un nouveau modèle
+        store.clear();
+        assert!(store.is_empty() && store.model_fingerprint().is_none());
+        store.add_embedding("b".to_string(), embedding(0.2, "ollama:b:3"), HashMap::new()).unwrap();
+        assert_eq!(store.model_fingerprint(), Some("ollama:b:3"));
+    }
+
+    #[test]
+    fn test_load_for_model_checks_fingerprint() {
+        let index_path = std::env::temp_dir().join(format!("test_vector_model_{}", uuid::Uuid::new_v4()));
+        let mut store = VectorStore::new(3, index_path.clone());
+        store.add_embedding("a".to_string(), embedding(0.1, "local:a:3"), HashMap::new()).unwrap();
+        store.save().unwrap();
+
+        assert!(VectorStore::load_for_model(index_path.clone(), "local:a:3").is_ok());
+        assert!(matches!(
+            VectorStore::load_for_model(index_path.clone(), "gemini:x:3"),
+            Err(VectorStoreError::ModelMismatch { .. })
+        ));
+
+        // Store non vide sans empreinte : origine inconnue, refusé
+        let mut anonymous = VectorStore::new(3, index_path.clone());
+        anonymous.add_point(angle_point("a", 0.1)).unwrap();
+        anonymous.save().unwrap();
+        assert!(VectorStore::load_for_model(index_path.clone(), "local:a:3").is_err());
+
+        remove_store_files(&index_path);
+    }
 }
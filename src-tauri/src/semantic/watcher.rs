@@ -0,0 +1,648 @@
+// TITANE∞ v13 - Folder Watcher
+// Synchronisation de dossiers surveillés avec l'index sémantique :
+// globs d'inclusion/exclusion, extraction de texte, détection par hash,
+// propagation des suppressions, tâche de fond throttlée avec progression
+
+use crate::semantic::indexer::IndexManager;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, State};
+
+/// Événement Tauri émis à chaque étape de progression
+pub const PROGRESS_EVENT: &str = "semantic://index-progress";
+
+/// Préfixe des identifiants de documents issus de fichiers
+const FILE_DOC_PREFIX: &str = "file:";
+
+/// Racine surveillée
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchRoot {
+    pub path: PathBuf,
+    /// Globs relatifs à la racine (`**/*.md`) ; vide = extensions supportées
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Configuration du watcher
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatcherConfig {
+    pub poll_interval_secs: u64,
+    /// Fichiers traités avant une pause (throttling)
+    pub files_per_batch: usize,
+    pub batch_pause_ms: u64,
+    pub max_file_size_bytes: u64,
+    pub default_exclude: Vec<String>,
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: 5,
+            files_per_batch: 25,
+            batch_pause_ms: 50,
+            max_file_size_bytes: 2 * 1024 * 1024,
+            default_exclude: vec![
+                "**/.git/**".to_string(),
+                "**/node_modules/**".to_string(),
+                "**/target/**".to_string(),
+                "**/dist/**".to_string(),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IndexPhase {
+    Idle,
+    Scanning,
+    Indexing,
+}
+
+/// Progression d'une passe de synchronisation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexProgress {
+    pub phase: IndexPhase,
+    pub files_total: usize,
+    pub files_processed: usize,
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+    pub errors: Vec<String>,
+    pub current_file: Option<String>,
+    pub last_scan_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Default for IndexProgress {
+    fn default() -> Self {
+        Self {
+            phase: IndexPhase::Idle,
+            files_total: 0,
+            files_processed: 0,
+            added: 0,
+            updated: 0,
+            removed: 0,
+            unchanged: 0,
+            errors: Vec::new(),
+            current_file: None,
+            last_scan_at: None,
+        }
+    }
+}
+
+/// État connu d'un fichier indexé
+#[derive(Debug, Clone)]
+struct FileState {
+    modified: Option<SystemTime>,
+    hash: String,
+}
+
+/// Texte extrait d'un fichier
+#[derive(Debug, Clone)]
+pub struct ExtractedText {
+    pub title: String,
+    pub content: String,
+    pub doc_type: String,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Synchronisation (une passe)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Compare les dossiers à l'état connu et applique les différences à l'index
+pub struct FolderSync {
+    config: WatcherConfig,
+    files: HashMap<PathBuf, FileState>,
+}
+
+impl FolderSync {
+    pub fn new(config: WatcherConfig) -> Self {
+        Self {
+            config,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Effectue une passe complète ; `on_progress` est appelé après chaque lot
+    pub fn scan(
+        &mut self,
+        roots: &[WatchRoot],
+        manager: &Mutex<IndexManager>,
+        on_progress: &dyn Fn(&IndexProgress),
+    ) -> IndexProgress {
+        let mut progress = IndexProgress {
+            phase: IndexPhase::Scanning,
+            ..Default::default()
+        };
+        on_progress(&progress);
+
+        let mut candidates = Vec::new();
+        for root in roots {
+            match collect_files(root, &self.config) {
+                Ok(files) => candidates.extend(files),
+                Err(e) => progress.errors.push(format!("{}: {}", root.path.display(), e)),
+            }
+        }
+        candidates.sort();
+        candidates.dedup();
+
+        progress.phase = IndexPhase::Indexing;
+        progress.files_total = candidates.len();
+        on_progress(&progress);
+
+        let seen: HashSet<PathBuf> = candidates.iter().cloned().collect();
+
+        for (i, path) in candidates.iter().enumerate() {
+            progress.current_file = Some(path.display().to_string());
+            if let Err(e) = self.sync_file(path, manager, &mut progress) {
+                progress.errors.push(format!("{}: {}", path.display(), e));
+            }
+            progress.files_processed += 1;
+
+            if (i + 1) % self.config.files_per_batch.max(1) == 0 {
+                on_progress(&progress);
+                std::thread::sleep(Duration::from_millis(self.config.batch_pause_ms));
+            }
+        }
+
+        // Propagation des suppressions (fichier effacé, exclu ou racine retirée)
+        let removed: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|p| !seen.contains(*p))
+            .cloned()
+            .collect();
+        for path in removed {
+            self.files.remove(&path);
+            manager.lock().unwrap().remove_document(&doc_id_for(&path));
+            progress.removed += 1;
+        }
+
+        progress.phase = IndexPhase::Idle;
+        progress.current_file = None;
+        progress.last_scan_at = Some(chrono::Utc::now());
+        on_progress(&progress);
+        progress
+    }
+
+    fn sync_file(
+        &mut self,
+        path: &Path,
+        manager: &Mutex<IndexManager>,
+        progress: &mut IndexProgress,
+    ) -> Result<(), String> {
+        let metadata = fs::metadata(path).map_err(|e| e.to_string())?;
+        let modified = metadata.modified().ok();
+
+        // Date inchangée : pas besoin de relire le fichier
+        if let Some(known) = self.files.get(path) {
+            if known.modified.is_some() && known.modified == modified {
+                progress.unchanged += 1;
+                return Ok(());
+            }
+        }
+
+        let bytes = fs::read(path).map_err(|e| e.to_string())?;
+        let hash = content_hash(&bytes);
+
+        if let Some(known) = self.files.get_mut(path) {
+            if known.hash == hash {
+                known.modified = modified;
+                progress.unchanged += 1;
+                return Ok(());
+            }
+        }
+
+        let extracted = extract_text(path, &bytes).ok_or("Format non supporté")?;
+        let doc_id = doc_id_for(path);
+        let is_new = !self.files.contains_key(path);
+
+        {
+            let mut manager = manager.lock().unwrap();
+            if is_new || manager.get_document(&doc_id).is_none() {
+                let mut doc_metadata = HashMap::new();
+                doc_metadata.insert("source_path".to_string(), path.display().to_string());
+                doc_metadata.insert("content_hash".to_string(), hash.clone());
+                manager.add_document(
+                    doc_id,
+                    extracted.title,
+                    extracted.content,
+                    extracted.doc_type,
+                    doc_metadata,
+                );
+            } else {
+                // Re-chunking incrémental via DocumentIndexer::reindex_document
+                manager.set_metadata(&doc_id, "content_hash", &hash);
+                manager.update_document(&doc_id, extracted.content)?;
+            }
+        }
+
+        if is_new {
+            progress.added += 1;
+        } else {
+            progress.updated += 1;
+        }
+        self.files.insert(path.to_path_buf(), FileState { modified, hash });
+        Ok(())
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tâche de fond
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub type ProgressCallback = Arc<dyn Fn(&IndexProgress) + Send + Sync>;
+
+/// Watcher par scrutation périodique (pas de dépendance aux notifications OS)
+pub struct FolderWatcher {
+    roots: Arc<RwLock<Vec<WatchRoot>>>,
+    progress: Arc<RwLock<IndexProgress>>,
+    running: Arc<AtomicBool>,
+    rescan: Arc<AtomicBool>,
+}
+
+impl FolderWatcher {
+    pub fn spawn(
+        manager: Arc<Mutex<IndexManager>>,
+        config: WatcherConfig,
+        on_progress: ProgressCallback,
+    ) -> Self {
+        let watcher = Self {
+            roots: Arc::new(RwLock::new(Vec::new())),
+            progress: Arc::new(RwLock::new(IndexProgress::default())),
+            running: Arc::new(AtomicBool::new(true)),
+            rescan: Arc::new(AtomicBool::new(true)),
+        };
+
+        let roots = watcher.roots.clone();
+        let progress = watcher.progress.clone();
+        let running = watcher.running.clone();
+        let rescan = watcher.rescan.clone();
+        let poll_interval = Duration::from_secs(config.poll_interval_secs.max(1));
+
+        std::thread::spawn(move || {
+            let mut sync = FolderSync::new(config);
+            let mut last_scan: Option<std::time::Instant> = None;
+
+            while running.load(Ordering::Relaxed) {
+                let due = last_scan.map(|t| t.elapsed() >= poll_interval).unwrap_or(true);
+                // Toujours consommé : une demande servie par un scan périodique
+                // ne doit pas en déclencher un second au tour suivant
+                let forced = rescan.swap(false, Ordering::Relaxed);
+                if due || forced {
+                    let current_roots = roots.read().unwrap().clone();
+                    let report = |p: &IndexProgress| {
+                        *progress.write().unwrap() = p.clone();
+                        on_progress(p);
+                    };
+                    let summary = sync.scan(&current_roots, &manager, &report);
+                    if summary.added + summary.updated + summary.removed > 0 {
+                        println!(
+                            "[SEMANTIC] Sync dossiers: +{} ~{} -{} ({} erreurs)",
+                            summary.added,
+                            summary.updated,
+                            summary.removed,
+                            summary.errors.len()
+                        );
+                    }
+                    last_scan = Some(std::time::Instant::now());
+                }
+                std::thread::sleep(Duration::from_millis(250));
+            }
+        });
+
+        watcher
+    }
+
+    pub fn add_root(&self, root: WatchRoot) {
+        let mut roots = self.roots.write().unwrap();
+        roots.retain(|r| r.path != root.path);
+        roots.push(root);
+        self.request_rescan();
+    }
+
+    /// Retire une racine ; ses documents sont supprimés à la passe suivante
+    pub fn remove_root(&self, path: &Path) -> bool {
+        let mut roots = self.roots.write().unwrap();
+        let before = roots.len();
+        roots.retain(|r| r.path != path);
+        let removed = roots.len() != before;
+        if removed {
+            self.request_rescan();
+        }
+        removed
+    }
+
+    pub fn roots(&self) -> Vec<WatchRoot> {
+        self.roots.read().unwrap().clone()
+    }
+
+    pub fn progress(&self) -> IndexProgress {
+        self.progress.read().unwrap().clone()
+    }
+
+    pub fn request_rescan(&self) {
+        self.rescan.store(true, Ordering::Relaxed);
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl Drop for FolderWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Fichiers, globs et extraction
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn collect_files(root: &WatchRoot, config: &WatcherConfig) -> Result<Vec<PathBuf>, String> {
+    if !root.path.is_dir() {
+        return Err("Dossier introuvable".to_string());
+    }
+
+    let mut files = Vec::new();
+    let mut stack = vec![root.path.clone()];
+
+    while let Some(dir) = stack.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let relative = relative_path(&root.path, &path);
+            let file_type = match entry.file_type() {
+                Ok(t) => t,
+                Err(_) => continue,
+            };
+
+            let excluded = config
+                .default_exclude
+                .iter()
+                .chain(root.exclude.iter())
+                .any(|pattern| glob_match(pattern, &relative) || glob_match(pattern, &format!("{}/", relative)));
+            if excluded || file_type.is_symlink() {
+                continue;
+            }
+
+            if file_type.is_dir() {
+                stack.push(path);
+            } else if file_type.is_file() {
+                let included = if root.include.is_empty() {
+                    detect_doc_type(&path).is_some()
+                } else {
+                    root.include.iter().any(|pattern| glob_match(pattern, &relative))
+                };
+                let small_enough = entry
+                    .metadata()
+                    .map(|m| m.len() <= config.max_file_size_bytes)
+                    .unwrap_or(false);
+                if included && small_enough {
+                    files.push(path);
+                }
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Glob sur chemin relatif : `*` et `?` dans un segment, `**` sur plusieurs
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some(&"**") => (0..=path.len()).any(|skip| match_segments(&pattern[1..], &path[skip..])),
+        Some(segment) => {
+            !path.is_empty()
+                && match_segment(segment.as_bytes(), path[0].as_bytes())
+                && match_segments(&pattern[1..], &path[1..])
+        }
+    }
+}
+
+fn match_segment(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') => (0..=text.len()).any(|skip| match_segment(&pattern[1..], &text[skip..])),
+        Some(b'?') => !text.is_empty() && match_segment(&pattern[1..], &text[1..]),
+        Some(c) => text.first() == Some(c) && match_segment(&pattern[1..], &text[1..]),
+    }
+}
+
+/// Type de document selon l'extension (les langages servent au chunking)
+pub fn detect_doc_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    Some(match extension.as_str() {
+        "md" | "markdown" => "markdown",
+        "txt" | "text" | "log" => "text",
+        "html" | "htm" => "html",
+        "rs" => "rust",
+        "ts" | "tsx" => "typescript",
+        "js" | "jsx" | "mjs" => "javascript",
+        "py" => "python",
+        "go" => "go",
+        "java" => "java",
+        "c" | "h" | "cpp" | "hpp" => "c",
+        "toml" | "json" | "yaml" | "yml" => "config",
+        _ => return None,
+    })
+}
+
+/// Extrait le texte indexable d'un fichier
+pub fn extract_text(path: &Path, bytes: &[u8]) -> Option<ExtractedText> {
+    let doc_type = detect_doc_type(path)?;
+    let title = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let content = match doc_type {
+        "html" => html2text::from_read(bytes, 100),
+        _ => String::from_utf8_lossy(bytes).to_string(),
+    };
+
+    Some(ExtractedText {
+        title,
+        content,
+        doc_type: doc_type.to_string(),
+    })
+}
+
+fn content_hash(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn doc_id_for(path: &Path) -> String {
+    format!("{}{}", FILE_DOC_PREFIX, path.display())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Commandes Tauri
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub struct SemanticWatchState {
+    manager: Arc<Mutex<IndexManager>>,
+    watcher: Mutex<Option<FolderWatcher>>,
+}
+
+impl SemanticWatchState {
+    pub fn new(manager: Arc<Mutex<IndexManager>>) -> Self {
+        Self {
+            manager,
+            watcher: Mutex::new(None),
+        }
+    }
+
+    /// Démarre le watcher au premier besoin, avec émission d'événements
+    fn with_watcher<T>(&self, app: &AppHandle, f: impl FnOnce(&FolderWatcher) -> T) -> T {
+        let mut watcher = self.watcher.lock().unwrap();
+        let watcher = watcher.get_or_insert_with(|| {
+            let app = app.clone();
+            FolderWatcher::spawn(
+                self.manager.clone(),
+                WatcherConfig::default(),
+                Arc::new(move |progress: &IndexProgress| {
+                    let _ = app.emit(PROGRESS_EVENT, progress.clone());
+                }),
+            )
+        });
+        f(watcher)
+    }
+}
+
+#[tauri::command]
+pub fn semantic_watch_add_root(
+    root: WatchRoot,
+    app: AppHandle,
+    state: State<SemanticWatchState>,
+) -> Result<Vec<WatchRoot>, String> {
+    if !root.path.is_dir() {
+        return Err(format!("Dossier introuvable: {}", root.path.display()));
+    }
+    Ok(state.with_watcher(&app, |w| {
+        w.add_root(root);
+        w.roots()
+    }))
+}
+
+#[tauri::command]
+pub fn semantic_watch_remove_root(
+    path: PathBuf,
+    app: AppHandle,
+    state: State<SemanticWatchState>,
+) -> Result<bool, String> {
+    Ok(state.with_watcher(&app, |w| w.remove_root(&path)))
+}
+
+#[tauri::command]
+pub fn semantic_watch_list_roots(app: AppHandle, state: State<SemanticWatchState>) -> Result<Vec<WatchRoot>, String> {
+    Ok(state.with_watcher(&app, |w| w.roots()))
+}
+
+#[tauri::command]
+pub fn semantic_watch_get_progress(
+    app: AppHandle,
+    state: State<SemanticWatchState>,
+) -> Result<IndexProgress, String> {
+    Ok(state.with_watcher(&app, |w| w.progress()))
+}
+
+#[tauri::command]
+pub fn semantic_watch_rescan(app: AppHandle, state: State<SemanticWatchState>) -> Result<(), String> {
+    state.with_watcher(&app, |w| w.request_rescan());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::indexer::IndexerConfig;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("**/*.md", "notes/2024/plan.md"));
+        assert!(glob_match("**/*.md", "plan.md"));
+        assert!(glob_match("docs/*.txt", "docs/a.txt"));
+        assert!(!glob_match("docs/*.txt", "docs/sub/a.txt"));
+        assert!(glob_match("**/node_modules/**", "web/node_modules/lib/index.js"));
+        assert!(glob_match("file?.rs", "file1.rs"));
+        assert!(!glob_match("**/*.md", "plan.mdx"));
+    }
+
+    #[test]
+    fn test_extract_html() {
+        let extracted = extract_text(Path::new("page.html"), b"<h1>Titre</h1><p>Bonjour <b>monde</b></p>").unwrap();
+        assert_eq!(extracted.doc_type, "html");
+        assert!(extracted.content.contains("Bonjour"));
+        assert!(!extracted.content.contains("<p>"));
+    }
+
+    #[test]
+    fn test_folder_sync_add_update_delete() {
+        let root = std::env::temp_dir().join(format!("titane_watch_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        fs::write(root.join("notes.md"), "# Notes\nContenu initial des notes.").unwrap();
+        fs::write(root.join("ignored.bin"), "binaire").unwrap();
+        fs::write(root.join("node_modules/lib.js"), "ignored()").unwrap();
+
+        let manager = Mutex::new(IndexManager::new(IndexerConfig::default()));
+        let roots = vec![WatchRoot {
+            path: root.clone(),
+            include: vec![],
+            exclude: vec![],
+        }];
+        let mut sync = FolderSync::new(WatcherConfig::default());
+
+        let first = sync.scan(&roots, &manager, &|_| {});
+        assert_eq!(first.added, 1);
+        assert_eq!(first.files_total, 1);
+
+        let unchanged = sync.scan(&roots, &manager, &|_| {});
+        assert_eq!(unchanged.added + unchanged.updated, 0);
+        assert_eq!(unchanged.unchanged, 1);
+
+        fs::write(root.join("notes.md"), "# Notes\nContenu modifié, facture FAC-77.").unwrap();
+        // Date de modification potentiellement identique : le hash tranche
+        sync.files.values_mut().for_each(|f| f.modified = None);
+        let updated = sync.scan(&roots, &manager, &|_| {});
+        assert_eq!(updated.updated, 1);
+        let doc_id = doc_id_for(&root.join("notes.md"));
+        assert!(manager.lock().unwrap().get_document(&doc_id).unwrap().content.contains("FAC-77"));
+
+        fs::remove_file(root.join("notes.md")).unwrap();
+        let deleted = sync.scan(&roots, &manager, &|_| {});
+        assert_eq!(deleted.removed, 1);
+        assert!(manager.lock().unwrap().get_document(&doc_id).is_none());
+
+        fs::remove_dir_all(root).ok();
+    }
+}
@@ -0,0 +1,233 @@
+//! TITANE∞ - Primitives de chiffrement partagées
+//! Argon2id pour dériver une clé d'un mot de passe, AES-256-GCM (nonce aléatoire,
+//! données associées) pour sceller. Utilisées par le coffre de secrets, le
+//! stockage sémantique, le chiffrement de la mémoire et les sauvegardes.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+
+pub const KEY_SIZE: usize = 32;
+pub const SALT_SIZE: usize = 16;
+pub const NONCE_SIZE: usize = 12;
+pub const KDF_ALGORITHM: &str = "argon2id";
+
+/// Coûts Argon2id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfConfig {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfConfig {
+    fn default() -> Self {
+        // Recommandation OWASP : 19 MiB, 2 passes, 1 voie
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+/// Paramètres KDF persistés dans un en-tête JSON (sel en base64)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub algorithm: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    pub salt: String,
+}
+
+impl KdfParams {
+    /// Nouveaux paramètres avec un sel aléatoire
+    pub fn generate(config: KdfConfig) -> Self {
+        Self {
+            algorithm: KDF_ALGORITHM.to_string(),
+            memory_kib: config.memory_kib,
+            iterations: config.iterations,
+            parallelism: config.parallelism,
+            salt: general_purpose::STANDARD.encode(random_salt()),
+        }
+    }
+
+    pub fn config(&self) -> KdfConfig {
+        KdfConfig {
+            memory_kib: self.memory_kib,
+            iterations: self.iterations,
+            parallelism: self.parallelism,
+        }
+    }
+
+    pub fn derive_key(&self, password: &str) -> Result<[u8; KEY_SIZE], CryptoError> {
+        if self.algorithm != KDF_ALGORITHM {
+            return Err(CryptoError::UnsupportedKdf(self.algorithm.clone()));
+        }
+        let salt = general_purpose::STANDARD
+            .decode(&self.salt)
+            .map_err(|_| CryptoError::InvalidEncoding)?;
+        derive_key(password, &salt, self.config())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryptoError {
+    UnsupportedKdf(String),
+    InvalidParams(String),
+    KeyDerivation(String),
+    Encryption,
+    /// Clé incorrecte ou données altérées (indiscernables avec AES-GCM)
+    Decryption,
+    InvalidEncoding,
+    Truncated,
+}
+
+impl std::fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CryptoError::UnsupportedKdf(name) => write!(f, "KDF non supportée: {}", name),
+            CryptoError::InvalidParams(e) => write!(f, "Paramètres Argon2 invalides: {}", e),
+            CryptoError::KeyDerivation(e) => write!(f, "Dérivation de clé échouée: {}", e),
+            CryptoError::Encryption => write!(f, "Chiffrement échoué"),
+            CryptoError::Decryption => write!(f, "Déchiffrement échoué (clé incorrecte ou données altérées)"),
+            CryptoError::InvalidEncoding => write!(f, "Encodage base64 invalide"),
+            CryptoError::Truncated => write!(f, "Données chiffrées tronquées"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+pub fn random_salt() -> [u8; SALT_SIZE] {
+    rand::random()
+}
+
+/// Dérive une clé AES-256 du mot de passe (Argon2id v1.3)
+pub fn derive_key(password: &str, salt: &[u8], config: KdfConfig) -> Result<[u8; KEY_SIZE], CryptoError> {
+    let params = Params::new(config.memory_kib, config.iterations, config.parallelism, Some(KEY_SIZE))
+        .map_err(|e| CryptoError::InvalidParams(e.to_string()))?;
+
+    let mut key = [0u8; KEY_SIZE];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+    Ok(key)
+}
+
+/// Chiffre avec un nonce aléatoire ; sortie : nonce || texte chiffré
+pub fn encrypt(key: &[u8; KEY_SIZE], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| CryptoError::Encryption)?;
+    let nonce_bytes: [u8; NONCE_SIZE] = rand::random();
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), Payload { msg: plaintext, aad })
+        .map_err(|_| CryptoError::Encryption)?;
+
+    let mut output = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+    output.extend_from_slice(&nonce_bytes);
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+/// Inverse de `encrypt` ; échoue si la clé, l'AAD ou les données diffèrent
+pub fn decrypt(key: &[u8; KEY_SIZE], data: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if data.len() < NONCE_SIZE {
+        return Err(CryptoError::Truncated);
+    }
+    let (nonce_bytes, ciphertext) = data.split_at(NONCE_SIZE);
+
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| CryptoError::Decryption)?;
+    cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), Payload { msg: ciphertext, aad })
+        .map_err(|_| CryptoError::Decryption)
+}
+
+/// `encrypt` encodé en base64, pour les en-têtes JSON
+pub fn seal(key: &[u8; KEY_SIZE], plaintext: &[u8], aad: &[u8]) -> Result<String, CryptoError> {
+    Ok(general_purpose::STANDARD.encode(encrypt(key, plaintext, aad)?))
+}
+
+pub fn open_sealed(key: &[u8; KEY_SIZE], sealed: &str, aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let data = general_purpose::STANDARD
+        .decode(sealed.trim())
+        .map_err(|_| CryptoError::InvalidEncoding)?;
+    decrypt(key, &data, aad)
+}
+
+/// Efface une clé par écritures volatiles (non supprimables par l'optimiseur)
+pub fn wipe(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        // SAFETY: `byte` est une référence valide et alignée sur un u8
+        unsafe { std::ptr::write_volatile(byte, 0) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_KDF: KdfConfig = KdfConfig {
+        memory_kib: 256,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn test_derive_key_is_deterministic_per_salt() {
+        let salt = random_salt();
+        let a = derive_key("secret", &salt, TEST_KDF).unwrap();
+        assert_eq!(a, derive_key("secret", &salt, TEST_KDF).unwrap());
+        assert_ne!(a, derive_key("autre", &salt, TEST_KDF).unwrap());
+        assert_ne!(a, derive_key("secret", &random_salt(), TEST_KDF).unwrap());
+    }
+
+    #[test]
+    fn test_encrypt_round_trip_and_tampering() {
+        let key = [7u8; KEY_SIZE];
+        let data = encrypt(&key, b"message", b"aad").unwrap();
+        assert_eq!(decrypt(&key, &data, b"aad").unwrap(), b"message");
+
+        // Nonce aléatoire : deux chiffrements diffèrent
+        assert_ne!(data, encrypt(&key, b"message", b"aad").unwrap());
+
+        assert_eq!(decrypt(&key, &data, b"autre"), Err(CryptoError::Decryption));
+        assert_eq!(decrypt(&[8u8; KEY_SIZE], &data, b"aad"), Err(CryptoError::Decryption));
+        let mut tampered = data.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(decrypt(&key, &tampered, b"aad"), Err(CryptoError::Decryption));
+        assert_eq!(decrypt(&key, &data[..4], b"aad"), Err(CryptoError::Truncated));
+    }
+
+    #[test]
+    fn test_kdf_params_serialize_and_derive() {
+        let params = KdfParams::generate(TEST_KDF);
+        let json = serde_json::to_string(&params).unwrap();
+        let parsed: KdfParams = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.config(), TEST_KDF);
+        assert_eq!(parsed.derive_key("pw").unwrap(), params.derive_key("pw").unwrap());
+
+        let key = params.derive_key("pw").unwrap();
+        let sealed = seal(&key, b"valeur", b"nom").unwrap();
+        assert_eq!(open_sealed(&key, &sealed, b"nom").unwrap(), b"valeur");
+        assert_eq!(open_sealed(&key, "%%%", b"nom"), Err(CryptoError::InvalidEncoding));
+
+        let other = KdfParams {
+            algorithm: "scrypt".to_string(),
+            ..params
+        };
+        assert!(matches!(other.derive_key("pw"), Err(CryptoError::UnsupportedKdf(_))));
+    }
+
+    #[test]
+    fn test_wipe_zeroes_every_byte() {
+        let mut key = [0xAB; KEY_SIZE];
+        wipe(&mut key);
+        assert_eq!(key, [0; KEY_SIZE]);
+    }
+}
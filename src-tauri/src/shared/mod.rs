@@ -1,6 +1,7 @@
 // TITANE∞ v8.0 - Shared Module
 // Exports shared utilities, types, and macros
 
+pub mod crypto;
 pub mod macros;
 pub mod types;
 pub mod utils;
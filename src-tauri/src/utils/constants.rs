@@ -20,6 +20,7 @@ pub const SENTINEL_INTERVAL_MS: u64 = 5000; // 5s
 /// Memory management
 pub const MEMORY_MAX_SNAPSHOTS: usize = 100;
 pub const MEMORY_MAX_LOG_ENTRIES: usize = 1000;
+pub const MEMORY_MAX_TIMELINE_EVENTS: usize = 500;
 pub const MEMORY_SNAPSHOT_INTERVAL_MS: u64 = 10000; // 10s
 
 /// Evolution engine
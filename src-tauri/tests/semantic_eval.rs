@@ -0,0 +1,66 @@
+// TITANE∞ v13 - Évaluation de la recherche sémantique
+// Compare les variantes de configuration sur le corpus de référence (hors ligne).
+// `cargo test --test semantic_eval -- --nocapture` affiche le tableau comparatif.
+
+use std::path::PathBuf;
+use titane_infinity::semantic::eval::{evaluate, EvalCorpus, EvalSet, EvalVariant};
+use titane_infinity::semantic::indexer::IndexerConfig;
+use titane_infinity::semantic::query::FusionWeights;
+
+const K: usize = 5;
+
+/// Seuils de non-régression de la configuration par défaut
+const MIN_RECALL: f32 = 0.90;
+const MIN_MRR: f32 = 0.85;
+const MIN_NDCG: f32 = 0.85;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/semantic_eval")
+        .join(name)
+}
+
+fn variants() -> Vec<EvalVariant> {
+    let baseline = EvalVariant::baseline("défaut");
+
+    let mut lexical = EvalVariant::baseline("BM25 seul");
+    for weights in lexical.query.intent_weights.values_mut() {
+        *weights = FusionWeights { vector: 0.0, lexical: 1.0 };
+    }
+
+    let mut vector = EvalVariant::baseline("vectoriel seul");
+    vector.query.similarity_threshold = 0.0;
+    for weights in vector.query.intent_weights.values_mut() {
+        *weights = FusionWeights { vector: 1.0, lexical: 0.0 };
+    }
+
+    let mut no_rerank = EvalVariant::baseline("sans reranking");
+    no_rerank.reranker = None;
+
+    let mut small_chunks = EvalVariant::baseline("petits chunks");
+    small_chunks.indexer = IndexerConfig {
+        max_chunk_size: 160,
+        min_chunk_size: 40,
+        ..IndexerConfig::default()
+    };
+
+    vec![baseline, lexical, vector, no_rerank, small_chunks]
+}
+
+#[tokio::test]
+async fn semantic_retrieval_quality() {
+    let corpus = EvalCorpus::from_file(&fixture("corpus.json")).unwrap();
+    let queries = EvalSet::from_file(&fixture("queries.json")).unwrap();
+
+    let report = evaluate(&corpus, &queries, &variants(), K).await.unwrap();
+    println!("\n{}", report.to_table());
+
+    let baseline = report.variant("défaut").unwrap();
+    for query in baseline.queries.iter().filter(|q| q.first_hit.is_none()) {
+        println!("aucun résultat pertinent pour {}", query.id);
+    }
+
+    assert!(baseline.recall_at_k >= MIN_RECALL, "recall@{} = {:.3}", K, baseline.recall_at_k);
+    assert!(baseline.mrr >= MIN_MRR, "MRR = {:.3}", baseline.mrr);
+    assert!(baseline.ndcg_at_k >= MIN_NDCG, "nDCG@{} = {:.3}", K, baseline.ndcg_at_k);
+}